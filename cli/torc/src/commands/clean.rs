@@ -5,24 +5,36 @@ use std::path::Path;
 
 use anyhow::Result;
 
+use crate::commands::proofs::PROOFS_DIR;
+
 /// Remove build artifacts from the project directory.
+///
+/// The proof cache under `out/` survives unless `proofs` is set.
 pub fn run(project_dir: &Path, proofs: bool) -> Result<()> {
     let out_dir = project_dir.join("out");
-    if out_dir.exists() {
+    let proofs_dir = project_dir.join(PROOFS_DIR);
+    if !out_dir.exists() {
+        println!("Already clean: {} does not exist", out_dir.display());
+    } else if proofs || !proofs_dir.exists() {
         fs::remove_dir_all(&out_dir)?;
         println!("Removed {}", out_dir.display());
     } else {
-        println!("Already clean: {} does not exist", out_dir.display());
-    }
-
-    if proofs {
-        let proofs_dir = project_dir.join(".torc-proofs");
-        if proofs_dir.exists() {
-            fs::remove_dir_all(&proofs_dir)?;
-            println!("Removed {}", proofs_dir.display());
-        } else {
-            println!("Already clean: {} does not exist", proofs_dir.display());
+        for entry in fs::read_dir(&out_dir)? {
+            let path = entry?.path();
+            if proofs_dir.starts_with(&path) {
+                continue;
+            }
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
         }
+        println!(
+            "Removed {} (kept cached proofs in {})",
+            out_dir.display(),
+            proofs_dir.display()
+        );
     }
 
     Ok(())
@@ -51,15 +63,18 @@ mod tests {
     }
 
     #[test]
-    fn clean_with_proofs() {
+    fn clean_keeps_proofs_unless_asked() {
         let dir = tempfile::tempdir().unwrap();
         let out = dir.path().join("out");
-        let proofs = dir.path().join(".torc-proofs");
-        fs::create_dir(&out).unwrap();
-        fs::create_dir(&proofs).unwrap();
+        let proofs = dir.path().join(PROOFS_DIR);
+        fs::create_dir_all(&proofs).unwrap();
+        fs::write(out.join("artifact.o"), b"data").unwrap();
+
+        run(dir.path(), false).unwrap();
+        assert!(!out.join("artifact.o").exists());
+        assert!(proofs.exists());
 
         run(dir.path(), true).unwrap();
         assert!(!out.exists());
-        assert!(!proofs.exists());
     }
 }
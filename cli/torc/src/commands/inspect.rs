@@ -4,9 +4,10 @@ use std::fs;
 use std::path::Path;
 
 use anyhow::{Context, Result};
+use torc_core::graph::query::Query;
 use torc_observe::{
-    available_views, ContractView, DataflowView, DecisionView, ProvenanceView, PseudoCodeView,
-    RenderContext, ResourceBudgetView, View, ViewFormat, ViewKind,
+    available_views, node_display_name, ContractView, DataflowView, DecisionView, ProvenanceView,
+    PseudoCodeView, RenderContext, ResourceBudgetView, View, ViewFormat, ViewKind,
 };
 use torc_trc::TrcFile;
 
@@ -20,6 +21,7 @@ pub fn run(
     input: Option<&str>,
     export: Option<&str>,
     target: Option<&str>,
+    select: Option<&str>,
 ) -> Result<()> {
     // Load graph
     let graph_path = match input {
@@ -39,15 +41,37 @@ pub fn run(
     let trc =
         TrcFile::from_bytes(&bytes).with_context(|| format!("parsing {}", graph_path.display()))?;
 
+    // Narrow to the selected nodes
+    let selected = match select {
+        Some(src) => {
+            let query = Query::parse(src).map_err(|e| anyhow::anyhow!("{e}"))?;
+            Some(trc.graph.select(&query))
+        }
+        None => None,
+    };
+    let graph = match &selected {
+        Some(ids) => trc.graph.extract_subgraph(ids),
+        None => trc.graph,
+    };
+
     // If no view specified, show summary + available views
     let view_name = match view {
         Some(v) => v,
         None => {
             println!("--- Graph Stats ({}) ---", graph_path.display());
-            println!("  Nodes:   {}", trc.graph.node_count());
-            println!("  Edges:   {}", trc.graph.edge_count());
-            println!("  Regions: {}", trc.graph.region_count());
+            println!("  Nodes:   {}", graph.node_count());
+            println!("  Edges:   {}", graph.edge_count());
+            println!("  Regions: {}", graph.region_count());
             println!();
+            if selected.is_some() {
+                let mut nodes: Vec<_> = graph.nodes().collect();
+                nodes.sort_by_key(|n| n.id);
+                println!("Selected nodes:");
+                for node in nodes {
+                    println!("  {:<24} {}", node_display_name(node), node.kind);
+                }
+                println!();
+            }
             println!("Available views:");
             for vk in available_views() {
                 println!("  --view {:<16} {}", vk.name(), view_description(vk));
@@ -104,7 +128,7 @@ pub fn run(
     };
 
     let output = view_impl
-        .render(&trc.graph, &ctx)
+        .render(&graph, &ctx)
         .map_err(|e| anyhow::anyhow!("{e}"))?;
 
     print!("{}", output.render(format));
@@ -136,7 +160,7 @@ mod tests {
         crate::commands::init::create_project(&project_path, "inspect-test").unwrap();
 
         // Should succeed and show stats
-        run(&project_path, None, None, None, None).unwrap();
+        run(&project_path, None, None, None, None, None).unwrap();
     }
 
     /// Test: --view pseudo-code renders pseudo-code.
@@ -146,7 +170,7 @@ mod tests {
         let project_path = dir.path().join("pc-test");
         crate::commands::init::create_project(&project_path, "pc-test").unwrap();
 
-        run(&project_path, Some("pseudo-code"), None, None, None).unwrap();
+        run(&project_path, Some("pseudo-code"), None, None, None, None).unwrap();
     }
 
     /// Test: --view contracts --export json produces JSON.
@@ -156,7 +180,35 @@ mod tests {
         let project_path = dir.path().join("json-test");
         crate::commands::init::create_project(&project_path, "json-test").unwrap();
 
-        run(&project_path, Some("contracts"), None, Some("json"), None).unwrap();
+        run(
+            &project_path,
+            Some("contracts"),
+            None,
+            Some("json"),
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    /// Test: --select narrows the view and rejects malformed queries.
+    #[test]
+    fn select_narrows_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().join("select-test");
+        crate::commands::init::create_project(&project_path, "select-test").unwrap();
+
+        run(&project_path, None, None, None, None, Some("kind(Literal)")).unwrap();
+        run(
+            &project_path,
+            Some("contracts"),
+            None,
+            Some("json"),
+            None,
+            Some("upstream(kind(Arithmetic), 1)"),
+        )
+        .unwrap();
+        assert!(run(&project_path, None, None, None, None, Some("kind(")).is_err());
     }
 
     /// Test: unknown view returns error.
@@ -166,7 +218,7 @@ mod tests {
         let project_path = dir.path().join("err-test");
         crate::commands::init::create_project(&project_path, "err-test").unwrap();
 
-        let result = run(&project_path, Some("nonexistent"), None, None, None);
+        let result = run(&project_path, Some("nonexistent"), None, None, None, None);
         assert!(result.is_err());
     }
 }
@@ -7,6 +7,7 @@ pub mod doctor;
 pub mod ffi;
 pub mod init;
 pub mod inspect;
+pub mod proofs;
 pub mod registry;
 pub mod target;
 pub mod verify;
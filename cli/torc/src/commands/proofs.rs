@@ -0,0 +1,160 @@
+//! `torc proofs` — manage the project's persistent proof cache.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use torc_verify::cache::{EvictionPolicy, ProofCache};
+use torc_verify::incremental::Snapshot;
+
+use crate::manifest::TorcManifest;
+
+/// Where the proof cache lives, relative to the project directory.
+pub const PROOFS_DIR: &str = "out/proofs";
+
+/// Open the project's proof cache for the running toolchain.
+pub fn open(project_dir: &Path) -> Result<ProofCache> {
+    let root = project_dir.join(PROOFS_DIR);
+    ProofCache::open(&root).with_context(|| format!("opening proof cache {}", root.display()))
+}
+
+/// The eviction policy the manifest asks for, if any.
+pub fn manifest_policy(manifest: Option<&TorcManifest>) -> Option<EvictionPolicy> {
+    let v = manifest?.verification.as_ref()?;
+    let mut policy = EvictionPolicy::default();
+    if let Some(max) = v.cache_max_entries {
+        policy = policy.with_max_entries(max);
+    }
+    if let Some(days) = v.cache_max_age_days {
+        policy = policy.with_max_age_secs(days * 86_400);
+    }
+    (policy != EvictionPolicy::default()).then_some(policy)
+}
+
+/// Where the outcome of verifying `graph_path` is recorded, for
+/// `torc verify --incremental`.
+pub fn snapshot_path(project_dir: &Path, graph_path: &Path) -> PathBuf {
+    let stem = graph_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("graph");
+    project_dir
+        .join(PROOFS_DIR)
+        .join(format!("{stem}.snapshot.json"))
+}
+
+/// The snapshot at `path`, if there is a readable one.
+pub fn load_snapshot(path: &Path) -> Option<Snapshot> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+pub fn save_snapshot(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    let json = serde_json::to_vec(snapshot)?;
+    fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Write every cached proof to a bundle file.
+pub fn export(project_dir: &Path, path: &str) -> Result<()> {
+    let cache = open(project_dir)?;
+    let count = cache
+        .export(Path::new(path))
+        .with_context(|| format!("exporting proofs to {path}"))?;
+    println!("Exported {count} proof(s) to {path} ({})", cache.version());
+    Ok(())
+}
+
+/// Add the proofs of a bundle file to the project's cache.
+pub fn import(project_dir: &Path, path: &str) -> Result<()> {
+    let mut cache = open(project_dir)?;
+    let count = cache
+        .import(Path::new(path))
+        .with_context(|| format!("importing proofs from {path}"))?;
+    println!("Imported {count} new proof(s) from {path}");
+    Ok(())
+}
+
+/// Evict cached proofs by count and age (defaults from the manifest), and
+/// those of other toolchain versions.
+pub fn prune(
+    project_dir: &Path,
+    manifest: Option<&TorcManifest>,
+    max_entries: Option<usize>,
+    max_age_days: Option<u64>,
+) -> Result<()> {
+    let mut policy = manifest_policy(manifest).unwrap_or_default();
+    if let Some(max) = max_entries {
+        policy = policy.with_max_entries(max);
+    }
+    if let Some(days) = max_age_days {
+        policy = policy.with_max_age_secs(days * 86_400);
+    }
+    let mut cache = open(project_dir)?;
+    let removed = cache.evict(&policy)?;
+    println!(
+        "Removed {removed} proof(s); {} remain",
+        cache.statistics().entries
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_import_and_prune() {
+        use torc_core::contract::{ObligationKind, ProofObligation, ProofStatus};
+        use torc_core::types::Predicate;
+        use torc_verify::cache::cache_key;
+        use torc_verify::witness::generate_witness;
+
+        let ci = tempfile::tempdir().unwrap();
+        let dev = tempfile::tempdir().unwrap();
+        let bundle = ci.path().join("proofs.json");
+        let bundle = bundle.to_str().unwrap();
+
+        // One proof made on CI
+        let obligation = ProofObligation {
+            kind: ObligationKind::Postcondition,
+            predicate: Predicate::BoolLit(true),
+            description: "holds".into(),
+            status: ProofStatus::Verified,
+            witness: None,
+            waiver: None,
+        };
+        let key = cache_key("fingerprint", &["interval_domain"]);
+        open(ci.path()).unwrap().store(
+            &key,
+            generate_witness("interval_domain", &obligation, vec![]),
+        );
+
+        export(ci.path(), bundle).unwrap();
+        import(dev.path(), bundle).unwrap();
+        let mut cache = open(dev.path()).unwrap();
+        assert_eq!(cache.statistics().entries, 1);
+        assert!(cache.lookup(&key).unwrap().imported);
+
+        prune(dev.path(), None, Some(0), None).unwrap();
+        assert_eq!(open(dev.path()).unwrap().statistics().entries, 0);
+    }
+
+    #[test]
+    fn policy_from_manifest() {
+        let manifest = TorcManifest::from_str(
+            r#"
+[project]
+name = "demo"
+version = "0.1.0"
+
+[verification]
+cache_max_entries = 500
+cache_max_age_days = 30
+"#,
+        )
+        .unwrap();
+        let policy = manifest_policy(Some(&manifest)).unwrap();
+        assert_eq!(policy.max_entries, Some(500));
+        assert_eq!(policy.max_age_secs, Some(30 * 86_400));
+        assert_eq!(manifest_policy(None), None);
+    }
+}
@@ -2,17 +2,23 @@
 
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use torc_spec::bridge::decision_aware_profile;
 use torc_trc::TrcFile;
+use torc_verify::checker::ProcessProofChecker;
 use torc_verify::engine::VerificationEngine;
 use torc_verify::profile::VerificationProfile;
+use torc_verify::report::Severity;
+use torc_verify::smtlib::ProcessSolver;
 
 use crate::commands::decision::load_tdg_optional;
+use crate::commands::proofs;
 use crate::manifest::TorcManifest;
 
 /// Run verification on a Torc graph.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     project_dir: &Path,
     manifest: Option<&TorcManifest>,
@@ -20,7 +26,8 @@ pub fn run(
     status_only: bool,
     report_format: Option<&str>,
     profile: Option<&str>,
-    _incremental: bool,
+    incremental: bool,
+    test_vectors: Option<&str>,
 ) -> Result<()> {
     // Load graph
     let graph_path = match input {
@@ -65,9 +72,40 @@ pub fn run(
         }
     }
 
-    // Run verification
+    // Run verification, reusing proofs from earlier runs
+    let (timeout, proofs) = (vprofile.solver_timeout, vprofile.check_witnesses);
     let mut engine = VerificationEngine::new(vprofile);
+    if let Some(solver) = manifest_solver(manifest, timeout, proofs) {
+        engine = engine.with_solver(Box::new(solver));
+    }
+    for solver in manifest_cross_checkers(manifest, timeout) {
+        engine = engine.with_cross_checker(Box::new(solver));
+    }
+    if let Some(checker) = manifest_proof_checker(manifest) {
+        engine = engine.with_unsat_checker(Box::new(checker));
+    }
+    match proofs::open(project_dir) {
+        Ok(cache) => engine = engine.with_cache(cache),
+        Err(e) => eprintln!("warning: {e:#}; proofs will not be cached"),
+    }
+    let snapshot_path = proofs::snapshot_path(project_dir, &graph_path);
+    if incremental {
+        match proofs::load_snapshot(&snapshot_path) {
+            Some(snapshot) => engine = engine.with_baseline(snapshot),
+            None => println!("note: no previous run recorded; verifying everything"),
+        }
+    }
     let report = engine.verify(&trc.graph);
+    if let Some(snapshot) = engine.snapshot() {
+        if let Err(e) = proofs::save_snapshot(&snapshot_path, snapshot) {
+            eprintln!("warning: {e:#}; the next incremental run will verify everything");
+        }
+    }
+    if let Some(policy) = proofs::manifest_policy(manifest) {
+        if let Err(e) = engine.cache_mut().evict(&policy) {
+            eprintln!("warning: evicting cached proofs: {e}");
+        }
+    }
 
     // Output
     if status_only {
@@ -77,6 +115,9 @@ pub fn run(
         println!("  Pending:  {}", report.summary.pending);
         println!("  Waived:   {}", report.summary.waived);
         println!("  Failed:   {}", report.summary.failed);
+        if incremental {
+            println!("  Reused:   {}", report.summary.reused);
+        }
     } else {
         match report_format {
             Some("json") => {
@@ -89,16 +130,20 @@ pub fn run(
                         "waived": report.summary.waived,
                         "failed": report.summary.failed,
                         "cache_hits": report.summary.cache_hits,
+                        "reused": report.summary.reused,
                     },
+                    "reused": report.reused,
                     "diagnostics": report.diagnostics.iter().map(|d| {
                         serde_json::json!({
                             "obligation_id": d.obligation_id,
                             "severity": format!("{}", d.severity),
                             "message": d.message,
                             "context": d.context,
+                            "counterexample": d.counterexample,
                             "suggestions": d.suggestions,
                         })
                     }).collect::<Vec<_>>(),
+                    "counterexamples": report.counterexamples,
                 });
                 println!("{}", serde_json::to_string_pretty(&json)?);
             }
@@ -109,17 +154,79 @@ pub fn run(
         }
     }
 
-    // Exit code 1 if any failed
+    if let Some(path) = test_vectors {
+        let vectors: Vec<_> = report
+            .counterexamples
+            .iter()
+            .map(|ce| ce.test_vector(&trc.graph))
+            .collect();
+        fs::write(path, serde_json::to_vec_pretty(&vectors)?)
+            .with_context(|| format!("writing {path}"))?;
+        println!("Wrote {} test vector(s) to {path}", vectors.len());
+    }
+
+    // Exit code 1 if any failed, or were disproven or refused
     if report.summary.failed > 0 {
         bail!(
             "verification failed: {} obligation(s) not satisfied",
             report.summary.failed
         );
     }
+    let errors = report
+        .diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!(
+            "verification failed: {errors} error(s), {} counterexample(s)",
+            report.counterexamples.len()
+        );
+    }
 
     Ok(())
 }
 
+/// The external solver the manifest asks for, if any. `solver = "z3"` keeps
+/// the linked Z3 when it is compiled in, unless `proofs` are needed: only a
+/// subprocess solver produces them.
+fn manifest_solver(
+    manifest: Option<&TorcManifest>,
+    timeout: Duration,
+    proofs: bool,
+) -> Option<ProcessSolver> {
+    let v = manifest?.verification.as_ref()?;
+    let tool = v.solver.as_deref()?;
+    if tool == "z3" && torc_verify::Z3_AVAILABLE && !proofs {
+        return None;
+    }
+    let timeout = v.timeout.map(Duration::from_secs).unwrap_or(timeout);
+    Some(ProcessSolver::named(tool).with_timeout(timeout))
+}
+
+/// The solvers the manifest asks to cross-check the main solver's verdicts.
+fn manifest_cross_checkers(
+    manifest: Option<&TorcManifest>,
+    timeout: Duration,
+) -> Vec<ProcessSolver> {
+    let Some(v) = manifest.and_then(|m| m.verification.as_ref()) else {
+        return Vec::new();
+    };
+    let timeout = v.timeout.map(Duration::from_secs).unwrap_or(timeout);
+    v.cross_check
+        .iter()
+        .flatten()
+        .map(|tool| ProcessSolver::named(tool).with_timeout(timeout))
+        .collect()
+}
+
+/// The proof checker the manifest configures for UNSAT certificates.
+fn manifest_proof_checker(manifest: Option<&TorcManifest>) -> Option<ProcessProofChecker> {
+    let command = manifest?.verification.as_ref()?.proof_checker.as_ref()?;
+    let (program, args) = command.split_first()?;
+    Some(ProcessProofChecker::new(program, args.to_vec()))
+}
+
 fn resolve_profile(name: Option<&str>) -> Result<VerificationProfile> {
     match name {
         Some("development") | None => Ok(VerificationProfile::development()),
@@ -144,6 +251,37 @@ mod tests {
         assert!(resolve_profile(Some("unknown")).is_err());
     }
 
+    #[test]
+    fn solver_and_checker_from_manifest() {
+        use torc_verify::smt::SolverBackend;
+
+        let manifest = TorcManifest::from_str(
+            r#"
+[project]
+name = "demo"
+version = "0.1.0"
+
+[verification]
+solver = "cvc5"
+cross_check = ["yices", "z3"]
+timeout = 5
+proof_checker = ["carcara", "check"]
+"#,
+        )
+        .unwrap();
+        let solver = manifest_solver(Some(&manifest), Duration::from_secs(60), false).unwrap();
+        assert_eq!(solver.name(), "smtlib:cvc5");
+        assert!(manifest_solver(None, Duration::from_secs(60), false).is_none());
+        let names: Vec<_> = manifest_cross_checkers(Some(&manifest), Duration::from_secs(60))
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect();
+        assert_eq!(names, ["smtlib:yices-smt2", "smtlib:z3"]);
+        assert!(manifest_cross_checkers(None, Duration::from_secs(60)).is_empty());
+        assert!(manifest_proof_checker(Some(&manifest)).is_some());
+        assert!(manifest_proof_checker(None).is_none());
+    }
+
     #[test]
     fn verify_empty_graph() {
         let dir = tempfile::tempdir().unwrap();
@@ -157,6 +295,59 @@ mod tests {
         std::fs::write(graph_dir.join("main.trc"), &bytes).unwrap();
 
         // Verify should succeed (no obligations)
-        run(dir.path(), None, None, false, None, None, false).unwrap();
+        run(dir.path(), None, None, false, None, None, false, None).unwrap();
+    }
+
+    #[test]
+    fn test_vectors_written_for_disproven_obligations() {
+        use torc_core::graph::edge::Edge;
+        use torc_core::graph::node::{Node, NodeKind};
+        use torc_core::types::{Predicate, Type, TypeSignature};
+        use torc_core::value::Value;
+        use torc_verify::counterexample::TestVector;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = torc_core::graph::Graph::new();
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_value(Value::i32(12));
+        let sink = Node::new(NodeKind::Write).with_type_signature(TypeSignature::sink(
+            Type::i32().refined(Predicate::in_range("value", 0, 9)),
+        ));
+        let lit = graph.add_node(lit).unwrap();
+        let sink = graph.add_node(sink).unwrap();
+        graph.add_edge(Edge::new((lit, 0), (sink, 0))).unwrap();
+        let input = dir.path().join("bad.trc");
+        std::fs::write(&input, TrcFile::new(graph).to_bytes().unwrap()).unwrap();
+
+        let out = dir.path().join("vectors.json");
+        let result = run(
+            dir.path(),
+            None,
+            Some(input.to_str().unwrap()),
+            true,
+            None,
+            None,
+            false,
+            Some(out.to_str().unwrap()),
+        );
+        assert!(result.is_err());
+        let vectors: Vec<TestVector> =
+            serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        assert_eq!(vectors.len(), 1);
+        assert_eq!(vectors[0].literals.get(&lit), Some(&Value::i32(12)));
+    }
+
+    #[test]
+    fn incremental_run_records_and_reads_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::commands::init::create_project(&dir.path().join("p"), "p").unwrap();
+        let project = dir.path().join("p");
+        let graph = project.join("graph/main.trc");
+
+        run(&project, None, None, true, None, None, true, None).unwrap();
+        let snapshot = proofs::snapshot_path(&project, &graph);
+        assert!(proofs::load_snapshot(&snapshot).is_some());
+        run(&project, None, None, true, None, None, true, None).unwrap();
     }
 }
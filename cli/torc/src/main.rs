@@ -65,6 +65,9 @@ enum Commands {
         /// Enable incremental verification
         #[arg(long)]
         incremental: bool,
+        /// Write replayable test vectors for disproven obligations to FILE
+        #[arg(long, value_name = "FILE")]
+        test_vectors: Option<String>,
     },
     /// Inspect a Torc graph
     Inspect {
@@ -80,6 +83,9 @@ enum Commands {
         /// Target platform (needed for resource budget view)
         #[arg(long)]
         target: Option<String>,
+        /// Restrict to the nodes a query selects (e.g. "effect(IO) | kind(FFICall)")
+        #[arg(long)]
+        select: Option<String>,
     },
     /// Manage target platforms
     Target {
@@ -98,6 +104,11 @@ enum Commands {
         #[arg(long)]
         proofs: bool,
     },
+    /// Manage the persistent proof cache
+    Proofs {
+        #[command(subcommand)]
+        action: ProofsAction,
+    },
     /// FFI bridge generation
     Ffi {
         #[command(subcommand)]
@@ -157,6 +168,29 @@ enum FfiAction {
     },
 }
 
+#[derive(Subcommand)]
+enum ProofsAction {
+    /// Write every cached proof to a bundle file
+    Export {
+        /// Bundle file to write
+        path: String,
+    },
+    /// Add the proofs of a bundle file to the cache
+    Import {
+        /// Bundle file to read
+        path: String,
+    },
+    /// Evict old proofs and those of other toolchain versions
+    Prune {
+        /// Keep at most this many proofs
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Evict proofs older than this many days
+        #[arg(long)]
+        max_age_days: Option<u64>,
+    },
+}
+
 #[derive(Subcommand)]
 enum DecisionAction {
     /// Initialize decision tracking (create decisions.tdg)
@@ -281,6 +315,7 @@ fn run(cli: Cli) -> anyhow::Result<()> {
             report,
             profile,
             incremental,
+            test_vectors,
         } => {
             let (manifest, project_dir) = load_manifest_optional(&cwd)?;
             let project_dir = project_dir.unwrap_or(cwd);
@@ -292,6 +327,7 @@ fn run(cli: Cli) -> anyhow::Result<()> {
                 report.as_deref(),
                 profile.as_deref(),
                 incremental,
+                test_vectors.as_deref(),
             )
         }
 
@@ -300,6 +336,7 @@ fn run(cli: Cli) -> anyhow::Result<()> {
             input,
             export,
             target,
+            select,
         } => {
             let (_, project_dir) = load_manifest_optional(&cwd)?;
             let project_dir = project_dir.unwrap_or(cwd);
@@ -309,6 +346,7 @@ fn run(cli: Cli) -> anyhow::Result<()> {
                 input.as_deref(),
                 export.as_deref(),
                 target.as_deref(),
+                select.as_deref(),
             )
         }
 
@@ -339,6 +377,24 @@ fn run(cli: Cli) -> anyhow::Result<()> {
             commands::clean::run(&project_dir, proofs)
         }
 
+        Commands::Proofs { action } => {
+            let (manifest, project_dir) = load_manifest_optional(&cwd)?;
+            let project_dir = project_dir.unwrap_or(cwd);
+            match action {
+                ProofsAction::Export { path } => commands::proofs::export(&project_dir, &path),
+                ProofsAction::Import { path } => commands::proofs::import(&project_dir, &path),
+                ProofsAction::Prune {
+                    max_entries,
+                    max_age_days,
+                } => commands::proofs::prune(
+                    &project_dir,
+                    manifest.as_ref(),
+                    max_entries,
+                    max_age_days,
+                ),
+            }
+        }
+
         Commands::Ffi { action } => match action {
             FfiAction::Bridge {
                 from_c,
@@ -490,6 +546,7 @@ mod integration_tests {
             None,
             None,
             false,
+            None,
         )
         .unwrap();
 
@@ -507,9 +564,12 @@ mod integration_tests {
         )
         .unwrap();
 
-        // 4. Clean — create out/ first (build graph-stats doesn't create it)
-        std::fs::create_dir_all(project_path.join("out")).unwrap();
+        // 4. Clean — verify cached its proofs under out/, which survive
+        // unless --proofs is given
+        assert!(project_path.join(commands::proofs::PROOFS_DIR).is_dir());
         commands::clean::run(&project_path, false).unwrap();
+        assert!(project_path.join(commands::proofs::PROOFS_DIR).is_dir());
+        commands::clean::run(&project_path, true).unwrap();
         assert!(!project_path.join("out").exists());
     }
 
@@ -531,6 +591,7 @@ mod integration_tests {
             Some("json"),
             None,
             false,
+            None,
         )
         .unwrap();
     }
@@ -542,7 +603,7 @@ mod integration_tests {
         let project_path = dir.path().join("status-test");
         commands::init::create_project(&project_path, "status-test").unwrap();
 
-        commands::verify::run(&project_path, None, None, true, None, None, false).unwrap();
+        commands::verify::run(&project_path, None, None, true, None, None, false, None).unwrap();
     }
 
     /// FFI bridge-from-c workflow: .ffi.toml → bridge graph → .trc file.
@@ -942,7 +1003,7 @@ platform_trusted = ["libm", "libc"]
         commands::init::create_project(&project_path, "verify-no-tdg").unwrap();
 
         // No decisions.tdg — verify should work exactly as before
-        commands::verify::run(&project_path, None, None, false, None, None, false).unwrap();
+        commands::verify::run(&project_path, None, None, false, None, None, false, None).unwrap();
     }
 
     /// Verify with TDG present — profile upgrade note.
@@ -973,7 +1034,7 @@ platform_trusted = ["libm", "libc"]
         std::fs::write(project_path.join("spec/decisions.tdg"), bytes).unwrap();
 
         // Verify should succeed — profile is upgraded but no error
-        commands::verify::run(&project_path, None, None, false, None, None, false).unwrap();
+        commands::verify::run(&project_path, None, None, false, None, None, false, None).unwrap();
     }
 
     /// Build blocks on conflicted decisions.
@@ -1067,7 +1128,7 @@ platform_trusted = ["libm", "libc"]
         commands::decision::add(&project_path, "PWM freq", "performance", None).unwrap();
 
         // inspect --view decision should succeed
-        commands::inspect::run(&project_path, Some("decision"), None, None, None).unwrap();
+        commands::inspect::run(&project_path, Some("decision"), None, None, None, None).unwrap();
     }
 
     /// Inspect --view decision with no TDG prints helpful message.
@@ -1078,7 +1139,7 @@ platform_trusted = ["libm", "libc"]
         commands::init::create_project(&project_path, "inspect-no-tdg").unwrap();
 
         // No decisions.tdg — should not error, just print helpful message
-        commands::inspect::run(&project_path, Some("decision"), None, None, None).unwrap();
+        commands::inspect::run(&project_path, Some("decision"), None, None, None, None).unwrap();
     }
 
     /// Decision: commit records history with timestamp.
@@ -102,9 +102,27 @@ pub struct VerificationConfig {
     /// Default verification profile.
     #[serde(default)]
     pub profile: Option<String>,
+    /// SMT-LIB2 solver to run as a subprocess: `z3`, `cvc5`, `yices`, or a
+    /// path to any solver that reads SMT-LIB2 on stdin.
+    #[serde(default)]
+    pub solver: Option<String>,
+    /// Further SMT-LIB2 solvers, named like `solver`, that re-check every
+    /// obligation the main solver decides; disagreement is an error.
+    #[serde(default)]
+    pub cross_check: Option<Vec<String>>,
+    /// Command validating UNSAT proofs under the certification profile,
+    /// e.g. `["carcara", "check"]`; it is passed a script and a proof file.
+    #[serde(default)]
+    pub proof_checker: Option<Vec<String>>,
     /// Solver timeout in seconds.
     #[serde(default)]
     pub timeout: Option<u64>,
+    /// Keep at most this many proofs in the proof cache.
+    #[serde(default)]
+    pub cache_max_entries: Option<usize>,
+    /// Evict cached proofs older than this many days.
+    #[serde(default)]
+    pub cache_max_age_days: Option<u64>,
 }
 
 /// FFI configuration section.
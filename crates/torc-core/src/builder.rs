@@ -40,6 +40,7 @@ use crate::graph::region::{Region, RegionId, RegionKind};
 use crate::graph::{Graph, GraphError};
 use crate::provenance::Provenance;
 use crate::types::{Type, TypeSignature};
+use crate::value::Value;
 
 /// A builder for constructing Torc computation graphs.
 ///
@@ -340,6 +341,16 @@ impl GraphBuilder {
         Ok(())
     }
 
+    /// Set the constant value of a `Literal` node.
+    pub fn set_value(&mut self, node_id: NodeId, value: Value) -> Result<(), GraphError> {
+        let node = self
+            .graph
+            .get_node_mut(&node_id)
+            .ok_or(GraphError::NodeNotFound(node_id))?;
+        node.value = Some(value);
+        Ok(())
+    }
+
     /// Set the contract on a node.
     pub fn set_contract(&mut self, node_id: NodeId, contract: Contract) -> Result<(), GraphError> {
         let node = self
@@ -372,6 +383,16 @@ impl GraphBuilder {
         Ok(self.graph)
     }
 
+    /// Validate and return the constructed graph, renumbered with
+    /// content-addressed IDs in the module `namespace` (see
+    /// `Graph::canonicalize`), so that building the same program again
+    /// gives the same IDs.
+    pub fn build_canonical(self, namespace: &str) -> Result<Graph, Vec<GraphError>> {
+        let mut graph = self.build()?;
+        graph.canonicalize(namespace);
+        Ok(graph)
+    }
+
     /// Return the graph without validation (for testing or incremental construction).
     pub fn into_graph(self) -> Graph {
         self.graph
@@ -402,6 +423,33 @@ mod tests {
         assert_eq!(graph.edge_count(), 2);
     }
 
+    #[test]
+    fn canonical_build_is_reproducible() {
+        let build = || {
+            let mut b = GraphBuilder::new();
+            let a = b.add_typed_node(NodeKind::Literal, "a", TypeSignature::source(Type::i32()));
+            b.set_value(a, Value::i32(1)).unwrap();
+            let neg = b.add_typed_node(
+                NodeKind::Arithmetic(ArithmeticOp::Sub),
+                "neg",
+                TypeSignature::pure_fn(vec![Type::i32()], Type::i32()),
+            );
+            b.connect(a, 0, neg, 0).unwrap();
+            b.build_canonical("example").unwrap()
+        };
+        let (first, second) = (build(), build());
+        let ids = |g: &Graph| {
+            let mut ids: Vec<_> = g.nodes().map(|n| n.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+    }
+
     #[test]
     fn named_node_lookup() {
         let mut b = GraphBuilder::new();
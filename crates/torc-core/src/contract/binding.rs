@@ -0,0 +1,202 @@
+//! Binding contract variables to node ports.
+//!
+//! A contract talks about its node's values through free predicate variables.
+//! Each variable may be bound to one of the node's own ports, explicitly via
+//! `Contract::with_binding` or by convention:
+//!
+//! - `input`, `input0`, `input1`, ... name input ports;
+//! - `output`, `result`, `output0`, `output1`, ... name output ports.
+//!
+//! Within a graph a value is identified by the output port that produces it,
+//! written as a *port variable* (`port_var`). `Graph::bind_predicate` replaces
+//! a node's bound variables with port variables — outputs directly, inputs
+//! through the incoming edge — so a producer's postcondition and a consumer's
+//! precondition end up talking about the same variable.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::contract::Contract;
+use crate::graph::edge::{Edge, PortRef};
+use crate::graph::node::NodeId;
+use crate::graph::port::PortDirection;
+use crate::graph::Graph;
+use crate::types::Predicate;
+
+/// A contract variable bound to one of the node's ports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PortBinding {
+    pub direction: PortDirection,
+    pub port: usize,
+}
+
+impl PortBinding {
+    pub fn input(port: usize) -> Self {
+        Self {
+            direction: PortDirection::Input,
+            port,
+        }
+    }
+
+    pub fn output(port: usize) -> Self {
+        Self {
+            direction: PortDirection::Output,
+            port,
+        }
+    }
+
+    /// The binding a variable name has by convention, if any.
+    pub fn conventional(var: &str) -> Option<Self> {
+        if var == "result" {
+            return Some(Self::output(0));
+        }
+        let indexed = |prefix: &str| match var.strip_prefix(prefix)? {
+            "" | "0" => Some(0),
+            n if !n.starts_with('0') && n.bytes().all(|b| b.is_ascii_digit()) => n.parse().ok(),
+            _ => None,
+        };
+        indexed("input")
+            .map(Self::input)
+            .or_else(|| indexed("output").map(Self::output))
+    }
+}
+
+impl Contract {
+    /// Bind a predicate variable to one of this node's ports.
+    pub fn with_binding(mut self, var: impl Into<String>, binding: PortBinding) -> Self {
+        self.bindings.insert(var.into(), binding);
+        self
+    }
+
+    /// The port a variable refers to: an explicit binding, else the
+    /// conventional meaning of the name.
+    pub fn binding(&self, var: &str) -> Option<PortBinding> {
+        self.bindings
+            .get(var)
+            .copied()
+            .or_else(|| PortBinding::conventional(var))
+    }
+}
+
+/// The variable naming the value on an output port: `"<node>.<port>"`.
+pub fn port_var((node, port): PortRef) -> String {
+    format!("{node}.{port}")
+}
+
+/// Recover the output port from a port variable.
+pub fn parse_port_var(var: &str) -> Option<PortRef> {
+    let (node, port) = var.rsplit_once('.')?;
+    Some((node.parse::<NodeId>().ok()?, port.parse().ok()?))
+}
+
+impl Graph {
+    /// The edge feeding an input port, if the input is connected.
+    pub fn input_edge(&self, (node, port): PortRef) -> Option<&Edge> {
+        self.incoming_edges(&node)
+            .iter()
+            .filter_map(|id| self.get_edge(id))
+            .find(|edge| edge.target.1 == port)
+    }
+
+    /// The output port feeding an input port, if the input is connected.
+    pub fn input_source(&self, input: PortRef) -> Option<PortRef> {
+        self.input_edge(input).map(|edge| edge.source)
+    }
+
+    /// Rewrite a predicate written against `node`'s contract over port
+    /// variables.
+    ///
+    /// Variables bound to an output become that output's port variable;
+    /// variables bound to a connected input become the port variable of the
+    /// output feeding it. Unbound variables, and inputs with no incoming
+    /// edge, are left as they are. Substitution is capture-avoiding.
+    pub fn bind_predicate(&self, node: NodeId, predicate: &Predicate) -> Predicate {
+        let contract = self.get_node(&node).and_then(|n| n.contract.as_ref());
+        let map: BTreeMap<String, Predicate> = predicate
+            .free_vars()
+            .into_iter()
+            .filter_map(|var| {
+                let binding = match contract {
+                    Some(c) => c.binding(&var),
+                    None => PortBinding::conventional(&var),
+                }?;
+                let source = match binding.direction {
+                    PortDirection::Output => Some((node, binding.port)),
+                    PortDirection::Input => self.input_source((node, binding.port)),
+                }?;
+                Some((var, Predicate::Var(port_var(source))))
+            })
+            .collect();
+        predicate.substitute(&map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::{Node, NodeKind};
+
+    #[test]
+    fn conventional_names() {
+        assert_eq!(
+            PortBinding::conventional("input"),
+            Some(PortBinding::input(0))
+        );
+        assert_eq!(
+            PortBinding::conventional("input2"),
+            Some(PortBinding::input(2))
+        );
+        assert_eq!(
+            PortBinding::conventional("result"),
+            Some(PortBinding::output(0))
+        );
+        assert_eq!(
+            PortBinding::conventional("output1"),
+            Some(PortBinding::output(1))
+        );
+        assert_eq!(PortBinding::conventional("input01"), None);
+        assert_eq!(PortBinding::conventional("inputs"), None);
+        assert_eq!(PortBinding::conventional("x"), None);
+    }
+
+    #[test]
+    fn port_var_round_trips() {
+        let port = (NodeId::new_v4(), 3);
+        assert_eq!(parse_port_var(&port_var(port)), Some(port));
+        assert_eq!(parse_port_var("value"), None);
+    }
+
+    #[test]
+    fn producer_and_consumer_share_port_variable() {
+        let mut g = Graph::new();
+        let producer = Node::new(NodeKind::Read).with_contract(Contract::with_conditions(
+            vec![],
+            vec![Predicate::positive("output")],
+        ));
+        let consumer = Node::new(NodeKind::Write).with_contract(
+            Contract::with_conditions(vec![Predicate::positive("x")], vec![])
+                .with_binding("x", PortBinding::input(0)),
+        );
+        let (p, c) = (producer.id, consumer.id);
+        g.add_node(producer).unwrap();
+        g.add_node(consumer).unwrap();
+        g.add_edge(Edge::new((p, 0), (c, 0))).unwrap();
+
+        let post = g.bind_predicate(p, &Predicate::positive("output"));
+        let pre = g.bind_predicate(c, &Predicate::positive("x"));
+        assert_eq!(post, Predicate::positive(&port_var((p, 0))));
+        assert_eq!(post, pre);
+
+        // Unbound names and unconnected inputs are left alone
+        assert_eq!(
+            g.bind_predicate(c, &Predicate::positive("input1")),
+            Predicate::positive("input1")
+        );
+        assert_eq!(
+            g.bind_predicate(c, &Predicate::positive("y")),
+            Predicate::positive("y")
+        );
+    }
+}
@@ -2,9 +2,27 @@
 //!
 //! Turns a `Contract` into a list of concrete `ProofObligation`s that
 //! the verification engine (torc-verify) must discharge before materialization.
+//! Arithmetic nodes under the `proven` overflow policy get a no-overflow
+//! obligation from `overflow_obligation`, the implicit preconditions of
+//! partial operations come from `domain_obligations`, loops get
+//! termination obligations from `termination_obligations` and
+//! `region_termination_obligations`, region budgets get resource
+//! obligations from `resource_obligations`, and `Approximate` outputs get
+//! error-propagation obligations from `approximation_obligation`.
 
-use crate::contract::{Contract, ObligationKind, ProofObligation, ProofStatus};
-use crate::types::Predicate;
+use std::collections::{BTreeMap, HashSet};
+
+use crate::contract::binding::port_var;
+use crate::contract::{Contract, ObligationKind, ProofObligation, ProofStatus, Variant};
+use crate::fixed::{self, Overflow};
+use crate::graph::edge::{EdgeId, PortRef};
+use crate::graph::effects::{has_measure, DIVERGE_JUSTIFICATION};
+use crate::graph::node::{ArithmeticOp, Node, NodeId, NodeKind};
+use crate::graph::region::{Region, RegionKind};
+use crate::graph::resources::Resource;
+use crate::graph::Graph;
+use crate::types::{Effect, Predicate, Signedness, Type};
+use crate::value::Value;
 
 impl Contract {
     /// Generate all proof obligations from this contract.
@@ -129,6 +147,549 @@ impl Contract {
     }
 }
 
+/// The no-overflow obligation of an `Arithmetic` node whose overflow policy
+/// is `proven`, stated over the port variables of its operands.
+///
+/// Returns `None` for other nodes and policies, for results that are not
+/// integer or fixed-point, for unconnected operands, and where the
+/// operation cannot overflow (fixed-point `Mod`, unsigned `Div` and `Mod`).
+/// `Pow` has no predicate operator, so its bound is stated over an opaque
+/// `pow(a, b)` that no engine decides; it stays pending until proven by hand
+/// or waived. A malformed `"overflow"` annotation gives an obligation that
+/// is `false`, so the node cannot verify.
+pub fn overflow_obligation(graph: &Graph, node: &Node) -> Option<ProofObligation> {
+    let NodeKind::Arithmetic(op) = node.kind else {
+        return None;
+    };
+    match Overflow::of_node(node) {
+        Ok(Overflow::Proven) => {}
+        Ok(_) => return None,
+        Err(err) => {
+            return Some(ProofObligation {
+                kind: ObligationKind::NoOverflow,
+                predicate: Predicate::BoolLit(false),
+                description: format!("{} has an invalid overflow annotation: {err}", node.kind),
+                status: ProofStatus::Pending,
+                witness: None,
+                waiver: None,
+            })
+        }
+    }
+    let ty = node.type_signature.as_ref()?.outputs.first()?.base_type();
+    let operand = |port| {
+        graph
+            .input_source((node.id, port))
+            .map(|source| Box::new(Predicate::Var(port_var(source))))
+    };
+    let (a, b) = (operand(0)?, operand(1)?);
+    let (lo, hi) = match ty {
+        Type::Int { width, signedness } => {
+            let (lo, hi) = fixed::int_range(*width, *signedness);
+            (Predicate::IntLit(lo), Predicate::IntLit(hi))
+        }
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => {
+            let (lo, hi) = fixed::raw_range(*total_bits);
+            let scale = 2f64.powi(*frac_bits as i32);
+            (
+                Predicate::FloatLit(toward_zero(lo) / scale),
+                Predicate::FloatLit(toward_zero(hi) / scale),
+            )
+        }
+        _ => return None,
+    };
+
+    let result = match op {
+        ArithmeticOp::Add => Predicate::Add(a, b),
+        ArithmeticOp::Sub => Predicate::Sub(a, b),
+        ArithmeticOp::Mul => Predicate::Mul(a, b),
+        ArithmeticOp::Pow => Predicate::Apply("pow".into(), vec![*a, *b]),
+        ArithmeticOp::Div | ArithmeticOp::Mod => match ty {
+            // Only MIN / -1 overflows
+            Type::Int {
+                signedness: Signedness::Signed,
+                ..
+            } => Predicate::Or(
+                Box::new(Predicate::Ne(a, Box::new(lo.clone()))),
+                Box::new(Predicate::Ne(b, Box::new(Predicate::IntLit(-1)))),
+            ),
+            Type::Fixed { .. } if op == ArithmeticOp::Div => Predicate::Div(a, b),
+            _ => return None,
+        },
+    };
+    let predicate = match result {
+        Predicate::Add(..)
+        | Predicate::Sub(..)
+        | Predicate::Mul(..)
+        | Predicate::Div(..)
+        | Predicate::Apply(..) => {
+            let result = Box::new(result);
+            Predicate::And(
+                Box::new(Predicate::Le(Box::new(lo), result.clone())),
+                Box::new(Predicate::Le(result, Box::new(hi))),
+            )
+        }
+        other => other,
+    };
+    Some(ProofObligation {
+        kind: ObligationKind::NoOverflow,
+        predicate,
+        description: format!("{} result must fit in {ty}", node.kind),
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    })
+}
+
+/// `raw` as the nearest `f64` no further from zero, so that a range bound
+/// stated with it is at most as wide as the exact one.
+fn toward_zero(raw: i128) -> f64 {
+    let f = raw as f64;
+    // `f as i128` saturates, so 2^127 would compare equal to i128::MAX
+    if raw >= 0 && (f >= 2f64.powi(127) || f as i128 > raw) {
+        f.next_down()
+    } else if raw < 0 && (f as i128) < raw {
+        f.next_up()
+    } else {
+        f
+    }
+}
+
+/// The implicit preconditions of a partial operation, each tied to the edge
+/// carrying the input it constrains:
+///
+/// - integer and fixed-point `Div`/`Mod`: the divisor is non-zero, also
+///   when neither the node nor the edge gives the divisor a type
+/// - signed integer `Pow`: the exponent is non-negative
+/// - `Index`: `0 <= index < len`
+/// - `Slice`: `0 <= start <= end` and `end <= len`
+///
+/// The length is the static length of an array or tuple input and
+/// `len(collection)` otherwise. Unconnected inputs get no obligation.
+pub fn domain_obligations(graph: &Graph, node: &Node) -> Vec<(EdgeId, ProofObligation)> {
+    let input_ty = |port: usize| {
+        node.type_signature
+            .as_ref()
+            .and_then(|sig| sig.inputs.get(port))
+            .map(Type::base_type)
+    };
+    let operand = |port| {
+        let edge = graph.input_edge((node.id, port))?;
+        Some((edge.id, Box::new(Predicate::Var(port_var(edge.source)))))
+    };
+    let obligation = |predicate, description: String| ProofObligation {
+        kind: ObligationKind::Precondition,
+        predicate,
+        description,
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    };
+    let int = |v| Box::new(Predicate::IntLit(v));
+    let length = |var: &Predicate| match input_ty(0) {
+        Some(Type::Array { length, .. }) => int(*length as i128),
+        Some(Type::Tuple(elements)) => int(elements.len() as i128),
+        _ => Box::new(Predicate::Apply("len".into(), vec![var.clone()])),
+    };
+
+    let mut obligations = Vec::new();
+    match node.kind {
+        NodeKind::Arithmetic(ArithmeticOp::Div | ArithmeticOp::Mod) => {
+            let divisor_ty = input_ty(1).or_else(|| {
+                graph
+                    .input_edge((node.id, 1))
+                    .and_then(|edge| edge.data_type.as_ref())
+                    .map(Type::base_type)
+            });
+            let zero = match divisor_ty {
+                Some(Type::Int { .. }) | None => Predicate::IntLit(0),
+                Some(Type::Fixed { .. }) => Predicate::FloatLit(0.0),
+                _ => return obligations,
+            };
+            if let Some((edge, divisor)) = operand(1) {
+                obligations.push((
+                    edge,
+                    obligation(
+                        Predicate::Ne(divisor, Box::new(zero)),
+                        format!("{} divisor must be non-zero", node.kind),
+                    ),
+                ));
+            }
+        }
+        NodeKind::Arithmetic(ArithmeticOp::Pow) => {
+            let signed = matches!(
+                input_ty(1),
+                Some(Type::Int {
+                    signedness: Signedness::Signed,
+                    ..
+                })
+            );
+            if let (true, Some((edge, exponent))) = (signed, operand(1)) {
+                obligations.push((
+                    edge,
+                    obligation(
+                        Predicate::Ge(exponent, int(0)),
+                        "Pow exponent must be non-negative".into(),
+                    ),
+                ));
+            }
+        }
+        NodeKind::Index => {
+            if let (Some((_, collection)), Some((edge, index))) = (operand(0), operand(1)) {
+                let predicate = Predicate::And(
+                    Box::new(Predicate::Ge(index.clone(), int(0))),
+                    Box::new(Predicate::Lt(index, length(&collection))),
+                );
+                obligations.push((
+                    edge,
+                    obligation(predicate, "Index must be in bounds".into()),
+                ));
+            }
+        }
+        NodeKind::Slice => {
+            let Some((_, collection)) = operand(0) else {
+                return obligations;
+            };
+            let (start, end) = (operand(1), operand(2));
+            if let (Some((edge, start)), Some((_, end))) = (&start, &end) {
+                let predicate = Predicate::And(
+                    Box::new(Predicate::Ge(start.clone(), int(0))),
+                    Box::new(Predicate::Le(start.clone(), end.clone())),
+                );
+                obligations.push((
+                    *edge,
+                    obligation(predicate, "Slice start must not exceed its end".into()),
+                ));
+            }
+            if let Some((edge, end)) = end {
+                obligations.push((
+                    edge,
+                    obligation(
+                        Predicate::Le(end, length(&collection)),
+                        "Slice end must be in bounds".into(),
+                    ),
+                ));
+            }
+        }
+        _ => {}
+    }
+    obligations
+}
+
+/// The termination obligations of a loop node (`Iterate`, `Recurse`,
+/// `Fixpoint`).
+///
+/// A `Variant` in the node's contract, bound to its ports, must strictly
+/// decrease and stay bounded below. So must the metric of a `Recurse` with a
+/// metric port (see `metric_obligations`). A bounded `Iterate` needs no
+/// obligation. Any other loop may diverge: it must declare `Diverge` with a
+/// `DIVERGE_JUSTIFICATION`, and gets an unprovable obligation until it does.
+pub fn termination_obligations(graph: &Graph, node: &Node) -> Vec<ProofObligation> {
+    if !matches!(
+        node.kind,
+        NodeKind::Iterate | NodeKind::Recurse | NodeKind::Fixpoint
+    ) {
+        return Vec::new();
+    }
+    let what = format!("{} node", node.kind);
+    if let Some(variant) = node.contract.as_ref().and_then(|c| c.variant.as_ref()) {
+        let bind = |p: &Predicate| graph.bind_predicate(node.id, p);
+        let bound = Variant {
+            measure: bind(&variant.measure),
+            next: bind(&variant.next),
+            lower_bound: bind(&variant.lower_bound),
+        };
+        return variant_obligations(&bound, &what);
+    }
+    if node.kind == NodeKind::Recurse && graph.input_source((node.id, 3)).is_some() {
+        return metric_obligations(graph, node, &what);
+    }
+    let justified = node
+        .contract
+        .as_ref()
+        .is_some_and(|c| c.effects.has_effect(&Effect::Diverge))
+        && node.annotations.contains_key(DIVERGE_JUSTIFICATION);
+    if has_measure(node, graph) || justified {
+        return Vec::new();
+    }
+    vec![unmeasured(&what)]
+}
+
+/// The obligations of a `Recurse` node's metric port: the metric of the
+/// next argument is below the metric, and the metric is non-negative.
+///
+/// Both are written over the argument (the node's output) by following the
+/// metric and the next argument back through integer literals, `Add`,
+/// `Sub` and `Mul`; other ports outside the loop body are the same on every
+/// iteration and stay variables. A metric or next argument computed any
+/// other way cannot be related to the next metric, and gets an unprovable
+/// obligation. Non-negativity may assume the argument is within its type.
+fn metric_obligations(graph: &Graph, node: &Node, what: &str) -> Vec<ProofObligation> {
+    let body: HashSet<NodeId> = graph.loop_body(node.id).into_iter().collect();
+    let lift = |port| {
+        let source = graph.input_source((node.id, port))?;
+        lift_port(graph, source, node.id, &body)
+    };
+    let (Some(metric), Some(next)) = (lift(3), lift(2)) else {
+        return vec![ProofObligation {
+            kind: ObligationKind::Termination,
+            predicate: Predicate::BoolLit(false),
+            description: format!(
+                "{what} metric is not arithmetic in its argument; give it a variant instead"
+            ),
+            status: ProofStatus::Pending,
+            witness: None,
+            waiver: None,
+        }];
+    };
+    let argument = port_var((node.id, 0));
+    let next_metric = metric.substitute(&BTreeMap::from([(argument.clone(), next)]));
+    let mut obligations = variant_obligations(&Variant::new(metric, next_metric), what);
+    let within_type = node
+        .type_signature
+        .as_ref()
+        .and_then(|s| s.outputs.first())
+        .and_then(|ty| match ty.base_type() {
+            Type::Int { width, signedness } => {
+                let (lo, hi) = fixed::int_range(*width, *signedness);
+                Some(Predicate::in_range(&argument, lo, hi))
+            }
+            _ => None,
+        });
+    if let Some(premise) = within_type {
+        let bounded = &mut obligations[1].predicate;
+        *bounded = Predicate::Implies(Box::new(premise), Box::new(bounded.clone()));
+    }
+    obligations
+}
+
+/// The value of `port` as an expression over the port variable of `loop_node`'s
+/// output and ports outside `body`, if it is computed from them by
+/// arithmetic `metric_obligations` can follow.
+fn lift_port(
+    graph: &Graph,
+    (id, port): PortRef,
+    loop_node: NodeId,
+    body: &HashSet<NodeId>,
+) -> Option<Predicate> {
+    let node = graph.get_node(&id)?;
+    if let (NodeKind::Literal, Some(Value::Int(v))) = (&node.kind, &node.value) {
+        return v.to_i128().map(Predicate::IntLit);
+    }
+    if id == loop_node || !body.contains(&id) {
+        return Some(Predicate::Var(port_var((id, port))));
+    }
+    let operand = |port| {
+        let source = graph.input_source((id, port))?;
+        lift_port(graph, source, loop_node, body).map(Box::new)
+    };
+    match &node.kind {
+        NodeKind::Arithmetic(ArithmeticOp::Add) => Some(Predicate::Add(operand(0)?, operand(1)?)),
+        NodeKind::Arithmetic(ArithmeticOp::Sub) => Some(Predicate::Sub(operand(0)?, operand(1)?)),
+        NodeKind::Arithmetic(ArithmeticOp::Mul) => Some(Predicate::Mul(operand(0)?, operand(1)?)),
+        _ => None,
+    }
+}
+
+/// The termination obligations of an iterative region: those of its
+/// `Variant`, or without one, an unprovable obligation unless a node in the
+/// region performs a justified `Diverge`.
+pub fn region_termination_obligations(graph: &Graph, region: &Region) -> Vec<ProofObligation> {
+    if region.kind != RegionKind::Iterative {
+        return Vec::new();
+    }
+    let what = "iterative region";
+    if let Some(variant) = &region.variant {
+        return variant_obligations(variant, what);
+    }
+    let justified = graph
+        .effect_sources(&Effect::Diverge, Some(&region.id))
+        .iter()
+        .filter_map(|id| graph.get_node(id))
+        .any(|n| n.annotations.contains_key(DIVERGE_JUSTIFICATION));
+    if justified {
+        Vec::new()
+    } else {
+        vec![unmeasured(what)]
+    }
+}
+
+/// The resource obligations of a region: for each budget among its
+/// constraints, that the usage composed from its children (see
+/// `graph::resources`) fits it, for any non-negative values of the unknowns
+/// in the usage.
+///
+/// When the usage is known to exceed the budget, the description names the
+/// node contributing the most on the worst-case path.
+pub fn resource_obligations(graph: &Graph, region: &Region) -> Vec<ProofObligation> {
+    Resource::ALL
+        .into_iter()
+        .filter_map(|resource| {
+            let budget = resource.budget(&region.constraints)?;
+            let usage = graph.region_usage(region.id, resource);
+            let unit = resource.unit();
+            let description = match (usage.worst, usage.critical.first()) {
+                (Some(worst), Some((node, amount))) if worst > budget => format!(
+                    "{} region {} uses {worst}{unit} of {resource}, over its budget of \
+                     {budget}{unit}; node {node} alone uses {amount}{unit}",
+                    region.kind, region.id
+                ),
+                _ => format!(
+                    "{} region {} must fit its {resource} budget of {budget}{unit}",
+                    region.kind, region.id
+                ),
+            };
+            // Unknown counts and amounts are never negative
+            let unknowns = usage
+                .expr
+                .free_vars()
+                .into_iter()
+                .map(|var| {
+                    Predicate::Ge(
+                        Box::new(Predicate::Var(var)),
+                        Box::new(Predicate::IntLit(0)),
+                    )
+                })
+                .reduce(|a, b| Predicate::And(Box::new(a), Box::new(b)));
+            let fits = Predicate::Le(
+                Box::new(usage.expr),
+                Box::new(Predicate::IntLit(budget as i128)),
+            );
+            Some(ProofObligation {
+                kind: ObligationKind::ResourceBound,
+                predicate: match unknowns {
+                    Some(unknowns) => Predicate::Implies(Box::new(unknowns), Box::new(fits)),
+                    None => fits,
+                },
+                description,
+                status: ProofStatus::Pending,
+                witness: None,
+                waiver: None,
+            })
+        })
+        .collect()
+}
+
+/// The error bound declared by an `Approximate` output must cover the
+/// error its inputs propagate to it.
+///
+/// An input of `Approximate` type is off by up to its `max_error`; any
+/// other input is exact. Errors add through `Add` and `Sub`. Through `Mul`
+/// the result is off by up to `|a|·e_b + |b|·e_a + e_a·e_b`, and through
+/// `Div` by up to `(|a|·e_b + |b|·e_a) / (|b|·(|b| - e_b))` provided
+/// `|b| > e_b`, where `a` and `b` are the computed operands. An
+/// `Approximate` node passes its input's error through. `Mod` and `Pow`
+/// have no rule, so their obligation is `false`.
+///
+/// Only `Arithmetic` and `Approximate` nodes with an `Approximate` output
+/// get an obligation; `Mul` and `Div` also need connected operands.
+pub fn approximation_obligation(graph: &Graph, node: &Node) -> Option<ProofObligation> {
+    let sig = node.type_signature.as_ref()?;
+    let Type::Approximate { max_error, .. } = sig.outputs.first()?.base_type() else {
+        return None;
+    };
+    let error = |port: usize| match sig.inputs.get(port).map(Type::base_type) {
+        Some(Type::Approximate { max_error, .. }) => *max_error,
+        _ => 0.0,
+    };
+    let float = |x: f64| Box::new(Predicate::FloatLit(x));
+    let abs = |port| {
+        graph.input_source((node.id, port)).map(|source| {
+            Box::new(Predicate::Apply(
+                "abs".into(),
+                vec![Predicate::Var(port_var(source))],
+            ))
+        })
+    };
+    let bound = float(*max_error);
+    let (ea, eb) = (error(0), error(1));
+
+    let predicate = match node.kind {
+        NodeKind::Approximate => Predicate::Le(float(ea), bound),
+        NodeKind::Arithmetic(ArithmeticOp::Add | ArithmeticOp::Sub) => {
+            Predicate::Le(Box::new(Predicate::Add(float(ea), float(eb))), bound)
+        }
+        NodeKind::Arithmetic(ArithmeticOp::Mul) => {
+            let (a, b) = (abs(0)?, abs(1)?);
+            let propagated = Predicate::Add(
+                Box::new(Predicate::Add(
+                    Box::new(Predicate::Mul(a, float(eb))),
+                    Box::new(Predicate::Mul(b, float(ea))),
+                )),
+                float(ea * eb),
+            );
+            Predicate::Le(Box::new(propagated), bound)
+        }
+        NodeKind::Arithmetic(ArithmeticOp::Div) => {
+            let (a, b) = (abs(0)?, abs(1)?);
+            let propagated = Predicate::Div(
+                Box::new(Predicate::Add(
+                    Box::new(Predicate::Mul(a, float(eb))),
+                    Box::new(Predicate::Mul(b.clone(), float(ea))),
+                )),
+                Box::new(Predicate::Mul(
+                    b.clone(),
+                    Box::new(Predicate::Sub(b.clone(), float(eb))),
+                )),
+            );
+            Predicate::And(
+                Box::new(Predicate::Gt(b, float(eb))),
+                Box::new(Predicate::Le(Box::new(propagated), bound)),
+            )
+        }
+        NodeKind::Arithmetic(ArithmeticOp::Mod | ArithmeticOp::Pow) => Predicate::BoolLit(false),
+        _ => return None,
+    };
+    Some(ProofObligation {
+        kind: ObligationKind::ApproximationError,
+        predicate,
+        description: format!(
+            "{} result must stay within its error bound of {max_error}",
+            node.kind
+        ),
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    })
+}
+
+fn variant_obligations(variant: &Variant, what: &str) -> Vec<ProofObligation> {
+    let termination = |predicate, description| ProofObligation {
+        kind: ObligationKind::Termination,
+        predicate,
+        description,
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    };
+    let measure = || Box::new(variant.measure.clone());
+    vec![
+        termination(
+            Predicate::Lt(Box::new(variant.next.clone()), measure()),
+            format!("{what} measure must strictly decrease"),
+        ),
+        termination(
+            Predicate::Ge(measure(), Box::new(variant.lower_bound.clone())),
+            format!("{what} measure must be bounded below"),
+        ),
+    ]
+}
+
+fn unmeasured(what: &str) -> ProofObligation {
+    ProofObligation {
+        kind: ObligationKind::Termination,
+        predicate: Predicate::BoolLit(false),
+        description: format!(
+            "{what} has no termination measure; give it a variant or declare a justified Diverge"
+        ),
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    }
+}
+
 /// A predicate is trivial if it is `BoolLit(true)`.
 fn is_trivial(pred: &Predicate) -> bool {
     matches!(pred, Predicate::BoolLit(true))
@@ -250,4 +811,450 @@ mod tests {
         assert_eq!(obs[0].kind, ObligationKind::Postcondition);
         assert!(obs[0].description.contains("ADC_TIMEOUT"));
     }
+
+    #[test]
+    fn wide_fixed_point_limits_round_inward() {
+        use super::toward_zero;
+
+        let (lo, hi) = (i64::MIN as i128 + 1, i64::MAX as i128);
+        assert!((toward_zero(hi) as i128) <= hi);
+        assert!((toward_zero(lo) as i128) >= lo);
+        assert_eq!(toward_zero(1 << 53), 2f64.powi(53));
+        assert_eq!(toward_zero(-(1 << 53)), -(2f64.powi(53)));
+    }
+
+    #[test]
+    fn proven_overflow_policy_generates_obligation() {
+        use crate::contract::binding::port_var;
+        use crate::contract::generate::overflow_obligation;
+        use crate::graph::edge::Edge;
+        use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+        use crate::graph::Graph;
+        use crate::types::{Type, TypeSignature};
+        use crate::value::Value;
+
+        let mut g = Graph::new();
+        let a = g
+            .add_node(
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(Type::i32()))
+                    .with_value(Value::i32(7)),
+            )
+            .unwrap();
+        let mut add = |policy: Option<&str>, op| {
+            let mut node = Node::new(NodeKind::Arithmetic(op)).with_type_signature(
+                TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
+            );
+            if let Some(policy) = policy {
+                node.annotations.insert("overflow".into(), policy.into());
+            }
+            let id = g.add_node(node).unwrap();
+            g.add_edge(Edge::new((a, 0), (id, 0))).unwrap();
+            g.add_edge(Edge::new((a, 0), (id, 1))).unwrap();
+            id
+        };
+        let proven = add(Some("proven"), ArithmeticOp::Add);
+        let wrapping = add(None, ArithmeticOp::Add);
+        let division = add(Some("proven"), ArithmeticOp::Div);
+        let power = add(Some("proven"), ArithmeticOp::Pow);
+        let malformed = add(Some("prooven"), ArithmeticOp::Add);
+
+        let ob = overflow_obligation(&g, g.get_node(&proven).unwrap()).unwrap();
+        assert_eq!(ob.kind, ObligationKind::NoOverflow);
+        let sum = Box::new(Predicate::Add(
+            Box::new(Predicate::Var(port_var((a, 0)))),
+            Box::new(Predicate::Var(port_var((a, 0)))),
+        ));
+        assert_eq!(
+            ob.predicate,
+            Predicate::And(
+                Box::new(Predicate::Le(
+                    Box::new(Predicate::IntLit(i32::MIN as i128)),
+                    sum.clone()
+                )),
+                Box::new(Predicate::Le(
+                    sum,
+                    Box::new(Predicate::IntLit(i32::MAX as i128))
+                )),
+            )
+        );
+        assert!(overflow_obligation(&g, g.get_node(&wrapping).unwrap()).is_none());
+        let div = overflow_obligation(&g, g.get_node(&division).unwrap()).unwrap();
+        assert!(matches!(div.predicate, Predicate::Or(..)));
+        let pow = overflow_obligation(&g, g.get_node(&power).unwrap()).unwrap();
+        assert!(matches!(pow.predicate, Predicate::And(..)));
+        assert!(format!("{:?}", pow.predicate).contains("\"pow\""));
+        let bad = overflow_obligation(&g, g.get_node(&malformed).unwrap()).unwrap();
+        assert_eq!(bad.predicate, Predicate::BoolLit(false));
+        assert!(bad.description.contains("prooven"));
+
+        let kinds: Vec<_> = g
+            .validate_contracts()
+            .into_iter()
+            .filter(|ob| ob.kind == ObligationKind::NoOverflow)
+            .collect();
+        assert_eq!(kinds.len(), 4);
+    }
+
+    #[test]
+    fn partial_operations_generate_domain_obligations() {
+        use crate::contract::binding::port_var;
+        use crate::contract::generate::domain_obligations;
+        use crate::graph::edge::Edge;
+        use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+        use crate::graph::{Graph, ObligationSite};
+        use crate::types::{Type, TypeSignature};
+
+        let table = Type::Array {
+            element: Box::new(Type::i32()),
+            length: 4,
+        };
+        let mut g = Graph::new();
+        let mut source = |ty: Type| {
+            g.add_node(Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(ty)))
+                .unwrap()
+        };
+        let (xs, i) = (source(table.clone()), source(Type::i32()));
+        let mut sink = |kind, inputs: Vec<Type>, output| {
+            g.add_node(
+                Node::new(kind).with_type_signature(TypeSignature::new(inputs, vec![output])),
+            )
+            .unwrap()
+        };
+        let div = sink(
+            NodeKind::Arithmetic(ArithmeticOp::Div),
+            vec![Type::i32(), Type::i32()],
+            Type::i32(),
+        );
+        let index = sink(
+            NodeKind::Index,
+            vec![table.clone(), Type::i32()],
+            Type::i32(),
+        );
+        let divisor = g.add_edge(Edge::new((i, 0), (div, 1))).unwrap();
+        g.add_edge(Edge::new((xs, 0), (index, 0))).unwrap();
+        let position = g.add_edge(Edge::new((i, 0), (index, 1))).unwrap();
+
+        let obs = domain_obligations(&g, g.get_node(&div).unwrap());
+        assert_eq!(obs.len(), 1);
+        assert_eq!(obs[0].0, divisor);
+        assert_eq!(obs[0].1.kind, ObligationKind::Precondition);
+        let var = || Box::new(Predicate::Var(port_var((i, 0))));
+        assert_eq!(
+            obs[0].1.predicate,
+            Predicate::Ne(var(), Box::new(Predicate::IntLit(0)))
+        );
+
+        let obs = domain_obligations(&g, g.get_node(&index).unwrap());
+        assert_eq!(obs[0].0, position);
+        assert_eq!(
+            obs[0].1.predicate,
+            Predicate::And(
+                Box::new(Predicate::Ge(var(), Box::new(Predicate::IntLit(0)))),
+                Box::new(Predicate::Lt(var(), Box::new(Predicate::IntLit(4)))),
+            )
+        );
+
+        // An untyped divisor may still be zero
+        let untyped = g
+            .add_node(Node::new(NodeKind::Arithmetic(ArithmeticOp::Mod)))
+            .unwrap();
+        let edge = g.add_edge(Edge::new((i, 0), (untyped, 1))).unwrap();
+        let obs = domain_obligations(&g, g.get_node(&untyped).unwrap());
+        assert_eq!(obs.len(), 1);
+        assert_eq!(obs[0].0, edge);
+        assert_eq!(
+            obs[0].1.predicate,
+            Predicate::Ne(var(), Box::new(Predicate::IntLit(0)))
+        );
+
+        let sites: Vec<_> = g
+            .contract_obligations()
+            .into_iter()
+            .filter(|(_, ob)| ob.kind == ObligationKind::Precondition)
+            .map(|(site, _)| site)
+            .collect();
+        assert_eq!(sites.len(), 3);
+        assert!(sites.contains(&ObligationSite::Edge(divisor)));
+        assert!(sites.contains(&ObligationSite::Edge(position)));
+    }
+
+    #[test]
+    fn loops_generate_termination_obligations() {
+        use crate::contract::binding::port_var;
+        use crate::contract::generate::{region_termination_obligations, termination_obligations};
+        use crate::contract::{PortBinding, Variant};
+        use crate::graph::edge::Edge;
+        use crate::graph::effects::DIVERGE_JUSTIFICATION;
+        use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+        use crate::graph::region::{Region, RegionKind};
+        use crate::graph::Graph;
+        use crate::types::Effect;
+
+        let mut g = Graph::new();
+        let seed = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let step = g
+            .add_node(Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub)))
+            .unwrap();
+        let mut contract = Contract::pure_default().with_variant(Variant::new(
+            Predicate::Var("n".into()),
+            Predicate::Var("next".into()),
+        ));
+        contract.bindings.insert("n".into(), PortBinding::output(0));
+        contract
+            .bindings
+            .insert("next".into(), PortBinding::input(1));
+        let fix = g
+            .add_node(Node::new(NodeKind::Fixpoint).with_contract(contract))
+            .unwrap();
+        g.add_edge(Edge::new((seed, 0), (fix, 0))).unwrap();
+        g.add_edge(Edge::new((fix, 0), (step, 0))).unwrap();
+        g.add_edge(Edge::new((step, 0), (fix, 1))).unwrap();
+
+        let obs = termination_obligations(&g, g.get_node(&fix).unwrap());
+        assert_eq!(obs.len(), 2);
+        assert!(obs.iter().all(|ob| ob.kind == ObligationKind::Termination));
+        let (n, next) = (
+            Box::new(Predicate::Var(port_var((fix, 0)))),
+            Box::new(Predicate::Var(port_var((step, 0)))),
+        );
+        assert_eq!(obs[0].predicate, Predicate::Lt(next, n.clone()));
+        assert_eq!(
+            obs[1].predicate,
+            Predicate::Ge(n, Box::new(Predicate::IntLit(0)))
+        );
+
+        // Without a measure, only a justified Diverge avoids an obligation
+        let open = g.add_node(Node::new(NodeKind::Fixpoint)).unwrap();
+        let obs = termination_obligations(&g, g.get_node(&open).unwrap());
+        assert_eq!(obs[0].predicate, Predicate::BoolLit(false));
+        let node = g.get_node_mut(&open).unwrap();
+        node.contract = Some(
+            Contract::pure_default().with_effects(EffectSet::from_effects(vec![Effect::Diverge])),
+        );
+        node.annotations
+            .insert(DIVERGE_JUSTIFICATION.into(), "server loop".into());
+        assert!(termination_obligations(&g, g.get_node(&open).unwrap()).is_empty());
+
+        let region = Region::new(RegionKind::Iterative, vec![step]);
+        assert_eq!(region_termination_obligations(&g, &region).len(), 1);
+        let region = region.with_variant(Variant::new(
+            Predicate::Var("k".into()),
+            Predicate::Sub(
+                Box::new(Predicate::Var("k".into())),
+                Box::new(Predicate::IntLit(1)),
+            ),
+        ));
+        assert_eq!(region_termination_obligations(&g, &region).len(), 2);
+        let open_region = g
+            .add_region(Region::new(RegionKind::Iterative, vec![open]))
+            .unwrap();
+        let open_region = g.get_region(&open_region).unwrap();
+        assert!(region_termination_obligations(&g, open_region).is_empty());
+    }
+
+    #[test]
+    fn recurse_metrics_must_decrease() {
+        use crate::contract::binding::port_var;
+        use crate::contract::generate::termination_obligations;
+        use crate::graph::edge::Edge;
+        use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+        use crate::graph::Graph;
+        use crate::types::{Type, TypeSignature};
+        use crate::value::Value;
+
+        // Counting down by one, with the argument as its own metric
+        let mut g = Graph::new();
+        let one = g
+            .add_node(Node::new(NodeKind::Literal).with_value(Value::u32(1)))
+            .unwrap();
+        let rec = g
+            .add_node(
+                Node::new(NodeKind::Recurse)
+                    .with_type_signature(TypeSignature::new(vec![Type::u32()], vec![Type::u32()])),
+            )
+            .unwrap();
+        let step = g
+            .add_node(Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub)))
+            .unwrap();
+        g.add_edge(Edge::new((rec, 0), (step, 0))).unwrap();
+        g.add_edge(Edge::new((one, 0), (step, 1))).unwrap();
+        g.add_edge(Edge::new((step, 0), (rec, 2))).unwrap();
+        g.add_edge(Edge::new((rec, 0), (rec, 3))).unwrap();
+
+        let obs = termination_obligations(&g, g.get_node(&rec).unwrap());
+        let n = || Box::new(Predicate::Var(port_var((rec, 0))));
+        assert_eq!(
+            obs[0].predicate,
+            Predicate::Lt(
+                Box::new(Predicate::Sub(n(), Box::new(Predicate::IntLit(1)))),
+                n()
+            )
+        );
+        assert_eq!(
+            obs[1].predicate,
+            Predicate::Implies(
+                Box::new(Predicate::in_range(
+                    &port_var((rec, 0)),
+                    0,
+                    u32::MAX as i128
+                )),
+                Box::new(Predicate::Ge(n(), Box::new(Predicate::IntLit(0)))),
+            )
+        );
+
+        // A metric computed in a way the obligations cannot follow
+        let convert = g.add_node(Node::new(NodeKind::Conversion)).unwrap();
+        let metric = g.input_edge((rec, 3)).unwrap().id;
+        g.remove_edge(metric).unwrap();
+        g.add_edge(Edge::new((rec, 0), (convert, 0))).unwrap();
+        g.add_edge(Edge::new((convert, 0), (rec, 3))).unwrap();
+        let obs = termination_obligations(&g, g.get_node(&rec).unwrap());
+        assert_eq!(obs.len(), 1);
+        assert_eq!(obs[0].predicate, Predicate::BoolLit(false));
+    }
+
+    #[test]
+    fn region_budgets_generate_resource_obligations() {
+        use crate::contract::generate::resource_obligations;
+        use crate::graph::constraints::Constraint;
+        use crate::graph::node::{Node, NodeKind};
+        use crate::graph::region::{Region, RegionKind};
+        use crate::graph::Graph;
+
+        let mut g = Graph::new();
+        let mut timed = |ns| {
+            g.add_node(
+                Node::new(NodeKind::Literal)
+                    .with_contract(Contract::pure_default().with_wcet(ns, "m4")),
+            )
+            .unwrap()
+        };
+        let (fast, slow) = (timed(200), timed(900));
+        let region = g
+            .add_region(
+                Region::new(RegionKind::Sequential, vec![fast, slow])
+                    .with_constraints(vec![Constraint::MaxTime(1_000)]),
+            )
+            .unwrap();
+
+        let obs = resource_obligations(&g, g.get_region(&region).unwrap());
+        assert_eq!(obs.len(), 1);
+        assert_eq!(obs[0].kind, ObligationKind::ResourceBound);
+        assert_eq!(
+            obs[0].predicate,
+            Predicate::Le(
+                Box::new(Predicate::Add(
+                    Box::new(Predicate::IntLit(200)),
+                    Box::new(Predicate::IntLit(900)),
+                )),
+                Box::new(Predicate::IntLit(1_000)),
+            )
+        );
+        assert!(obs[0].description.contains(&slow.to_string()));
+
+        let parallel = g
+            .add_region(
+                Region::new(RegionKind::Parallel, vec![fast, slow])
+                    .with_constraints(vec![Constraint::MaxTime(1_000), Constraint::MaxMemory(64)]),
+            )
+            .unwrap();
+        let obs = resource_obligations(&g, g.get_region(&parallel).unwrap());
+        assert_eq!(obs.len(), 2);
+        assert!(!obs[0].description.contains(&slow.to_string()));
+    }
+
+    #[test]
+    fn resource_obligations_assume_non_negative_unknowns() {
+        use crate::contract::generate::resource_obligations;
+        use crate::graph::constraints::Constraint;
+        use crate::graph::node::{Node, NodeKind};
+        use crate::graph::region::{Region, RegionKind};
+        use crate::graph::Graph;
+
+        let mut g = Graph::new();
+        let body = g
+            .add_node(
+                Node::new(NodeKind::Literal)
+                    .with_contract(Contract::pure_default().with_wcet(100, "m4")),
+            )
+            .unwrap();
+        let region = g
+            .add_region(
+                Region::new(RegionKind::Iterative, vec![body])
+                    .with_constraints(vec![Constraint::MaxTime(1_000)]),
+            )
+            .unwrap();
+
+        let obs = resource_obligations(&g, g.get_region(&region).unwrap());
+        assert_eq!(obs.len(), 1);
+        let Predicate::Implies(premise, _) = &obs[0].predicate else {
+            panic!(
+                "expected a premise over the unknowns: {:?}",
+                obs[0].predicate
+            );
+        };
+        assert_eq!(
+            **premise,
+            Predicate::Ge(
+                Box::new(Predicate::Var(format!("iterations.{region}"))),
+                Box::new(Predicate::IntLit(0)),
+            )
+        );
+    }
+
+    #[test]
+    fn approximate_outputs_bound_propagated_error() {
+        use crate::contract::generate::approximation_obligation;
+        use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+        use crate::graph::Graph;
+        use crate::types::{Type, TypeSignature};
+
+        let approx = |max_error| Type::Approximate {
+            inner: Box::new(Type::f64()),
+            max_error,
+        };
+        let mut g = Graph::new();
+        let mut node = |kind, inputs, output| {
+            let id = g
+                .add_node(
+                    Node::new(kind).with_type_signature(TypeSignature::pure_fn(inputs, output)),
+                )
+                .unwrap();
+            g.get_node(&id).unwrap().clone()
+        };
+        // One approximate and one exact operand
+        let sum = node(
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            vec![approx(0.5), Type::f64()],
+            approx(0.25),
+        );
+        let widen = node(NodeKind::Approximate, vec![approx(0.5)], approx(1.0));
+        let exact = node(
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            vec![Type::f64(), Type::f64()],
+            Type::f64(),
+        );
+
+        let ob = approximation_obligation(&g, &sum).unwrap();
+        assert_eq!(ob.kind, ObligationKind::ApproximationError);
+        assert_eq!(
+            ob.predicate,
+            Predicate::Le(
+                Box::new(Predicate::Add(
+                    Box::new(Predicate::FloatLit(0.5)),
+                    Box::new(Predicate::FloatLit(0.0))
+                )),
+                Box::new(Predicate::FloatLit(0.25))
+            )
+        );
+        assert_eq!(
+            approximation_obligation(&g, &widen).unwrap().predicate,
+            Predicate::Le(
+                Box::new(Predicate::FloatLit(0.5)),
+                Box::new(Predicate::FloatLit(1.0))
+            )
+        );
+        assert!(approximation_obligation(&g, &exact).is_none());
+    }
 }
@@ -5,15 +5,18 @@
 //! its full behavioral requirements. Contracts generate proof obligations
 //! that the verification engine must discharge before materialization.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
+pub mod binding;
 pub mod generate;
 
 use crate::types::{Effect, Predicate};
 
+pub use self::binding::PortBinding;
+
 /// The status of a proof obligation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProofStatus {
@@ -125,6 +128,36 @@ pub struct StackBound {
     pub max_bytes: u64,
 }
 
+/// A ranking function proving that a loop terminates: a measure that every
+/// iteration strictly decreases and that never drops below `lower_bound`.
+///
+/// `measure` is the value on entry to an iteration and `next` the value the
+/// iteration hands on; on a node they are bound to its ports like contract
+/// predicates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Variant {
+    pub measure: Predicate,
+    pub next: Predicate,
+    pub lower_bound: Predicate,
+}
+
+impl Variant {
+    /// A measure bounded below by zero.
+    pub fn new(measure: Predicate, next: Predicate) -> Self {
+        Self {
+            measure,
+            next,
+            lower_bound: Predicate::IntLit(0),
+        }
+    }
+
+    /// Set the lower bound.
+    pub fn with_lower_bound(mut self, lower_bound: Predicate) -> Self {
+        self.lower_bound = lower_bound;
+        self
+    }
+}
+
 /// A composable set of effects.
 ///
 /// Effects propagate upward: a node's effect set is the union of its own
@@ -221,6 +254,15 @@ pub struct Contract {
     pub proof_status: ProofStatus,
     /// Machine-checkable proof object (if verified).
     pub proof_witness: Option<ProofWitness>,
+
+    /// Predicate variables bound to the node's own ports, beyond the
+    /// conventional names (see `Contract::binding`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bindings: BTreeMap<String, PortBinding>,
+
+    /// Termination measure, for loop nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<Variant>,
 }
 
 impl Contract {
@@ -238,6 +280,8 @@ impl Contract {
             recovery_strategy: RecoveryStrategy::Propagate,
             proof_status: ProofStatus::Pending,
             proof_witness: None,
+            bindings: BTreeMap::new(),
+            variant: None,
         }
     }
 
@@ -293,6 +337,12 @@ impl Contract {
         self
     }
 
+    /// Set the termination measure.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
     /// Add a failure mode.
     pub fn add_failure_mode(&mut self, mode: FailureMode) {
         self.failure_modes.push(mode);
@@ -340,6 +390,10 @@ pub enum ObligationKind {
     Linearity,
     /// Termination proof for iterative/recursive nodes.
     Termination,
+    /// An arithmetic result fits its type (the `proven` overflow policy).
+    NoOverflow,
+    /// The error propagated to an `Approximate` output fits its bound.
+    ApproximationError,
 }
 
 /// A proof obligation generated by the type system or contracts.
@@ -0,0 +1,562 @@
+//! Fixed-point arithmetic semantics.
+//!
+//! A `Fixed<total, frac>` value is a signed `total`-bit integer scaled by
+//! `2^-frac`. Arithmetic takes two operands of the same format: `Add` and
+//! `Sub` work on the raw integers, `Mul` rescales the double-width product
+//! by `2^-frac`, `Div` prescales the dividend by `2^frac`, and `Mod` is the
+//! remainder of the raw integers. `Pow` is not defined.
+//!
+//! Two choices are made per node, through annotations:
+//!
+//! - `"rounding"`: how a result that falls between two representable
+//!   values is rounded (`toward_zero`, `floor`, `ceiling`, `nearest_even`,
+//!   `nearest_away`). Defaults to `toward_zero`, like integer division and
+//!   float-to-int conversion.
+//! - `"overflow"`: what happens to a result outside the format (`wrap`,
+//!   `saturate`, `trap`, `proven`). Arithmetic wraps by default, like
+//!   integer arithmetic; `Conversion` traps by default, as other
+//!   conversions do. The same annotation sets the policy of integer
+//!   arithmetic.
+//!
+//! The interpreter, the interval analysis and LLVM lowering all follow
+//! these rules. Intermediates are 128-bit, so formats wider than 64 bits
+//! can overflow a `Mul` or `Div` before rounding; that is reported as an
+//! overflow whatever the mode.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+use crate::types::{Signedness, Type};
+use crate::value::{FixedValue, IntValue};
+
+/// Annotation selecting the overflow behavior of a node.
+pub const OVERFLOW_ANNOTATION: &str = "overflow";
+
+/// Annotation selecting the rounding mode of a node.
+pub const ROUNDING_ANNOTATION: &str = "rounding";
+
+/// What happens to a result that does not fit its format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    /// Keep the low bits (two's-complement wrap-around).
+    #[default]
+    Wrap,
+    /// Clamp to the nearest representable value.
+    Saturate,
+    /// The result is an error, checked at run time.
+    Trap,
+    /// The result never overflows: a proof obligation is generated and
+    /// the generated code does no check.
+    Proven,
+}
+
+impl Overflow {
+    /// The policy requested by a node's `"overflow"` annotation, or the
+    /// default for its kind.
+    pub fn of_node(node: &Node) -> Result<Self, FixedError> {
+        match node.annotations.get(OVERFLOW_ANNOTATION) {
+            Some(mode) => mode.parse(),
+            None if node.kind == NodeKind::Conversion => Ok(Overflow::Trap),
+            None => Ok(Overflow::Wrap),
+        }
+    }
+}
+
+/// How a result between two representable values is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    #[default]
+    TowardZero,
+    /// Toward negative infinity (an arithmetic shift).
+    Floor,
+    /// Toward positive infinity.
+    Ceiling,
+    /// To nearest, ties to even.
+    NearestEven,
+    /// To nearest, ties away from zero.
+    NearestAway,
+}
+
+/// Errors from fixed-point operations.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FixedError {
+    #[error("unknown {annotation} mode \"{value}\"")]
+    UnknownMode {
+        annotation: &'static str,
+        value: String,
+    },
+
+    #[error("division by zero")]
+    DivisionByZero,
+
+    #[error("{value} does not fit in {ty}")]
+    Overflow { value: String, ty: Type },
+
+    #[error("{0:?} is not defined for fixed-point values")]
+    Unsupported(ArithmeticOp),
+}
+
+/// The rounding and overflow behavior of one node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct FixedSemantics {
+    pub overflow: Overflow,
+    pub rounding: Rounding,
+}
+
+impl FixedSemantics {
+    pub fn new(overflow: Overflow, rounding: Rounding) -> Self {
+        Self { overflow, rounding }
+    }
+
+    /// The semantics requested by a node's annotations, with the defaults
+    /// for its kind where none are given.
+    pub fn of_node(node: &Node) -> Result<Self, FixedError> {
+        let overflow = Overflow::of_node(node)?;
+        let rounding = match node.annotations.get(ROUNDING_ANNOTATION) {
+            Some(mode) => mode.parse()?,
+            None => Rounding::default(),
+        };
+        Ok(Self { overflow, rounding })
+    }
+
+    /// Fit an exact raw result into `[lo, hi]`, wrapping to `bits` bits.
+    ///
+    /// `None` stands for a result that overflowed the 128-bit intermediate.
+    fn fit(
+        &self,
+        raw: Option<i128>,
+        (lo, hi): (i128, i128),
+        bits: u8,
+        ty: impl FnOnce() -> Type,
+        value: impl FnOnce() -> String,
+    ) -> Result<i128, FixedError> {
+        let fitted = match raw {
+            Some(raw) if (lo..=hi).contains(&raw) => Some(raw),
+            Some(raw) => match self.overflow {
+                Overflow::Saturate => Some(raw.clamp(lo, hi)),
+                Overflow::Wrap => Some(wrap(raw, bits, lo < 0)),
+                Overflow::Trap | Overflow::Proven => None,
+            },
+            None => None,
+        };
+        fitted.ok_or_else(|| FixedError::Overflow {
+            value: value(),
+            ty: ty(),
+        })
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Overflow::Wrap => "wrap",
+            Overflow::Saturate => "saturate",
+            Overflow::Trap => "trap",
+            Overflow::Proven => "proven",
+        })
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = FixedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "wrap" => Ok(Overflow::Wrap),
+            "saturate" => Ok(Overflow::Saturate),
+            "trap" => Ok(Overflow::Trap),
+            "proven" => Ok(Overflow::Proven),
+            other => Err(FixedError::UnknownMode {
+                annotation: OVERFLOW_ANNOTATION,
+                value: other.to_string(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rounding::TowardZero => "toward_zero",
+            Rounding::Floor => "floor",
+            Rounding::Ceiling => "ceiling",
+            Rounding::NearestEven => "nearest_even",
+            Rounding::NearestAway => "nearest_away",
+        })
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = FixedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "toward_zero" => Ok(Rounding::TowardZero),
+            "floor" => Ok(Rounding::Floor),
+            "ceiling" => Ok(Rounding::Ceiling),
+            "nearest_even" => Ok(Rounding::NearestEven),
+            "nearest_away" => Ok(Rounding::NearestAway),
+            other => Err(FixedError::UnknownMode {
+                annotation: ROUNDING_ANNOTATION,
+                value: other.to_string(),
+            }),
+        }
+    }
+}
+
+/// The raw range of a `total_bits`-bit fixed-point format.
+pub fn raw_range(total_bits: u8) -> (i128, i128) {
+    int_range(total_bits, Signedness::Signed)
+}
+
+/// The range of an integer type, capped to what an `i128` holds.
+pub fn int_range(width: u8, signedness: Signedness) -> (i128, i128) {
+    match signedness {
+        Signedness::Signed if width >= 128 => (i128::MIN, i128::MAX),
+        Signedness::Signed => (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1),
+        Signedness::Unsigned if width >= 127 => (0, i128::MAX),
+        Signedness::Unsigned => (0, (1i128 << width) - 1),
+    }
+}
+
+/// Keep the low `bits` bits of `raw`, as a signed or unsigned integer.
+fn wrap(raw: i128, bits: u8, signed: bool) -> i128 {
+    let signedness = if signed {
+        Signedness::Signed
+    } else {
+        Signedness::Unsigned
+    };
+    let v = IntValue::new(raw, bits, signedness);
+    // Unsigned 128-bit values above i128::MAX keep their bit pattern.
+    v.to_i128().unwrap_or(v.bits() as i128)
+}
+
+/// `n / d` rounded as `rounding` says, or `None` if `d` is zero or the
+/// quotient does not fit in an `i128`.
+pub fn round_div(n: i128, d: i128, rounding: Rounding) -> Option<i128> {
+    let q = n.checked_div(d)?;
+    let r = n % d;
+    if r == 0 {
+        return Some(q);
+    }
+    // The exact quotient lies strictly between q and q + step.
+    let step = if (r < 0) != (d < 0) { -1 } else { 1 };
+    let away = match rounding {
+        Rounding::TowardZero => false,
+        Rounding::Floor => step < 0,
+        Rounding::Ceiling => step > 0,
+        Rounding::NearestEven | Rounding::NearestAway => {
+            let (twice, divisor) = (r.unsigned_abs() * 2, d.unsigned_abs());
+            twice > divisor
+                || (twice == divisor && (rounding == Rounding::NearestAway || q.rem_euclid(2) == 1))
+        }
+    };
+    if away {
+        q.checked_add(step)
+    } else {
+        Some(q)
+    }
+}
+
+/// Round a float to an integral value as `rounding` says.
+pub fn round_f64(x: f64, rounding: Rounding) -> f64 {
+    match rounding {
+        Rounding::TowardZero => x.trunc(),
+        Rounding::Floor => x.floor(),
+        Rounding::Ceiling => x.ceil(),
+        Rounding::NearestEven => x.round_ties_even(),
+        Rounding::NearestAway => x.round(),
+    }
+}
+
+fn pow2(n: u8) -> Option<i128> {
+    (n < 127).then(|| 1i128 << n)
+}
+
+/// Apply `op` to two values of the same fixed-point format.
+pub fn arithmetic(
+    op: ArithmeticOp,
+    x: &FixedValue,
+    y: &FixedValue,
+    semantics: FixedSemantics,
+) -> Result<FixedValue, FixedError> {
+    let (total, frac) = (x.total_bits(), x.frac_bits());
+    let (a, b) = (x.raw(), y.raw());
+    let exact = match op {
+        ArithmeticOp::Add => a.checked_add(b),
+        ArithmeticOp::Sub => a.checked_sub(b),
+        ArithmeticOp::Mul => a
+            .checked_mul(b)
+            .zip(pow2(frac))
+            .and_then(|(p, scale)| round_div(p, scale, semantics.rounding)),
+        ArithmeticOp::Div => {
+            if b == 0 {
+                return Err(FixedError::DivisionByZero);
+            }
+            pow2(frac)
+                .and_then(|scale| a.checked_mul(scale))
+                .and_then(|n| round_div(n, b, semantics.rounding))
+        }
+        ArithmeticOp::Mod => {
+            if b == 0 {
+                return Err(FixedError::DivisionByZero);
+            }
+            Some(a % b)
+        }
+        ArithmeticOp::Pow => return Err(FixedError::Unsupported(op)),
+    };
+    let raw = semantics.fit(
+        exact,
+        raw_range(total),
+        total,
+        || x.ty(),
+        || format!("{} {op:?} {}", x.to_f64(), y.to_f64()),
+    )?;
+    Ok(FixedValue::from_raw(raw, total, frac))
+}
+
+/// Convert between fixed-point formats.
+pub fn rescale(
+    x: &FixedValue,
+    total_bits: u8,
+    frac_bits: u8,
+    semantics: FixedSemantics,
+) -> Result<FixedValue, FixedError> {
+    let exact = if frac_bits >= x.frac_bits() {
+        pow2(frac_bits - x.frac_bits()).and_then(|scale| x.raw().checked_mul(scale))
+    } else {
+        pow2(x.frac_bits() - frac_bits)
+            .and_then(|scale| round_div(x.raw(), scale, semantics.rounding))
+    };
+    let raw = semantics.fit(
+        exact,
+        raw_range(total_bits),
+        total_bits,
+        || fixed_type(total_bits, frac_bits),
+        || x.to_f64().to_string(),
+    )?;
+    Ok(FixedValue::from_raw(raw, total_bits, frac_bits))
+}
+
+/// Convert an integer to a fixed-point format.
+pub fn from_int(
+    x: &IntValue,
+    total_bits: u8,
+    frac_bits: u8,
+    semantics: FixedSemantics,
+) -> Result<FixedValue, FixedError> {
+    let exact = x
+        .to_i128()
+        .zip(pow2(frac_bits))
+        .and_then(|(v, scale)| v.checked_mul(scale));
+    let raw = semantics.fit(
+        exact,
+        raw_range(total_bits),
+        total_bits,
+        || fixed_type(total_bits, frac_bits),
+        || x.to_string(),
+    )?;
+    Ok(FixedValue::from_raw(raw, total_bits, frac_bits))
+}
+
+/// Convert a fixed-point value to an integer, rounding away the fraction.
+pub fn to_int(
+    x: &FixedValue,
+    width: u8,
+    signedness: Signedness,
+    semantics: FixedSemantics,
+) -> Result<IntValue, FixedError> {
+    let exact = match pow2(x.frac_bits()) {
+        Some(scale) => round_div(x.raw(), scale, semantics.rounding),
+        // Only Fixed<128, 127> gets here: every value lies in [-1, 1).
+        None => Some(round_f64(x.to_f64(), semantics.rounding) as i128),
+    };
+    let v = semantics.fit(
+        exact,
+        int_range(width, signedness),
+        width,
+        || Type::Int { width, signedness },
+        || x.to_f64().to_string(),
+    )?;
+    Ok(IntValue::new(v, width, signedness))
+}
+
+/// Convert a float to a fixed-point format. Under `Saturate`, NaN becomes
+/// zero and infinities clamp.
+pub fn from_f64(
+    x: f64,
+    total_bits: u8,
+    frac_bits: u8,
+    semantics: FixedSemantics,
+) -> Result<FixedValue, FixedError> {
+    let scaled = round_f64(x * 2f64.powi(frac_bits as i32), semantics.rounding);
+    let (lo, hi) = raw_range(total_bits);
+    let exact = if scaled.is_nan() {
+        (semantics.overflow == Overflow::Saturate).then_some(0)
+    } else if scaled.abs() >= 2f64.powi(127) {
+        // Beyond any intermediate: only saturation has an answer.
+        (semantics.overflow == Overflow::Saturate).then_some(if scaled < 0.0 { lo } else { hi })
+    } else {
+        Some(scaled as i128)
+    };
+    let raw = semantics.fit(
+        exact,
+        (lo, hi),
+        total_bits,
+        || fixed_type(total_bits, frac_bits),
+        || x.to_string(),
+    )?;
+    Ok(FixedValue::from_raw(raw, total_bits, frac_bits))
+}
+
+fn fixed_type(total_bits: u8, frac_bits: u8) -> Type {
+    Type::Fixed {
+        total_bits,
+        frac_bits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(x: f64) -> FixedValue {
+        FixedValue::from_f64(x, 16, 8).unwrap()
+    }
+
+    fn sem(overflow: Overflow, rounding: Rounding) -> FixedSemantics {
+        FixedSemantics::new(overflow, rounding)
+    }
+
+    #[test]
+    fn round_div_modes() {
+        let cases = [
+            (Rounding::TowardZero, [2, -2, 2, -2]),
+            (Rounding::Floor, [2, -3, 2, -3]),
+            (Rounding::Ceiling, [3, -2, 3, -2]),
+            (Rounding::NearestEven, [2, -2, 3, -3]),
+            (Rounding::NearestAway, [3, -3, 3, -3]),
+        ];
+        for (mode, [a, b, c, d]) in cases {
+            // 5/2 and -5/2 are ties; 11/4 and -11/4 are not
+            assert_eq!(round_div(5, 2, mode), Some(a), "{mode}");
+            assert_eq!(round_div(-5, 2, mode), Some(b), "{mode}");
+            assert_eq!(round_div(11, 4, mode), Some(c), "{mode}");
+            assert_eq!(round_div(-11, 4, mode), Some(d), "{mode}");
+        }
+        assert_eq!(round_div(1, 0, Rounding::Floor), None);
+    }
+
+    #[test]
+    fn mul_rescales_and_rounds() {
+        // 1/256 * 1/2 lies halfway between 0 and 1/256
+        let tiny = FixedValue::from_raw(1, 16, 8);
+        let half = q(0.5);
+        let down = arithmetic(ArithmeticOp::Mul, &tiny, &half, FixedSemantics::default());
+        assert_eq!(down.unwrap().raw(), 0);
+        let up = arithmetic(
+            ArithmeticOp::Mul,
+            &tiny,
+            &half,
+            sem(Overflow::Wrap, Rounding::NearestAway),
+        );
+        assert_eq!(up.unwrap().raw(), 1);
+        let p = arithmetic(ArithmeticOp::Mul, &q(1.5), &q(-2.25), Default::default()).unwrap();
+        assert_eq!(p.to_f64(), -3.375);
+    }
+
+    #[test]
+    fn overflow_modes() {
+        let big = q(100.0);
+        let wrapped = arithmetic(ArithmeticOp::Add, &big, &big, Default::default()).unwrap();
+        assert_eq!(wrapped.to_f64(), 200.0 - 256.0);
+        let saturated = arithmetic(
+            ArithmeticOp::Add,
+            &big,
+            &big,
+            sem(Overflow::Saturate, Rounding::TowardZero),
+        )
+        .unwrap();
+        assert_eq!(saturated.raw(), i16::MAX as i128);
+        assert!(matches!(
+            arithmetic(
+                ArithmeticOp::Mul,
+                &big,
+                &big,
+                sem(Overflow::Trap, Rounding::TowardZero)
+            ),
+            Err(FixedError::Overflow { .. })
+        ));
+        assert_eq!(
+            arithmetic(ArithmeticOp::Div, &big, &q(0.0), Default::default()),
+            Err(FixedError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        let trap = sem(Overflow::Trap, Rounding::TowardZero);
+        let x = q(-2.75);
+        assert_eq!(
+            to_int(&x, 32, Signedness::Signed, trap).unwrap().to_i128(),
+            Some(-2)
+        );
+        let floor = sem(Overflow::Trap, Rounding::Floor);
+        assert_eq!(
+            to_int(&x, 32, Signedness::Signed, floor).unwrap().to_i128(),
+            Some(-3)
+        );
+        assert!(to_int(&x, 8, Signedness::Unsigned, trap).is_err());
+        let sat = sem(Overflow::Saturate, Rounding::TowardZero);
+        assert_eq!(
+            to_int(&x, 8, Signedness::Unsigned, sat).unwrap().to_i128(),
+            Some(0)
+        );
+
+        // Q15 from float: saturates at just under 1.0
+        assert_eq!(from_f64(1.0, 16, 15, sat).unwrap().raw(), i16::MAX as i128);
+        assert_eq!(from_f64(f64::NAN, 16, 15, sat).unwrap().raw(), 0);
+        assert!(from_f64(1.0, 16, 15, trap).is_err());
+
+        // Q8.8 to Q1.15 and back
+        let narrow = rescale(&q(0.5), 16, 15, trap).unwrap();
+        assert_eq!(narrow.raw(), 1 << 14);
+        assert_eq!(rescale(&narrow, 16, 8, trap).unwrap(), q(0.5));
+
+        let three = IntValue::new(3, 32, Signedness::Signed);
+        assert_eq!(from_int(&three, 16, 8, trap).unwrap(), q(3.0));
+        assert!(from_int(&three, 16, 15, trap).is_err());
+    }
+
+    #[test]
+    fn semantics_from_annotations() {
+        let mut node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Mul));
+        assert_eq!(
+            FixedSemantics::of_node(&node).unwrap(),
+            FixedSemantics::default()
+        );
+        node.annotations
+            .insert(OVERFLOW_ANNOTATION.into(), "saturate".into());
+        node.annotations
+            .insert(ROUNDING_ANNOTATION.into(), "nearest_even".into());
+        assert_eq!(
+            FixedSemantics::of_node(&node).unwrap(),
+            sem(Overflow::Saturate, Rounding::NearestEven)
+        );
+        node.annotations
+            .insert(ROUNDING_ANNOTATION.into(), "banker".into());
+        assert!(FixedSemantics::of_node(&node).is_err());
+
+        let conversion = Node::new(NodeKind::Conversion);
+        assert_eq!(
+            FixedSemantics::of_node(&conversion).unwrap().overflow,
+            Overflow::Trap
+        );
+    }
+}
@@ -64,6 +64,8 @@ pub enum Constraint {
     MaxMemory(usize),
     /// Maximum energy budget in microjoules.
     MaxEnergy(u64),
+    /// Maximum number of times an iterative region's body runs.
+    MaxIterations(u64),
     /// User-defined constraint with a name and description.
     Custom { name: String, description: String },
 }
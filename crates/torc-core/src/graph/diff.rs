@@ -0,0 +1,1042 @@
+//! Structural graph diffing.
+//!
+//! `Graph::diff` compares two versions of a graph semantically. Nodes are
+//! matched by identity first, then by content (kind, type signature,
+//! contract, value and annotations), so a rebuild that assigns fresh IDs to
+//! unchanged nodes reports no change. Identical nodes are told apart by
+//! their already-matched neighbours. Edges are matched by identity, then by
+//! their endpoints under the node matching; regions by identity, then by
+//! kind and children.
+//!
+//! Provenance, proof status and proof witnesses are not compared.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::constraints::{BandwidthConstraint, Lifetime};
+use super::edge::{EdgeId, PortRef};
+use super::node::{Node, NodeId, NodeKind};
+use super::region::{Region, RegionId, RegionKind};
+use super::Graph;
+use crate::contract::Contract;
+use crate::hash::{content_hash, ContentHash};
+use crate::types::{Effect, Predicate, Type, TypeSignature};
+use crate::value::Value;
+
+/// How a contract clause changed.
+///
+/// A clause is strengthened when it admits fewer executions: more pre- or
+/// postconditions, fewer effects, or tighter resource bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strength {
+    Strengthened,
+    Weakened,
+    /// Neither stronger nor weaker than before.
+    Changed,
+}
+
+impl Strength {
+    fn inverted(self) -> Self {
+        match self {
+            Strength::Strengthened => Strength::Weakened,
+            Strength::Weakened => Strength::Strengthened,
+            Strength::Changed => Strength::Changed,
+        }
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strength::Strengthened => write!(f, "strengthened"),
+            Strength::Weakened => write!(f, "weakened"),
+            Strength::Changed => write!(f, "changed"),
+        }
+    }
+}
+
+/// A change to a node's contract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContractChange {
+    Added,
+    Removed,
+    Preconditions(Strength),
+    Postconditions(Strength),
+    Effects(Strength),
+    ResourceBounds(Strength),
+    /// Failure modes or the default recovery strategy changed.
+    FailureModes,
+}
+
+impl fmt::Display for ContractChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContractChange::Added => write!(f, "contract added"),
+            ContractChange::Removed => write!(f, "contract removed"),
+            ContractChange::Preconditions(s) => write!(f, "preconditions {s}"),
+            ContractChange::Postconditions(s) => write!(f, "postconditions {s}"),
+            ContractChange::Effects(s) => write!(f, "effects {s}"),
+            ContractChange::ResourceBounds(s) => write!(f, "resource bounds {s}"),
+            ContractChange::FailureModes => write!(f, "failure modes changed"),
+        }
+    }
+}
+
+/// A change to a matched node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NodeChange {
+    Kind {
+        old: NodeKind,
+        new: NodeKind,
+    },
+    TypeSignature {
+        old: Option<TypeSignature>,
+        new: Option<TypeSignature>,
+    },
+    Contract(ContractChange),
+    Value {
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    Annotation {
+        key: String,
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+impl fmt::Display for NodeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeChange::Kind { old, new } => write!(f, "kind {old} -> {new}"),
+            NodeChange::TypeSignature { .. } => write!(f, "type signature changed"),
+            NodeChange::Contract(change) => write!(f, "{change}"),
+            NodeChange::Value { old, new } => {
+                let show = |v: &Option<Value>| v.as_ref().map_or("none".into(), |v| v.to_string());
+                write!(f, "value {} -> {}", show(old), show(new))
+            }
+            NodeChange::Annotation { key, .. } => write!(f, "annotation {key} changed"),
+        }
+    }
+}
+
+/// A node present in both graphs whose content differs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifiedNode {
+    pub old: NodeId,
+    pub new: NodeId,
+    pub changes: Vec<NodeChange>,
+}
+
+/// An edge whose endpoints moved: matched by identity, or by feeding the
+/// same input port from a different source.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewiredEdge {
+    pub old: EdgeId,
+    pub new: EdgeId,
+    /// Endpoints in the old graph.
+    pub old_source: PortRef,
+    pub old_target: PortRef,
+    /// Endpoints in the new graph.
+    pub new_source: PortRef,
+    pub new_target: PortRef,
+}
+
+/// A change to a matched edge's attributes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EdgeChange {
+    DataType {
+        old: Option<Type>,
+        new: Option<Type>,
+    },
+    Lifetime {
+        old: Lifetime,
+        new: Lifetime,
+    },
+    Bandwidth {
+        old: Option<BandwidthConstraint>,
+        new: Option<BandwidthConstraint>,
+    },
+}
+
+/// An edge present in both graphs whose attributes differ.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifiedEdge {
+    pub old: EdgeId,
+    pub new: EdgeId,
+    pub changes: Vec<EdgeChange>,
+}
+
+/// A change to a matched region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RegionChange {
+    Kind {
+        old: RegionKind,
+        new: RegionKind,
+    },
+    /// Nodes that joined (new IDs) or left (old IDs) the region.
+    Children {
+        added: Vec<NodeId>,
+        removed: Vec<NodeId>,
+    },
+    /// The region moved under a different parent (old and new IDs).
+    Parent {
+        old: Option<RegionId>,
+        new: Option<RegionId>,
+    },
+    Constraints,
+    Interfaces,
+}
+
+impl fmt::Display for RegionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionChange::Kind { old, new } => write!(f, "kind {old} -> {new}"),
+            RegionChange::Children { added, removed } => {
+                write!(f, "{} node(s) joined, {} left", added.len(), removed.len())
+            }
+            RegionChange::Parent { .. } => write!(f, "reparented"),
+            RegionChange::Constraints => write!(f, "constraints changed"),
+            RegionChange::Interfaces => write!(f, "interfaces changed"),
+        }
+    }
+}
+
+/// A region present in both graphs whose structure differs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModifiedRegion {
+    pub old: RegionId,
+    pub new: RegionId,
+    pub changes: Vec<RegionChange>,
+}
+
+/// A change to the graph's named type definitions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypeDefChange {
+    Added(String),
+    Removed(String),
+    Changed(String),
+}
+
+/// The semantic difference between two versions of a graph.
+///
+/// Added elements carry new-graph IDs, removed elements old-graph IDs, and
+/// matched elements both. All lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    /// Every matched node, old ID to new ID (including unchanged nodes).
+    pub node_matches: BTreeMap<NodeId, NodeId>,
+    pub added_nodes: Vec<NodeId>,
+    pub removed_nodes: Vec<NodeId>,
+    pub modified_nodes: Vec<ModifiedNode>,
+    pub added_edges: Vec<EdgeId>,
+    pub removed_edges: Vec<EdgeId>,
+    pub rewired_edges: Vec<RewiredEdge>,
+    pub modified_edges: Vec<ModifiedEdge>,
+    pub added_regions: Vec<RegionId>,
+    pub removed_regions: Vec<RegionId>,
+    pub modified_regions: Vec<ModifiedRegion>,
+    pub type_defs: Vec<TypeDefChange>,
+}
+
+impl GraphDiff {
+    /// Whether the two graphs are semantically the same.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.rewired_edges.is_empty()
+            && self.modified_edges.is_empty()
+            && self.added_regions.is_empty()
+            && self.removed_regions.is_empty()
+            && self.modified_regions.is_empty()
+            && self.type_defs.is_empty()
+    }
+
+    /// Contract changes of modified nodes, by new node ID.
+    pub fn contract_changes(&self) -> impl Iterator<Item = (NodeId, &ContractChange)> {
+        self.modified_nodes.iter().flat_map(|m| {
+            m.changes.iter().filter_map(move |c| match c {
+                NodeChange::Contract(change) => Some((m.new, change)),
+                _ => None,
+            })
+        })
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no semantic change");
+        }
+        for id in &self.added_nodes {
+            writeln!(f, "+ node {id}")?;
+        }
+        for id in &self.removed_nodes {
+            writeln!(f, "- node {id}")?;
+        }
+        for m in &self.modified_nodes {
+            for change in &m.changes {
+                writeln!(f, "~ node {}: {change}", m.new)?;
+            }
+        }
+        for id in &self.added_edges {
+            writeln!(f, "+ edge {id}")?;
+        }
+        for id in &self.removed_edges {
+            writeln!(f, "- edge {id}")?;
+        }
+        for r in &self.rewired_edges {
+            writeln!(
+                f,
+                "~ edge {}: {} port {} -> {} port {} now {} port {} -> {} port {}",
+                r.new,
+                r.old_source.0,
+                r.old_source.1,
+                r.old_target.0,
+                r.old_target.1,
+                r.new_source.0,
+                r.new_source.1,
+                r.new_target.0,
+                r.new_target.1,
+            )?;
+        }
+        for m in &self.modified_edges {
+            writeln!(
+                f,
+                "~ edge {}: {} attribute(s) changed",
+                m.new,
+                m.changes.len()
+            )?;
+        }
+        for id in &self.added_regions {
+            writeln!(f, "+ region {id}")?;
+        }
+        for id in &self.removed_regions {
+            writeln!(f, "- region {id}")?;
+        }
+        for m in &self.modified_regions {
+            for change in &m.changes {
+                writeln!(f, "~ region {}: {change}", m.new)?;
+            }
+        }
+        for change in &self.type_defs {
+            match change {
+                TypeDefChange::Added(name) => writeln!(f, "+ type {name}")?,
+                TypeDefChange::Removed(name) => writeln!(f, "- type {name}")?,
+                TypeDefChange::Changed(name) => writeln!(f, "~ type {name}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Compute the semantic difference from `self` (old) to `new`.
+    pub fn diff(&self, new: &Graph) -> GraphDiff {
+        let node_matches = match_nodes(self, new);
+        let matched_new: HashSet<NodeId> = node_matches.values().copied().collect();
+
+        let mut diff = GraphDiff {
+            added_nodes: sorted(
+                new.nodes()
+                    .map(|n| n.id)
+                    .filter(|id| !matched_new.contains(id)),
+            ),
+            removed_nodes: sorted(
+                self.nodes()
+                    .map(|n| n.id)
+                    .filter(|id| !node_matches.contains_key(id)),
+            ),
+            ..GraphDiff::default()
+        };
+        for (old_id, new_id) in &node_matches {
+            let (Some(old), Some(new)) = (self.get_node(old_id), new.get_node(new_id)) else {
+                continue;
+            };
+            let changes = node_changes(old, new);
+            if !changes.is_empty() {
+                diff.modified_nodes.push(ModifiedNode {
+                    old: *old_id,
+                    new: *new_id,
+                    changes,
+                });
+            }
+        }
+        diff.node_matches = node_matches;
+
+        diff_edges(self, new, &mut diff);
+        diff_regions(self, new, &mut diff);
+
+        let old_types = self.types();
+        let new_types = new.types();
+        for (name, def) in old_types.iter() {
+            match new_types.get(name) {
+                None => diff.type_defs.push(TypeDefChange::Removed(name.clone())),
+                Some(other) if other != def => {
+                    diff.type_defs.push(TypeDefChange::Changed(name.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, _) in new_types.iter() {
+            if old_types.get(name).is_none() {
+                diff.type_defs.push(TypeDefChange::Added(name.clone()));
+            }
+        }
+
+        diff
+    }
+}
+
+fn sorted<T: Ord>(items: impl Iterator<Item = T>) -> Vec<T> {
+    let mut items: Vec<T> = items.collect();
+    items.sort();
+    items
+}
+
+// === Node matching ===
+
+/// Hash of the compared content of a node, excluding its ID, provenance and
+/// proof state.
+fn node_content_hash(node: &Node) -> ContentHash {
+    let contract = node.contract.as_ref().map(comparable_contract);
+    let annotations: BTreeMap<&String, &String> = node.annotations.iter().collect();
+    content_hash(&(
+        &node.kind,
+        &node.type_signature,
+        &contract,
+        &node.value,
+        &annotations,
+    ))
+}
+
+fn comparable_contract(contract: &Contract) -> Contract {
+    Contract {
+        proof_status: crate::contract::ProofStatus::Pending,
+        proof_witness: None,
+        ..contract.clone()
+    }
+}
+
+/// A node's neighbours as (is output, own port, matched neighbour port)
+/// triples, in new-graph IDs.
+type NeighbourContext = Vec<(bool, usize, Option<PortRef>)>;
+
+fn neighbour_context(
+    graph: &Graph,
+    id: NodeId,
+    to_new: &dyn Fn(NodeId) -> Option<NodeId>,
+) -> NeighbourContext {
+    let edges = |ids: &[EdgeId]| -> Vec<&super::edge::Edge> {
+        ids.iter().filter_map(|e| graph.get_edge(e)).collect()
+    };
+    let inputs = edges(graph.incoming_edges(&id)).into_iter().map(|e| {
+        (
+            false,
+            e.target.1,
+            to_new(e.source.0).map(|n| (n, e.source.1)),
+        )
+    });
+    let outputs = edges(graph.outgoing_edges(&id)).into_iter().map(|e| {
+        (
+            true,
+            e.source.1,
+            to_new(e.target.0).map(|n| (n, e.target.1)),
+        )
+    });
+    let mut context: NeighbourContext = inputs.chain(outputs).collect();
+    context.sort();
+    context
+}
+
+/// Match old nodes to new nodes: by ID, then by content and matched neighbours
+/// while that is unambiguous, then by content alone in ID order.
+fn match_nodes(old: &Graph, new: &Graph) -> BTreeMap<NodeId, NodeId> {
+    let mut matches: BTreeMap<NodeId, NodeId> = old
+        .nodes()
+        .filter(|n| new.get_node(&n.id).is_some())
+        .map(|n| (n.id, n.id))
+        .collect();
+    let old_hashes: HashMap<NodeId, ContentHash> = old
+        .nodes()
+        .filter(|n| !matches.contains_key(&n.id))
+        .map(|n| (n.id, node_content_hash(n)))
+        .collect();
+    let new_hashes: HashMap<NodeId, ContentHash> = new
+        .nodes()
+        .filter(|n| old.get_node(&n.id).is_none())
+        .map(|n| (n.id, node_content_hash(n)))
+        .collect();
+
+    loop {
+        let matched_new: HashSet<NodeId> = matches.values().copied().collect();
+        let mut old_groups: HashMap<(ContentHash, NeighbourContext), Vec<NodeId>> = HashMap::new();
+        for (id, hash) in &old_hashes {
+            if !matches.contains_key(id) {
+                let context = neighbour_context(old, *id, &|n| matches.get(&n).copied());
+                old_groups.entry((*hash, context)).or_default().push(*id);
+            }
+        }
+        let mut new_groups: HashMap<(ContentHash, NeighbourContext), Vec<NodeId>> = HashMap::new();
+        for (id, hash) in &new_hashes {
+            if !matched_new.contains(id) {
+                let context =
+                    neighbour_context(new, *id, &|n| matched_new.contains(&n).then_some(n));
+                new_groups.entry((*hash, context)).or_default().push(*id);
+            }
+        }
+
+        let mut progress = false;
+        for (key, olds) in &old_groups {
+            if let (&[old_id], Some(&[new_id])) =
+                (olds.as_slice(), new_groups.get(key).map(Vec::as_slice))
+            {
+                matches.insert(old_id, new_id);
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+
+    // Whatever is left is ambiguous: pair identical nodes in ID order
+    let matched_new: HashSet<NodeId> = matches.values().copied().collect();
+    let mut old_by_hash: BTreeMap<ContentHash, Vec<NodeId>> = BTreeMap::new();
+    for (id, hash) in &old_hashes {
+        if !matches.contains_key(id) {
+            old_by_hash.entry(*hash).or_default().push(*id);
+        }
+    }
+    let mut new_by_hash: HashMap<ContentHash, Vec<NodeId>> = HashMap::new();
+    for (id, hash) in &new_hashes {
+        if !matched_new.contains(id) {
+            new_by_hash.entry(*hash).or_default().push(*id);
+        }
+    }
+    for (hash, mut olds) in old_by_hash {
+        let Some(news) = new_by_hash.get_mut(&hash) else {
+            continue;
+        };
+        olds.sort();
+        news.sort();
+        for (old_id, new_id) in olds.into_iter().zip(news.iter()) {
+            matches.insert(old_id, *new_id);
+        }
+    }
+    matches
+}
+
+// === Node comparison ===
+
+fn node_changes(old: &Node, new: &Node) -> Vec<NodeChange> {
+    let mut changes = Vec::new();
+    if old.kind != new.kind {
+        changes.push(NodeChange::Kind {
+            old: old.kind.clone(),
+            new: new.kind.clone(),
+        });
+    }
+    if old.type_signature != new.type_signature {
+        changes.push(NodeChange::TypeSignature {
+            old: old.type_signature.clone(),
+            new: new.type_signature.clone(),
+        });
+    }
+    changes.extend(
+        contract_changes(old.contract.as_ref(), new.contract.as_ref())
+            .into_iter()
+            .map(NodeChange::Contract),
+    );
+    if old.value != new.value {
+        changes.push(NodeChange::Value {
+            old: old.value.clone(),
+            new: new.value.clone(),
+        });
+    }
+    let keys: std::collections::BTreeSet<&String> = old
+        .annotations
+        .keys()
+        .chain(new.annotations.keys())
+        .collect();
+    for key in keys {
+        let (before, after) = (old.annotations.get(key), new.annotations.get(key));
+        if before != after {
+            changes.push(NodeChange::Annotation {
+                key: key.clone(),
+                old: before.cloned(),
+                new: after.cloned(),
+            });
+        }
+    }
+    changes
+}
+
+/// Classify how a contract changed, clause by clause.
+pub fn contract_changes(old: Option<&Contract>, new: Option<&Contract>) -> Vec<ContractChange> {
+    let (old, new) = match (old, new) {
+        (None, None) => return Vec::new(),
+        (None, Some(_)) => return vec![ContractChange::Added],
+        (Some(_), None) => return vec![ContractChange::Removed],
+        (Some(old), Some(new)) => (old, new),
+    };
+
+    let mut changes = Vec::new();
+    if let Some(s) = set_strength(
+        &conjuncts(&old.preconditions),
+        &conjuncts(&new.preconditions),
+    ) {
+        changes.push(ContractChange::Preconditions(s));
+    }
+    if let Some(s) = set_strength(
+        &conjuncts(&old.postconditions),
+        &conjuncts(&new.postconditions),
+    ) {
+        changes.push(ContractChange::Postconditions(s));
+    }
+    let effects = |c: &Contract| -> Vec<Effect> {
+        c.effects
+            .effects
+            .iter()
+            .filter(|e| **e != Effect::Pure)
+            .cloned()
+            .collect()
+    };
+    // More effects admit more executions
+    if let Some(s) = set_strength(&effects(old), &effects(new)) {
+        changes.push(ContractChange::Effects(s.inverted()));
+    }
+    let bounds = resource_bounds(old)
+        .into_iter()
+        .zip(resource_bounds(new))
+        .filter_map(|(before, after)| bound_strength(before, after));
+    if let Some(s) = combine(bounds) {
+        changes.push(ContractChange::ResourceBounds(s));
+    }
+    if old.failure_modes != new.failure_modes || old.recovery_strategy != new.recovery_strategy {
+        changes.push(ContractChange::FailureModes);
+    }
+    changes
+}
+
+/// Canonical conjuncts of a list of predicates, with trivial ones dropped.
+pub(super) fn conjuncts(predicates: &[Predicate]) -> Vec<Predicate> {
+    fn flatten(p: Predicate, out: &mut Vec<Predicate>) {
+        match p {
+            Predicate::And(a, b) => {
+                flatten(*a, out);
+                flatten(*b, out);
+            }
+            Predicate::BoolLit(true) => {}
+            other => {
+                if !out.contains(&other) {
+                    out.push(other);
+                }
+            }
+        }
+    }
+    let mut out = Vec::new();
+    for p in predicates {
+        flatten(p.canonicalize(), &mut out);
+    }
+    out
+}
+
+/// `Strengthened` if `new` strictly contains `old`, `Weakened` if strictly
+/// contained in it, `Changed` otherwise, and `None` if they are equal.
+fn set_strength<T: PartialEq>(old: &[T], new: &[T]) -> Option<Strength> {
+    let grew = old.iter().all(|x| new.contains(x));
+    let shrank = new.iter().all(|x| old.contains(x));
+    match (grew, shrank) {
+        (true, true) => None,
+        (true, false) => Some(Strength::Strengthened),
+        (false, true) => Some(Strength::Weakened),
+        (false, false) => Some(Strength::Changed),
+    }
+}
+
+/// WCET, peak and allocated memory, stack and energy bounds (`None` is
+/// unbounded).
+fn resource_bounds(c: &Contract) -> [Option<u64>; 5] {
+    [
+        c.time_bound.as_ref().and_then(|t| t.wcet_ns),
+        c.memory_bound.as_ref().and_then(|m| m.peak_bytes),
+        c.memory_bound.as_ref().and_then(|m| m.allocated_bytes),
+        c.stack_bound.as_ref().map(|s| s.max_bytes),
+        c.energy_bound.as_ref().map(|e| e.max_uj),
+    ]
+}
+
+fn bound_strength(old: Option<u64>, new: Option<u64>) -> Option<Strength> {
+    match (old, new) {
+        _ if old == new => None,
+        (None, Some(_)) => Some(Strength::Strengthened),
+        (Some(_), None) => Some(Strength::Weakened),
+        (Some(a), Some(b)) if b < a => Some(Strength::Strengthened),
+        _ => Some(Strength::Weakened),
+    }
+}
+
+fn combine(strengths: impl Iterator<Item = Strength>) -> Option<Strength> {
+    strengths.reduce(|a, b| if a == b { a } else { Strength::Changed })
+}
+
+// === Edges and regions ===
+
+fn diff_edges(old: &Graph, new: &Graph, diff: &mut GraphDiff) {
+    let map_port = |(node, port): PortRef| diff.node_matches.get(&node).map(|n| (*n, port));
+
+    let mut old_edges: Vec<_> = old.edges().collect();
+    old_edges.sort_by_key(|e| e.id);
+    let mut unmatched_new: BTreeMap<EdgeId, &super::edge::Edge> =
+        new.edges().map(|e| (e.id, e)).collect();
+    let mut unmatched_old = Vec::new();
+    let mut pairs = Vec::new();
+
+    // By identity
+    for edge in old_edges {
+        match unmatched_new.remove(&edge.id) {
+            Some(other) => pairs.push((edge, other)),
+            None => unmatched_old.push(edge),
+        }
+    }
+    // By endpoints
+    let mut rest = Vec::new();
+    for edge in unmatched_old {
+        let endpoints = (map_port(edge.source), map_port(edge.target));
+        let found = unmatched_new
+            .values()
+            .find(|e| (Some(e.source), Some(e.target)) == endpoints)
+            .map(|e| e.id);
+        match found.and_then(|id| unmatched_new.remove(&id)) {
+            Some(other) => pairs.push((edge, other)),
+            None => rest.push(edge),
+        }
+    }
+    // An input port fed from a different source
+    for edge in rest {
+        let target = map_port(edge.target);
+        let found = unmatched_new
+            .values()
+            .find(|e| Some(e.target) == target)
+            .map(|e| e.id);
+        match found.and_then(|id| unmatched_new.remove(&id)) {
+            Some(other) => pairs.push((edge, other)),
+            None => diff.removed_edges.push(edge.id),
+        }
+    }
+    diff.added_edges = unmatched_new.into_keys().collect();
+
+    for (before, after) in pairs {
+        if (map_port(before.source), map_port(before.target))
+            != (Some(after.source), Some(after.target))
+        {
+            diff.rewired_edges.push(RewiredEdge {
+                old: before.id,
+                new: after.id,
+                old_source: before.source,
+                old_target: before.target,
+                new_source: after.source,
+                new_target: after.target,
+            });
+        }
+        let mut changes = Vec::new();
+        if before.data_type != after.data_type {
+            changes.push(EdgeChange::DataType {
+                old: before.data_type.clone(),
+                new: after.data_type.clone(),
+            });
+        }
+        if before.lifetime != after.lifetime {
+            changes.push(EdgeChange::Lifetime {
+                old: before.lifetime.clone(),
+                new: after.lifetime.clone(),
+            });
+        }
+        if before.bandwidth != after.bandwidth {
+            changes.push(EdgeChange::Bandwidth {
+                old: before.bandwidth.clone(),
+                new: after.bandwidth.clone(),
+            });
+        }
+        if !changes.is_empty() {
+            diff.modified_edges.push(ModifiedEdge {
+                old: before.id,
+                new: after.id,
+                changes,
+            });
+        }
+    }
+    diff.removed_edges.sort();
+    diff.rewired_edges.sort_by_key(|r| r.new);
+    diff.modified_edges.sort_by_key(|m| m.new);
+}
+
+fn diff_regions(old: &Graph, new: &Graph, diff: &mut GraphDiff) {
+    let map_node = |id: &NodeId| diff.node_matches.get(id).copied();
+    let shape = |r: &Region, map: &dyn Fn(&NodeId) -> Option<NodeId>| {
+        let children: Vec<Option<NodeId>> = sorted(r.children.iter().map(map));
+        (r.kind, children)
+    };
+
+    let mut old_regions: Vec<&Region> = old.regions().collect();
+    old_regions.sort_by_key(|r| r.id);
+    let mut unmatched_new: BTreeMap<RegionId, &Region> = new.regions().map(|r| (r.id, r)).collect();
+    let mut region_matches: BTreeMap<RegionId, RegionId> = BTreeMap::new();
+    let mut rest = Vec::new();
+    for region in old_regions {
+        if unmatched_new.remove(&region.id).is_some() {
+            region_matches.insert(region.id, region.id);
+        } else {
+            rest.push(region);
+        }
+    }
+    for region in rest {
+        let wanted = shape(region, &map_node);
+        let found = unmatched_new
+            .values()
+            .find(|r| shape(r, &|id| Some(*id)) == wanted)
+            .map(|r| r.id);
+        match found {
+            Some(id) => {
+                unmatched_new.remove(&id);
+                region_matches.insert(region.id, id);
+            }
+            None => diff.removed_regions.push(region.id),
+        }
+    }
+    diff.added_regions = unmatched_new.into_keys().collect();
+
+    for (old_id, new_id) in &region_matches {
+        let (Some(before), Some(after)) = (old.get_region(old_id), new.get_region(new_id)) else {
+            continue;
+        };
+        let mut changes = Vec::new();
+        if before.kind != after.kind {
+            changes.push(RegionChange::Kind {
+                old: before.kind,
+                new: after.kind,
+            });
+        }
+        let kept: HashSet<NodeId> = before.children.iter().filter_map(map_node).collect();
+        let added = sorted(after.children.iter().copied().filter(|c| !kept.contains(c)));
+        let removed = sorted(
+            before
+                .children
+                .iter()
+                .copied()
+                .filter(|c| map_node(c).is_none_or(|n| !after.children.contains(&n))),
+        );
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(RegionChange::Children { added, removed });
+        }
+        if before.parent.and_then(|p| region_matches.get(&p).copied()) != after.parent {
+            changes.push(RegionChange::Parent {
+                old: before.parent,
+                new: after.parent,
+            });
+        }
+        if before.constraints != after.constraints {
+            changes.push(RegionChange::Constraints);
+        }
+        if before.interfaces != after.interfaces {
+            changes.push(RegionChange::Interfaces);
+        }
+        if !changes.is_empty() {
+            diff.modified_regions.push(ModifiedRegion {
+                old: *old_id,
+                new: *new_id,
+                changes,
+            });
+        }
+    }
+    diff.modified_regions.sort_by_key(|m| m.new);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::EffectSet;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::ArithmeticOp;
+    use crate::types::TypeDef;
+
+    /// `a + b -> c`, with `c` negating the sum.
+    fn sample() -> (Graph, [NodeId; 4]) {
+        let mut g = Graph::new();
+        let a = Node::new(NodeKind::Literal).with_value(Value::i32(1));
+        let b = Node::new(NodeKind::Literal).with_value(Value::i32(2));
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_contract(
+            Contract::with_conditions(vec![], vec![Predicate::positive("output")]),
+        );
+        let neg = Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub));
+        let ids = [a.id, b.id, add.id, neg.id];
+        for n in [a, b, add, neg] {
+            g.add_node(n).unwrap();
+        }
+        g.add_edge(Edge::typed((ids[0], 0), (ids[2], 0), Type::i32()))
+            .unwrap();
+        g.add_edge(Edge::typed((ids[1], 0), (ids[2], 1), Type::i32()))
+            .unwrap();
+        g.add_edge(Edge::typed((ids[2], 0), (ids[3], 0), Type::i32()))
+            .unwrap();
+        (g, ids)
+    }
+
+    #[test]
+    fn rebuild_with_fresh_ids_is_no_change() {
+        let (old, _) = sample();
+        let (new, _) = sample();
+        let diff = old.diff(&new);
+        assert!(diff.is_empty(), "{diff}");
+        assert_eq!(diff.node_matches.len(), 4);
+        assert_eq!(diff.to_string(), "no semantic change\n");
+    }
+
+    #[test]
+    fn identical_nodes_are_told_apart_by_neighbours() {
+        // Two literals differ only in what they feed
+        let build = || {
+            let mut g = Graph::new();
+            let x = Node::new(NodeKind::Literal);
+            let y = Node::new(NodeKind::Literal);
+            let sub = Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub));
+            let ids = (x.id, y.id, sub.id);
+            for n in [x, y, sub] {
+                g.add_node(n).unwrap();
+            }
+            g.add_edge(Edge::new((ids.0, 0), (ids.2, 0))).unwrap();
+            g.add_edge(Edge::new((ids.1, 0), (ids.2, 1))).unwrap();
+            (g, ids)
+        };
+        let (old, _) = build();
+        let (new, _) = build();
+        assert!(old.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn node_edge_and_contract_changes() {
+        let (old, [_, b, add, neg]) = sample();
+        let mut new = old.clone();
+
+        // Strengthen the postcondition, widen the effects
+        let node = new.get_node_mut(&add).unwrap();
+        let contract = node.contract.as_mut().unwrap();
+        contract.add_postcondition(Predicate::in_range("output", 0, 10));
+        contract.effects = EffectSet::from_effects(vec![Effect::Alloc("heap".into())]);
+        node.kind = NodeKind::Arithmetic(ArithmeticOp::Mul);
+
+        // Feed `neg` from `b` instead of the sum, retype it, and add a node
+        let old_edge = new.incoming_edges(&neg)[0];
+        new.remove_edge(old_edge).unwrap();
+        let rewired = new
+            .add_edge(Edge::typed((b, 0), (neg, 0), Type::i64()))
+            .unwrap();
+        let extra = new.add_node(Node::new(NodeKind::Literal)).unwrap();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_nodes, vec![extra]);
+        assert!(diff.removed_nodes.is_empty());
+        assert_eq!(diff.modified_nodes.len(), 1);
+        let changes = &diff.modified_nodes[0].changes;
+        assert!(changes.contains(&NodeChange::Kind {
+            old: NodeKind::Arithmetic(ArithmeticOp::Add),
+            new: NodeKind::Arithmetic(ArithmeticOp::Mul),
+        }));
+        let contract: Vec<_> = diff.contract_changes().map(|(_, c)| c.clone()).collect();
+        assert_eq!(
+            contract,
+            vec![
+                ContractChange::Postconditions(Strength::Strengthened),
+                ContractChange::Effects(Strength::Weakened),
+            ]
+        );
+
+        assert_eq!(diff.rewired_edges.len(), 1);
+        let r = &diff.rewired_edges[0];
+        assert_eq!((r.old_source, r.new_source), ((add, 0), (b, 0)));
+        assert_eq!(r.new, rewired);
+        assert_eq!(diff.modified_edges.len(), 1);
+        assert!(diff.added_edges.is_empty() && diff.removed_edges.is_empty());
+
+        // Dropping the postcondition entirely weakens it
+        let mut weaker = old.clone();
+        weaker
+            .get_node_mut(&add)
+            .unwrap()
+            .contract
+            .as_mut()
+            .unwrap()
+            .postconditions
+            .clear();
+        let contract: Vec<_> = old
+            .diff(&weaker)
+            .contract_changes()
+            .map(|(_, c)| c.clone())
+            .collect();
+        assert_eq!(
+            contract,
+            vec![ContractChange::Postconditions(Strength::Weakened)]
+        );
+    }
+
+    #[test]
+    fn region_restructuring_and_type_defs() {
+        let (mut old, [a, b, add, neg]) = sample();
+        let outer = Region::new(RegionKind::Sequential, vec![a, b]);
+        let inner = Region::new(RegionKind::Parallel, vec![add]);
+        let (outer_id, inner_id) = (outer.id, inner.id);
+        old.add_region(outer).unwrap();
+        old.add_region(inner).unwrap();
+        old.define_type("Sample", TypeDef::alias(Type::i32()));
+
+        let mut new = old.clone();
+        // Move `add` out of `inner`, nest `inner` in `outer`, drop the type
+        new.get_region_mut(&inner_id).unwrap().children = vec![neg];
+        new.set_region_parent(inner_id, outer_id).unwrap();
+        new.get_region_mut(&outer_id).unwrap().kind = RegionKind::Atomic;
+        new.define_type("Sample", TypeDef::alias(Type::i64()));
+        new.define_type("Extra", TypeDef::alias(Type::u8()));
+
+        let diff = old.diff(&new);
+        let inner_changes = &diff
+            .modified_regions
+            .iter()
+            .find(|m| m.new == inner_id)
+            .unwrap()
+            .changes;
+        assert!(inner_changes.contains(&RegionChange::Children {
+            added: vec![neg],
+            removed: vec![add],
+        }));
+        assert!(inner_changes.contains(&RegionChange::Parent {
+            old: None,
+            new: Some(outer_id),
+        }));
+        let outer_changes = &diff
+            .modified_regions
+            .iter()
+            .find(|m| m.new == outer_id)
+            .unwrap()
+            .changes;
+        assert_eq!(
+            outer_changes,
+            &vec![RegionChange::Kind {
+                old: RegionKind::Sequential,
+                new: RegionKind::Atomic,
+            }]
+        );
+        assert_eq!(
+            diff.type_defs,
+            vec![
+                TypeDefChange::Changed("Sample".into()),
+                TypeDefChange::Added("Extra".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! Effect inference.
+//!
+//! A node performs the effects its contract declares plus the effects its
+//! kind implies: `Read`/`Write`/`Syscall` do IO, `Allocate`/`Deallocate`
+//! allocate, `Atomic`/`Fence` are atomic, `FFICall` calls foreign code, a
+//! loop without a termination measure may diverge, and a failure mode that
+//! aborts may panic. A declared effect of the same kind
+//! names the resource (`IO<uart0>`); otherwise it comes from the node's
+//! annotations (`io.device`, `alloc.region`, `ffi.abi`) or a default.
+//!
+//! A loop node (`Iterate`, `Recurse`, `Fixpoint`) also performs the effects
+//! of its body: the nodes on a cycle through it. Regions perform the effects
+//! of their child nodes and nested regions, and the graph the effects of all
+//! its nodes.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem::discriminant;
+
+use super::node::{Node, NodeId, NodeKind};
+use super::region::RegionId;
+use super::{Graph, GraphError};
+use crate::contract::{EffectSet, RecoveryStrategy};
+use crate::types::Effect;
+
+/// Annotation naming the device a `Read`/`Write`/`Syscall` node accesses.
+pub const IO_DEVICE: &str = "io.device";
+/// Annotation naming the memory region an allocation node uses.
+pub const ALLOC_REGION: &str = "alloc.region";
+/// Annotation naming the ABI of an `FFICall` node.
+pub const FFI_ABI: &str = "ffi.abi";
+/// Annotation justifying a node's declared `Diverge` effect.
+pub const DIVERGE_JUSTIFICATION: &str = "diverge.justification";
+
+/// Inferred effects of every node and region, and of the whole graph.
+#[derive(Debug, Clone)]
+pub struct EffectSummary {
+    /// Effects each node performs, including loop bodies.
+    pub nodes: HashMap<NodeId, EffectSet>,
+    /// Effects each region performs, including nested regions.
+    pub regions: HashMap<RegionId, EffectSet>,
+    /// Effects of the graph as a whole.
+    pub graph: EffectSet,
+}
+
+/// The effects a node performs by itself, ignoring loop bodies.
+pub fn intrinsic_effects(node: &Node, graph: &Graph) -> EffectSet {
+    let declared = node
+        .contract
+        .as_ref()
+        .map_or_else(EffectSet::empty, |c| c.effects.clone());
+    let annotation = |key: &str, default: &str| {
+        node.annotations
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| default.to_string())
+    };
+
+    let mut implied = Vec::new();
+    match &node.kind {
+        NodeKind::Read | NodeKind::Write | NodeKind::Syscall => {
+            implied.push(Effect::IO(annotation(IO_DEVICE, "unknown")));
+        }
+        NodeKind::Allocate | NodeKind::Deallocate => {
+            implied.push(Effect::Alloc(annotation(ALLOC_REGION, "heap")));
+        }
+        NodeKind::Atomic(ordering) | NodeKind::Fence(ordering) => {
+            implied.push(Effect::Atomic(format!("{ordering:?}")));
+        }
+        NodeKind::FFICall => implied.push(Effect::FFI(annotation(FFI_ABI, "C"))),
+        kind if is_loop(kind) && !has_measure(node, graph) => implied.push(Effect::Diverge),
+        _ => {}
+    }
+    let aborts = node.contract.as_ref().is_some_and(|c| {
+        c.failure_modes
+            .iter()
+            .any(|f| f.recovery == RecoveryStrategy::Abort)
+    });
+    if aborts {
+        implied.push(Effect::Panic);
+    }
+
+    let mut effects = declared.clone();
+    for effect in implied {
+        let kind = discriminant(&effect);
+        if !declared.effects.iter().any(|d| discriminant(d) == kind) {
+            effects.merge(&EffectSet::from_effects(vec![effect]));
+        }
+    }
+    effects
+}
+
+fn is_loop(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Iterate | NodeKind::Recurse | NodeKind::Fixpoint
+    )
+}
+
+/// Whether a loop node has a termination measure: its contract gives a
+/// `Variant`, or it is an `Iterate` with a bound, which is checked as it
+/// runs, or a `Recurse` with a metric port, which
+/// `termination_obligations` requires to decrease.
+pub fn has_measure(node: &Node, graph: &Graph) -> bool {
+    let connected = |port| graph.input_source((node.id, port)).is_some();
+    node.contract.as_ref().is_some_and(|c| c.variant.is_some())
+        || match node.kind {
+            NodeKind::Iterate => connected(2) || node.annotations.contains_key("bound"),
+            NodeKind::Recurse => connected(3),
+            _ => false,
+        }
+}
+
+impl Graph {
+    /// The body of a loop node: every other node on a cycle through it.
+    ///
+    /// Empty for nodes that are not on a cycle.
+    pub fn loop_body(&self, node: NodeId) -> Vec<NodeId> {
+        let reach = |next: &dyn Fn(NodeId) -> Vec<NodeId>| {
+            let mut seen = HashSet::new();
+            let mut queue: VecDeque<NodeId> = next(node).into();
+            while let Some(id) = queue.pop_front() {
+                if seen.insert(id) {
+                    queue.extend(next(id));
+                }
+            }
+            seen
+        };
+        let forward = reach(&|id| {
+            self.outgoing_edges(&id)
+                .iter()
+                .filter_map(|e| self.get_edge(e))
+                .map(|e| e.target.0)
+                .collect()
+        });
+        let backward = reach(&|id| {
+            self.incoming_edges(&id)
+                .iter()
+                .filter_map(|e| self.get_edge(e))
+                .map(|e| e.source.0)
+                .collect()
+        });
+        let mut body: Vec<NodeId> = forward
+            .intersection(&backward)
+            .copied()
+            .filter(|&id| id != node)
+            .collect();
+        body.sort();
+        body
+    }
+
+    /// The effects a node performs: its own, plus its body's if it is a loop.
+    pub fn node_effects(&self, id: &NodeId) -> EffectSet {
+        let Some(node) = self.get_node(id) else {
+            return EffectSet::empty();
+        };
+        let mut effects = intrinsic_effects(node, self);
+        if is_loop(&node.kind) {
+            for member in self.loop_body(*id) {
+                if let Some(n) = self.get_node(&member) {
+                    effects.merge(&intrinsic_effects(n, self));
+                }
+            }
+        }
+        effects
+    }
+
+    /// Infer effects bottom-up for every node and region, and the graph.
+    pub fn infer_effects(&self) -> EffectSummary {
+        let mut summary = EffectSummary {
+            nodes: HashMap::new(),
+            regions: HashMap::new(),
+            graph: EffectSet::empty(),
+        };
+        for node in self.nodes() {
+            let effects = self.node_effects(&node.id);
+            summary.graph.merge(&effects);
+            summary.nodes.insert(node.id, effects);
+        }
+        for region in self.regions() {
+            self.region_effects_into(region.id, &mut summary);
+        }
+        summary
+    }
+
+    fn region_effects_into(&self, id: RegionId, summary: &mut EffectSummary) -> EffectSet {
+        if let Some(done) = summary.regions.get(&id) {
+            return done.clone();
+        }
+        // Placeholder, in case of a malformed parent cycle
+        summary.regions.insert(id, EffectSet::empty());
+        let mut effects = EffectSet::empty();
+        if let Some(region) = self.get_region(&id) {
+            for child in &region.children {
+                if let Some(child_effects) = summary.nodes.get(child) {
+                    effects.merge(child_effects);
+                }
+            }
+        }
+        for child in self.child_regions(&id) {
+            effects.merge(&self.region_effects_into(child, summary));
+        }
+        summary.regions.insert(id, effects.clone());
+        effects
+    }
+
+    /// The nodes that perform `effect` themselves, within `region` (and its
+    /// nested regions) or, for `None`, anywhere in the graph.
+    pub fn effect_sources(&self, effect: &Effect, region: Option<&RegionId>) -> Vec<NodeId> {
+        let scope: Option<HashSet<NodeId>> = region.map(|r| self.nodes_within(*r));
+        let mut sources: Vec<NodeId> = self
+            .nodes()
+            .filter(|n| scope.as_ref().is_none_or(|s| s.contains(&n.id)))
+            .filter(|n| intrinsic_effects(n, self).has_effect(effect))
+            .map(|n| n.id)
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    /// Nodes in a region or any region nested in it.
+    pub(crate) fn nodes_within(&self, region: RegionId) -> HashSet<NodeId> {
+        let mut nodes = HashSet::new();
+        let mut pending = vec![region];
+        let mut visited = HashSet::new();
+        while let Some(id) = pending.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(r) = self.get_region(&id) {
+                nodes.extend(r.children.iter().copied());
+            }
+            pending.extend(self.child_regions(&id));
+        }
+        nodes
+    }
+
+    /// Check that every node with a contract declares the effects it
+    /// performs, including those of its loop body.
+    ///
+    /// Reports one `UndeclaredEffect` per missing effect, naming the node
+    /// that performs it, and an `UnjustifiedDivergence` for a declared
+    /// `Diverge` without a `DIVERGE_JUSTIFICATION` annotation.
+    pub fn validate_declared_effects(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        let mut nodes: Vec<&Node> = self.nodes().collect();
+        nodes.sort_by_key(|n| n.id);
+
+        for node in nodes {
+            let Some(contract) = &node.contract else {
+                continue;
+            };
+            if contract.effects.has_effect(&Effect::Diverge)
+                && !node.annotations.contains_key(DIVERGE_JUSTIFICATION)
+            {
+                errors.push(GraphError::UnjustifiedDivergence { node: node.id });
+            }
+            let mut performers = vec![node.id];
+            if is_loop(&node.kind) {
+                performers.extend(self.loop_body(node.id));
+            }
+            let mut reported = HashSet::new();
+            for performer in performers {
+                let Some(n) = self.get_node(&performer) else {
+                    continue;
+                };
+                for effect in &intrinsic_effects(n, self).effects {
+                    if *effect == Effect::Pure || contract.effects.has_effect(effect) {
+                        continue;
+                    }
+                    if reported.insert(effect.clone()) {
+                        errors.push(GraphError::UndeclaredEffect {
+                            node: node.id,
+                            effect: effect.to_string(),
+                            cause: performer,
+                        });
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{Contract, FailureMode, Variant};
+    use crate::graph::edge::Edge;
+    use crate::graph::node::{ArithmeticOp, MemoryOrdering};
+    use crate::graph::region::{Region, RegionKind};
+    use crate::types::Predicate;
+
+    fn declaring(kind: NodeKind, effects: Vec<Effect>) -> Node {
+        Node::new(kind)
+            .with_contract(Contract::pure_default().with_effects(EffectSet::from_effects(effects)))
+    }
+
+    #[test]
+    fn kind_implied_effects_use_declared_names() {
+        let g = Graph::new();
+        let uart = declaring(NodeKind::Write, vec![Effect::IO("uart0".into())]);
+        assert_eq!(
+            intrinsic_effects(&uart, &g),
+            EffectSet::from_effects(vec![Effect::IO("uart0".into())])
+        );
+
+        let mut read = Node::new(NodeKind::Read);
+        read.annotations.insert(IO_DEVICE.into(), "adc1".into());
+        assert!(intrinsic_effects(&read, &g).has_effect(&Effect::IO("adc1".into())));
+
+        let fence = Node::new(NodeKind::Fence(MemoryOrdering::SeqCst));
+        assert!(intrinsic_effects(&fence, &g).has_effect(&Effect::Atomic("SeqCst".into())));
+
+        let mut aborting = Contract::pure_default();
+        aborting.failure_modes.push(FailureMode {
+            name: "OVERFLOW".into(),
+            description: "sum overflows".into(),
+            recovery: RecoveryStrategy::Abort,
+        });
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_contract(aborting);
+        assert!(intrinsic_effects(&add, &g).has_effect(&Effect::Panic));
+    }
+
+    #[test]
+    fn regions_infer_bottom_up_and_report_sources() {
+        let mut g = Graph::new();
+        let tx = declaring(NodeKind::Write, vec![Effect::IO("uart0".into())]);
+        let alloc = Node::new(NodeKind::Allocate);
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add));
+        let (tx_id, alloc_id, add_id) = (tx.id, alloc.id, add.id);
+        for n in [tx, alloc, add] {
+            g.add_node(n).unwrap();
+        }
+        let inner = Region::new(RegionKind::Sequential, vec![tx_id]);
+        let outer = Region::new(RegionKind::Sequential, vec![alloc_id, add_id]);
+        let (inner_id, outer_id) = (inner.id, outer.id);
+        g.add_region(outer).unwrap();
+        g.add_region(inner.with_parent(outer_id)).unwrap();
+
+        let summary = g.infer_effects();
+        assert_eq!(
+            summary.regions[&inner_id],
+            EffectSet::from_effects(vec![Effect::IO("uart0".into())])
+        );
+        let outer_effects = &summary.regions[&outer_id];
+        assert!(outer_effects.has_effect(&Effect::IO("uart0".into())));
+        assert!(outer_effects.has_effect(&Effect::Alloc("heap".into())));
+        assert_eq!(summary.graph, *outer_effects);
+        assert!(summary.nodes[&add_id].is_pure());
+
+        let uart0 = Effect::IO("uart0".into());
+        assert_eq!(g.effect_sources(&uart0, Some(&outer_id)), vec![tx_id]);
+        assert_eq!(g.effect_sources(&uart0, None), vec![tx_id]);
+        assert!(g
+            .effect_sources(&Effect::IO("uart1".into()), Some(&outer_id))
+            .is_empty());
+    }
+
+    #[test]
+    fn narrow_declarations_are_reported() {
+        let mut g = Graph::new();
+        // A pure-declared loop whose body writes to a device
+        let seed = Node::new(NodeKind::Literal);
+        let mut looped = declaring(NodeKind::Iterate, vec![]);
+        looped.annotations.insert("bound".into(), "3".into());
+        let tx = declaring(NodeKind::Write, vec![Effect::IO("uart0".into())]);
+        let step = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add));
+        let (seed_id, loop_id, tx_id, step_id) = (seed.id, looped.id, tx.id, step.id);
+        for n in [seed, looped, tx, step] {
+            g.add_node(n).unwrap();
+        }
+        g.add_edge(Edge::new((seed_id, 0), (loop_id, 0))).unwrap();
+        g.add_edge(Edge::new((loop_id, 0), (tx_id, 0))).unwrap();
+        g.add_edge(Edge::new((tx_id, 0), (step_id, 0))).unwrap();
+        g.add_edge(Edge::new((step_id, 0), (loop_id, 1))).unwrap();
+
+        assert_eq!(g.loop_body(loop_id), {
+            let mut body = vec![tx_id, step_id];
+            body.sort();
+            body
+        });
+        assert!(g
+            .node_effects(&loop_id)
+            .has_effect(&Effect::IO("uart0".into())));
+
+        let errors = g.validate_declared_effects().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            GraphError::UndeclaredEffect { node, cause, .. } if node == loop_id && cause == tx_id
+        ));
+
+        // A pure-declared Write is narrower than what it does
+        let mut g = Graph::new();
+        g.add_node(declaring(NodeKind::Write, vec![Effect::Pure]))
+            .unwrap();
+        assert!(g.validate_declared_effects().is_err());
+    }
+
+    #[test]
+    fn unmeasured_loops_diverge_with_justification() {
+        let mut g = Graph::new();
+        let variant = Variant::new(Predicate::Var("n".into()), Predicate::Var("next".into()));
+        let measured = Node::new(NodeKind::Fixpoint)
+            .with_contract(Contract::pure_default().with_variant(variant));
+        let mut bounded = Node::new(NodeKind::Iterate);
+        bounded.annotations.insert("bound".into(), "8".into());
+        let open = declaring(NodeKind::Fixpoint, vec![Effect::Diverge]);
+        let open_id = open.id;
+        assert!(!intrinsic_effects(&measured, &g).has_effect(&Effect::Diverge));
+        assert!(!intrinsic_effects(&bounded, &g).has_effect(&Effect::Diverge));
+        assert!(intrinsic_effects(&Node::new(NodeKind::Recurse), &g).has_effect(&Effect::Diverge));
+
+        g.add_node(open).unwrap();
+        let errors = g.validate_declared_effects().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [GraphError::UnjustifiedDivergence { node }] if node == open_id
+        ));
+        g.get_node_mut(&open_id).unwrap().annotations.insert(
+            DIVERGE_JUSTIFICATION.into(),
+            "control loop runs until reset".into(),
+        );
+        assert!(g.validate_declared_effects().is_ok());
+    }
+}
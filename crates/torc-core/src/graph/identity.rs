@@ -0,0 +1,672 @@
+//! Content-addressed identity.
+//!
+//! `Node::new` draws a random ID, so building the same program twice gives
+//! two graphs that serialize differently and that the proof cache and the
+//! registry cannot recognise as the same. `Graph::canonicalize` renumbers a
+//! graph so that every ID depends only on content:
+//!
+//! - A node's ID hashes its module namespace, its kind, type signature,
+//!   contract (without proof status or witness), value and annotations, and
+//!   for each input port the edge's type, lifetime and bandwidth and the ID
+//!   and port of the producer feeding it. IDs are derived producers first;
+//!   a producer on a cycle that is not yet numbered contributes its own
+//!   content instead. A port variable (`<node>.<port>`) in a contract is
+//!   hashed as its producer's structure, found with port variables erased,
+//!   so the random IDs of a fresh build never reach the hash.
+//! - Nodes that still hash alike are told apart by what consumes them and
+//!   by the regions they are in, repeatedly until that splits them no
+//!   further. Nodes left alike are interchangeable and get an ordinal.
+//! - An edge's ID hashes its endpoints; a region's, its kind, children,
+//!   constraints and variant.
+//!
+//! Provenance is not part of identity. Port variables in contracts and
+//! region variants are rewritten to the new IDs.
+//!
+//! `Graph::cone_hashes` hashes the same content over a node's dependency
+//! cone, keeping IDs as they are, to tell what changed between versions.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+
+use uuid::Uuid;
+
+use super::constraints::Lifetime;
+use super::edge::{Edge, EdgeId, PortRef};
+use super::node::{Node, NodeId};
+use super::region::RegionId;
+use super::Graph;
+use crate::contract::binding::{parse_port_var, port_var};
+use crate::contract::{Contract, ProofStatus, Variant};
+use crate::hash::{content_hash, content_id, hash_hex, ContentHash};
+use crate::types::Predicate;
+
+/// The new ID of every node, edge and region of a renumbered graph, by old
+/// ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Renumbering {
+    pub nodes: HashMap<NodeId, NodeId>,
+    pub edges: HashMap<EdgeId, EdgeId>,
+    pub regions: HashMap<RegionId, RegionId>,
+}
+
+/// The part of a node its identity covers, with the port variables of its
+/// contract written by `port_name` (left as they are where it gives `None`).
+fn node_content(node: &Node, port_name: &dyn Fn(PortRef) -> Option<String>) -> ContentHash {
+    let contract = node.contract.clone().map(|mut c| {
+        c.proof_status = ProofStatus::Pending;
+        c.proof_witness = None;
+        rename_ports(&mut c, port_name);
+        c
+    });
+    let annotations: BTreeMap<_, _> = node.annotations.iter().collect();
+    content_hash(&(
+        &node.kind,
+        &node.type_signature,
+        &contract,
+        &node.value,
+        annotations,
+    ))
+}
+
+/// Rewrite the port variables (`<node>.<port>`) of a predicate.
+fn rename_port_vars(
+    predicate: &Predicate,
+    port_name: &dyn Fn(PortRef) -> Option<String>,
+) -> Predicate {
+    let map: BTreeMap<String, Predicate> = predicate
+        .free_vars()
+        .into_iter()
+        .filter_map(|var| {
+            let renamed = port_name(parse_port_var(&var)?)?;
+            Some((var, Predicate::Var(renamed)))
+        })
+        .collect();
+    predicate.substitute(&map)
+}
+
+fn rename_variant_ports(variant: &mut Variant, port_name: &dyn Fn(PortRef) -> Option<String>) {
+    variant.measure = rename_port_vars(&variant.measure, port_name);
+    variant.next = rename_port_vars(&variant.next, port_name);
+    variant.lower_bound = rename_port_vars(&variant.lower_bound, port_name);
+}
+
+fn rename_ports(contract: &mut Contract, port_name: &dyn Fn(PortRef) -> Option<String>) {
+    let rename = |p: &Predicate| rename_port_vars(p, port_name);
+    contract.preconditions = contract.preconditions.iter().map(rename).collect();
+    contract.postconditions = contract.postconditions.iter().map(rename).collect();
+    if let Some(variant) = &mut contract.variant {
+        rename_variant_ports(variant, port_name);
+    }
+}
+
+/// The attributes of an edge its consumer's identity covers. A region
+/// lifetime counts by kind only; the region shows in the consumer's
+/// membership.
+fn edge_content(edge: &Edge) -> ContentHash {
+    let lifetime = match edge.lifetime {
+        Lifetime::Region(_) => Lifetime::Region(Uuid::nil()),
+        ref other => other.clone(),
+    };
+    content_hash(&(&edge.data_type, lifetime, &edge.bandwidth))
+}
+
+/// IDs for hashed items: equal hashes are numbered in key order.
+fn number<K: Copy + Ord + Hash>(
+    hashes: impl IntoIterator<Item = (K, ContentHash)>,
+) -> HashMap<K, Uuid> {
+    let mut groups: BTreeMap<ContentHash, Vec<K>> = BTreeMap::new();
+    for (key, hash) in hashes {
+        groups.entry(hash).or_default().push(key);
+    }
+    groups
+        .into_iter()
+        .flat_map(|(hash, mut keys)| {
+            keys.sort();
+            keys.into_iter()
+                .enumerate()
+                .map(move |(i, key)| (key, content_id(&content_hash(&(hash, i)))))
+        })
+        .collect()
+}
+
+impl Graph {
+    /// The content-addressed ID of every node, in the module `namespace`.
+    pub fn content_ids(&self, namespace: &str) -> HashMap<NodeId, NodeId> {
+        // Port variables name their producer by its structure, found first
+        // with the variables erased
+        let erased: HashMap<NodeId, ContentHash> = self
+            .nodes
+            .values()
+            .map(|n| {
+                (
+                    n.id,
+                    node_content(n, &|(_, port)| Some(format!("_.{port}"))),
+                )
+            })
+            .collect();
+        let first = self.structures(namespace, &erased);
+        let content: HashMap<NodeId, ContentHash> = self
+            .nodes
+            .values()
+            .map(|n| {
+                let port_name = |(id, port): PortRef| {
+                    let producer = first.get(&id).map(hash_hex);
+                    Some(format!("{}.{port}", producer.as_deref().unwrap_or("_")))
+                };
+                (n.id, node_content(n, &port_name))
+            })
+            .collect();
+        let mut hashes = self.structures(namespace, &content);
+
+        // Tell alike nodes apart by their consumers and regions until that
+        // splits them no further
+        loop {
+            let mut alike: HashMap<ContentHash, usize> = HashMap::new();
+            for hash in hashes.values() {
+                *alike.entry(*hash).or_default() += 1;
+            }
+            let classes = alike.len();
+            let refined: HashMap<NodeId, ContentHash> = hashes
+                .iter()
+                .map(|(id, hash)| {
+                    if alike[hash] == 1 {
+                        return (*id, *hash);
+                    }
+                    let mut uses: Vec<_> = self
+                        .outgoing_edges(id)
+                        .iter()
+                        .filter_map(|e| self.edges.get(e))
+                        .map(|e| (e.source.1, hashes[&e.target.0], e.target.1))
+                        .collect();
+                    uses.sort();
+                    (
+                        *id,
+                        content_hash(&(hash, uses, self.membership(id, &hashes))),
+                    )
+                })
+                .collect();
+            let split = refined.values().collect::<HashSet<_>>().len() > classes;
+            hashes = refined;
+            if !split {
+                break;
+            }
+        }
+        number(hashes)
+    }
+
+    /// Hashes of each node's content and, producers first, of the edges
+    /// and producers feeding it.
+    fn structures(
+        &self,
+        namespace: &str,
+        content: &HashMap<NodeId, ContentHash>,
+    ) -> HashMap<NodeId, ContentHash> {
+        let inputs = |id: &NodeId| {
+            let mut inputs: Vec<(usize, PortRef, ContentHash)> = self
+                .incoming_edges(id)
+                .iter()
+                .filter_map(|e| self.edges.get(e))
+                .map(|e| (e.target.1, e.source, edge_content(e)))
+                .collect();
+            inputs.sort();
+            inputs
+        };
+        let consumers = |id: &NodeId| -> BTreeSet<NodeId> {
+            self.outgoing_edges(id)
+                .iter()
+                .filter_map(|e| self.edges.get(e))
+                .map(|e| e.target.0)
+                .collect()
+        };
+
+        // Producers first, least content first; a cycle is entered at its
+        // least content
+        let mut waiting: HashMap<NodeId, usize> = self
+            .nodes
+            .keys()
+            .map(|id| {
+                let producers: HashSet<_> = inputs(id).into_iter().map(|(_, s, _)| s.0).collect();
+                (*id, producers.len())
+            })
+            .collect();
+        let mut remaining: BTreeSet<(ContentHash, NodeId)> =
+            content.iter().map(|(id, c)| (*c, *id)).collect();
+        let mut ready: BTreeSet<(ContentHash, NodeId)> = remaining
+            .iter()
+            .filter(|(_, id)| waiting[id] == 0)
+            .copied()
+            .collect();
+        let mut structure: HashMap<NodeId, ContentHash> = HashMap::new();
+        while let Some(next) = ready.pop_first().or_else(|| remaining.first().copied()) {
+            remaining.remove(&next);
+            let (own, id) = next;
+            let fed: Vec<_> = inputs(&id)
+                .into_iter()
+                .map(|(port, (source, source_port), edge)| {
+                    let numbered = structure.get(&source);
+                    let hash = numbered.copied().unwrap_or(content[&source]);
+                    (port, numbered.is_some(), hash, source_port, edge)
+                })
+                .collect();
+            structure.insert(id, content_hash(&(namespace, own, fed)));
+            for consumer in consumers(&id) {
+                let count = waiting.get_mut(&consumer).expect("consumer is a node");
+                *count = count.saturating_sub(1);
+                if *count == 0 && remaining.contains(&(content[&consumer], consumer)) {
+                    ready.insert((content[&consumer], consumer));
+                }
+            }
+        }
+        structure
+    }
+
+    /// The regions enclosing a node, innermost first, each by its kind,
+    /// constraints and the hashes of its children.
+    fn membership(&self, id: &NodeId, hashes: &HashMap<NodeId, ContentHash>) -> Vec<ContentHash> {
+        let mut chain = Vec::new();
+        let mut current = self.containing_region(id).copied();
+        while let Some(region) = current.and_then(|r| self.regions.get(&r)) {
+            if chain.len() > self.regions.len() {
+                // A malformed parent cycle
+                break;
+            }
+            let mut children: Vec<_> = region
+                .children
+                .iter()
+                .filter_map(|c| hashes.get(c))
+                .collect();
+            children.sort();
+            chain.push(content_hash(&(region.kind, &region.constraints, children)));
+            current = region.parent;
+        }
+        chain
+    }
+
+    /// Renumber every node, edge and region with its content-addressed ID
+    /// in the module `namespace`, so that identical programs serialize to
+    /// identical bytes.
+    pub fn canonicalize(&mut self, namespace: &str) -> Renumbering {
+        let nodes = self.content_ids(namespace);
+        let node = |id: &NodeId| nodes.get(id).copied().unwrap_or(*id);
+        let edges = number(self.edges.values().map(|e| {
+            let (source, target) = (
+                (node(&e.source.0), e.source.1),
+                (node(&e.target.0), e.target.1),
+            );
+            (e.id, content_hash(&(namespace, "edge", source, target)))
+        }));
+        let port_name = |(id, port): PortRef| Some(port_var((*nodes.get(&id)?, port)));
+        let regions = number(self.regions.values().map(|r| {
+            let mut children: Vec<_> = r.children.iter().map(node).collect();
+            children.sort();
+            let variant = r.variant.clone().map(|mut v| {
+                rename_variant_ports(&mut v, &port_name);
+                v
+            });
+            let hash = content_hash(&(
+                namespace,
+                "region",
+                r.kind,
+                children,
+                &r.constraints,
+                variant,
+            ));
+            (r.id, hash)
+        }));
+        let edge = |id: &EdgeId| edges.get(id).copied().unwrap_or(*id);
+        let region = |id: &RegionId| regions.get(id).copied().unwrap_or(*id);
+
+        self.nodes = std::mem::take(&mut self.nodes)
+            .into_values()
+            .map(|mut n| {
+                n.id = node(&n.id);
+                if let Some(contract) = &mut n.contract {
+                    rename_ports(contract, &port_name);
+                }
+                (n.id, n)
+            })
+            .collect();
+        self.edges = std::mem::take(&mut self.edges)
+            .into_values()
+            .map(|mut e| {
+                e.id = edge(&e.id);
+                e.source.0 = node(&e.source.0);
+                e.target.0 = node(&e.target.0);
+                if let Lifetime::Region(r) = &mut e.lifetime {
+                    *r = region(r);
+                }
+                (e.id, e)
+            })
+            .collect();
+        self.regions = std::mem::take(&mut self.regions)
+            .into_values()
+            .map(|mut r| {
+                r.id = region(&r.id);
+                r.children = r.children.iter().map(node).collect();
+                r.parent = r.parent.as_ref().map(region);
+                if let Some(variant) = &mut r.variant {
+                    rename_variant_ports(variant, &port_name);
+                }
+                (r.id, r)
+            })
+            .collect();
+        let edge_index = |index: HashMap<NodeId, Vec<EdgeId>>| {
+            index
+                .into_iter()
+                .map(|(n, es)| (node(&n), es.iter().map(edge).collect()))
+                .collect()
+        };
+        self.outgoing = edge_index(std::mem::take(&mut self.outgoing));
+        self.incoming = edge_index(std::mem::take(&mut self.incoming));
+        self.region_children = std::mem::take(&mut self.region_children)
+            .into_iter()
+            .map(|(r, ns)| (region(&r), ns.iter().map(node).collect()))
+            .collect();
+        self.node_region = std::mem::take(&mut self.node_region)
+            .into_iter()
+            .map(|(n, r)| (node(&n), region(&r)))
+            .collect();
+        self.region_parent = std::mem::take(&mut self.region_parent)
+            .into_iter()
+            .map(|(child, parent)| (region(&child), region(&parent)))
+            .collect();
+
+        Renumbering {
+            nodes,
+            edges,
+            regions,
+        }
+    }
+
+    /// For each node, a hash of its dependency cone: the node and every node
+    /// it transitively consumes from, by ID and content, and the edges
+    /// between them with their content.
+    ///
+    /// A node's cone hash is unchanged between two versions of a graph
+    /// exactly when nothing upstream of it has changed, which is what
+    /// incremental verification keys reused proofs on. Cones are hashed in
+    /// one pass, producers first: a node hashes the cone hashes of its
+    /// producers rather than their cones, and the nodes of a cycle share
+    /// one hash.
+    pub fn cone_hashes(&self) -> HashMap<NodeId, ContentHash> {
+        let mut cones: HashMap<NodeId, ContentHash> = HashMap::new();
+        for component in self.upstream_components() {
+            let members: BTreeMap<NodeId, ContentHash> = component
+                .iter()
+                .map(|n| (*n, node_content(&self.nodes[n], &|_| None)))
+                .collect();
+            // Edges from upstream carry their producer's cone; edges
+            // within the component are covered by its members
+            let mut edges: Vec<_> = members
+                .keys()
+                .flat_map(|n| self.incoming.get(n).into_iter().flatten())
+                .map(|e| {
+                    let edge = &self.edges[e];
+                    let upstream = cones.get(&edge.source.0).copied();
+                    (edge.source, edge.target, edge_content(edge), upstream)
+                })
+                .collect();
+            edges.sort();
+            let hash = content_hash(&(members, edges));
+            cones.extend(component.into_iter().map(|n| (n, hash)));
+        }
+        cones
+    }
+
+    /// The strongly connected components of the graph, each after every
+    /// component it consumes from (Tarjan's algorithm, following edges
+    /// from consumer to producer).
+    fn upstream_components(&self) -> Vec<Vec<NodeId>> {
+        let producers: HashMap<NodeId, Vec<NodeId>> = self
+            .nodes
+            .keys()
+            .map(|n| {
+                let sources = self.incoming.get(n).into_iter().flatten();
+                (*n, sources.map(|e| self.edges[e].source.0).collect())
+            })
+            .collect();
+        // Visit order and lowest reachable visit order of each node
+        let mut order: HashMap<NodeId, (usize, usize)> = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+
+        let mut roots: Vec<NodeId> = self.nodes.keys().copied().collect();
+        roots.sort();
+        for root in roots {
+            if order.contains_key(&root) {
+                continue;
+            }
+            order.insert(root, (order.len(), order.len()));
+            stack.push(root);
+            on_stack.insert(root);
+            // Each frame is a node and the next of its producers to visit
+            let mut frames = vec![(root, 0)];
+            while let Some(frame) = frames.last_mut() {
+                let node = frame.0;
+                let next = producers[&node].get(frame.1).copied();
+                frame.1 += 1;
+                match next {
+                    Some(producer) => match order.get(&producer) {
+                        None => {
+                            order.insert(producer, (order.len(), order.len()));
+                            stack.push(producer);
+                            on_stack.insert(producer);
+                            frames.push((producer, 0));
+                        }
+                        Some(&(visited, _)) if on_stack.contains(&producer) => {
+                            let low = &mut order.get_mut(&node).unwrap().1;
+                            *low = (*low).min(visited);
+                        }
+                        Some(_) => {}
+                    },
+                    None => {
+                        frames.pop();
+                        let (visited, low) = order[&node];
+                        if let Some(&(parent, _)) = frames.last() {
+                            let parent_low = &mut order.get_mut(&parent).unwrap().1;
+                            *parent_low = (*parent_low).min(low);
+                        }
+                        if visited == low {
+                            let mut component = Vec::new();
+                            while let Some(member) = stack.pop() {
+                                on_stack.remove(&member);
+                                component.push(member);
+                                if member == node {
+                                    break;
+                                }
+                            }
+                            components.push(component);
+                        }
+                    }
+                }
+            }
+        }
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::{ArithmeticOp, NodeKind};
+    use crate::graph::region::{Region, RegionKind};
+    use crate::types::{Type, TypeSignature};
+    use crate::value::Value;
+
+    /// `(a + b) * a` over literals `a` and `b`, in a sequential region,
+    /// with the nodes added in the given order.
+    fn program(a: i32, b: i32, reversed: bool) -> (Graph, NodeId, NodeId) {
+        let mut g = Graph::new();
+        let lit = |v| {
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(Type::i32()))
+                .with_value(Value::i32(v))
+        };
+        let op = |op| {
+            Node::new(NodeKind::Arithmetic(op)).with_type_signature(TypeSignature::pure_fn(
+                vec![Type::i32(), Type::i32()],
+                Type::i32(),
+            ))
+        };
+        let mut nodes = vec![lit(a), lit(b), op(ArithmeticOp::Add), op(ArithmeticOp::Mul)];
+        let ids: Vec<_> = nodes.iter().map(|n| n.id).collect();
+        if reversed {
+            nodes.reverse();
+        }
+        for n in nodes {
+            g.add_node(n).unwrap();
+        }
+        let (a, b, sum, product) = (ids[0], ids[1], ids[2], ids[3]);
+        for (source, target) in [
+            (a, (sum, 0)),
+            (b, (sum, 1)),
+            (sum, (product, 0)),
+            (a, (product, 1)),
+        ] {
+            g.add_edge(Edge::new((source, 0), target)).unwrap();
+        }
+        g.add_region(Region::new(RegionKind::Sequential, vec![sum, product]))
+            .unwrap();
+        (g, a, product)
+    }
+
+    #[test]
+    fn identical_programs_serialize_identically() {
+        let (mut first, ..) = program(2, 3, false);
+        let (mut second, ..) = program(2, 3, true);
+        assert_ne!(
+            serde_json::to_vec(&first).unwrap(),
+            serde_json::to_vec(&second).unwrap()
+        );
+
+        first.canonicalize("m");
+        second.canonicalize("m");
+        assert_eq!(
+            serde_json::to_vec(&first).unwrap(),
+            serde_json::to_vec(&second).unwrap()
+        );
+        assert!(first.validate().is_ok());
+
+        // Canonical IDs are a fixed point
+        let before = serde_json::to_vec(&first).unwrap();
+        first.canonicalize("m");
+        assert_eq!(serde_json::to_vec(&first).unwrap(), before);
+    }
+
+    #[test]
+    fn fresh_builds_get_identical_ids() {
+        use crate::contract::binding::port_var;
+
+        // A contract over a port variable, and twin literals told apart only
+        // by the region one of them shares with `c`
+        let build = || {
+            let (mut g, a, product) = program(2, 3, false);
+            let lit = || {
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(Type::i32()))
+                    .with_value(Value::i32(1))
+            };
+            let (x, y, c) = (
+                g.add_node(lit()).unwrap(),
+                g.add_node(lit()).unwrap(),
+                g.add_node(lit().with_value(Value::i32(5))).unwrap(),
+            );
+            g.add_region(Region::new(RegionKind::Sequential, vec![x, c]))
+                .unwrap();
+            g.add_region(Region::new(RegionKind::Sequential, vec![y]))
+                .unwrap();
+            g.get_node_mut(&product).unwrap().contract = Some(Contract::with_conditions(
+                vec![Predicate::positive(&port_var((a, 0)))],
+                vec![],
+            ));
+            g.canonicalize("m");
+            serde_json::to_vec(&g).unwrap()
+        };
+        let first = build();
+        for _ in 0..20 {
+            assert_eq!(build(), first);
+        }
+
+        // Canonicalizing a canonical graph changes nothing
+        let mut g: Graph = serde_json::from_slice(&first).unwrap();
+        g.canonicalize("m");
+        assert_eq!(serde_json::to_vec(&g).unwrap(), first);
+    }
+
+    #[test]
+    fn ids_follow_content_and_namespace() {
+        let (g, a, product) = program(2, 3, false);
+        let ids = g.content_ids("m");
+        let (other, other_a, other_product) = program(2, 4, false);
+        let other_ids = other.content_ids("m");
+
+        // `b` feeds the sum, and the sum the product
+        assert_eq!(ids[&a], other_ids[&other_a]);
+        assert_ne!(ids[&product], other_ids[&other_product]);
+        assert_ne!(ids[&a], g.content_ids("n")[&a]);
+
+        // Equal literals still get distinct IDs
+        let (same, ..) = program(2, 2, false);
+        let ids: HashSet<_> = same.content_ids("m").into_values().collect();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn cones_cover_upstream_only() {
+        let (mut g, a, product) = program(2, 3, false);
+        let before = g.cone_hashes();
+        let sum = g.incoming_edges(&product)[0];
+        let sum = g.edges[&sum].source.0;
+
+        // Editing the product leaves the cones of its producers alone.
+        g.get_node_mut(&product)
+            .unwrap()
+            .annotations
+            .insert("note".into(), "x".into());
+        let after = g.cone_hashes();
+        assert_eq!(before[&a], after[&a]);
+        assert_eq!(before[&sum], after[&sum]);
+        assert_ne!(before[&product], after[&product]);
+
+        // Editing `a` changes everything downstream of it.
+        g.get_node_mut(&a).unwrap().value = Some(Value::i32(5));
+        let edited = g.cone_hashes();
+        assert!([a, sum, product].iter().all(|n| edited[n] != after[n]));
+
+        // So does retyping the edge from the sum.
+        let into_product = g.incoming_edges(&product)[0];
+        g.edges.get_mut(&into_product).unwrap().data_type = Some(Type::i32());
+        let retyped = g.cone_hashes();
+        assert_eq!(retyped[&sum], edited[&sum]);
+        assert_ne!(retyped[&product], edited[&product]);
+    }
+
+    #[test]
+    fn cycles_share_a_cone() {
+        let mut g = Graph::new();
+        let mut node = |kind| g.add_node(Node::new(kind)).unwrap();
+        let (seed, fix, step, sink) = (
+            node(NodeKind::Literal),
+            node(NodeKind::Fixpoint),
+            node(NodeKind::Arithmetic(ArithmeticOp::Sub)),
+            node(NodeKind::Write),
+        );
+        for (source, target) in [(seed, fix), (fix, step), (step, fix), (fix, sink)] {
+            let port = g.incoming_edges(&target).len();
+            g.add_edge(Edge::new((source, 0), (target, port))).unwrap();
+        }
+        let before = g.cone_hashes();
+        assert_eq!(before[&fix], before[&step]);
+        assert_ne!(before[&seed], before[&fix]);
+
+        g.get_node_mut(&step)
+            .unwrap()
+            .annotations
+            .insert("note".into(), "x".into());
+        let after = g.cone_hashes();
+        assert_eq!(before[&seed], after[&seed]);
+        assert!([fix, step, sink].iter().all(|n| before[n] != after[n]));
+    }
+}
@@ -0,0 +1,356 @@
+//! Module interfaces.
+//!
+//! A module is a set of nodes cut out of a graph. Its interface is what the
+//! rest of the graph sees: a named, typed port for every internal input fed
+//! from outside (`in0`, `in1`, ...) and every internal output read outside
+//! (`out0`, ...), and an interface contract over those names:
+//!
+//! - the preconditions of its nodes that mention only interface inputs;
+//! - the postconditions of its nodes that mention only interface ports;
+//! - the effects its nodes perform.
+//!
+//! A replacement honours an interface when, port for port, it accepts every
+//! value the original accepted and produces only values the original could
+//! (checked like edges, so refinements become obligations), performs no
+//! effect the original did not, needs no precondition the original did not
+//! guarantee, and guarantees every postcondition the original did.
+//! Contract clauses are decided structurally when the conjunct needed is
+//! among the assumptions, and become obligations otherwise.
+
+use std::collections::BTreeMap;
+
+use super::diff::conjuncts;
+use super::edge::PortRef;
+use super::node::NodeId;
+use super::port::PortDirection;
+use super::{Graph, GraphError};
+use crate::contract::{Contract, EffectSet, ObligationKind, ProofObligation, ProofStatus};
+use crate::types::check::types_compatible;
+use crate::types::{Effect, Predicate, Type};
+
+/// A named, typed port of a module interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfacePort {
+    /// `in<i>` or `out<i>`; the interface contract refers to the port by it.
+    pub name: String,
+    pub direction: PortDirection,
+    /// The port inside the module it stands for.
+    pub endpoint: PortRef,
+    /// `None` if the node inside has no type signature.
+    pub port_type: Option<Type>,
+}
+
+/// The ports and contract a module presents to the rest of the graph.
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub inputs: Vec<InterfacePort>,
+    pub outputs: Vec<InterfacePort>,
+    pub contract: Contract,
+}
+
+impl Interface {
+    /// Check that `replacement`, with ports in the same order, honours this
+    /// interface.
+    ///
+    /// Returns the obligations that could not be decided structurally, or
+    /// a mismatch in the number of ports, the first port whose types are
+    /// incompatible, or an effect the replacement adds.
+    pub fn check_replacement(
+        &self,
+        replacement: &Interface,
+    ) -> Result<Vec<ProofObligation>, GraphError> {
+        let mut obligations = Vec::new();
+
+        for (item, old, new) in [
+            ("inputs", self.inputs.len(), replacement.inputs.len()),
+            ("outputs", self.outputs.len(), replacement.outputs.len()),
+        ] {
+            if old != new {
+                return Err(GraphError::InterfaceMismatch {
+                    item: item.into(),
+                    expected: format!("{old} ports"),
+                    found: format!("{new} ports"),
+                });
+            }
+        }
+
+        // Inputs receive what the original accepted; outputs must be
+        // acceptable wherever the original's went
+        let inputs = self.inputs.iter().zip(&replacement.inputs);
+        let outputs = self.outputs.iter().zip(&replacement.outputs);
+        for (old, new) in inputs {
+            obligations.extend(port_compatible(old, &old.port_type, &new.port_type)?);
+        }
+        for (old, new) in outputs {
+            obligations.extend(port_compatible(old, &new.port_type, &old.port_type)?);
+        }
+
+        let (old, new) = (&self.contract, &replacement.contract);
+        if let Some(effect) = new
+            .effects
+            .effects
+            .iter()
+            .find(|e| **e != Effect::Pure && !old.effects.has_effect(e))
+        {
+            return Err(GraphError::InterfaceMismatch {
+                item: "effects".into(),
+                expected: old.effects.to_string(),
+                found: format!("{} (adds {effect})", new.effects),
+            });
+        }
+
+        let assumed = conjuncts(&old.preconditions);
+        for needed in conjuncts(&new.preconditions) {
+            if !assumed.contains(&needed) {
+                obligations.push(obligation(
+                    ObligationKind::Precondition,
+                    Predicate::Implies(Box::new(conjoin(&assumed)), Box::new(needed.clone())),
+                    format!("module precondition must imply replacement precondition {needed:?}"),
+                ));
+            }
+        }
+        let mut guaranteed = assumed;
+        guaranteed.extend(conjuncts(&new.postconditions));
+        for promised in conjuncts(&old.postconditions) {
+            if !guaranteed.contains(&promised) {
+                obligations.push(obligation(
+                    ObligationKind::Postcondition,
+                    Predicate::Implies(Box::new(conjoin(&guaranteed)), Box::new(promised.clone())),
+                    format!("replacement must guarantee module postcondition {promised:?}"),
+                ));
+            }
+        }
+        Ok(obligations)
+    }
+}
+
+/// Obligations for a value of type `source` flowing through the port where
+/// `target` is expected; no check if either type is unknown.
+fn port_compatible(
+    port: &InterfacePort,
+    source: &Option<Type>,
+    target: &Option<Type>,
+) -> Result<Vec<ProofObligation>, GraphError> {
+    let (Some(source), Some(target)) = (source, target) else {
+        return Ok(Vec::new());
+    };
+    types_compatible(source, target).map_err(|_| GraphError::InterfaceMismatch {
+        item: format!("port {}", port.name),
+        expected: target.to_string(),
+        found: source.to_string(),
+    })
+}
+
+fn conjoin(predicates: &[Predicate]) -> Predicate {
+    predicates
+        .iter()
+        .cloned()
+        .fold(Predicate::BoolLit(true), Predicate::conjoin)
+}
+
+fn obligation(kind: ObligationKind, predicate: Predicate, description: String) -> ProofObligation {
+    ProofObligation {
+        kind,
+        predicate,
+        description,
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    }
+}
+
+impl Graph {
+    /// The interface of this graph as a module whose boundary is the given
+    /// internal input and output ports, in port order.
+    pub fn interface(&self, inputs: &[PortRef], outputs: &[PortRef]) -> Interface {
+        let ports = |endpoints: &[PortRef], direction| -> Vec<InterfacePort> {
+            endpoints
+                .iter()
+                .enumerate()
+                .map(|(i, &(node, port))| {
+                    let sig = self.get_node(&node).and_then(|n| n.type_signature.as_ref());
+                    let (prefix, ty) = match direction {
+                        PortDirection::Input => ("in", sig.and_then(|s| s.inputs.get(port))),
+                        PortDirection::Output => ("out", sig.and_then(|s| s.outputs.get(port))),
+                    };
+                    InterfacePort {
+                        name: format!("{prefix}{i}"),
+                        direction,
+                        endpoint: (node, port),
+                        port_type: ty.map(|t| self.resolve_type(t).unwrap_or_else(|_| t.clone())),
+                    }
+                })
+                .collect()
+        };
+        let inputs = ports(inputs, PortDirection::Input);
+        let outputs = ports(outputs, PortDirection::Output);
+
+        let mut contract = Contract::pure_default();
+        contract.effects = EffectSet::pure_set();
+        contract.effects.merge(&self.infer_effects().graph);
+        // Preconditions may assume only inputs; postconditions relate both
+        let assumed: Vec<_> = inputs.iter().collect();
+        let all: Vec<_> = inputs.iter().chain(&outputs).collect();
+        let mut nodes: Vec<_> = self.nodes().collect();
+        nodes.sort_by_key(|n| n.id);
+        for node in nodes {
+            let Some(own) = &node.contract else {
+                continue;
+            };
+            contract.preconditions.extend(
+                own.preconditions
+                    .iter()
+                    .filter_map(|p| self.interface_names(node.id, own, p, &assumed)),
+            );
+            contract.postconditions.extend(
+                own.postconditions
+                    .iter()
+                    .filter_map(|p| self.interface_names(node.id, own, p, &all)),
+            );
+        }
+
+        Interface {
+            inputs,
+            outputs,
+            contract,
+        }
+    }
+
+    /// Rewrite a predicate of `node`'s contract over interface port names,
+    /// or `None` if it mentions a value that is not at one of `ports`.
+    fn interface_names(
+        &self,
+        node: NodeId,
+        contract: &Contract,
+        predicate: &Predicate,
+        ports: &[&InterfacePort],
+    ) -> Option<Predicate> {
+        let name = |direction, endpoint| {
+            ports
+                .iter()
+                .find(|p| p.direction == direction && p.endpoint == endpoint)
+                .map(|p| Predicate::Var(p.name.clone()))
+        };
+        let mut map = BTreeMap::new();
+        for var in predicate.free_vars() {
+            let binding = contract.binding(&var)?;
+            let port = (node, binding.port);
+            let value = match binding.direction {
+                // An input is at the interface itself, or fed by an output
+                // that is
+                PortDirection::Input => name(PortDirection::Input, port)
+                    .or_else(|| name(PortDirection::Output, self.input_source(port)?)),
+                PortDirection::Output => name(PortDirection::Output, port),
+            }?;
+            map.insert(var, value);
+        }
+        Some(predicate.substitute(&map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::edge::Edge;
+    use crate::graph::node::{ArithmeticOp, Node, NodeKind};
+    use crate::types::TypeSignature;
+
+    fn var(name: &str) -> Box<Predicate> {
+        Box::new(Predicate::Var(name.into()))
+    }
+
+    fn zero() -> Box<Predicate> {
+        Box::new(Predicate::IntLit(0))
+    }
+
+    /// A one-node module negating its input, with the given contract.
+    fn negate(contract: Contract) -> (Graph, PortRef, PortRef) {
+        let mut g = Graph::new();
+        let id = g
+            .add_node(
+                Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub))
+                    .with_type_signature(TypeSignature::pure_fn(vec![Type::i32()], Type::i32()))
+                    .with_contract(contract),
+            )
+            .unwrap();
+        (g, (id, 0), (id, 0))
+    }
+
+    #[test]
+    fn interface_contract_uses_port_names() {
+        let contract = Contract::with_conditions(
+            vec![Predicate::Gt(var("input"), zero())],
+            vec![Predicate::Lt(var("output"), zero())],
+        );
+        let (g, input, output) = negate(contract);
+        let interface = g.interface(&[input], &[output]);
+
+        assert_eq!(interface.inputs[0].name, "in0");
+        assert_eq!(interface.outputs[0].port_type, Some(Type::i32()));
+        assert_eq!(
+            interface.contract.preconditions,
+            vec![Predicate::Gt(var("in0"), zero())]
+        );
+        assert_eq!(
+            interface.contract.postconditions,
+            vec![Predicate::Lt(var("out0"), zero())]
+        );
+
+        // The same node without its output at the boundary keeps no
+        // postcondition
+        assert!(g
+            .interface(&[input], &[])
+            .contract
+            .postconditions
+            .is_empty());
+    }
+
+    #[test]
+    fn replacement_must_refine_the_interface() {
+        let positive = Predicate::Gt(var("input"), zero());
+        let negative = Predicate::Lt(var("output"), zero());
+        let original = Contract::with_conditions(vec![positive.clone()], vec![negative.clone()]);
+        let (g, input, output) = negate(original);
+        let module = g.interface(&[input], &[output]);
+
+        // Weaker precondition, same postcondition: decided structurally
+        let (weaker, i, o) = negate(Contract::with_conditions(vec![], vec![negative]));
+        assert!(module
+            .check_replacement(&weaker.interface(&[i], &[o]))
+            .unwrap()
+            .is_empty());
+
+        // Stronger precondition, no postcondition: two obligations
+        let (stronger, i, o) = negate(Contract::with_conditions(
+            vec![
+                positive,
+                Predicate::Lt(var("input"), Box::new(Predicate::IntLit(10))),
+            ],
+            vec![],
+        ));
+        let obs = module
+            .check_replacement(&stronger.interface(&[i], &[o]))
+            .unwrap();
+        let kinds: Vec<_> = obs.iter().map(|o| o.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![ObligationKind::Precondition, ObligationKind::Postcondition]
+        );
+
+        // Effects the original does not perform are rejected
+        let (mut io, i, o) = negate(Contract::pure_default());
+        let write = io.add_node(Node::new(NodeKind::Write)).unwrap();
+        io.add_edge(Edge::new(o, (write, 0))).unwrap();
+        assert!(matches!(
+            module.check_replacement(&io.interface(&[i], &[o])),
+            Err(GraphError::InterfaceMismatch { ref item, .. }) if item == "effects"
+        ));
+
+        // Dropping an output is rejected, not left unchecked
+        let (fewer, i, _) = negate(Contract::pure_default());
+        assert!(matches!(
+            module.check_replacement(&fewer.interface(&[i], &[])),
+            Err(GraphError::InterfaceMismatch { ref item, .. }) if item == "outputs"
+        ));
+    }
+}
@@ -6,26 +6,43 @@
 //! (scope boundaries).
 
 pub mod constraints;
+pub mod diff;
 pub mod edge;
+pub mod effects;
+pub mod identity;
+pub mod interface;
 pub mod node;
+pub mod ownership;
+pub mod patch;
 pub mod port;
+pub mod query;
 pub mod region;
+pub mod resources;
+pub mod three_way;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 
 use std::collections::HashSet;
 
 use self::constraints::{BandwidthConstraint, Lifetime};
 use self::edge::{Edge, EdgeId};
+use self::interface::Interface;
 use self::node::{Node, NodeId, NodeKind};
 use self::region::{Region, RegionId};
 
+use crate::contract::binding::port_var;
+use crate::contract::generate::{
+    approximation_obligation, domain_obligations, overflow_obligation,
+    region_termination_obligations, resource_obligations, termination_obligations,
+};
 use crate::contract::{EffectSet, ObligationKind, ProofObligation, ProofStatus};
-use crate::types::check::types_compatible;
-use crate::types::{Linearity, Predicate, Type};
+use crate::prob;
+use crate::types::check::TypeError;
+use crate::types::subtype::Counterexample;
+use crate::types::{Predicate, Type, TypeDef, TypeTable};
 
 /// Errors that can occur during graph construction or validation.
 #[derive(Debug, Error)]
@@ -68,6 +85,50 @@ pub enum GraphError {
         consumers: usize,
     },
 
+    #[error(
+        "linearity violation: {kind:?} value at node {node} port {port} is consumed between {fewest} and {} time(s) on different control paths",
+        times(.most)
+    )]
+    PathLinearityViolation {
+        node: NodeId,
+        port: usize,
+        kind: crate::types::Linearity,
+        fewest: usize,
+        most: Option<usize>,
+    },
+
+    #[error("counted value at node {node} port {port} has {references} references, but node {consumer} takes unique ownership of it")]
+    SharedOwnership {
+        node: NodeId,
+        port: usize,
+        consumer: NodeId,
+        references: usize,
+    },
+
+    #[error("edge {edge} carries a value scoped to region {region} to node {target} outside it")]
+    LifetimeEscape {
+        edge: EdgeId,
+        region: RegionId,
+        target: NodeId,
+    },
+
+    #[error(
+        "allocation at node {node} is freed between {fewest} and {} time(s) on different control paths, not exactly once",
+        times(.most)
+    )]
+    UnpairedAllocation {
+        node: NodeId,
+        fewest: usize,
+        most: Option<usize>,
+    },
+
+    #[error("node {node} uses the allocation of node {allocation} after node {free} frees it")]
+    UseAfterFree {
+        node: NodeId,
+        allocation: NodeId,
+        free: NodeId,
+    },
+
     #[error("effect violation: node {node} declares {declared} but depends on {required}")]
     EffectViolation {
         node: NodeId,
@@ -75,6 +136,16 @@ pub enum GraphError {
         required: String,
     },
 
+    #[error("undeclared effect: node {node} performs {effect} (via node {cause}) but does not declare it")]
+    UndeclaredEffect {
+        node: NodeId,
+        effect: String,
+        cause: NodeId,
+    },
+
+    #[error("node {node} declares Diverge without a justification")]
+    UnjustifiedDivergence { node: NodeId },
+
     #[error("type mismatch on edge {edge}: expected {expected}, found {found}")]
     TypeMismatch {
         edge: EdgeId,
@@ -82,13 +153,53 @@ pub enum GraphError {
         found: String,
     },
 
+    #[error(
+        "refinement violated on edge {edge}: {found} admits {counterexample}, \
+         which {expected} excludes"
+    )]
+    RefinementViolated {
+        edge: EdgeId,
+        expected: String,
+        found: String,
+        counterexample: Box<Counterexample>,
+    },
+
     #[error("merge conflict: duplicate {kind} id {id}")]
     MergeConflict { kind: String, id: uuid::Uuid },
 
+    #[error("patch does not apply: {0} is not in the state the patch expects")]
+    StalePatch(String),
+
+    #[error("merge conflict: conflicting definitions of type {0}")]
+    TypeConflict(String),
+
+    #[error("type resolution failed: {0}")]
+    TypeResolution(#[from] TypeError),
+
+    #[error("invalid literal at node {node}: {message}")]
+    InvalidLiteral { node: NodeId, message: String },
+
+    #[error("invalid probabilistic node {node}: {message}")]
+    InvalidProbabilistic { node: NodeId, message: String },
+
+    #[error(
+        "replacement breaks the module interface at {item}: expected {expected}, found {found}"
+    )]
+    InterfaceMismatch {
+        item: String,
+        expected: String,
+        found: String,
+    },
+
     #[error("incomplete port mapping: unmapped boundary port ({node}, {port})")]
     UnmappedBoundaryPort { node: NodeId, port: usize },
 }
 
+/// An upper bound on a count, for error messages.
+fn times(most: &Option<usize>) -> String {
+    most.map_or_else(|| "unboundedly many".into(), |n| n.to_string())
+}
+
 /// Direction of a boundary edge relative to a module.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BoundaryDirection {
@@ -98,6 +209,17 @@ pub enum BoundaryDirection {
     Out,
 }
 
+/// The graph element a proof obligation was generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObligationSite {
+    /// A node's own contract or termination.
+    Node(NodeId),
+    /// An edge, for refinement subtyping and contract crossing.
+    Edge(EdgeId),
+    /// A region, for the termination of an iterative region.
+    Region(RegionId),
+}
+
 /// A boundary edge that crosses a module boundary.
 #[derive(Debug, Clone)]
 pub struct BoundaryEdge {
@@ -123,30 +245,78 @@ pub struct ModuleInterface {
     pub inputs: Vec<BoundaryEdge>,
     /// Edges flowing out of the module.
     pub outputs: Vec<BoundaryEdge>,
+    /// Named, typed ports for the boundary endpoints, and the contract the
+    /// module offers over them.
+    pub interface: Interface,
 }
 
 /// The core graph container for a Torc program.
 ///
 /// Stores nodes, edges, and regions with efficient lookup by ID.
 /// Provides topological ordering, subgraph extraction, and validation.
+///
+/// Serialization is deterministic: maps are written in key order and edge
+/// indexes in ID order, so two graphs with the same content produce the same
+/// bytes regardless of insertion order.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Graph {
+    #[serde(serialize_with = "ordered_map")]
     nodes: HashMap<NodeId, Node>,
+    #[serde(serialize_with = "ordered_map")]
     edges: HashMap<EdgeId, Edge>,
+    #[serde(serialize_with = "ordered_map")]
     regions: HashMap<RegionId, Region>,
+    /// Named type definitions referenced by the graph's types.
+    #[serde(default, skip_serializing_if = "TypeTable::is_empty")]
+    types: TypeTable,
 
     /// Index: node -> outgoing edges (edges where this node is the source)
+    #[serde(serialize_with = "ordered_edge_index")]
     outgoing: HashMap<NodeId, Vec<EdgeId>>,
     /// Index: node -> incoming edges (edges where this node is the target)
+    #[serde(serialize_with = "ordered_edge_index")]
     incoming: HashMap<NodeId, Vec<EdgeId>>,
     /// Index: region -> child nodes
+    #[serde(serialize_with = "ordered_map")]
     region_children: HashMap<RegionId, Vec<NodeId>>,
     /// Index: node -> containing region (if any)
+    #[serde(serialize_with = "ordered_map")]
     node_region: HashMap<NodeId, RegionId>,
     /// Index: child region -> parent region
+    #[serde(serialize_with = "ordered_map")]
     region_parent: HashMap<RegionId, RegionId>,
 }
 
+/// Serialize a hash map with its entries in key order.
+pub(crate) fn ordered_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    let ordered: BTreeMap<&K, &V> = map.iter().collect();
+    ordered.serialize(serializer)
+}
+
+/// Serialize an edge index with keys in order and each edge list sorted.
+///
+/// The order of edges within an index list is an artifact of insertion order
+/// and carries no meaning, so it is normalized here.
+fn ordered_edge_index<S: Serializer>(
+    index: &HashMap<NodeId, Vec<EdgeId>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let ordered: BTreeMap<&NodeId, Vec<EdgeId>> = index
+        .iter()
+        .map(|(id, edges)| {
+            let mut edges = edges.clone();
+            edges.sort();
+            (id, edges)
+        })
+        .collect();
+    ordered.serialize(serializer)
+}
+
 impl Graph {
     /// Create a new empty graph.
     pub fn new() -> Self {
@@ -154,6 +324,7 @@ impl Graph {
             nodes: HashMap::new(),
             edges: HashMap::new(),
             regions: HashMap::new(),
+            types: TypeTable::new(),
             outgoing: HashMap::new(),
             incoming: HashMap::new(),
             region_children: HashMap::new(),
@@ -304,6 +475,7 @@ impl Graph {
     /// and any regions fully contained within the node set.
     pub fn extract_subgraph(&self, node_ids: &HashSet<NodeId>) -> Graph {
         let mut sub = Graph::new();
+        sub.types = self.types.clone();
 
         // Copy selected nodes
         for id in node_ids {
@@ -428,6 +600,49 @@ impl Graph {
         self.regions.values()
     }
 
+    /// The graph's named type definitions.
+    pub fn types(&self) -> &TypeTable {
+        &self.types
+    }
+
+    /// Define (or redefine) a named type, returning the previous definition.
+    pub fn define_type(&mut self, name: impl Into<String>, def: TypeDef) -> Option<TypeDef> {
+        self.types.define(name, def)
+    }
+
+    /// Expand the named and parameterized types in `ty` using the graph's
+    /// type definitions.
+    pub fn resolve_type(&self, ty: &Type) -> Result<Type, TypeError> {
+        self.types.resolve(ty)
+    }
+
+    /// A copy of the graph with every type resolved against its definitions:
+    /// node signatures, edge types, and region interfaces.
+    ///
+    /// The definitions themselves are kept, so the copy can still be
+    /// printed and extended.
+    pub fn monomorphize(&self) -> Result<Graph, GraphError> {
+        let mut graph = self.clone();
+        for node in graph.nodes.values_mut() {
+            if let Some(sig) = &mut node.type_signature {
+                for ty in sig.inputs.iter_mut().chain(sig.outputs.iter_mut()) {
+                    *ty = self.types.resolve(ty)?;
+                }
+            }
+        }
+        for edge in graph.edges.values_mut() {
+            if let Some(ty) = &mut edge.data_type {
+                *ty = self.types.resolve(ty)?;
+            }
+        }
+        for region in graph.regions.values_mut() {
+            for port in &mut region.interfaces {
+                port.port_type = self.types.resolve(&port.port_type)?;
+            }
+        }
+        Ok(graph)
+    }
+
     /// Check if a node kind is allowed to have back-edges (cycles).
     fn is_cycle_exempt(kind: &NodeKind) -> bool {
         matches!(
@@ -520,67 +735,6 @@ impl Graph {
         Ok(result)
     }
 
-    /// Validate that linear/affine values have the correct number of consumers.
-    ///
-    /// - `Linear` / `Unique`: exactly 1 consumer
-    /// - `Affine`: 0 or 1 consumers
-    /// - Others: any count (skip)
-    pub fn validate_linearity(&self) -> Result<(), Vec<GraphError>> {
-        let mut errors = Vec::new();
-
-        for node in self.nodes.values() {
-            let sig = match &node.type_signature {
-                Some(s) => s,
-                None => continue,
-            };
-
-            for (port_idx, output_type) in sig.outputs.iter().enumerate() {
-                let lin = match output_type.linearity() {
-                    Some(l) => l,
-                    None => continue,
-                };
-
-                // Count outgoing edges from this (node, port_index) pair
-                let consumers = self
-                    .outgoing_edges(&node.id)
-                    .iter()
-                    .filter_map(|eid| self.edges.get(eid))
-                    .filter(|e| e.source.0 == node.id && e.source.1 == port_idx)
-                    .count();
-
-                match lin {
-                    Linearity::Linear | Linearity::Unique => {
-                        if consumers != 1 {
-                            errors.push(GraphError::LinearityViolation {
-                                node: node.id,
-                                port: port_idx,
-                                kind: lin,
-                                consumers,
-                            });
-                        }
-                    }
-                    Linearity::Affine => {
-                        if consumers > 1 {
-                            errors.push(GraphError::LinearityViolation {
-                                node: node.id,
-                                port: port_idx,
-                                kind: lin,
-                                consumers,
-                            });
-                        }
-                    }
-                    Linearity::Shared | Linearity::Counted | Linearity::Unrestricted => {}
-                }
-            }
-        }
-
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
-    }
-
     /// Validate that each node's declared effects are a superset of its predecessors' effects.
     ///
     /// For each node, gathers effects from all predecessor nodes (via incoming edges).
@@ -633,44 +787,129 @@ impl Graph {
 
     /// Validate that edge source/target types are compatible.
     ///
-    /// Returns any proof obligations generated by refinement subtyping.
-    /// Skips edges where either node lacks a TypeSignature.
+    /// Refinement subtyping is decided by interval reasoning where possible
+    /// (see `types::subtype`): implied refinements need nothing, violated
+    /// ones are reported with a counterexample, and the rest are returned as
+    /// proof obligations. Skips edges where either node lacks a TypeSignature.
     pub fn validate_edge_types(&self) -> Result<Vec<ProofObligation>, Vec<GraphError>> {
+        self.edge_obligations()
+            .map(|obs| obs.into_iter().map(|(_, ob)| ob).collect())
+    }
+
+    /// Like `validate_edge_types`, but pairs each obligation with the edge
+    /// that produced it.
+    pub fn edge_obligations(&self) -> Result<Vec<(EdgeId, ProofObligation)>, Vec<GraphError>> {
         let mut errors = Vec::new();
         let mut obligations = Vec::new();
 
         for edge in self.edges.values() {
-            let source_type = self
-                .nodes
-                .get(&edge.source.0)
-                .and_then(|n| n.type_signature.as_ref())
-                .and_then(|sig| sig.outputs.get(edge.source.1));
-
-            let target_type = self
-                .nodes
-                .get(&edge.target.0)
-                .and_then(|n| n.type_signature.as_ref())
-                .and_then(|sig| sig.inputs.get(edge.target.1));
+            match self.edge_compatibility(edge) {
+                Ok(obs) => obligations.extend(obs.into_iter().map(|ob| (edge.id, ob))),
+                Err(e) => errors.push(e),
+            }
+        }
 
-            let (src_ty, tgt_ty) = match (source_type, target_type) {
-                (Some(s), Some(t)) => (s, t),
-                _ => continue,
-            };
+        if errors.is_empty() {
+            Ok(obligations)
+        } else {
+            Err(errors)
+        }
+    }
 
-            match types_compatible(src_ty, tgt_ty) {
-                Ok(obs) => obligations.extend(obs),
-                Err(_) => {
-                    errors.push(GraphError::TypeMismatch {
-                        edge: edge.id,
-                        expected: format!("{tgt_ty}"),
-                        found: format!("{src_ty}"),
-                    });
+    /// Check the types at an edge's ends, as `edge_obligations` does for
+    /// every edge. Untyped ends are not checked.
+    fn edge_compatibility(&self, edge: &Edge) -> Result<Vec<ProofObligation>, GraphError> {
+        let source_type = self
+            .nodes
+            .get(&edge.source.0)
+            .and_then(|n| n.type_signature.as_ref())
+            .and_then(|sig| sig.outputs.get(edge.source.1));
+
+        let target_type = self
+            .nodes
+            .get(&edge.target.0)
+            .and_then(|n| n.type_signature.as_ref())
+            .and_then(|sig| sig.inputs.get(edge.target.1));
+
+        let (src_ty, tgt_ty) = match (source_type, target_type) {
+            (Some(s), Some(t)) => (s, t),
+            _ => return Ok(Vec::new()),
+        };
+
+        self.types.compatible(src_ty, tgt_ty).map_err(|e| match e {
+            e @ (TypeError::ArityMismatch { .. }
+            | TypeError::RecursiveType(_)
+            | TypeError::InvalidArrayLength(_)) => GraphError::TypeResolution(e),
+            TypeError::RefinementViolated { counterexample, .. } => {
+                GraphError::RefinementViolated {
+                    edge: edge.id,
+                    expected: format!("{tgt_ty}"),
+                    found: format!("{src_ty}"),
+                    counterexample,
                 }
             }
+            _ => GraphError::TypeMismatch {
+                edge: edge.id,
+                expected: format!("{tgt_ty}"),
+                found: format!("{src_ty}"),
+            },
+        })
+    }
+
+    /// Validate constant values against the nodes that carry them.
+    ///
+    /// Only `Literal` nodes may carry a value, and it must inhabit the
+    /// node's declared output type. A literal with a declared output type
+    /// other than `Unit` must carry a value.
+    pub fn validate_literals(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+
+        for node in self.nodes.values() {
+            let output = node
+                .type_signature
+                .as_ref()
+                .and_then(|sig| sig.outputs.first());
+            let message = match (&node.kind, &node.value, output) {
+                (NodeKind::Literal, Some(value), Some(ty)) if !value.conforms_to(ty) => {
+                    format!("value {value} does not inhabit {ty}")
+                }
+                (NodeKind::Literal, None, Some(ty)) if *ty.base_type() != Type::Unit => {
+                    format!("no value for output type {ty}")
+                }
+                (NodeKind::Literal, _, _) | (_, None, _) => continue,
+                (kind, Some(_), _) => format!("{kind} node cannot carry a value"),
+            };
+            errors.push(GraphError::InvalidLiteral {
+                node: node.id,
+                message,
+            });
         }
 
         if errors.is_empty() {
-            Ok(obligations)
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check probabilistic nodes, and `Construct` nodes building a
+    /// distribution, against the type rules of `prob::check_signature`.
+    pub fn validate_probabilistic(&self) -> Result<(), Vec<GraphError>> {
+        let errors: Vec<_> = self
+            .nodes
+            .values()
+            .filter_map(|node| {
+                prob::check_signature(node)
+                    .err()
+                    .map(|e| GraphError::InvalidProbabilistic {
+                        node: node.id,
+                        message: e.to_string(),
+                    })
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
         } else {
             Err(errors)
         }
@@ -681,19 +920,52 @@ impl Graph {
     /// Performs three kinds of obligation generation:
     /// 1. Per-node: calls `contract.generate_obligations()` for each contracted node
     /// 2. Edge-crossing: for edges where both source and target have contracts,
-    ///    generates an implication obligation (postcondition => precondition)
-    /// 3. Termination: for Iterate/Recurse/Fixpoint nodes, generates a termination obligation
+    ///    generates an implication obligation (postconditions => precondition)
+    ///    for each target precondition that reads the edge's value, with
+    ///    variables bound to ports via `bind_predicate`
+    /// 3. Termination: for Iterate/Recurse/Fixpoint nodes and iterative
+    ///    regions, generates obligations that the `Variant` measure decreases
+    ///    and is bounded below, or an unprovable one for a loop that has no
+    ///    measure and no justified `Diverge` effect
+    /// 4. Overflow: for Arithmetic nodes with the `proven` overflow policy,
+    ///    generates a no-overflow obligation over the operands' ports
+    /// 5. Resources: for regions with a time, memory or energy budget,
+    ///    generates an obligation that the usage composed from their
+    ///    children fits it
+    /// 6. Domain: for Div, Mod, Pow, Index and Slice nodes, generates the
+    ///    implicit precondition (non-zero divisor, in-bounds index, ...) on
+    ///    the edge carrying the constrained input
+    /// 7. Approximation: for Arithmetic and Approximate nodes with an
+    ///    `Approximate` output, generates an obligation that the error
+    ///    propagated from their inputs fits its `max_error`
     pub fn validate_contracts(&self) -> Vec<ProofObligation> {
+        self.contract_obligations()
+            .into_iter()
+            .map(|(_, ob)| ob)
+            .collect()
+    }
+
+    /// Like `validate_contracts`, but pairs each obligation with the node or
+    /// edge that produced it.
+    pub fn contract_obligations(&self) -> Vec<(ObligationSite, ProofObligation)> {
         let mut obligations = Vec::new();
 
         // A. Per-node obligations
         for node in self.nodes.values() {
             if let Some(ref contract) = node.contract {
-                obligations.extend(contract.generate_obligations());
+                let site = ObligationSite::Node(node.id);
+                obligations.extend(
+                    contract
+                        .generate_obligations()
+                        .into_iter()
+                        .map(|ob| (site, ob)),
+                );
             }
         }
 
-        // B. Edge-crossing obligations
+        // B. Edge-crossing obligations: the source's postconditions, bound to
+        // its ports, must establish each target precondition that reads the
+        // value carried by the edge.
         for edge in self.edges.values() {
             let src_contract = self
                 .nodes
@@ -705,40 +977,85 @@ impl Graph {
                 .and_then(|n| n.contract.as_ref());
 
             if let (Some(src_c), Some(tgt_c)) = (src_contract, tgt_contract) {
-                // For each postcondition of source and precondition of target,
-                // generate an implication obligation: post => pre
-                for post in &src_c.postconditions {
-                    for pre in &tgt_c.preconditions {
-                        obligations.push(ProofObligation {
+                if src_c.postconditions.is_empty() {
+                    continue;
+                }
+                let assumption = src_c
+                    .postconditions
+                    .iter()
+                    .map(|post| self.bind_predicate(edge.source.0, post))
+                    .reduce(Predicate::conjoin)
+                    .expect("non-empty postconditions");
+                let carried = port_var(edge.source);
+
+                for pre in &tgt_c.preconditions {
+                    let pre = self.bind_predicate(edge.target.0, pre);
+                    if !pre.free_vars().contains(&carried) {
+                        continue;
+                    }
+                    obligations.push((
+                        ObligationSite::Edge(edge.id),
+                        ProofObligation {
                             kind: ObligationKind::Precondition,
                             predicate: Predicate::Implies(
-                                Box::new(post.clone()),
-                                Box::new(pre.clone()),
+                                Box::new(assumption.clone()),
+                                Box::new(pre),
                             ),
                             description: "edge-crossing: postcondition of source implies precondition of target".to_string(),
                             status: ProofStatus::Pending,
                             witness: None,
                             waiver: None,
-                        });
-                    }
+                        },
+                    ));
                 }
             }
         }
 
         // C. Termination obligations
         for node in self.nodes.values() {
-            if matches!(
-                node.kind,
-                NodeKind::Iterate | NodeKind::Recurse | NodeKind::Fixpoint
-            ) {
-                obligations.push(ProofObligation {
-                    kind: ObligationKind::Termination,
-                    predicate: Predicate::BoolLit(true),
-                    description: format!("{} node must terminate", node.kind),
-                    status: ProofStatus::Pending,
-                    witness: None,
-                    waiver: None,
-                });
+            obligations.extend(
+                termination_obligations(self, node)
+                    .into_iter()
+                    .map(|ob| (ObligationSite::Node(node.id), ob)),
+            );
+        }
+        for region in self.regions.values() {
+            obligations.extend(
+                region_termination_obligations(self, region)
+                    .into_iter()
+                    .map(|ob| (ObligationSite::Region(region.id), ob)),
+            );
+        }
+
+        // D. Overflow obligations
+        for node in self.nodes.values() {
+            if let Some(ob) = overflow_obligation(self, node) {
+                obligations.push((ObligationSite::Node(node.id), ob));
+            }
+        }
+
+        // E. Resource budgets of regions
+        for region in self.regions.values() {
+            obligations.extend(
+                resource_obligations(self, region)
+                    .into_iter()
+                    .map(|ob| (ObligationSite::Region(region.id), ob)),
+            );
+        }
+
+        // F. Domain obligations of partial operations, on their input edges
+        for node in self.nodes.values() {
+            obligations.extend(
+                domain_obligations(self, node)
+                    .into_iter()
+                    .map(|(edge, ob)| (ObligationSite::Edge(edge), ob)),
+            );
+        }
+
+        // G. Error propagation into approximate outputs
+        for node in self.nodes.values() {
+            if let Some(ob) = approximation_obligation(self, node) {
+                obligations.push((ObligationSite::Node(node.id), ob));
             }
         }
 
@@ -747,15 +1064,15 @@ impl Graph {
 
     /// Run all type-related validation checks.
     ///
-    /// Combines consistency (edge type compatibility), linearity validation,
-    /// effect propagation checks, and contract validation. Returns proof
+    /// Combines consistency (edge type compatibility), literal values,
+    /// probabilistic node signatures, linearity validation, effect propagation checks, and contract validation. Returns proof
     /// obligations from refinement subtyping and contract generation.
     /// Structural validation (`validate()`) should be run separately.
     pub fn validate_types(&self) -> Result<Vec<ProofObligation>, Vec<GraphError>> {
         let mut all_errors = Vec::new();
         let mut all_obligations = Vec::new();
 
-        if let Err(errs) = self.validate_linearity() {
+        if let Err(errs) = self.validate_ownership() {
             all_errors.extend(errs);
         }
 
@@ -768,6 +1085,14 @@ impl Graph {
             Err(errs) => all_errors.extend(errs),
         }
 
+        if let Err(errs) = self.validate_literals() {
+            all_errors.extend(errs);
+        }
+
+        if let Err(errs) = self.validate_probabilistic() {
+            all_errors.extend(errs);
+        }
+
         all_obligations.extend(self.validate_contracts());
 
         if all_errors.is_empty() {
@@ -780,8 +1105,10 @@ impl Graph {
     /// Merge another graph into this one.
     ///
     /// All node, edge, and region IDs in `other` must be disjoint from `self`.
-    /// Returns `MergeConflict` if any ID collides. On success, all nodes, edges,
-    /// regions, and indexes from `other` are copied into `self`.
+    /// Returns `MergeConflict` if any ID collides, and `TypeConflict` if both
+    /// graphs define a type under the same name differently. On success, all
+    /// nodes, edges, regions, type definitions, and indexes from `other` are
+    /// copied into `self`.
     pub fn merge(&mut self, other: &Graph) -> Result<(), GraphError> {
         // Conflict check — scan all IDs before mutating
         for id in other.nodes.keys() {
@@ -809,7 +1136,16 @@ impl Graph {
             }
         }
 
+        for (name, def) in other.types.iter() {
+            if self.types.get(name).is_some_and(|own| own != def) {
+                return Err(GraphError::TypeConflict(name.clone()));
+            }
+        }
+
         // Copy data
+        for (name, def) in other.types.iter() {
+            self.types.define(name.clone(), def.clone());
+        }
         for (id, node) in &other.nodes {
             self.nodes.insert(*id, node.clone());
         }
@@ -1004,18 +1340,24 @@ impl Graph {
     /// Merge another graph into this one and create edges between specified port pairs.
     ///
     /// Each connection specifies `((src_node, src_port), (dst_node, dst_port))`.
-    /// The source and target nodes may come from either graph.
+    /// The source and target nodes may come from either graph. Connections are
+    /// type-checked like any edge: the refinement obligations they generate
+    /// are returned, and on an error this graph is left unchanged.
     pub fn compose(
         &mut self,
         other: &Graph,
         connections: &[(edge::PortRef, edge::PortRef)],
-    ) -> Result<(), GraphError> {
-        self.merge(other)?;
-        for &((src_node, src_port), (dst_node, dst_port)) in connections {
-            let edge = Edge::new((src_node, src_port), (dst_node, dst_port));
-            self.add_edge(edge)?;
+    ) -> Result<Vec<ProofObligation>, GraphError> {
+        let mut composed = self.clone();
+        composed.merge(other)?;
+        let mut obligations = Vec::new();
+        for &(source, target) in connections {
+            let edge = Edge::new(source, target);
+            let id = composed.add_edge(edge)?;
+            obligations.extend(composed.edge_compatibility(&composed.edges[&id])?);
         }
-        Ok(())
+        *self = composed;
+        Ok(obligations)
     }
 
     /// Extract a module (subgraph with boundary information) for the given nodes.
@@ -1059,10 +1401,22 @@ impl Graph {
             }
         }
 
+        let endpoints = |edges: &[BoundaryEdge]| {
+            let mut ends: Vec<_> = edges
+                .iter()
+                .map(|e| (e.internal_node, e.internal_port))
+                .collect();
+            ends.sort();
+            ends.dedup();
+            ends
+        };
+        let interface = graph.interface(&endpoints(&inputs), &endpoints(&outputs));
+
         ModuleInterface {
             graph,
             inputs,
             outputs,
+            interface,
         }
     }
 
@@ -1070,16 +1424,23 @@ impl Graph {
     ///
     /// `port_map` maps `(old_node, port)` → `(new_node, port)` for each boundary
     /// edge endpoint that falls within `old_nodes`.
+    ///
+    /// The replacement must honour the interface of the nodes it replaces
+    /// (see `interface`): incompatible port types or added effects are an
+    /// `InterfaceMismatch` and leave this graph unchanged, and the contract
+    /// refinements that cannot be decided structurally are returned as
+    /// obligations.
     pub fn replace_subgraph(
         &mut self,
         old_nodes: &HashSet<NodeId>,
         replacement: &Graph,
         port_map: &HashMap<(NodeId, usize), (NodeId, usize)>,
-    ) -> Result<(), GraphError> {
+    ) -> Result<Vec<ProofObligation>, GraphError> {
         // 1. Collect boundary edge descriptors before removing anything
         struct BoundaryDescriptor {
             external_endpoint: (NodeId, usize),
             internal_is_source: bool,
+            old_endpoint: (NodeId, usize),
             new_endpoint: (NodeId, usize),
             data_type: Option<Type>,
             lifetime: Lifetime,
@@ -1104,6 +1465,7 @@ impl Graph {
                     descriptors.push(BoundaryDescriptor {
                         external_endpoint: edge.target,
                         internal_is_source: true,
+                        old_endpoint: edge.source,
                         new_endpoint: *new_ep,
                         data_type: edge.data_type.clone(),
                         lifetime: edge.lifetime.clone(),
@@ -1121,6 +1483,7 @@ impl Graph {
                     descriptors.push(BoundaryDescriptor {
                         external_endpoint: edge.source,
                         internal_is_source: false,
+                        old_endpoint: edge.target,
                         new_endpoint: *new_ep,
                         data_type: edge.data_type.clone(),
                         lifetime: edge.lifetime.clone(),
@@ -1138,16 +1501,35 @@ impl Graph {
             }
         }
 
-        // 3. Remove old nodes (this removes their edges and region membership)
+        // 3. Check the replacement against the interface of the old nodes
+        let boundary = |internal_is_source: bool| -> (Vec<_>, Vec<_>) {
+            let mut ends: Vec<_> = descriptors
+                .iter()
+                .filter(|d| d.internal_is_source == internal_is_source)
+                .map(|d| (d.old_endpoint, d.new_endpoint))
+                .collect();
+            ends.sort();
+            ends.dedup();
+            ends.into_iter().unzip()
+        };
+        let (old_inputs, new_inputs) = boundary(false);
+        let (old_outputs, new_outputs) = boundary(true);
+        let original = self
+            .extract_subgraph(old_nodes)
+            .interface(&old_inputs, &old_outputs);
+        let obligations =
+            original.check_replacement(&replacement.interface(&new_inputs, &new_outputs))?;
+
+        // 4. Remove old nodes (this removes their edges and region membership)
         let old_node_ids: Vec<NodeId> = old_nodes.iter().copied().collect();
         for id in &old_node_ids {
             self.remove_node(*id)?;
         }
 
-        // 4. Merge replacement
+        // 5. Merge replacement
         self.merge(replacement)?;
 
-        // 5. Recreate boundary edges
+        // 6. Recreate boundary edges
         for desc in descriptors {
             let mut new_edge = if desc.internal_is_source {
                 Edge::new(desc.new_endpoint, desc.external_endpoint)
@@ -1160,7 +1542,7 @@ impl Graph {
             self.add_edge(new_edge)?;
         }
 
-        Ok(())
+        Ok(obligations)
     }
 
     /// Validate graph well-formedness.
@@ -1230,6 +1612,7 @@ mod tests {
     use crate::graph::edge::Edge;
     use crate::graph::node::{Node, NodeKind};
     use crate::graph::region::{Region, RegionKind};
+    use crate::value::Value;
 
     fn make_literal_node() -> Node {
         Node::new(NodeKind::Literal)
@@ -1803,6 +2186,36 @@ mod tests {
         assert!(result.unwrap().is_empty());
     }
 
+    #[test]
+    fn edge_refinements_decided_without_obligations() {
+        use crate::types::{Type, TypeSignature};
+
+        let mut g = Graph::new();
+        let sensor = Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(
+            Type::i32().refined(Predicate::in_range("value", 0, 100)),
+        ));
+        let adc = Type::i32().refined(Predicate::in_range("x", 0, 4095));
+        let wide = Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(
+            Type::i32().refined(Predicate::in_range("value", 0, 5000)),
+        ));
+        let sink = Node::new(NodeKind::Arithmetic(node::ArithmeticOp::Add))
+            .with_type_signature(TypeSignature::pure_fn(vec![adc.clone(), adc], Type::i32()));
+        let (sensor_id, wide_id, sink_id) = (sensor.id, wide.id, sink.id);
+        g.add_node(sensor).unwrap();
+        g.add_node(wide).unwrap();
+        g.add_node(sink).unwrap();
+        g.add_edge(Edge::new((sensor_id, 0), (sink_id, 0))).unwrap();
+        assert!(g.validate_edge_types().unwrap().is_empty());
+
+        let rejected = g.add_edge(Edge::new((wide_id, 0), (sink_id, 1))).unwrap();
+        let errors = g.validate_edge_types().unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [GraphError::RefinementViolated { edge, counterexample, .. }]
+                if *edge == rejected && counterexample.to_string() == "value = 4096"
+        ));
+    }
+
     #[test]
     fn edge_type_compatibility_fail() {
         use crate::types::{Type, TypeSignature};
@@ -1833,8 +2246,9 @@ mod tests {
         use crate::types::{Type, TypeSignature};
 
         let mut g = Graph::new();
-        let n1 =
-            Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
+        let n1 = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_value(Value::i32(1));
         let n2 = Node::new(NodeKind::Arithmetic(node::ArithmeticOp::Add)).with_type_signature(
             TypeSignature::pure_fn(vec![Type::i32(), Type::i32()], Type::i32()),
         );
@@ -1849,6 +2263,35 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn literal_values_checked_against_signature() {
+        use crate::types::{Type, TypeSignature};
+
+        let mut g = Graph::new();
+        let wrong_width = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::u8()))
+            .with_value(Value::i32(1));
+        let missing = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::Tuple(vec![Type::Bool])));
+        let not_literal = Node::new(NodeKind::Annotate).with_value(Value::Bool(true));
+        let composite = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::Array {
+                element: Box::new(Type::u8()),
+                length: 2,
+            }))
+            .with_value(Value::Array(vec![Value::u8(1), Value::u8(2)]));
+        let bad = [wrong_width.id, missing.id, not_literal.id];
+        for n in [wrong_width, missing, not_literal, composite] {
+            g.add_node(n).unwrap();
+        }
+
+        let errors = g.validate_types().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        for e in errors {
+            assert!(matches!(e, GraphError::InvalidLiteral { node, .. } if bad.contains(&node)));
+        }
+    }
+
     #[test]
     fn validate_does_not_check_cycles() {
         // validate() checks structural integrity but NOT the DAG property;
@@ -1942,11 +2385,63 @@ mod tests {
 
         let obs = g.validate_contracts();
         // Should have: 1 postcondition from n1 + 1 precondition from n2 + 1 edge-crossing implication
-        let edge_crossing = obs
+        let edge_crossing: Vec<_> = obs
             .iter()
             .filter(|o| matches!(&o.predicate, Predicate::Implies(..)))
-            .count();
-        assert_eq!(edge_crossing, 1);
+            .collect();
+        assert_eq!(edge_crossing.len(), 1);
+
+        // "output" of n1 and "input" of n2 both name the value on the edge
+        let carried = Predicate::positive(&port_var((id1, 0)));
+        assert_eq!(
+            edge_crossing[0].predicate,
+            Predicate::Implies(Box::new(carried.clone()), Box::new(carried))
+        );
+    }
+
+    #[test]
+    fn edge_crossing_skips_unrelated_preconditions() {
+        use crate::contract::{Contract, PortBinding};
+        use crate::types::Predicate;
+
+        let mut g = Graph::new();
+        let src = Node::new(NodeKind::Read).with_contract(Contract::with_conditions(
+            vec![],
+            vec![
+                Predicate::positive("output"),
+                Predicate::in_range("output", 0, 9),
+            ],
+        ));
+        // Precondition on input 1 is not about the edge into input 0
+        let tgt = Node::new(NodeKind::Arithmetic(node::ArithmeticOp::Div)).with_contract(
+            Contract::with_conditions(
+                vec![Predicate::positive("d"), Predicate::positive("input1")],
+                vec![],
+            )
+            .with_binding("d", PortBinding::input(0)),
+        );
+        let (s_id, t_id) = (src.id, tgt.id);
+        g.add_node(src).unwrap();
+        g.add_node(tgt).unwrap();
+        g.add_edge(Edge::new((s_id, 0), (t_id, 0))).unwrap();
+
+        let implications: Vec<_> = g
+            .validate_contracts()
+            .into_iter()
+            .filter_map(|o| match o.predicate {
+                Predicate::Implies(lhs, rhs) => Some((*lhs, *rhs)),
+                _ => None,
+            })
+            .collect();
+        let carried = port_var((s_id, 0));
+        assert_eq!(implications.len(), 1);
+        assert_eq!(
+            implications[0],
+            (
+                Predicate::positive(&carried).conjoin(Predicate::in_range(&carried, 0, 9)),
+                Predicate::positive(&carried)
+            )
+        );
     }
 
     #[test]
@@ -2041,6 +2536,76 @@ mod tests {
         assert_eq!(g1.node_count(), 2);
     }
 
+    #[test]
+    fn named_edge_types_resolve_through_definitions() {
+        use crate::types::{Type, TypeDef, TypeSignature};
+
+        let mut g = Graph::new();
+        g.define_type("Volts", TypeDef::alias(Type::f32()));
+        g.define_type(
+            "Pair",
+            TypeDef::alias(Type::Tuple(vec![
+                Type::Named("T".into()),
+                Type::Named("T".into()),
+            ]))
+            .with_type_param("T"),
+        );
+        let pair_of = |t: Type| Type::Parameterized {
+            name: "Pair".into(),
+            type_params: vec![t],
+            value_params: vec![],
+        };
+        let n1 = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(pair_of(Type::Named("Volts".into()))));
+        let n2 = Node::new(NodeKind::Read).with_type_signature(TypeSignature::new(
+            vec![Type::Tuple(vec![Type::f32(), Type::f32()])],
+            vec![],
+        ));
+        let (id1, id2) = (n1.id, n2.id);
+        g.add_node(n1).unwrap();
+        g.add_node(n2).unwrap();
+        g.add_edge(Edge::new((id1, 0), (id2, 0))).unwrap();
+        assert!(g.validate_edge_types().is_ok());
+
+        let mono = g.monomorphize().unwrap();
+        assert_eq!(
+            mono.get_node(&id1)
+                .unwrap()
+                .type_signature
+                .as_ref()
+                .unwrap()
+                .outputs[0],
+            Type::Tuple(vec![Type::f32(), Type::f32()])
+        );
+
+        // A definition that refers to itself is reported, not looped on
+        g.define_type(
+            "Volts",
+            TypeDef::alias(Type::Option(Box::new(Type::Named("Volts".into())))),
+        );
+        let errors = g.validate_edge_types().unwrap_err();
+        assert!(matches!(errors[0], GraphError::TypeResolution(_)));
+        assert!(g.monomorphize().is_err());
+    }
+
+    #[test]
+    fn merge_type_definitions() {
+        use crate::types::{Type, TypeDef};
+
+        let mut g1 = Graph::new();
+        g1.define_type("Id", TypeDef::alias(Type::u32()));
+        let mut g2 = Graph::new();
+        g2.define_type("Id", TypeDef::alias(Type::u32()));
+        g2.define_type("Flag", TypeDef::alias(Type::Bool));
+        g1.merge(&g2).unwrap();
+        assert_eq!(g1.types().len(), 2);
+
+        let mut g3 = Graph::new();
+        g3.define_type("Id", TypeDef::alias(Type::u64()));
+        let err = g1.merge(&g3).unwrap_err();
+        assert!(matches!(err, GraphError::TypeConflict(ref name) if name == "Id"));
+    }
+
     #[test]
     fn merge_conflict_detected() {
         let mut g1 = Graph::new();
@@ -2569,6 +3134,43 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn compose_and_replace_check_port_types() {
+        use crate::types::{Type, TypeSignature};
+
+        let typed = |inputs: Vec<Type>, output: Type| {
+            Node::new(NodeKind::Arithmetic(node::ArithmeticOp::Add))
+                .with_type_signature(TypeSignature::pure_fn(inputs, output))
+        };
+        let mut g1 = Graph::new();
+        let src = g1.add_node(typed(vec![], Type::i32())).unwrap();
+        let mut g2 = Graph::new();
+        let wide = g2.add_node(typed(vec![Type::i64()], Type::i64())).unwrap();
+        let result = g1.compose(&g2, &[((src, 0), (wide, 0))]);
+        assert!(matches!(result, Err(GraphError::TypeMismatch { .. })));
+        assert_eq!(g1.node_count(), 1);
+
+        let mut g2 = Graph::new();
+        let mid = g2.add_node(typed(vec![Type::i32()], Type::i32())).unwrap();
+        let sink = g2.add_node(typed(vec![Type::i32()], Type::i32())).unwrap();
+        g2.add_edge(Edge::new((mid, 0), (sink, 0))).unwrap();
+        assert!(g1.compose(&g2, &[((src, 0), (mid, 0))]).unwrap().is_empty());
+
+        // Replacing `mid` with a node of another type breaks its interface
+        let mut replacement = Graph::new();
+        let new_mid = replacement
+            .add_node(typed(vec![Type::i64()], Type::i64()))
+            .unwrap();
+        let old_nodes: HashSet<NodeId> = [mid].into_iter().collect();
+        let port_map = HashMap::from([((mid, 0), (new_mid, 0))]);
+        let result = g1.replace_subgraph(&old_nodes, &replacement, &port_map);
+        assert!(matches!(
+            result,
+            Err(GraphError::InterfaceMismatch { ref item, .. }) if item == "port in0"
+        ));
+        assert!(g1.get_node(&mid).is_some());
+    }
+
     #[test]
     fn inline_region_preserves_edges() {
         let mut g = Graph::new();
@@ -2590,4 +3192,34 @@ mod tests {
         assert_eq!(g.outgoing_edges(&id1).len(), 1);
         assert_eq!(g.incoming_edges(&id2).len(), 1);
     }
+
+    #[test]
+    fn serialization_independent_of_insertion_order() {
+        let mut n1 = make_literal_node();
+        n1.annotations.insert("a".into(), "1".into());
+        n1.annotations.insert("b".into(), "2".into());
+        let n2 = make_literal_node();
+        let n3 = make_arithmetic_node();
+        let e1 = Edge::new((n1.id, 0), (n3.id, 0));
+        let e2 = Edge::new((n2.id, 0), (n3.id, 1));
+
+        let mut forward = Graph::new();
+        forward.add_node(n1.clone()).unwrap();
+        forward.add_node(n2.clone()).unwrap();
+        forward.add_node(n3.clone()).unwrap();
+        forward.add_edge(e1.clone()).unwrap();
+        forward.add_edge(e2.clone()).unwrap();
+
+        let mut backward = Graph::new();
+        backward.add_node(n3).unwrap();
+        backward.add_node(n2).unwrap();
+        backward.add_node(n1).unwrap();
+        backward.add_edge(e2).unwrap();
+        backward.add_edge(e1).unwrap();
+
+        assert_eq!(
+            serde_json::to_vec(&forward).unwrap(),
+            serde_json::to_vec(&backward).unwrap()
+        );
+    }
 }
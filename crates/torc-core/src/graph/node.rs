@@ -11,8 +11,10 @@ use uuid::Uuid;
 use crate::contract::Contract;
 use crate::provenance::Provenance;
 use crate::types::TypeSignature;
+use crate::value::Value;
 
-/// Globally unique, content-addressed node identifier.
+/// Globally unique node identifier: random from `Node::new`, derived from
+/// content by `Graph::canonicalize`.
 pub type NodeId = Uuid;
 
 /// Arithmetic operations.
@@ -159,7 +161,11 @@ pub struct Node {
     pub contract: Option<Contract>,
     /// Provenance: who created this node, when, and why.
     pub provenance: Option<Provenance>,
+    /// Constant value produced by a `Literal` node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
     /// Extensible metadata (optimization hints, safety class, etc.).
+    #[serde(serialize_with = "super::ordered_map")]
     pub annotations: HashMap<String, String>,
 }
 
@@ -172,6 +178,7 @@ impl Node {
             type_signature: None,
             contract: None,
             provenance: None,
+            value: None,
             annotations: HashMap::new(),
         }
     }
@@ -184,6 +191,7 @@ impl Node {
             type_signature: None,
             contract: None,
             provenance: None,
+            value: None,
             annotations: HashMap::new(),
         }
     }
@@ -205,4 +213,10 @@ impl Node {
         self.provenance = Some(provenance);
         self
     }
+
+    /// Attach the constant value of a `Literal` node.
+    pub fn with_value(mut self, value: Value) -> Self {
+        self.value = Some(value);
+        self
+    }
 }
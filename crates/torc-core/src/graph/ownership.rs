@@ -0,0 +1,646 @@
+//! Ownership checking.
+//!
+//! A value is owned according to the linearity of the output port that
+//! produces it, and is consumed once by every consumer that runs. Which
+//! consumers run depends on the control path:
+//!
+//! - `Select` and `Switch` read only the chosen data input, so their data
+//!   ports are the arms of a choice. The choice covers every path only when
+//!   each data port is an arm.
+//! - A `Conditional` region runs only when something outside it demands its
+//!   results. A region whose results all flow into the same data input of a
+//!   `Select` or `Switch` is that arm of the node's choice. Any other
+//!   conditional region may be skipped or run independently of its
+//!   siblings.
+//! - A consumer in an `Iterative` region that does not contain the producer
+//!   runs once per iteration.
+//!
+//! Regions that contain both producer and consumer do not split paths.
+//!
+//! | Linearity                | Consumers on every path                        |
+//! |--------------------------|------------------------------------------------|
+//! | `Linear`, `Unique`       | exactly one                                    |
+//! | `Affine`                 | at most one                                    |
+//! | `Counted`                | any, but none may take unique ownership of it  |
+//! | `Shared`, `Unrestricted` | any                                            |
+//!
+//! An edge with a `Lifetime::Region` must end inside that region. The handle
+//! an `Allocate` node produces on port 0 must reach exactly one `Deallocate`
+//! on every path, and nothing downstream of the `Deallocate` may use it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::constraints::Lifetime;
+use super::edge::{Edge, PortRef};
+use super::node::{NodeId, NodeKind};
+use super::region::{RegionId, RegionKind};
+use super::{Graph, GraphError};
+use crate::types::Linearity;
+
+/// How many times a value is consumed, over all control paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Uses {
+    fewest: usize,
+    /// `None` when a loop may consume it any number of times.
+    most: Option<usize>,
+}
+
+impl Uses {
+    fn exactly(n: usize) -> Self {
+        Self {
+            fewest: n,
+            most: Some(n),
+        }
+    }
+
+    /// Consumed by both `self` and `other`.
+    fn and(self, other: Uses) -> Self {
+        Self {
+            fewest: self.fewest + other.fewest,
+            most: self.most.zip(other.most).map(|(a, b)| a + b),
+        }
+    }
+}
+
+/// Where a choice between control paths is made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Choice {
+    /// Whether a conditional region that is no arm of a choice runs.
+    Optional(RegionId),
+    /// Between the data inputs of a `Select` or `Switch`.
+    Inputs(NodeId),
+}
+
+/// One arm of a `Choice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Arm {
+    Region(RegionId),
+    Port(usize),
+}
+
+/// What must happen for a consumer to run, outermost first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Step {
+    Branch(Choice, Arm),
+    Repeat(RegionId),
+}
+
+impl Graph {
+    /// Check ownership: linearity on every control path, region lifetimes
+    /// on edges, and allocation pairing. See the module documentation.
+    pub fn validate_ownership(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        for check in [
+            Self::validate_linearity,
+            Self::validate_lifetimes,
+            Self::validate_allocations,
+        ] {
+            if let Err(errs) = check(self) {
+                errors.extend(errs);
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validate that linear/affine values have the correct number of consumers
+    /// on every control path.
+    ///
+    /// - `Linear` / `Unique`: exactly 1 consumer
+    /// - `Affine`: 0 or 1 consumers
+    /// - `Counted`: no consumer takes a shared value as `Linear` or `Unique`
+    /// - Others: any count (skip)
+    pub fn validate_linearity(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        let mut nodes: Vec<_> = self.nodes.values().collect();
+        nodes.sort_by_key(|n| n.id);
+
+        for node in nodes {
+            let Some(sig) = &node.type_signature else {
+                continue;
+            };
+            for (port, output_type) in sig.outputs.iter().enumerate() {
+                let Some(kind) = output_type.linearity() else {
+                    continue;
+                };
+                let consumers = self.consumers((node.id, port));
+                let guards: Vec<_> = consumers
+                    .iter()
+                    .map(|e| self.guard(node.id, e.target))
+                    .collect();
+                let uses = self.count_uses(&guards.iter().map(Vec::as_slice).collect::<Vec<_>>());
+                let violated = match kind {
+                    Linearity::Linear | Linearity::Unique => uses != Uses::exactly(1),
+                    Linearity::Affine => !matches!(uses.most, Some(m) if m <= 1),
+                    Linearity::Counted => {
+                        if consumers.len() > 1 {
+                            errors.extend(
+                                consumers
+                                    .iter()
+                                    .filter(|e| self.takes_unique(e.target))
+                                    .map(|e| GraphError::SharedOwnership {
+                                        node: node.id,
+                                        port,
+                                        consumer: e.target.0,
+                                        references: consumers.len(),
+                                    }),
+                            );
+                        }
+                        false
+                    }
+                    Linearity::Shared | Linearity::Unrestricted => false,
+                };
+                if !violated {
+                    continue;
+                }
+                if guards.iter().all(Vec::is_empty) {
+                    errors.push(GraphError::LinearityViolation {
+                        node: node.id,
+                        port,
+                        kind,
+                        consumers: consumers.len(),
+                    });
+                } else {
+                    errors.push(GraphError::PathLinearityViolation {
+                        node: node.id,
+                        port,
+                        kind,
+                        fewest: uses.fewest,
+                        most: uses.most,
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validate that every edge scoped to a region ends inside it.
+    pub fn validate_lifetimes(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        let mut edges: Vec<_> = self.edges.values().collect();
+        edges.sort_by_key(|e| e.id);
+
+        for edge in edges {
+            let Lifetime::Region(region) = edge.lifetime else {
+                continue;
+            };
+            if self.get_region(&region).is_none() {
+                errors.push(GraphError::RegionNotFound(region));
+            } else if !self.enclosing_regions(&edge.target.0).contains(&region) {
+                errors.push(GraphError::LifetimeEscape {
+                    edge: edge.id,
+                    region,
+                    target: edge.target.0,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validate that every allocation is freed exactly once on every control
+    /// path and is not used after it is freed.
+    pub fn validate_allocations(&self) -> Result<(), Vec<GraphError>> {
+        let mut errors = Vec::new();
+        let mut allocations: Vec<_> = self
+            .nodes
+            .values()
+            .filter(|n| n.kind == NodeKind::Allocate)
+            .map(|n| n.id)
+            .collect();
+        allocations.sort();
+
+        for allocation in allocations {
+            let users = self.consumers((allocation, 0));
+            let frees: Vec<_> = users
+                .iter()
+                .filter(|e| {
+                    e.target.1 == 0 && self.kind_of(&e.target.0) == Some(&NodeKind::Deallocate)
+                })
+                .collect();
+            let guards: Vec<_> = frees
+                .iter()
+                .map(|e| self.guard(allocation, e.target))
+                .collect();
+            let uses = self.count_uses(&guards.iter().map(Vec::as_slice).collect::<Vec<_>>());
+            if uses != Uses::exactly(1) {
+                errors.push(GraphError::UnpairedAllocation {
+                    node: allocation,
+                    fewest: uses.fewest,
+                    most: uses.most,
+                });
+            }
+
+            for free in &frees {
+                let after = self.downstream(free.target.0);
+                let mut late: Vec<_> = users
+                    .iter()
+                    .map(|e| e.target.0)
+                    .filter(|n| after.contains(n))
+                    .collect();
+                late.sort();
+                late.dedup();
+                errors.extend(late.into_iter().map(|node| GraphError::UseAfterFree {
+                    node,
+                    allocation,
+                    free: free.target.0,
+                }));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Edges leaving an output port.
+    fn consumers(&self, (node, port): PortRef) -> Vec<&Edge> {
+        self.outgoing_edges(&node)
+            .iter()
+            .filter_map(|id| self.edges.get(id))
+            .filter(|e| e.source.1 == port)
+            .collect()
+    }
+
+    fn kind_of(&self, node: &NodeId) -> Option<&NodeKind> {
+        self.get_node(node).map(|n| &n.kind)
+    }
+
+    /// Regions containing a node, innermost first.
+    fn enclosing_regions(&self, node: &NodeId) -> Vec<RegionId> {
+        let mut chain = Vec::new();
+        let mut current = self.containing_region(node).copied();
+        while let Some(region) = current {
+            if chain.contains(&region) {
+                break;
+            }
+            chain.push(region);
+            current = self.parent_region(&region).copied();
+        }
+        chain
+    }
+
+    /// The steps on which a consumer of a value produced by `producer` runs.
+    fn guard(&self, producer: NodeId, (consumer, port): PortRef) -> Vec<Step> {
+        let shared = self.enclosing_regions(&producer);
+        let mut steps: Vec<_> = self
+            .enclosing_regions(&consumer)
+            .into_iter()
+            .rev()
+            .filter(|r| !shared.contains(r))
+            .filter_map(|r| match self.get_region(&r)?.kind {
+                RegionKind::Conditional => Some(match self.branch_of(r) {
+                    Some((node, port)) => Step::Branch(Choice::Inputs(node), Arm::Port(port)),
+                    None => Step::Branch(Choice::Optional(r), Arm::Region(r)),
+                }),
+                RegionKind::Iterative => Some(Step::Repeat(r)),
+                RegionKind::Sequential | RegionKind::Parallel | RegionKind::Atomic => None,
+            })
+            .collect();
+        if port > 0
+            && matches!(
+                self.kind_of(&consumer),
+                Some(NodeKind::Select | NodeKind::Switch)
+            )
+        {
+            steps.push(Step::Branch(Choice::Inputs(consumer), Arm::Port(port)));
+        }
+        steps
+    }
+
+    /// The `Select` or `Switch` data input that every result of a
+    /// conditional region flows into, making the region that arm.
+    fn branch_of(&self, region: RegionId) -> Option<(NodeId, usize)> {
+        let mut members = HashSet::new();
+        let mut pending = vec![region];
+        while let Some(r) = pending.pop() {
+            members.extend(self.get_region(&r)?.children.iter().copied());
+            pending.extend(self.child_regions(&r));
+        }
+        let mut targets = members
+            .iter()
+            .flat_map(|n| self.outgoing_edges(n))
+            .filter_map(|id| self.edges.get(id))
+            .map(|e| e.target)
+            .filter(|(node, _)| !members.contains(node));
+        let first = targets.next()?;
+        let is_arm = first.1 > 0
+            && matches!(
+                self.kind_of(&first.0),
+                Some(NodeKind::Select | NodeKind::Switch)
+            );
+        (is_arm && targets.all(|t| t == first)).then_some(first)
+    }
+
+    /// How many of the consumers with these guards run, over all paths.
+    fn count_uses(&self, guards: &[&[Step]]) -> Uses {
+        let mut total = Uses::exactly(guards.iter().filter(|g| g.is_empty()).count());
+        let mut loops: HashMap<RegionId, Vec<&[Step]>> = HashMap::new();
+        let mut choices: HashMap<Choice, HashMap<Arm, Vec<&[Step]>>> = HashMap::new();
+        for guard in guards {
+            match guard.split_first() {
+                None => {}
+                Some((Step::Repeat(region), rest)) => loops.entry(*region).or_default().push(rest),
+                Some((Step::Branch(choice, arm), rest)) => choices
+                    .entry(*choice)
+                    .or_default()
+                    .entry(*arm)
+                    .or_default()
+                    .push(rest),
+            }
+        }
+
+        for body in loops.values() {
+            let once = self.count_uses(body);
+            total = total.and(Uses {
+                fewest: once.fewest,
+                most: (once.most == Some(0)).then_some(0),
+            });
+        }
+        for (choice, arms) in &choices {
+            let counts: Vec<_> = arms.values().map(|arm| self.count_uses(arm)).collect();
+            let covered = match choice {
+                Choice::Optional(_) => false,
+                Choice::Inputs(node) => arms.len() >= self.data_inputs(node),
+            };
+            total = total.and(Uses {
+                fewest: if covered {
+                    counts.iter().map(|u| u.fewest).min().unwrap_or(0)
+                } else {
+                    0
+                },
+                most: counts.iter().try_fold(0, |most, u| Some(most.max(u.most?))),
+            });
+        }
+        total
+    }
+
+    /// The number of connected data inputs of a `Select` or `Switch`.
+    fn data_inputs(&self, node: &NodeId) -> usize {
+        let ports: HashSet<_> = self
+            .incoming_edges(node)
+            .iter()
+            .filter_map(|id| self.edges.get(id))
+            .map(|e| e.target.1)
+            .filter(|&port| port > 0)
+            .collect();
+        ports.len()
+    }
+
+    /// Whether the input port takes unique ownership of what it receives.
+    fn takes_unique(&self, (node, port): PortRef) -> bool {
+        self.get_node(&node)
+            .and_then(|n| n.type_signature.as_ref())
+            .and_then(|sig| sig.inputs.get(port))
+            .and_then(|ty| ty.linearity())
+            .is_some_and(|l| matches!(l, Linearity::Linear | Linearity::Unique))
+    }
+
+    /// Nodes reachable from `start` along edges, excluding `start`.
+    fn downstream(&self, start: NodeId) -> HashSet<NodeId> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            for edge in self
+                .outgoing_edges(&id)
+                .iter()
+                .filter_map(|e| self.edges.get(e))
+            {
+                if seen.insert(edge.target.0) {
+                    queue.push_back(edge.target.0);
+                }
+            }
+        }
+        seen.remove(&start);
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::node::Node;
+    use crate::graph::region::Region;
+    use crate::types::{Type, TypeSignature};
+
+    fn linear_source(g: &mut Graph, linearity: Linearity) -> NodeId {
+        g.add_node(
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(Type::i32().with_linearity(linearity))),
+        )
+        .unwrap()
+    }
+
+    fn sink(g: &mut Graph, input: Type) -> NodeId {
+        g.add_node(Node::new(NodeKind::Write).with_type_signature(TypeSignature::sink(input)))
+            .unwrap()
+    }
+
+    #[test]
+    fn unique_values_consumed_once_per_branch() {
+        let mut g = Graph::new();
+        let value = linear_source(&mut g, Linearity::Unique);
+        let cond = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let select = g.add_node(Node::new(NodeKind::Select)).unwrap();
+        g.add_edge(Edge::new((cond, 0), (select, 0))).unwrap();
+        let arm =
+            |g: &mut Graph, port| {
+                let node = g
+                    .add_node(Node::new(NodeKind::Conversion).with_type_signature(
+                        TypeSignature::pure_fn(vec![Type::i32()], Type::i32()),
+                    ))
+                    .unwrap();
+                g.add_edge(Edge::new((value, 0), (node, 0))).unwrap();
+                g.add_edge(Edge::new((node, 0), (select, port))).unwrap();
+                node
+            };
+        let then = arm(&mut g, 1);
+        let then_region = g
+            .add_region(Region::new(RegionKind::Conditional, vec![then]))
+            .unwrap();
+
+        // One conditional consumer: nothing consumes it when the branch is skipped
+        let other = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let plain = g.add_edge(Edge::new((other, 0), (select, 2))).unwrap();
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::PathLinearityViolation {
+                fewest: 0,
+                most: Some(1),
+                ..
+            }
+        ));
+
+        // Both arms of the select: exactly once either way
+        g.remove_edge(plain).unwrap();
+        let other = arm(&mut g, 2);
+        g.add_region(Region::new(RegionKind::Conditional, vec![other]))
+            .unwrap();
+        assert!(g.validate_linearity().is_ok());
+
+        // A second consumer in the same branch consumes it twice there
+        let again = sink(&mut g, Type::i32());
+        g.add_edge(Edge::new((value, 0), (again, 0))).unwrap();
+        g.remove_region(then_region).unwrap();
+        g.add_region(Region::new(RegionKind::Conditional, vec![then, again]))
+            .unwrap();
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::PathLinearityViolation {
+                fewest: 1,
+                most: Some(2),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn independent_conditionals_may_both_run() {
+        let mut g = Graph::new();
+        let value = linear_source(&mut g, Linearity::Linear);
+        for _ in 0..2 {
+            let consumer = sink(&mut g, Type::i32());
+            g.add_edge(Edge::new((value, 0), (consumer, 0))).unwrap();
+            g.add_region(Region::new(RegionKind::Conditional, vec![consumer]))
+                .unwrap();
+        }
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::PathLinearityViolation {
+                fewest: 0,
+                most: Some(2),
+                ..
+            }
+        ));
+
+        // Freeing an allocation in two independent branches may free it twice
+        let mut g = Graph::new();
+        let alloc = g.add_node(Node::new(NodeKind::Allocate)).unwrap();
+        for _ in 0..2 {
+            let free = g.add_node(Node::new(NodeKind::Deallocate)).unwrap();
+            g.add_edge(Edge::new((alloc, 0), (free, 0))).unwrap();
+            g.add_region(Region::new(RegionKind::Conditional, vec![free]))
+                .unwrap();
+        }
+        let errors = g.validate_allocations().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::UnpairedAllocation {
+                fewest: 0,
+                most: Some(2),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn select_arms_and_loops_split_paths() {
+        let mut g = Graph::new();
+        let cond = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let value = linear_source(&mut g, Linearity::Linear);
+        let other = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let select = g.add_node(Node::new(NodeKind::Select)).unwrap();
+        g.add_edge(Edge::new((cond, 0), (select, 0))).unwrap();
+        g.add_edge(Edge::new((value, 0), (select, 1))).unwrap();
+        g.add_edge(Edge::new((other, 0), (select, 2))).unwrap();
+
+        // Dropped when the other arm is chosen
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::PathLinearityViolation { fewest: 0, .. }
+        ));
+
+        let mut g = Graph::new();
+        let value = linear_source(&mut g, Linearity::Affine);
+        let body = sink(&mut g, Type::i32());
+        g.add_edge(Edge::new((value, 0), (body, 0))).unwrap();
+        g.add_region(Region::new(RegionKind::Iterative, vec![body]))
+            .unwrap();
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[0],
+            GraphError::PathLinearityViolation { most: None, .. }
+        ));
+    }
+
+    #[test]
+    fn counted_values_are_not_taken_uniquely() {
+        let mut g = Graph::new();
+        let value = linear_source(&mut g, Linearity::Counted);
+        let reader = sink(&mut g, Type::i32());
+        let owner = sink(&mut g, Type::i32().with_linearity(Linearity::Unique));
+        g.add_edge(Edge::new((value, 0), (owner, 0))).unwrap();
+        assert!(g.validate_linearity().is_ok());
+
+        g.add_edge(Edge::new((value, 0), (reader, 0))).unwrap();
+        let errors = g.validate_linearity().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [GraphError::SharedOwnership { consumer, references: 2, .. }] if consumer == owner
+        ));
+    }
+
+    #[test]
+    fn region_lifetimes_do_not_escape() {
+        let mut g = Graph::new();
+        let inner = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let user = g.add_node(Node::new(NodeKind::Write)).unwrap();
+        let outside = g.add_node(Node::new(NodeKind::Write)).unwrap();
+        let region = g
+            .add_region(Region::new(RegionKind::Sequential, vec![inner, user]))
+            .unwrap();
+        let scoped =
+            |target| Edge::new((inner, 0), (target, 0)).with_lifetime(Lifetime::Region(region));
+        g.add_edge(scoped(user)).unwrap();
+        assert!(g.validate_lifetimes().is_ok());
+
+        let escaping = g.add_edge(scoped(outside)).unwrap();
+        let errors = g.validate_lifetimes().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [GraphError::LifetimeEscape { edge, target, .. }] if edge == escaping && target == outside
+        ));
+    }
+
+    #[test]
+    fn allocations_freed_once_and_not_used_after() {
+        let mut g = Graph::new();
+        let alloc = g.add_node(Node::new(NodeKind::Allocate)).unwrap();
+        assert!(matches!(
+            g.validate_allocations().unwrap_err()[..],
+            [GraphError::UnpairedAllocation { fewest: 0, .. }]
+        ));
+
+        let free = g.add_node(Node::new(NodeKind::Deallocate)).unwrap();
+        g.add_edge(Edge::new((alloc, 0), (free, 0))).unwrap();
+        assert!(g.validate_ownership().is_ok());
+
+        // A write ordered after the free still holds the handle
+        let write = g.add_node(Node::new(NodeKind::Write)).unwrap();
+        g.add_edge(Edge::new((alloc, 0), (write, 0))).unwrap();
+        g.add_edge(Edge::new((free, 0), (write, 1))).unwrap();
+        let errors = g.validate_allocations().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [GraphError::UseAfterFree { node, free: f, .. }] if node == write && f == free
+        ));
+    }
+}
@@ -0,0 +1,463 @@
+//! Reversible graph patches.
+//!
+//! A `GraphPatch` records, for every node, edge, region and named type that
+//! changed, its state before and after the change (`None` when absent).
+//! Elements are identified by ID (types by name). Because both states are
+//! recorded, a patch can be checked against the graph it is applied to and
+//! inverted by swapping them.
+//!
+//! Patches are plain serde values, so they can be stored or sent as JSON.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::edge::{Edge, EdgeId};
+use super::node::{Node, NodeId};
+use super::region::{Region, RegionId};
+use super::{Graph, GraphError};
+use crate::hash::content_hash;
+use crate::types::TypeDef;
+
+/// The graph element a patch operation changes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PatchTarget {
+    Node(NodeId),
+    Edge(EdgeId),
+    Region(RegionId),
+    Type(String),
+}
+
+impl fmt::Display for PatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchTarget::Node(id) => write!(f, "node {id}"),
+            PatchTarget::Edge(id) => write!(f, "edge {id}"),
+            PatchTarget::Region(id) => write!(f, "region {id}"),
+            PatchTarget::Type(name) => write!(f, "type {name}"),
+        }
+    }
+}
+
+/// The change to a single element: its state before and after.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PatchOp {
+    Node {
+        before: Option<Box<Node>>,
+        after: Option<Box<Node>>,
+    },
+    Edge {
+        before: Option<Edge>,
+        after: Option<Edge>,
+    },
+    Region {
+        before: Option<Region>,
+        after: Option<Region>,
+    },
+    Type {
+        name: String,
+        before: Option<TypeDef>,
+        after: Option<TypeDef>,
+    },
+}
+
+impl PatchOp {
+    /// The element this operation changes.
+    pub fn target(&self) -> PatchTarget {
+        match self {
+            PatchOp::Node { before, after } => {
+                PatchTarget::Node(element_id(before, after, |n| n.id))
+            }
+            PatchOp::Edge { before, after } => {
+                PatchTarget::Edge(element_id(before, after, |e| e.id))
+            }
+            PatchOp::Region { before, after } => {
+                PatchTarget::Region(element_id(before, after, |r| r.id))
+            }
+            PatchOp::Type { name, .. } => PatchTarget::Type(name.clone()),
+        }
+    }
+
+    /// The same change, undone.
+    pub fn inverse(&self) -> PatchOp {
+        match self.clone() {
+            PatchOp::Node { before, after } => PatchOp::Node {
+                before: after,
+                after: before,
+            },
+            PatchOp::Edge { before, after } => PatchOp::Edge {
+                before: after,
+                after: before,
+            },
+            PatchOp::Region { before, after } => PatchOp::Region {
+                before: after,
+                after: before,
+            },
+            PatchOp::Type {
+                name,
+                before,
+                after,
+            } => PatchOp::Type {
+                name,
+                before: after,
+                after: before,
+            },
+        }
+    }
+}
+
+/// A set of element changes that takes one graph version to another.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphPatch {
+    /// At most one operation per element, in target order.
+    pub ops: Vec<PatchOp>,
+}
+
+/// Whether two values serialize identically.
+pub(crate) fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    content_hash(a) == content_hash(b)
+}
+
+impl GraphPatch {
+    /// The patch that takes `old` to `new`, matching elements by ID.
+    pub fn between(old: &Graph, new: &Graph) -> GraphPatch {
+        let mut ops = Vec::new();
+
+        let node_ids: HashSet<NodeId> = old.nodes().chain(new.nodes()).map(|n| n.id).collect();
+        for id in node_ids {
+            let (before, after) = (old.get_node(&id), new.get_node(&id));
+            if !same(&before, &after) {
+                ops.push(PatchOp::Node {
+                    before: before.cloned().map(Box::new),
+                    after: after.cloned().map(Box::new),
+                });
+            }
+        }
+        let edge_ids: HashSet<EdgeId> = old.edges().chain(new.edges()).map(|e| e.id).collect();
+        for id in edge_ids {
+            let (before, after) = (old.get_edge(&id), new.get_edge(&id));
+            if !same(&before, &after) {
+                ops.push(PatchOp::Edge {
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+        let region_ids: HashSet<RegionId> =
+            old.regions().chain(new.regions()).map(|r| r.id).collect();
+        for id in region_ids {
+            let (before, after) = (old.get_region(&id), new.get_region(&id));
+            if !same(&before, &after) {
+                ops.push(PatchOp::Region {
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+        let names: HashSet<&String> = old
+            .types()
+            .iter()
+            .chain(new.types().iter())
+            .map(|(name, _)| name)
+            .collect();
+        for name in names {
+            let (before, after) = (old.types().get(name), new.types().get(name));
+            if before != after {
+                ops.push(PatchOp::Type {
+                    name: name.clone(),
+                    before: before.cloned(),
+                    after: after.cloned(),
+                });
+            }
+        }
+
+        GraphPatch::from_ops(ops)
+    }
+
+    /// A patch from operations, sorted by target.
+    pub fn from_ops(mut ops: Vec<PatchOp>) -> GraphPatch {
+        ops.sort_by_key(PatchOp::target);
+        GraphPatch { ops }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// The patch that undoes this one.
+    pub fn inverse(&self) -> GraphPatch {
+        GraphPatch::from_ops(self.ops.iter().map(PatchOp::inverse).collect())
+    }
+
+    /// Operations keyed by target.
+    pub fn by_target(&self) -> BTreeMap<PatchTarget, &PatchOp> {
+        self.ops.iter().map(|op| (op.target(), op)).collect()
+    }
+}
+
+impl Graph {
+    /// Apply a patch.
+    ///
+    /// Every element the patch changes must currently be in the patch's
+    /// `before` state, otherwise this returns `StalePatch`. The graph is left
+    /// unchanged on error.
+    pub fn apply_patch(&mut self, patch: &GraphPatch) -> Result<(), GraphError> {
+        let mut patched = self.clone();
+        patched.apply_ops(patch, &mut |_, err| Err(err))?;
+        *self = patched;
+        Ok(())
+    }
+
+    /// Apply a patch's operations in dependency order: edge and region
+    /// removals, node changes, types, then region and edge insertions.
+    ///
+    /// `on_error` decides whether a failing operation aborts the patch or is
+    /// skipped. A skipped operation, including one that is stale, is not
+    /// applied; the skipped operations are returned with their reasons.
+    pub(crate) fn apply_ops(
+        &mut self,
+        patch: &GraphPatch,
+        on_error: &mut dyn FnMut(PatchTarget, GraphError) -> Result<(), GraphError>,
+    ) -> Result<Vec<(PatchTarget, String)>, GraphError> {
+        let mut skipped: Vec<(PatchTarget, String)> = Vec::new();
+        for op in &patch.ops {
+            if let Err(err) = self.check_before(op) {
+                let reason = err.to_string();
+                on_error(op.target(), err)?;
+                skipped.push((op.target(), reason));
+            }
+        }
+
+        let mut run = |graph: &mut Graph,
+                       op: &PatchOp,
+                       step: &dyn Fn(&mut Graph) -> Result<(), GraphError>|
+         -> Result<(), GraphError> {
+            let target = op.target();
+            if skipped.iter().any(|(t, _)| *t == target) {
+                return Ok(());
+            }
+            if let Err(err) = step(graph) {
+                let reason = err.to_string();
+                on_error(target.clone(), err)?;
+                skipped.push((target, reason));
+            }
+            Ok(())
+        };
+
+        for op in &patch.ops {
+            if let PatchOp::Edge {
+                before: Some(edge), ..
+            } = op
+            {
+                // Already gone if an endpoint was removed
+                run(self, op, &|g| match g.get_edge(&edge.id) {
+                    Some(_) => g.remove_edge(edge.id),
+                    None => Ok(()),
+                })?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Region {
+                before: Some(region),
+                after: None,
+            } = op
+            {
+                run(self, op, &|g| g.remove_region(region.id))?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Node {
+                before: Some(node),
+                after: None,
+            } = op
+            {
+                run(self, op, &|g| g.remove_node(node.id))?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Node {
+                before,
+                after: Some(node),
+            } = op
+            {
+                run(self, op, &|g| match (before, g.get_node_mut(&node.id)) {
+                    (Some(_), Some(existing)) => {
+                        *existing = (**node).clone();
+                        Ok(())
+                    }
+                    (Some(_), None) => Err(GraphError::NodeNotFound(node.id)),
+                    (None, _) => g.add_node((**node).clone()).map(|_| ()),
+                })?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Type { name, after, .. } = op {
+                run(self, op, &|g| {
+                    match after {
+                        Some(def) => g.types.define(name.clone(), def.clone()),
+                        None => g.types.remove(name),
+                    };
+                    Ok(())
+                })?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Region {
+                after: Some(region),
+                ..
+            } = op
+            {
+                run(self, op, &|g| g.put_region(region.clone()))?;
+            }
+        }
+        for op in &patch.ops {
+            if let PatchOp::Edge {
+                after: Some(edge), ..
+            } = op
+            {
+                run(self, op, &|g| g.add_edge(edge.clone()).map(|_| ()))?;
+            }
+        }
+        Ok(skipped)
+    }
+
+    /// Check that the element an operation changes is in its `before` state.
+    fn check_before(&self, op: &PatchOp) -> Result<(), GraphError> {
+        let current_matches = match op {
+            PatchOp::Node { before, after } => {
+                let id = element_id(before, after, |n| n.id);
+                same(&before.as_deref(), &self.get_node(&id))
+            }
+            PatchOp::Edge { before, after } => {
+                let id = element_id(before, after, |e| e.id);
+                same(&before.as_ref(), &self.get_edge(&id))
+            }
+            PatchOp::Region { before, after } => {
+                let id = element_id(before, after, |r| r.id);
+                same(&before.as_ref(), &self.get_region(&id))
+            }
+            PatchOp::Type { name, before, .. } => before.as_ref() == self.types.get(name),
+        };
+        if current_matches {
+            Ok(())
+        } else {
+            Err(GraphError::StalePatch(op.target().to_string()))
+        }
+    }
+
+    /// Insert a region, or replace the one with the same ID, keeping the
+    /// membership and parent indexes in step.
+    fn put_region(&mut self, region: Region) -> Result<(), GraphError> {
+        let id = region.id;
+        // Validate before touching the old region
+        if let Some(missing) = region.children.iter().find(|c| !self.nodes.contains_key(c)) {
+            return Err(GraphError::NodeNotFound(*missing));
+        }
+        if let Some(old) = self.region_children.remove(&id) {
+            for child in old {
+                if self.node_region.get(&child) == Some(&id) {
+                    self.node_region.remove(&child);
+                }
+            }
+            self.region_parent.remove(&id);
+            self.regions.remove(&id);
+        }
+        self.add_region(region).map(|_| ())
+    }
+}
+
+/// The ID of the element an operation changes.
+fn element_id<T>(before: &Option<T>, after: &Option<T>, id: fn(&T) -> uuid::Uuid) -> uuid::Uuid {
+    before
+        .as_ref()
+        .or(after.as_ref())
+        .map(id)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::Contract;
+    use crate::graph::node::{ArithmeticOp, NodeKind};
+    use crate::graph::region::RegionKind;
+    use crate::types::{Predicate, Type};
+
+    fn sample() -> (Graph, [NodeId; 3]) {
+        let mut g = Graph::new();
+        let a = Node::new(NodeKind::Literal);
+        let b = Node::new(NodeKind::Literal);
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add));
+        let ids = [a.id, b.id, add.id];
+        for n in [a, b, add] {
+            g.add_node(n).unwrap();
+        }
+        g.add_edge(Edge::new((ids[0], 0), (ids[2], 0))).unwrap();
+        g.add_edge(Edge::new((ids[1], 0), (ids[2], 1))).unwrap();
+        g.add_region(Region::new(RegionKind::Sequential, ids.to_vec()))
+            .unwrap();
+        (g, ids)
+    }
+
+    fn bytes(g: &Graph) -> Vec<u8> {
+        serde_json::to_vec(g).unwrap()
+    }
+
+    #[test]
+    fn apply_and_invert_round_trip() {
+        let (old, [a, b, add]) = sample();
+        let mut new = old.clone();
+        new.remove_node(a).unwrap();
+        let c = new.add_node(Node::new(NodeKind::Literal)).unwrap();
+        new.add_edge(Edge::typed((c, 0), (add, 0), Type::i32()))
+            .unwrap();
+        new.get_node_mut(&add).unwrap().contract = Some(Contract::with_conditions(
+            vec![],
+            vec![Predicate::positive("output")],
+        ));
+        let region = *new.containing_region(&b).unwrap();
+        new.get_region_mut(&region).unwrap().kind = RegionKind::Parallel;
+        new.define_type("Sum", TypeDef::alias(Type::i32()));
+
+        let patch = GraphPatch::between(&old, &new);
+        let json = serde_json::to_string(&patch).unwrap();
+        let patch: GraphPatch = serde_json::from_str(&json).unwrap();
+
+        let mut patched = old.clone();
+        patched.apply_patch(&patch).unwrap();
+        assert_eq!(bytes(&patched), bytes(&new));
+        assert!(GraphPatch::between(&patched, &new).is_empty());
+
+        patched.apply_patch(&patch.inverse()).unwrap();
+        assert_eq!(bytes(&patched), bytes(&old));
+    }
+
+    #[test]
+    fn stale_patch_is_rejected_without_changes() {
+        let (old, [_, _, add]) = sample();
+        let mut new = old.clone();
+        new.get_node_mut(&add).unwrap().kind = NodeKind::Arithmetic(ArithmeticOp::Mul);
+        let patch = GraphPatch::between(&old, &new);
+
+        let mut drifted = old.clone();
+        drifted
+            .get_node_mut(&add)
+            .unwrap()
+            .annotations
+            .insert("hint".into(), "inline".into());
+        let before = bytes(&drifted);
+        assert!(matches!(
+            drifted.apply_patch(&patch),
+            Err(GraphError::StalePatch(_))
+        ));
+        assert_eq!(bytes(&drifted), before);
+
+        // Tolerated, the stale operation is reported as skipped and not applied
+        let skipped = drifted.apply_ops(&patch, &mut |_, _| Ok(())).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].0, PatchTarget::Node(add));
+        assert!(skipped[0].1.contains("not in the state the patch expects"));
+        assert_eq!(bytes(&drifted), before);
+    }
+}
@@ -0,0 +1,567 @@
+//! Node selection queries.
+//!
+//! A `Query` selects a set of nodes from a graph; `Graph::select` evaluates
+//! it to a `HashSet<NodeId>`, the form `extract_subgraph` and
+//! `extract_module` take. Queries are built with the constructors and
+//! combinators below or parsed from a small text syntax:
+//!
+//! ```text
+//! kind(Arithmetic) & !kind(Arithmetic(Div))
+//! effect(IO<uart0>) | annotation(safety=critical)
+//! upstream(kind(Write), 2)        // writers and two hops of their inputs
+//! region(<uuid>) & author(human:*)
+//! ```
+//!
+//! `&` binds tighter than `|`, and `!` tighter than both. Patterns may use
+//! `*` as a wildcard. A kind or effect pattern without arguments matches
+//! the whole family: `Arithmetic` matches `Arithmetic(Add)` and `IO`
+//! matches `IO<uart0>`.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use thiserror::Error;
+
+use super::node::{Node, NodeId};
+use super::region::RegionId;
+use super::Graph;
+
+/// Errors from parsing a query.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("query syntax error at offset {offset}: {message}")]
+    Syntax { offset: usize, message: String },
+
+    #[error("unknown query predicate: {0}")]
+    UnknownPredicate(String),
+
+    #[error("invalid id in query: {0}")]
+    InvalidId(String),
+
+    #[error("invalid hop count in query: {0}")]
+    InvalidHops(String),
+}
+
+/// A node selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Every node.
+    All,
+    /// A single node.
+    Node(NodeId),
+    /// Nodes whose kind matches the pattern.
+    Kind(String),
+    /// Nodes performing an effect matching the pattern (see
+    /// `Graph::node_effects`).
+    Effect(String),
+    /// Nodes with an input or output type matching the pattern.
+    Type(String),
+    /// Nodes with the annotation, optionally with a matching value.
+    Annotation {
+        key: String,
+        value: Option<String>,
+    },
+    /// Nodes created or edited by an author matching the pattern.
+    Author(String),
+    /// Nodes in the region or any region nested in it.
+    InRegion(RegionId),
+    /// The selection plus up to `hops` levels of its producers (`None` for
+    /// all of them).
+    Upstream(Box<Query>, Option<usize>),
+    /// The selection plus up to `hops` levels of its consumers.
+    Downstream(Box<Query>, Option<usize>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    pub fn kind(pattern: impl Into<String>) -> Self {
+        Query::Kind(pattern.into())
+    }
+
+    pub fn effect(pattern: impl Into<String>) -> Self {
+        Query::Effect(pattern.into())
+    }
+
+    pub fn ty(pattern: impl Into<String>) -> Self {
+        Query::Type(pattern.into())
+    }
+
+    pub fn annotation(key: impl Into<String>, value: Option<&str>) -> Self {
+        Query::Annotation {
+            key: key.into(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    pub fn author(pattern: impl Into<String>) -> Self {
+        Query::Author(pattern.into())
+    }
+
+    pub fn and(self, other: Query) -> Self {
+        Query::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Query) -> Self {
+        Query::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn upstream(self, hops: Option<usize>) -> Self {
+        Query::Upstream(Box::new(self), hops)
+    }
+
+    pub fn downstream(self, hops: Option<usize>) -> Self {
+        Query::Downstream(Box::new(self), hops)
+    }
+
+    /// Parse the text syntax described in the module docs.
+    pub fn parse(src: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser { src, pos: 0 };
+        let query = parser.or()?;
+        parser.skip_ws();
+        if parser.pos < src.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(query)
+    }
+}
+
+impl std::ops::Not for Query {
+    type Output = Query;
+
+    fn not(self) -> Query {
+        Query::Not(Box::new(self))
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hops = |h: &Option<usize>| h.map_or("*".to_string(), |n| n.to_string());
+        match self {
+            Query::All => write!(f, "all"),
+            Query::Node(id) => write!(f, "node({id})"),
+            Query::Kind(p) => write!(f, "kind({p})"),
+            Query::Effect(p) => write!(f, "effect({p})"),
+            Query::Type(p) => write!(f, "type({p})"),
+            Query::Annotation { key, value: None } => write!(f, "annotation({key})"),
+            Query::Annotation {
+                key,
+                value: Some(v),
+            } => write!(f, "annotation({key}={v})"),
+            Query::Author(p) => write!(f, "author({p})"),
+            Query::InRegion(id) => write!(f, "region({id})"),
+            Query::Upstream(q, h) => write!(f, "upstream({q}, {})", hops(h)),
+            Query::Downstream(q, h) => write!(f, "downstream({q}, {})", hops(h)),
+            Query::And(a, b) => write!(f, "({a} & {b})"),
+            Query::Or(a, b) => write!(f, "({a} | {b})"),
+            Query::Not(q) => write!(f, "!{q}"),
+        }
+    }
+}
+
+impl Graph {
+    /// The nodes a query selects.
+    pub fn select(&self, query: &Query) -> HashSet<NodeId> {
+        match query {
+            Query::All => self.nodes().map(|n| n.id).collect(),
+            Query::Node(id) => self.get_node(id).map(|n| n.id).into_iter().collect(),
+            Query::InRegion(region) => self.nodes_within(*region),
+            Query::Upstream(q, hops) => self.walk(self.select(q), *hops, false),
+            Query::Downstream(q, hops) => self.walk(self.select(q), *hops, true),
+            Query::And(a, b) => {
+                let a = self.select(a);
+                self.select(b)
+                    .into_iter()
+                    .filter(|id| a.contains(id))
+                    .collect()
+            }
+            Query::Or(a, b) => {
+                let mut a = self.select(a);
+                a.extend(self.select(b));
+                a
+            }
+            Query::Not(q) => {
+                let excluded = self.select(q);
+                self.nodes()
+                    .map(|n| n.id)
+                    .filter(|id| !excluded.contains(id))
+                    .collect()
+            }
+            _ => self
+                .nodes()
+                .filter(|n| self.matches(query, n))
+                .map(|n| n.id)
+                .collect(),
+        }
+    }
+
+    /// Parse and evaluate a query.
+    pub fn query(&self, src: &str) -> Result<HashSet<NodeId>, QueryError> {
+        Ok(self.select(&Query::parse(src)?))
+    }
+
+    /// Whether a node satisfies a per-node predicate.
+    fn matches(&self, query: &Query, node: &Node) -> bool {
+        match query {
+            Query::Kind(p) => family_match(p, &node.kind.to_string(), '('),
+            Query::Effect(p) => self
+                .node_effects(&node.id)
+                .effects
+                .iter()
+                .any(|e| family_match(p, &e.to_string(), '<')),
+            Query::Type(p) => node.type_signature.as_ref().is_some_and(|sig| {
+                sig.inputs
+                    .iter()
+                    .chain(&sig.outputs)
+                    .any(|t| glob(p, &t.to_string()) || glob(p, &t.base_type().to_string()))
+            }),
+            Query::Annotation { key, value } => node
+                .annotations
+                .get(key)
+                .is_some_and(|v| value.as_ref().is_none_or(|p| glob(p, v))),
+            Query::Author(p) => node.provenance.as_ref().is_some_and(|prov| {
+                std::iter::once(&prov.created_by)
+                    .chain(prov.edit_history.iter().map(|e| &e.author))
+                    .any(|author| glob(p, &author.to_string()) || glob(p, &author_name(author)))
+            }),
+            _ => self.select(query).contains(&node.id),
+        }
+    }
+
+    /// Expand a node set along edges, `hops` levels at most.
+    fn walk(
+        &self,
+        start: HashSet<NodeId>,
+        hops: Option<usize>,
+        downstream: bool,
+    ) -> HashSet<NodeId> {
+        let mut seen = start.clone();
+        let mut queue: VecDeque<(NodeId, usize)> = start.into_iter().map(|id| (id, 0)).collect();
+        while let Some((id, depth)) = queue.pop_front() {
+            if hops.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            let edges = if downstream {
+                self.outgoing_edges(&id)
+            } else {
+                self.incoming_edges(&id)
+            };
+            for edge in edges.iter().filter_map(|e| self.get_edge(e)) {
+                let next = if downstream {
+                    edge.target.0
+                } else {
+                    edge.source.0
+                };
+                if seen.insert(next) {
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        seen
+    }
+}
+
+/// The short name of an author: the model, identity, or `torc-toolchain`.
+fn author_name(author: &crate::provenance::Author) -> String {
+    use crate::provenance::Author;
+    match author {
+        Author::AI { model, .. } => model.clone(),
+        Author::Human { identity } => identity.clone(),
+        Author::Toolchain { .. } => "torc-toolchain".to_string(),
+    }
+}
+
+/// Match `text` against `pattern`, or its family (the part before `open`)
+/// when the pattern has no arguments of its own.
+fn family_match(pattern: &str, text: &str, open: char) -> bool {
+    glob(pattern, text)
+        || (!pattern.contains(open)
+            && text
+                .split_once(open)
+                .is_some_and(|(family, _)| glob(pattern, family)))
+}
+
+/// Whole-string match where `*` matches any run of characters.
+fn glob(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(i) => remaining = &remaining[i + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+// === Parser ===
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> QueryError {
+        QueryError::Syntax {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.src[self.pos..].starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.and()?;
+        while self.eat('|') {
+            query = query.or(self.and()?);
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, QueryError> {
+        let mut query = self.unary()?;
+        while self.eat('&') {
+            query = query.and(self.unary()?);
+        }
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        if self.eat('!') {
+            return Ok(!self.unary()?);
+        }
+        if self.eat('(') {
+            let query = self.or()?;
+            if !self.eat(')') {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(query);
+        }
+        self.skip_ws();
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a query"));
+        }
+        let name = &rest[..len];
+        self.pos += len;
+        if name == "all" {
+            return Ok(Query::All);
+        }
+        if !self.eat('(') {
+            return Err(self.error("expected `(`"));
+        }
+        let query = match name {
+            "upstream" | "downstream" => {
+                let inner = self.or()?;
+                let hops = if self.eat(',') {
+                    let arg = self.raw_arg()?;
+                    match arg.as_str() {
+                        "*" => None,
+                        n => Some(n.parse().map_err(|_| QueryError::InvalidHops(arg))?),
+                    }
+                } else {
+                    Some(1)
+                };
+                if name == "upstream" {
+                    inner.upstream(hops)
+                } else {
+                    inner.downstream(hops)
+                }
+            }
+            "kind" => Query::Kind(self.raw_arg()?),
+            "effect" => Query::Effect(self.raw_arg()?),
+            "type" => Query::Type(self.raw_arg()?),
+            "author" => Query::Author(self.raw_arg()?),
+            "annotation" => {
+                let arg = self.raw_arg()?;
+                match arg.split_once('=') {
+                    Some((key, value)) => Query::annotation(key.trim(), Some(value.trim())),
+                    None => Query::annotation(arg, None),
+                }
+            }
+            "node" => Query::Node(parse_id(&self.raw_arg()?)?),
+            "region" => Query::InRegion(parse_id(&self.raw_arg()?)?),
+            other => return Err(QueryError::UnknownPredicate(other.to_string())),
+        };
+        if !self.eat(')') {
+            return Err(self.error("expected `)`"));
+        }
+        Ok(query)
+    }
+
+    /// Text up to the next `,` or `)` outside brackets, trimmed.
+    fn raw_arg(&mut self) -> Result<String, QueryError> {
+        let start = self.pos;
+        let mut depth = 0usize;
+        for (i, c) in self.src[start..].char_indices() {
+            match c {
+                '(' | '[' | '<' | '{' => depth += 1,
+                ')' | ']' | '>' | '}' if depth > 0 => depth -= 1,
+                ')' | ',' => {
+                    self.pos = start + i;
+                    let arg = self.src[start..self.pos].trim();
+                    if arg.is_empty() {
+                        return Err(self.error("expected an argument"));
+                    }
+                    return Ok(arg.to_string());
+                }
+                _ => {}
+            }
+        }
+        self.pos = self.src.len();
+        Err(self.error("unterminated argument list"))
+    }
+}
+
+fn parse_id(text: &str) -> Result<uuid::Uuid, QueryError> {
+    uuid::Uuid::parse_str(text).map_err(|_| QueryError::InvalidId(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{Contract, EffectSet};
+    use crate::graph::edge::Edge;
+    use crate::graph::node::{ArithmeticOp, NodeKind};
+    use crate::graph::region::{Region, RegionKind};
+    use crate::provenance::{Author, Provenance};
+    use crate::types::{Effect, Type, TypeSignature};
+
+    /// `src -> scale -> div -> tx`, with `div` and `tx` in a region.
+    fn pipeline() -> (Graph, [NodeId; 4], RegionId) {
+        let mut g = Graph::new();
+        let src = Node::new(NodeKind::Read)
+            .with_type_signature(TypeSignature::source(Type::f32()))
+            .with_provenance(Provenance::ai_authored("claude", "anthropic", "v1", "read"));
+        let scale = Node::new(NodeKind::Arithmetic(ArithmeticOp::Mul))
+            .with_type_signature(TypeSignature::pure_fn(vec![Type::f32()], Type::f32()));
+        let mut div = Node::new(NodeKind::Arithmetic(ArithmeticOp::Div));
+        div.annotations.insert("safety".into(), "critical".into());
+        let mut tx = Node::new(NodeKind::Write).with_contract(
+            Contract::pure_default()
+                .with_effects(EffectSet::from_effects(vec![Effect::IO("uart0".into())])),
+        );
+        let mut prov = Provenance::toolchain_generated("0.1", "bridge");
+        prov.record_edit(
+            Author::Human {
+                identity: "alice".into(),
+            },
+            "tune",
+            None,
+        );
+        tx.provenance = Some(prov);
+        let ids = [src.id, scale.id, div.id, tx.id];
+        for n in [src, scale, div, tx] {
+            g.add_node(n).unwrap();
+        }
+        for pair in ids.windows(2) {
+            g.add_edge(Edge::new((pair[0], 0), (pair[1], 0))).unwrap();
+        }
+        let region = Region::new(RegionKind::Sequential, vec![ids[2], ids[3]]);
+        let region_id = g.add_region(region).unwrap();
+        (g, ids, region_id)
+    }
+
+    fn set(ids: &[NodeId]) -> HashSet<NodeId> {
+        ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn predicates_select_nodes() {
+        let (g, [src, scale, div, tx], region) = pipeline();
+        assert_eq!(g.select(&Query::kind("Arithmetic")), set(&[scale, div]));
+        assert_eq!(g.select(&Query::kind("Arithmetic(Div)")), set(&[div]));
+        assert_eq!(g.select(&Query::effect("IO<uart0>")), set(&[tx]));
+        assert_eq!(g.select(&Query::effect("IO")), set(&[src, tx]));
+        assert_eq!(g.select(&Query::ty("f32")), set(&[src, scale]));
+        assert_eq!(
+            g.select(&Query::annotation("safety", Some("crit*"))),
+            set(&[div])
+        );
+        assert_eq!(g.select(&Query::author("claude")), set(&[src]));
+        assert_eq!(g.select(&Query::author("human:*")), set(&[tx]));
+        assert_eq!(g.select(&Query::InRegion(region)), set(&[div, tx]));
+    }
+
+    #[test]
+    fn hops_and_combinators() {
+        let (g, [src, scale, div, tx], _) = pipeline();
+        let writes = Query::kind("Write");
+        assert_eq!(
+            g.select(&writes.clone().upstream(Some(2))),
+            set(&[scale, div, tx])
+        );
+        assert_eq!(g.select(&writes.upstream(None)).len(), 4);
+        assert_eq!(
+            g.select(&Query::kind("Read").downstream(Some(1))),
+            set(&[src, scale])
+        );
+        assert_eq!(
+            g.select(&Query::kind("Arithmetic").and(!Query::kind("Arithmetic(Div)"))),
+            set(&[scale])
+        );
+
+        let sub = g.extract_subgraph(&g.select(&Query::kind("Arithmetic")));
+        assert_eq!((sub.node_count(), sub.edge_count()), (2, 1));
+    }
+
+    #[test]
+    fn parse_round_trips_and_evaluates() {
+        let (g, [_, scale, div, tx], region) = pipeline();
+        let q =
+            Query::parse("kind(Arithmetic) & !kind(Arithmetic(Div)) | effect(IO<uart0>)").unwrap();
+        assert_eq!(g.select(&q), set(&[scale, tx]));
+        assert_eq!(Query::parse(&q.to_string()).unwrap(), q);
+
+        assert_eq!(
+            g.query(&format!("region({region}) & annotation(safety = critical)"))
+                .unwrap(),
+            set(&[div])
+        );
+        assert_eq!(
+            g.query("upstream(kind(Write), *)").unwrap(),
+            g.select(&Query::All)
+        );
+        assert_eq!(
+            g.query(&format!("downstream(node({div}))")).unwrap(),
+            set(&[div, tx])
+        );
+
+        assert_eq!(
+            Query::parse("colour(red)"),
+            Err(QueryError::UnknownPredicate("colour".into()))
+        );
+        assert!(matches!(
+            Query::parse("kind(Literal"),
+            Err(QueryError::Syntax { .. })
+        ));
+        assert_eq!(
+            Query::parse("upstream(all, two)"),
+            Err(QueryError::InvalidHops("two".into()))
+        );
+    }
+}
@@ -11,6 +11,7 @@ use uuid::Uuid;
 use super::constraints::Constraint;
 use super::node::NodeId;
 use super::port::Port;
+use crate::contract::Variant;
 
 /// Globally unique region identifier.
 pub type RegionId = Uuid;
@@ -57,6 +58,9 @@ pub struct Region {
     pub interfaces: Vec<Port>,
     /// Parent region, if this region is nested.
     pub parent: Option<RegionId>,
+    /// Termination measure, for iterative regions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<Variant>,
 }
 
 impl Region {
@@ -69,6 +73,7 @@ impl Region {
             constraints: Vec::new(),
             interfaces: Vec::new(),
             parent: None,
+            variant: None,
         }
     }
 
@@ -81,6 +86,7 @@ impl Region {
             constraints: Vec::new(),
             interfaces: Vec::new(),
             parent: None,
+            variant: None,
         }
     }
 
@@ -101,6 +107,12 @@ impl Region {
         self.parent = Some(parent);
         self
     }
+
+    /// Set the termination measure.
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,350 @@
+//! Resource composition.
+//!
+//! A node uses the time, memory, stack and energy its contract bounds
+//! (`TimeBound::wcet_ns`, `MemoryBound::peak_bytes`, `StackBound`,
+//! `EnergyBound`). A region combines its child nodes and nested regions by
+//! its kind:
+//!
+//! | Region kind              | Time      | Memory    | Stack | Energy    |
+//! |--------------------------|-----------|-----------|-------|-----------|
+//! | `Sequential`, `Atomic`   | sum       | sum       | max   | sum       |
+//! | `Iterative`              | n × sum   | n × sum   | max   | n × sum   |
+//! | `Parallel`               | max       | sum       | sum   | sum       |
+//! | `Conditional`            | max       | max       | max   | max       |
+//!
+//! `n` is the iterative region's `Constraint::MaxIterations` bound. Without
+//! one it is the unknown `iterations.<region>`, so no budget over the loop
+//! can be discharged. Stack is reused by every iteration. Usage is kept as a
+//! numeric `Predicate` (`Add` for sums, `max(..)` for maxima) so the
+//! verifier can compare it with the region's `Constraint::MaxTime`,
+//! `MaxMemory` and `MaxEnergy` budgets. A node without a bound stands for
+//! an unknown amount, the variable `<resource>.<node>`. Budget obligations
+//! assume every unknown is non-negative.
+
+use std::fmt;
+
+use super::constraints::Constraint;
+use super::node::NodeId;
+use super::region::{RegionId, RegionKind};
+use super::Graph;
+use crate::contract::Contract;
+use crate::types::Predicate;
+
+/// A resource a contract can bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    Time,
+    Memory,
+    Stack,
+    Energy,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 4] = [
+        Resource::Time,
+        Resource::Memory,
+        Resource::Stack,
+        Resource::Energy,
+    ];
+
+    /// The unit amounts are given in.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Resource::Time => "ns",
+            Resource::Memory | Resource::Stack => "B",
+            Resource::Energy => "μJ",
+        }
+    }
+
+    /// The amount a contract bounds this resource to.
+    pub fn of_contract(self, contract: &Contract) -> Option<u64> {
+        match self {
+            Resource::Time => contract.time_bound.as_ref()?.wcet_ns,
+            Resource::Memory => contract.memory_bound.as_ref()?.peak_bytes,
+            Resource::Stack => Some(contract.stack_bound.as_ref()?.max_bytes),
+            Resource::Energy => Some(contract.energy_bound.as_ref()?.max_uj),
+        }
+    }
+
+    /// The tightest budget the constraints place on this resource.
+    pub fn budget(self, constraints: &[Constraint]) -> Option<u64> {
+        constraints
+            .iter()
+            .filter_map(|c| match (self, c) {
+                (Resource::Time, Constraint::MaxTime(ns)) => Some(*ns),
+                (Resource::Memory, Constraint::MaxMemory(bytes)) => Some(*bytes as u64),
+                (Resource::Energy, Constraint::MaxEnergy(uj)) => Some(*uj),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// Whether a region of `kind` uses the most of its parts rather than
+    /// their sum.
+    fn takes_max(self, kind: RegionKind) -> bool {
+        match kind {
+            RegionKind::Conditional => true,
+            RegionKind::Parallel => self == Resource::Time,
+            RegionKind::Sequential | RegionKind::Atomic | RegionKind::Iterative => {
+                self == Resource::Stack
+            }
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Time => write!(f, "time"),
+            Resource::Memory => write!(f, "memory"),
+            Resource::Stack => write!(f, "stack"),
+            Resource::Energy => write!(f, "energy"),
+        }
+    }
+}
+
+/// How much of a resource a node or region uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage {
+    /// The amount, as a numeric expression.
+    pub expr: Predicate,
+    /// The amount, if every part is bounded.
+    pub worst: Option<u64>,
+    /// The nodes on the worst-case path and what each uses, largest first.
+    pub critical: Vec<(NodeId, u64)>,
+}
+
+impl Usage {
+    fn none() -> Self {
+        Self {
+            expr: Predicate::IntLit(0),
+            worst: Some(0),
+            critical: Vec::new(),
+        }
+    }
+
+    fn sum(parts: Vec<Usage>) -> Self {
+        let Some(expr) = parts
+            .iter()
+            .map(|p| p.expr.clone())
+            .reduce(|a, b| Predicate::Add(Box::new(a), Box::new(b)))
+        else {
+            return Self::none();
+        };
+        let worst = parts
+            .iter()
+            .try_fold(0u64, |acc, p| Some(acc.saturating_add(p.worst?)));
+        let mut critical: Vec<_> = parts.into_iter().flat_map(|p| p.critical).collect();
+        critical.sort_by_key(|&(_, amount)| std::cmp::Reverse(amount));
+        Self {
+            expr,
+            worst,
+            critical,
+        }
+    }
+
+    /// The usage of `count` runs, or of an unknown number of runs of
+    /// `region`.
+    fn times(self, count: Option<u64>, region: RegionId) -> Self {
+        let Some(n) = count else {
+            return Self {
+                expr: Predicate::Mul(
+                    Box::new(Predicate::Var(format!("iterations.{region}"))),
+                    Box::new(self.expr),
+                ),
+                worst: None,
+                critical: self.critical,
+            };
+        };
+        Self {
+            expr: Predicate::Mul(Box::new(Predicate::IntLit(n as i128)), Box::new(self.expr)),
+            worst: self.worst.map(|w| w.saturating_mul(n)),
+            critical: self
+                .critical
+                .into_iter()
+                .map(|(node, amount)| (node, amount.saturating_mul(n)))
+                .collect(),
+        }
+    }
+
+    fn max(parts: Vec<Usage>) -> Self {
+        if parts.len() <= 1 {
+            return parts.into_iter().next().unwrap_or_else(Self::none);
+        }
+        let expr = Predicate::Apply("max".into(), parts.iter().map(|p| p.expr.clone()).collect());
+        let worst = parts.iter().map(|p| p.worst).collect::<Option<Vec<_>>>();
+        let worst = worst.and_then(|w| w.into_iter().max());
+        let critical = parts
+            .into_iter()
+            .max_by_key(|p| p.worst.unwrap_or(u64::MAX))
+            .map(|p| p.critical)
+            .unwrap_or_default();
+        Self {
+            expr,
+            worst,
+            critical,
+        }
+    }
+}
+
+impl Graph {
+    /// How much of `resource` a node uses: its contract bound, or an
+    /// unknown amount.
+    pub fn node_usage(&self, id: NodeId, resource: Resource) -> Usage {
+        let bound = self
+            .get_node(&id)
+            .and_then(|n| n.contract.as_ref())
+            .and_then(|c| resource.of_contract(c));
+        match bound {
+            Some(amount) => Usage {
+                expr: Predicate::IntLit(amount as i128),
+                worst: Some(amount),
+                critical: vec![(id, amount)],
+            },
+            None => Usage {
+                expr: Predicate::Var(format!("{resource}.{id}")),
+                worst: None,
+                critical: Vec::new(),
+            },
+        }
+    }
+
+    /// How much of `resource` a region uses, composed from its child nodes
+    /// and nested regions by its kind.
+    pub fn region_usage(&self, id: RegionId, resource: Resource) -> Usage {
+        self.region_usage_within(id, resource, &mut Vec::new())
+    }
+
+    fn region_usage_within(
+        &self,
+        id: RegionId,
+        resource: Resource,
+        visiting: &mut Vec<RegionId>,
+    ) -> Usage {
+        let Some(region) = self.get_region(&id) else {
+            return Usage::none();
+        };
+        if visiting.contains(&id) {
+            // A malformed parent cycle
+            return Usage::none();
+        }
+        visiting.push(id);
+        let mut nested = self.child_regions(&id);
+        nested.sort();
+        let in_nested = |node: &NodeId| {
+            nested.iter().any(|r| {
+                self.get_region(r)
+                    .is_some_and(|r| r.children.contains(node))
+            })
+        };
+        let mut parts: Vec<Usage> = region
+            .children
+            .iter()
+            .filter(|n| !in_nested(n))
+            .map(|&n| self.node_usage(n, resource))
+            .collect();
+        parts.extend(
+            nested
+                .iter()
+                .map(|&r| self.region_usage_within(r, resource, visiting)),
+        );
+        visiting.pop();
+        if resource.takes_max(region.kind) {
+            Usage::max(parts)
+        } else if region.kind == RegionKind::Iterative {
+            let count = region
+                .constraints
+                .iter()
+                .filter_map(|c| match c {
+                    Constraint::MaxIterations(n) => Some(*n),
+                    _ => None,
+                })
+                .min();
+            Usage::sum(parts).times(count, id)
+        } else {
+            Usage::sum(parts)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::MemoryBound;
+    use crate::graph::node::{Node, NodeKind};
+    use crate::graph::region::Region;
+
+    #[test]
+    fn regions_sum_or_max_by_kind() {
+        let mut g = Graph::new();
+        let mut timed = |ns| {
+            g.add_node(
+                Node::new(NodeKind::Literal)
+                    .with_contract(Contract::pure_default().with_wcet(ns, "m4")),
+            )
+            .unwrap()
+        };
+        let (a, b, c) = (timed(100), timed(300), timed(200));
+        let unbounded = g.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let outer = g
+            .add_region(Region::new(RegionKind::Sequential, vec![a]))
+            .unwrap();
+        let fork = g
+            .add_region(Region::new(RegionKind::Parallel, vec![b, c]).with_parent(outer))
+            .unwrap();
+
+        let time = g.region_usage(outer, Resource::Time);
+        assert_eq!(time.worst, Some(400));
+        assert_eq!(time.critical, vec![(b, 300), (a, 100)]);
+        assert_eq!(
+            g.region_usage(fork, Resource::Time).expr,
+            Predicate::Apply(
+                "max".into(),
+                vec![Predicate::IntLit(300), Predicate::IntLit(200)]
+            )
+        );
+        // Parallel energy sums, but nothing declares any
+        assert_eq!(g.region_usage(fork, Resource::Energy).worst, None);
+
+        let open = g
+            .add_region(Region::new(RegionKind::Sequential, vec![unbounded]))
+            .unwrap();
+        let usage = g.region_usage(open, Resource::Time);
+        assert_eq!(usage.worst, None);
+        assert_eq!(usage.expr, Predicate::Var(format!("time.{unbounded}")));
+    }
+
+    #[test]
+    fn loops_scale_by_their_iteration_bound() {
+        let mut g = Graph::new();
+        let mut bound = Contract::pure_default();
+        bound.memory_bound = Some(MemoryBound {
+            peak_bytes: Some(64),
+            allocated_bytes: Some(64),
+            freed_bytes: None,
+        });
+        let alloc = g
+            .add_node(Node::new(NodeKind::Allocate).with_contract(bound))
+            .unwrap();
+        let bounded = g
+            .add_region(
+                Region::new(RegionKind::Iterative, vec![alloc])
+                    .with_constraints(vec![Constraint::MaxIterations(10)]),
+            )
+            .unwrap();
+        let usage = g.region_usage(bounded, Resource::Memory);
+        assert_eq!(usage.worst, Some(640));
+        assert_eq!(usage.critical, vec![(alloc, 640)]);
+
+        g.get_region_mut(&bounded).unwrap().constraints.clear();
+        let usage = g.region_usage(bounded, Resource::Memory);
+        assert_eq!(usage.worst, None);
+        assert_eq!(
+            usage.expr,
+            Predicate::Mul(
+                Box::new(Predicate::Var(format!("iterations.{bounded}"))),
+                Box::new(Predicate::IntLit(64)),
+            )
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Three-way graph merge.
+//!
+//! `merge3` combines two versions of a graph edited from a common base. It
+//! takes the patch from the base to each side and merges them element by
+//! element: an element changed on one side takes that change, and an
+//! element changed on both sides is merged field by field. Fields both
+//! sides changed differently are conflicts and keep `ours`' value. Changes
+//! that no longer apply to the merged graph, such as an edge into a node the
+//! other side removed, are dropped and reported as conflicts too.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::edge::{Edge, EdgeId};
+use super::node::{Node, NodeId};
+use super::patch::{same, GraphPatch, PatchOp, PatchTarget};
+use super::region::{Region, RegionId};
+use super::Graph;
+
+/// A change on one side that could not be reconciled with the other.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Conflict {
+    /// Both sides changed the same field of a node differently.
+    NodeField { node: NodeId, field: String },
+    /// Both sides changed a node's contract differently.
+    Contract { node: NodeId },
+    /// Both sides moved an edge to different endpoints.
+    EdgeRetargeted { edge: EdgeId },
+    /// Both sides changed the same attribute of an edge differently.
+    EdgeField { edge: EdgeId, field: String },
+    /// Both sides changed the same field of a region differently.
+    RegionField { region: RegionId, field: String },
+    /// Both sides defined a named type differently.
+    TypeDef(String),
+    /// One side removed an element the other side changed.
+    RemovedAndChanged(PatchTarget),
+    /// Both sides added an element with the same ID but different content.
+    AddedDifferently(PatchTarget),
+    /// A merged change did not apply to the merged graph.
+    Unapplied { target: PatchTarget, reason: String },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::NodeField { node, field } => {
+                write!(f, "node {node}: {field} edited differently on both sides")
+            }
+            Conflict::Contract { node } => {
+                write!(f, "node {node}: conflicting contracts")
+            }
+            Conflict::EdgeRetargeted { edge } => {
+                write!(f, "edge {edge}: retargeted differently on both sides")
+            }
+            Conflict::EdgeField { edge, field } => {
+                write!(f, "edge {edge}: {field} edited differently on both sides")
+            }
+            Conflict::RegionField { region, field } => {
+                write!(
+                    f,
+                    "region {region}: {field} edited differently on both sides"
+                )
+            }
+            Conflict::TypeDef(name) => write!(f, "type {name}: conflicting definitions"),
+            Conflict::RemovedAndChanged(target) => {
+                write!(f, "{target}: removed on one side, changed on the other")
+            }
+            Conflict::AddedDifferently(target) => {
+                write!(f, "{target}: added differently on both sides")
+            }
+            Conflict::Unapplied { target, reason } => {
+                write!(f, "{target}: change dropped: {reason}")
+            }
+        }
+    }
+}
+
+/// The result of a three-way merge.
+#[derive(Debug, Clone)]
+pub struct ThreeWayMerge {
+    /// The merged graph.
+    pub graph: Graph,
+    /// The merged changes, relative to the base.
+    pub patch: GraphPatch,
+    /// Everything that could not be merged automatically.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ThreeWayMerge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merge `ours` and `theirs`, both derived from `base`.
+pub fn merge3(base: &Graph, ours: &Graph, theirs: &Graph) -> ThreeWayMerge {
+    let ours_patch = GraphPatch::between(base, ours);
+    let theirs_patch = GraphPatch::between(base, theirs);
+    let ours_ops = ours_patch.by_target();
+    let theirs_ops = theirs_patch.by_target();
+
+    let mut conflicts = Vec::new();
+    let targets: BTreeSet<&PatchTarget> = ours_ops.keys().chain(theirs_ops.keys()).collect();
+    let mut ops = Vec::new();
+    for target in targets {
+        let op = match (ours_ops.get(target), theirs_ops.get(target)) {
+            (Some(op), None) | (None, Some(op)) => (*op).clone(),
+            (Some(o), Some(t)) => merge_op(o, t, &mut conflicts),
+            (None, None) => continue,
+        };
+        ops.push(op);
+    }
+    let patch = GraphPatch::from_ops(ops);
+
+    let mut graph = base.clone();
+    let skipped = graph
+        .apply_ops(&patch, &mut |_, _| Ok(()))
+        .unwrap_or_default();
+    conflicts.extend(
+        skipped
+            .into_iter()
+            .map(|(target, reason)| Conflict::Unapplied { target, reason }),
+    );
+
+    ThreeWayMerge {
+        graph,
+        patch,
+        conflicts,
+    }
+}
+
+/// Merge two changes to the same element.
+fn merge_op(ours: &PatchOp, theirs: &PatchOp, conflicts: &mut Vec<Conflict>) -> PatchOp {
+    let target = ours.target();
+    match (ours, theirs) {
+        (PatchOp::Node { after: a, .. }, PatchOp::Node { after: b, .. }) if same(a, b) => {
+            ours.clone()
+        }
+        (
+            PatchOp::Node {
+                before: Some(base),
+                after: Some(a),
+            },
+            PatchOp::Node { after: Some(b), .. },
+        ) => PatchOp::Node {
+            before: Some(base.clone()),
+            after: Some(Box::new(merge_node(base, a, b, conflicts))),
+        },
+        (PatchOp::Edge { after: a, .. }, PatchOp::Edge { after: b, .. }) if same(a, b) => {
+            ours.clone()
+        }
+        (
+            PatchOp::Edge {
+                before: Some(base),
+                after: Some(a),
+            },
+            PatchOp::Edge { after: Some(b), .. },
+        ) => PatchOp::Edge {
+            before: Some(base.clone()),
+            after: Some(merge_edge(base, a, b, conflicts)),
+        },
+        (PatchOp::Region { after: a, .. }, PatchOp::Region { after: b, .. }) if same(a, b) => {
+            ours.clone()
+        }
+        (
+            PatchOp::Region {
+                before: Some(base),
+                after: Some(a),
+            },
+            PatchOp::Region { after: Some(b), .. },
+        ) => PatchOp::Region {
+            before: Some(base.clone()),
+            after: Some(merge_region(base, a, b, conflicts)),
+        },
+        (PatchOp::Type { after: a, .. }, PatchOp::Type { after: b, .. }) if a == b => ours.clone(),
+        (
+            PatchOp::Type {
+                name,
+                after: Some(_),
+                ..
+            },
+            PatchOp::Type { after: Some(_), .. },
+        ) => {
+            conflicts.push(Conflict::TypeDef(name.clone()));
+            ours.clone()
+        }
+        _ => {
+            let added_on_both = matches!(
+                (ours, theirs),
+                (PatchOp::Node { before: None, .. }, PatchOp::Node { .. })
+                    | (PatchOp::Edge { before: None, .. }, PatchOp::Edge { .. })
+                    | (PatchOp::Region { before: None, .. }, PatchOp::Region { .. })
+                    | (PatchOp::Type { before: None, .. }, PatchOp::Type { .. })
+            );
+            conflicts.push(if added_on_both {
+                Conflict::AddedDifferently(target)
+            } else {
+                Conflict::RemovedAndChanged(target)
+            });
+            ours.clone()
+        }
+    }
+}
+
+/// Take whichever side changed a field; on a conflict, call `on_conflict`
+/// and keep `ours`.
+fn resolve<T: Serialize + Clone>(base: &T, ours: &T, theirs: &T, on_conflict: impl FnOnce()) -> T {
+    if same(ours, theirs) || same(theirs, base) {
+        ours.clone()
+    } else if same(ours, base) {
+        theirs.clone()
+    } else {
+        on_conflict();
+        ours.clone()
+    }
+}
+
+fn merge_node(base: &Node, ours: &Node, theirs: &Node, conflicts: &mut Vec<Conflict>) -> Node {
+    let node = ours.id;
+    let mut field_conflict = |field: &str| {
+        conflicts.push(Conflict::NodeField {
+            node,
+            field: field.to_string(),
+        })
+    };
+
+    let mut merged = ours.clone();
+    merged.kind = resolve(&base.kind, &ours.kind, &theirs.kind, || {
+        field_conflict("kind")
+    });
+    merged.type_signature = resolve(
+        &base.type_signature,
+        &ours.type_signature,
+        &theirs.type_signature,
+        || field_conflict("type signature"),
+    );
+    merged.value = resolve(&base.value, &ours.value, &theirs.value, || {
+        field_conflict("value")
+    });
+    merged.provenance = resolve(
+        &base.provenance,
+        &ours.provenance,
+        &theirs.provenance,
+        || field_conflict("provenance"),
+    );
+    let keys: BTreeSet<&String> = base
+        .annotations
+        .keys()
+        .chain(ours.annotations.keys())
+        .chain(theirs.annotations.keys())
+        .collect();
+    for key in keys {
+        let value = resolve(
+            &base.annotations.get(key),
+            &ours.annotations.get(key),
+            &theirs.annotations.get(key),
+            || field_conflict(&format!("annotation {key}")),
+        );
+        match value {
+            Some(value) => merged.annotations.insert(key.clone(), value.clone()),
+            None => merged.annotations.remove(key),
+        };
+    }
+    merged.contract = resolve(&base.contract, &ours.contract, &theirs.contract, || {
+        conflicts.push(Conflict::Contract { node })
+    });
+    merged
+}
+
+fn merge_edge(base: &Edge, ours: &Edge, theirs: &Edge, conflicts: &mut Vec<Conflict>) -> Edge {
+    let edge = ours.id;
+    let mut merged = ours.clone();
+    (merged.source, merged.target) = resolve(
+        &(base.source, base.target),
+        &(ours.source, ours.target),
+        &(theirs.source, theirs.target),
+        || conflicts.push(Conflict::EdgeRetargeted { edge }),
+    );
+    let mut field_conflict = |field: &str| {
+        conflicts.push(Conflict::EdgeField {
+            edge,
+            field: field.to_string(),
+        })
+    };
+    merged.data_type = resolve(&base.data_type, &ours.data_type, &theirs.data_type, || {
+        field_conflict("data type")
+    });
+    merged.lifetime = resolve(&base.lifetime, &ours.lifetime, &theirs.lifetime, || {
+        field_conflict("lifetime")
+    });
+    merged.bandwidth = resolve(&base.bandwidth, &ours.bandwidth, &theirs.bandwidth, || {
+        field_conflict("bandwidth")
+    });
+    merged
+}
+
+fn merge_region(
+    base: &Region,
+    ours: &Region,
+    theirs: &Region,
+    conflicts: &mut Vec<Conflict>,
+) -> Region {
+    let region = ours.id;
+    let mut field_conflict = |field: &str| {
+        conflicts.push(Conflict::RegionField {
+            region,
+            field: field.to_string(),
+        })
+    };
+    let mut merged = ours.clone();
+    merged.kind = resolve(&base.kind, &ours.kind, &theirs.kind, || {
+        field_conflict("kind")
+    });
+    merged.children = resolve(&base.children, &ours.children, &theirs.children, || {
+        field_conflict("children")
+    });
+    merged.constraints = resolve(
+        &base.constraints,
+        &ours.constraints,
+        &theirs.constraints,
+        || field_conflict("constraints"),
+    );
+    merged.interfaces = resolve(
+        &base.interfaces,
+        &ours.interfaces,
+        &theirs.interfaces,
+        || field_conflict("interfaces"),
+    );
+    merged.parent = resolve(&base.parent, &ours.parent, &theirs.parent, || {
+        field_conflict("parent")
+    });
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::Contract;
+    use crate::graph::node::{ArithmeticOp, NodeKind};
+    use crate::types::Predicate;
+
+    fn base() -> (Graph, [NodeId; 3], EdgeId) {
+        let mut g = Graph::new();
+        let a = Node::new(NodeKind::Literal);
+        let b = Node::new(NodeKind::Literal);
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add));
+        let ids = [a.id, b.id, add.id];
+        for n in [a, b, add] {
+            g.add_node(n).unwrap();
+        }
+        let edge = g.add_edge(Edge::new((ids[0], 0), (ids[2], 0))).unwrap();
+        g.add_edge(Edge::new((ids[1], 0), (ids[2], 1))).unwrap();
+        (g, ids, edge)
+    }
+
+    #[test]
+    fn independent_edits_merge_cleanly() {
+        let (base, [a, b, add], _) = base();
+        let mut ours = base.clone();
+        ours.get_node_mut(&add)
+            .unwrap()
+            .annotations
+            .insert("hint".into(), "inline".into());
+        let extra = ours.add_node(Node::new(NodeKind::Literal)).unwrap();
+
+        let mut theirs = base.clone();
+        theirs.get_node_mut(&add).unwrap().contract = Some(Contract::with_conditions(
+            vec![],
+            vec![Predicate::positive("output")],
+        ));
+        theirs.remove_node(a).unwrap();
+
+        let merged = merge3(&base, &ours, &theirs);
+        assert!(merged.is_clean(), "{:?}", merged.conflicts);
+        let g = &merged.graph;
+        assert!(g.get_node(&a).is_none());
+        assert!(g.get_node(&extra).is_some());
+        let node = g.get_node(&add).unwrap();
+        assert_eq!(node.annotations["hint"], "inline");
+        assert!(node.contract.is_some());
+        assert_eq!(g.incoming_edges(&add).len(), 1);
+        assert!(g.get_node(&b).is_some());
+
+        let mut replayed = base.clone();
+        replayed.apply_patch(&merged.patch).unwrap();
+        assert!(GraphPatch::between(&replayed, g).is_empty());
+    }
+
+    #[test]
+    fn conflicting_edits_are_reported() {
+        let (base, [a, b, add], edge) = base();
+        let mut ours = base.clone();
+        ours.get_node_mut(&add).unwrap().kind = NodeKind::Arithmetic(ArithmeticOp::Mul);
+        ours.get_node_mut(&add).unwrap().contract = Some(Contract::with_conditions(
+            vec![Predicate::positive("input")],
+            vec![],
+        ));
+        ours.remove_edge(edge).unwrap();
+        ours.add_edge(Edge::with_id(edge, (b, 0), (add, 0)))
+            .unwrap();
+
+        let mut theirs = base.clone();
+        theirs.get_node_mut(&add).unwrap().kind = NodeKind::Arithmetic(ArithmeticOp::Sub);
+        theirs.get_node_mut(&add).unwrap().contract = Some(Contract::pure_default());
+        theirs.remove_edge(edge).unwrap();
+        theirs
+            .add_edge(Edge::with_id(edge, (a, 0), (add, 1)))
+            .unwrap();
+        // An edge out of a node ours keeps but theirs removes is fine; an
+        // edge into a node theirs removed is not
+        let c = ours.add_node(Node::new(NodeKind::Literal)).unwrap();
+        let dangling = ours.add_edge(Edge::new((c, 0), (a, 0))).unwrap();
+        theirs.remove_node(a).unwrap();
+
+        let merged = merge3(&base, &ours, &theirs);
+        let conflicts = &merged.conflicts;
+        assert!(conflicts.contains(&Conflict::NodeField {
+            node: add,
+            field: "kind".into(),
+        }));
+        assert!(conflicts.contains(&Conflict::Contract { node: add }));
+        assert!(conflicts.contains(&Conflict::RemovedAndChanged(PatchTarget::Edge(edge))));
+        assert!(conflicts
+            .iter()
+            .any(|c| matches!(c, Conflict::Unapplied { target, .. } if *target == PatchTarget::Edge(dangling))));
+
+        // Conflicting fields keep our side
+        let node = merged.graph.get_node(&add).unwrap();
+        assert_eq!(node.kind, NodeKind::Arithmetic(ArithmeticOp::Mul));
+        assert!(merged.graph.validate().is_ok());
+    }
+
+    #[test]
+    fn both_sides_retargeting_an_edge_conflicts() {
+        let (base, [_, b, add], edge) = base();
+        let c = Node::new(NodeKind::Literal);
+        let c_id = c.id;
+        let mut base = base;
+        base.add_node(c).unwrap();
+
+        let mut ours = base.clone();
+        ours.remove_edge(edge).unwrap();
+        ours.add_edge(Edge::with_id(edge, (b, 0), (add, 0)))
+            .unwrap();
+        let mut theirs = base.clone();
+        theirs.remove_edge(edge).unwrap();
+        theirs
+            .add_edge(Edge::with_id(edge, (c_id, 0), (add, 0)))
+            .unwrap();
+
+        let merged = merge3(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, vec![Conflict::EdgeRetargeted { edge }]);
+        assert_eq!(merged.graph.get_edge(&edge).unwrap().source, (b, 0));
+    }
+}
@@ -3,10 +3,12 @@
 //! Every node, edge, and graph can be content-addressed via SHA-256.
 //! The hash covers the semantic content (kind, type, contract) but not
 //! the randomly-assigned UUID, allowing identical computations to be
-//! deduplicated.
+//! deduplicated. `content_id` turns a hash into a UUID, so that IDs can be
+//! derived from content (see `graph::identity`).
 
 use serde::Serialize;
 use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
 /// A 32-byte SHA-256 content hash.
 pub type ContentHash = [u8; 32];
@@ -19,6 +21,14 @@ pub fn content_hash<T: Serialize>(value: &T) -> ContentHash {
     hasher.finalize().into()
 }
 
+/// A UUID derived from a content hash: its first 16 bytes, marked as a
+/// version-8 (custom) UUID.
+pub fn content_id(hash: &ContentHash) -> Uuid {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid()
+}
+
 /// Format a content hash as a hex string.
 pub fn hash_hex(hash: &ContentHash) -> String {
     hash.iter().map(|b| format!("{b:02x}")).collect()
@@ -42,6 +52,13 @@ mod tests {
         assert_ne!(h1, h2);
     }
 
+    #[test]
+    fn content_id_is_a_custom_uuid() {
+        let id = content_id(&content_hash(&"node"));
+        assert_eq!(id.get_version_num(), 8);
+        assert_eq!(id, content_id(&content_hash(&"node")));
+    }
+
     #[test]
     fn hash_hex_format() {
         let h = content_hash(&42u32);
@@ -1,6 +1,9 @@
 pub mod builder;
 pub mod contract;
+pub mod fixed;
 pub mod graph;
 pub mod hash;
+pub mod prob;
 pub mod provenance;
 pub mod types;
+pub mod value;
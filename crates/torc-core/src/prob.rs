@@ -0,0 +1,776 @@
+//! Probabilistic semantics.
+//!
+//! A `Distribution<T>` value is one of a small library of families over
+//! numbers, read back into `T` (`Bool`, `Int`, `Float` or `Fixed`) when
+//! sampled:
+//!
+//! | Family        | Parameters           | Support                 |
+//! |---------------|----------------------|-------------------------|
+//! | `bernoulli`   | `p`                  | `{0, 1}`                |
+//! | `uniform`     | `lo`, `hi`           | `[lo, hi)`              |
+//! | `normal`      | `mean`, `std_dev`    | reals                   |
+//! | `exponential` | `rate`               | `[0, ∞)`                |
+//! | `categorical` | `weights`            | `{0, .., n - 1}`        |
+//! | `empirical`   | weighted `samples`   | the sample points       |
+//!
+//! `Construct` with a distribution output builds the family named by its
+//! `"distribution"` annotation from its inputs, in table order. As a value,
+//! a distribution is the variant case of its family name holding a record
+//! of its parameters; `empirical` distributions are the result of
+//! conditioning and are not constructed directly.
+//!
+//! The probabilistic nodes take a distribution on input 0:
+//!
+//! - `Sample: Distribution<T> → T` draws one value.
+//! - `Condition: (Distribution<T>, T, T) → Posterior<T, e>` restricts the
+//!   distribution to the evidence `lo <= x <= hi`. Discrete families are
+//!   conditioned exactly; continuous ones by rejection sampling, keeping
+//!   the accepted draws as an `empirical` posterior.
+//! - `Expectation: Distribution<T> → Float` is the mean, exact for discrete
+//!   families and a Monte Carlo estimate otherwise.
+//! - `Entropy: Distribution<T> → Float` is the entropy in nats, differential
+//!   for continuous families and the plug-in estimate for `empirical`.
+//! - `Approximate: T → Approximate<T, e>` passes its value through.
+//!
+//! A `Posterior` is accepted wherever a `Distribution` over the same type
+//! is. Draws come from a seeded SplitMix64 generator, so evaluation is
+//! reproducible.
+
+use std::f64::consts::{E, PI};
+
+use thiserror::Error;
+
+use crate::graph::node::{Node, NodeKind};
+use crate::types::Type;
+use crate::value::{FixedValue, FloatValue, IntValue, Value};
+
+/// Annotation naming the family a `Construct` node builds.
+pub const DISTRIBUTION_ANNOTATION: &str = "distribution";
+
+/// Errors from probabilistic operations.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ProbError {
+    #[error("unknown distribution \"{0}\"")]
+    UnknownFamily(String),
+
+    #[error("{family} takes {expected} parameter(s), found {found}")]
+    Arity {
+        family: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("invalid {family} parameters: {message}")]
+    InvalidParameters {
+        family: &'static str,
+        message: String,
+    },
+
+    #[error("{0} is not a distribution")]
+    NotADistribution(String),
+
+    #[error("{0} is not a number")]
+    NotANumber(String),
+
+    #[error("{value} does not fit in {ty}")]
+    OutOfRange { value: f64, ty: Type },
+
+    #[error("evidence [{lo}, {hi}] has no probability mass")]
+    ImpossibleEvidence { lo: f64, hi: f64 },
+
+    #[error("{kind} node: {message}")]
+    Signature { kind: NodeKind, message: String },
+}
+
+/// A probability distribution over numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    Bernoulli {
+        p: f64,
+    },
+    Uniform {
+        lo: f64,
+        hi: f64,
+    },
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+    Exponential {
+        rate: f64,
+    },
+    /// Index `i` with probability proportional to `weights[i]`.
+    Categorical {
+        weights: Vec<f64>,
+    },
+    /// Points with their (unnormalized) weights.
+    Empirical {
+        samples: Vec<(f64, f64)>,
+    },
+}
+
+impl Distribution {
+    /// The family name, as used by the `"distribution"` annotation.
+    pub fn family(&self) -> &'static str {
+        match self {
+            Distribution::Bernoulli { .. } => "bernoulli",
+            Distribution::Uniform { .. } => "uniform",
+            Distribution::Normal { .. } => "normal",
+            Distribution::Exponential { .. } => "exponential",
+            Distribution::Categorical { .. } => "categorical",
+            Distribution::Empirical { .. } => "empirical",
+        }
+    }
+
+    /// Build a family from its parameters, in the order of the table.
+    pub fn from_parameters(family: &str, params: &[Value]) -> Result<Self, ProbError> {
+        let arity = |family, expected| {
+            if params.len() == expected {
+                Ok(())
+            } else {
+                Err(ProbError::Arity {
+                    family,
+                    expected,
+                    found: params.len(),
+                })
+            }
+        };
+        let num = |i: usize| to_number(&params[i]);
+        let dist = match family {
+            "bernoulli" => {
+                arity("bernoulli", 1)?;
+                Distribution::Bernoulli { p: num(0)? }
+            }
+            "uniform" => {
+                arity("uniform", 2)?;
+                Distribution::Uniform {
+                    lo: num(0)?,
+                    hi: num(1)?,
+                }
+            }
+            "normal" => {
+                arity("normal", 2)?;
+                Distribution::Normal {
+                    mean: num(0)?,
+                    std_dev: num(1)?,
+                }
+            }
+            "exponential" => {
+                arity("exponential", 1)?;
+                Distribution::Exponential { rate: num(0)? }
+            }
+            "categorical" => {
+                arity("categorical", 1)?;
+                let weights = params[0]
+                    .elements()
+                    .ok_or_else(|| ProbError::NotANumber(params[0].to_string()))?;
+                Distribution::Categorical {
+                    weights: weights.iter().map(to_number).collect::<Result<_, _>>()?,
+                }
+            }
+            other => return Err(ProbError::UnknownFamily(other.to_string())),
+        };
+        dist.validated()
+    }
+
+    fn validated(self) -> Result<Self, ProbError> {
+        let invalid = |message: &str| {
+            Err(ProbError::InvalidParameters {
+                family: self.family(),
+                message: message.to_string(),
+            })
+        };
+        match &self {
+            Distribution::Bernoulli { p } if !(0.0..=1.0).contains(p) => {
+                invalid("p must be in [0, 1]")
+            }
+            Distribution::Uniform { lo, hi } if !(lo < hi && hi.is_finite() && lo.is_finite()) => {
+                invalid("lo must be below hi")
+            }
+            Distribution::Normal { mean, std_dev } if !(mean.is_finite() && *std_dev > 0.0) => {
+                invalid("std_dev must be positive")
+            }
+            Distribution::Exponential { rate } if !(*rate > 0.0 && rate.is_finite()) => {
+                invalid("rate must be positive")
+            }
+            Distribution::Categorical { weights } if !valid_weights(weights.iter().copied()) => {
+                invalid("weights must be non-negative with a positive sum")
+            }
+            Distribution::Empirical { samples } if !valid_weights(samples.iter().map(|s| s.1)) => {
+                invalid("weights must be non-negative with a positive sum")
+            }
+            _ => Ok(self),
+        }
+    }
+
+    /// Read a distribution value back.
+    pub fn from_value(value: &Value) -> Result<Self, ProbError> {
+        let not_one = || ProbError::NotADistribution(value.to_string());
+        let Value::Variant { tag, value: params } = value else {
+            return Err(not_one());
+        };
+        let Value::Record(fields) = params.as_ref() else {
+            return Err(not_one());
+        };
+        let field = |name: &str| fields.get(name).ok_or_else(not_one);
+        let num = |name: &str| to_number(field(name)?);
+        let list = |name: &str| field(name)?.elements().ok_or_else(not_one);
+        let dist = match tag.as_str() {
+            "bernoulli" => Distribution::Bernoulli { p: num("p")? },
+            "uniform" => Distribution::Uniform {
+                lo: num("lo")?,
+                hi: num("hi")?,
+            },
+            "normal" => Distribution::Normal {
+                mean: num("mean")?,
+                std_dev: num("std_dev")?,
+            },
+            "exponential" => Distribution::Exponential { rate: num("rate")? },
+            "categorical" => Distribution::Categorical {
+                weights: list("weights")?
+                    .iter()
+                    .map(to_number)
+                    .collect::<Result<_, _>>()?,
+            },
+            "empirical" => Distribution::Empirical {
+                samples: list("samples")?
+                    .iter()
+                    .map(|s| match s {
+                        Value::Tuple(pair) if pair.len() == 2 => {
+                            Ok((to_number(&pair[0])?, to_number(&pair[1])?))
+                        }
+                        _ => Err(not_one()),
+                    })
+                    .collect::<Result<_, _>>()?,
+            },
+            _ => return Err(not_one()),
+        };
+        dist.validated()
+    }
+
+    /// This distribution as a value.
+    pub fn to_value(&self) -> Value {
+        let float = |x: f64| Value::f64(x);
+        let fields: Vec<(&str, Value)> = match self {
+            Distribution::Bernoulli { p } => vec![("p", float(*p))],
+            Distribution::Uniform { lo, hi } => vec![("lo", float(*lo)), ("hi", float(*hi))],
+            Distribution::Normal { mean, std_dev } => {
+                vec![("mean", float(*mean)), ("std_dev", float(*std_dev))]
+            }
+            Distribution::Exponential { rate } => vec![("rate", float(*rate))],
+            Distribution::Categorical { weights } => vec![(
+                "weights",
+                Value::Vec(weights.iter().copied().map(float).collect()),
+            )],
+            Distribution::Empirical { samples } => vec![(
+                "samples",
+                Value::Vec(
+                    samples
+                        .iter()
+                        .map(|&(x, w)| Value::Tuple(vec![float(x), float(w)]))
+                        .collect(),
+                ),
+            )],
+        };
+        let record = fields
+            .into_iter()
+            .map(|(name, v)| (name.to_string(), v))
+            .collect();
+        Value::variant(self.family(), Value::Record(record))
+    }
+
+    /// The points and weights of a discrete distribution, or `None` for a
+    /// continuous one.
+    fn support(&self) -> Option<Vec<(f64, f64)>> {
+        match self {
+            Distribution::Bernoulli { p } => Some(vec![(0.0, 1.0 - p), (1.0, *p)]),
+            Distribution::Categorical { weights } => Some(
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &w)| (i as f64, w))
+                    .collect(),
+            ),
+            Distribution::Empirical { samples } => Some(samples.clone()),
+            _ => None,
+        }
+    }
+
+    /// Draw one value.
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        match self {
+            Distribution::Uniform { lo, hi } => lo + (hi - lo) * rng.next_f64(),
+            Distribution::Normal { mean, std_dev } => {
+                // Box–Muller; 1 - u keeps the logarithm finite
+                let (u, v) = (1.0 - rng.next_f64(), rng.next_f64());
+                mean + std_dev * (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+            }
+            Distribution::Exponential { rate } => -(1.0 - rng.next_f64()).ln() / rate,
+            discrete => {
+                let support = discrete.support().expect("discrete family");
+                let total: f64 = support.iter().map(|s| s.1).sum();
+                let mut target = rng.next_f64() * total;
+                for &(x, w) in &support {
+                    if target < w {
+                        return x;
+                    }
+                    target -= w;
+                }
+                // Rounding left the target just past the last weight
+                support
+                    .iter()
+                    .rev()
+                    .find(|s| s.1 > 0.0)
+                    .expect("positive weight")
+                    .0
+            }
+        }
+    }
+
+    /// The exact mean.
+    pub fn mean(&self) -> f64 {
+        match self {
+            Distribution::Uniform { lo, hi } => (lo + hi) / 2.0,
+            Distribution::Normal { mean, .. } => *mean,
+            Distribution::Exponential { rate } => 1.0 / rate,
+            discrete => {
+                let support = discrete.support().expect("discrete family");
+                let total: f64 = support.iter().map(|s| s.1).sum();
+                support.iter().map(|(x, w)| x * w).sum::<f64>() / total
+            }
+        }
+    }
+
+    /// The mean as `Expectation` evaluates it: exact for discrete families,
+    /// the average of `draws` draws otherwise.
+    pub fn expectation(&self, draws: usize, rng: &mut Rng) -> f64 {
+        if self.support().is_some() || draws == 0 {
+            return self.mean();
+        }
+        (0..draws).map(|_| self.sample(rng)).sum::<f64>() / draws as f64
+    }
+
+    /// The entropy in nats.
+    pub fn entropy(&self) -> f64 {
+        match self {
+            Distribution::Uniform { lo, hi } => (hi - lo).ln(),
+            Distribution::Normal { std_dev, .. } => 0.5 * (2.0 * PI * E * std_dev * std_dev).ln(),
+            Distribution::Exponential { rate } => 1.0 - rate.ln(),
+            discrete => {
+                // Equal points are one outcome
+                let mut support = discrete.support().expect("discrete family");
+                support.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut merged: Vec<(f64, f64)> = Vec::new();
+                for (x, w) in support {
+                    match merged.last_mut() {
+                        Some(last) if last.0 == x => last.1 += w,
+                        _ => merged.push((x, w)),
+                    }
+                }
+                let total: f64 = merged.iter().map(|s| s.1).sum();
+                -merged
+                    .iter()
+                    .map(|s| s.1 / total)
+                    .filter(|&p| p > 0.0)
+                    .map(|p| p * p.ln())
+                    .sum::<f64>()
+            }
+        }
+    }
+
+    /// Restrict to the evidence `lo <= x <= hi`, rejection-sampling
+    /// `draws` proposals from a continuous family.
+    pub fn condition(
+        &self,
+        lo: f64,
+        hi: f64,
+        draws: usize,
+        rng: &mut Rng,
+    ) -> Result<Distribution, ProbError> {
+        let within = |x: &f64| lo <= *x && *x <= hi;
+        let samples: Vec<(f64, f64)> = match self.support() {
+            Some(support) => support
+                .into_iter()
+                .filter(|(x, w)| within(x) && *w > 0.0)
+                .collect(),
+            None => (0..draws)
+                .map(|_| self.sample(rng))
+                .filter(within)
+                .map(|x| (x, 1.0))
+                .collect(),
+        };
+        if samples.is_empty() {
+            return Err(ProbError::ImpossibleEvidence { lo, hi });
+        }
+        Ok(Distribution::Empirical { samples })
+    }
+}
+
+fn valid_weights(mut weights: impl Iterator<Item = f64>) -> bool {
+    let mut total = 0.0;
+    let non_negative = weights.all(|w| {
+        total += w;
+        w >= 0.0 && w.is_finite()
+    });
+    non_negative && total > 0.0
+}
+
+/// A numeric value as an `f64`: booleans are 0 or 1.
+pub fn to_number(value: &Value) -> Result<f64, ProbError> {
+    match value {
+        Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        Value::Int(i) => Ok(i.to_f64()),
+        Value::Float(x) => Ok(x.value()),
+        Value::Fixed(x) => Ok(x.to_f64()),
+        other => Err(ProbError::NotANumber(other.to_string())),
+    }
+}
+
+/// A drawn number as a value of `ty`: non-zero is `true`, integers round
+/// to nearest and fixed-point to the nearest representable value.
+pub fn from_number(x: f64, ty: &Type) -> Result<Value, ProbError> {
+    let out_of_range = || ProbError::OutOfRange {
+        value: x,
+        ty: ty.clone(),
+    };
+    match element_or_self(ty) {
+        Type::Bool => Ok(Value::Bool(x != 0.0)),
+        Type::Int { width, signedness } => {
+            let rounded = x.round();
+            if !rounded.is_finite() || rounded.abs() >= 2f64.powi(127) {
+                return Err(out_of_range());
+            }
+            IntValue::checked(rounded as i128, *width, *signedness)
+                .map(Value::Int)
+                .ok_or_else(out_of_range)
+        }
+        Type::Float { precision } => Ok(Value::Float(FloatValue::new(x, *precision))),
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => FixedValue::from_f64(x, *total_bits, *frac_bits)
+            .map(Value::Fixed)
+            .ok_or_else(out_of_range),
+        _ => Err(out_of_range()),
+    }
+}
+
+/// `ty` with an `Approximate` or `Interval` wrapper peeled.
+fn element_or_self(ty: &Type) -> &Type {
+    match ty.base_type() {
+        Type::Approximate { inner, .. } | Type::Interval { inner, .. } => inner.base_type(),
+        base => base,
+    }
+}
+
+/// The type a distribution or posterior type is over.
+pub fn element_type(ty: &Type) -> Option<&Type> {
+    match ty.base_type() {
+        Type::Distribution(inner) | Type::Posterior { inner, .. } => Some(inner),
+        _ => None,
+    }
+}
+
+/// Check the signature of a probabilistic node, or of a `Construct` node
+/// building a distribution, against the type rules.
+///
+/// Nodes without a type signature, and other kinds, are not checked.
+pub fn check_signature(node: &Node) -> Result<(), ProbError> {
+    let Some(sig) = &node.type_signature else {
+        return Ok(());
+    };
+    let fail = |message: String| ProbError::Signature {
+        kind: node.kind.clone(),
+        message,
+    };
+    let count = |inputs: usize, outputs: usize| {
+        if sig.inputs.len() == inputs && sig.outputs.len() == outputs {
+            Ok(())
+        } else {
+            Err(fail(format!(
+                "expected {inputs} input(s) and {outputs} output(s), found {} and {}",
+                sig.inputs.len(),
+                sig.outputs.len()
+            )))
+        }
+    };
+    let distribution = |port: usize| {
+        let ty = &sig.inputs[port];
+        element_type(ty).ok_or_else(|| fail(format!("expected a distribution, found {ty}")))
+    };
+    let same = |found: &Type, expected: &Type| {
+        if element_or_self(found) == expected.base_type() {
+            Ok(())
+        } else {
+            Err(fail(format!("expected {expected}, found {found}")))
+        }
+    };
+    let numeric = |ty: &Type| {
+        if matches!(
+            ty.base_type(),
+            Type::Bool | Type::Int { .. } | Type::Float { .. } | Type::Fixed { .. }
+        ) {
+            Ok(())
+        } else {
+            Err(fail(format!("{ty} is not a numeric type")))
+        }
+    };
+    let float = |ty: &Type| {
+        if matches!(element_or_self(ty), Type::Float { .. }) {
+            Ok(())
+        } else {
+            Err(fail(format!("expected a float, found {ty}")))
+        }
+    };
+
+    match node.kind {
+        NodeKind::Sample => {
+            count(1, 1)?;
+            let element = distribution(0)?;
+            numeric(element)?;
+            same(&sig.outputs[0], element)
+        }
+        NodeKind::Condition => {
+            count(3, 1)?;
+            let element = distribution(0)?;
+            numeric(element)?;
+            same(&sig.inputs[1], element)?;
+            same(&sig.inputs[2], element)?;
+            match sig.outputs[0].base_type() {
+                Type::Posterior { inner, .. } => same(inner, element),
+                other => Err(fail(format!("expected a posterior, found {other}"))),
+            }
+        }
+        NodeKind::Expectation => {
+            count(1, 1)?;
+            numeric(distribution(0)?)?;
+            float(&sig.outputs[0])
+        }
+        NodeKind::Entropy => {
+            count(1, 1)?;
+            distribution(0)?;
+            float(&sig.outputs[0])
+        }
+        NodeKind::Approximate => {
+            count(1, 1)?;
+            match sig.outputs[0].base_type() {
+                Type::Approximate { inner, max_error } if *max_error >= 0.0 => {
+                    same(&sig.inputs[0], inner)
+                }
+                other => Err(fail(format!(
+                    "expected an approximation with a non-negative error, found {other}"
+                ))),
+            }
+        }
+        NodeKind::Construct => {
+            let Some(output) = sig.outputs.first() else {
+                return Ok(());
+            };
+            let Some(element) = element_type(output) else {
+                return Ok(());
+            };
+            numeric(element)?;
+            match node.annotations.get(DISTRIBUTION_ANNOTATION) {
+                Some(family) => {
+                    let expected = match family.as_str() {
+                        "bernoulli" | "exponential" | "categorical" => 1,
+                        "uniform" | "normal" => 2,
+                        other => return Err(ProbError::UnknownFamily(other.to_string())),
+                    };
+                    if sig.inputs.len() == expected {
+                        Ok(())
+                    } else {
+                        Err(fail(format!(
+                            "{family} takes {expected} parameter(s), found {}",
+                            sig.inputs.len()
+                        )))
+                    }
+                }
+                None => Err(fail(format!(
+                    "building {output} needs a \"{DISTRIBUTION_ANNOTATION}\" annotation"
+                ))),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A SplitMix64 pseudo-random generator.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform draw from `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Predicate, TypeSignature};
+
+    #[test]
+    fn distributions_round_trip_through_values() {
+        let dists = [
+            Distribution::Bernoulli { p: 0.25 },
+            Distribution::Uniform { lo: -1.0, hi: 3.0 },
+            Distribution::Normal {
+                mean: 0.0,
+                std_dev: 2.0,
+            },
+            Distribution::Exponential { rate: 0.5 },
+            Distribution::Categorical {
+                weights: vec![1.0, 0.0, 3.0],
+            },
+            Distribution::Empirical {
+                samples: vec![(1.5, 1.0), (2.5, 2.0)],
+            },
+        ];
+        for dist in dists {
+            let value = dist.to_value();
+            assert!(value.conforms_to(&Type::Distribution(Box::new(Type::f64()))));
+            assert_eq!(Distribution::from_value(&value).unwrap(), dist);
+        }
+
+        assert!(matches!(
+            Distribution::from_parameters("normal", &[Value::f64(0.0), Value::f64(-1.0)]),
+            Err(ProbError::InvalidParameters { .. })
+        ));
+        assert!(matches!(
+            Distribution::from_parameters("uniform", &[Value::f64(0.0)]),
+            Err(ProbError::Arity { expected: 2, .. })
+        ));
+        assert!(!Value::f64(1.0).conforms_to(&Type::Distribution(Box::new(Type::f64()))));
+    }
+
+    #[test]
+    fn monte_carlo_estimates_converge() {
+        let mut rng = Rng::new(7);
+        let normal = Distribution::Normal {
+            mean: 3.0,
+            std_dev: 1.0,
+        };
+        assert!((normal.expectation(20_000, &mut rng) - 3.0).abs() < 0.05);
+
+        let exp = Distribution::Exponential { rate: 2.0 };
+        assert!((exp.expectation(20_000, &mut rng) - 0.5).abs() < 0.02);
+
+        // Discrete families are exact
+        let coin = Distribution::Bernoulli { p: 0.3 };
+        assert_eq!(coin.expectation(10, &mut rng), 0.3);
+        assert!((coin.entropy() - 0.6108643).abs() < 1e-6);
+        let uniform = Distribution::Uniform { lo: 0.0, hi: E };
+        assert!((uniform.entropy() - 1.0).abs() < 1e-12);
+
+        let mut draws = Rng::new(1);
+        let d = Distribution::Categorical {
+            weights: vec![0.0, 1.0, 0.0],
+        };
+        assert!((0..100).all(|_| d.sample(&mut draws) == 1.0));
+    }
+
+    #[test]
+    fn conditioning_restricts_the_support() {
+        let mut rng = Rng::new(11);
+        let d = Distribution::Categorical {
+            weights: vec![1.0, 1.0, 2.0],
+        };
+        let posterior = d.condition(1.0, 2.0, 0, &mut rng).unwrap();
+        assert!((posterior.mean() - 5.0 / 3.0).abs() < 1e-12);
+
+        let u = Distribution::Uniform { lo: 0.0, hi: 10.0 };
+        let posterior = u.condition(2.0, 4.0, 5_000, &mut rng).unwrap();
+        let Distribution::Empirical { samples } = &posterior else {
+            panic!("expected an empirical posterior");
+        };
+        assert!(samples.iter().all(|&(x, _)| (2.0..=4.0).contains(&x)));
+        assert!((posterior.mean() - 3.0).abs() < 0.1);
+
+        assert!(matches!(
+            u.condition(20.0, 30.0, 1_000, &mut rng),
+            Err(ProbError::ImpossibleEvidence { .. })
+        ));
+    }
+
+    #[test]
+    fn signatures_follow_the_type_rules() {
+        let dist = Type::Distribution(Box::new(Type::i32()));
+        let node = |kind, inputs, outputs| {
+            Node::new(kind).with_type_signature(TypeSignature::new(inputs, outputs))
+        };
+
+        assert!(check_signature(&node(
+            NodeKind::Sample,
+            vec![dist.clone()],
+            vec![Type::i32()]
+        ))
+        .is_ok());
+        assert!(check_signature(&node(
+            NodeKind::Sample,
+            vec![dist.clone()],
+            vec![Type::f64()]
+        ))
+        .is_err());
+        assert!(check_signature(&node(
+            NodeKind::Sample,
+            vec![Type::i32()],
+            vec![Type::i32()]
+        ))
+        .is_err());
+
+        let posterior = Type::Posterior {
+            inner: Box::new(Type::i32().refined(Predicate::BoolLit(true))),
+            evidence: "in range".into(),
+        };
+        let condition = node(
+            NodeKind::Condition,
+            vec![dist.clone(), Type::i32(), Type::i32()],
+            vec![posterior.clone()],
+        );
+        assert!(check_signature(&condition).is_ok());
+        // A posterior can be sampled like any distribution
+        assert!(
+            check_signature(&node(NodeKind::Sample, vec![posterior], vec![Type::i32()])).is_ok()
+        );
+
+        assert!(check_signature(&node(
+            NodeKind::Entropy,
+            vec![dist.clone()],
+            vec![Type::f64()]
+        ))
+        .is_ok());
+        assert!(check_signature(&node(
+            NodeKind::Expectation,
+            vec![dist.clone()],
+            vec![Type::i32()]
+        ))
+        .is_err());
+
+        let mut build = node(
+            NodeKind::Construct,
+            vec![Type::f64(), Type::f64()],
+            vec![dist],
+        );
+        assert!(check_signature(&build).is_err());
+        build
+            .annotations
+            .insert(DISTRIBUTION_ANNOTATION.into(), "uniform".into());
+        assert!(check_signature(&build).is_ok());
+    }
+}
@@ -7,6 +7,7 @@
 use thiserror::Error;
 
 use crate::contract::{ObligationKind, ProofObligation, ProofStatus};
+use crate::types::subtype::{decide_refinement, Counterexample, Subtyping};
 use crate::types::{Linearity, Predicate, Type, ValueParam};
 
 /// Errors produced by the type compatibility checker.
@@ -30,6 +31,31 @@ pub enum TypeError {
 
     #[error("unresolved named type: {0}")]
     UnresolvedNamedType(String),
+
+    #[error(
+        "type {name} takes {expected_types} type and {expected_values} value parameter(s), \
+         found {found_types} and {found_values}"
+    )]
+    ArityMismatch {
+        name: String,
+        expected_types: usize,
+        expected_values: usize,
+        found_types: usize,
+        found_values: usize,
+    },
+
+    #[error("recursive type definition: {0}")]
+    RecursiveType(String),
+
+    #[error("invalid array length: {0}")]
+    InvalidArrayLength(i128),
+
+    #[error("refinement violated: {found} admits {counterexample}, which {expected} excludes")]
+    RefinementViolated {
+        expected: String,
+        found: String,
+        counterexample: Box<Counterexample>,
+    },
 }
 
 /// Check if `source` linearity can satisfy `target` linearity requirement.
@@ -54,7 +80,8 @@ pub fn linearity_compatible(source: Linearity, target: Linearity) -> bool {
 /// Check if `source` type is assignable to `target` type.
 ///
 /// Returns proof obligations generated by refinement/dependent subtyping.
-/// Named types must be resolved before calling this function.
+/// Named types must be resolved before calling this function; see
+/// `TypeTable::compatible`.
 pub fn types_compatible(source: &Type, target: &Type) -> Result<Vec<ProofObligation>, TypeError> {
     // Named types must be resolved before checking
     if let Type::Named(name) = source {
@@ -193,7 +220,7 @@ fn check_compatible(
             check_compatible(se, te, obligations)
         }
 
-        // === Refinement: generates proof obligation ===
+        // === Refinement: decided by intervals, else a proof obligation ===
         (
             Type::Refined {
                 base: sb,
@@ -206,20 +233,16 @@ fn check_compatible(
         ) => {
             check_compatible(sb, tb, obligations)?;
             // Source predicate must imply target predicate
-            obligations.push(ProofObligation {
-                kind: ObligationKind::TypeRefinement,
-                predicate: Predicate::Implies(Box::new(sp.clone()), Box::new(tp.clone())),
-                description: "refinement subtyping: source predicate must imply target predicate"
-                    .to_string(),
-                status: ProofStatus::Pending,
-                witness: None,
-                waiver: None,
-            });
-            Ok(())
+            refine(
+                source,
+                target,
+                decide_refinement(sb, Some(sp), tp),
+                obligations,
+            )
         }
 
-        // Unrefined assignable to refined: base must match, generates obligation
-        // that `true` implies the target predicate (always the target predicate).
+        // Unrefined assignable to refined: base must match, and the target
+        // predicate must hold for every value of the source type.
         (
             src,
             Type::Refined {
@@ -228,15 +251,12 @@ fn check_compatible(
             },
         ) if !matches!(src, Type::Refined { .. }) => {
             check_compatible(src, tb, obligations)?;
-            obligations.push(ProofObligation {
-                kind: ObligationKind::TypeRefinement,
-                predicate: tp.clone(),
-                description: "unrefined source must satisfy target refinement".to_string(),
-                status: ProofStatus::Pending,
-                witness: None,
-                waiver: None,
-            });
-            Ok(())
+            refine(
+                source,
+                target,
+                decide_refinement(src, None, tp),
+                obligations,
+            )
         }
 
         // Refined assignable to unrefined: just check base types.
@@ -495,6 +515,28 @@ fn incompatible(source: &Type, target: &Type, context: &str) -> TypeError {
     }
 }
 
+/// Apply a refinement subtyping decision: accepted edges need nothing,
+/// rejected ones fail with their counterexample.
+fn refine(
+    source: &Type,
+    target: &Type,
+    decision: Subtyping,
+    obligations: &mut Vec<ProofObligation>,
+) -> Result<(), TypeError> {
+    match decision {
+        Subtyping::Accepted => Ok(()),
+        Subtyping::Rejected(counterexample) => Err(TypeError::RefinementViolated {
+            expected: format!("{target}"),
+            found: format!("{source}"),
+            counterexample: Box::new(counterexample),
+        }),
+        Subtyping::Obligation(ob) => {
+            obligations.push(*ob);
+            Ok(())
+        }
+    }
+}
+
 fn value_param_to_predicate(vp: &ValueParam) -> Predicate {
     match vp {
         ValueParam::Concrete(v) => Predicate::IntLit(*v),
@@ -557,7 +599,19 @@ mod tests {
 
     #[test]
     fn refinement_generates_obligation() {
-        let src = Type::i32().refined(Predicate::positive("value"));
+        // Parity is beyond interval reasoning, so the implication is left to
+        // the proof engine.
+        let even = Predicate::Eq(
+            Box::new(Predicate::Mod(
+                Box::new(Predicate::Var("value".into())),
+                Box::new(Predicate::IntLit(2)),
+            )),
+            Box::new(Predicate::IntLit(0)),
+        );
+        let src = Type::i32().refined(Predicate::And(
+            Box::new(Predicate::positive("value")),
+            Box::new(even),
+        ));
         let tgt = Type::i32().refined(Predicate::in_range("value", 0, 100));
         let result = types_compatible(&src, &tgt).unwrap();
         assert_eq!(result.len(), 1);
@@ -565,6 +619,22 @@ mod tests {
         assert!(matches!(result[0].predicate, Predicate::Implies(..)));
     }
 
+    #[test]
+    fn refinement_decided_by_intervals() {
+        let tgt = Type::i32().refined(Predicate::in_range("value", 0, 4095));
+
+        let src = Type::i32().refined(Predicate::in_range("x", 0, 100));
+        assert!(types_compatible(&src, &tgt).unwrap().is_empty());
+
+        let src = Type::i32().refined(Predicate::positive("value"));
+        match types_compatible(&src, &tgt) {
+            Err(TypeError::RefinementViolated { counterexample, .. }) => {
+                assert_eq!(counterexample.to_string(), "value = 4096");
+            }
+            other => panic!("expected a refinement violation, got {other:?}"),
+        }
+    }
+
     #[test]
     fn linearity_subtyping() {
         // Linear assignable to Affine
@@ -0,0 +1,508 @@
+//! User-defined types: aliases, generics, and monomorphization.
+//!
+//! A `TypeTable` maps names to `TypeDef`s. A definition is a body type that
+//! may refer to its type parameters as `Type::Named(param)` and to its value
+//! parameters as `ValueParam::Symbolic(param)` or as predicate variables in
+//! refinements. `TypeTable::resolve` expands every `Type::Named` and
+//! `Type::Parameterized` reference to a definition into its body with the
+//! arguments substituted, recursively, so downstream passes see structural
+//! types.
+//!
+//! `Array<T; N>` is built in: once `N` is concrete it resolves to `[T; N]`,
+//! which lets definitions abstract over array lengths:
+//!
+//! ```text
+//! type Matrix<T; R, C> = Array<Array<T; C>; R>;
+//! Matrix<f32; 3, 3>  ~>  [[f32; 3]; 3]
+//! ```
+//!
+//! Names without a definition are left as they are (opaque external types).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::contract::ProofObligation;
+use crate::types::check::{types_compatible, TypeError};
+use crate::types::{Predicate, Type, ValueParam};
+
+/// The built-in parameterized array, `Array<T; N>`.
+pub const ARRAY: &str = "Array";
+
+/// A named type definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeDef {
+    /// Type parameter names, referenced in the body as `Type::Named`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub type_params: Vec<String>,
+    /// Value parameter names, referenced in the body as
+    /// `ValueParam::Symbolic` or predicate variables.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub value_params: Vec<String>,
+    /// The defining type.
+    pub body: Type,
+}
+
+impl TypeDef {
+    /// A plain alias for `body`.
+    pub fn alias(body: Type) -> Self {
+        Self {
+            type_params: Vec::new(),
+            value_params: Vec::new(),
+            body,
+        }
+    }
+
+    /// Add a type parameter.
+    pub fn with_type_param(mut self, name: impl Into<String>) -> Self {
+        self.type_params.push(name.into());
+        self
+    }
+
+    /// Add a value parameter.
+    pub fn with_value_param(mut self, name: impl Into<String>) -> Self {
+        self.value_params.push(name.into());
+        self
+    }
+
+    /// Substitute arguments for the parameters in the body.
+    ///
+    /// `name` is only used for error reporting. The result is not resolved
+    /// further; see `TypeTable::resolve`.
+    pub fn instantiate(
+        &self,
+        name: &str,
+        type_args: &[Type],
+        value_args: &[ValueParam],
+    ) -> Result<Type, TypeError> {
+        if type_args.len() != self.type_params.len() || value_args.len() != self.value_params.len()
+        {
+            return Err(TypeError::ArityMismatch {
+                name: name.to_string(),
+                expected_types: self.type_params.len(),
+                expected_values: self.value_params.len(),
+                found_types: type_args.len(),
+                found_values: value_args.len(),
+            });
+        }
+        let types: BTreeMap<&str, &Type> = self
+            .type_params
+            .iter()
+            .map(String::as_str)
+            .zip(type_args)
+            .collect();
+        let values: BTreeMap<&str, &ValueParam> = self
+            .value_params
+            .iter()
+            .map(String::as_str)
+            .zip(value_args)
+            .collect();
+        Ok(substitute(&self.body, &types, &values))
+    }
+}
+
+/// A table of named type definitions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TypeTable {
+    defs: BTreeMap<String, TypeDef>,
+}
+
+impl TypeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    /// Define (or redefine) a type, returning the previous definition.
+    pub fn define(&mut self, name: impl Into<String>, def: TypeDef) -> Option<TypeDef> {
+        self.defs.insert(name.into(), def)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.defs.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<TypeDef> {
+        self.defs.remove(name)
+    }
+
+    /// Definitions in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &TypeDef)> {
+        self.defs.iter()
+    }
+
+    /// Expand every reference to a defined type, recursively.
+    ///
+    /// Fails on arity mismatches and on definitions that refer to
+    /// themselves, directly or through other definitions.
+    pub fn resolve(&self, ty: &Type) -> Result<Type, TypeError> {
+        self.resolve_in(ty, &mut Vec::new())
+    }
+
+    /// `types_compatible` after resolving both sides.
+    pub fn compatible(
+        &self,
+        source: &Type,
+        target: &Type,
+    ) -> Result<Vec<ProofObligation>, TypeError> {
+        types_compatible(&self.resolve(source)?, &self.resolve(target)?)
+    }
+
+    fn resolve_in(&self, ty: &Type, expanding: &mut Vec<String>) -> Result<Type, TypeError> {
+        match ty {
+            Type::Named(name) => match self.defs.get(name) {
+                Some(def) => self.expand(name, def, &[], &[], expanding),
+                None => Ok(ty.clone()),
+            },
+            Type::Parameterized {
+                name,
+                type_params,
+                value_params,
+            } => {
+                let type_args = type_params
+                    .iter()
+                    .map(|t| self.resolve_in(t, expanding))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Some(def) = self.defs.get(name) {
+                    return self.expand(name, def, &type_args, value_params, expanding);
+                }
+                match (name.as_str(), type_args.as_slice(), value_params.as_slice()) {
+                    (ARRAY, [element], [ValueParam::Concrete(n)]) => {
+                        let length =
+                            usize::try_from(*n).map_err(|_| TypeError::InvalidArrayLength(*n))?;
+                        Ok(Type::Array {
+                            element: Box::new(element.clone()),
+                            length,
+                        })
+                    }
+                    _ => Ok(Type::Parameterized {
+                        name: name.clone(),
+                        type_params: type_args,
+                        value_params: value_params.clone(),
+                    }),
+                }
+            }
+            _ => map_children(ty, &mut |child| self.resolve_in(child, expanding)),
+        }
+    }
+
+    fn expand(
+        &self,
+        name: &str,
+        def: &TypeDef,
+        type_args: &[Type],
+        value_args: &[ValueParam],
+        expanding: &mut Vec<String>,
+    ) -> Result<Type, TypeError> {
+        if expanding.iter().any(|n| n == name) {
+            return Err(TypeError::RecursiveType(name.to_string()));
+        }
+        let body = def.instantiate(name, type_args, value_args)?;
+        expanding.push(name.to_string());
+        let resolved = self.resolve_in(&body, expanding);
+        expanding.pop();
+        resolved
+    }
+}
+
+/// Replace type and value parameters in `ty`, in one simultaneous pass.
+fn substitute(
+    ty: &Type,
+    types: &BTreeMap<&str, &Type>,
+    values: &BTreeMap<&str, &ValueParam>,
+) -> Type {
+    match ty {
+        Type::Named(name) => match types.get(name.as_str()) {
+            Some(arg) => (*arg).clone(),
+            None => ty.clone(),
+        },
+        Type::Parameterized {
+            name,
+            type_params,
+            value_params,
+        } => Type::Parameterized {
+            name: name.clone(),
+            type_params: type_params
+                .iter()
+                .map(|t| substitute(t, types, values))
+                .collect(),
+            value_params: value_params
+                .iter()
+                .map(|vp| match vp {
+                    ValueParam::Symbolic(s) => values
+                        .get(s.as_str())
+                        .map_or_else(|| vp.clone(), |arg| (*arg).clone()),
+                    ValueParam::Concrete(_) => vp.clone(),
+                })
+                .collect(),
+        },
+        Type::Refined { base, predicate } => {
+            let map: BTreeMap<String, Predicate> = values
+                .iter()
+                .map(|(param, arg)| {
+                    let replacement = match arg {
+                        ValueParam::Concrete(v) => Predicate::IntLit(*v),
+                        ValueParam::Symbolic(s) => Predicate::Var(s.clone()),
+                    };
+                    (param.to_string(), replacement)
+                })
+                .collect();
+            Type::Refined {
+                base: Box::new(substitute(base, types, values)),
+                predicate: predicate.substitute(&map),
+            }
+        }
+        _ => map_children::<std::convert::Infallible>(ty, &mut |child| {
+            Ok(substitute(child, types, values))
+        })
+        .unwrap_or_else(|never| match never {}),
+    }
+}
+
+/// Rebuild `ty` with `f` applied to each immediate child type.
+///
+/// Refinement predicates are carried over unchanged.
+fn map_children<E>(ty: &Type, f: &mut impl FnMut(&Type) -> Result<Type, E>) -> Result<Type, E> {
+    let mut boxed = |inner: &Type| f(inner).map(Box::new);
+    Ok(match ty {
+        Type::Void
+        | Type::Unit
+        | Type::Bool
+        | Type::Int { .. }
+        | Type::Float { .. }
+        | Type::Fixed { .. }
+        | Type::Named(_) => ty.clone(),
+        Type::Tuple(elems) => Type::Tuple(elems.iter().map(&mut *f).collect::<Result<_, _>>()?),
+        Type::Record(fields) => Type::Record(
+            fields
+                .iter()
+                .map(|(k, t)| Ok((k.clone(), f(t)?)))
+                .collect::<Result<_, _>>()?,
+        ),
+        Type::Variant(cases) => Type::Variant(
+            cases
+                .iter()
+                .map(|(k, t)| Ok((k.clone(), f(t)?)))
+                .collect::<Result<_, _>>()?,
+        ),
+        Type::Array { element, length } => Type::Array {
+            element: boxed(element)?,
+            length: *length,
+        },
+        Type::Vec { element } => Type::Vec {
+            element: boxed(element)?,
+        },
+        Type::Refined { base, predicate } => Type::Refined {
+            base: boxed(base)?,
+            predicate: predicate.clone(),
+        },
+        Type::Linear { inner, linearity } => Type::Linear {
+            inner: boxed(inner)?,
+            linearity: *linearity,
+        },
+        Type::Timed { inner, bound } => Type::Timed {
+            inner: boxed(inner)?,
+            bound: bound.clone(),
+        },
+        Type::Sized { inner, max_bytes } => Type::Sized {
+            inner: boxed(inner)?,
+            max_bytes: *max_bytes,
+        },
+        Type::Powered { inner, energy_uj } => Type::Powered {
+            inner: boxed(inner)?,
+            energy_uj: *energy_uj,
+        },
+        Type::Bandwidth { inner, min_bps } => Type::Bandwidth {
+            inner: boxed(inner)?,
+            min_bps: *min_bps,
+        },
+        Type::Distribution(inner) => Type::Distribution(boxed(inner)?),
+        Type::Posterior { inner, evidence } => Type::Posterior {
+            inner: boxed(inner)?,
+            evidence: evidence.clone(),
+        },
+        Type::Interval { inner, confidence } => Type::Interval {
+            inner: boxed(inner)?,
+            confidence: *confidence,
+        },
+        Type::Approximate { inner, max_error } => Type::Approximate {
+            inner: boxed(inner)?,
+            max_error: *max_error,
+        },
+        Type::Parameterized {
+            name,
+            type_params,
+            value_params,
+        } => Type::Parameterized {
+            name: name.clone(),
+            type_params: type_params.iter().map(&mut *f).collect::<Result<_, _>>()?,
+            value_params: value_params.clone(),
+        },
+        Type::Option(inner) => Type::Option(boxed(inner)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str) -> Type {
+        Type::Named(name.to_string())
+    }
+
+    fn array(type_param: Type, length: ValueParam) -> Type {
+        Type::Parameterized {
+            name: ARRAY.to_string(),
+            type_params: vec![type_param],
+            value_params: vec![length],
+        }
+    }
+
+    fn matrix_table() -> TypeTable {
+        let mut table = TypeTable::new();
+        table.define(
+            "Matrix",
+            TypeDef::alias(array(
+                array(param("T"), ValueParam::Symbolic("C".into())),
+                ValueParam::Symbolic("R".into()),
+            ))
+            .with_type_param("T")
+            .with_value_param("R")
+            .with_value_param("C"),
+        );
+        table
+    }
+
+    fn matrix(elem: Type, rows: ValueParam, cols: ValueParam) -> Type {
+        Type::Parameterized {
+            name: "Matrix".to_string(),
+            type_params: vec![elem],
+            value_params: vec![rows, cols],
+        }
+    }
+
+    #[test]
+    fn generic_instantiation_monomorphizes() {
+        let table = matrix_table();
+        let m = matrix(
+            Type::f32(),
+            ValueParam::Concrete(3),
+            ValueParam::Concrete(4),
+        );
+        let resolved = table.resolve(&m).unwrap();
+        assert_eq!(format!("{resolved}"), "[[f32; 4]; 3]");
+
+        // A symbolic size stays parameterized over the built-in array
+        let n = ValueParam::Symbolic("N".into());
+        let open = table.resolve(&matrix(Type::f32(), n.clone(), n)).unwrap();
+        assert!(matches!(open, Type::Parameterized { ref name, .. } if name == ARRAY));
+    }
+
+    #[test]
+    fn aliases_and_refinements_substitute_value_params() {
+        let mut table = TypeTable::new();
+        table.define("Sample", TypeDef::alias(Type::u16()));
+        table.define(
+            "Index",
+            TypeDef::alias(Type::u32().refined(Predicate::Lt(
+                Box::new(Predicate::Var("value".into())),
+                Box::new(Predicate::Var("N".into())),
+            )))
+            .with_value_param("N"),
+        );
+
+        assert_eq!(
+            table.resolve(&Type::Named("Sample".into())).unwrap(),
+            Type::u16()
+        );
+        let index = Type::Parameterized {
+            name: "Index".into(),
+            type_params: vec![],
+            value_params: vec![ValueParam::Concrete(8)],
+        };
+        match table.resolve(&index).unwrap() {
+            Type::Refined { predicate, .. } => assert_eq!(
+                predicate,
+                Predicate::Lt(
+                    Box::new(Predicate::Var("value".into())),
+                    Box::new(Predicate::IntLit(8)),
+                )
+            ),
+            other => panic!("expected refined type, got {other}"),
+        }
+
+        // Undefined names are opaque and left alone
+        let opaque = Type::Named("Handle".into());
+        assert_eq!(table.resolve(&opaque).unwrap(), opaque);
+    }
+
+    #[test]
+    fn arity_and_recursion_are_errors() {
+        let mut table = matrix_table();
+        let bad = Type::Parameterized {
+            name: "Matrix".into(),
+            type_params: vec![Type::f32()],
+            value_params: vec![ValueParam::Concrete(3)],
+        };
+        assert!(matches!(
+            table.resolve(&bad),
+            Err(TypeError::ArityMismatch { .. })
+        ));
+
+        table.define("A", TypeDef::alias(Type::Option(Box::new(param("B")))));
+        table.define("B", TypeDef::alias(Type::Tuple(vec![param("A")])));
+        assert!(matches!(
+            table.resolve(&param("A")),
+            Err(TypeError::RecursiveType(_))
+        ));
+
+        // Nesting the same generic is not recursion
+        table.define(
+            "Wrap",
+            TypeDef::alias(Type::Option(Box::new(param("T")))).with_type_param("T"),
+        );
+        let wrap = |t: Type| Type::Parameterized {
+            name: "Wrap".into(),
+            type_params: vec![t],
+            value_params: vec![],
+        };
+        assert_eq!(
+            table.resolve(&wrap(wrap(Type::i32()))).unwrap(),
+            Type::Option(Box::new(Type::Option(Box::new(Type::i32()))))
+        );
+    }
+
+    #[test]
+    fn compatible_relates_named_and_structural_types() {
+        let table = matrix_table();
+        let m = matrix(
+            Type::f32(),
+            ValueParam::Concrete(2),
+            ValueParam::Concrete(2),
+        );
+        let structural = Type::Array {
+            element: Box::new(Type::Array {
+                element: Box::new(Type::f32()),
+                length: 2,
+            }),
+            length: 2,
+        };
+        assert!(table.compatible(&m, &structural).is_ok());
+        assert!(types_compatible(&m, &structural).is_err());
+
+        let wrong = matrix(
+            Type::f32(),
+            ValueParam::Concrete(2),
+            ValueParam::Concrete(3),
+        );
+        assert!(table.compatible(&wrong, &structural).is_err());
+    }
+}
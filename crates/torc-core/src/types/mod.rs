@@ -11,6 +11,11 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 
 pub mod check;
+pub mod defs;
+pub mod normalize;
+pub mod subtype;
+
+pub use self::defs::{TypeDef, TypeTable};
 
 /// Signedness of an integer type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
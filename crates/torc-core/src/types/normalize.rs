@@ -0,0 +1,818 @@
+//! Predicate rewriting: simplification and normal forms.
+//!
+//! Predicates are interpreted over mathematical integers (as the SMT backend
+//! does), so `!(a < b)` may be rewritten to `a >= b` and `x * 0` to `0`.
+//!
+//! - `simplify` folds constants and removes identities and double negations.
+//! - `to_nnf` / `to_cnf` produce negation and conjunctive normal form.
+//! - `alpha_normalize` renames bound variables by binding depth.
+//! - `canonicalize` combines the above with operand ordering, so that
+//!   predicates differing only in bound names, operand order or redundant
+//!   literals compare equal.
+//! - `substitute` replaces free variables without capturing.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Predicate;
+
+impl Predicate {
+    /// Fold constants and drop redundant structure.
+    ///
+    /// Integer division and modulo are only folded for non-negative operands,
+    /// and folding that would overflow `i128` is skipped.
+    pub fn simplify(&self) -> Predicate {
+        use Predicate::*;
+        match self {
+            BoolLit(_) | IntLit(_) | FloatLit(_) | Var(_) => self.clone(),
+            Add(a, b) => fold_arith(Arith::Add, a.simplify(), b.simplify()),
+            Sub(a, b) => fold_arith(Arith::Sub, a.simplify(), b.simplify()),
+            Mul(a, b) => fold_arith(Arith::Mul, a.simplify(), b.simplify()),
+            Div(a, b) => fold_arith(Arith::Div, a.simplify(), b.simplify()),
+            Mod(a, b) => fold_arith(Arith::Mod, a.simplify(), b.simplify()),
+            Neg(a) => match a.simplify() {
+                IntLit(n) => n
+                    .checked_neg()
+                    .map_or_else(|| Neg(Box::new(IntLit(n))), IntLit),
+                FloatLit(x) => FloatLit(-x),
+                Neg(inner) => *inner,
+                other => Neg(Box::new(other)),
+            },
+            Eq(a, b) => fold_cmp(Cmp::Eq, a.simplify(), b.simplify()),
+            Ne(a, b) => fold_cmp(Cmp::Ne, a.simplify(), b.simplify()),
+            Lt(a, b) => fold_cmp(Cmp::Lt, a.simplify(), b.simplify()),
+            Le(a, b) => fold_cmp(Cmp::Le, a.simplify(), b.simplify()),
+            Gt(a, b) => fold_cmp(Cmp::Gt, a.simplify(), b.simplify()),
+            Ge(a, b) => fold_cmp(Cmp::Ge, a.simplify(), b.simplify()),
+            And(a, b) => fold_and(a.simplify(), b.simplify()),
+            Or(a, b) => fold_or(a.simplify(), b.simplify()),
+            Not(a) => fold_not(a.simplify()),
+            Implies(a, b) => {
+                let (a, b) = (a.simplify(), b.simplify());
+                match (&a, &b) {
+                    (BoolLit(true), _) => b,
+                    (BoolLit(false), _) | (_, BoolLit(true)) => BoolLit(true),
+                    (_, BoolLit(false)) => fold_not(a),
+                    _ if a == b => BoolLit(true),
+                    _ => Implies(Box::new(a), Box::new(b)),
+                }
+            }
+            ForAll { var, range, body } => {
+                let (range, body) = (range.simplify(), body.simplify());
+                if range == BoolLit(false) || body == BoolLit(true) {
+                    return BoolLit(true);
+                }
+                ForAll {
+                    var: var.clone(),
+                    range: Box::new(range),
+                    body: Box::new(body),
+                }
+            }
+            Exists { var, range, body } => {
+                let (range, body) = (range.simplify(), body.simplify());
+                if range == BoolLit(false) || body == BoolLit(false) {
+                    return BoolLit(false);
+                }
+                Exists {
+                    var: var.clone(),
+                    range: Box::new(range),
+                    body: Box::new(body),
+                }
+            }
+            Apply(name, args) => Apply(name.clone(), args.iter().map(Self::simplify).collect()),
+        }
+    }
+
+    /// Negation normal form: no `Implies`, and `Not` only around atoms.
+    ///
+    /// Negated comparisons are flipped (`!(a < b)` becomes `a >= b`) and
+    /// negated quantifiers are dualized; quantifier ranges are left as is.
+    pub fn to_nnf(&self) -> Predicate {
+        nnf(self, false)
+    }
+
+    /// Conjunctive normal form: a conjunction of disjunctions of literals.
+    ///
+    /// Quantified formulas are treated as atoms (with their bodies converted
+    /// in turn). Distribution can grow the predicate exponentially.
+    pub fn to_cnf(&self) -> Predicate {
+        cnf(&self.to_nnf())
+    }
+
+    /// Rename every bound variable after its binding depth (`_q0`, `_q1`,
+    /// ...), so alpha-equivalent predicates become structurally equal.
+    ///
+    /// Generated names never collide with the predicate's free variables.
+    pub fn alpha_normalize(&self) -> Predicate {
+        let free = self.free_vars();
+        rename(self, &free, &mut Vec::new())
+    }
+
+    /// A canonical representative of the predicate.
+    ///
+    /// Bound variables are renamed, constants folded, `>`/`>=` rewritten to
+    /// `<`/`<=`, and the operands of commutative operators flattened, sorted
+    /// and (for `&&`/`||`) deduplicated.
+    pub fn canonicalize(&self) -> Predicate {
+        canonical(&self.alpha_normalize().simplify())
+    }
+
+    /// Replace free occurrences of variables with the mapped expressions.
+    ///
+    /// Substitution is capture-avoiding: a quantifier whose variable occurs
+    /// free in an expression substituted beneath it is renamed first.
+    pub fn substitute(&self, map: &BTreeMap<String, Predicate>) -> Predicate {
+        if map.is_empty() {
+            return self.clone();
+        }
+        substitute(self, map)
+    }
+
+    /// Variables referenced but not bound by a quantifier.
+    pub fn free_vars(&self) -> BTreeSet<String> {
+        let mut free = BTreeSet::new();
+        collect_free(self, &mut Vec::new(), &mut free);
+        free
+    }
+}
+
+// === Simplification ===
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+impl Arith {
+    fn build(self, a: Predicate, b: Predicate) -> Predicate {
+        let (a, b) = (Box::new(a), Box::new(b));
+        match self {
+            Arith::Add => Predicate::Add(a, b),
+            Arith::Sub => Predicate::Sub(a, b),
+            Arith::Mul => Predicate::Mul(a, b),
+            Arith::Div => Predicate::Div(a, b),
+            Arith::Mod => Predicate::Mod(a, b),
+        }
+    }
+}
+
+fn fold_arith(op: Arith, a: Predicate, b: Predicate) -> Predicate {
+    use Predicate::{FloatLit, IntLit};
+    let folded = match (&a, &b) {
+        (IntLit(x), IntLit(y)) => match op {
+            Arith::Add => x.checked_add(*y),
+            Arith::Sub => x.checked_sub(*y),
+            Arith::Mul => x.checked_mul(*y),
+            Arith::Div if *x >= 0 && *y > 0 => Some(x / y),
+            Arith::Mod if *x >= 0 && *y > 0 => Some(x % y),
+            _ => None,
+        }
+        .map(IntLit),
+        (FloatLit(x), FloatLit(y)) => match op {
+            Arith::Add => Some(x + y),
+            Arith::Sub => Some(x - y),
+            Arith::Mul => Some(x * y),
+            Arith::Div if *y != 0.0 => Some(x / y),
+            _ => None,
+        }
+        .map(FloatLit),
+        _ => None,
+    };
+    if let Some(p) = folded {
+        return p;
+    }
+
+    match (op, &a, &b) {
+        (Arith::Add, IntLit(0), _) | (Arith::Mul, IntLit(1), _) => b,
+        (Arith::Add | Arith::Sub, _, IntLit(0)) | (Arith::Mul | Arith::Div, _, IntLit(1)) => a,
+        (Arith::Mul, IntLit(0), _) | (Arith::Mul, _, IntLit(0)) => IntLit(0),
+        _ => op.build(a, b),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn of(p: &Predicate) -> Option<(Cmp, &Predicate, &Predicate)> {
+        let (cmp, a, b) = match p {
+            Predicate::Eq(a, b) => (Cmp::Eq, a, b),
+            Predicate::Ne(a, b) => (Cmp::Ne, a, b),
+            Predicate::Lt(a, b) => (Cmp::Lt, a, b),
+            Predicate::Le(a, b) => (Cmp::Le, a, b),
+            Predicate::Gt(a, b) => (Cmp::Gt, a, b),
+            Predicate::Ge(a, b) => (Cmp::Ge, a, b),
+            _ => return None,
+        };
+        Some((cmp, a, b))
+    }
+
+    fn build(self, a: Predicate, b: Predicate) -> Predicate {
+        let (a, b) = (Box::new(a), Box::new(b));
+        match self {
+            Cmp::Eq => Predicate::Eq(a, b),
+            Cmp::Ne => Predicate::Ne(a, b),
+            Cmp::Lt => Predicate::Lt(a, b),
+            Cmp::Le => Predicate::Le(a, b),
+            Cmp::Gt => Predicate::Gt(a, b),
+            Cmp::Ge => Predicate::Ge(a, b),
+        }
+    }
+
+    fn holds(self, ord: Ordering) -> bool {
+        match self {
+            Cmp::Eq => ord == Ordering::Equal,
+            Cmp::Ne => ord != Ordering::Equal,
+            Cmp::Lt => ord == Ordering::Less,
+            Cmp::Le => ord != Ordering::Greater,
+            Cmp::Gt => ord == Ordering::Greater,
+            Cmp::Ge => ord != Ordering::Less,
+        }
+    }
+
+    fn negate(self) -> Cmp {
+        match self {
+            Cmp::Eq => Cmp::Ne,
+            Cmp::Ne => Cmp::Eq,
+            Cmp::Lt => Cmp::Ge,
+            Cmp::Le => Cmp::Gt,
+            Cmp::Gt => Cmp::Le,
+            Cmp::Ge => Cmp::Lt,
+        }
+    }
+}
+
+fn fold_cmp(cmp: Cmp, a: Predicate, b: Predicate) -> Predicate {
+    let ord = match (&a, &b) {
+        (Predicate::IntLit(x), Predicate::IntLit(y)) => Some(x.cmp(y)),
+        (Predicate::FloatLit(x), Predicate::FloatLit(y)) => x.partial_cmp(y),
+        // Structural equality implies no NaN literal on either side
+        _ if a == b => Some(Ordering::Equal),
+        _ => None,
+    };
+    match ord {
+        Some(ord) => Predicate::BoolLit(cmp.holds(ord)),
+        None => cmp.build(a, b),
+    }
+}
+
+/// True if one side is the negation of the other.
+fn complementary(a: &Predicate, b: &Predicate) -> bool {
+    matches!(a, Predicate::Not(inner) if **inner == *b)
+        || matches!(b, Predicate::Not(inner) if **inner == *a)
+}
+
+fn fold_and(a: Predicate, b: Predicate) -> Predicate {
+    use Predicate::BoolLit;
+    match (&a, &b) {
+        (BoolLit(false), _) | (_, BoolLit(false)) => BoolLit(false),
+        (BoolLit(true), _) => b,
+        (_, BoolLit(true)) => a,
+        _ if a == b => a,
+        _ if complementary(&a, &b) => BoolLit(false),
+        _ => Predicate::And(Box::new(a), Box::new(b)),
+    }
+}
+
+fn fold_or(a: Predicate, b: Predicate) -> Predicate {
+    use Predicate::BoolLit;
+    match (&a, &b) {
+        (BoolLit(true), _) | (_, BoolLit(true)) => BoolLit(true),
+        (BoolLit(false), _) => b,
+        (_, BoolLit(false)) => a,
+        _ if a == b => a,
+        _ if complementary(&a, &b) => BoolLit(true),
+        _ => Predicate::Or(Box::new(a), Box::new(b)),
+    }
+}
+
+fn fold_not(a: Predicate) -> Predicate {
+    match a {
+        Predicate::BoolLit(b) => Predicate::BoolLit(!b),
+        Predicate::Not(inner) => *inner,
+        other => Predicate::Not(Box::new(other)),
+    }
+}
+
+// === Normal forms ===
+
+fn and(a: Predicate, b: Predicate) -> Predicate {
+    Predicate::And(Box::new(a), Box::new(b))
+}
+
+fn or(a: Predicate, b: Predicate) -> Predicate {
+    Predicate::Or(Box::new(a), Box::new(b))
+}
+
+/// NNF of `p`, or of `!p` if `negate` is set.
+fn nnf(p: &Predicate, negate: bool) -> Predicate {
+    use Predicate::*;
+    match p {
+        Not(a) => nnf(a, !negate),
+        BoolLit(b) => BoolLit(*b != negate),
+        And(a, b) if negate => or(nnf(a, true), nnf(b, true)),
+        And(a, b) => and(nnf(a, false), nnf(b, false)),
+        Or(a, b) if negate => and(nnf(a, true), nnf(b, true)),
+        Or(a, b) => or(nnf(a, false), nnf(b, false)),
+        Implies(a, b) if negate => and(nnf(a, false), nnf(b, true)),
+        Implies(a, b) => or(nnf(a, true), nnf(b, false)),
+        ForAll { var, range, body } | Exists { var, range, body } => {
+            let universal = matches!(p, ForAll { .. }) != negate;
+            let (var, range, body) = (var.clone(), range.clone(), Box::new(nnf(body, negate)));
+            if universal {
+                ForAll { var, range, body }
+            } else {
+                Exists { var, range, body }
+            }
+        }
+        _ => match Cmp::of(p) {
+            Some((cmp, a, b)) if negate => cmp.negate().build(a.clone(), b.clone()),
+            _ if negate => Not(Box::new(p.clone())),
+            _ => p.clone(),
+        },
+    }
+}
+
+/// CNF of a predicate already in NNF.
+fn cnf(p: &Predicate) -> Predicate {
+    use Predicate::*;
+    match p {
+        And(a, b) => and(cnf(a), cnf(b)),
+        Or(a, b) => distribute(cnf(a), cnf(b)),
+        ForAll { var, range, body } => ForAll {
+            var: var.clone(),
+            range: range.clone(),
+            body: Box::new(cnf(body)),
+        },
+        Exists { var, range, body } => Exists {
+            var: var.clone(),
+            range: range.clone(),
+            body: Box::new(cnf(body)),
+        },
+        _ => p.clone(),
+    }
+}
+
+/// `a || b` for CNF operands, distributed over their conjunctions.
+fn distribute(a: Predicate, b: Predicate) -> Predicate {
+    match (a, b) {
+        (Predicate::And(a1, a2), b) => and(distribute(*a1, b.clone()), distribute(*a2, b)),
+        (a, Predicate::And(b1, b2)) => and(distribute(a.clone(), *b1), distribute(a, *b2)),
+        (a, b) => or(a, b),
+    }
+}
+
+// === Variables ===
+
+fn collect_free(p: &Predicate, bound: &mut Vec<String>, free: &mut BTreeSet<String>) {
+    match p {
+        Predicate::Var(name) => {
+            if !bound.contains(name) {
+                free.insert(name.clone());
+            }
+        }
+        Predicate::ForAll { var, range, body } | Predicate::Exists { var, range, body } => {
+            bound.push(var.clone());
+            collect_free(range, bound, free);
+            collect_free(body, bound, free);
+            bound.pop();
+        }
+        _ => {
+            for child in p.children() {
+                collect_free(child, bound, free);
+            }
+        }
+    }
+}
+
+fn substitute(p: &Predicate, map: &BTreeMap<String, Predicate>) -> Predicate {
+    match p {
+        Predicate::Var(name) => map.get(name).cloned().unwrap_or_else(|| p.clone()),
+        Predicate::ForAll { var, range, body } | Predicate::Exists { var, range, body } => {
+            // The binder shadows `var`; only substitutions that reach a free
+            // occurrence below it matter.
+            let mut used = range.free_vars();
+            used.extend(body.free_vars());
+            let inner: BTreeMap<String, Predicate> = map
+                .iter()
+                .filter(|(k, _)| *k != var && used.contains(*k))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+
+            let incoming: BTreeSet<String> = inner.values().flat_map(|v| v.free_vars()).collect();
+            let (var, range, body) = if incoming.contains(var) {
+                let mut fresh = format!("{var}_1");
+                let mut n = 1;
+                while used.contains(&fresh) || incoming.contains(&fresh) {
+                    n += 1;
+                    fresh = format!("{var}_{n}");
+                }
+                let rename = BTreeMap::from([(var.clone(), Predicate::Var(fresh.clone()))]);
+                (fresh, substitute(range, &rename), substitute(body, &rename))
+            } else {
+                (var.clone(), (**range).clone(), (**body).clone())
+            };
+
+            let range = Box::new(substitute(&range, &inner));
+            let body = Box::new(substitute(&body, &inner));
+            if matches!(p, Predicate::ForAll { .. }) {
+                Predicate::ForAll { var, range, body }
+            } else {
+                Predicate::Exists { var, range, body }
+            }
+        }
+        _ => p.map_children(|c| substitute(c, map)),
+    }
+}
+
+/// Rename bound variables; `scope` maps original names to new ones.
+fn rename(p: &Predicate, free: &BTreeSet<String>, scope: &mut Vec<(String, String)>) -> Predicate {
+    match p {
+        Predicate::Var(name) => match scope.iter().rev().find(|(old, _)| old == name) {
+            Some((_, new)) => Predicate::Var(new.clone()),
+            None => p.clone(),
+        },
+        Predicate::ForAll { var, range, body } | Predicate::Exists { var, range, body } => {
+            let mut fresh = format!("_q{}", scope.len());
+            while free.contains(&fresh) {
+                fresh.insert(0, '_');
+            }
+            scope.push((var.clone(), fresh.clone()));
+            let range = Box::new(rename(range, free, scope));
+            let body = Box::new(rename(body, free, scope));
+            scope.pop();
+            if matches!(p, Predicate::ForAll { .. }) {
+                Predicate::ForAll {
+                    var: fresh,
+                    range,
+                    body,
+                }
+            } else {
+                Predicate::Exists {
+                    var: fresh,
+                    range,
+                    body,
+                }
+            }
+        }
+        _ => p.map_children(|c| rename(c, free, scope)),
+    }
+}
+
+impl Predicate {
+    /// Direct subexpressions, in order.
+    fn children(&self) -> Vec<&Predicate> {
+        use Predicate::*;
+        match self {
+            BoolLit(_) | IntLit(_) | FloatLit(_) | Var(_) => Vec::new(),
+            Neg(a) | Not(a) => vec![a],
+            Add(a, b)
+            | Sub(a, b)
+            | Mul(a, b)
+            | Div(a, b)
+            | Mod(a, b)
+            | Eq(a, b)
+            | Ne(a, b)
+            | Lt(a, b)
+            | Le(a, b)
+            | Gt(a, b)
+            | Ge(a, b)
+            | And(a, b)
+            | Or(a, b)
+            | Implies(a, b) => vec![a, b],
+            ForAll { range, body, .. } | Exists { range, body, .. } => vec![range, body],
+            Apply(_, args) => args.iter().collect(),
+        }
+    }
+
+    /// Rebuild with every direct subexpression replaced by `f(child)`.
+    fn map_children(&self, mut f: impl FnMut(&Predicate) -> Predicate) -> Predicate {
+        use Predicate::*;
+        let mut g = |p: &Predicate| Box::new(f(p));
+        match self {
+            BoolLit(_) | IntLit(_) | FloatLit(_) | Var(_) => self.clone(),
+            Neg(a) => Neg(g(a)),
+            Not(a) => Not(g(a)),
+            Add(a, b) => Add(g(a), g(b)),
+            Sub(a, b) => Sub(g(a), g(b)),
+            Mul(a, b) => Mul(g(a), g(b)),
+            Div(a, b) => Div(g(a), g(b)),
+            Mod(a, b) => Mod(g(a), g(b)),
+            Eq(a, b) => Eq(g(a), g(b)),
+            Ne(a, b) => Ne(g(a), g(b)),
+            Lt(a, b) => Lt(g(a), g(b)),
+            Le(a, b) => Le(g(a), g(b)),
+            Gt(a, b) => Gt(g(a), g(b)),
+            Ge(a, b) => Ge(g(a), g(b)),
+            And(a, b) => And(g(a), g(b)),
+            Or(a, b) => Or(g(a), g(b)),
+            Implies(a, b) => Implies(g(a), g(b)),
+            ForAll { var, range, body } => ForAll {
+                var: var.clone(),
+                range: g(range),
+                body: g(body),
+            },
+            Exists { var, range, body } => Exists {
+                var: var.clone(),
+                range: g(range),
+                body: g(body),
+            },
+            Apply(name, args) => Apply(name.clone(), args.iter().map(|a| *g(a)).collect()),
+        }
+    }
+}
+
+// === Canonical ordering ===
+
+fn canonical(p: &Predicate) -> Predicate {
+    use Predicate::*;
+    match p {
+        And(..) | Or(..) | Add(..) | Mul(..) => {
+            let mut operands = Vec::new();
+            flatten(p, p, &mut operands);
+            let mut operands: Vec<Predicate> = operands.into_iter().map(canonical).collect();
+            operands.sort_by(order);
+            if matches!(p, And(..) | Or(..)) {
+                operands.dedup();
+            }
+            let last = operands.pop().expect("flattened operator has operands");
+            operands.into_iter().rev().fold(last, |acc, op| {
+                let (op, acc) = (Box::new(op), Box::new(acc));
+                match p {
+                    And(..) => And(op, acc),
+                    Or(..) => Or(op, acc),
+                    Add(..) => Add(op, acc),
+                    _ => Mul(op, acc),
+                }
+            })
+        }
+        Gt(a, b) => Lt(Box::new(canonical(b)), Box::new(canonical(a))),
+        Ge(a, b) => Le(Box::new(canonical(b)), Box::new(canonical(a))),
+        Eq(a, b) | Ne(a, b) => {
+            let (mut a, mut b) = (canonical(a), canonical(b));
+            if order(&a, &b) == Ordering::Greater {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let (a, b) = (Box::new(a), Box::new(b));
+            if matches!(p, Eq(..)) {
+                Eq(a, b)
+            } else {
+                Ne(a, b)
+            }
+        }
+        _ => p.map_children(canonical),
+    }
+}
+
+/// Collect the operands of a chain of the same operator as `root`.
+fn flatten<'a>(root: &Predicate, p: &'a Predicate, out: &mut Vec<&'a Predicate>) {
+    if std::mem::discriminant(root) == std::mem::discriminant(p) {
+        for child in p.children() {
+            flatten(root, child, out);
+        }
+    } else {
+        out.push(p);
+    }
+}
+
+/// Rank of a predicate's outermost constructor, for `order`.
+fn rank(p: &Predicate) -> u8 {
+    use Predicate::*;
+    match p {
+        BoolLit(_) => 0,
+        IntLit(_) => 1,
+        FloatLit(_) => 2,
+        Var(_) => 3,
+        Add(..) => 4,
+        Sub(..) => 5,
+        Mul(..) => 6,
+        Div(..) => 7,
+        Mod(..) => 8,
+        Neg(_) => 9,
+        Eq(..) => 10,
+        Ne(..) => 11,
+        Lt(..) => 12,
+        Le(..) => 13,
+        Gt(..) => 14,
+        Ge(..) => 15,
+        And(..) => 16,
+        Or(..) => 17,
+        Not(_) => 18,
+        Implies(..) => 19,
+        ForAll { .. } => 20,
+        Exists { .. } => 21,
+        Apply(..) => 22,
+    }
+}
+
+/// A total structural order on predicates.
+fn order(a: &Predicate, b: &Predicate) -> Ordering {
+    use Predicate::*;
+    let leaf = match (a, b) {
+        (BoolLit(x), BoolLit(y)) => x.cmp(y),
+        (IntLit(x), IntLit(y)) => x.cmp(y),
+        (FloatLit(x), FloatLit(y)) => x.total_cmp(y),
+        (Var(x), Var(y)) => x.cmp(y),
+        (ForAll { var: x, .. }, ForAll { var: y, .. })
+        | (Exists { var: x, .. }, Exists { var: y, .. })
+        | (Apply(x, _), Apply(y, _)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    };
+    leaf.then_with(|| {
+        let (xs, ys) = (a.children(), b.children());
+        xs.iter()
+            .zip(&ys)
+            .map(|(x, y)| order(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| xs.len().cmp(&ys.len()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Predicate {
+        Predicate::Var(name.into())
+    }
+
+    fn int(n: i128) -> Predicate {
+        Predicate::IntLit(n)
+    }
+
+    fn bx(p: Predicate) -> Box<Predicate> {
+        Box::new(p)
+    }
+
+    #[test]
+    fn folds_constants_and_identities() {
+        // true && (x + (2 * 3) > 0 + 1)
+        let p = Predicate::BoolLit(true).conjoin(Predicate::Gt(
+            bx(Predicate::Add(
+                bx(var("x")),
+                bx(Predicate::Mul(bx(int(2)), bx(int(3)))),
+            )),
+            bx(Predicate::Add(bx(int(0)), bx(int(1)))),
+        ));
+        let p = Predicate::And(bx(Predicate::BoolLit(true)), bx(p));
+        assert_eq!(
+            p.simplify(),
+            Predicate::Gt(bx(Predicate::Add(bx(var("x")), bx(int(6)))), bx(int(1)))
+        );
+
+        let double = Predicate::Not(bx(Predicate::Not(bx(var("b")))));
+        assert_eq!(double.simplify(), var("b"));
+        assert_eq!(
+            Predicate::Ge(bx(int(10)), bx(int(5))).simplify(),
+            Predicate::BoolLit(true)
+        );
+        assert_eq!(
+            Predicate::And(bx(var("b")), bx(Predicate::Not(bx(var("b"))))).simplify(),
+            Predicate::BoolLit(false)
+        );
+        // Overflow and signed division are left alone
+        let overflow = Predicate::Add(bx(int(i128::MAX)), bx(int(1)));
+        assert_eq!(overflow.simplify(), overflow);
+        let signed_div = Predicate::Div(bx(int(-7)), bx(int(2)));
+        assert_eq!(signed_div.simplify(), signed_div);
+    }
+
+    #[test]
+    fn negation_normal_form() {
+        // !(x < 0 => (y == 1 && !z))  ~>  x < 0 && (y != 1 || z)
+        let p = Predicate::Not(bx(Predicate::Implies(
+            bx(Predicate::Lt(bx(var("x")), bx(int(0)))),
+            bx(Predicate::And(
+                bx(Predicate::Eq(bx(var("y")), bx(int(1)))),
+                bx(Predicate::Not(bx(var("z")))),
+            )),
+        )));
+        assert_eq!(
+            p.to_nnf(),
+            Predicate::And(
+                bx(Predicate::Lt(bx(var("x")), bx(int(0)))),
+                bx(Predicate::Or(
+                    bx(Predicate::Ne(bx(var("y")), bx(int(1)))),
+                    bx(var("z")),
+                )),
+            )
+        );
+
+        let q = Predicate::Not(bx(Predicate::ForAll {
+            var: "i".into(),
+            range: bx(Predicate::in_range("i", 0, 4)),
+            body: bx(Predicate::Gt(bx(var("i")), bx(int(0)))),
+        }));
+        assert_eq!(
+            q.to_nnf(),
+            Predicate::Exists {
+                var: "i".into(),
+                range: bx(Predicate::in_range("i", 0, 4)),
+                body: bx(Predicate::Le(bx(var("i")), bx(int(0)))),
+            }
+        );
+    }
+
+    #[test]
+    fn conjunctive_normal_form() {
+        // (a && b) || c  ~>  (a || c) && (b || c)
+        let p = Predicate::Or(bx(Predicate::And(bx(var("a")), bx(var("b")))), bx(var("c")));
+        assert_eq!(
+            p.to_cnf(),
+            Predicate::And(
+                bx(Predicate::Or(bx(var("a")), bx(var("c")))),
+                bx(Predicate::Or(bx(var("b")), bx(var("c")))),
+            )
+        );
+    }
+
+    #[test]
+    fn alpha_renaming_avoids_free_names() {
+        let body = |v: &str| Predicate::Lt(bx(var(v)), bx(var("_q0")));
+        let a = Predicate::ForAll {
+            var: "i".into(),
+            range: bx(Predicate::in_range("i", 0, 8)),
+            body: bx(body("i")),
+        };
+        let b = Predicate::ForAll {
+            var: "j".into(),
+            range: bx(Predicate::in_range("j", 0, 8)),
+            body: bx(body("j")),
+        };
+        assert_ne!(a, b);
+        assert_eq!(a.alpha_normalize(), b.alpha_normalize());
+
+        let Predicate::ForAll { var: bound, .. } = a.alpha_normalize() else {
+            panic!("expected forall");
+        };
+        assert_eq!(bound, "__q0");
+        assert_eq!(a.free_vars(), BTreeSet::from(["_q0".to_string()]));
+    }
+
+    #[test]
+    fn substitution_avoids_capture() {
+        // forall i in [0, 8]. i < n   with n := i + 1
+        let p = Predicate::ForAll {
+            var: "i".into(),
+            range: bx(Predicate::in_range("i", 0, 8)),
+            body: bx(Predicate::Lt(bx(var("i")), bx(var("n")))),
+        };
+        let map = BTreeMap::from([("n".to_string(), Predicate::Add(bx(var("i")), bx(int(1))))]);
+        assert_eq!(
+            p.substitute(&map),
+            Predicate::ForAll {
+                var: "i_1".into(),
+                range: bx(Predicate::in_range("i_1", 0, 8)),
+                body: bx(Predicate::Lt(
+                    bx(var("i_1")),
+                    bx(Predicate::Add(bx(var("i")), bx(int(1)))),
+                )),
+            }
+        );
+
+        // Bound occurrences are not substituted
+        let shadow = BTreeMap::from([("i".to_string(), int(5))]);
+        assert_eq!(p.substitute(&shadow), p);
+    }
+
+    #[test]
+    fn canonical_form_ignores_operand_order() {
+        // (y + x > 0) && true && (x >= 1)   vs   (1 <= x) && (0 < x + y) && (x >= 1)
+        let a = Predicate::And(
+            bx(Predicate::And(
+                bx(Predicate::Gt(
+                    bx(Predicate::Add(bx(var("y")), bx(var("x")))),
+                    bx(int(0)),
+                )),
+                bx(Predicate::BoolLit(true)),
+            )),
+            bx(Predicate::Ge(bx(var("x")), bx(int(1)))),
+        );
+        let b = Predicate::And(
+            bx(Predicate::Le(bx(int(1)), bx(var("x")))),
+            bx(Predicate::And(
+                bx(Predicate::Lt(
+                    bx(int(0)),
+                    bx(Predicate::Add(bx(var("x")), bx(var("y")))),
+                )),
+                bx(Predicate::Ge(bx(var("x")), bx(int(1)))),
+            )),
+        );
+        assert_eq!(a.canonicalize(), b.canonicalize());
+        assert_ne!(
+            a.canonicalize(),
+            Predicate::Lt(bx(int(0)), bx(var("x"))).canonicalize()
+        );
+    }
+}
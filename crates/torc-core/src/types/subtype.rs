@@ -0,0 +1,527 @@
+//! Refinement subtyping.
+//!
+//! A value of `{v: B | P}` may flow into `{v: B | Q}` when `P ⇒ Q` holds for
+//! every `v` of `B`. Most refinements in practice bound their variable by
+//! constants (`0 <= v && v <= 100`), and for those the implication is decided
+//! here with interval reasoning rather than handed to the proof engine:
+//!
+//! - the source refinement, intersected with the range of `B`, is widened to
+//!   an interval; if that interval lies inside the target's, the edge is
+//!   **accepted**;
+//! - if the source refinement is exactly its interval and some value of it
+//!   falls outside the target's bounds, the edge is **rejected** with that
+//!   value as a counterexample. An unrefined source is never rejected: the
+//!   values that actually reach the edge (a literal's, say) may be narrower
+//!   than its type, which the verifier can still show;
+//! - anything else (several variables, arithmetic, disjunctions, quantifiers)
+//!   becomes a `TypeRefinement` **obligation**, as before.
+//!
+//! Like the predicate normalizer, bounds are read over mathematical numbers,
+//! and only comparisons of the refinement variable with literals are used.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::contract::{ObligationKind, ProofObligation, ProofStatus};
+use crate::fixed;
+use crate::types::{Predicate, Signedness, Type};
+use crate::value::{FixedValue, FloatValue, IntValue, Value};
+
+/// The outcome of a refinement subtyping check.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subtyping {
+    /// Every value admitted by the source satisfies the target refinement.
+    Accepted,
+    /// A value admitted by the source violates the target refinement.
+    Rejected(Counterexample),
+    /// Undecided here; the implication must be proven.
+    Obligation(Box<ProofObligation>),
+}
+
+/// A value the source type admits and the target refinement excludes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample {
+    /// The refinement variable, as the source refinement names it.
+    pub var: String,
+    pub value: Value,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.var, self.value)
+    }
+}
+
+/// Decide whether a value of `base` refined by `source` (unrefined if
+/// `None`) satisfies the refinement `target`.
+///
+/// The obligation returned when undecided is the one the type checker has
+/// always emitted: `source ⇒ target`, or `target` for an unrefined source.
+pub fn decide_refinement(base: &Type, source: Option<&Predicate>, target: &Predicate) -> Subtyping {
+    let obligation = || Subtyping::Obligation(Box::new(refinement_obligation(source, target)));
+
+    // Each side refines a single variable; the two may name it differently.
+    let target_vars = target.free_vars();
+    let Some(target_var) = target_vars.first().filter(|_| target_vars.len() == 1) else {
+        return obligation();
+    };
+    let var = match source.map(Predicate::free_vars) {
+        None => target_var.clone(),
+        Some(vars) if vars.is_empty() => target_var.clone(),
+        Some(vars) if vars.len() == 1 => vars.into_iter().next().unwrap_or_default(),
+        Some(_) => return obligation(),
+    };
+    let renamed = target.substitute(&BTreeMap::from([(
+        target_var.clone(),
+        Predicate::Var(var.clone()),
+    )]));
+
+    let decision = match *base.base_type() {
+        Type::Int { width, signedness } => {
+            let (lo, hi) = fixed::int_range(width, signedness);
+            // `int_range` caps u128 at i128::MAX; don't assume that cap.
+            let hi = (signedness == Signedness::Signed || width < 127).then_some(hi);
+            let domain = Bounds::closed(Some(lo), hi);
+            decide(&var, source, &renamed, domain, |n| {
+                let v = IntValue::new(n, width, signedness);
+                (v.to_i128() == Some(n)).then_some((Value::Int(v), n))
+            })
+        }
+        Type::Float { precision } => decide(&var, source, &renamed, Bounds::UNBOUNDED, |x| {
+            let v = FloatValue::new(x, precision);
+            Some((Value::Float(v), v.value()))
+        }),
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => {
+            let (lo, hi) = fixed::raw_range(total_bits);
+            let scale = 2f64.powi(frac_bits as i32);
+            let domain = Bounds::closed(Some(lo as f64 / scale), Some(hi as f64 / scale));
+            decide(&var, source, &renamed, domain, |x| {
+                let v = FixedValue::from_f64(x, total_bits, frac_bits)?;
+                Some((Value::Fixed(v), v.to_f64()))
+            })
+        }
+        _ => Decision::Unknown,
+    };
+
+    match decision {
+        Decision::Accepted => Subtyping::Accepted,
+        Decision::Rejected(value) => Subtyping::Rejected(Counterexample { var, value }),
+        Decision::Unknown => obligation(),
+    }
+}
+
+/// The obligation `source ⇒ target` (just `target` when unrefined).
+fn refinement_obligation(source: Option<&Predicate>, target: &Predicate) -> ProofObligation {
+    let (predicate, description) = match source {
+        Some(sp) => (
+            Predicate::Implies(Box::new(sp.clone()), Box::new(target.clone())),
+            "refinement subtyping: source predicate must imply target predicate",
+        ),
+        None => (
+            target.clone(),
+            "unrefined source must satisfy target refinement",
+        ),
+    };
+    ProofObligation {
+        kind: ObligationKind::TypeRefinement,
+        predicate,
+        description: description.to_string(),
+        status: ProofStatus::Pending,
+        witness: None,
+        waiver: None,
+    }
+}
+
+enum Decision {
+    Accepted,
+    Rejected(Value),
+    Unknown,
+}
+
+/// Decide `source ⇒ target` over `var` ranging within `domain`.
+///
+/// `value` turns a candidate counterexample into a value of the base type,
+/// along with the number that value actually denotes (after rounding), which
+/// is checked again before the counterexample is reported.
+fn decide<N: Scalar>(
+    var: &str,
+    source: Option<&Predicate>,
+    target: &Predicate,
+    domain: Bounds<N>,
+    value: impl Fn(N) -> Option<(Value, N)>,
+) -> Decision {
+    let mut assumed = Vec::new();
+    let mut admitted = domain;
+    let mut exact = source.is_some();
+    if let Some(source) = source {
+        for conjunct in conjuncts(source) {
+            match Bounds::of_atom(var, conjunct) {
+                Some(b) => admitted = admitted.meet(b),
+                None => exact = false,
+            }
+            assumed.push(conjunct.canonicalize());
+        }
+    }
+    if admitted.is_empty() {
+        return Decision::Accepted;
+    }
+
+    let mut required = Bounds::UNBOUNDED;
+    let mut bounded = true;
+    for conjunct in conjuncts(target) {
+        if assumed.contains(&conjunct.canonicalize()) {
+            continue;
+        }
+        match Bounds::of_atom(var, conjunct) {
+            Some(b) => required = required.meet(b),
+            None => bounded = false,
+        }
+    }
+    if bounded && admitted.within(&required) {
+        return Decision::Accepted;
+    }
+    if exact {
+        // A point outside the target's bounds violates the whole target.
+        for candidate in admitted.candidates_outside(&required) {
+            if let Some((v, n)) = value(candidate) {
+                if admitted.contains(n) && !required.contains(n) {
+                    return Decision::Rejected(v);
+                }
+            }
+        }
+    }
+    Decision::Unknown
+}
+
+/// The conjuncts of `p`, with nested `&&` flattened.
+fn conjuncts(p: &Predicate) -> Vec<&Predicate> {
+    match p {
+        Predicate::And(a, b) => {
+            let mut out = conjuncts(a);
+            out.extend(conjuncts(b));
+            out
+        }
+        Predicate::BoolLit(true) => Vec::new(),
+        _ => vec![p],
+    }
+}
+
+/// Numbers the bounds range over.
+trait Scalar: Copy + PartialOrd {
+    /// Whether only whole numbers are values, so that strict bounds can be
+    /// tightened by one.
+    const DISCRETE: bool;
+
+    fn literal(p: &Predicate) -> Option<Self>;
+
+    /// `self + by`, if representable.
+    fn offset(self, by: i8) -> Option<Self>;
+
+    fn midpoint(self, other: Self) -> Self;
+}
+
+impl Scalar for i128 {
+    const DISCRETE: bool = true;
+
+    fn literal(p: &Predicate) -> Option<Self> {
+        match p {
+            Predicate::IntLit(n) => Some(*n),
+            Predicate::FloatLit(x) if x.fract() == 0.0 && x.abs() < 2f64.powi(127) => {
+                Some(*x as i128)
+            }
+            Predicate::Neg(inner) => Self::literal(inner)?.checked_neg(),
+            _ => None,
+        }
+    }
+
+    fn offset(self, by: i8) -> Option<Self> {
+        self.checked_add(by as i128)
+    }
+
+    fn midpoint(self, other: Self) -> Self {
+        self / 2 + other / 2 + (self % 2 + other % 2) / 2
+    }
+}
+
+impl Scalar for f64 {
+    const DISCRETE: bool = false;
+
+    fn literal(p: &Predicate) -> Option<Self> {
+        match p {
+            Predicate::IntLit(n) => Some(*n as f64),
+            Predicate::FloatLit(x) if x.is_finite() => Some(*x),
+            Predicate::Neg(inner) => Self::literal(inner).map(|x| -x),
+            _ => None,
+        }
+    }
+
+    fn offset(self, by: i8) -> Option<Self> {
+        Some(self + by as f64)
+    }
+
+    fn midpoint(self, other: Self) -> Self {
+        self / 2.0 + other / 2.0
+    }
+}
+
+/// An interval, each end optional and either closed or open (`strict`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds<N> {
+    lo: Option<(N, bool)>,
+    hi: Option<(N, bool)>,
+}
+
+impl<N: Scalar> Bounds<N> {
+    const UNBOUNDED: Self = Bounds { lo: None, hi: None };
+
+    fn closed(lo: Option<N>, hi: Option<N>) -> Self {
+        Bounds {
+            lo: lo.map(|n| (n, false)),
+            hi: hi.map(|n| (n, false)),
+        }
+    }
+
+    /// The bounds `atom` places on `var`, if it compares `var` with a literal.
+    fn of_atom(var: &str, atom: &Predicate) -> Option<Self> {
+        let is_var = |p: &Predicate| matches!(p, Predicate::Var(v) if v == var);
+        let (op, bound, flipped) = match atom {
+            Predicate::Eq(a, b)
+            | Predicate::Lt(a, b)
+            | Predicate::Le(a, b)
+            | Predicate::Gt(a, b)
+            | Predicate::Ge(a, b) => {
+                if is_var(a) {
+                    (atom, N::literal(b)?, false)
+                } else if is_var(b) {
+                    (atom, N::literal(a)?, true)
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        };
+        let below = |strict| Self::lower(bound, strict);
+        let above = |strict| Self::upper(bound, strict);
+        Some(match (op, flipped) {
+            (Predicate::Eq(..), _) => Self::closed(Some(bound), Some(bound)),
+            (Predicate::Lt(..), false) | (Predicate::Gt(..), true) => above(true),
+            (Predicate::Le(..), false) | (Predicate::Ge(..), true) => above(false),
+            (Predicate::Gt(..), false) | (Predicate::Lt(..), true) => below(true),
+            _ => below(false),
+        })
+    }
+
+    fn lower(n: N, strict: bool) -> Self {
+        let lo = match (N::DISCRETE && strict).then(|| n.offset(1)).flatten() {
+            Some(m) => (m, false),
+            None => (n, strict),
+        };
+        Bounds {
+            lo: Some(lo),
+            hi: None,
+        }
+    }
+
+    fn upper(n: N, strict: bool) -> Self {
+        let hi = match (N::DISCRETE && strict).then(|| n.offset(-1)).flatten() {
+            Some(m) => (m, false),
+            None => (n, strict),
+        };
+        Bounds {
+            lo: None,
+            hi: Some(hi),
+        }
+    }
+
+    fn meet(self, other: Self) -> Self {
+        let pick =
+            |a: Option<(N, bool)>, b: Option<(N, bool)>, tighter: fn(N, N) -> bool| match (a, b) {
+                (Some(x), Some(y)) if x.0 == y.0 => Some((x.0, x.1 || y.1)),
+                (Some(x), Some(y)) => Some(if tighter(x.0, y.0) { x } else { y }),
+                (x, None) => x,
+                (None, y) => y,
+            };
+        Bounds {
+            lo: pick(self.lo, other.lo, |a, b| a > b),
+            hi: pick(self.hi, other.hi, |a, b| a < b),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match (self.lo, self.hi) {
+            (Some((lo, ls)), Some((hi, hs))) => lo > hi || (lo == hi && (ls || hs)),
+            _ => false,
+        }
+    }
+
+    fn contains(&self, x: N) -> bool {
+        let above_lo = self
+            .lo
+            .is_none_or(|(lo, strict)| x > lo || (x == lo && !strict));
+        let below_hi = self
+            .hi
+            .is_none_or(|(hi, strict)| x < hi || (x == hi && !strict));
+        above_lo && below_hi
+    }
+
+    /// Whether every point of `self` lies in `other`.
+    fn within(&self, other: &Self) -> bool {
+        let lo_ok = match (self.lo, other.lo) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some((s, ss)), Some((t, ts))) => s > t || (s == t && (ss || !ts)),
+        };
+        let hi_ok = match (self.hi, other.hi) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some((s, ss)), Some((t, ts))) => s < t || (s == t && (ss || !ts)),
+        };
+        lo_ok && hi_ok
+    }
+
+    /// Points near the ends of `self` that may lie outside `other`; the
+    /// caller checks each one.
+    fn candidates_outside(&self, other: &Self) -> Vec<N> {
+        let mut out = Vec::new();
+        if let Some((t, _)) = other.lo {
+            out.push(t);
+            out.extend(t.offset(-1));
+            if let Some((s, _)) = self.lo {
+                out.push(s);
+                out.push(s.midpoint(t));
+            }
+        }
+        if let Some((t, _)) = other.hi {
+            out.push(t);
+            out.extend(t.offset(1));
+            if let Some((s, _)) = self.hi {
+                out.push(s);
+                out.push(s.midpoint(t));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FloatPrecision;
+
+    fn u16() -> Type {
+        Type::Int {
+            width: 16,
+            signedness: Signedness::Unsigned,
+        }
+    }
+
+    fn counterexample(outcome: Subtyping) -> Value {
+        match outcome {
+            Subtyping::Rejected(c) => c.value,
+            other => panic!("expected a rejection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn nested_ranges_are_accepted() {
+        let narrow = Predicate::in_range("x", 0, 100);
+        let wide = Predicate::in_range("value", 0, 4095);
+        assert_eq!(
+            decide_refinement(&u16(), Some(&narrow), &wide),
+            Subtyping::Accepted
+        );
+        // The base type's range counts as the source refinement.
+        let u8 = Type::Int {
+            width: 8,
+            signedness: Signedness::Unsigned,
+        };
+        assert_eq!(decide_refinement(&u8, None, &wide), Subtyping::Accepted);
+        // Strict integer bounds tighten by one.
+        let strict = Predicate::And(
+            Box::new(Predicate::Gt(
+                Box::new(Predicate::Var("x".into())),
+                Box::new(Predicate::IntLit(-1)),
+            )),
+            Box::new(Predicate::Lt(
+                Box::new(Predicate::Var("x".into())),
+                Box::new(Predicate::IntLit(4096)),
+            )),
+        );
+        assert_eq!(
+            decide_refinement(&u16(), Some(&strict), &wide),
+            Subtyping::Accepted
+        );
+    }
+
+    #[test]
+    fn escaping_ranges_are_rejected_with_a_counterexample() {
+        let wide = Predicate::in_range("x", 0, 5000);
+        let narrow = Predicate::in_range("x", 0, 4095);
+        let value = counterexample(decide_refinement(&u16(), Some(&wide), &narrow));
+        assert_eq!(
+            value,
+            Value::Int(IntValue::new(4096, 16, Signedness::Unsigned))
+        );
+
+        // What reaches an unrefined port may be narrower than its type.
+        let Subtyping::Obligation(ob) = decide_refinement(&u16(), None, &narrow) else {
+            panic!("an unrefined source is never rejected");
+        };
+        assert_eq!(ob.predicate, narrow);
+
+        let f64 = Type::Float {
+            precision: FloatPrecision::F64,
+        };
+        let non_negative = Predicate::Ge(
+            Box::new(Predicate::Var("x".into())),
+            Box::new(Predicate::FloatLit(0.0)),
+        );
+        let unit = Predicate::in_range("x", 0, 1);
+        let outcome = decide_refinement(&f64, Some(&non_negative), &unit);
+        let Value::Float(v) = counterexample(outcome) else {
+            panic!("expected a float counterexample");
+        };
+        assert!(!(0.0..=1.0).contains(&v.value()));
+    }
+
+    #[test]
+    fn undecided_implications_become_obligations() {
+        let source = Predicate::in_range("x", 0, 100);
+        let parity = Predicate::Eq(
+            Box::new(Predicate::Mod(
+                Box::new(Predicate::Var("x".into())),
+                Box::new(Predicate::IntLit(2)),
+            )),
+            Box::new(Predicate::IntLit(0)),
+        );
+        let Subtyping::Obligation(ob) = decide_refinement(&u16(), Some(&source), &parity) else {
+            panic!("expected an obligation");
+        };
+        assert_eq!(ob.kind, ObligationKind::TypeRefinement);
+        assert_eq!(
+            ob.predicate,
+            Predicate::Implies(Box::new(source.clone()), Box::new(parity.clone()))
+        );
+
+        // A source that is not just bounds cannot be rejected.
+        let guarded = Predicate::And(Box::new(source), Box::new(parity));
+        let narrow = Predicate::in_range("x", 0, 50);
+        assert!(matches!(
+            decide_refinement(&u16(), Some(&guarded), &narrow),
+            Subtyping::Obligation(_)
+        ));
+
+        // Neither can one mentioning another variable.
+        let dependent = Predicate::Le(
+            Box::new(Predicate::Var("x".into())),
+            Box::new(Predicate::Var("len".into())),
+        );
+        assert!(matches!(
+            decide_refinement(&u16(), Some(&dependent), &narrow),
+            Subtyping::Obligation(_)
+        ));
+    }
+}
@@ -0,0 +1,563 @@
+//! Concrete values mirroring the `Type` universe.
+//!
+//! A `Value` is what flows along an edge at run time. Integers carry their
+//! width and signedness and are always stored wrapped to that width; floats
+//! are rounded to their declared precision on construction, so two values
+//! compare equal exactly when the hardware representation would.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prob::Distribution;
+use crate::types::{FloatPrecision, Signedness, Type};
+
+/// A concrete value of some `Type`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    /// The single value of `Unit`.
+    Unit,
+    /// A boolean.
+    Bool(bool),
+    /// A fixed-width integer.
+    Int(IntValue),
+    /// An IEEE 754 float.
+    Float(FloatValue),
+    /// A fixed-point number.
+    Fixed(FixedValue),
+    /// A tuple.
+    Tuple(Vec<Value>),
+    /// A record, fields in name order.
+    Record(BTreeMap<String, Value>),
+    /// A tagged variant case.
+    Variant { tag: String, value: Box<Value> },
+    /// A fixed-length array.
+    Array(Vec<Value>),
+    /// A variable-length vector.
+    Vec(Vec<Value>),
+    /// An optional value.
+    Option(Option<Box<Value>>),
+}
+
+/// A two's-complement integer of 1..=128 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IntValue {
+    /// Raw bit pattern, masked to `width` bits.
+    bits: u128,
+    width: u8,
+    signedness: Signedness,
+}
+
+/// A float rounded to its declared precision.
+///
+/// The value is held as an `f64`; `F128` values are therefore limited to
+/// double precision.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FloatValue {
+    value: f64,
+    precision: FloatPrecision,
+}
+
+/// A signed fixed-point number: `raw / 2^frac_bits`, wrapped to `total_bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FixedValue {
+    raw: i128,
+    total_bits: u8,
+    frac_bits: u8,
+}
+
+fn mask(width: u8) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << width) - 1
+    }
+}
+
+impl IntValue {
+    /// Wrap an integer to the given width.
+    ///
+    /// # Panics
+    /// Panics if `width` is 0 or greater than 128.
+    pub fn new(value: i128, width: u8, signedness: Signedness) -> Self {
+        Self::from_bits(value as u128, width, signedness)
+    }
+
+    /// Build from a raw bit pattern, truncating to `width` bits.
+    ///
+    /// # Panics
+    /// Panics if `width` is 0 or greater than 128.
+    pub fn from_bits(bits: u128, width: u8, signedness: Signedness) -> Self {
+        assert!(
+            (1..=128).contains(&width),
+            "integer width {width} out of range"
+        );
+        Self {
+            bits: bits & mask(width),
+            width,
+            signedness,
+        }
+    }
+
+    /// Build from a mathematical value, or `None` if it does not fit.
+    pub fn checked(value: i128, width: u8, signedness: Signedness) -> Option<Self> {
+        let v = Self::new(value, width, signedness);
+        (v.to_i128() == Some(value)).then_some(v)
+    }
+
+    /// Build from an unsigned mathematical value, or `None` if it does not fit.
+    pub fn checked_unsigned(value: u128, width: u8, signedness: Signedness) -> Option<Self> {
+        let v = Self::from_bits(value, width, signedness);
+        (v.to_u128() == Some(value)).then_some(v)
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn signedness(&self) -> Signedness {
+        self.signedness
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signedness == Signedness::Signed
+    }
+
+    /// The raw bit pattern.
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    /// The bit pattern sign-extended to 128 bits (for signed values).
+    fn extended(&self) -> i128 {
+        if self.is_signed() && self.width < 128 && self.bits >> (self.width - 1) & 1 == 1 {
+            (self.bits | !mask(self.width)) as i128
+        } else {
+            self.bits as i128
+        }
+    }
+
+    /// True if the mathematical value is negative.
+    pub fn is_negative(&self) -> bool {
+        self.is_signed() && self.extended() < 0
+    }
+
+    /// The mathematical value, if it fits in an `i128`.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.is_signed() {
+            Some(self.extended())
+        } else {
+            i128::try_from(self.bits).ok()
+        }
+    }
+
+    /// The mathematical value, if it is non-negative.
+    pub fn to_u128(&self) -> Option<u128> {
+        (!self.is_negative()).then_some(self.bits)
+    }
+
+    /// The mathematical value as a float.
+    pub fn to_f64(&self) -> f64 {
+        if self.is_signed() {
+            self.extended() as f64
+        } else {
+            self.bits as f64
+        }
+    }
+
+    /// The type of this value.
+    pub fn ty(&self) -> Type {
+        Type::Int {
+            width: self.width,
+            signedness: self.signedness,
+        }
+    }
+}
+
+impl fmt::Display for IntValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_signed() {
+            write!(f, "{}", self.extended())
+        } else {
+            write!(f, "{}", self.bits)
+        }
+    }
+}
+
+/// Round to the nearest IEEE binary16 value (ties to even).
+fn round_to_f16(x: f64) -> f64 {
+    const MAX: f64 = 65504.0;
+    if !x.is_finite() || x == 0.0 {
+        return x;
+    }
+    let a = x.abs();
+    let exponent = (((a.to_bits() >> 52) & 0x7ff) as i32 - 1023).max(-14);
+    let quantum = 2f64.powi(exponent - 10);
+    let rounded = (a / quantum).round_ties_even() * quantum;
+    let rounded = if rounded > MAX {
+        f64::INFINITY
+    } else {
+        rounded
+    };
+    rounded.copysign(x)
+}
+
+impl FloatValue {
+    /// Round `value` to `precision`.
+    pub fn new(value: f64, precision: FloatPrecision) -> Self {
+        let value = match precision {
+            FloatPrecision::F16 => round_to_f16(value),
+            FloatPrecision::F32 => value as f32 as f64,
+            FloatPrecision::F64 | FloatPrecision::F128 => value,
+        };
+        Self { value, precision }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn precision(&self) -> FloatPrecision {
+        self.precision
+    }
+
+    /// The type of this value.
+    pub fn ty(&self) -> Type {
+        Type::Float {
+            precision: self.precision,
+        }
+    }
+}
+
+impl FixedValue {
+    /// Wrap a raw scaled integer to `total_bits`.
+    ///
+    /// # Panics
+    /// Panics if `total_bits` is 0 or greater than 128, or `frac_bits`
+    /// exceeds `total_bits`.
+    pub fn from_raw(raw: i128, total_bits: u8, frac_bits: u8) -> Self {
+        assert!(
+            (1..=128).contains(&total_bits) && frac_bits <= total_bits,
+            "invalid fixed-point format <{total_bits}, {frac_bits}>"
+        );
+        let raw = IntValue::new(raw, total_bits, Signedness::Signed).extended();
+        Self {
+            raw,
+            total_bits,
+            frac_bits,
+        }
+    }
+
+    /// The nearest representable value to `x`, or `None` if out of range.
+    pub fn from_f64(x: f64, total_bits: u8, frac_bits: u8) -> Option<Self> {
+        let scaled = (x * 2f64.powi(frac_bits as i32)).round();
+        if !scaled.is_finite() || scaled.abs() >= 2f64.powi(127) {
+            return None;
+        }
+        let raw = scaled as i128;
+        let v = Self::from_raw(raw, total_bits, frac_bits);
+        (v.raw == raw).then_some(v)
+    }
+
+    pub fn raw(&self) -> i128 {
+        self.raw
+    }
+
+    pub fn total_bits(&self) -> u8 {
+        self.total_bits
+    }
+
+    pub fn frac_bits(&self) -> u8 {
+        self.frac_bits
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.raw as f64 / 2f64.powi(self.frac_bits as i32)
+    }
+
+    /// The type of this value.
+    pub fn ty(&self) -> Type {
+        Type::Fixed {
+            total_bits: self.total_bits,
+            frac_bits: self.frac_bits,
+        }
+    }
+}
+
+impl Value {
+    /// A wrapped integer value.
+    pub fn int(value: i128, width: u8, signedness: Signedness) -> Self {
+        Value::Int(IntValue::new(value, width, signedness))
+    }
+
+    pub fn i32(value: i32) -> Self {
+        Self::int(value as i128, 32, Signedness::Signed)
+    }
+
+    pub fn i64(value: i64) -> Self {
+        Self::int(value as i128, 64, Signedness::Signed)
+    }
+
+    pub fn u8(value: u8) -> Self {
+        Self::int(value as i128, 8, Signedness::Unsigned)
+    }
+
+    pub fn u32(value: u32) -> Self {
+        Self::int(value as i128, 32, Signedness::Unsigned)
+    }
+
+    pub fn u64(value: u64) -> Self {
+        Self::int(value as i128, 64, Signedness::Unsigned)
+    }
+
+    pub fn f32(value: f32) -> Self {
+        Value::Float(FloatValue::new(value as f64, FloatPrecision::F32))
+    }
+
+    pub fn f64(value: f64) -> Self {
+        Value::Float(FloatValue::new(value, FloatPrecision::F64))
+    }
+
+    /// `Some(value)`.
+    pub fn some(value: Value) -> Self {
+        Value::Option(Some(Box::new(value)))
+    }
+
+    /// `None`.
+    pub fn none() -> Self {
+        Value::Option(None)
+    }
+
+    /// A variant case.
+    pub fn variant(tag: impl Into<String>, value: Value) -> Self {
+        Value::Variant {
+            tag: tag.into(),
+            value: Box::new(value),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<&IntValue> {
+        match self {
+            Value::Int(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Elements of a tuple, array or vector.
+    pub fn elements(&self) -> Option<&[Value]> {
+        match self {
+            Value::Tuple(elems) | Value::Array(elems) | Value::Vec(elems) => Some(elems),
+            _ => None,
+        }
+    }
+
+    /// Short name of the value's shape, for diagnostics.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "unit",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Fixed(_) => "fixed",
+            Value::Tuple(_) => "tuple",
+            Value::Record(_) => "record",
+            Value::Variant { .. } => "variant",
+            Value::Array(_) => "array",
+            Value::Vec(_) => "vec",
+            Value::Option(_) => "option",
+        }
+    }
+
+    /// Check whether this value inhabits `ty`.
+    ///
+    /// Wrappers (refinement, linearity, resource bounds, intervals,
+    /// approximations) are transparent; refinement predicates are not
+    /// evaluated. Distribution and posterior types accept the values of
+    /// `prob::Distribution`. `Named` and `Parameterized`
+    /// types are resolved during linking and accept any value.
+    pub fn conforms_to(&self, ty: &Type) -> bool {
+        match (self, ty.base_type()) {
+            (_, Type::Named(_) | Type::Parameterized { .. }) => true,
+            (_, Type::Interval { inner, .. } | Type::Approximate { inner, .. }) => {
+                self.conforms_to(inner)
+            }
+            (_, Type::Distribution(_) | Type::Posterior { .. }) => {
+                Distribution::from_value(self).is_ok()
+            }
+            (Value::Unit, Type::Unit) => true,
+            (Value::Bool(_), Type::Bool) => true,
+            (Value::Int(i), Type::Int { width, signedness }) => {
+                i.width == *width && i.signedness == *signedness
+            }
+            (Value::Float(x), Type::Float { precision }) => x.precision == *precision,
+            (
+                Value::Fixed(x),
+                Type::Fixed {
+                    total_bits,
+                    frac_bits,
+                },
+            ) => x.total_bits == *total_bits && x.frac_bits == *frac_bits,
+            (Value::Tuple(vals), Type::Tuple(tys)) => {
+                vals.len() == tys.len() && vals.iter().zip(tys).all(|(v, t)| v.conforms_to(t))
+            }
+            (Value::Record(vals), Type::Record(tys)) => {
+                vals.len() == tys.len()
+                    && vals
+                        .iter()
+                        .all(|(name, v)| tys.get(name).is_some_and(|t| v.conforms_to(t)))
+            }
+            (Value::Variant { tag, value }, Type::Variant(cases)) => {
+                cases.get(tag).is_some_and(|t| value.conforms_to(t))
+            }
+            (Value::Array(vals), Type::Array { element, length }) => {
+                vals.len() == *length && vals.iter().all(|v| v.conforms_to(element))
+            }
+            (Value::Vec(vals), Type::Vec { element }) => {
+                vals.iter().all(|v| v.conforms_to(element))
+            }
+            (Value::Option(v), Type::Option(inner)) => {
+                v.as_ref().is_none_or(|v| v.conforms_to(inner))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, open: &str, items: &[Value], close: &str) -> fmt::Result {
+    write!(f, "{open}")?;
+    for (i, v) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{v}")?;
+    }
+    write!(f, "{close}")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{:?}", x.value),
+            Value::Fixed(x) => write!(f, "{}", x.to_f64()),
+            Value::Tuple(elems) if elems.len() == 1 => write!(f, "({},)", elems[0]),
+            Value::Tuple(elems) => write_list(f, "(", elems, ")"),
+            Value::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{name}: {v}")?;
+                }
+                write!(f, "}}")
+            }
+            Value::Variant { tag, value } => write!(f, "{tag}({value})"),
+            Value::Array(elems) => write_list(f, "[", elems, "]"),
+            Value::Vec(elems) => write_list(f, "vec[", elems, "]"),
+            Value::Option(Some(v)) => write!(f, "Some({v})"),
+            Value::Option(None) => write!(f, "None"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_wraps_to_width() {
+        let v = IntValue::new(300, 8, Signedness::Unsigned);
+        assert_eq!(v.bits(), 44);
+        let v = IntValue::new(200, 8, Signedness::Signed);
+        assert_eq!(v.to_i128(), Some(-56));
+        let v = IntValue::new(-1, 128, Signedness::Unsigned);
+        assert_eq!(v.to_u128(), Some(u128::MAX));
+        assert_eq!(v.to_i128(), None);
+    }
+
+    #[test]
+    fn checked_construction() {
+        assert!(IntValue::checked(127, 8, Signedness::Signed).is_some());
+        assert!(IntValue::checked(128, 8, Signedness::Signed).is_none());
+        assert!(IntValue::checked(-1, 8, Signedness::Unsigned).is_none());
+        assert!(IntValue::checked_unsigned(255, 8, Signedness::Unsigned).is_some());
+        assert!(IntValue::checked(-4, 3, Signedness::Signed).is_some());
+        assert!(IntValue::checked(-5, 3, Signedness::Signed).is_none());
+    }
+
+    #[test]
+    fn floats_round_to_precision() {
+        assert_eq!(
+            Value::f32(0.1),
+            Value::Float(FloatValue::new(0.1, FloatPrecision::F32))
+        );
+        assert_ne!(Value::f32(0.1), Value::f64(0.1));
+        assert_eq!(
+            FloatValue::new(1.0009765625, FloatPrecision::F16).value(),
+            1.0009765625
+        );
+        assert_eq!(FloatValue::new(1.0004, FloatPrecision::F16).value(), 1.0);
+        assert_eq!(
+            FloatValue::new(70000.0, FloatPrecision::F16).value(),
+            f64::INFINITY
+        );
+        assert_eq!(
+            FloatValue::new(65504.0, FloatPrecision::F16).value(),
+            65504.0
+        );
+    }
+
+    #[test]
+    fn fixed_point_round_trip() {
+        let x = FixedValue::from_f64(1.25, 16, 8).unwrap();
+        assert_eq!(x.raw(), 320);
+        assert_eq!(x.to_f64(), 1.25);
+        assert!(FixedValue::from_f64(200.0, 16, 8).is_none());
+        assert_eq!(FixedValue::from_raw(1 << 15, 16, 8).raw(), -(1 << 15));
+    }
+
+    #[test]
+    fn conformance() {
+        let rec = Value::Record(BTreeMap::from([
+            ("x".to_string(), Value::i32(1)),
+            ("y".to_string(), Value::some(Value::f64(2.0))),
+        ]));
+        let ty = Type::Record(BTreeMap::from([
+            (
+                "x".to_string(),
+                Type::i32().refined(crate::types::Predicate::positive("x")),
+            ),
+            ("y".to_string(), Type::Option(Box::new(Type::f64()))),
+        ]));
+        assert!(rec.conforms_to(&ty));
+        assert!(!Value::i32(1).conforms_to(&Type::i64()));
+        assert!(
+            !Value::Array(vec![Value::Bool(true)]).conforms_to(&Type::Array {
+                element: Box::new(Type::Bool),
+                length: 2,
+            })
+        );
+        assert!(Value::Unit.conforms_to(&Type::Named("Anything".into())));
+    }
+
+    #[test]
+    fn display() {
+        let v = Value::Tuple(vec![
+            Value::i32(-3),
+            Value::variant("Ok", Value::Vec(vec![Value::u8(1), Value::u8(2)])),
+            Value::none(),
+        ]);
+        assert_eq!(v.to_string(), "(-3, Ok(vec[1, 2]), None)");
+    }
+}
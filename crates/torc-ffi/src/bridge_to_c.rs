@@ -1,9 +1,14 @@
 //! Torc-to-C header generation.
 //!
 //! Generates C header files from Torc graph nodes that have `export.name` annotations.
-//! Contracts are emitted as documentation comments.
+//! Contracts are emitted as documentation comments. Types are resolved against
+//! the graph's type definitions; named record types become C struct
+//! definitions.
+
+use std::collections::HashSet;
 
 use torc_core::graph::Graph;
+use torc_core::types::{Type, TypeTable};
 
 use crate::error::Result;
 use crate::marshal::ctype_string_from_torc;
@@ -17,12 +22,50 @@ struct ExportedFunction {
     contract_comment: Option<String>,
 }
 
+/// Named record types referenced by exports, in definition order.
+#[derive(Debug, Default)]
+struct CStructs {
+    defs: Vec<(String, Vec<(String, String)>)>, // (name, [(type, field)])
+    visited: HashSet<String>,
+}
+
+impl CStructs {
+    /// The C spelling of `ty`, collecting the struct definitions it needs.
+    ///
+    /// A named type defined as a record keeps its name; any other definition
+    /// is expanded. Types that fail to resolve are spelled as written.
+    fn c_type(&mut self, types: &TypeTable, ty: &Type) -> String {
+        if let Type::Named(name) = ty {
+            let record = types.get(name).and_then(|def| match def.body.base_type() {
+                Type::Record(fields)
+                    if def.type_params.is_empty() && def.value_params.is_empty() =>
+                {
+                    Some(fields)
+                }
+                _ => None,
+            });
+            if let Some(fields) = record {
+                if self.visited.insert(name.clone()) {
+                    let fields = fields
+                        .iter()
+                        .map(|(field, ty)| (self.c_type(types, ty), field.clone()))
+                        .collect();
+                    self.defs.push((name.clone(), fields));
+                }
+                return ctype_string_from_torc(ty);
+            }
+        }
+        ctype_string_from_torc(&types.resolve(ty).unwrap_or_else(|_| ty.clone()))
+    }
+}
+
 /// Generate a C header file from a Torc graph's exported functions.
 ///
 /// Finds nodes with `export.name` annotations and converts their type signatures
 /// to C declarations. Contracts are included as doc comments.
 pub fn generate_c_header(graph: &Graph, guard_name: &str) -> Result<String> {
-    let exports = collect_exports(graph);
+    let mut structs = CStructs::default();
+    let exports = collect_exports(graph, &mut structs);
 
     let mut out = String::new();
 
@@ -40,6 +83,15 @@ pub fn generate_c_header(graph: &Graph, guard_name: &str) -> Result<String> {
 
     out.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
 
+    // Struct definitions, dependencies first
+    for (name, fields) in &structs.defs {
+        out.push_str(&format!("struct {name} {{\n"));
+        for (ty, field) in fields {
+            out.push_str(&format!("    {ty} {field};\n"));
+        }
+        out.push_str("};\n\n");
+    }
+
     // Function declarations
     for export in &exports {
         // Contract as doc comment
@@ -82,7 +134,7 @@ pub fn generate_c_header(graph: &Graph, guard_name: &str) -> Result<String> {
 }
 
 /// Collect exported functions from a graph.
-fn collect_exports(graph: &Graph) -> Vec<ExportedFunction> {
+fn collect_exports(graph: &Graph, structs: &mut CStructs) -> Vec<ExportedFunction> {
     let mut exports = Vec::new();
 
     for node in graph.nodes() {
@@ -100,7 +152,7 @@ fn collect_exports(graph: &Graph) -> Vec<ExportedFunction> {
         let return_type = if type_sig.outputs.is_empty() {
             "void".to_string()
         } else {
-            ctype_string_from_torc(&type_sig.outputs[0])
+            structs.c_type(graph.types(), &type_sig.outputs[0])
         };
 
         // Convert parameters
@@ -109,7 +161,7 @@ fn collect_exports(graph: &Graph) -> Vec<ExportedFunction> {
             .iter()
             .enumerate()
             .map(|(i, ty)| {
-                let c_type = ctype_string_from_torc(ty);
+                let c_type = structs.c_type(graph.types(), ty);
                 let param_name = node
                     .annotations
                     .get(&format!("export.param.{i}"))
@@ -159,7 +211,7 @@ mod tests {
     use torc_core::builder::GraphBuilder;
     use torc_core::contract::{Contract, EffectSet};
     use torc_core::graph::node::{ArithmeticOp, NodeKind};
-    use torc_core::types::{Predicate, Type, TypeSignature};
+    use torc_core::types::{Predicate, TypeDef, TypeSignature};
 
     fn make_export_graph() -> Graph {
         let mut builder = GraphBuilder::new();
@@ -266,4 +318,38 @@ mod tests {
         assert!(header.contains("torc_add"));
         assert!(header.contains("torc_mul"));
     }
+
+    #[test]
+    fn named_types_resolved_for_c() {
+        let mut builder = GraphBuilder::new();
+        let id = builder.add_typed_node(
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            "drive",
+            TypeSignature::pure_fn(
+                vec![Type::Named("Command".into()), Type::Named("Duty".into())],
+                Type::Named("Millivolts".into()),
+            ),
+        );
+        builder.annotate(id, "export.name", "torc_drive").unwrap();
+        builder.annotate(id, "export.param.0", "cmd").unwrap();
+        builder.annotate(id, "export.param.1", "duty").unwrap();
+        let mut graph = builder.into_graph();
+
+        let mut axis = std::collections::BTreeMap::new();
+        axis.insert("target".to_string(), Type::i32());
+        let mut command = std::collections::BTreeMap::new();
+        command.insert("axis".to_string(), Type::Named("Axis".into()));
+        command.insert("gain".to_string(), Type::f32());
+        graph.define_type("Axis", TypeDef::alias(Type::Record(axis)));
+        graph.define_type("Command", TypeDef::alias(Type::Record(command)));
+        graph.define_type("Duty", TypeDef::alias(Type::u16()));
+        graph.define_type("Millivolts", TypeDef::alias(Type::i32()));
+
+        let header = generate_c_header(&graph, "drive").unwrap();
+        assert!(header.contains("int32_t torc_drive(struct Command cmd, uint16_t duty);"));
+        let axis_at = header.find("struct Axis {").unwrap();
+        let command_at = header.find("struct Command {").unwrap();
+        assert!(axis_at < command_at, "dependencies come first");
+        assert!(header.contains("    struct Axis axis;\n    float gain;\n"));
+    }
 }
@@ -0,0 +1,89 @@
+//! Interpreter errors.
+
+use thiserror::Error;
+use torc_core::graph::node::NodeId;
+use torc_core::graph::region::RegionId;
+use torc_core::graph::GraphError;
+
+/// Convenience alias for results within the interp crate.
+pub type Result<T> = std::result::Result<T, InterpError>;
+
+/// Errors that can occur while executing a graph.
+#[derive(Debug, Error)]
+pub enum InterpError {
+    #[error("graph error: {0}")]
+    Graph(#[from] GraphError),
+
+    #[error("cycle through node {0} is not broken by a loop node")]
+    Cycle(NodeId),
+
+    #[error("node {node} has no input on port {port}")]
+    MissingInput { node: NodeId, port: usize },
+
+    #[error("node {node} has more than one edge into port {port}")]
+    DuplicateInput { node: NodeId, port: usize },
+
+    #[error("no value produced on port {port} of node {node}")]
+    MissingValue { node: NodeId, port: usize },
+
+    #[error("type mismatch at node {node}: {message}")]
+    TypeMismatch { node: NodeId, message: String },
+
+    #[error("invalid literal at node {node}: {message}")]
+    InvalidLiteral { node: NodeId, message: String },
+
+    #[error("invalid annotation at node {node}: {message}")]
+    InvalidAnnotation { node: NodeId, message: String },
+
+    #[error("division by zero at node {node}")]
+    DivisionByZero { node: NodeId },
+
+    #[error("arithmetic overflow at node {node}")]
+    Overflow { node: NodeId },
+
+    #[error("shift amount {amount} out of range at node {node}")]
+    ShiftOutOfRange { node: NodeId, amount: String },
+
+    #[error("conversion out of range at node {node}: {message}")]
+    ConversionOutOfRange { node: NodeId, message: String },
+
+    #[error("index {index} out of bounds for length {len} at node {node}")]
+    IndexOutOfBounds {
+        node: NodeId,
+        index: u128,
+        len: usize,
+    },
+
+    #[error("verification failed at node {node}")]
+    AssertionFailed { node: NodeId },
+
+    #[error("assumption violated at node {node}")]
+    AssumptionViolated { node: NodeId },
+
+    #[error("iteration at node {node} did not finish within its bound of {bound}")]
+    BoundExceeded { node: NodeId, bound: u128 },
+
+    #[error("fixpoint at node {node} did not converge after {iterations} iterations")]
+    NoConvergence { node: NodeId, iterations: u64 },
+
+    #[error("recursion at node {node} exceeded depth {depth}")]
+    RecursionLimit { node: NodeId, depth: u64 },
+
+    #[error("termination metric did not decrease at node {node}")]
+    MetricNotDecreasing { node: NodeId },
+
+    #[error("loop body of node {node} escapes iterative region {region}")]
+    LoopEscapesRegion { node: NodeId, region: RegionId },
+
+    #[error("probabilistic evaluation failed at node {node}: {message}")]
+    Probabilistic { node: NodeId, message: String },
+
+    #[error("node {node} ({kind}) is not supported by the interpreter")]
+    Unsupported { node: NodeId, kind: String },
+
+    #[error("host error at node {node}: {message}")]
+    Host { node: NodeId, message: String },
+
+    #[error("step limit of {limit} exceeded")]
+    StepLimit { limit: u64 },
+}
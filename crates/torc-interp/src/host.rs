@@ -0,0 +1,106 @@
+//! The interpreter's view of the outside world.
+//!
+//! Effect nodes (`Read`, `Write`, `Syscall`, `FFICall`, `Atomic`) never touch
+//! real devices; they go through a `Host`, which tests script with known
+//! inputs and inspect afterwards.
+
+use std::collections::{HashMap, VecDeque};
+
+use torc_core::graph::node::{Node, NodeId};
+use torc_core::value::Value;
+
+/// Environment that services effect nodes.
+///
+/// Errors are plain messages; the interpreter attaches the node ID.
+pub trait Host {
+    /// Produce the value for a `Read` node.
+    fn read(&mut self, node: &Node) -> Result<Value, String>;
+
+    /// Consume the value of a `Write` node.
+    fn write(&mut self, node: &Node, value: Value) -> Result<(), String>;
+
+    /// Perform a `Syscall` or `FFICall`, returning one value per output port.
+    fn call(&mut self, node: &Node, args: Vec<Value>) -> Result<Vec<Value>, String> {
+        let _ = args;
+        Err(format!("no handler for {}", node.kind))
+    }
+
+    /// Load a shared memory cell for an `Atomic` node.
+    fn load(&mut self, cell: &str) -> Option<Value>;
+
+    /// Store a shared memory cell for an `Atomic` node.
+    fn store(&mut self, cell: &str, value: Value);
+}
+
+/// A `Host` with queued inputs per `Read` node that records every write.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedHost {
+    inputs: HashMap<NodeId, VecDeque<Value>>,
+    writes: Vec<(NodeId, Value)>,
+    cells: HashMap<String, Value>,
+}
+
+impl ScriptedHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue one value for a `Read` node.
+    pub fn with_input(mut self, node: NodeId, value: Value) -> Self {
+        self.inputs.entry(node).or_default().push_back(value);
+        self
+    }
+
+    /// Queue a sequence of values for a `Read` node (one per execution).
+    pub fn with_inputs(mut self, node: NodeId, values: impl IntoIterator<Item = Value>) -> Self {
+        self.inputs.entry(node).or_default().extend(values);
+        self
+    }
+
+    /// Initialize a memory cell used by `Atomic` nodes.
+    pub fn with_cell(mut self, cell: impl Into<String>, value: Value) -> Self {
+        self.cells.insert(cell.into(), value);
+        self
+    }
+
+    /// All writes in the order they were committed.
+    pub fn writes(&self) -> &[(NodeId, Value)] {
+        &self.writes
+    }
+
+    /// Values written by one `Write` node, in order.
+    pub fn writes_to(&self, node: &NodeId) -> Vec<&Value> {
+        self.writes
+            .iter()
+            .filter(|(id, _)| id == node)
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Current contents of a memory cell.
+    pub fn cell(&self, cell: &str) -> Option<&Value> {
+        self.cells.get(cell)
+    }
+}
+
+impl Host for ScriptedHost {
+    fn read(&mut self, node: &Node) -> Result<Value, String> {
+        self.inputs
+            .get_mut(&node.id)
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| "no input queued".to_string())
+    }
+
+    fn write(&mut self, node: &Node, value: Value) -> Result<(), String> {
+        self.writes.push((node.id, value));
+        Ok(())
+    }
+
+    fn load(&mut self, cell: &str) -> Option<Value> {
+        self.cells.get(cell).cloned()
+    }
+
+    fn store(&mut self, cell: &str, value: Value) {
+        self.cells.insert(cell.to_string(), value);
+    }
+}
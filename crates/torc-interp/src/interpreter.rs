@@ -0,0 +1,1645 @@
+//! Graph execution.
+//!
+//! Execution has two phases. `Plan` analyses the graph once: it resolves
+//! each input port to its producer, separates loop back-edges from forward
+//! edges, computes a deterministic schedule and works out loop bodies and
+//! region units. `Machine` then evaluates nodes on demand, memoizing every
+//! output port, so that conditional regions and untaken `Select` branches
+//! are never executed.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use torc_core::fixed::FixedSemantics;
+use torc_core::graph::edge::PortRef;
+use torc_core::graph::node::{ArithmeticOp, BitwiseOp, Node, NodeId, NodeKind};
+use torc_core::graph::region::{RegionId, RegionKind};
+use torc_core::graph::{Graph, GraphError};
+use torc_core::prob::{self, Distribution, ProbError, Rng, DISTRIBUTION_ANNOTATION};
+use torc_core::types::Type;
+use torc_core::value::{IntValue, Value};
+
+use crate::error::{InterpError, Result};
+use crate::host::{Host, ScriptedHost};
+use crate::ops;
+
+/// Limits that keep a faulty graph from running forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterpConfig {
+    /// Iterations a `Fixpoint` may take before giving up.
+    pub max_iterations: u64,
+    /// Unfoldings a `Recurse` may take before giving up.
+    pub max_depth: u64,
+    /// Node evaluations (including loop iterations) across the whole run.
+    pub max_steps: u64,
+    /// Seed of the generator probabilistic nodes draw from.
+    pub seed: u64,
+    /// Draws behind a Monte Carlo estimate or a rejection-sampled posterior.
+    pub samples: usize,
+}
+
+impl Default for InterpConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100_000,
+            max_depth: 10_000,
+            max_steps: 10_000_000,
+            seed: 0,
+            samples: 10_000,
+        }
+    }
+}
+
+/// Something observable that happened during execution, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    /// A `Measure` node was reached at the given step.
+    Measure { node: NodeId, step: u64 },
+    /// A `Checkpoint` node captured its input values.
+    Checkpoint { node: NodeId, values: Vec<Value> },
+}
+
+/// The result of running a graph.
+#[derive(Debug, Clone)]
+pub struct Execution {
+    values: HashMap<PortRef, Value>,
+    /// Observation events in execution order.
+    pub trace: Vec<TraceEvent>,
+    /// Number of evaluation steps taken.
+    pub steps: u64,
+}
+
+impl Execution {
+    /// The value produced on an output port, if that port was evaluated.
+    pub fn value(&self, node: &NodeId, port: usize) -> Option<&Value> {
+        self.values.get(&(*node, port))
+    }
+
+    /// The value on output port 0 of a node.
+    pub fn output(&self, node: &NodeId) -> Option<&Value> {
+        self.value(node, 0)
+    }
+
+    /// Every evaluated output port.
+    pub fn values(&self) -> &HashMap<PortRef, Value> {
+        &self.values
+    }
+}
+
+/// Executes a graph against a `Host`.
+#[derive(Debug, Clone)]
+pub struct Interpreter<'g> {
+    graph: &'g Graph,
+    config: InterpConfig,
+}
+
+impl<'g> Interpreter<'g> {
+    pub fn new(graph: &'g Graph) -> Self {
+        Self {
+            graph,
+            config: InterpConfig::default(),
+        }
+    }
+
+    /// Replace the default execution limits.
+    pub fn with_config(mut self, config: InterpConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Execute every node that is not inside a conditional region (those
+    /// run only when demanded), returning all produced values.
+    pub fn run(&self, host: &mut dyn Host) -> Result<Execution> {
+        let plan = Plan::new(self.graph)?;
+        let mut machine = Machine {
+            plan: &plan,
+            config: self.config,
+            host,
+            values: HashMap::new(),
+            produced: HashMap::new(),
+            done: HashSet::new(),
+            in_progress: HashSet::new(),
+            active: HashSet::new(),
+            buffers: Vec::new(),
+            trace: Vec::new(),
+            steps: 0,
+            rng: Rng::new(self.config.seed),
+        };
+        for id in &plan.order {
+            if !plan.lazy.contains(id) {
+                machine.ensure(*id)?;
+            }
+        }
+        Ok(Execution {
+            values: machine.values,
+            trace: machine.trace,
+            steps: machine.steps,
+        })
+    }
+}
+
+/// Run a graph with a host that has no inputs queued.
+pub fn run(graph: &Graph) -> Result<Execution> {
+    Interpreter::new(graph).run(&mut ScriptedHost::new())
+}
+
+/// Whether an input port of a loop node carries a value from the previous
+/// iteration rather than from outside the loop.
+fn is_back_port(kind: &NodeKind, port: usize) -> bool {
+    match kind {
+        NodeKind::Iterate => matches!(port, 1 | 3),
+        NodeKind::Recurse => matches!(port, 1..=3),
+        NodeKind::Fixpoint => matches!(port, 1 | 2),
+        _ => false,
+    }
+}
+
+fn is_loop(kind: &NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Iterate | NodeKind::Recurse | NodeKind::Fixpoint
+    )
+}
+
+/// Static analysis of a graph, shared by every evaluation.
+struct Plan<'g> {
+    graph: &'g Graph,
+    /// Schedule over forward edges, ties broken by smallest node ID.
+    order: Vec<NodeId>,
+    /// Producer of each connected input port.
+    sources: HashMap<NodeId, BTreeMap<usize, PortRef>>,
+    /// Number of input ports, from the signature or the highest connected port.
+    arity: HashMap<NodeId, usize>,
+    /// Loop node -> nodes re-evaluated on every iteration, in schedule order.
+    bodies: HashMap<NodeId, Vec<NodeId>>,
+    /// Node -> innermost loop whose body contains it.
+    body_of: HashMap<NodeId, NodeId>,
+    /// Node -> region that must run as a unit when the node is demanded.
+    unit: HashMap<NodeId, RegionId>,
+    /// Nodes inside a conditional region, skipped by the main pass.
+    lazy: HashSet<NodeId>,
+    /// Region -> nodes run when the region runs as a unit, in schedule order.
+    eager: HashMap<RegionId, Vec<NodeId>>,
+}
+
+impl<'g> Plan<'g> {
+    fn new(graph: &'g Graph) -> Result<Self> {
+        let mut edges: Vec<_> = graph.edges().collect();
+        edges.sort_by_key(|e| e.id);
+
+        let mut sources: HashMap<NodeId, BTreeMap<usize, PortRef>> = HashMap::new();
+        let mut forward: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        let mut reverse: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        let mut back_sources: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        let mut in_degree: HashMap<NodeId, usize> = graph.nodes().map(|n| (n.id, 0)).collect();
+
+        for edge in edges {
+            let (target, port) = edge.target;
+            if sources
+                .entry(target)
+                .or_default()
+                .insert(port, edge.source)
+                .is_some()
+            {
+                return Err(InterpError::DuplicateInput { node: target, port });
+            }
+            let kind = &graph
+                .get_node(&target)
+                .ok_or(GraphError::NodeNotFound(target))?
+                .kind;
+            if is_back_port(kind, port) {
+                back_sources.entry(target).or_default().push(edge.source.0);
+            } else {
+                forward.entry(edge.source.0).or_default().push(target);
+                reverse.entry(target).or_default().push(edge.source.0);
+                *in_degree.entry(target).or_default() += 1;
+            }
+        }
+
+        // Kahn's algorithm over forward edges.
+        let mut ready: BTreeSet<NodeId> = in_degree
+            .iter()
+            .filter(|(_, d)| **d == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        let mut order = Vec::with_capacity(in_degree.len());
+        while let Some(id) = ready.pop_first() {
+            order.push(id);
+            for next in forward.get(&id).into_iter().flatten() {
+                let d = in_degree.get_mut(next).expect("edge target is a node");
+                *d -= 1;
+                if *d == 0 {
+                    ready.insert(*next);
+                }
+            }
+        }
+        if order.len() < in_degree.len() {
+            let stuck = in_degree
+                .iter()
+                .filter(|(_, d)| **d > 0)
+                .map(|(id, _)| *id)
+                .min()
+                .expect("some node is stuck");
+            return Err(InterpError::Cycle(stuck));
+        }
+        let rank: HashMap<NodeId, usize> =
+            order.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        let arity = graph
+            .nodes()
+            .map(|n| {
+                let declared = n.type_signature.as_ref().map_or(0, |s| s.inputs.len());
+                let connected = sources
+                    .get(&n.id)
+                    .and_then(|ports| ports.keys().next_back())
+                    .map_or(0, |p| p + 1);
+                (n.id, declared.max(connected))
+            })
+            .collect();
+
+        // Loop bodies: reachable from the loop node and reaching one of its
+        // back-edge sources.
+        let mut bodies = HashMap::new();
+        let mut body_of: HashMap<NodeId, NodeId> = HashMap::new();
+        for id in order
+            .iter()
+            .filter(|id| graph.get_node(id).is_some_and(|n| is_loop(&n.kind)))
+        {
+            let downstream = reachable(*id, &forward);
+            let mut upstream = HashSet::new();
+            for source in back_sources.get(id).into_iter().flatten() {
+                upstream.insert(*source);
+                upstream.extend(reachable(*source, &reverse));
+            }
+            let mut body: Vec<NodeId> = downstream
+                .intersection(&upstream)
+                .filter(|n| *n != id)
+                .copied()
+                .collect();
+            body.sort_by_key(|n| rank[n]);
+            for member in &body {
+                let smaller = body_of.get(member).is_none_or(|current: &NodeId| {
+                    bodies
+                        .get(current)
+                        .is_some_and(|b: &Vec<NodeId>| body.len() < b.len())
+                });
+                if smaller {
+                    body_of.insert(*member, *id);
+                }
+            }
+            bodies.insert(*id, body);
+        }
+
+        // Region units and laziness.
+        let mut unit = HashMap::new();
+        let mut lazy = HashSet::new();
+        for id in &order {
+            let mut chain = Vec::new();
+            let mut current = graph.containing_region(id).copied();
+            while let Some(rid) = current {
+                chain.push(rid);
+                current = graph.parent_region(&rid).copied();
+            }
+            let mut outermost_atomic = None;
+            let mut conditional = None;
+            for rid in &chain {
+                match graph.get_region(rid).map(|r| r.kind) {
+                    Some(RegionKind::Atomic) if conditional.is_none() => {
+                        outermost_atomic = Some(*rid)
+                    }
+                    Some(RegionKind::Conditional) => {
+                        conditional.get_or_insert(*rid);
+                        lazy.insert(*id);
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(rid) = outermost_atomic.or(conditional) {
+                unit.insert(*id, rid);
+            }
+        }
+
+        let mut eager = HashMap::new();
+        for region in graph.regions() {
+            let mut members = HashSet::new();
+            collect_members(graph, region.id, true, &mut members);
+            let mut members: Vec<NodeId> = members.into_iter().collect();
+            members.sort_by_key(|n| rank.get(n).copied().unwrap_or(usize::MAX));
+            eager.insert(region.id, members);
+        }
+
+        // A loop declared inside an iterative region must not reach outside it.
+        for (id, body) in &bodies {
+            let mut current = graph.containing_region(id).copied();
+            while let Some(rid) = current {
+                if graph.get_region(&rid).map(|r| r.kind) == Some(RegionKind::Iterative) {
+                    let mut members = HashSet::new();
+                    collect_members(graph, rid, false, &mut members);
+                    if body.iter().any(|n| !members.contains(n)) {
+                        return Err(InterpError::LoopEscapesRegion {
+                            node: *id,
+                            region: rid,
+                        });
+                    }
+                }
+                current = graph.parent_region(&rid).copied();
+            }
+        }
+
+        Ok(Self {
+            graph,
+            order,
+            sources,
+            arity,
+            bodies,
+            body_of,
+            unit,
+            lazy,
+            eager,
+        })
+    }
+
+    fn node(&self, id: &NodeId) -> &'g Node {
+        self.graph.get_node(id).expect("planned node exists")
+    }
+
+    fn source(&self, id: &NodeId, port: usize) -> Option<PortRef> {
+        self.sources
+            .get(id)
+            .and_then(|ports| ports.get(&port))
+            .copied()
+    }
+}
+
+fn reachable(start: NodeId, adjacency: &HashMap<NodeId, Vec<NodeId>>) -> HashSet<NodeId> {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+        for next in adjacency.get(&id).into_iter().flatten() {
+            if seen.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    seen
+}
+
+/// Nodes of a region and its descendants. With `stop_at_conditional`,
+/// nested conditional regions are left out (they run only on demand).
+fn collect_members(
+    graph: &Graph,
+    rid: RegionId,
+    stop_at_conditional: bool,
+    out: &mut HashSet<NodeId>,
+) {
+    let Some(region) = graph.get_region(&rid) else {
+        return;
+    };
+    out.extend(region.children.iter().copied());
+    for child in graph.child_regions(&rid) {
+        let conditional = graph.get_region(&child).map(|r| r.kind) == Some(RegionKind::Conditional);
+        if !(stop_at_conditional && conditional) {
+            collect_members(graph, child, stop_at_conditional, out);
+        }
+    }
+}
+
+/// Mutable evaluation state for one run.
+struct Machine<'p, 'g, 'h> {
+    plan: &'p Plan<'g>,
+    config: InterpConfig,
+    host: &'h mut dyn Host,
+    values: HashMap<PortRef, Value>,
+    /// Number of output ports each evaluated node produced.
+    produced: HashMap<NodeId, usize>,
+    done: HashSet<NodeId>,
+    in_progress: HashSet<NodeId>,
+    /// Regions currently running as a unit.
+    active: HashSet<RegionId>,
+    /// Writes held back by enclosing atomic regions, innermost last.
+    buffers: Vec<Vec<(NodeId, Value)>>,
+    trace: Vec<TraceEvent>,
+    steps: u64,
+    /// Draws for probabilistic nodes, in evaluation order.
+    rng: Rng,
+}
+
+impl Machine<'_, '_, '_> {
+    fn tick(&mut self) -> Result<()> {
+        self.steps += 1;
+        if self.steps > self.config.max_steps {
+            return Err(InterpError::StepLimit {
+                limit: self.config.max_steps,
+            });
+        }
+        Ok(())
+    }
+
+    /// Make sure a node's outputs are available.
+    fn ensure(&mut self, id: NodeId) -> Result<()> {
+        if self.done.contains(&id) {
+            return Ok(());
+        }
+        if let Some(l) = self.plan.body_of.get(&id) {
+            if !self.done.contains(l) {
+                self.ensure(*l)?;
+                if self.done.contains(&id) {
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(rid) = self.plan.unit.get(&id) {
+            if !self.active.contains(rid) {
+                self.run_unit(*rid)?;
+                if self.done.contains(&id) {
+                    return Ok(());
+                }
+            }
+        }
+        if !self.in_progress.insert(id) {
+            return Err(InterpError::Cycle(id));
+        }
+        self.tick()?;
+        let outputs = self.eval(self.plan.node(&id));
+        self.in_progress.remove(&id);
+        let outputs = outputs?;
+        self.produced.insert(id, outputs.len());
+        for (port, value) in outputs.into_iter().enumerate() {
+            self.values.insert((id, port), value);
+        }
+        self.done.insert(id);
+        Ok(())
+    }
+
+    /// Evaluate every eagerly-run member of a region. Writes inside an
+    /// atomic region are only released once the whole region succeeds.
+    fn run_unit(&mut self, rid: RegionId) -> Result<()> {
+        let atomic = self.plan.graph.get_region(&rid).map(|r| r.kind) == Some(RegionKind::Atomic);
+        self.active.insert(rid);
+        if atomic {
+            self.buffers.push(Vec::new());
+        }
+        let members = self.plan.eager.get(&rid).map_or(&[][..], Vec::as_slice);
+        let result = members.iter().try_for_each(|id| self.ensure(*id));
+        self.active.remove(&rid);
+        if atomic {
+            let writes = self.buffers.pop().expect("buffer pushed above");
+            if result.is_ok() {
+                self.emit(writes)?;
+            }
+        }
+        result
+    }
+
+    fn emit(&mut self, writes: Vec<(NodeId, Value)>) -> Result<()> {
+        match self.buffers.last_mut() {
+            Some(parent) => parent.extend(writes),
+            None => {
+                for (id, value) in writes {
+                    let node = self.plan.node(&id);
+                    self.host
+                        .write(node, value)
+                        .map_err(|message| InterpError::Host { node: id, message })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch the value flowing into an input port.
+    fn input(&mut self, id: NodeId, port: usize) -> Result<Value> {
+        let (source, out) = self
+            .plan
+            .source(&id, port)
+            .ok_or(InterpError::MissingInput { node: id, port })?;
+        self.ensure(source)?;
+        self.values
+            .get(&(source, out))
+            .cloned()
+            .ok_or(InterpError::MissingValue {
+                node: source,
+                port: out,
+            })
+    }
+
+    fn inputs(&mut self, id: NodeId) -> Result<Vec<Value>> {
+        let arity = self.plan.arity.get(&id).copied().unwrap_or(0);
+        (0..arity).map(|port| self.input(id, port)).collect()
+    }
+
+    fn has_input(&self, id: NodeId, port: usize) -> bool {
+        self.plan.source(&id, port).is_some()
+    }
+
+    fn eval(&mut self, node: &Node) -> Result<Vec<Value>> {
+        let id = node.id;
+        let outputs = match node.kind {
+            NodeKind::Iterate | NodeKind::Recurse | NodeKind::Fixpoint => {
+                self.done.insert(id);
+                let result = match node.kind {
+                    NodeKind::Iterate => self.iterate(node),
+                    NodeKind::Recurse => self.recurse(id),
+                    _ => self.fixpoint(id),
+                };
+                self.done.remove(&id);
+                let body = self.plan.bodies.get(&id).map_or(&[][..], Vec::as_slice);
+                self.done.extend(body.iter().copied());
+                result?
+            }
+            NodeKind::Select => {
+                let condition = self.input(id, 0)?;
+                let branch = if expect_bool(id, &condition)? { 1 } else { 2 };
+                vec![self.input(id, branch)?]
+            }
+            NodeKind::Switch => {
+                let selector = self.input(id, 0)?;
+                let cases = self
+                    .plan
+                    .arity
+                    .get(&id)
+                    .copied()
+                    .unwrap_or(0)
+                    .saturating_sub(1);
+                let index = match &selector {
+                    Value::Bool(b) => *b as u128,
+                    Value::Int(x) => x.to_u128().ok_or_else(|| InterpError::TypeMismatch {
+                        node: id,
+                        message: format!("negative switch index {x}"),
+                    })?,
+                    other => {
+                        return Err(InterpError::TypeMismatch {
+                            node: id,
+                            message: format!("switch on {}", other.kind_name()),
+                        })
+                    }
+                };
+                if index >= cases as u128 {
+                    return Err(InterpError::IndexOutOfBounds {
+                        node: id,
+                        index,
+                        len: cases,
+                    });
+                }
+                vec![self.input(id, 1 + index as usize)?]
+            }
+            _ => {
+                let args = self.inputs(id)?;
+                if let Some(sig) = &node.type_signature {
+                    for (i, (value, ty)) in args.iter().zip(&sig.inputs).enumerate() {
+                        if !value.conforms_to(ty) {
+                            return Err(InterpError::TypeMismatch {
+                                node: id,
+                                message: format!("input {i} is {value}, expected {ty}"),
+                            });
+                        }
+                    }
+                }
+                self.apply(node, args)?
+            }
+        };
+        if let Some(sig) = &node.type_signature {
+            for (i, (value, ty)) in outputs.iter().zip(&sig.outputs).enumerate() {
+                if !value.conforms_to(ty) {
+                    return Err(InterpError::TypeMismatch {
+                        node: id,
+                        message: format!("output {i} is {value}, expected {ty}"),
+                    });
+                }
+            }
+        }
+        Ok(outputs)
+    }
+
+    /// Start a loop iteration: forget the body's previous values and expose
+    /// the loop node's current outputs to it.
+    fn begin_iteration(&mut self, id: NodeId, outputs: &[Value]) -> Result<()> {
+        self.tick()?;
+        for member in self.plan.bodies.get(&id).into_iter().flatten() {
+            self.done.remove(member);
+            for port in 0..self.produced.remove(member).unwrap_or(0) {
+                self.values.remove(&(*member, port));
+            }
+        }
+        for (port, value) in outputs.iter().enumerate() {
+            self.values.insert((id, port), value.clone());
+        }
+        Ok(())
+    }
+
+    fn iterate(&mut self, node: &Node) -> Result<Vec<Value>> {
+        let id = node.id;
+        let mut state = self.input(id, 0)?;
+        let bound = if self.has_input(id, 2) {
+            self.input(id, 2)?
+        } else {
+            let text = node
+                .annotations
+                .get("bound")
+                .ok_or(InterpError::MissingInput { node: id, port: 2 })?;
+            ops::parse_literal(text, &Type::u64())
+                .map_err(|message| InterpError::InvalidLiteral { node: id, message })?
+        };
+        let bound_int = bound
+            .as_int()
+            .copied()
+            .ok_or_else(|| InterpError::TypeMismatch {
+                node: id,
+                message: format!("iteration bound is {}", bound.kind_name()),
+            })?;
+        let limit = bound_int
+            .to_u128()
+            .ok_or_else(|| InterpError::TypeMismatch {
+                node: id,
+                message: format!("negative iteration bound {bound_int}"),
+            })?;
+        let (width, signedness) = match node
+            .type_signature
+            .as_ref()
+            .and_then(|s| s.outputs.get(1))
+            .map(Type::base_type)
+        {
+            Some(Type::Int { width, signedness }) => (*width, *signedness),
+            _ => (bound_int.width(), bound_int.signedness()),
+        };
+        let index = |i: u128| Value::Int(IntValue::from_bits(i, width, signedness));
+
+        let conditional = self.has_input(id, 3);
+        let mut i = 0u128;
+        loop {
+            if !conditional && i == limit {
+                break;
+            }
+            self.begin_iteration(id, &[state.clone(), index(i)])?;
+            if conditional {
+                let proceed = self.input(id, 3)?;
+                if !expect_bool(id, &proceed)? {
+                    break;
+                }
+                if i == limit {
+                    return Err(InterpError::BoundExceeded {
+                        node: id,
+                        bound: limit,
+                    });
+                }
+            }
+            state = self.input(id, 1)?;
+            i += 1;
+        }
+        Ok(vec![state, index(i)])
+    }
+
+    fn recurse(&mut self, id: NodeId) -> Result<Vec<Value>> {
+        let mut argument = self.input(id, 0)?;
+        let mut previous_metric: Option<u128> = None;
+        let mut depth = 0u64;
+        loop {
+            self.begin_iteration(id, std::slice::from_ref(&argument))?;
+            let base = self.input(id, 1)?;
+            if expect_bool(id, &base)? {
+                break;
+            }
+            if self.has_input(id, 3) {
+                let metric = self
+                    .input(id, 3)?
+                    .as_int()
+                    .and_then(IntValue::to_u128)
+                    .ok_or(InterpError::MetricNotDecreasing { node: id })?;
+                if previous_metric.is_some_and(|p| metric >= p) {
+                    return Err(InterpError::MetricNotDecreasing { node: id });
+                }
+                previous_metric = Some(metric);
+            }
+            argument = self.input(id, 2)?;
+            depth += 1;
+            if depth > self.config.max_depth {
+                return Err(InterpError::RecursionLimit { node: id, depth });
+            }
+        }
+        Ok(vec![argument])
+    }
+
+    fn fixpoint(&mut self, id: NodeId) -> Result<Vec<Value>> {
+        let mut current = self.input(id, 0)?;
+        for _ in 0..self.config.max_iterations {
+            self.begin_iteration(id, std::slice::from_ref(&current))?;
+            let next = self.input(id, 1)?;
+            let converged = if self.has_input(id, 2) {
+                let flag = self.input(id, 2)?;
+                expect_bool(id, &flag)?
+            } else {
+                next == current
+            };
+            current = next;
+            if converged {
+                return Ok(vec![current]);
+            }
+        }
+        Err(InterpError::NoConvergence {
+            node: id,
+            iterations: self.config.max_iterations,
+        })
+    }
+
+    /// Semantics of every non-control node.
+    fn apply(&mut self, node: &Node, args: Vec<Value>) -> Result<Vec<Value>> {
+        let id = node.id;
+        let arg = |i: usize| {
+            args.get(i)
+                .ok_or(InterpError::MissingInput { node: id, port: i })
+        };
+        let output_ty = node.type_signature.as_ref().and_then(|s| s.outputs.first());
+        let single = |v: Value| Ok(vec![v]);
+        let fixed = || {
+            FixedSemantics::of_node(node).map_err(|e| InterpError::InvalidAnnotation {
+                node: id,
+                message: e.to_string(),
+            })
+        };
+        let prob_error = |e: ProbError| InterpError::Probabilistic {
+            node: id,
+            message: e.to_string(),
+        };
+        let distribution = |v: &Value| Distribution::from_value(v).map_err(prob_error);
+        let number =
+            |x: f64| prob::from_number(x, output_ty.unwrap_or(&Type::f64())).map_err(prob_error);
+
+        match &node.kind {
+            NodeKind::Literal => {
+                let value = match (&node.value, output_ty) {
+                    (Some(value), _) => value.clone(),
+                    (None, Some(ty)) if matches!(ty.base_type(), Type::Unit) => Value::Unit,
+                    (None, _) => {
+                        return Err(InterpError::InvalidLiteral {
+                            node: id,
+                            message: "literal has no value".into(),
+                        })
+                    }
+                };
+                single(value)
+            }
+            NodeKind::Arithmetic(op) => {
+                single(ops::arithmetic(*op, arg(0)?, arg(1)?, fixed()?).map_err(|f| f.at(id))?)
+            }
+            NodeKind::Bitwise(op) => {
+                let rhs = match op {
+                    BitwiseOp::Not => None,
+                    _ => Some(arg(1)?),
+                };
+                single(ops::bitwise(*op, arg(0)?, rhs).map_err(|f| f.at(id))?)
+            }
+            NodeKind::Comparison(op) => single(Value::Bool(
+                ops::comparison(*op, arg(0)?, arg(1)?).map_err(|f| f.at(id))?,
+            )),
+            NodeKind::Conversion => {
+                let target = output_ty.ok_or_else(|| InterpError::TypeMismatch {
+                    node: id,
+                    message: "conversion has no target type".into(),
+                })?;
+                single(ops::convert(arg(0)?, target, fixed()?).map_err(|f| f.at(id))?)
+            }
+            NodeKind::Construct if output_ty.and_then(prob::element_type).is_some() => {
+                let family = node
+                    .annotations
+                    .get(DISTRIBUTION_ANNOTATION)
+                    .ok_or_else(|| InterpError::InvalidAnnotation {
+                        node: id,
+                        message: format!(
+                            "building a distribution needs a \"{DISTRIBUTION_ANNOTATION}\" annotation"
+                        ),
+                    })?;
+                let dist = Distribution::from_parameters(family, &args).map_err(prob_error)?;
+                single(dist.to_value())
+            }
+            NodeKind::Construct => single(construct(node, output_ty, args.clone())?),
+            NodeKind::Destructure => destructure(id, arg(0)?),
+            NodeKind::Index => {
+                let elements = elements_of(id, arg(0)?)?;
+                let index = index_of(id, arg(1)?)?;
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| elements.get(i))
+                    .cloned()
+                    .map(|v| vec![v])
+                    .ok_or(InterpError::IndexOutOfBounds {
+                        node: id,
+                        index,
+                        len: elements.len(),
+                    })
+            }
+            NodeKind::Slice => {
+                let collection = arg(0)?;
+                let elements = elements_of(id, collection)?;
+                let (start, end) = (index_of(id, arg(1)?)?, index_of(id, arg(2)?)?);
+                let len = elements.len();
+                if end > len as u128 || start > end {
+                    return Err(InterpError::IndexOutOfBounds {
+                        node: id,
+                        index: if start > end { start } else { end },
+                        len,
+                    });
+                }
+                let part = elements[start as usize..end as usize].to_vec();
+                single(match collection {
+                    Value::Tuple(_) => Value::Tuple(part),
+                    Value::Array(_) => Value::Array(part),
+                    _ => Value::Vec(part),
+                })
+            }
+            NodeKind::Allocate => single(args.first().cloned().unwrap_or(Value::Unit)),
+            NodeKind::Deallocate => single(Value::Unit),
+            NodeKind::Read => {
+                let value = self
+                    .host
+                    .read(node)
+                    .map_err(|message| InterpError::Host { node: id, message })?;
+                single(value)
+            }
+            NodeKind::Write => {
+                let value = match args.len() {
+                    1 => args[0].clone(),
+                    _ => Value::Tuple(args.clone()),
+                };
+                self.emit(vec![(id, value)])?;
+                Ok(vec![])
+            }
+            NodeKind::Atomic(_) => {
+                let cell = node
+                    .annotations
+                    .get("cell")
+                    .or_else(|| node.annotations.get("name"))
+                    .cloned()
+                    .unwrap_or_else(|| id.to_string());
+                let old = self.host.load(&cell).ok_or_else(|| InterpError::Host {
+                    node: id,
+                    message: format!("memory cell \"{cell}\" is uninitialized"),
+                })?;
+                let op = node.annotations.get("op").map_or("swap", String::as_str);
+                let fault = |f: ops::Fault| f.at(id);
+                let new = match op {
+                    "load" => old.clone(),
+                    "swap" => arg(0)?.clone(),
+                    "add" => ops::arithmetic(ArithmeticOp::Add, &old, arg(0)?, fixed()?)
+                        .map_err(fault)?,
+                    "sub" => ops::arithmetic(ArithmeticOp::Sub, &old, arg(0)?, fixed()?)
+                        .map_err(fault)?,
+                    "and" => ops::bitwise(BitwiseOp::And, &old, Some(arg(0)?)).map_err(fault)?,
+                    "or" => ops::bitwise(BitwiseOp::Or, &old, Some(arg(0)?)).map_err(fault)?,
+                    "xor" => ops::bitwise(BitwiseOp::Xor, &old, Some(arg(0)?)).map_err(fault)?,
+                    other => {
+                        return Err(InterpError::Unsupported {
+                            node: id,
+                            kind: format!("atomic op \"{other}\""),
+                        })
+                    }
+                };
+                self.host.store(&cell, new);
+                single(old)
+            }
+            NodeKind::Fence(_) | NodeKind::Annotate => Ok(args),
+            NodeKind::Syscall | NodeKind::FFICall => self
+                .host
+                .call(node, args)
+                .map_err(|message| InterpError::Host { node: id, message }),
+            NodeKind::Verify | NodeKind::Assume => {
+                if !expect_bool(id, arg(0)?)? {
+                    return Err(match node.kind {
+                        NodeKind::Verify => InterpError::AssertionFailed { node: id },
+                        _ => InterpError::AssumptionViolated { node: id },
+                    });
+                }
+                Ok(args)
+            }
+            NodeKind::Measure => {
+                self.trace.push(TraceEvent::Measure {
+                    node: id,
+                    step: self.steps,
+                });
+                Ok(args)
+            }
+            NodeKind::Checkpoint => {
+                self.trace.push(TraceEvent::Checkpoint {
+                    node: id,
+                    values: args.clone(),
+                });
+                Ok(args)
+            }
+            NodeKind::Sample => {
+                let x = distribution(arg(0)?)?.sample(&mut self.rng);
+                single(number(x)?)
+            }
+            NodeKind::Condition => {
+                let prior = distribution(arg(0)?)?;
+                let lo = prob::to_number(arg(1)?).map_err(prob_error)?;
+                let hi = prob::to_number(arg(2)?).map_err(prob_error)?;
+                let posterior = prior
+                    .condition(lo, hi, self.config.samples, &mut self.rng)
+                    .map_err(prob_error)?;
+                single(posterior.to_value())
+            }
+            NodeKind::Expectation => {
+                let mean = distribution(arg(0)?)?.expectation(self.config.samples, &mut self.rng);
+                single(number(mean)?)
+            }
+            NodeKind::Entropy => single(number(distribution(arg(0)?)?.entropy())?),
+            NodeKind::Approximate => single(arg(0)?.clone()),
+            NodeKind::Select
+            | NodeKind::Switch
+            | NodeKind::Iterate
+            | NodeKind::Recurse
+            | NodeKind::Fixpoint => unreachable!("control nodes are evaluated in eval"),
+        }
+    }
+}
+
+fn expect_bool(node: NodeId, value: &Value) -> Result<bool> {
+    value.as_bool().ok_or_else(|| InterpError::TypeMismatch {
+        node,
+        message: format!("expected bool, found {}", value.kind_name()),
+    })
+}
+
+fn elements_of(node: NodeId, value: &Value) -> Result<&[Value]> {
+    value.elements().ok_or_else(|| InterpError::TypeMismatch {
+        node,
+        message: format!("cannot index into {}", value.kind_name()),
+    })
+}
+
+fn index_of(node: NodeId, value: &Value) -> Result<u128> {
+    value
+        .as_int()
+        .and_then(IntValue::to_u128)
+        .ok_or_else(|| InterpError::TypeMismatch {
+            node,
+            message: format!("{value} is not a valid index"),
+        })
+}
+
+fn construct(node: &Node, ty: Option<&Type>, args: Vec<Value>) -> Result<Value> {
+    let arity_mismatch = |expected: usize| InterpError::TypeMismatch {
+        node: node.id,
+        message: format!("expected {expected} fields, found {}", args.len()),
+    };
+    Ok(match ty.map(Type::base_type) {
+        Some(Type::Unit) => Value::Unit,
+        Some(Type::Record(fields)) => {
+            if fields.len() != args.len() {
+                return Err(arity_mismatch(fields.len()));
+            }
+            Value::Record(fields.keys().cloned().zip(args).collect())
+        }
+        Some(Type::Array { .. }) => Value::Array(args),
+        Some(Type::Vec { .. }) => Value::Vec(args),
+        Some(Type::Option(_)) => match args.len() {
+            0 => Value::none(),
+            1 => Value::some(args.into_iter().next().expect("one argument")),
+            _ => return Err(arity_mismatch(1)),
+        },
+        Some(Type::Variant(cases)) => {
+            let tag = match (node.annotations.get("variant"), cases.len()) {
+                (Some(tag), _) => tag.clone(),
+                (None, 1) => cases.keys().next().expect("one case").clone(),
+                (None, _) => {
+                    return Err(InterpError::TypeMismatch {
+                        node: node.id,
+                        message: "variant construction needs a \"variant\" annotation".into(),
+                    })
+                }
+            };
+            let payload = match args.len() {
+                0 => Value::Unit,
+                1 => args.into_iter().next().expect("one argument"),
+                _ => Value::Tuple(args),
+            };
+            Value::variant(tag, payload)
+        }
+        _ => Value::Tuple(args),
+    })
+}
+
+fn destructure(node: NodeId, value: &Value) -> Result<Vec<Value>> {
+    Ok(match value {
+        Value::Tuple(elems) | Value::Array(elems) | Value::Vec(elems) => elems.clone(),
+        Value::Record(fields) => fields.values().cloned().collect(),
+        Value::Variant { value, .. } => vec![(**value).clone()],
+        Value::Option(inner) => match inner {
+            Some(v) => vec![Value::Bool(true), (**v).clone()],
+            None => vec![Value::Bool(false)],
+        },
+        other => {
+            return Err(InterpError::TypeMismatch {
+                node,
+                message: format!("cannot destructure {}", other.kind_name()),
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::ComparisonOp;
+    use torc_core::graph::region::Region;
+    use torc_core::types::TypeSignature;
+
+    fn lit(g: &mut Graph, ty: Type, value: &str) -> NodeId {
+        let value = ops::parse_literal(value, &ty).unwrap();
+        g.add_node(
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(ty))
+                .with_value(value),
+        )
+        .unwrap()
+    }
+
+    fn node(g: &mut Graph, kind: NodeKind, inputs: Vec<Type>, outputs: Vec<Type>) -> NodeId {
+        g.add_node(Node::new(kind).with_type_signature(TypeSignature::new(inputs, outputs)))
+            .unwrap()
+    }
+
+    fn binary(g: &mut Graph, kind: NodeKind, ty: Type, a: NodeId, b: NodeId) -> NodeId {
+        let out = if matches!(kind, NodeKind::Comparison(_)) {
+            Type::Bool
+        } else {
+            ty.clone()
+        };
+        let id = node(g, kind, vec![ty.clone(), ty], vec![out]);
+        wire(g, a, 0, id, 0);
+        wire(g, b, 0, id, 1);
+        id
+    }
+
+    fn wire(g: &mut Graph, from: NodeId, from_port: usize, to: NodeId, to_port: usize) {
+        g.add_edge(Edge::new((from, from_port), (to, to_port)))
+            .unwrap();
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        let mut g = Graph::new();
+        let (a, b) = (
+            lit(&mut g, Type::i32(), "10"),
+            lit(&mut g, Type::i32(), "32"),
+        );
+        let (two, five) = (lit(&mut g, Type::i32(), "2"), lit(&mut g, Type::i32(), "5"));
+        let add = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            Type::i32(),
+            a,
+            b,
+        );
+        let mul = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Mul),
+            Type::i32(),
+            add,
+            two,
+        );
+        let sub = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Sub),
+            Type::i32(),
+            mul,
+            five,
+        );
+
+        let exec = run(&g).unwrap();
+        assert_eq!(exec.output(&sub), Some(&Value::i32(79)));
+        assert_eq!(exec.steps, 7);
+    }
+
+    #[test]
+    fn fixed_point_follows_node_annotations() {
+        let q15 = Type::Fixed {
+            total_bits: 16,
+            frac_bits: 15,
+        };
+        let mut g = Graph::new();
+        let x = lit(&mut g, q15.clone(), "0.75");
+        let wrapped = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            q15.clone(),
+            x,
+            x,
+        );
+        let saturated = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            q15.clone(),
+            x,
+            x,
+        );
+        g.get_node_mut(&saturated)
+            .unwrap()
+            .annotations
+            .insert("overflow".into(), "saturate".into());
+        let scaled = node(
+            &mut g,
+            NodeKind::Conversion,
+            vec![q15.clone()],
+            vec![Type::Fixed {
+                total_bits: 16,
+                frac_bits: 1,
+            }],
+        );
+        wire(&mut g, x, 0, scaled, 0);
+        g.get_node_mut(&scaled)
+            .unwrap()
+            .annotations
+            .insert("rounding".into(), "nearest_even".into());
+
+        let exec = run(&g).unwrap();
+        let raw = |id: &NodeId| match exec.output(id) {
+            Some(Value::Fixed(v)) => v.raw(),
+            other => panic!("expected fixed, got {other:?}"),
+        };
+        assert_eq!(raw(&wrapped), 0x6000 * 2 - 0x10000);
+        assert_eq!(raw(&saturated), 0x7FFF);
+        // 0.75 lies halfway between 0.5 and 1.0
+        assert_eq!(raw(&scaled), 2);
+
+        g.get_node_mut(&scaled)
+            .unwrap()
+            .annotations
+            .insert("rounding".into(), "sideways".into());
+        assert!(matches!(
+            run(&g),
+            Err(InterpError::InvalidAnnotation { .. })
+        ));
+    }
+
+    #[test]
+    fn integer_arithmetic_follows_overflow_policy() {
+        let mut g = Graph::new();
+        let x = lit(&mut g, Type::i8(), "100");
+        let y = lit(&mut g, Type::i8(), "-100");
+        let add = |g: &mut Graph, l, r, policy: &str| {
+            let id = binary(g, NodeKind::Arithmetic(ArithmeticOp::Add), Type::i8(), l, r);
+            g.get_node_mut(&id)
+                .unwrap()
+                .annotations
+                .insert("overflow".into(), policy.into());
+            id
+        };
+        let wrapped = add(&mut g, x, x, "wrap");
+        let high = add(&mut g, x, x, "saturate");
+        let low = add(&mut g, y, y, "saturate");
+        let exact = add(&mut g, x, y, "trap");
+
+        let exec = run(&g).unwrap();
+        let int = |id: &NodeId| exec.output(id).and_then(Value::as_int).unwrap().to_i128();
+        assert_eq!(int(&wrapped), Some(-56));
+        assert_eq!(int(&high), Some(127));
+        assert_eq!(int(&low), Some(-128));
+        assert_eq!(int(&exact), Some(0));
+
+        add(&mut g, x, x, "proven");
+        assert!(matches!(run(&g), Err(InterpError::Overflow { .. })));
+    }
+
+    #[test]
+    fn indexes_composite_literal() {
+        let mut g = Graph::new();
+        let table_ty = Type::Array {
+            element: Box::new(Type::u8()),
+            length: 3,
+        };
+        let table = g
+            .add_node(
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(table_ty.clone()))
+                    .with_value(Value::Array(vec![Value::u8(3), Value::u8(5), Value::u8(7)])),
+            )
+            .unwrap();
+        let i = lit(&mut g, Type::u32(), "2");
+        let index = node(
+            &mut g,
+            NodeKind::Index,
+            vec![table_ty, Type::u32()],
+            vec![Type::u8()],
+        );
+        wire(&mut g, table, 0, index, 0);
+        wire(&mut g, i, 0, index, 1);
+
+        assert_eq!(run(&g).unwrap().output(&index), Some(&Value::u8(7)));
+    }
+
+    #[test]
+    fn iterate_runs_bound_times() {
+        let mut g = Graph::new();
+        let init = lit(&mut g, Type::i32(), "0");
+        let bound = lit(&mut g, Type::i32(), "5");
+        let iter = node(
+            &mut g,
+            NodeKind::Iterate,
+            vec![Type::i32(), Type::i32(), Type::i32()],
+            vec![Type::i32(), Type::i32()],
+        );
+        wire(&mut g, init, 0, iter, 0);
+        wire(&mut g, bound, 0, iter, 2);
+        let add = node(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            vec![Type::i32(), Type::i32()],
+            vec![Type::i32()],
+        );
+        wire(&mut g, iter, 0, add, 0);
+        wire(&mut g, iter, 1, add, 1);
+        wire(&mut g, add, 0, iter, 1);
+
+        let exec = run(&g).unwrap();
+        assert_eq!(exec.value(&iter, 0), Some(&Value::i32(10)));
+        assert_eq!(exec.value(&iter, 1), Some(&Value::i32(5)));
+    }
+
+    fn doubling_loop(bound: &str) -> (Graph, NodeId) {
+        let mut g = Graph::new();
+        let init = lit(&mut g, Type::u32(), "1");
+        let two = lit(&mut g, Type::u32(), "2");
+        let limit = lit(&mut g, Type::u32(), "100");
+        let iter = node(
+            &mut g,
+            NodeKind::Iterate,
+            vec![Type::u32(), Type::u32()],
+            vec![Type::u32(), Type::u64()],
+        );
+        g.get_node_mut(&iter)
+            .unwrap()
+            .annotations
+            .insert("bound".into(), bound.into());
+        wire(&mut g, init, 0, iter, 0);
+        let mul = node(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Mul),
+            vec![Type::u32(), Type::u32()],
+            vec![Type::u32()],
+        );
+        wire(&mut g, iter, 0, mul, 0);
+        wire(&mut g, two, 0, mul, 1);
+        wire(&mut g, mul, 0, iter, 1);
+        let lt = node(
+            &mut g,
+            NodeKind::Comparison(ComparisonOp::Lt),
+            vec![Type::u32(), Type::u32()],
+            vec![Type::Bool],
+        );
+        wire(&mut g, iter, 0, lt, 0);
+        wire(&mut g, limit, 0, lt, 1);
+        wire(&mut g, lt, 0, iter, 3);
+        (g, iter)
+    }
+
+    #[test]
+    fn iterate_stops_on_condition() {
+        let (g, iter) = doubling_loop("20");
+        let exec = run(&g).unwrap();
+        assert_eq!(exec.value(&iter, 0), Some(&Value::u32(128)));
+        assert_eq!(exec.value(&iter, 1), Some(&Value::u64(7)));
+
+        let (g, iter) = doubling_loop("3");
+        assert!(matches!(
+            run(&g),
+            Err(InterpError::BoundExceeded { node, bound: 3 }) if node == iter
+        ));
+    }
+
+    #[test]
+    fn fixpoint_converges() {
+        let mut g = Graph::new();
+        let init = lit(&mut g, Type::u32(), "100");
+        let two = lit(&mut g, Type::u32(), "2");
+        let fix = node(
+            &mut g,
+            NodeKind::Fixpoint,
+            vec![Type::u32(), Type::u32()],
+            vec![Type::u32()],
+        );
+        wire(&mut g, init, 0, fix, 0);
+        let half = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Div),
+            Type::u32(),
+            fix,
+            two,
+        );
+        wire(&mut g, half, 0, fix, 1);
+
+        assert_eq!(run(&g).unwrap().output(&fix), Some(&Value::u32(0)));
+
+        let config = InterpConfig {
+            max_iterations: 3,
+            ..InterpConfig::default()
+        };
+        let err = Interpreter::new(&g)
+            .with_config(config)
+            .run(&mut ScriptedHost::new())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            InterpError::NoConvergence { iterations: 3, .. }
+        ));
+    }
+
+    fn countdown(step: &str, with_metric: bool) -> (Graph, NodeId) {
+        let mut g = Graph::new();
+        let init = lit(&mut g, Type::u32(), "10");
+        let zero = lit(&mut g, Type::u32(), "0");
+        let step = lit(&mut g, Type::u32(), step);
+        let rec = node(
+            &mut g,
+            NodeKind::Recurse,
+            vec![Type::u32()],
+            vec![Type::u32()],
+        );
+        wire(&mut g, init, 0, rec, 0);
+        let done = binary(
+            &mut g,
+            NodeKind::Comparison(ComparisonOp::Eq),
+            Type::u32(),
+            rec,
+            zero,
+        );
+        wire(&mut g, done, 0, rec, 1);
+        let next = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Sub),
+            Type::u32(),
+            rec,
+            step,
+        );
+        wire(&mut g, next, 0, rec, 2);
+        if with_metric {
+            wire(&mut g, rec, 0, rec, 3);
+        }
+        (g, rec)
+    }
+
+    #[test]
+    fn recurse_checks_metric() {
+        let (g, rec) = countdown("1", true);
+        assert_eq!(run(&g).unwrap().output(&rec), Some(&Value::u32(0)));
+
+        let (g, _) = countdown("0", true);
+        assert!(matches!(
+            run(&g),
+            Err(InterpError::MetricNotDecreasing { .. })
+        ));
+
+        let (g, _) = countdown("0", false);
+        let config = InterpConfig {
+            max_depth: 50,
+            ..InterpConfig::default()
+        };
+        let err = Interpreter::new(&g)
+            .with_config(config)
+            .run(&mut ScriptedHost::new())
+            .unwrap_err();
+        assert!(matches!(err, InterpError::RecursionLimit { depth: 51, .. }));
+    }
+
+    #[test]
+    fn conditional_region_runs_only_on_demand() {
+        let build = |conditional: bool| {
+            let mut g = Graph::new();
+            let cond = lit(&mut g, Type::Bool, "true");
+            let safe = lit(&mut g, Type::i32(), "1");
+            let zero = lit(&mut g, Type::i32(), "0");
+            let div = binary(
+                &mut g,
+                NodeKind::Arithmetic(ArithmeticOp::Div),
+                Type::i32(),
+                safe,
+                zero,
+            );
+            let select = node(
+                &mut g,
+                NodeKind::Select,
+                vec![Type::Bool, Type::i32(), Type::i32()],
+                vec![Type::i32()],
+            );
+            wire(&mut g, cond, 0, select, 0);
+            wire(&mut g, safe, 0, select, 1);
+            wire(&mut g, div, 0, select, 2);
+            if conditional {
+                g.add_region(Region::new(RegionKind::Conditional, vec![zero, div]))
+                    .unwrap();
+            }
+            (g, select, div)
+        };
+
+        let (g, select, div) = build(true);
+        let exec = run(&g).unwrap();
+        assert_eq!(exec.output(&select), Some(&Value::i32(1)));
+        assert_eq!(exec.output(&div), None);
+
+        let (g, _, div) = build(false);
+        assert!(matches!(run(&g), Err(InterpError::DivisionByZero { node }) if node == div));
+    }
+
+    fn guarded_write(holds: &str) -> (Graph, NodeId, NodeId) {
+        let mut g = Graph::new();
+        let read = node(&mut g, NodeKind::Read, vec![], vec![Type::u32()]);
+        let one = lit(&mut g, Type::u32(), "1");
+        let inc = binary(
+            &mut g,
+            NodeKind::Arithmetic(ArithmeticOp::Add),
+            Type::u32(),
+            read,
+            one,
+        );
+        let write = node(&mut g, NodeKind::Write, vec![Type::u32()], vec![]);
+        wire(&mut g, inc, 0, write, 0);
+        let check = lit(&mut g, Type::Bool, holds);
+        let verify = node(&mut g, NodeKind::Verify, vec![Type::Bool], vec![Type::Bool]);
+        wire(&mut g, check, 0, verify, 0);
+        g.add_region(Region::new(
+            RegionKind::Atomic,
+            vec![inc, write, check, verify],
+        ))
+        .unwrap();
+        (g, read, write)
+    }
+
+    #[test]
+    fn atomic_region_commits_writes_on_success() {
+        let (g, read, write) = guarded_write("true");
+        let mut host = ScriptedHost::new().with_input(read, Value::u32(7));
+        Interpreter::new(&g).run(&mut host).unwrap();
+        assert_eq!(host.writes_to(&write), vec![&Value::u32(8)]);
+    }
+
+    #[test]
+    fn atomic_region_discards_writes_on_failure() {
+        let (g, read, _) = guarded_write("false");
+        let mut host = ScriptedHost::new().with_input(read, Value::u32(7));
+        let err = Interpreter::new(&g).run(&mut host).unwrap_err();
+        assert!(matches!(err, InterpError::AssertionFailed { .. }));
+        assert!(host.writes().is_empty());
+    }
+
+    #[test]
+    fn read_values_are_type_checked() {
+        let mut g = Graph::new();
+        let read = node(&mut g, NodeKind::Read, vec![], vec![Type::u32()]);
+        let mut host = ScriptedHost::new().with_input(read, Value::i32(-1));
+        let err = Interpreter::new(&g).run(&mut host).unwrap_err();
+        assert!(matches!(err, InterpError::TypeMismatch { node, .. } if node == read));
+
+        let err = run(&g).unwrap_err();
+        assert!(matches!(err, InterpError::Host { node, .. } if node == read));
+    }
+
+    #[test]
+    fn atomic_nodes_update_cells() {
+        let mut g = Graph::new();
+        let five = lit(&mut g, Type::u32(), "5");
+        let mut fetch_add = Node::new(NodeKind::Atomic(
+            torc_core::graph::node::MemoryOrdering::SeqCst,
+        ))
+        .with_type_signature(TypeSignature::pure_fn(vec![Type::u32()], Type::u32()));
+        fetch_add
+            .annotations
+            .insert("cell".into(), "counter".into());
+        fetch_add.annotations.insert("op".into(), "add".into());
+        let fetch_add = g.add_node(fetch_add).unwrap();
+        wire(&mut g, five, 0, fetch_add, 0);
+
+        let mut host = ScriptedHost::new().with_cell("counter", Value::u32(10));
+        let exec = Interpreter::new(&g).run(&mut host).unwrap();
+        assert_eq!(exec.output(&fetch_add), Some(&Value::u32(10)));
+        assert_eq!(host.cell("counter"), Some(&Value::u32(15)));
+    }
+
+    #[test]
+    fn checkpoints_are_traced() {
+        let mut g = Graph::new();
+        let x = lit(&mut g, Type::i32(), "3");
+        let checkpoint = node(
+            &mut g,
+            NodeKind::Checkpoint,
+            vec![Type::i32()],
+            vec![Type::i32()],
+        );
+        wire(&mut g, x, 0, checkpoint, 0);
+
+        let exec = run(&g).unwrap();
+        assert_eq!(
+            exec.trace,
+            vec![TraceEvent::Checkpoint {
+                node: checkpoint,
+                values: vec![Value::i32(3)],
+            }]
+        );
+    }
+
+    #[test]
+    fn cycle_without_loop_node_is_rejected() {
+        let mut g = Graph::new();
+        let a = node(
+            &mut g,
+            NodeKind::Annotate,
+            vec![Type::i32()],
+            vec![Type::i32()],
+        );
+        let b = node(
+            &mut g,
+            NodeKind::Annotate,
+            vec![Type::i32()],
+            vec![Type::i32()],
+        );
+        wire(&mut g, a, 0, b, 0);
+        wire(&mut g, b, 0, a, 0);
+        assert!(matches!(run(&g), Err(InterpError::Cycle(_))));
+    }
+
+    #[test]
+    fn evaluates_probabilistic_nodes() {
+        let mut g = Graph::new();
+        let dist = Type::Distribution(Box::new(Type::f64()));
+        let (lo, hi) = (
+            lit(&mut g, Type::f64(), "2.0"),
+            lit(&mut g, Type::f64(), "4.0"),
+        );
+        let uniform = node(
+            &mut g,
+            NodeKind::Construct,
+            vec![Type::f64(), Type::f64()],
+            vec![dist.clone()],
+        );
+        g.get_node_mut(&uniform)
+            .unwrap()
+            .annotations
+            .insert(DISTRIBUTION_ANNOTATION.into(), "uniform".into());
+        wire(&mut g, lo, 0, uniform, 0);
+        wire(&mut g, hi, 0, uniform, 1);
+
+        let sample = node(
+            &mut g,
+            NodeKind::Sample,
+            vec![dist.clone()],
+            vec![Type::f64()],
+        );
+        let mean = node(
+            &mut g,
+            NodeKind::Expectation,
+            vec![dist.clone()],
+            vec![Type::f64()],
+        );
+        let entropy = node(
+            &mut g,
+            NodeKind::Entropy,
+            vec![dist.clone()],
+            vec![Type::f64()],
+        );
+        let posterior = Type::Posterior {
+            inner: Box::new(Type::f64()),
+            evidence: "upper half".into(),
+        };
+        let (three, four) = (
+            lit(&mut g, Type::f64(), "3.0"),
+            lit(&mut g, Type::f64(), "4.0"),
+        );
+        let condition = node(
+            &mut g,
+            NodeKind::Condition,
+            vec![dist.clone(), Type::f64(), Type::f64()],
+            vec![posterior.clone()],
+        );
+        let posterior_mean = node(
+            &mut g,
+            NodeKind::Expectation,
+            vec![posterior],
+            vec![Type::f64()],
+        );
+        for n in [sample, mean, entropy, condition] {
+            wire(&mut g, uniform, 0, n, 0);
+        }
+        wire(&mut g, three, 0, condition, 1);
+        wire(&mut g, four, 0, condition, 2);
+        wire(&mut g, condition, 0, posterior_mean, 0);
+        g.validate_probabilistic().unwrap();
+
+        let exec = run(&g).unwrap();
+        let float = |id| match exec.output(&id) {
+            Some(Value::Float(x)) => x.value(),
+            other => panic!("expected a float, got {other:?}"),
+        };
+        assert!((2.0..4.0).contains(&float(sample)));
+        assert!((float(mean) - 3.0).abs() < 0.05);
+        assert!((float(entropy) - 2f64.ln()).abs() < 1e-12);
+        assert!((float(posterior_mean) - 3.5).abs() < 0.05);
+
+        // The same seed draws the same values
+        assert_eq!(run(&g).unwrap().output(&sample), exec.output(&sample));
+
+        // Evidence outside the support cannot be conditioned on
+        g.get_node_mut(&three).unwrap().value = Some(Value::f64(10.0));
+        g.get_node_mut(&four).unwrap().value = Some(Value::f64(11.0));
+        assert!(matches!(
+            run(&g),
+            Err(InterpError::Probabilistic { node, .. }) if node == condition
+        ));
+    }
+}
@@ -0,0 +1,58 @@
+//! Reference interpreter for the Torc language.
+//!
+//! Executes a graph directly, without materializing it, so that its
+//! semantics can be checked against generated code and tests can run
+//! programs on concrete inputs. Effects go through a [`Host`]; the
+//! [`ScriptedHost`] feeds queued inputs to `Read` nodes and records writes.
+//!
+//! # Execution model
+//!
+//! Nodes are scheduled in dependency order (ties broken by node ID) and
+//! evaluated on demand. `Select` and `Switch` evaluate only the chosen
+//! input, and nodes inside a `Conditional` region run only when something
+//! outside demands one of their outputs, at which point the whole region
+//! runs. `Atomic` regions also run as a unit; their writes are released to
+//! the host only after every node in the region succeeds.
+//!
+//! Integer arithmetic wraps at the operand width. Division by zero,
+//! `MIN / -1`, shifts by at least the bit width, out-of-range conversions and
+//! out-of-bounds indexing are errors rather than undefined behavior.
+//! Fixed-point arithmetic and conversions round and overflow as their node's
+//! `"rounding"` and `"overflow"` annotations say (see `torc_core::fixed`).
+//!
+//! Probabilistic nodes follow `torc_core::prob`: distributions are values,
+//! `Sample` and rejection sampling in `Condition` draw from a generator
+//! seeded by `InterpConfig::seed`, and continuous `Expectation`s average
+//! `InterpConfig::samples` draws. A run is therefore reproducible.
+//!
+//! # Loop ports
+//!
+//! Loop nodes take some inputs from the previous iteration ("back" ports);
+//! the nodes between a loop's outputs and its back ports form its body and
+//! are re-evaluated on every iteration.
+//!
+//! | Node       | Inputs                                                   | Outputs            |
+//! |------------|----------------------------------------------------------|--------------------|
+//! | `Iterate`  | 0 initial, 1 next (back), 2 bound, 3 continue (back, opt)| 0 state, 1 index   |
+//! | `Recurse`  | 0 argument, 1 base case (back), 2 next argument (back), 3 metric (back, opt) | 0 argument |
+//! | `Fixpoint` | 0 initial, 1 next (back), 2 converged (back, opt)        | 0 value            |
+//!
+//! `Iterate` without a continue condition runs exactly `bound` times (the
+//! bound may also be given as a `"bound"` annotation); with one, it stops when
+//! the condition is false and fails if it would exceed the bound. `Recurse`
+//! is the tail-recursive form: it stops when the base case holds, and an
+//! optional metric must stay non-negative and strictly decrease. `Fixpoint`
+//! stops when the converged flag holds, or by default when the next value
+//! equals the current one.
+//!
+//! Literals produce the `Value` carried on the node; a literal whose output
+//! type is `Unit` may omit it.
+
+pub mod error;
+pub mod host;
+pub mod interpreter;
+mod ops;
+
+pub use error::InterpError;
+pub use host::{Host, ScriptedHost};
+pub use interpreter::{run, Execution, InterpConfig, Interpreter, TraceEvent};
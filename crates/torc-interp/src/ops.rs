@@ -0,0 +1,701 @@
+//! Primitive operations on values.
+//!
+//! Integer arithmetic wraps at the operand width, as the generated code
+//! does; everything the generated code would leave undefined (division by
+//! zero, `MIN / -1`, oversized shifts, out-of-range conversions) is reported
+//! as a fault instead. Fixed-point operations round and overflow as their
+//! node asks (see `torc_core::fixed`).
+
+use std::cmp::Ordering;
+
+use torc_core::fixed::{self, FixedError, FixedSemantics, Overflow};
+use torc_core::graph::node::{ArithmeticOp, BitwiseOp, ComparisonOp, NodeId};
+use torc_core::types::{Signedness, Type};
+use torc_core::value::{FixedValue, FloatValue, IntValue, Value};
+
+use crate::error::InterpError;
+
+/// A failed primitive operation, not yet attributed to a node.
+#[derive(Debug)]
+pub(crate) enum Fault {
+    DivisionByZero,
+    Overflow,
+    ShiftOutOfRange(String),
+    OutOfRange(String),
+    Type(String),
+}
+
+impl Fault {
+    pub(crate) fn at(self, node: NodeId) -> InterpError {
+        match self {
+            Fault::DivisionByZero => InterpError::DivisionByZero { node },
+            Fault::Overflow => InterpError::Overflow { node },
+            Fault::ShiftOutOfRange(amount) => InterpError::ShiftOutOfRange { node, amount },
+            Fault::OutOfRange(message) => InterpError::ConversionOutOfRange { node, message },
+            Fault::Type(message) => InterpError::TypeMismatch { node, message },
+        }
+    }
+}
+
+type OpResult<T> = Result<T, Fault>;
+
+/// Type-ish description of a value for diagnostics.
+fn describe(v: &Value) -> String {
+    match v {
+        Value::Int(x) => x.ty().to_string(),
+        Value::Float(x) => x.ty().to_string(),
+        Value::Fixed(x) => x.ty().to_string(),
+        other => other.kind_name().to_string(),
+    }
+}
+
+fn mismatch(op: impl std::fmt::Debug, a: &Value, b: &Value) -> Fault {
+    Fault::Type(format!(
+        "cannot apply {op:?} to {} and {}",
+        describe(a),
+        describe(b)
+    ))
+}
+
+fn same_int(a: &IntValue, b: &IntValue) -> bool {
+    a.width() == b.width() && a.signedness() == b.signedness()
+}
+
+fn same_fixed(a: &FixedValue, b: &FixedValue) -> bool {
+    a.total_bits() == b.total_bits() && a.frac_bits() == b.frac_bits()
+}
+
+/// Apply an arithmetic operation; `fixed` gives the rounding and overflow
+/// behavior for fixed-point operands, and its overflow policy also governs
+/// integer operands.
+pub(crate) fn arithmetic(
+    op: ArithmeticOp,
+    a: &Value,
+    b: &Value,
+    fixed: FixedSemantics,
+) -> OpResult<Value> {
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) if same_int(x, y) => {
+            int_arithmetic(op, x, y, fixed.overflow).map(Value::Int)
+        }
+        (Value::Float(x), Value::Float(y)) if x.precision() == y.precision() => {
+            let (l, r) = (x.value(), y.value());
+            let v = match op {
+                ArithmeticOp::Add => l + r,
+                ArithmeticOp::Sub => l - r,
+                ArithmeticOp::Mul => l * r,
+                ArithmeticOp::Div => l / r,
+                ArithmeticOp::Mod => l % r,
+                ArithmeticOp::Pow => l.powf(r),
+            };
+            Ok(Value::Float(FloatValue::new(v, x.precision())))
+        }
+        (Value::Fixed(x), Value::Fixed(y)) if same_fixed(x, y) => {
+            fixed::arithmetic(op, x, y, fixed)
+                .map(Value::Fixed)
+                .map_err(|e| match e {
+                    FixedError::DivisionByZero => Fault::DivisionByZero,
+                    FixedError::Overflow { .. } => Fault::Overflow,
+                    other => Fault::Type(other.to_string()),
+                })
+        }
+        _ => Err(mismatch(op, a, b)),
+    }
+}
+
+/// Where the exact result of an integer operation lies relative to the
+/// operand type.
+enum Exact {
+    Fits(IntValue),
+    Above,
+    Below,
+}
+
+fn int_arithmetic(
+    op: ArithmeticOp,
+    x: &IntValue,
+    y: &IntValue,
+    overflow: Overflow,
+) -> OpResult<IntValue> {
+    let (width, signedness) = (x.width(), x.signedness());
+    let wrap = |bits: u128| IntValue::from_bits(bits, width, signedness);
+    let ones = u128::MAX >> (128 - width);
+    let max = wrap(if x.is_signed() { ones >> 1 } else { ones });
+    let min = wrap(if x.is_signed() { 1 << (width - 1) } else { 0 });
+    if !matches!(overflow, Overflow::Wrap) && !matches!(op, ArithmeticOp::Div | ArithmeticOp::Mod) {
+        if matches!(op, ArithmeticOp::Pow) && y.is_negative() {
+            return Err(Fault::OutOfRange(format!("negative exponent {y}")));
+        }
+        return match (exact(op, x, y), overflow) {
+            (Exact::Fits(v), _) => Ok(v),
+            (Exact::Above, Overflow::Saturate) => Ok(max),
+            (Exact::Below, Overflow::Saturate) => Ok(min),
+            _ => Err(Fault::Overflow),
+        };
+    }
+    match op {
+        ArithmeticOp::Add => Ok(wrap(x.bits().wrapping_add(y.bits()))),
+        ArithmeticOp::Sub => Ok(wrap(x.bits().wrapping_sub(y.bits()))),
+        ArithmeticOp::Mul => Ok(wrap(x.bits().wrapping_mul(y.bits()))),
+        ArithmeticOp::Div | ArithmeticOp::Mod => {
+            if y.bits() == 0 {
+                return Err(Fault::DivisionByZero);
+            }
+            if x.is_signed() {
+                let (l, r) = (x.to_i128().unwrap(), y.to_i128().unwrap());
+                if l == min.to_i128().unwrap() && r == -1 {
+                    // The only signed division that leaves the range.
+                    return match (op, overflow) {
+                        (ArithmeticOp::Div, Overflow::Saturate) => Ok(max),
+                        (ArithmeticOp::Mod, Overflow::Saturate) => Ok(wrap(0)),
+                        _ => Err(Fault::Overflow),
+                    };
+                }
+                let v = if matches!(op, ArithmeticOp::Div) {
+                    l / r
+                } else {
+                    l % r
+                };
+                Ok(IntValue::new(v, width, signedness))
+            } else if matches!(op, ArithmeticOp::Div) {
+                Ok(wrap(x.bits() / y.bits()))
+            } else {
+                Ok(wrap(x.bits() % y.bits()))
+            }
+        }
+        ArithmeticOp::Pow => {
+            if y.is_negative() {
+                return Err(Fault::OutOfRange(format!("negative exponent {y}")));
+            }
+            let (mut base, mut exp, mut acc) = (x.bits(), y.bits(), 1u128);
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    acc = acc.wrapping_mul(base);
+                }
+                base = base.wrapping_mul(base);
+                exp >>= 1;
+            }
+            Ok(wrap(acc))
+        }
+    }
+}
+
+/// Classify the mathematically exact result of `x op y` (for `Add`, `Sub`,
+/// `Mul`, and non-negative `Pow`).
+fn exact(op: ArithmeticOp, x: &IntValue, y: &IntValue) -> Exact {
+    let (width, signedness) = (x.width(), x.signedness());
+    if x.is_signed() {
+        let (l, r) = (x.to_i128().unwrap(), y.to_i128().unwrap());
+        let v = match op {
+            ArithmeticOp::Add => l.checked_add(r),
+            ArithmeticOp::Sub => l.checked_sub(r),
+            ArithmeticOp::Mul => l.checked_mul(r),
+            // Exponents past 128 overflow unless |l| <= 1, where only the
+            // parity matters.
+            _ => l.checked_pow(r.min(128 + (r & 1)) as u32),
+        };
+        let negative = match op {
+            ArithmeticOp::Add => l < 0,
+            ArithmeticOp::Sub => l < 0 && r > 0,
+            ArithmeticOp::Mul => (l < 0) != (r < 0),
+            _ => l < 0 && r & 1 == 1,
+        };
+        match v.map(|v| IntValue::checked(v, width, signedness)) {
+            Some(Some(v)) => Exact::Fits(v),
+            Some(None) if v < Some(0) => Exact::Below,
+            Some(None) => Exact::Above,
+            None if negative => Exact::Below,
+            None => Exact::Above,
+        }
+    } else {
+        let (l, r) = (x.bits(), y.bits());
+        let v = match op {
+            ArithmeticOp::Add => l.checked_add(r),
+            ArithmeticOp::Sub => match l.checked_sub(r) {
+                Some(v) => Some(v),
+                None => return Exact::Below,
+            },
+            ArithmeticOp::Mul => l.checked_mul(r),
+            _ => l.checked_pow(r.min(128) as u32),
+        };
+        match v.and_then(|v| IntValue::checked_unsigned(v, width, signedness)) {
+            Some(v) => Exact::Fits(v),
+            None => Exact::Above,
+        }
+    }
+}
+
+/// Apply a bitwise operation. `b` is `None` only for `Not`.
+pub(crate) fn bitwise(op: BitwiseOp, a: &Value, b: Option<&Value>) -> OpResult<Value> {
+    if let BitwiseOp::Not = op {
+        return match a {
+            Value::Int(x) => Ok(Value::Int(IntValue::from_bits(
+                !x.bits(),
+                x.width(),
+                x.signedness(),
+            ))),
+            Value::Bool(v) => Ok(Value::Bool(!v)),
+            other => Err(Fault::Type(format!(
+                "cannot apply Not to {}",
+                describe(other)
+            ))),
+        };
+    }
+    let b = b.ok_or_else(|| Fault::Type(format!("{op:?} requires two operands")))?;
+    match (op, a, b) {
+        (BitwiseOp::And | BitwiseOp::Or | BitwiseOp::Xor, Value::Bool(x), Value::Bool(y)) => {
+            Ok(Value::Bool(match op {
+                BitwiseOp::And => x & y,
+                BitwiseOp::Or => x | y,
+                _ => x ^ y,
+            }))
+        }
+        (BitwiseOp::And | BitwiseOp::Or | BitwiseOp::Xor, Value::Int(x), Value::Int(y))
+            if same_int(x, y) =>
+        {
+            let bits = match op {
+                BitwiseOp::And => x.bits() & y.bits(),
+                BitwiseOp::Or => x.bits() | y.bits(),
+                _ => x.bits() ^ y.bits(),
+            };
+            Ok(Value::Int(IntValue::from_bits(
+                bits,
+                x.width(),
+                x.signedness(),
+            )))
+        }
+        (
+            BitwiseOp::ShiftLeft | BitwiseOp::ShiftRight | BitwiseOp::Rotate,
+            Value::Int(x),
+            Value::Int(amount),
+        ) => shift(op, x, amount).map(Value::Int),
+        _ => Err(mismatch(op, a, b)),
+    }
+}
+
+fn shift(op: BitwiseOp, x: &IntValue, amount: &IntValue) -> OpResult<IntValue> {
+    let width = x.width() as u128;
+    let rebuild = |bits: u128| IntValue::from_bits(bits, x.width(), x.signedness());
+    if let BitwiseOp::Rotate = op {
+        // Positive amounts rotate left, negative amounts rotate right.
+        let r = match amount.to_i128() {
+            Some(v) => v.rem_euclid(width as i128) as u128,
+            None => amount.bits() % width,
+        };
+        if r == 0 {
+            return Ok(*x);
+        }
+        return Ok(rebuild((x.bits() << r) | (x.bits() >> (width - r))));
+    }
+    let n = match amount.to_u128() {
+        Some(n) if n < width => n as u32,
+        _ => return Err(Fault::ShiftOutOfRange(amount.to_string())),
+    };
+    if let BitwiseOp::ShiftLeft = op {
+        Ok(rebuild(x.bits() << n))
+    } else if x.is_signed() {
+        Ok(IntValue::new(
+            x.to_i128().unwrap() >> n,
+            x.width(),
+            x.signedness(),
+        ))
+    } else {
+        Ok(rebuild(x.bits() >> n))
+    }
+}
+
+/// Compare two values. Float comparisons are ordered: any NaN operand
+/// makes every comparison false, matching the generated code.
+pub(crate) fn comparison(op: ComparisonOp, a: &Value, b: &Value) -> OpResult<bool> {
+    let ordering = match (a, b) {
+        (Value::Int(x), Value::Int(y)) if same_int(x, y) => Some(if x.is_signed() {
+            x.to_i128().cmp(&y.to_i128())
+        } else {
+            x.bits().cmp(&y.bits())
+        }),
+        (Value::Float(x), Value::Float(y)) if x.precision() == y.precision() => {
+            x.value().partial_cmp(&y.value())
+        }
+        (Value::Fixed(x), Value::Fixed(y)) if same_fixed(x, y) => Some(x.raw().cmp(&y.raw())),
+        (Value::Bool(x), Value::Bool(y)) => Some(x.cmp(y)),
+        _ if a.kind_name() == b.kind_name()
+            && matches!(op, ComparisonOp::Eq | ComparisonOp::Ne) =>
+        {
+            return Ok((a == b) == matches!(op, ComparisonOp::Eq));
+        }
+        _ => return Err(mismatch(op, a, b)),
+    };
+    Ok(ordering.is_some_and(|o| match op {
+        ComparisonOp::Eq => o == Ordering::Equal,
+        ComparisonOp::Ne => o != Ordering::Equal,
+        ComparisonOp::Lt => o == Ordering::Less,
+        ComparisonOp::Le => o != Ordering::Greater,
+        ComparisonOp::Gt => o == Ordering::Greater,
+        ComparisonOp::Ge => o != Ordering::Less,
+    }))
+}
+
+fn int_checked(x: &IntValue, width: u8, signedness: Signedness) -> OpResult<IntValue> {
+    let converted = match x.to_i128() {
+        Some(v) if v < 0 => IntValue::checked(v, width, signedness),
+        _ => IntValue::checked_unsigned(x.bits(), width, signedness),
+    };
+    converted.ok_or_else(|| {
+        Fault::OutOfRange(format!(
+            "{x} does not fit in {}",
+            Type::Int { width, signedness }
+        ))
+    })
+}
+
+/// Convert a value to `target`, checking that it is representable.
+///
+/// Conversions to and from fixed-point follow `fixed`; the others always
+/// trap when out of range.
+pub(crate) fn convert(value: &Value, target: &Type, fixed: FixedSemantics) -> OpResult<Value> {
+    let out_of_range =
+        |v: &dyn std::fmt::Display| Fault::OutOfRange(format!("{v} does not fit in {target}"));
+    let fixed_fault = |e: FixedError| Fault::OutOfRange(e.to_string());
+    let converted = match (value, target.base_type()) {
+        (Value::Int(x), Type::Int { width, signedness }) => {
+            Value::Int(int_checked(x, *width, *signedness)?)
+        }
+        (Value::Bool(b), Type::Int { width, signedness }) => {
+            Value::Int(IntValue::from_bits(*b as u128, *width, *signedness))
+        }
+        (Value::Float(x), Type::Int { width, signedness }) => {
+            let t = x.value().trunc();
+            let (lo, hi) = match signedness {
+                Signedness::Signed => (
+                    -(2f64.powi(*width as i32 - 1)),
+                    2f64.powi(*width as i32 - 1),
+                ),
+                Signedness::Unsigned => (0.0, 2f64.powi(*width as i32)),
+            };
+            if !(t >= lo && t < hi) {
+                return Err(out_of_range(&x.value()));
+            }
+            Value::Int(if t < 0.0 {
+                IntValue::new(t as i128, *width, *signedness)
+            } else {
+                IntValue::from_bits(t as u128, *width, *signedness)
+            })
+        }
+        (Value::Fixed(x), Type::Int { width, signedness }) => {
+            Value::Int(fixed::to_int(x, *width, *signedness, fixed).map_err(fixed_fault)?)
+        }
+        (Value::Int(x), Type::Float { precision }) => {
+            Value::Float(FloatValue::new(x.to_f64(), *precision))
+        }
+        (Value::Float(x), Type::Float { precision }) => {
+            Value::Float(FloatValue::new(x.value(), *precision))
+        }
+        (Value::Fixed(x), Type::Float { precision }) => {
+            Value::Float(FloatValue::new(x.to_f64(), *precision))
+        }
+        (Value::Bool(b), Type::Float { precision }) => {
+            Value::Float(FloatValue::new(*b as u8 as f64, *precision))
+        }
+        (
+            Value::Int(x),
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => Value::Fixed(fixed::from_int(x, *total_bits, *frac_bits, fixed).map_err(fixed_fault)?),
+        (
+            Value::Float(x),
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => Value::Fixed(
+            fixed::from_f64(x.value(), *total_bits, *frac_bits, fixed).map_err(fixed_fault)?,
+        ),
+        (
+            Value::Fixed(x),
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => Value::Fixed(fixed::rescale(x, *total_bits, *frac_bits, fixed).map_err(fixed_fault)?),
+        (Value::Int(x), Type::Bool) => Value::Bool(x.bits() != 0),
+        _ if value.conforms_to(target) => value.clone(),
+        _ => {
+            return Err(Fault::Type(format!(
+                "cannot convert {} to {target}",
+                describe(value)
+            )))
+        }
+    };
+    Ok(converted)
+}
+
+/// Parse a scalar of type `ty` written as text, as in the `"bound"`
+/// annotation of `Iterate`.
+///
+/// Integers accept an optional sign and `0x`/`0o`/`0b` prefixes and must fit
+/// the declared width exactly.
+pub(crate) fn parse_literal(text: &str, ty: &Type) -> Result<Value, String> {
+    let text = text.trim();
+    match ty.base_type() {
+        Type::Unit => Ok(Value::Unit),
+        Type::Bool => text
+            .parse()
+            .map(Value::Bool)
+            .map_err(|_| format!("cannot parse bool from \"{text}\"")),
+        Type::Int { width, signedness } => {
+            let (negative, digits) = match text.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, text.strip_prefix('+').unwrap_or(text)),
+            };
+            let (radix, digits) = [
+                ("0x", 16),
+                ("0X", 16),
+                ("0o", 8),
+                ("0O", 8),
+                ("0b", 2),
+                ("0B", 2),
+            ]
+            .iter()
+            .find_map(|(prefix, radix)| digits.strip_prefix(prefix).map(|d| (*radix, d)))
+            .unwrap_or((10, digits));
+            let magnitude = u128::from_str_radix(digits, radix)
+                .map_err(|_| format!("cannot parse int from \"{text}\""))?;
+            let value = if negative {
+                if magnitude > i128::MIN.unsigned_abs() {
+                    None
+                } else {
+                    IntValue::checked((magnitude as i128).wrapping_neg(), *width, *signedness)
+                }
+            } else {
+                IntValue::checked_unsigned(magnitude, *width, *signedness)
+            };
+            value
+                .map(Value::Int)
+                .ok_or_else(|| format!("{text} does not fit in {ty}"))
+        }
+        Type::Float { precision } => text
+            .parse::<f64>()
+            .map(|v| Value::Float(FloatValue::new(v, *precision)))
+            .map_err(|_| format!("cannot parse float from \"{text}\"")),
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => {
+            let v: f64 = text
+                .parse()
+                .map_err(|_| format!("cannot parse fixed-point from \"{text}\""))?;
+            FixedValue::from_f64(v, *total_bits, *frac_bits)
+                .map(Value::Fixed)
+                .ok_or_else(|| format!("{text} does not fit in {ty}"))
+        }
+        _ => Err(format!("literals of type {ty} are not supported")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::types::FloatPrecision;
+
+    fn int(v: i128, w: u8, s: Signedness) -> Value {
+        Value::int(v, w, s)
+    }
+
+    #[test]
+    fn integer_arithmetic_wraps() {
+        let r = arithmetic(
+            ArithmeticOp::Add,
+            &Value::u8(250),
+            &Value::u8(10),
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(r, Value::u8(4));
+        let r = arithmetic(
+            ArithmeticOp::Mul,
+            &Value::i32(i32::MAX),
+            &Value::i32(2),
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(r, Value::i32(-2));
+        let r = arithmetic(
+            ArithmeticOp::Pow,
+            &Value::u8(3),
+            &Value::u8(5),
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(r, Value::u8(243));
+        let seven = int(7, 3, Signedness::Unsigned);
+        let r = arithmetic(
+            ArithmeticOp::Add,
+            &seven,
+            &int(1, 3, Signedness::Unsigned),
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(r, int(0, 3, Signedness::Unsigned));
+    }
+
+    #[test]
+    fn division_faults() {
+        assert!(matches!(
+            arithmetic(
+                ArithmeticOp::Div,
+                &Value::i32(1),
+                &Value::i32(0),
+                FixedSemantics::default()
+            ),
+            Err(Fault::DivisionByZero)
+        ));
+        assert!(matches!(
+            arithmetic(
+                ArithmeticOp::Div,
+                &Value::i32(i32::MIN),
+                &Value::i32(-1),
+                FixedSemantics::default()
+            ),
+            Err(Fault::Overflow)
+        ));
+        let r = arithmetic(
+            ArithmeticOp::Mod,
+            &Value::i32(-7),
+            &Value::i32(2),
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(r, Value::i32(-1));
+    }
+
+    #[test]
+    fn mixed_operands_rejected() {
+        assert!(matches!(
+            arithmetic(
+                ArithmeticOp::Add,
+                &Value::i32(1),
+                &Value::i64(1),
+                FixedSemantics::default()
+            ),
+            Err(Fault::Type(_))
+        ));
+    }
+
+    #[test]
+    fn fixed_point_arithmetic() {
+        let a = Value::Fixed(FixedValue::from_f64(1.5, 16, 8).unwrap());
+        let b = Value::Fixed(FixedValue::from_f64(2.25, 16, 8).unwrap());
+        let Value::Fixed(p) =
+            arithmetic(ArithmeticOp::Mul, &a, &b, FixedSemantics::default()).unwrap()
+        else {
+            panic!("expected fixed");
+        };
+        assert_eq!(p.to_f64(), 3.375);
+        let Value::Fixed(q) =
+            arithmetic(ArithmeticOp::Div, &b, &a, FixedSemantics::default()).unwrap()
+        else {
+            panic!("expected fixed");
+        };
+        assert_eq!(q.to_f64(), 1.5);
+    }
+
+    #[test]
+    fn shifts_and_rotates() {
+        let x = Value::u8(0b1000_0001);
+        assert_eq!(
+            bitwise(BitwiseOp::Rotate, &x, Some(&Value::u8(1))).unwrap(),
+            Value::u8(0b0000_0011)
+        );
+        assert_eq!(
+            bitwise(BitwiseOp::Rotate, &x, Some(&Value::i32(-1))).unwrap(),
+            Value::u8(0b1100_0000)
+        );
+        assert_eq!(
+            bitwise(BitwiseOp::ShiftRight, &Value::i32(-8), Some(&Value::i32(1))).unwrap(),
+            Value::i32(-4)
+        );
+        assert!(matches!(
+            bitwise(BitwiseOp::ShiftLeft, &x, Some(&Value::u8(8))),
+            Err(Fault::ShiftOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(comparison(ComparisonOp::Lt, &Value::i32(-1), &Value::i32(0)).unwrap());
+        assert!(!comparison(ComparisonOp::Lt, &Value::u32(u32::MAX), &Value::u32(0)).unwrap());
+        let nan = Value::f64(f64::NAN);
+        assert!(!comparison(ComparisonOp::Ne, &nan, &nan).unwrap());
+        let t = Value::Tuple(vec![Value::Bool(true)]);
+        assert!(comparison(ComparisonOp::Eq, &t, &t.clone()).unwrap());
+        assert!(comparison(ComparisonOp::Lt, &t, &t).is_err());
+    }
+
+    #[test]
+    fn conversions_are_checked() {
+        assert_eq!(
+            convert(&Value::u32(65535), &Type::u16(), FixedSemantics::default()).unwrap(),
+            Value::int(65535, 16, Signedness::Unsigned)
+        );
+        assert!(convert(&Value::u32(65536), &Type::u16(), FixedSemantics::default()).is_err());
+        assert!(convert(&Value::i32(-1), &Type::u32(), FixedSemantics::default()).is_err());
+        assert_eq!(
+            convert(&Value::f64(-3.9), &Type::i32(), FixedSemantics::default()).unwrap(),
+            Value::i32(-3)
+        );
+        assert!(convert(
+            &Value::f64(f64::NAN),
+            &Type::i32(),
+            FixedSemantics::default()
+        )
+        .is_err());
+        assert_eq!(
+            convert(
+                &Value::i32(3),
+                &Type::Float {
+                    precision: FloatPrecision::F32
+                },
+                FixedSemantics::default()
+            )
+            .unwrap(),
+            Value::f32(3.0)
+        );
+        let fixed = convert(
+            &Value::f64(-2.75),
+            &Type::Fixed {
+                total_bits: 16,
+                frac_bits: 4,
+            },
+            FixedSemantics::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            convert(&fixed, &Type::i32(), FixedSemantics::default()).unwrap(),
+            Value::i32(-2)
+        );
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(
+            parse_literal("0xFF", &Type::u32()).unwrap(),
+            Value::u32(255)
+        );
+        assert_eq!(
+            parse_literal("-128", &Type::i8()).unwrap(),
+            Value::int(-128, 8, Signedness::Signed)
+        );
+        assert!(parse_literal("256", &Type::u8()).is_err());
+        assert!(parse_literal("-1", &Type::u8()).is_err());
+        assert_eq!(parse_literal("2.5", &Type::f64()).unwrap(), Value::f64(2.5));
+        assert!(parse_literal(
+            "1",
+            &Type::Vec {
+                element: Box::new(Type::u8())
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,509 @@
+//! Fixed-point lowering: arithmetic and conversions on scaled integers.
+//!
+//! A `Fixed<total, frac>` value is a `total`-bit integer. Each operation
+//! sign-extends its operands to a width where the exact result fits, rounds
+//! and fits that result as `torc_core::fixed` specifies, and truncates back.
+//! `Trap` results that leave the range call `llvm.trap`; `Proven` results are
+//! truncated like `Wrap`, their range being a discharged obligation. Formats
+//! wider than 64 bits are not lowered.
+
+use inkwell::builder::Builder;
+use inkwell::context::Context;
+use inkwell::types::IntType;
+use inkwell::values::{BasicValueEnum, FloatValue, IntValue};
+use inkwell::{FloatPredicate, IntPredicate};
+
+use torc_core::fixed::{self, FixedSemantics, Overflow, Rounding};
+use torc_core::graph::node::{ArithmeticOp, Node};
+use torc_core::types::{Signedness, Type};
+
+use crate::error::MaterializationError;
+
+use super::context::CodegenContext;
+use super::lower::build_err;
+use super::overflow::trap_if;
+use super::types::to_llvm_type;
+
+/// The widest format lowered; intermediates are up to twice as wide.
+const MAX_TOTAL_BITS: u8 = 64;
+
+type Lowered<T> = Result<T, MaterializationError>;
+
+/// Lower an arithmetic node whose operands are `Fixed<total, frac>`.
+pub(super) fn lower_fixed_arithmetic<'ctx>(
+    node: &Node,
+    ctx: &CodegenContext<'ctx>,
+    op: ArithmeticOp,
+    (lhs, rhs): (BasicValueEnum<'ctx>, BasicValueEnum<'ctx>),
+    (total, frac): (u8, u8),
+    name: &str,
+) -> Lowered<BasicValueEnum<'ctx>> {
+    check_width(node, total)?;
+    let semantics = semantics_of(node)?;
+    let e = Emitter::new(ctx, name);
+    let wide = e.int_type(2 * total as u32);
+    let a = e.extend(lhs.into_int_value(), wide, true)?;
+    let b = e.extend(rhs.into_int_value(), wide, true)?;
+    let exact = match op {
+        ArithmeticOp::Add => {
+            e.b.build_int_add(a, b, name)
+                .map_err(|e| build_err("add", e))?
+        }
+        ArithmeticOp::Sub => {
+            e.b.build_int_sub(a, b, name)
+                .map_err(|e| build_err("sub", e))?
+        }
+        ArithmeticOp::Mul => {
+            let product =
+                e.b.build_int_mul(a, b, name)
+                    .map_err(|e| build_err("mul", e))?;
+            e.round_div(product, e.konst(wide, 1 << frac), semantics.rounding)?
+        }
+        ArithmeticOp::Div => {
+            let shifted =
+                e.b.build_left_shift(a, e.konst(wide, frac as i128), name)
+                    .map_err(|e| build_err("shl", e))?;
+            e.round_div(shifted, b, semantics.rounding)?
+        }
+        ArithmeticOp::Mod => {
+            e.b.build_int_signed_rem(a, b, name)
+                .map_err(|e| build_err("srem", e))?
+        }
+        ArithmeticOp::Pow => {
+            return Err(MaterializationError::CodegenFailed {
+                stage: "lower_arithmetic".into(),
+                message: format!(
+                    "Pow is not defined for fixed-point values (node {})",
+                    node.id
+                ),
+            })
+        }
+    };
+    let narrow = e.int_type(total as u32);
+    Ok(
+        e.fit(exact, fixed::raw_range(total), narrow, semantics.overflow)?
+            .into(),
+    )
+}
+
+/// Lower a conversion to or from a fixed-point type. Returns `None` when
+/// neither side is fixed-point.
+pub(super) fn lower_fixed_conversion<'ctx>(
+    node: &Node,
+    ctx: &CodegenContext<'ctx>,
+    input: BasicValueEnum<'ctx>,
+    (in_ty, out_ty): (&Type, &Type),
+    name: &str,
+) -> Lowered<Option<BasicValueEnum<'ctx>>> {
+    let e = Emitter::new(ctx, name);
+    let result: BasicValueEnum<'ctx> = match (in_ty, out_ty) {
+        (
+            Type::Int { width, signedness },
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => {
+            check_width(node, *total_bits)?;
+            let semantics = semantics_of(node)?;
+            let bits = (*width as u32 + 1).max(*total_bits as u32) + *frac_bits as u32;
+            let wide = e.int_type(bits);
+            let signed = *signedness == Signedness::Signed;
+            let v = e.extend(input.into_int_value(), wide, signed)?;
+            let scaled =
+                e.b.build_left_shift(v, e.konst(wide, *frac_bits as i128), name)
+                    .map_err(|e| build_err("shl", e))?;
+            let narrow = e.int_type(*total_bits as u32);
+            e.fit(
+                scaled,
+                fixed::raw_range(*total_bits),
+                narrow,
+                semantics.overflow,
+            )?
+            .into()
+        }
+        (
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+            Type::Int { width, signedness },
+        ) => {
+            check_width(node, *total_bits)?;
+            let semantics = semantics_of(node)?;
+            let wide = e.int_type((*total_bits).max(*width) as u32 + 1);
+            let v = e.extend(input.into_int_value(), wide, true)?;
+            let whole = e.round_div(v, e.konst(wide, 1 << frac_bits), semantics.rounding)?;
+            let narrow = e.int_type(*width as u32);
+            e.fit(
+                whole,
+                fixed::int_range(*width, *signedness),
+                narrow,
+                semantics.overflow,
+            )?
+            .into()
+        }
+        (
+            Type::Fixed {
+                total_bits: in_total,
+                frac_bits: in_frac,
+            },
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => {
+            check_width(node, (*in_total).max(*total_bits))?;
+            let semantics = semantics_of(node)?;
+            let grow = frac_bits.saturating_sub(*in_frac);
+            let wide = e.int_type((*in_total + grow).max(*total_bits) as u32 + 1);
+            let v = e.extend(input.into_int_value(), wide, true)?;
+            let rescaled = if frac_bits >= in_frac {
+                e.b.build_left_shift(v, e.konst(wide, grow as i128), name)
+                    .map_err(|e| build_err("shl", e))?
+            } else {
+                let scale = e.konst(wide, 1 << (in_frac - frac_bits));
+                e.round_div(v, scale, semantics.rounding)?
+            };
+            let narrow = e.int_type(*total_bits as u32);
+            e.fit(
+                rescaled,
+                fixed::raw_range(*total_bits),
+                narrow,
+                semantics.overflow,
+            )?
+            .into()
+        }
+        (Type::Fixed { frac_bits, .. }, Type::Float { .. }) => {
+            let float_ty = to_llvm_type(out_ty, e.llvm)
+                .expect("float types always lower")
+                .into_float_type();
+            let v =
+                e.b.build_signed_int_to_float(input.into_int_value(), float_ty, name)
+                    .map_err(|e| build_err("sitofp", e))?;
+            let ulp = float_ty.const_float(2f64.powi(-(*frac_bits as i32)));
+            e.b.build_float_mul(v, ulp, name)
+                .map_err(|e| build_err("fmul", e))?
+                .into()
+        }
+        (
+            Type::Float { .. },
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            },
+        ) => {
+            check_width(node, *total_bits)?;
+            let semantics = semantics_of(node)?;
+            let raw = e.float_to_fixed(input.into_float_value(), *frac_bits, semantics)?;
+            let narrow = e.int_type(*total_bits as u32);
+            e.fit(
+                raw,
+                fixed::raw_range(*total_bits),
+                narrow,
+                semantics.overflow,
+            )?
+            .into()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+fn check_width(node: &Node, total_bits: u8) -> Lowered<()> {
+    if total_bits > MAX_TOTAL_BITS {
+        return Err(MaterializationError::CodegenFailed {
+            stage: "lower_fixed".into(),
+            message: format!(
+                "fixed-point formats wider than {MAX_TOTAL_BITS} bits are not supported (node {})",
+                node.id
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn semantics_of(node: &Node) -> Lowered<FixedSemantics> {
+    FixedSemantics::of_node(node).map_err(|e| MaterializationError::CodegenFailed {
+        stage: "lower_fixed".into(),
+        message: format!("node {}: {e}", node.id),
+    })
+}
+
+/// Builds the integer sequences shared by fixed-point operations.
+struct Emitter<'a, 'ctx> {
+    ctx: &'a CodegenContext<'ctx>,
+    b: &'a Builder<'ctx>,
+    llvm: &'ctx Context,
+    name: &'a str,
+}
+
+impl<'a, 'ctx> Emitter<'a, 'ctx> {
+    fn new(ctx: &'a CodegenContext<'ctx>, name: &'a str) -> Self {
+        Self {
+            ctx,
+            b: ctx.builder(),
+            llvm: ctx.llvm_context(),
+            name,
+        }
+    }
+
+    fn int_type(&self, bits: u32) -> IntType<'ctx> {
+        self.llvm.custom_width_int_type(bits)
+    }
+
+    fn konst(&self, ty: IntType<'ctx>, v: i128) -> IntValue<'ctx> {
+        let bits = v as u128;
+        ty.const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64])
+    }
+
+    fn extend(
+        &self,
+        v: IntValue<'ctx>,
+        ty: IntType<'ctx>,
+        signed: bool,
+    ) -> Lowered<IntValue<'ctx>> {
+        if v.get_type().get_bit_width() == ty.get_bit_width() {
+            return Ok(v);
+        }
+        if signed {
+            self.b.build_int_s_extend(v, ty, self.name)
+        } else {
+            self.b.build_int_z_extend(v, ty, self.name)
+        }
+        .map_err(|e| build_err("extend", e))
+    }
+
+    fn icmp(
+        &self,
+        pred: IntPredicate,
+        a: IntValue<'ctx>,
+        b: IntValue<'ctx>,
+    ) -> Lowered<IntValue<'ctx>> {
+        self.b
+            .build_int_compare(pred, a, b, self.name)
+            .map_err(|e| build_err("icmp", e))
+    }
+
+    fn select(
+        &self,
+        cond: IntValue<'ctx>,
+        a: IntValue<'ctx>,
+        b: IntValue<'ctx>,
+    ) -> Lowered<IntValue<'ctx>> {
+        Ok(self
+            .b
+            .build_select(cond, a, b, self.name)
+            .map_err(|e| build_err("select", e))?
+            .into_int_value())
+    }
+
+    fn and(&self, a: IntValue<'ctx>, b: IntValue<'ctx>) -> Lowered<IntValue<'ctx>> {
+        self.b
+            .build_and(a, b, self.name)
+            .map_err(|e| build_err("and", e))
+    }
+
+    fn abs(&self, v: IntValue<'ctx>, negative: IntValue<'ctx>) -> Lowered<IntValue<'ctx>> {
+        let neg = self
+            .b
+            .build_int_neg(v, self.name)
+            .map_err(|e| build_err("neg", e))?;
+        self.select(negative, neg, v)
+    }
+
+    /// Signed `n / d` rounded as `rounding` says; mirrors
+    /// `torc_core::fixed::round_div`.
+    fn round_div(
+        &self,
+        n: IntValue<'ctx>,
+        d: IntValue<'ctx>,
+        rounding: Rounding,
+    ) -> Lowered<IntValue<'ctx>> {
+        let ty = n.get_type();
+        let q = self
+            .b
+            .build_int_signed_div(n, d, self.name)
+            .map_err(|e| build_err("sdiv", e))?;
+        if rounding == Rounding::TowardZero {
+            return Ok(q);
+        }
+        let r = self
+            .b
+            .build_int_signed_rem(n, d, self.name)
+            .map_err(|e| build_err("srem", e))?;
+        let zero = ty.const_zero();
+        let inexact = self.icmp(IntPredicate::NE, r, zero)?;
+        let r_negative = self.icmp(IntPredicate::SLT, r, zero)?;
+        let d_negative = self.icmp(IntPredicate::SLT, d, zero)?;
+        // The exact quotient lies between q and q - 1 when this holds,
+        // between q and q + 1 otherwise.
+        let negative = self
+            .b
+            .build_xor(r_negative, d_negative, self.name)
+            .map_err(|e| build_err("xor", e))?;
+        let away = match rounding {
+            Rounding::TowardZero => unreachable!(),
+            Rounding::Floor => negative,
+            Rounding::Ceiling => self
+                .b
+                .build_not(negative, self.name)
+                .map_err(|e| build_err("not", e))?,
+            Rounding::NearestEven | Rounding::NearestAway => {
+                let abs_r = self.abs(r, r_negative)?;
+                let abs_d = self.abs(d, d_negative)?;
+                let twice = self
+                    .b
+                    .build_int_add(abs_r, abs_r, self.name)
+                    .map_err(|e| build_err("add", e))?;
+                let above = self.icmp(IntPredicate::UGT, twice, abs_d)?;
+                let mut tie = self.icmp(IntPredicate::EQ, twice, abs_d)?;
+                if rounding == Rounding::NearestEven {
+                    let odd = self
+                        .b
+                        .build_int_truncate(q, self.llvm.bool_type(), self.name)
+                        .map_err(|e| build_err("trunc", e))?;
+                    tie = self.and(tie, odd)?;
+                }
+                self.b
+                    .build_or(above, tie, self.name)
+                    .map_err(|e| build_err("or", e))?
+            }
+        };
+        let adjust = self.and(inexact, away)?;
+        let step = self.select(negative, self.konst(ty, -1), self.konst(ty, 1))?;
+        let stepped = self
+            .b
+            .build_int_add(q, step, self.name)
+            .map_err(|e| build_err("add", e))?;
+        self.select(adjust, stepped, q)
+    }
+
+    /// Fit an exact result into `[lo, hi]` and truncate it to `out`.
+    fn fit(
+        &self,
+        v: IntValue<'ctx>,
+        (lo, hi): (i128, i128),
+        out: IntType<'ctx>,
+        overflow: Overflow,
+    ) -> Lowered<IntValue<'ctx>> {
+        let ty = v.get_type();
+        let (lo, hi) = (self.konst(ty, lo), self.konst(ty, hi));
+        let v = match overflow {
+            Overflow::Saturate => {
+                let below = self.icmp(IntPredicate::SLT, v, lo)?;
+                let v = self.select(below, lo, v)?;
+                let above = self.icmp(IntPredicate::SGT, v, hi)?;
+                self.select(above, hi, v)?
+            }
+            Overflow::Trap => {
+                let below = self.icmp(IntPredicate::SLT, v, lo)?;
+                let above = self.icmp(IntPredicate::SGT, v, hi)?;
+                let outside = self
+                    .b
+                    .build_or(below, above, self.name)
+                    .map_err(|e| build_err("or", e))?;
+                trap_if(self.ctx, outside, self.name)?;
+                v
+            }
+            Overflow::Wrap | Overflow::Proven => v,
+        };
+        if v.get_type().get_bit_width() == out.get_bit_width() {
+            return Ok(v);
+        }
+        self.b
+            .build_int_truncate(v, out, self.name)
+            .map_err(|e| build_err("trunc", e))
+    }
+
+    /// Scale a float by `2^frac` and round it to a 128-bit integer.
+    ///
+    /// The scaled value is clamped to `±2^126` first so the conversion is
+    /// defined; NaN becomes zero.
+    fn float_to_fixed(
+        &self,
+        x: FloatValue<'ctx>,
+        frac: u8,
+        semantics: FixedSemantics,
+    ) -> Lowered<IntValue<'ctx>> {
+        let fty = x.get_type();
+        let fcmp = |pred: FloatPredicate, a: FloatValue<'ctx>, b: FloatValue<'ctx>| {
+            self.b
+                .build_float_compare(pred, a, b, self.name)
+                .map_err(|e| build_err("fcmp", e))
+        };
+        let fselect = |cond: IntValue<'ctx>,
+                       a: FloatValue<'ctx>,
+                       b: FloatValue<'ctx>|
+         -> Lowered<FloatValue<'ctx>> {
+            Ok(self
+                .b
+                .build_select(cond, a, b, self.name)
+                .map_err(|e| build_err("select", e))?
+                .into_float_value())
+        };
+
+        let scaled = self
+            .b
+            .build_float_mul(x, fty.const_float(2f64.powi(frac as i32)), self.name)
+            .map_err(|e| build_err("fmul", e))?;
+        let (lo, hi) = (
+            fty.const_float(-(2f64.powi(126))),
+            fty.const_float(2f64.powi(126)),
+        );
+        let scaled = fselect(fcmp(FloatPredicate::OLT, scaled, lo)?, lo, scaled)?;
+        let scaled = fselect(fcmp(FloatPredicate::OGT, scaled, hi)?, hi, scaled)?;
+        let nan = fcmp(FloatPredicate::UNO, scaled, scaled)?;
+        let scaled = fselect(nan, fty.const_zero(), scaled)?;
+
+        let wide = self.int_type(128);
+        let q = self
+            .b
+            .build_float_to_signed_int(scaled, wide, self.name)
+            .map_err(|e| build_err("fptosi", e))?;
+        if semantics.rounding == Rounding::TowardZero {
+            return Ok(q);
+        }
+        let qf = self
+            .b
+            .build_signed_int_to_float(q, fty, self.name)
+            .map_err(|e| build_err("sitofp", e))?;
+        let diff = self
+            .b
+            .build_float_sub(scaled, qf, self.name)
+            .map_err(|e| build_err("fsub", e))?;
+        let zero = fty.const_zero();
+        let half = fty.const_float(0.5);
+        let negative = fcmp(FloatPredicate::OLT, diff, zero)?;
+        let away = match semantics.rounding {
+            Rounding::TowardZero => unreachable!(),
+            Rounding::Floor => negative,
+            Rounding::Ceiling => fcmp(FloatPredicate::OGT, diff, zero)?,
+            Rounding::NearestEven | Rounding::NearestAway => {
+                let neg_diff = self
+                    .b
+                    .build_float_neg(diff, self.name)
+                    .map_err(|e| build_err("fneg", e))?;
+                let magnitude = fselect(negative, neg_diff, diff)?;
+                let above = fcmp(FloatPredicate::OGT, magnitude, half)?;
+                let mut tie = fcmp(FloatPredicate::OEQ, magnitude, half)?;
+                if semantics.rounding == Rounding::NearestEven {
+                    let odd = self
+                        .b
+                        .build_int_truncate(q, self.llvm.bool_type(), self.name)
+                        .map_err(|e| build_err("trunc", e))?;
+                    tie = self.and(tie, odd)?;
+                }
+                self.b
+                    .build_or(above, tie, self.name)
+                    .map_err(|e| build_err("or", e))?
+            }
+        };
+        let step = self.select(negative, self.konst(wide, -1), self.konst(wide, 1))?;
+        let stepped = self
+            .b
+            .build_int_add(q, step, self.name)
+            .map_err(|e| build_err("add", e))?;
+        self.select(away, stepped, q)
+    }
+}
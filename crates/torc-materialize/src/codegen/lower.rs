@@ -1,15 +1,21 @@
 //! Node lowering: translate Torc NodeKind operations into LLVM instructions.
 
-use inkwell::values::BasicValueEnum;
+use inkwell::context::Context;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{ArrayValue, BasicValueEnum};
 use inkwell::IntPredicate;
 
 use torc_core::graph::node::{ArithmeticOp, BitwiseOp, ComparisonOp, Node, NodeKind};
 use torc_core::graph::Graph;
 use torc_core::types::{Signedness, Type};
+use torc_core::value::Value;
 
 use crate::error::MaterializationError;
 
 use super::context::CodegenContext;
+use super::fixed::{lower_fixed_arithmetic, lower_fixed_conversion};
+use super::overflow::lower_int_overflow;
+use super::types::to_llvm_type;
 
 /// Lower a single node into LLVM instructions.
 ///
@@ -30,6 +36,7 @@ pub fn lower_node<'ctx>(
         NodeKind::Comparison(op) => lower_comparison(node, graph, ctx, *op, &node_name),
         NodeKind::Select => lower_select(node, graph, ctx, &node_name),
         NodeKind::Conversion => lower_conversion(node, graph, ctx, &node_name),
+        NodeKind::Approximate => lower_approximate(node, graph, ctx),
         other => Err(MaterializationError::CodegenFailed {
             stage: "lower".into(),
             message: format!("unsupported node kind for codegen: {other}"),
@@ -114,65 +121,157 @@ fn lower_literal<'ctx>(
         message: format!("literal node {} has no output type", node.id),
     })?;
 
-    let value_str =
-        node.annotations
-            .get("value")
-            .ok_or_else(|| MaterializationError::CodegenFailed {
-                stage: "lower_literal".into(),
-                message: format!("literal node {} has no \"value\" annotation", node.id),
-            })?;
+    let value = node
+        .value
+        .as_ref()
+        .ok_or_else(|| MaterializationError::CodegenFailed {
+            stage: "lower_literal".into(),
+            message: format!("literal node {} has no value", node.id),
+        })?;
 
-    let base_ty = out_ty.base_type();
-    let llvm_val: BasicValueEnum<'ctx> = match base_ty {
-        Type::Bool => {
-            let v: bool = value_str
-                .parse()
-                .map_err(|_| MaterializationError::CodegenFailed {
-                    stage: "lower_literal".into(),
-                    message: format!("cannot parse bool from \"{value_str}\""),
-                })?;
-            ctx.llvm_context()
-                .bool_type()
-                .const_int(v as u64, false)
+    let llvm_val = const_value(value, out_ty, ctx.llvm_context())?;
+    ctx.set_value(node.id, 0, llvm_val);
+    Ok(())
+}
+
+/// Build an LLVM constant for `value`, laid out as `to_llvm_type(ty)`.
+fn const_value<'ctx>(
+    value: &Value,
+    ty: &Type,
+    context: &'ctx Context,
+) -> Result<BasicValueEnum<'ctx>, MaterializationError> {
+    let unsupported = || MaterializationError::CodegenFailed {
+        stage: "lower_literal".into(),
+        message: format!("cannot lower constant {value} of type {ty}"),
+    };
+    let base_ty = ty.base_type();
+    let llvm_ty = to_llvm_type(base_ty, context).ok_or_else(unsupported)?;
+
+    let llvm_val: BasicValueEnum<'ctx> = match (value, base_ty) {
+        (Value::Unit, Type::Unit) => context.const_struct(&[], false).into(),
+        (Value::Bool(b), Type::Bool) => context.bool_type().const_int(*b as u64, false).into(),
+        (Value::Int(v), Type::Int { .. }) => llvm_ty
+            .into_int_type()
+            .const_int_arbitrary_precision(&[v.bits() as u64, (v.bits() >> 64) as u64])
+            .into(),
+        (Value::Float(v), Type::Float { .. }) => {
+            llvm_ty.into_float_type().const_float(v.value()).into()
+        }
+        (Value::Fixed(v), Type::Fixed { .. }) => {
+            let bits = v.raw() as u128;
+            llvm_ty
+                .into_int_type()
+                .const_int_arbitrary_precision(&[bits as u64, (bits >> 64) as u64])
                 .into()
         }
-        Type::Int { width, signedness } => {
-            let v: i128 =
-                parse_int_literal(value_str).map_err(|_| MaterializationError::CodegenFailed {
-                    stage: "lower_literal".into(),
-                    message: format!("cannot parse int from \"{value_str}\""),
-                })?;
-            let sign_extend = *signedness == Signedness::Signed;
-            ctx.llvm_context()
-                .custom_width_int_type(*width as u32)
-                .const_int(v as u64, sign_extend)
+        (Value::Tuple(elems), Type::Tuple(tys)) if elems.len() == tys.len() => {
+            let fields = elems
+                .iter()
+                .zip(tys)
+                .map(|(v, t)| const_value(v, t, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            llvm_ty
+                .into_struct_type()
+                .const_named_struct(&fields)
                 .into()
         }
-        Type::Float { precision } => {
-            let v: f64 = value_str
-                .parse()
-                .map_err(|_| MaterializationError::CodegenFailed {
-                    stage: "lower_literal".into(),
-                    message: format!("cannot parse float from \"{value_str}\""),
-                })?;
-            let float_ty = match precision {
-                torc_core::types::FloatPrecision::F16 => ctx.llvm_context().f16_type(),
-                torc_core::types::FloatPrecision::F32 => ctx.llvm_context().f32_type(),
-                torc_core::types::FloatPrecision::F64 => ctx.llvm_context().f64_type(),
-                torc_core::types::FloatPrecision::F128 => ctx.llvm_context().f128_type(),
+        (Value::Record(fields), Type::Record(tys)) if fields.len() == tys.len() => {
+            // Sorted field order, matching `to_llvm_type`
+            let fields = tys
+                .iter()
+                .map(|(name, t)| {
+                    let v = fields.get(name).ok_or_else(unsupported)?;
+                    const_value(v, t, context)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            llvm_ty
+                .into_struct_type()
+                .const_named_struct(&fields)
+                .into()
+        }
+        (Value::Array(elems), Type::Array { element, length }) if elems.len() == *length => {
+            let elems = elems
+                .iter()
+                .map(|v| const_value(v, element, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            let elem_ty = llvm_ty.into_array_type().get_element_type();
+            const_array(elem_ty, &elems).ok_or_else(unsupported)?.into()
+        }
+        (Value::Option(inner), Type::Option(inner_ty)) => {
+            let payload = match inner {
+                Some(v) => const_value(v, inner_ty, context)?,
+                None => to_llvm_type(inner_ty, context)
+                    .ok_or_else(unsupported)?
+                    .const_zero(),
             };
-            float_ty.const_float(v).into()
+            let present = context.bool_type().const_int(inner.is_some() as u64, false);
+            llvm_ty
+                .into_struct_type()
+                .const_named_struct(&[present.into(), payload])
+                .into()
         }
-        _ => {
-            return Err(MaterializationError::CodegenFailed {
-                stage: "lower_literal".into(),
-                message: format!("unsupported literal type: {out_ty}"),
-            });
+        (
+            Value::Variant {
+                tag,
+                value: payload,
+            },
+            Type::Variant(cases),
+        ) => {
+            // { tag, widest payload }: only payloads of the widest case's
+            // layout (or payload-free cases) can be written as a constant.
+            let index = cases
+                .keys()
+                .position(|k| k == tag)
+                .ok_or_else(unsupported)?;
+            let struct_ty = llvm_ty.into_struct_type();
+            let tag_val = struct_ty
+                .get_field_type_at_index(0)
+                .ok_or_else(unsupported)?
+                .into_int_type()
+                .const_int(index as u64, false);
+            let mut fields = vec![tag_val.into()];
+            if let Some(payload_ty) = struct_ty.get_field_type_at_index(1) {
+                let case_ty = &cases[tag];
+                let payload = if to_llvm_type(case_ty, context) == Some(payload_ty) {
+                    const_value(payload, case_ty, context)?
+                } else if matches!(case_ty.base_type(), Type::Unit | Type::Void) {
+                    payload_ty.const_zero()
+                } else {
+                    return Err(unsupported());
+                };
+                fields.push(payload);
+            }
+            struct_ty.const_named_struct(&fields).into()
         }
+        _ => return Err(unsupported()),
     };
+    Ok(llvm_val)
+}
 
-    ctx.set_value(node.id, 0, llvm_val);
-    Ok(())
+/// Build a constant array from already-lowered element constants.
+fn const_array<'ctx>(
+    elem_ty: BasicTypeEnum<'ctx>,
+    elems: &[BasicValueEnum<'ctx>],
+) -> Option<ArrayValue<'ctx>> {
+    Some(match elem_ty {
+        BasicTypeEnum::IntType(t) => {
+            let vals: Vec<_> = elems.iter().map(|v| v.into_int_value()).collect();
+            t.const_array(&vals)
+        }
+        BasicTypeEnum::FloatType(t) => {
+            let vals: Vec<_> = elems.iter().map(|v| v.into_float_value()).collect();
+            t.const_array(&vals)
+        }
+        BasicTypeEnum::StructType(t) => {
+            let vals: Vec<_> = elems.iter().map(|v| v.into_struct_value()).collect();
+            t.const_array(&vals)
+        }
+        BasicTypeEnum::ArrayType(t) => {
+            let vals: Vec<_> = elems.iter().map(|v| v.into_array_value()).collect();
+            t.const_array(&vals)
+        }
+        _ => return None,
+    })
 }
 
 fn lower_arithmetic<'ctx>(
@@ -187,8 +286,27 @@ fn lower_arithmetic<'ctx>(
         stage: "lower_arithmetic".into(),
         message: format!("arithmetic node {} has no output type", node.id),
     })?;
+    if let Type::Fixed {
+        total_bits,
+        frac_bits,
+    } = out_ty.base_type()
+    {
+        let operands = two_inputs(&inputs, node)?;
+        let result =
+            lower_fixed_arithmetic(node, ctx, op, operands, (*total_bits, *frac_bits), name)?;
+        ctx.set_value(node.id, 0, result);
+        return Ok(());
+    }
     let float = is_float_type(out_ty);
     let signed = is_signed_int(out_ty);
+    if !float {
+        let (lhs, rhs) = two_inputs(&inputs, node)?;
+        let operands = (lhs.into_int_value(), rhs.into_int_value());
+        if let Some(result) = lower_int_overflow(node, ctx, op, operands, signed, name)? {
+            ctx.set_value(node.id, 0, result.into());
+            return Ok(());
+        }
+    }
 
     let result = match op {
         ArithmeticOp::Add => {
@@ -385,7 +503,9 @@ fn lower_comparison<'ctx>(
             .map_err(|e| build_err("fcmp", e))?
             .into()
     } else {
-        let signed = in_ty.is_none_or(is_signed_int);
+        // Fixed-point values compare as their signed raw integers
+        let signed = in_ty
+            .is_none_or(|ty| is_signed_int(ty) || matches!(ty.base_type(), Type::Fixed { .. }));
         let pred = match (op, signed) {
             (ComparisonOp::Eq, _) => IntPredicate::EQ,
             (ComparisonOp::Ne, _) => IntPredicate::NE,
@@ -408,6 +528,27 @@ fn lower_comparison<'ctx>(
     Ok(())
 }
 
+/// `Approximate` only bounds the error of its value, which passes through.
+fn lower_approximate<'ctx>(
+    node: &Node,
+    graph: &Graph,
+    ctx: &mut CodegenContext<'ctx>,
+) -> Result<(), MaterializationError> {
+    let inputs = collect_inputs(node, graph, ctx)?;
+    let [value] = inputs[..] else {
+        return Err(MaterializationError::CodegenFailed {
+            stage: "lower_approximate".into(),
+            message: format!(
+                "approximate node {} requires 1 input, got {}",
+                node.id,
+                inputs.len()
+            ),
+        });
+    };
+    ctx.set_value(node.id, 0, value);
+    Ok(())
+}
+
 fn lower_select<'ctx>(
     node: &Node,
     graph: &Graph,
@@ -460,6 +601,10 @@ fn lower_conversion<'ctx>(
     let in_base = in_ty.base_type();
     let out_base = out_ty.base_type();
 
+    if let Some(result) = lower_fixed_conversion(node, ctx, input, (in_base, out_base), name)? {
+        ctx.set_value(node.id, 0, result);
+        return Ok(());
+    }
     let result: BasicValueEnum<'ctx> = match (in_base, out_base) {
         // Int -> Int (widening, narrowing, sign change)
         (
@@ -586,21 +731,7 @@ fn one_input<'ctx>(
     Ok(inputs[0])
 }
 
-/// Parse an integer literal, supporting decimal, hex (0x), octal (0o), and binary (0b).
-fn parse_int_literal(s: &str) -> Result<i128, std::num::ParseIntError> {
-    let s = s.trim();
-    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
-        i128::from_str_radix(hex, 16)
-    } else if let Some(oct) = s.strip_prefix("0o").or_else(|| s.strip_prefix("0O")) {
-        i128::from_str_radix(oct, 8)
-    } else if let Some(bin) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
-        i128::from_str_radix(bin, 2)
-    } else {
-        s.parse()
-    }
-}
-
-fn build_err(op: &str, e: inkwell::builder::BuilderError) -> MaterializationError {
+pub(super) fn build_err(op: &str, e: inkwell::builder::BuilderError) -> MaterializationError {
     MaterializationError::CodegenFailed {
         stage: "lower".into(),
         message: format!("LLVM builder error in {op}: {e}"),
@@ -614,6 +745,7 @@ mod tests {
     use torc_core::graph::edge::Edge;
     use torc_core::graph::node::Node;
     use torc_core::types::{Type, TypeSignature};
+    use torc_core::value::Value;
 
     use super::super::context::CodegenContext;
 
@@ -634,7 +766,7 @@ mod tests {
 
         let mut node =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        node.annotations.insert("value".into(), "42".into());
+        node.value = Some(Value::i32(42));
 
         let graph = Graph::new();
         lower_node(&node, &graph, &mut cg).unwrap();
@@ -650,7 +782,7 @@ mod tests {
 
         let mut node =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::f64()));
-        node.annotations.insert("value".into(), "3.14".into());
+        node.value = Some(Value::f64(2.75));
 
         let graph = Graph::new();
         lower_node(&node, &graph, &mut cg).unwrap();
@@ -668,10 +800,10 @@ mod tests {
         // Two literal inputs
         let mut lit1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit1.annotations.insert("value".into(), "10".into());
+        lit1.value = Some(Value::i32(10));
         let mut lit2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit2.annotations.insert("value".into(), "20".into());
+        lit2.value = Some(Value::i32(20));
         let add_node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
             TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
         );
@@ -705,10 +837,10 @@ mod tests {
 
         let mut lit1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit1.annotations.insert("value".into(), "5".into());
+        lit1.value = Some(Value::i32(5));
         let mut lit2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit2.annotations.insert("value".into(), "5".into());
+        lit2.value = Some(Value::i32(5));
         let cmp = Node::new(NodeKind::Comparison(ComparisonOp::Eq)).with_type_signature(
             TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::Bool]),
         );
@@ -739,10 +871,10 @@ mod tests {
 
         let mut lit1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::u32()));
-        lit1.annotations.insert("value".into(), "0xFF".into());
+        lit1.value = Some(Value::u32(0xFF));
         let mut lit2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::u32()));
-        lit2.annotations.insert("value".into(), "0x0F".into());
+        lit2.value = Some(Value::u32(0x0F));
         let and_node = Node::new(NodeKind::Bitwise(BitwiseOp::And)).with_type_signature(
             TypeSignature::new(vec![Type::u32(), Type::u32()], vec![Type::u32()]),
         );
@@ -772,13 +904,13 @@ mod tests {
 
         let mut cond =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::Bool));
-        cond.annotations.insert("value".into(), "true".into());
+        cond.value = Some(Value::Bool(true));
         let mut true_val =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        true_val.annotations.insert("value".into(), "1".into());
+        true_val.value = Some(Value::i32(1));
         let mut false_val =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        false_val.annotations.insert("value".into(), "0".into());
+        false_val.value = Some(Value::i32(0));
         let sel = Node::new(NodeKind::Select).with_type_signature(TypeSignature::new(
             vec![Type::Bool, Type::i32(), Type::i32()],
             vec![Type::i32()],
@@ -814,10 +946,10 @@ mod tests {
 
         let mut lit1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::f64()));
-        lit1.annotations.insert("value".into(), "1.5".into());
+        lit1.value = Some(Value::f64(1.5));
         let mut lit2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::f64()));
-        lit2.annotations.insert("value".into(), "2.5".into());
+        lit2.value = Some(Value::f64(2.5));
         let add_node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
             TypeSignature::new(vec![Type::f64(), Type::f64()], vec![Type::f64()]),
         );
@@ -840,6 +972,93 @@ mod tests {
         assert!(result.is_float_value());
     }
 
+    #[test]
+    fn lower_fixed_point_as_scaled_integers() {
+        let context = Context::create();
+        let mut cg = setup_ctx(&context);
+        let mut graph = Graph::new();
+        let q15 = Type::Fixed {
+            total_bits: 16,
+            frac_bits: 15,
+        };
+
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(q15.clone()))
+            .with_value(Value::Fixed(
+                torc_core::value::FixedValue::from_f64(0.75, 16, 15).unwrap(),
+            ));
+        let binary = |op, overflow: &str| {
+            let mut node = Node::new(NodeKind::Arithmetic(op)).with_type_signature(
+                TypeSignature::new(vec![q15.clone(), q15.clone()], vec![q15.clone()]),
+            );
+            node.annotations.insert("overflow".into(), overflow.into());
+            node
+        };
+        let lit_id = graph.add_node(lit).unwrap();
+        let mul_id = graph.add_node(binary(ArithmeticOp::Mul, "wrap")).unwrap();
+        let add_id = graph
+            .add_node(binary(ArithmeticOp::Add, "saturate"))
+            .unwrap();
+        for id in [mul_id, add_id] {
+            for port in 0..2 {
+                graph
+                    .add_edge(Edge::typed((lit_id, 0), (id, port), q15.clone()))
+                    .unwrap();
+            }
+        }
+
+        for id in [lit_id, mul_id, add_id] {
+            lower_node(graph.get_node(&id).unwrap(), &graph, &mut cg).unwrap();
+        }
+
+        let raw = |id| {
+            let v = cg.get_value(&id, 0).unwrap().into_int_value();
+            assert_eq!(v.get_type().get_bit_width(), 16);
+            v.get_sign_extended_constant().unwrap()
+        };
+        // 0.75 * 0.75 = 0.5625; 0.75 + 0.75 saturates just below 1.0
+        assert_eq!(raw(mul_id), 18432);
+        assert_eq!(raw(add_id), 32767);
+    }
+
+    #[test]
+    fn lower_int_overflow_policies_with_intrinsics() {
+        let context = Context::create();
+        let mut cg = setup_ctx(&context);
+        let mut graph = Graph::new();
+
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i8()))
+            .with_value(Value::int(100, 8, Signedness::Signed));
+        let lit_id = graph.add_node(lit).unwrap();
+        let mut ids = vec![lit_id];
+        for overflow in ["saturate", "trap", "proven"] {
+            let mut node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
+                TypeSignature::new(vec![Type::i8(), Type::i8()], vec![Type::i8()]),
+            );
+            node.annotations.insert("overflow".into(), overflow.into());
+            let id = graph.add_node(node).unwrap();
+            for port in 0..2 {
+                graph
+                    .add_edge(Edge::typed((lit_id, 0), (id, port), Type::i8()))
+                    .unwrap();
+            }
+            ids.push(id);
+        }
+
+        for id in &ids {
+            lower_node(graph.get_node(id).unwrap(), &graph, &mut cg).unwrap();
+        }
+
+        let module = cg.module();
+        assert!(module.get_function("llvm.sadd.sat.i8").is_some());
+        assert!(module.get_function("llvm.sadd.with.overflow.i8").is_some());
+        assert!(module.get_function("llvm.trap").is_some());
+        // entry, then the trap and continuation blocks of the checked add
+        let function = module.get_function("test_fn").unwrap();
+        assert_eq!(function.count_basic_blocks(), 3);
+    }
+
     #[test]
     fn lower_conversion_i32_to_f64() {
         let context = Context::create();
@@ -848,7 +1067,7 @@ mod tests {
 
         let mut lit =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit.annotations.insert("value".into(), "42".into());
+        lit.value = Some(Value::i32(42));
         let conv = Node::new(NodeKind::Conversion)
             .with_type_signature(TypeSignature::new(vec![Type::i32()], vec![Type::f64()]));
 
@@ -873,7 +1092,7 @@ mod tests {
 
         let mut lit =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::f64()));
-        lit.annotations.insert("value".into(), "3.14".into());
+        lit.value = Some(Value::f64(2.75));
         let conv = Node::new(NodeKind::Conversion)
             .with_type_signature(TypeSignature::new(vec![Type::f64()], vec![Type::i32()]));
 
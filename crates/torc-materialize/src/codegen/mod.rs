@@ -7,7 +7,9 @@
 
 mod context;
 mod emit;
+mod fixed;
 mod lower;
+mod overflow;
 pub mod profile;
 mod types;
 
@@ -99,6 +101,9 @@ pub fn emit_code(
     platform: &Platform,
     config: &CodegenConfig,
 ) -> Result<CodegenOutput, MaterializationError> {
+    // Lowering works on structural types only
+    let graph = &graph.monomorphize()?;
+
     let context = Context::create();
     let mut cg_ctx = CodegenContext::new(&context, &config.function_name);
 
@@ -324,15 +329,16 @@ mod tests {
     use torc_core::graph::edge::Edge;
     use torc_core::graph::node::{ArithmeticOp, Node, NodeKind};
     use torc_core::types::{Type, TypeSignature};
+    use torc_core::value::Value;
 
     fn simple_arithmetic_graph() -> Graph {
         let mut g = Graph::new();
         let mut lit1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit1.annotations.insert("value".into(), "10".into());
+        lit1.value = Some(Value::i32(10));
         let mut lit2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit2.annotations.insert("value".into(), "32".into());
+        lit2.value = Some(Value::i32(32));
         let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
             TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
         );
@@ -400,7 +406,7 @@ mod tests {
         let mut g = Graph::new();
         let mut lit =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
-        lit.annotations.insert("value".into(), "42".into());
+        lit.value = Some(Value::i32(42));
         g.add_node(lit).unwrap();
 
         let platform = Platform::generic_linux_x86_64();
@@ -0,0 +1,136 @@
+//! Integer overflow policies for `Arithmetic` nodes.
+//!
+//! `wrap` is plain two's-complement arithmetic and `proven` adds the
+//! `nsw`/`nuw` flags its discharged obligation justifies. `saturate` uses
+//! the `llvm.*.sat` intrinsics (`llvm.*mul.fix.sat` with scale 0 for
+//! products), and `trap` branches on the overflow flag of
+//! `llvm.*.with.overflow` to `llvm.trap`. Division and remainder are lowered
+//! the same under every policy: their one overflowing case (`MIN / -1`) is
+//! left to the verifier.
+
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{AnyValue, FunctionValue, IntValue};
+
+use torc_core::fixed::Overflow;
+use torc_core::graph::node::{ArithmeticOp, Node};
+
+use crate::error::MaterializationError;
+
+use super::context::CodegenContext;
+use super::lower::build_err;
+
+type Lowered<T> = Result<T, MaterializationError>;
+
+/// Lower integer `Add`, `Sub` or `Mul` under the node's overflow policy.
+///
+/// Returns `None` for `wrap` and for other operations, which the caller
+/// lowers as plain arithmetic.
+pub(super) fn lower_int_overflow<'ctx>(
+    node: &Node,
+    ctx: &CodegenContext<'ctx>,
+    op: ArithmeticOp,
+    (lhs, rhs): (IntValue<'ctx>, IntValue<'ctx>),
+    signed: bool,
+    name: &str,
+) -> Lowered<Option<IntValue<'ctx>>> {
+    let overflow = Overflow::of_node(node).map_err(|e| MaterializationError::CodegenFailed {
+        stage: "lower_arithmetic".into(),
+        message: format!("node {}: {e}", node.id),
+    })?;
+    let stem = match op {
+        ArithmeticOp::Add => "add",
+        ArithmeticOp::Sub => "sub",
+        ArithmeticOp::Mul => "mul",
+        ArithmeticOp::Div | ArithmeticOp::Mod | ArithmeticOp::Pow => return Ok(None),
+    };
+    let sign = if signed { "s" } else { "u" };
+    let b = ctx.builder();
+    let ty: BasicTypeEnum<'ctx> = lhs.get_type().into();
+    let result = match overflow {
+        Overflow::Wrap => return Ok(None),
+        Overflow::Proven => match (op, signed) {
+            (ArithmeticOp::Add, true) => b.build_int_nsw_add(lhs, rhs, name),
+            (ArithmeticOp::Add, false) => b.build_int_nuw_add(lhs, rhs, name),
+            (ArithmeticOp::Sub, true) => b.build_int_nsw_sub(lhs, rhs, name),
+            (ArithmeticOp::Sub, false) => b.build_int_nuw_sub(lhs, rhs, name),
+            (_, true) => b.build_int_nsw_mul(lhs, rhs, name),
+            (_, false) => b.build_int_nuw_mul(lhs, rhs, name),
+        }
+        .map_err(|e| build_err(stem, e))?,
+        Overflow::Saturate if op == ArithmeticOp::Mul => {
+            let f = intrinsic(ctx, &format!("llvm.{sign}mul.fix.sat"), &[ty])?;
+            let scale = ctx.llvm_context().i32_type().const_zero();
+            b.build_call(f, &[lhs.into(), rhs.into(), scale.into()], name)
+                .map_err(|e| build_err("mul.fix.sat", e))?
+                .as_any_value_enum()
+                .into_int_value()
+        }
+        Overflow::Saturate => {
+            let f = intrinsic(ctx, &format!("llvm.{sign}{stem}.sat"), &[ty])?;
+            b.build_call(f, &[lhs.into(), rhs.into()], name)
+                .map_err(|e| build_err("sat", e))?
+                .as_any_value_enum()
+                .into_int_value()
+        }
+        Overflow::Trap => {
+            let f = intrinsic(ctx, &format!("llvm.{sign}{stem}.with.overflow"), &[ty])?;
+            let pair = b
+                .build_call(f, &[lhs.into(), rhs.into()], name)
+                .map_err(|e| build_err("with.overflow", e))?
+                .as_any_value_enum()
+                .into_struct_value();
+            let extract = |index| {
+                b.build_extract_value(pair, index, name)
+                    .map(|v| v.into_int_value())
+                    .map_err(|e| build_err("extractvalue", e))
+            };
+            let (value, overflowed) = (extract(0)?, extract(1)?);
+            trap_if(ctx, overflowed, name)?;
+            value
+        }
+    };
+    Ok(Some(result))
+}
+
+/// Branch to a block that calls `llvm.trap` when `cond` holds, and leave the
+/// builder positioned where execution continues.
+pub(super) fn trap_if<'ctx>(
+    ctx: &CodegenContext<'ctx>,
+    cond: IntValue<'ctx>,
+    name: &str,
+) -> Lowered<()> {
+    let b = ctx.builder();
+    let function = b
+        .get_insert_block()
+        .and_then(|block| block.get_parent())
+        .ok_or_else(|| MaterializationError::CodegenFailed {
+            stage: "trap".into(),
+            message: "builder is not positioned inside a function".into(),
+        })?;
+    let llvm = ctx.llvm_context();
+    let trap = llvm.append_basic_block(function, &format!("{name}.trap"));
+    let cont = llvm.append_basic_block(function, &format!("{name}.cont"));
+    b.build_conditional_branch(cond, trap, cont)
+        .map_err(|e| build_err("br", e))?;
+    b.position_at_end(trap);
+    b.build_call(intrinsic(ctx, "llvm.trap", &[])?, &[], "")
+        .map_err(|e| build_err("trap", e))?;
+    b.build_unreachable()
+        .map_err(|e| build_err("unreachable", e))?;
+    b.position_at_end(cont);
+    Ok(())
+}
+
+fn intrinsic<'ctx>(
+    ctx: &CodegenContext<'ctx>,
+    name: &str,
+    types: &[BasicTypeEnum<'ctx>],
+) -> Lowered<FunctionValue<'ctx>> {
+    Intrinsic::find(name)
+        .and_then(|i| i.get_declaration(ctx.module(), types))
+        .ok_or_else(|| MaterializationError::CodegenFailed {
+            stage: "intrinsic".into(),
+            message: format!("LLVM intrinsic {name} is unavailable"),
+        })
+}
@@ -7,7 +7,8 @@ use torc_core::types::{FloatPrecision, Type};
 /// Convert a Torc `Type` to an LLVM `BasicTypeEnum`.
 ///
 /// Returns `None` for types that cannot be represented in LLVM during Pass 2
-/// (e.g., Vec, Distribution, Named, Parameterized).
+/// (e.g., Vec, Distribution, Named, Parameterized). Resolve user-defined
+/// types first with `Graph::monomorphize`.
 ///
 /// Wrapper types (Refined, Linear, Timed, Sized, etc.) are peeled to their
 /// base type — refinements are enforced by verification, not by codegen.
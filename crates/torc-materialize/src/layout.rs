@@ -2,7 +2,7 @@
 
 use torc_core::graph::node::NodeId;
 use torc_core::graph::Graph;
-use torc_core::types::{FloatPrecision, Type};
+use torc_core::types::{FloatPrecision, Type, TypeTable};
 use torc_targets::Platform;
 
 use crate::error::MaterializationError;
@@ -179,10 +179,23 @@ pub fn estimate_type_size(ty: &Type, platform: &Platform) -> Option<TypeSize> {
         }
 
         // Named types and parameterized types can't be sized without resolution
+        // (see `estimate_resolved_type_size`)
         Type::Named(_) | Type::Parameterized { .. } => None,
     }
 }
 
+/// Estimate the size of a type after resolving it against `types`.
+///
+/// Returns `None` where `estimate_type_size` would, and for types that fail
+/// to resolve or still mention an undefined or symbolically-sized type.
+pub fn estimate_resolved_type_size(
+    ty: &Type,
+    types: &TypeTable,
+    platform: &Platform,
+) -> Option<TypeSize> {
+    estimate_type_size(&types.resolve(ty).ok()?, platform)
+}
+
 /// Estimate the memory layout for all nodes in a graph.
 pub fn estimate_layout(
     graph: &Graph,
@@ -197,13 +210,13 @@ pub fn estimate_layout(
             let inputs: u64 = sig
                 .inputs
                 .iter()
-                .filter_map(|t| estimate_type_size(t, platform))
+                .filter_map(|t| estimate_resolved_type_size(t, graph.types(), platform))
                 .map(|ts| ts.size_bytes)
                 .sum();
             let outputs: u64 = sig
                 .outputs
                 .iter()
-                .filter_map(|t| estimate_type_size(t, platform))
+                .filter_map(|t| estimate_resolved_type_size(t, graph.types(), platform))
                 .map(|ts| ts.size_bytes)
                 .sum();
             (inputs, outputs)
@@ -296,7 +309,7 @@ mod tests {
     use super::*;
     use torc_core::graph::edge::Edge;
     use torc_core::graph::node::{ArithmeticOp, Node, NodeKind};
-    use torc_core::types::TypeSignature;
+    use torc_core::types::{TypeDef, TypeSignature, ValueParam};
 
     #[test]
     fn primitive_type_sizes() {
@@ -365,6 +378,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn named_types_sized_after_resolution() {
+        let platform = Platform::generic_linux_x86_64();
+        let mut types = TypeTable::new();
+        types.define(
+            "Matrix",
+            TypeDef::alias(Type::Parameterized {
+                name: "Array".into(),
+                type_params: vec![Type::Parameterized {
+                    name: "Array".into(),
+                    type_params: vec![Type::Named("T".into())],
+                    value_params: vec![ValueParam::Symbolic("C".into())],
+                }],
+                value_params: vec![ValueParam::Symbolic("R".into())],
+            })
+            .with_type_param("T")
+            .with_value_param("R")
+            .with_value_param("C"),
+        );
+        let matrix = Type::Parameterized {
+            name: "Matrix".into(),
+            type_params: vec![Type::f32()],
+            value_params: vec![ValueParam::Concrete(3), ValueParam::Concrete(3)],
+        };
+
+        assert!(estimate_type_size(&matrix, &platform).is_none());
+        let size = estimate_resolved_type_size(&matrix, &types, &platform).unwrap();
+        assert_eq!(size.size_bytes, 36);
+        assert_eq!(size.alignment_bytes, 4);
+        assert!(
+            estimate_resolved_type_size(&Type::Named("Opaque".into()), &types, &platform).is_none()
+        );
+    }
+
     #[test]
     fn layout_estimation() {
         let mut g = Graph::new();
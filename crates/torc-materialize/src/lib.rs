@@ -26,7 +26,9 @@ pub use codegen::profile::OptimizationProfile;
 pub use codegen::{emit_code, CodegenConfig, CodegenOutput, EmitTarget};
 pub use error::MaterializationError;
 pub use gate::{gate_or_halt, verification_gate, GateConfig, GateDecision};
-pub use layout::{estimate_layout, estimate_type_size, MemoryLayout, TypeSize};
+pub use layout::{
+    estimate_layout, estimate_resolved_type_size, estimate_type_size, MemoryLayout, TypeSize,
+};
 pub use pipeline::{materialize, PipelineConfig, PipelineOutput};
 pub use postverify::{verify_binary, PostVerifyResult};
 pub use report::MaterializationReport;
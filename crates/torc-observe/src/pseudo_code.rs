@@ -90,9 +90,9 @@ impl View for PseudoCodeView {
             let stmt = match &node.kind {
                 NodeKind::Literal => {
                     let value = node
-                        .annotations
-                        .get("value")
-                        .cloned()
+                        .value
+                        .as_ref()
+                        .map(|v| v.to_string())
                         .unwrap_or_else(|| "?".to_string());
                     format!("let {name} = {value};")
                 }
@@ -244,6 +244,7 @@ mod tests {
     use torc_core::graph::edge::Edge;
     use torc_core::graph::node::{ArithmeticOp, ComparisonOp, Node, NodeKind};
     use torc_core::types::{Type, TypeSignature};
+    use torc_core::value::Value;
 
     fn empty_ctx() -> RenderContext<'static> {
         RenderContext::empty()
@@ -264,7 +265,7 @@ mod tests {
         let mut n =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
         n.annotations.insert("name".into(), "x".into());
-        n.annotations.insert("value".into(), "42".into());
+        n.value = Some(Value::i32(42));
         g.add_node(n).unwrap();
 
         let view = PseudoCodeView;
@@ -273,18 +274,33 @@ mod tests {
         assert_eq!(output.data["node_count"], 1);
     }
 
+    #[test]
+    fn composite_literal() {
+        let mut g = Graph::new();
+        let mut n = Node::new(NodeKind::Literal).with_value(Value::Tuple(vec![
+            Value::some(Value::f32(0.5)),
+            Value::Array(vec![Value::u8(1), Value::u8(2)]),
+        ]));
+        n.annotations.insert("name".into(), "cfg".into());
+        g.add_node(n).unwrap();
+
+        let view = PseudoCodeView;
+        let output = view.render(&g, &empty_ctx()).unwrap();
+        assert!(output.text.contains("let cfg = (Some(0.5), [1, 2]);"));
+    }
+
     #[test]
     fn linear_chain() {
         let mut g = Graph::new();
         let mut n1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
         n1.annotations.insert("name".into(), "a".into());
-        n1.annotations.insert("value".into(), "10".into());
+        n1.value = Some(Value::i32(10));
 
         let mut n2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
         n2.annotations.insert("name".into(), "b".into());
-        n2.annotations.insert("value".into(), "20".into());
+        n2.value = Some(Value::i32(20));
 
         let mut n3 = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
             TypeSignature::pure_fn(vec![Type::i32(), Type::i32()], Type::i32()),
@@ -337,12 +353,12 @@ mod tests {
         let mut n1 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
         n1.annotations.insert("name".into(), "x".into());
-        n1.annotations.insert("value".into(), "5".into());
+        n1.value = Some(Value::i32(5));
 
         let mut n2 =
             Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(Type::i32()));
         n2.annotations.insert("name".into(), "y".into());
-        n2.annotations.insert("value".into(), "10".into());
+        n2.value = Some(Value::i32(10));
 
         let mut n3 = Node::new(NodeKind::Comparison(ComparisonOp::Lt)).with_type_signature(
             TypeSignature::pure_fn(vec![Type::i32(), Type::i32()], Type::Bool),
@@ -368,7 +384,7 @@ mod tests {
         let mut g = Graph::new();
         let mut n = Node::new(NodeKind::Literal);
         n.annotations.insert("name".into(), "x".into());
-        n.annotations.insert("value".into(), "1".into());
+        n.value = Some(Value::i32(1));
         g.add_node(n).unwrap();
 
         let view = PseudoCodeView;
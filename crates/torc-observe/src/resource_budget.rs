@@ -83,7 +83,7 @@ impl View for ResourceBudgetView {
         if wcet_nodes.is_empty() {
             text.push_str("  No WCET bounds specified.\n");
         } else {
-            wcet_nodes.sort_by(|a, b| b.1.cmp(&a.1)); // Descending
+            wcet_nodes.sort_by_key(|n| std::cmp::Reverse(n.1)); // Descending
             text.push_str(&format!(
                 "  Total WCET (sum): {}\n",
                 format_time_ns(total_wcet_ns)
@@ -179,10 +179,10 @@ pub fn analyze_commit_impact(graph: &DecisionGraph, decision: &Decision) -> Impa
             continue;
         }
         match d.state {
-            DecisionState::Unexplored | DecisionState::Exploring | DecisionState::Deferred => {
-                if !dependents.contains(&d.id) && !report.triggered_revisits.contains(&d.id) {
-                    report.still_open.push(d.id);
-                }
+            DecisionState::Unexplored | DecisionState::Exploring | DecisionState::Deferred
+                if !dependents.contains(&d.id) && !report.triggered_revisits.contains(&d.id) =>
+            {
+                report.still_open.push(d.id);
             }
             _ => {}
         }
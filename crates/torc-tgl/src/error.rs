@@ -0,0 +1,35 @@
+//! Errors from parsing TGL source.
+
+use thiserror::Error;
+use torc_core::graph::GraphError;
+
+/// Convenience alias for results within the tgl crate.
+pub type Result<T> = std::result::Result<T, TglError>;
+
+/// Errors that can occur while parsing TGL source into a graph.
+#[derive(Debug, Error)]
+pub enum TglError {
+    #[error("{line}:{column}: {message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("{line}:{column}: unknown label '{label}'")]
+    UnknownLabel {
+        line: usize,
+        column: usize,
+        label: String,
+    },
+
+    #[error("{line}:{column}: duplicate label '{label}'")]
+    DuplicateLabel {
+        line: usize,
+        column: usize,
+        label: String,
+    },
+
+    #[error("graph error: {0}")]
+    Graph(#[from] GraphError),
+}
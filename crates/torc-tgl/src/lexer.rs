@@ -0,0 +1,441 @@
+//! Tokenizer for TGL source text.
+
+use uuid::Uuid;
+
+use crate::error::{Result, TglError};
+
+/// A lexical token.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// Bare identifier or keyword.
+    Ident(String),
+    /// Escaped identifier: `$"any text"`.
+    QuotedIdent(String),
+    /// Unsigned integer literal (sign is handled by the parser).
+    Int(u128),
+    /// Unsigned float literal (contains `.` or an exponent).
+    Float(f64),
+    /// String literal.
+    Str(String),
+    /// Explicit element ID: `@ <uuid>`.
+    Id(Uuid),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    EqEq,
+    Ne,
+    Assign,
+    Colon,
+    Semi,
+    Comma,
+    Dot,
+    Arrow,
+    FatArrow,
+    AndAnd,
+    OrOr,
+    Bang,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Pipe,
+    Eof,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "'{s}'"),
+            Token::QuotedIdent(s) => write!(f, "$\"{s}\""),
+            Token::Int(n) => write!(f, "{n}"),
+            Token::Float(x) => write!(f, "{x:?}"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Id(id) => write!(f, "@{id}"),
+            Token::LBrace => write!(f, "'{{'"),
+            Token::RBrace => write!(f, "'}}'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::Lt => write!(f, "'<'"),
+            Token::Gt => write!(f, "'>'"),
+            Token::Le => write!(f, "'<='"),
+            Token::Ge => write!(f, "'>='"),
+            Token::EqEq => write!(f, "'=='"),
+            Token::Ne => write!(f, "'!='"),
+            Token::Assign => write!(f, "'='"),
+            Token::Colon => write!(f, "':'"),
+            Token::Semi => write!(f, "';'"),
+            Token::Comma => write!(f, "','"),
+            Token::Dot => write!(f, "'.'"),
+            Token::Arrow => write!(f, "'->'"),
+            Token::FatArrow => write!(f, "'=>'"),
+            Token::AndAnd => write!(f, "'&&'"),
+            Token::OrOr => write!(f, "'||'"),
+            Token::Bang => write!(f, "'!'"),
+            Token::Plus => write!(f, "'+'"),
+            Token::Minus => write!(f, "'-'"),
+            Token::Star => write!(f, "'*'"),
+            Token::Slash => write!(f, "'/'"),
+            Token::Percent => write!(f, "'%'"),
+            Token::Pipe => write!(f, "'|'"),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Source position of a token (1-based).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Pos {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Pos {
+    pub fn error(self, message: impl Into<String>) -> TglError {
+        TglError::Syntax {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+/// Split source text into tokens, ending with `Token::Eof`.
+pub(crate) fn tokenize(src: &str) -> Result<Vec<(Token, Pos)>> {
+    let mut lexer = Lexer {
+        chars: src.chars().collect(),
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+    loop {
+        lexer.skip_trivia();
+        let pos = lexer.pos();
+        match lexer.next_token()? {
+            Token::Eof => {
+                tokens.push((Token::Eof, pos));
+                return Ok(tokens);
+            }
+            tok => tokens.push((tok, pos)),
+        }
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn pos(&self) -> Pos {
+        Pos {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.offset).copied()
+    }
+
+    fn peek_at(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.offset + ahead).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Skip whitespace and `//` line comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('/') if self.peek_at(1) == Some('/') => {
+                    while let Some(c) = self.bump() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Token> {
+        let pos = self.pos();
+        let c = match self.bump() {
+            Some(c) => c,
+            None => return Ok(Token::Eof),
+        };
+        let two = |lexer: &mut Lexer, next: char, yes: Token, no: Token| {
+            if lexer.peek() == Some(next) {
+                lexer.bump();
+                yes
+            } else {
+                no
+            }
+        };
+        let tok = match c {
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ':' => Token::Colon,
+            ';' => Token::Semi,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '+' => Token::Plus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '%' => Token::Percent,
+            '<' => two(self, '=', Token::Le, Token::Lt),
+            '>' => two(self, '=', Token::Ge, Token::Gt),
+            '!' => two(self, '=', Token::Ne, Token::Bang),
+            '-' => two(self, '>', Token::Arrow, Token::Minus),
+            '=' => match self.peek() {
+                Some('=') => {
+                    self.bump();
+                    Token::EqEq
+                }
+                Some('>') => {
+                    self.bump();
+                    Token::FatArrow
+                }
+                _ => Token::Assign,
+            },
+            '&' if self.peek() == Some('&') => {
+                self.bump();
+                Token::AndAnd
+            }
+            '|' => two(self, '|', Token::OrOr, Token::Pipe),
+            '"' => Token::Str(self.string_body(pos)?),
+            '$' => {
+                if self.bump() != Some('"') {
+                    return Err(pos.error("expected string after '$'"));
+                }
+                Token::QuotedIdent(self.string_body(pos)?)
+            }
+            '@' => {
+                self.skip_trivia();
+                let start = self.offset;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_hexdigit() || c == '-')
+                {
+                    self.bump();
+                }
+                let text: String = self.chars[start..self.offset].iter().collect();
+                let id = Uuid::parse_str(&text)
+                    .map_err(|_| pos.error(format!("invalid id '@{text}'")))?;
+                Token::Id(id)
+            }
+            c if c.is_ascii_digit() => self.number(c, pos)?,
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = String::from(c);
+                while let Some(c) = self.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        ident.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Ident(ident)
+            }
+            other => return Err(pos.error(format!("unexpected character '{other}'"))),
+        };
+        Ok(tok)
+    }
+
+    /// Lex a numeric literal whose first digit has already been consumed.
+    fn number(&mut self, first: char, pos: Pos) -> Result<Token> {
+        let mut text = String::from(first);
+        let mut is_float = false;
+        self.digits(&mut text);
+        if self.peek() == Some('.') && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()) {
+            is_float = true;
+            text.push('.');
+            self.bump();
+            self.digits(&mut text);
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let signed = matches!(self.peek_at(1), Some('+' | '-'));
+            let digit_at = if signed { 2 } else { 1 };
+            if self.peek_at(digit_at).is_some_and(|c| c.is_ascii_digit()) {
+                is_float = true;
+                for _ in 0..digit_at {
+                    text.push(self.bump().expect("peeked"));
+                }
+                self.digits(&mut text);
+            }
+        }
+        if is_float {
+            text.parse::<f64>()
+                .map(Token::Float)
+                .map_err(|_| pos.error(format!("invalid float literal '{text}'")))
+        } else {
+            text.parse::<u128>()
+                .map(Token::Int)
+                .map_err(|_| pos.error(format!("integer literal '{text}' out of range")))
+        }
+    }
+
+    fn digits(&mut self, text: &mut String) {
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                text.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Lex the remainder of a string literal after the opening quote.
+    fn string_body(&mut self, pos: Pos) -> Result<String> {
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(pos.error("unterminated string literal")),
+                Some('"') => return Ok(out),
+                Some('\\') => {
+                    let escaped = match self.bump() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('u') => self.unicode_escape(pos)?,
+                        _ => return Err(pos.error("invalid escape in string literal")),
+                    };
+                    out.push(escaped);
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    /// Lex `{XXXX}` after `\u`.
+    fn unicode_escape(&mut self, pos: Pos) -> Result<char> {
+        if self.bump() != Some('{') {
+            return Err(pos.error("expected '{' in unicode escape"));
+        }
+        let mut hex = String::new();
+        loop {
+            match self.bump() {
+                Some('}') => break,
+                Some(c) if c.is_ascii_hexdigit() => hex.push(c),
+                _ => return Err(pos.error("invalid unicode escape")),
+            }
+        }
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| pos.error("invalid unicode escape"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str) -> Vec<Token> {
+        tokenize(src).unwrap().into_iter().map(|(t, _)| t).collect()
+    }
+
+    #[test]
+    fn punctuation_and_operators() {
+        assert_eq!(
+            kinds("-> => == != <= >= && || < > ! = -"),
+            vec![
+                Token::Arrow,
+                Token::FatArrow,
+                Token::EqEq,
+                Token::Ne,
+                Token::Le,
+                Token::Ge,
+                Token::AndAnd,
+                Token::OrOr,
+                Token::Lt,
+                Token::Gt,
+                Token::Bang,
+                Token::Assign,
+                Token::Minus,
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            kinds("42 1.5 1e-7 100ns n0.1"),
+            vec![
+                Token::Int(42),
+                Token::Float(1.5),
+                Token::Float(1e-7),
+                Token::Int(100),
+                Token::Ident("ns".into()),
+                Token::Ident("n0".into()),
+                Token::Dot,
+                Token::Int(1),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn strings_and_ids() {
+        let id = Uuid::new_v4();
+        let src = format!(r#""a\"b\n\u{{3bc}}" $"odd name" @ {id}"#);
+        assert_eq!(
+            kinds(&src),
+            vec![
+                Token::Str("a\"b\nμ".into()),
+                Token::QuotedIdent("odd name".into()),
+                Token::Id(id),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_positions() {
+        let tokens = tokenize("// header\n  node").unwrap();
+        assert_eq!(tokens[0].0, Token::Ident("node".into()));
+        assert_eq!(tokens[0].1, Pos { line: 2, column: 3 });
+    }
+
+    #[test]
+    fn unterminated_string_rejected() {
+        assert!(tokenize("\"abc").is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Textual graph language (TGL) for the Torc language.
+//!
+//! TGL is a human-writable projection of a Torc graph. Every node, edge,
+//! region, contract, provenance record and annotation has a textual form, so
+//! a `.tgl` file and the `.trc` binary of the same program describe the same
+//! graph and serialize to identical bytes:
+//!
+//! ```text
+//! graph {
+//!     node a = Literal { sig () -> (i32); value 2i32; }
+//!     node b = Literal { sig () -> (i32); value -3i32; }
+//!     node sum = Arithmetic(Add) {
+//!         sig (i32, i32) -> (i32);
+//!         contract { ensures result == a + b; }
+//!     }
+//!     edge a.0 -> sum.0 : i32;
+//!     edge b.0 -> sum.1 : i32;
+//!     region body = sequential [a, b, sum];
+//! }
+//! ```
+//!
+//! Elements may carry an explicit `@ <uuid>`; the pretty-printer always
+//! writes one so that printing and re-parsing preserves identity.
+//!
+//! Named types are defined at the top of the graph, with type parameters
+//! before the `;` and value parameters after it:
+//!
+//! ```text
+//! type Millivolts = (i32 where value >= 0);
+//! type Matrix<T; R, C> = Array<Array<T; C>; R>;
+//! ```
+
+mod lexer;
+
+pub mod error;
+pub mod parser;
+pub mod printer;
+
+pub use error::TglError;
+pub use parser::{parse_graph, parse_predicate, parse_type, parse_value};
+pub use printer::{print_graph, print_predicate, print_type, print_value};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::contract::{
+        Contract, EffectSet, FailureMode, PortBinding, ProofStatus, ProofWitness, RecoveryStrategy,
+        Variant,
+    };
+    use torc_core::graph::constraints::{BandwidthConstraint, Constraint, Lifetime};
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::{ArithmeticOp, MemoryOrdering, Node, NodeKind};
+    use torc_core::graph::port::Port;
+    use torc_core::graph::region::{Region, RegionKind};
+    use torc_core::graph::Graph;
+    use torc_core::provenance::{Author, Provenance};
+    use torc_core::types::{
+        Effect, Linearity, Predicate, Type, TypeDef, TypeSignature, ValueParam,
+    };
+    use torc_core::value::{FixedValue, FloatValue, Value};
+    use torc_trc::TrcFile;
+
+    fn rich_graph() -> Graph {
+        let mut g = Graph::new();
+        g.define_type(
+            "Reading",
+            TypeDef::alias(Type::Record(
+                [
+                    ("raw".to_string(), Type::u16()),
+                    ("ok".to_string(), Type::Bool),
+                ]
+                .into(),
+            )),
+        );
+        g.define_type(
+            "Buffer",
+            TypeDef::alias(Type::Parameterized {
+                name: "Array".into(),
+                type_params: vec![Type::Named("T".into())],
+                value_params: vec![ValueParam::Symbolic("len".into())],
+            })
+            .with_type_param("T")
+            .with_value_param("offset")
+            .with_value_param("len"),
+        );
+        g.define_type(
+            "$odd name",
+            TypeDef::alias(Type::Unit).with_type_param("in"),
+        );
+
+        let mut a = Node::new(NodeKind::Literal).with_type_signature(TypeSignature::source(
+            Type::i32().refined(Predicate::in_range("x", -100, 100)),
+        ));
+        a.annotations.insert("name".into(), "a".into());
+        a.value = Some(Value::i32(42));
+
+        let mut provenance = Provenance::ai_authored("model", "vendor", "1", "sensor input");
+        provenance.link_requirement("REQ-1", Some("spec.md"), None);
+        provenance.record_edit(
+            Author::Human {
+                identity: "reviewer \"q\"".into(),
+            },
+            "tightened range",
+            Some("abc123".into()),
+        );
+        let b = Node::new(NodeKind::Read).with_provenance(provenance);
+
+        let mut contract = Contract::with_conditions(
+            vec![Predicate::Ne(
+                Box::new(Predicate::Var("b".into())),
+                Box::new(Predicate::IntLit(0)),
+            )],
+            vec![Predicate::ForAll {
+                var: "i".into(),
+                range: Box::new(Predicate::in_range("i", 0, 8)),
+                body: Box::new(Predicate::Apply(
+                    "ok".into(),
+                    vec![Predicate::Neg(Box::new(Predicate::FloatLit(0.5)))],
+                )),
+            }],
+        )
+        .with_wcet(120, "cortex-m4")
+        .with_stack(256)
+        .with_effects(EffectSet::from_effects(vec![
+            Effect::IO("uart".into()),
+            Effect::Panic,
+        ]))
+        .with_binding("b", PortBinding::input(1))
+        .with_binding("in", PortBinding::output(0))
+        .with_variant(Variant::new(
+            Predicate::Var("b".into()),
+            Predicate::Sub(
+                Box::new(Predicate::Var("b".into())),
+                Box::new(Predicate::IntLit(1)),
+            ),
+        ));
+        contract.add_failure_mode(FailureMode {
+            name: "DIV_ZERO".into(),
+            description: "divisor is zero".into(),
+            recovery: RecoveryStrategy::Degrade("0".into()),
+        });
+        contract.recovery_strategy = RecoveryStrategy::Retry(3);
+        contract.proof_status = ProofStatus::Verified;
+        contract.proof_witness = Some(ProofWitness {
+            hash: "deadbeef".into(),
+            solver: "z3".into(),
+            data: vec![0, 1, 0xff],
+        });
+        let div = Node::new(NodeKind::Arithmetic(ArithmeticOp::Div))
+            .with_type_signature(TypeSignature::pure_fn(
+                vec![Type::i32(), Type::i32()],
+                Type::i32(),
+            ))
+            .with_contract(contract);
+
+        let atomic = Node::new(NodeKind::Atomic(MemoryOrdering::SeqCst));
+
+        let (a_id, b_id, div_id, atomic_id) = (a.id, b.id, div.id, atomic.id);
+        for node in [a, b, div, atomic] {
+            g.add_node(node).unwrap();
+        }
+
+        let outer = Region::new(RegionKind::Sequential, vec![a_id, b_id, div_id])
+            .with_constraints(vec![
+                Constraint::MaxTime(1_000),
+                Constraint::MaxIterations(8),
+                Constraint::Custom {
+                    name: "asil".into(),
+                    description: "ASIL-D".into(),
+                },
+            ])
+            .with_interfaces(vec![
+                Port::input("x", 0, Type::i32()),
+                Port::output("y", 0, Type::Named("Reading".into())),
+            ]);
+        let outer_id = outer.id;
+        let inner = Region::new(RegionKind::Iterative, vec![atomic_id])
+            .with_parent(outer_id)
+            .with_variant(
+                Variant::new(Predicate::Var("k".into()), Predicate::Var("k2".into()))
+                    .with_lower_bound(Predicate::IntLit(-1)),
+            );
+
+        g.add_edge(Edge::typed((a_id, 0), (div_id, 0), Type::i32()))
+            .unwrap();
+        g.add_edge(
+            Edge::new((b_id, 0), (div_id, 1))
+                .with_lifetime(Lifetime::Region(outer_id))
+                .with_bandwidth(BandwidthConstraint::bounded(10, 20)),
+        )
+        .unwrap();
+        g.add_edge(
+            Edge::typed(
+                (div_id, 0),
+                (atomic_id, 0),
+                Type::Parameterized {
+                    name: "Buffer".into(),
+                    type_params: vec![Type::f32().with_linearity(Linearity::Unique)],
+                    value_params: vec![ValueParam::Concrete(-4), ValueParam::Symbolic("N".into())],
+                },
+            )
+            .with_lifetime(Lifetime::Bounded(500)),
+        )
+        .unwrap();
+
+        g.add_region(inner).unwrap();
+        g.add_region(outer).unwrap();
+        g
+    }
+
+    #[test]
+    fn round_trip_is_byte_identical_to_trc() {
+        let original = rich_graph();
+        let text = print_graph(&original);
+        let parsed = parse_graph(&text).unwrap_or_else(|e| panic!("{e}\n{text}"));
+
+        assert_eq!(print_graph(&parsed), text);
+        assert_eq!(
+            TrcFile::new(original).to_bytes().unwrap(),
+            TrcFile::new(parsed).to_bytes().unwrap()
+        );
+    }
+
+    #[test]
+    fn hand_written_source_parses() {
+        let g = parse_graph(
+            r#"
+            // Saturating increment.
+            graph {
+                node x = Literal { annotate "name" = "x"; }
+                node one = Literal;
+                node inc = Arithmetic(Add) {
+                    sig ((i32 where x < 2147483647), i32) -> (i32);
+                    contract {
+                        requires x < 2147483647;
+                        ensures result == x + 1;
+                        effects [Pure];
+                    }
+                }
+                edge x.0 -> inc.0 : i32;
+                edge one.0 -> inc.1 : i32 lifetime static;
+                region body = sequential [x, one, inc] {
+                    constraint max_time 50;
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 2);
+        assert!(g.validate().is_ok());
+        let inc = g
+            .nodes()
+            .find(|n| n.kind == NodeKind::Arithmetic(ArithmeticOp::Add))
+            .unwrap();
+        assert_eq!(inc.contract.as_ref().unwrap().preconditions.len(), 1);
+    }
+
+    #[test]
+    fn types_and_predicates_round_trip() {
+        let types = [
+            "Fixed<16, 8>",
+            "(i32 where x >= 0 && x < 10)",
+            "{\"odd key\": Bool, x: (u8,)}",
+            "Variant<Err(Named(\"Vec\")) | Ok([f64; 4])>",
+            "Timed<Sized<Unit, 64B>, 100ns, \"m4\">",
+            "Interval<Distribution<f32>, 0.95>",
+            "Tensor<;>",
+        ];
+        for src in types {
+            let ty = parse_type(src).unwrap();
+            assert_eq!(print_type(&ty), src);
+        }
+
+        let g =
+            parse_graph("graph { type Matrix<T; R, C> = Array<Array<T; C>; R>; type Id = u32; }")
+                .unwrap();
+        let resolved = g
+            .resolve_type(&parse_type("Matrix<Id; 2, 3>").unwrap())
+            .unwrap();
+        assert_eq!(print_type(&resolved), "[[u32; 3]; 2]");
+        assert!(parse_graph("graph { type A = u8; type A = u16; }").is_err());
+
+        let preds = [
+            "a - (b - c)",
+            "!(x > 0) => y",
+            "-(1) * -2",
+            "$\"in\" == f()",
+        ];
+        for src in preds {
+            let p = parse_predicate(src).unwrap();
+            assert_eq!(print_predicate(&p), src);
+        }
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let values = [
+            "42i32",
+            "-128i8",
+            "340282366920938463463374607431768211455u128",
+            "1.5f32",
+            "-0.0f64",
+            "-inf f64",
+            "NaN f16",
+            "Fixed<16, 8>(-384)",
+            "()",
+            "(true,)",
+            "{\"odd key\": None, x: Some([1u8, 2u8])}",
+            "vec[Err(()), $\"Some\"(7u3)]",
+        ];
+        for src in values {
+            let value = parse_value(src).unwrap_or_else(|e| panic!("{e}: {src}"));
+            assert_eq!(print_value(&value), src);
+        }
+
+        assert_eq!(
+            parse_value("Fixed<16, 8>(-384)").unwrap(),
+            Value::Fixed(FixedValue::from_raw(-384, 16, 8))
+        );
+        assert_eq!(
+            parse_value("2f32").unwrap(),
+            Value::Float(FloatValue::new(2.0, torc_core::types::FloatPrecision::F32))
+        );
+        assert!(parse_value("256u8").is_err());
+        assert!(parse_value("-1u8").is_err());
+        assert!(parse_value("1.5i32").is_err());
+        assert!(parse_value("7").is_err());
+    }
+}
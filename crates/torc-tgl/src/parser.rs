@@ -0,0 +1,1698 @@
+//! Recursive-descent parser from TGL source text to `Graph`.
+//!
+//! Declarations may appear in any order and refer to each other by label;
+//! labels are resolved after the whole file has been read. Elements written
+//! without an explicit `@ <uuid>` receive a fresh ID.
+
+use std::collections::{BTreeMap, HashMap};
+
+use torc_core::contract::{
+    Contract, EffectSet, EnergyBound, FailureMode, MemoryBound, PortBinding, ProofStatus,
+    ProofWitness, RecoveryStrategy, StackBound, TimeBound, Variant,
+};
+use torc_core::graph::constraints::{BandwidthConstraint, Constraint, Lifetime};
+use torc_core::graph::edge::Edge;
+use torc_core::graph::node::{
+    ArithmeticOp, BitwiseOp, ComparisonOp, MemoryOrdering, Node, NodeKind,
+};
+use torc_core::graph::port::Port;
+use torc_core::graph::region::{Region, RegionKind};
+use torc_core::graph::Graph;
+use torc_core::provenance::{Author, EditRecord, Provenance, RequirementLink};
+use torc_core::types::{
+    Effect, FloatPrecision, Linearity, Predicate, Signedness, TimeBound as TypeTimeBound, Type,
+    TypeDef, TypeSignature, ValueParam,
+};
+use torc_core::value::{FixedValue, FloatValue, IntValue, Value};
+use uuid::Uuid;
+
+use crate::error::{Result, TglError};
+use crate::lexer::{tokenize, Pos, Token};
+
+/// Parse a complete `graph { ... }` document.
+pub fn parse_graph(src: &str) -> Result<Graph> {
+    let mut parser = Parser::new(src)?;
+    let decls = parser.graph()?;
+    parser.expect(Token::Eof)?;
+    decls.build()
+}
+
+/// Parse a single type expression.
+pub fn parse_type(src: &str) -> Result<Type> {
+    let mut parser = Parser::new(src)?;
+    let ty = parser.ty()?;
+    parser.expect(Token::Eof)?;
+    Ok(ty)
+}
+
+/// Parse a single predicate expression.
+pub fn parse_predicate(src: &str) -> Result<Predicate> {
+    let mut parser = Parser::new(src)?;
+    let pred = parser.predicate()?;
+    parser.expect(Token::Eof)?;
+    Ok(pred)
+}
+
+/// Parse a single constant value.
+pub fn parse_value(src: &str) -> Result<Value> {
+    let mut parser = Parser::new(src)?;
+    let value = parser.value()?;
+    parser.expect(Token::Eof)?;
+    Ok(value)
+}
+
+/// A reference to a node or region: a label or an explicit ID.
+#[derive(Debug, Clone)]
+enum Ref {
+    Label(String, Pos),
+    Id(Uuid),
+}
+
+enum LifetimeDecl {
+    Resolved(Lifetime),
+    Region(Ref),
+}
+
+struct EdgeDecl {
+    id: Option<Uuid>,
+    source: (Ref, usize),
+    target: (Ref, usize),
+    data_type: Option<Type>,
+    lifetime: LifetimeDecl,
+    bandwidth: Option<BandwidthConstraint>,
+}
+
+struct RegionDecl {
+    label: String,
+    pos: Pos,
+    id: Option<Uuid>,
+    kind: RegionKind,
+    children: Vec<Ref>,
+    parent: Option<Ref>,
+    constraints: Vec<Constraint>,
+    interfaces: Vec<Port>,
+    variant: Option<Variant>,
+}
+
+#[derive(Default)]
+struct Declarations {
+    types: Vec<(String, TypeDef)>,
+    nodes: Vec<(String, Pos, Node)>,
+    edges: Vec<EdgeDecl>,
+    regions: Vec<RegionDecl>,
+}
+
+impl Declarations {
+    fn build(self) -> Result<Graph> {
+        let mut labels: HashMap<String, Uuid> = HashMap::new();
+        let mut bind = |label: &str, pos: Pos, id: Uuid| {
+            if labels.insert(label.to_string(), id).is_some() {
+                return Err(TglError::DuplicateLabel {
+                    line: pos.line,
+                    column: pos.column,
+                    label: label.to_string(),
+                });
+            }
+            Ok(())
+        };
+
+        let mut graph = Graph::new();
+        for (name, def) in self.types {
+            graph.define_type(name, def);
+        }
+        for (label, pos, node) in self.nodes {
+            bind(&label, pos, node.id)?;
+            graph.add_node(node)?;
+        }
+        let region_ids: Vec<Uuid> = self
+            .regions
+            .iter()
+            .map(|r| r.id.unwrap_or_else(Uuid::new_v4))
+            .collect();
+        for (decl, id) in self.regions.iter().zip(&region_ids) {
+            bind(&decl.label, decl.pos, *id)?;
+        }
+
+        let resolve = |r: &Ref| match r {
+            Ref::Id(id) => Ok(*id),
+            Ref::Label(label, pos) => {
+                labels
+                    .get(label)
+                    .copied()
+                    .ok_or_else(|| TglError::UnknownLabel {
+                        line: pos.line,
+                        column: pos.column,
+                        label: label.clone(),
+                    })
+            }
+        };
+
+        for decl in self.edges {
+            let source = (resolve(&decl.source.0)?, decl.source.1);
+            let target = (resolve(&decl.target.0)?, decl.target.1);
+            let mut edge = Edge::with_id(decl.id.unwrap_or_else(Uuid::new_v4), source, target);
+            edge.data_type = decl.data_type;
+            edge.lifetime = match decl.lifetime {
+                LifetimeDecl::Resolved(lifetime) => lifetime,
+                LifetimeDecl::Region(r) => Lifetime::Region(resolve(&r)?),
+            };
+            edge.bandwidth = decl.bandwidth;
+            graph.add_edge(edge)?;
+        }
+
+        for (decl, id) in self.regions.into_iter().zip(region_ids) {
+            let children = decl
+                .children
+                .iter()
+                .map(&resolve)
+                .collect::<Result<Vec<_>>>()?;
+            let mut region = Region::with_id(id, decl.kind, children)
+                .with_constraints(decl.constraints)
+                .with_interfaces(decl.interfaces);
+            if let Some(parent) = &decl.parent {
+                region = region.with_parent(resolve(parent)?);
+            }
+            region.variant = decl.variant;
+            graph.add_region(region)?;
+        }
+
+        Ok(graph)
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    cursor: usize,
+}
+
+impl Parser {
+    fn new(src: &str) -> Result<Self> {
+        Ok(Self {
+            tokens: tokenize(src)?,
+            cursor: 0,
+        })
+    }
+
+    // === Token plumbing ===
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.cursor].0
+    }
+
+    fn peek_ahead(&self, n: usize) -> &Token {
+        let idx = (self.cursor + n).min(self.tokens.len() - 1);
+        &self.tokens[idx].0
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens[self.cursor].1
+    }
+
+    fn next(&mut self) -> Token {
+        let tok = self.tokens[self.cursor].0.clone();
+        if self.cursor + 1 < self.tokens.len() {
+            self.cursor += 1;
+        }
+        tok
+    }
+
+    fn eat(&mut self, tok: &Token) -> bool {
+        if self.peek() == tok {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, tok: Token) -> Result<()> {
+        if self.peek() == &tok {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.unexpected(&tok.to_string()))
+        }
+    }
+
+    fn unexpected(&self, wanted: &str) -> TglError {
+        self.pos()
+            .error(format!("expected {wanted}, found {}", self.peek()))
+    }
+
+    fn at_keyword(&self, kw: &str) -> bool {
+        matches!(self.peek(), Token::Ident(s) if s == kw)
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        if self.at_keyword(kw) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, kw: &str) -> Result<()> {
+        if self.eat_keyword(kw) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{kw}'")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Ident(s) => {
+                self.next();
+                Ok(s)
+            }
+            _ => Err(self.unexpected("identifier")),
+        }
+    }
+
+    /// A bare or `$"..."`-escaped name.
+    fn name(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Ident(s) | Token::QuotedIdent(s) => {
+                self.next();
+                Ok(s)
+            }
+            _ => Err(self.unexpected("name")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Str(s) => {
+                self.next();
+                Ok(s)
+            }
+            _ => Err(self.unexpected("string literal")),
+        }
+    }
+
+    fn integer<T: TryFrom<u128>>(&mut self) -> Result<T> {
+        let pos = self.pos();
+        match self.next() {
+            Token::Int(n) => T::try_from(n).map_err(|_| pos.error(format!("{n} is out of range"))),
+            other => Err(pos.error(format!("expected integer, found {other}"))),
+        }
+    }
+
+    fn float(&mut self) -> Result<f64> {
+        let pos = self.pos();
+        let negative = self.eat(&Token::Minus);
+        let value = match self.next() {
+            Token::Float(x) => x,
+            Token::Int(n) => n as f64,
+            Token::Ident(s) if s == "inf" => f64::INFINITY,
+            Token::Ident(s) if s == "NaN" => f64::NAN,
+            other => return Err(pos.error(format!("expected number, found {other}"))),
+        };
+        Ok(if negative { -value } else { value })
+    }
+
+    /// An integer followed by a unit suffix, e.g. `100ns`.
+    fn quantity<T: TryFrom<u128>>(&mut self, unit: &str) -> Result<T> {
+        let value = self.integer()?;
+        self.expect_keyword(unit)?;
+        Ok(value)
+    }
+
+    fn optional_id(&mut self) -> Option<Uuid> {
+        match self.peek() {
+            Token::Id(id) => {
+                let id = *id;
+                self.next();
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    fn reference(&mut self) -> Result<Ref> {
+        let pos = self.pos();
+        match self.next() {
+            Token::Ident(label) => Ok(Ref::Label(label, pos)),
+            Token::Id(id) => Ok(Ref::Id(id)),
+            other => Err(pos.error(format!("expected label or @id, found {other}"))),
+        }
+    }
+
+    /// A `[a, b, ...]` list of references.
+    fn reference_list(&mut self) -> Result<Vec<Ref>> {
+        self.expect(Token::LBracket)?;
+        let mut refs = Vec::new();
+        if !self.eat(&Token::RBracket) {
+            loop {
+                refs.push(self.reference()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+            self.expect(Token::RBracket)?;
+        }
+        Ok(refs)
+    }
+
+    // === Declarations ===
+
+    fn graph(&mut self) -> Result<Declarations> {
+        self.expect_keyword("graph")?;
+        self.expect(Token::LBrace)?;
+        let mut decls = Declarations::default();
+        while !self.eat(&Token::RBrace) {
+            let pos = self.pos();
+            match self.ident()?.as_str() {
+                "type" => {
+                    let (name, def) = self.type_def()?;
+                    if decls.types.iter().any(|(n, _)| *n == name) {
+                        return Err(pos.error(format!("duplicate definition of type '{name}'")));
+                    }
+                    decls.types.push((name, def));
+                }
+                "node" => decls.nodes.push(self.node(pos)?),
+                "edge" => decls.edges.push(self.edge()?),
+                "region" => decls.regions.push(self.region(pos)?),
+                other => {
+                    return Err(pos.error(format!(
+                        "expected 'type', 'node', 'edge' or 'region', found '{other}'"
+                    )))
+                }
+            }
+        }
+        Ok(decls)
+    }
+
+    /// `Name<T, U; N, M> = body;`, after the `type` keyword.
+    fn type_def(&mut self) -> Result<(String, TypeDef)> {
+        let name = self.name()?;
+        let mut def = TypeDef::alias(Type::Unit);
+        if self.eat(&Token::Lt) {
+            while !matches!(self.peek(), Token::Semi | Token::Gt) {
+                def.type_params.push(self.name()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+            if self.eat(&Token::Semi) {
+                while !matches!(self.peek(), Token::Gt) {
+                    def.value_params.push(self.name()?);
+                    if !self.eat(&Token::Comma) {
+                        break;
+                    }
+                }
+            }
+            self.expect(Token::Gt)?;
+        }
+        self.expect(Token::Assign)?;
+        def.body = self.ty()?;
+        self.expect(Token::Semi)?;
+        Ok((name, def))
+    }
+
+    fn node(&mut self, pos: Pos) -> Result<(String, Pos, Node)> {
+        let label = self.ident()?;
+        let id = self.optional_id().unwrap_or_else(Uuid::new_v4);
+        self.expect(Token::Assign)?;
+        let mut node = Node::with_id(id, self.node_kind()?);
+
+        if self.eat(&Token::Semi) {
+            return Ok((label, pos, node));
+        }
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            let item_pos = self.pos();
+            match self.ident()?.as_str() {
+                "sig" => {
+                    let inputs = self.type_list()?;
+                    self.expect(Token::Arrow)?;
+                    let outputs = self.type_list()?;
+                    self.expect(Token::Semi)?;
+                    node.type_signature = Some(TypeSignature::new(inputs, outputs));
+                }
+                "value" => {
+                    node.value = Some(self.value()?);
+                    self.expect(Token::Semi)?;
+                }
+                "contract" => node.contract = Some(self.contract()?),
+                "provenance" => node.provenance = Some(self.provenance(item_pos)?),
+                "annotate" => {
+                    let key = self.string()?;
+                    self.expect(Token::Assign)?;
+                    let value = self.string()?;
+                    self.expect(Token::Semi)?;
+                    node.annotations.insert(key, value);
+                }
+                other => return Err(item_pos.error(format!("unknown node item '{other}'"))),
+            }
+        }
+        Ok((label, pos, node))
+    }
+
+    fn node_kind(&mut self) -> Result<NodeKind> {
+        let pos = self.pos();
+        let name = self.ident()?;
+        let mut operand = || -> Result<String> {
+            self.expect(Token::LParen)?;
+            let op = self.ident()?;
+            self.expect(Token::RParen)?;
+            Ok(op)
+        };
+        let bad_op = |op: &str| pos.error(format!("unknown operator '{op}' for {name}"));
+        let kind = match name.as_str() {
+            "Literal" => NodeKind::Literal,
+            "Arithmetic" => {
+                let op = operand()?;
+                NodeKind::Arithmetic(match op.as_str() {
+                    "Add" => ArithmeticOp::Add,
+                    "Sub" => ArithmeticOp::Sub,
+                    "Mul" => ArithmeticOp::Mul,
+                    "Div" => ArithmeticOp::Div,
+                    "Mod" => ArithmeticOp::Mod,
+                    "Pow" => ArithmeticOp::Pow,
+                    _ => return Err(bad_op(&op)),
+                })
+            }
+            "Bitwise" => {
+                let op = operand()?;
+                NodeKind::Bitwise(match op.as_str() {
+                    "And" => BitwiseOp::And,
+                    "Or" => BitwiseOp::Or,
+                    "Xor" => BitwiseOp::Xor,
+                    "Not" => BitwiseOp::Not,
+                    "ShiftLeft" => BitwiseOp::ShiftLeft,
+                    "ShiftRight" => BitwiseOp::ShiftRight,
+                    "Rotate" => BitwiseOp::Rotate,
+                    _ => return Err(bad_op(&op)),
+                })
+            }
+            "Comparison" => {
+                let op = operand()?;
+                NodeKind::Comparison(match op.as_str() {
+                    "Eq" => ComparisonOp::Eq,
+                    "Ne" => ComparisonOp::Ne,
+                    "Lt" => ComparisonOp::Lt,
+                    "Le" => ComparisonOp::Le,
+                    "Gt" => ComparisonOp::Gt,
+                    "Ge" => ComparisonOp::Ge,
+                    _ => return Err(bad_op(&op)),
+                })
+            }
+            "Atomic" | "Fence" => {
+                let op = operand()?;
+                let ordering = match op.as_str() {
+                    "Relaxed" => MemoryOrdering::Relaxed,
+                    "Acquire" => MemoryOrdering::Acquire,
+                    "Release" => MemoryOrdering::Release,
+                    "AcqRel" => MemoryOrdering::AcqRel,
+                    "SeqCst" => MemoryOrdering::SeqCst,
+                    _ => return Err(bad_op(&op)),
+                };
+                if name == "Atomic" {
+                    NodeKind::Atomic(ordering)
+                } else {
+                    NodeKind::Fence(ordering)
+                }
+            }
+            "Conversion" => NodeKind::Conversion,
+            "Construct" => NodeKind::Construct,
+            "Destructure" => NodeKind::Destructure,
+            "Index" => NodeKind::Index,
+            "Slice" => NodeKind::Slice,
+            "Select" => NodeKind::Select,
+            "Switch" => NodeKind::Switch,
+            "Iterate" => NodeKind::Iterate,
+            "Recurse" => NodeKind::Recurse,
+            "Fixpoint" => NodeKind::Fixpoint,
+            "Allocate" => NodeKind::Allocate,
+            "Deallocate" => NodeKind::Deallocate,
+            "Read" => NodeKind::Read,
+            "Write" => NodeKind::Write,
+            "Syscall" => NodeKind::Syscall,
+            "FFICall" => NodeKind::FFICall,
+            "Verify" => NodeKind::Verify,
+            "Assume" => NodeKind::Assume,
+            "Measure" => NodeKind::Measure,
+            "Checkpoint" => NodeKind::Checkpoint,
+            "Annotate" => NodeKind::Annotate,
+            "Sample" => NodeKind::Sample,
+            "Condition" => NodeKind::Condition,
+            "Expectation" => NodeKind::Expectation,
+            "Entropy" => NodeKind::Entropy,
+            "Approximate" => NodeKind::Approximate,
+            other => return Err(pos.error(format!("unknown node kind '{other}'"))),
+        };
+        Ok(kind)
+    }
+
+    /// A parenthesized, comma-separated list of types (signature side).
+    fn type_list(&mut self) -> Result<Vec<Type>> {
+        self.expect(Token::LParen)?;
+        let mut types = Vec::new();
+        if !self.eat(&Token::RParen) {
+            loop {
+                types.push(self.ty()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+            self.expect(Token::RParen)?;
+        }
+        Ok(types)
+    }
+
+    fn contract(&mut self) -> Result<Contract> {
+        let mut contract = Contract::pure_default();
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            let pos = self.pos();
+            match self.ident()?.as_str() {
+                "bind" => {
+                    let var = self.name()?;
+                    self.expect(Token::Assign)?;
+                    let binding = match self.ident()?.as_str() {
+                        "in" => PortBinding::input(self.integer()?),
+                        "out" => PortBinding::output(self.integer()?),
+                        other => {
+                            return Err(pos.error(format!(
+                                "expected port direction 'in' or 'out', found '{other}'"
+                            )))
+                        }
+                    };
+                    contract.bindings.insert(var, binding);
+                }
+                "requires" => contract.preconditions.push(self.predicate()?),
+                "variant" => contract.variant = Some(self.ranking()?),
+                "ensures" => contract.postconditions.push(self.predicate()?),
+                "time" => {
+                    let mut tb = TimeBound {
+                        wcet_ns: None,
+                        bcet_ns: None,
+                        avg_ns: None,
+                        target: None,
+                    };
+                    while !matches!(self.peek(), Token::Semi) {
+                        match self.ident()?.as_str() {
+                            "wcet" => tb.wcet_ns = Some(self.integer()?),
+                            "bcet" => tb.bcet_ns = Some(self.integer()?),
+                            "avg" => tb.avg_ns = Some(self.integer()?),
+                            "target" => tb.target = Some(self.string()?),
+                            other => {
+                                return Err(pos.error(format!("unknown time bound field '{other}'")))
+                            }
+                        }
+                    }
+                    contract.time_bound = Some(tb);
+                }
+                "memory" => {
+                    let mut mb = MemoryBound {
+                        peak_bytes: None,
+                        allocated_bytes: None,
+                        freed_bytes: None,
+                    };
+                    while !matches!(self.peek(), Token::Semi) {
+                        match self.ident()?.as_str() {
+                            "peak" => mb.peak_bytes = Some(self.integer()?),
+                            "allocated" => mb.allocated_bytes = Some(self.integer()?),
+                            "freed" => mb.freed_bytes = Some(self.integer()?),
+                            other => {
+                                return Err(
+                                    pos.error(format!("unknown memory bound field '{other}'"))
+                                )
+                            }
+                        }
+                    }
+                    contract.memory_bound = Some(mb);
+                }
+                "energy" => {
+                    contract.energy_bound = Some(EnergyBound {
+                        max_uj: self.integer()?,
+                    })
+                }
+                "stack" => {
+                    contract.stack_bound = Some(StackBound {
+                        max_bytes: self.integer()?,
+                    })
+                }
+                "effects" => {
+                    self.expect(Token::LBracket)?;
+                    let mut effects = Vec::new();
+                    if !self.eat(&Token::RBracket) {
+                        loop {
+                            effects.push(self.effect()?);
+                            if !self.eat(&Token::Comma) {
+                                break;
+                            }
+                        }
+                        self.expect(Token::RBracket)?;
+                    }
+                    contract.effects = EffectSet::from_effects(effects);
+                }
+                "failure" => {
+                    let name = self.string()?;
+                    let description = self.string()?;
+                    self.expect_keyword("recover")?;
+                    let recovery = self.recovery()?;
+                    contract.failure_modes.push(FailureMode {
+                        name,
+                        description,
+                        recovery,
+                    });
+                }
+                "recovery" => contract.recovery_strategy = self.recovery()?,
+                "status" => {
+                    contract.proof_status = match self.ident()?.as_str() {
+                        "verified" => ProofStatus::Verified,
+                        "assumed" => ProofStatus::Assumed,
+                        "pending" => ProofStatus::Pending,
+                        "waived" => ProofStatus::Waived,
+                        other => return Err(pos.error(format!("unknown proof status '{other}'"))),
+                    }
+                }
+                "witness" => {
+                    let hash = self.string()?;
+                    self.expect_keyword("solver")?;
+                    let solver = self.string()?;
+                    self.expect_keyword("data")?;
+                    let data_pos = self.pos();
+                    let data = decode_hex(&self.string()?)
+                        .ok_or_else(|| data_pos.error("witness data must be hex"))?;
+                    contract.proof_witness = Some(ProofWitness { hash, solver, data });
+                }
+                other => return Err(pos.error(format!("unknown contract item '{other}'"))),
+            }
+            self.expect(Token::Semi)?;
+        }
+        Ok(contract)
+    }
+
+    fn effect(&mut self) -> Result<Effect> {
+        let pos = self.pos();
+        let name = self.ident()?;
+        let mut arg = || -> Result<String> {
+            self.expect(Token::LParen)?;
+            let s = self.string()?;
+            self.expect(Token::RParen)?;
+            Ok(s)
+        };
+        Ok(match name.as_str() {
+            "Pure" => Effect::Pure,
+            "Alloc" => Effect::Alloc(arg()?),
+            "IO" => Effect::IO(arg()?),
+            "Atomic" => Effect::Atomic(arg()?),
+            "FFI" => Effect::FFI(arg()?),
+            "Diverge" => Effect::Diverge,
+            "Panic" => Effect::Panic,
+            other => return Err(pos.error(format!("unknown effect '{other}'"))),
+        })
+    }
+
+    fn recovery(&mut self) -> Result<RecoveryStrategy> {
+        let pos = self.pos();
+        Ok(match self.ident()?.as_str() {
+            "abort" => RecoveryStrategy::Abort,
+            "propagate" => RecoveryStrategy::Propagate,
+            "retry" => {
+                self.expect(Token::LParen)?;
+                let n = self.integer()?;
+                self.expect(Token::RParen)?;
+                RecoveryStrategy::Retry(n)
+            }
+            "degrade" => {
+                self.expect(Token::LParen)?;
+                let v = self.string()?;
+                self.expect(Token::RParen)?;
+                RecoveryStrategy::Degrade(v)
+            }
+            other => return Err(pos.error(format!("unknown recovery strategy '{other}'"))),
+        })
+    }
+
+    fn author(&mut self) -> Result<Author> {
+        let pos = self.pos();
+        let kind = self.ident()?;
+        self.expect(Token::LParen)?;
+        let author = match kind.as_str() {
+            "ai" => {
+                let model = self.string()?;
+                self.expect(Token::Comma)?;
+                let provider = self.string()?;
+                self.expect(Token::Comma)?;
+                let version = self.string()?;
+                Author::AI {
+                    model,
+                    provider,
+                    version,
+                }
+            }
+            "human" => Author::Human {
+                identity: self.string()?,
+            },
+            "toolchain" => Author::Toolchain {
+                version: self.string()?,
+            },
+            other => return Err(pos.error(format!("unknown author kind '{other}'"))),
+        };
+        self.expect(Token::RParen)?;
+        Ok(author)
+    }
+
+    fn provenance(&mut self, start: Pos) -> Result<Provenance> {
+        let mut created = None;
+        let mut reason = String::new();
+        let mut requirements = Vec::new();
+        let mut edit_history = Vec::new();
+
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            let pos = self.pos();
+            match self.ident()?.as_str() {
+                "created" => {
+                    let timestamp = self.string()?;
+                    self.expect_keyword("by")?;
+                    created = Some((timestamp, self.author()?));
+                }
+                "reason" => reason = self.string()?,
+                "requirement" => {
+                    let mut link = RequirementLink {
+                        id: self.string()?,
+                        document: None,
+                        description: None,
+                    };
+                    if self.eat_keyword("document") {
+                        link.document = Some(self.string()?);
+                    }
+                    if self.eat_keyword("description") {
+                        link.description = Some(self.string()?);
+                    }
+                    requirements.push(link);
+                }
+                "edit" => {
+                    let timestamp = self.string()?;
+                    self.expect_keyword("by")?;
+                    let author = self.author()?;
+                    let description = self.string()?;
+                    let previous_hash = if self.eat_keyword("previous") {
+                        Some(self.string()?)
+                    } else {
+                        None
+                    };
+                    edit_history.push(EditRecord {
+                        timestamp,
+                        author,
+                        description,
+                        previous_hash,
+                    });
+                }
+                other => return Err(pos.error(format!("unknown provenance item '{other}'"))),
+            }
+            self.expect(Token::Semi)?;
+        }
+
+        let (created, created_by) =
+            created.ok_or_else(|| start.error("provenance requires a 'created' entry"))?;
+        Ok(Provenance {
+            created,
+            created_by,
+            creation_reason: reason,
+            requirements,
+            edit_history,
+        })
+    }
+
+    fn edge(&mut self) -> Result<EdgeDecl> {
+        let id = self.optional_id();
+        let source = self.port_ref()?;
+        self.expect(Token::Arrow)?;
+        let target = self.port_ref()?;
+
+        let mut decl = EdgeDecl {
+            id,
+            source,
+            target,
+            data_type: None,
+            lifetime: LifetimeDecl::Resolved(Lifetime::Static),
+            bandwidth: None,
+        };
+        if self.eat(&Token::Colon) {
+            decl.data_type = Some(self.ty()?);
+        }
+        if self.eat_keyword("lifetime") {
+            let pos = self.pos();
+            decl.lifetime = match self.ident()?.as_str() {
+                "static" => LifetimeDecl::Resolved(Lifetime::Static),
+                "manual" => LifetimeDecl::Resolved(Lifetime::Manual),
+                "bounded" => {
+                    self.expect(Token::LParen)?;
+                    let ns = self.integer()?;
+                    self.expect(Token::RParen)?;
+                    LifetimeDecl::Resolved(Lifetime::Bounded(ns))
+                }
+                "region" => {
+                    self.expect(Token::LParen)?;
+                    let r = self.reference()?;
+                    self.expect(Token::RParen)?;
+                    LifetimeDecl::Region(r)
+                }
+                other => return Err(pos.error(format!("unknown lifetime '{other}'"))),
+            };
+        }
+        if self.eat_keyword("bandwidth") {
+            self.expect_keyword("min")?;
+            let min = self.integer()?;
+            let max = if self.eat_keyword("max") {
+                Some(self.integer()?)
+            } else {
+                None
+            };
+            if max.is_some_and(|max| max < min) {
+                return Err(self.pos().error("bandwidth max is below min"));
+            }
+            decl.bandwidth = Some(BandwidthConstraint {
+                min_bytes_per_sec: min,
+                max_bytes_per_sec: max,
+            });
+        }
+        self.expect(Token::Semi)?;
+        Ok(decl)
+    }
+
+    fn port_ref(&mut self) -> Result<(Ref, usize)> {
+        let r = self.reference()?;
+        self.expect(Token::Dot)?;
+        Ok((r, self.integer()?))
+    }
+
+    fn region(&mut self, pos: Pos) -> Result<RegionDecl> {
+        let label = self.ident()?;
+        let id = self.optional_id();
+        self.expect(Token::Assign)?;
+        let kind_pos = self.pos();
+        let kind = match self.ident()?.as_str() {
+            "sequential" => RegionKind::Sequential,
+            "parallel" => RegionKind::Parallel,
+            "conditional" => RegionKind::Conditional,
+            "iterative" => RegionKind::Iterative,
+            "atomic" => RegionKind::Atomic,
+            other => return Err(kind_pos.error(format!("unknown region kind '{other}'"))),
+        };
+        let children = self.reference_list()?;
+        let mut decl = RegionDecl {
+            label,
+            pos,
+            id,
+            kind,
+            children,
+            parent: None,
+            constraints: Vec::new(),
+            interfaces: Vec::new(),
+            variant: None,
+        };
+
+        if self.eat(&Token::Semi) {
+            return Ok(decl);
+        }
+        self.expect(Token::LBrace)?;
+        while !self.eat(&Token::RBrace) {
+            let item_pos = self.pos();
+            match self.ident()?.as_str() {
+                "parent" => decl.parent = Some(self.reference()?),
+                "variant" => decl.variant = Some(self.ranking()?),
+                "constraint" => {
+                    let c_pos = self.pos();
+                    let constraint = match self.ident()?.as_str() {
+                        "max_time" => Constraint::MaxTime(self.integer()?),
+                        "max_memory" => Constraint::MaxMemory(self.integer()?),
+                        "max_energy" => Constraint::MaxEnergy(self.integer()?),
+                        "max_iterations" => Constraint::MaxIterations(self.integer()?),
+                        "custom" => Constraint::Custom {
+                            name: self.string()?,
+                            description: self.string()?,
+                        },
+                        other => return Err(c_pos.error(format!("unknown constraint '{other}'"))),
+                    };
+                    decl.constraints.push(constraint);
+                }
+                "port" => {
+                    let dir_pos = self.pos();
+                    let direction = self.ident()?;
+                    let name = self.string()?;
+                    let index = self.integer()?;
+                    self.expect(Token::Colon)?;
+                    let ty = self.ty()?;
+                    decl.interfaces.push(match direction.as_str() {
+                        "in" => Port::input(name, index, ty),
+                        "out" => Port::output(name, index, ty),
+                        other => {
+                            return Err(dir_pos.error(format!(
+                                "expected port direction 'in' or 'out', found '{other}'"
+                            )))
+                        }
+                    });
+                }
+                other => return Err(item_pos.error(format!("unknown region item '{other}'"))),
+            }
+            self.expect(Token::Semi)?;
+        }
+        Ok(decl)
+    }
+
+    // === Types ===
+
+    fn ty(&mut self) -> Result<Type> {
+        let pos = self.pos();
+        match self.next() {
+            Token::LParen => {
+                if self.eat(&Token::RParen) {
+                    return Ok(Type::Tuple(Vec::new()));
+                }
+                let first = self.ty()?;
+                if self.eat_keyword("where") {
+                    let predicate = self.predicate()?;
+                    self.expect(Token::RParen)?;
+                    return Ok(first.refined(predicate));
+                }
+                if !self.eat(&Token::Comma) {
+                    // Plain grouping parentheses.
+                    self.expect(Token::RParen)?;
+                    return Ok(first);
+                }
+                let mut elems = vec![first];
+                while !self.eat(&Token::RParen) {
+                    elems.push(self.ty()?);
+                    if !self.eat(&Token::Comma) {
+                        self.expect(Token::RParen)?;
+                        break;
+                    }
+                }
+                Ok(Type::Tuple(elems))
+            }
+            Token::LBrace => {
+                let mut fields = BTreeMap::new();
+                if !self.eat(&Token::RBrace) {
+                    loop {
+                        let name = self.field_name()?;
+                        self.expect(Token::Colon)?;
+                        fields.insert(name, self.ty()?);
+                        if !self.eat(&Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(Token::RBrace)?;
+                }
+                Ok(Type::Record(fields))
+            }
+            Token::LBracket => {
+                let element = Box::new(self.ty()?);
+                self.expect(Token::Semi)?;
+                let length = self.integer()?;
+                self.expect(Token::RBracket)?;
+                Ok(Type::Array { element, length })
+            }
+            Token::Ident(name) => self.named_type(name, pos),
+            other => Err(pos.error(format!("expected type, found {other}"))),
+        }
+    }
+
+    fn field_name(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Ident(s) | Token::Str(s) => {
+                self.next();
+                Ok(s)
+            }
+            _ => Err(self.unexpected("field name")),
+        }
+    }
+
+    /// Parse `<inner>` or `<inner, ...>` after a wrapper keyword.
+    fn wrapped(&mut self) -> Result<Box<Type>> {
+        self.expect(Token::Lt)?;
+        Ok(Box::new(self.ty()?))
+    }
+
+    fn named_type(&mut self, name: String, pos: Pos) -> Result<Type> {
+        let ty = match name.as_str() {
+            "Void" => Type::Void,
+            "Unit" => Type::Unit,
+            "Bool" => Type::Bool,
+            "Fixed" => {
+                self.expect(Token::Lt)?;
+                let total_bits = self.integer()?;
+                self.expect(Token::Comma)?;
+                let frac_bits = self.integer()?;
+                self.expect(Token::Gt)?;
+                Type::Fixed {
+                    total_bits,
+                    frac_bits,
+                }
+            }
+            "Variant" => {
+                self.expect(Token::Lt)?;
+                let mut cases = BTreeMap::new();
+                if !self.eat(&Token::Gt) {
+                    loop {
+                        let tag = self.field_name()?;
+                        self.expect(Token::LParen)?;
+                        cases.insert(tag, self.ty()?);
+                        self.expect(Token::RParen)?;
+                        if !self.eat(&Token::Pipe) {
+                            break;
+                        }
+                    }
+                    self.expect(Token::Gt)?;
+                }
+                Type::Variant(cases)
+            }
+            "Vec" => {
+                let element = self.wrapped()?;
+                self.expect(Token::Gt)?;
+                Type::Vec { element }
+            }
+            "Option" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Gt)?;
+                Type::Option(inner)
+            }
+            "Distribution" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Gt)?;
+                Type::Distribution(inner)
+            }
+            "Linear" | "Affine" | "Shared" | "Unique" | "Counted" | "Unrestricted" => {
+                let linearity = match name.as_str() {
+                    "Linear" => Linearity::Linear,
+                    "Affine" => Linearity::Affine,
+                    "Shared" => Linearity::Shared,
+                    "Unique" => Linearity::Unique,
+                    "Counted" => Linearity::Counted,
+                    _ => Linearity::Unrestricted,
+                };
+                let inner = self.wrapped()?;
+                self.expect(Token::Gt)?;
+                Type::Linear { inner, linearity }
+            }
+            "Timed" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let wcet_ns = self.quantity("ns")?;
+                self.expect(Token::Comma)?;
+                let target = self.string()?;
+                self.expect(Token::Gt)?;
+                Type::Timed {
+                    inner,
+                    bound: TypeTimeBound { wcet_ns, target },
+                }
+            }
+            "Sized" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let max_bytes = self.quantity("B")?;
+                self.expect(Token::Gt)?;
+                Type::Sized { inner, max_bytes }
+            }
+            "Powered" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let energy_uj = self.quantity("uJ")?;
+                self.expect(Token::Gt)?;
+                Type::Powered { inner, energy_uj }
+            }
+            "Bandwidth" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let min_bps = self.quantity("Bps")?;
+                self.expect(Token::Gt)?;
+                Type::Bandwidth { inner, min_bps }
+            }
+            "Posterior" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let evidence = self.string()?;
+                self.expect(Token::Gt)?;
+                Type::Posterior { inner, evidence }
+            }
+            "Interval" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let confidence = self.float()?;
+                self.expect(Token::Gt)?;
+                Type::Interval { inner, confidence }
+            }
+            "Approximate" => {
+                let inner = self.wrapped()?;
+                self.expect(Token::Comma)?;
+                let max_error = self.float()?;
+                self.expect(Token::Gt)?;
+                Type::Approximate { inner, max_error }
+            }
+            "Named" => {
+                self.expect(Token::LParen)?;
+                let name = self.string()?;
+                self.expect(Token::RParen)?;
+                return self.maybe_parameterized(name);
+            }
+            _ => {
+                if let Some(ty) = primitive_numeric(&name, pos)? {
+                    return Ok(ty);
+                }
+                return self.maybe_parameterized(name);
+            }
+        };
+        Ok(ty)
+    }
+
+    /// A user-defined type name, optionally followed by `<types; values>`.
+    fn maybe_parameterized(&mut self, name: String) -> Result<Type> {
+        if !self.eat(&Token::Lt) {
+            return Ok(Type::Named(name));
+        }
+        let mut type_params = Vec::new();
+        if !matches!(self.peek(), Token::Semi) {
+            loop {
+                type_params.push(self.ty()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+        self.expect(Token::Semi)?;
+        let mut value_params = Vec::new();
+        if !matches!(self.peek(), Token::Gt) {
+            loop {
+                value_params.push(self.value_param()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+        self.expect(Token::Gt)?;
+        Ok(Type::Parameterized {
+            name,
+            type_params,
+            value_params,
+        })
+    }
+
+    fn value_param(&mut self) -> Result<ValueParam> {
+        match self.peek() {
+            Token::Ident(_) | Token::QuotedIdent(_) => Ok(ValueParam::Symbolic(self.name()?)),
+            _ => Ok(ValueParam::Concrete(self.signed_integer()?)),
+        }
+    }
+
+    fn signed_integer(&mut self) -> Result<i128> {
+        let pos = self.pos();
+        let negative = self.eat(&Token::Minus);
+        let magnitude: u128 = self.integer()?;
+        signed(magnitude, negative).ok_or_else(|| pos.error("integer literal out of range"))
+    }
+
+    // === Values ===
+
+    fn value(&mut self) -> Result<Value> {
+        let pos = self.pos();
+        match self.next() {
+            Token::LParen => {
+                if self.eat(&Token::RParen) {
+                    return Ok(Value::Unit);
+                }
+                let first = self.value()?;
+                if !self.eat(&Token::Comma) {
+                    // Plain grouping parentheses.
+                    self.expect(Token::RParen)?;
+                    return Ok(first);
+                }
+                let mut elems = vec![first];
+                while !self.eat(&Token::RParen) {
+                    elems.push(self.value()?);
+                    if !self.eat(&Token::Comma) {
+                        self.expect(Token::RParen)?;
+                        break;
+                    }
+                }
+                Ok(Value::Tuple(elems))
+            }
+            Token::LBrace => {
+                let mut fields = BTreeMap::new();
+                if !self.eat(&Token::RBrace) {
+                    loop {
+                        let name = self.field_name()?;
+                        self.expect(Token::Colon)?;
+                        fields.insert(name, self.value()?);
+                        if !self.eat(&Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(Token::RBrace)?;
+                }
+                Ok(Value::Record(fields))
+            }
+            Token::LBracket => Ok(Value::Array(self.value_list()?)),
+            Token::Minus => self.scalar(self.peek().clone(), true, pos),
+            tok @ (Token::Int(_) | Token::Float(_)) => self.scalar(tok, false, pos),
+            Token::Ident(s) => match s.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "inf" | "NaN" => self.scalar(Token::Ident(s), false, pos),
+                "None" => Ok(Value::Option(None)),
+                "Some" => {
+                    self.expect(Token::LParen)?;
+                    let inner = self.value()?;
+                    self.expect(Token::RParen)?;
+                    Ok(Value::some(inner))
+                }
+                "vec" => {
+                    self.expect(Token::LBracket)?;
+                    Ok(Value::Vec(self.value_list()?))
+                }
+                "Fixed" => {
+                    self.expect(Token::Lt)?;
+                    let total_bits: u8 = self.integer()?;
+                    self.expect(Token::Comma)?;
+                    let frac_bits: u8 = self.integer()?;
+                    self.expect(Token::Gt)?;
+                    self.expect(Token::LParen)?;
+                    let raw = self.signed_integer()?;
+                    self.expect(Token::RParen)?;
+                    if total_bits == 0 || total_bits > 128 || frac_bits > total_bits {
+                        return Err(pos.error(format!(
+                            "invalid fixed-point format <{total_bits}, {frac_bits}>"
+                        )));
+                    }
+                    let v = FixedValue::from_raw(raw, total_bits, frac_bits);
+                    if v.raw() != raw {
+                        return Err(pos.error(format!(
+                            "{raw} does not fit in Fixed<{total_bits}, {frac_bits}>"
+                        )));
+                    }
+                    Ok(Value::Fixed(v))
+                }
+                _ => self.variant(s),
+            },
+            Token::QuotedIdent(tag) => self.variant(tag),
+            other => Err(pos.error(format!("expected value, found {other}"))),
+        }
+    }
+
+    /// The elements of a `[...]` list after the opening bracket.
+    fn value_list(&mut self) -> Result<Vec<Value>> {
+        let mut elems = Vec::new();
+        if !self.eat(&Token::RBracket) {
+            loop {
+                elems.push(self.value()?);
+                if !self.eat(&Token::Comma) {
+                    break;
+                }
+            }
+            self.expect(Token::RBracket)?;
+        }
+        Ok(elems)
+    }
+
+    fn variant(&mut self, tag: String) -> Result<Value> {
+        self.expect(Token::LParen)?;
+        let payload = self.value()?;
+        self.expect(Token::RParen)?;
+        Ok(Value::variant(tag, payload))
+    }
+
+    /// A numeric literal with its type suffix, e.g. `-5i8`, `1.5f32`, `inf f64`.
+    ///
+    /// `first` is the magnitude token, already consumed unless `negative`.
+    fn scalar(&mut self, first: Token, negative: bool, pos: Pos) -> Result<Value> {
+        if negative {
+            self.next();
+        }
+        let suffix_pos = self.pos();
+        let suffix = self.ident()?;
+        let ty = primitive_numeric(&suffix, suffix_pos)?
+            .ok_or_else(|| suffix_pos.error(format!("expected numeric type, found '{suffix}'")))?;
+        let out_of_range = || pos.error(format!("literal out of range for {suffix}"));
+
+        match (first, ty) {
+            (Token::Int(n), Type::Int { width, signedness }) => {
+                if !(1..=128).contains(&width) {
+                    return Err(suffix_pos.error(format!("invalid integer width in '{suffix}'")));
+                }
+                let v = if negative {
+                    let value = signed(n, true).ok_or_else(out_of_range)?;
+                    IntValue::checked(value, width, signedness)
+                } else {
+                    IntValue::checked_unsigned(n, width, signedness)
+                };
+                v.map(Value::Int).ok_or_else(out_of_range)
+            }
+            (first, Type::Float { precision }) => {
+                let magnitude = match first {
+                    Token::Float(x) => x,
+                    Token::Int(n) => n as f64,
+                    Token::Ident(s) if s == "inf" => f64::INFINITY,
+                    Token::Ident(s) if s == "NaN" => f64::NAN,
+                    other => return Err(pos.error(format!("expected number, found {other}"))),
+                };
+                let x = if negative { -magnitude } else { magnitude };
+                Ok(Value::Float(FloatValue::new(x, precision)))
+            }
+            (_, ty) => Err(pos.error(format!("literal does not match suffix type {ty}"))),
+        }
+    }
+
+    // === Predicates ===
+
+    /// `<measure> -> <next> [bound <lower>]`
+    fn ranking(&mut self) -> Result<Variant> {
+        let measure = self.predicate()?;
+        self.expect(Token::Arrow)?;
+        let variant = Variant::new(measure, self.predicate()?);
+        if self.at_keyword("bound") {
+            self.ident()?;
+            return Ok(variant.with_lower_bound(self.predicate()?));
+        }
+        Ok(variant)
+    }
+
+    fn predicate(&mut self) -> Result<Predicate> {
+        if self.at_keyword("forall") || self.at_keyword("exists") {
+            let forall = self.ident()? == "forall";
+            let var = self.name()?;
+            self.expect_keyword("in")?;
+            let range = Box::new(self.implies()?);
+            self.expect(Token::Colon)?;
+            let body = Box::new(self.predicate()?);
+            return Ok(if forall {
+                Predicate::ForAll { var, range, body }
+            } else {
+                Predicate::Exists { var, range, body }
+            });
+        }
+        self.implies()
+    }
+
+    fn implies(&mut self) -> Result<Predicate> {
+        let lhs = self.or()?;
+        if self.eat(&Token::FatArrow) {
+            let rhs = self.implies()?;
+            return Ok(Predicate::Implies(Box::new(lhs), Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Predicate> {
+        let mut lhs = self.and()?;
+        while self.eat(&Token::OrOr) {
+            lhs = Predicate::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Predicate> {
+        let mut lhs = self.not()?;
+        while self.eat(&Token::AndAnd) {
+            lhs = Predicate::And(Box::new(lhs), Box::new(self.not()?));
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Predicate> {
+        if self.eat(&Token::Bang) {
+            return Ok(Predicate::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Predicate> {
+        let lhs = self.additive()?;
+        let op: fn(Box<Predicate>, Box<Predicate>) -> Predicate = match self.peek() {
+            Token::EqEq => Predicate::Eq,
+            Token::Ne => Predicate::Ne,
+            Token::Lt => Predicate::Lt,
+            Token::Le => Predicate::Le,
+            Token::Gt => Predicate::Gt,
+            Token::Ge => Predicate::Ge,
+            _ => return Ok(lhs),
+        };
+        self.next();
+        let rhs = self.additive()?;
+        Ok(op(Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn additive(&mut self) -> Result<Predicate> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op: fn(Box<Predicate>, Box<Predicate>) -> Predicate = match self.peek() {
+                Token::Plus => Predicate::Add,
+                Token::Minus => Predicate::Sub,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            let rhs = self.multiplicative()?;
+            lhs = op(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Predicate> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op: fn(Box<Predicate>, Box<Predicate>) -> Predicate = match self.peek() {
+                Token::Star => Predicate::Mul,
+                Token::Slash => Predicate::Div,
+                Token::Percent => Predicate::Mod,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            let rhs = self.unary()?;
+            lhs = op(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    /// Unary minus. `-` directly before a numeric literal is part of the literal.
+    fn unary(&mut self) -> Result<Predicate> {
+        if !matches!(self.peek(), Token::Minus) {
+            return self.atom();
+        }
+        let pos = self.pos();
+        match self.peek_ahead(1).clone() {
+            Token::Int(n) => {
+                self.next();
+                self.next();
+                signed(n, true)
+                    .map(Predicate::IntLit)
+                    .ok_or_else(|| pos.error("integer literal out of range"))
+            }
+            Token::Float(_) => Ok(Predicate::FloatLit(self.float()?)),
+            Token::Ident(s) if s == "inf" || s == "NaN" => Ok(Predicate::FloatLit(self.float()?)),
+            _ => {
+                self.next();
+                Ok(Predicate::Neg(Box::new(self.unary()?)))
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<Predicate> {
+        let pos = self.pos();
+        match self.next() {
+            Token::LParen => {
+                let inner = self.predicate()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Token::Int(n) => signed(n, false)
+                .map(Predicate::IntLit)
+                .ok_or_else(|| pos.error("integer literal out of range")),
+            Token::Float(x) => Ok(Predicate::FloatLit(x)),
+            Token::Ident(s) if s == "true" => Ok(Predicate::BoolLit(true)),
+            Token::Ident(s) if s == "false" => Ok(Predicate::BoolLit(false)),
+            Token::Ident(s) if s == "inf" => Ok(Predicate::FloatLit(f64::INFINITY)),
+            Token::Ident(s) if s == "NaN" => Ok(Predicate::FloatLit(f64::NAN)),
+            Token::Ident(name) | Token::QuotedIdent(name) => {
+                if !self.eat(&Token::LParen) {
+                    return Ok(Predicate::Var(name));
+                }
+                let mut args = Vec::new();
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        args.push(self.predicate()?);
+                        if !self.eat(&Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(Token::RParen)?;
+                }
+                Ok(Predicate::Apply(name, args))
+            }
+            other => Err(pos.error(format!("expected expression, found {other}"))),
+        }
+    }
+}
+
+/// Parse `i<N>`, `u<N>` and `f<N>` primitive names.
+fn primitive_numeric(name: &str, pos: Pos) -> Result<Option<Type>> {
+    let mut chars = name.chars();
+    let prefix = chars.next();
+    let digits = chars.as_str();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+    let ty = match prefix {
+        Some('i' | 'u') => {
+            let width = digits
+                .parse::<u8>()
+                .map_err(|_| pos.error(format!("integer width in '{name}' out of range")))?;
+            let signedness = if prefix == Some('i') {
+                Signedness::Signed
+            } else {
+                Signedness::Unsigned
+            };
+            Type::Int { width, signedness }
+        }
+        Some('f') => {
+            let precision = match digits {
+                "16" => FloatPrecision::F16,
+                "32" => FloatPrecision::F32,
+                "64" => FloatPrecision::F64,
+                "128" => FloatPrecision::F128,
+                _ => return Err(pos.error(format!("unsupported float precision '{name}'"))),
+            };
+            Type::Float { precision }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(ty))
+}
+
+/// Apply a sign to an unsigned literal magnitude, checking the i128 range.
+fn signed(magnitude: u128, negative: bool) -> Option<i128> {
+    if negative {
+        if magnitude == i128::MIN.unsigned_abs() {
+            Some(i128::MIN)
+        } else {
+            i128::try_from(magnitude).ok().map(|v| -v)
+        }
+    } else {
+        i128::try_from(magnitude).ok()
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predicate_precedence() {
+        let p = parse_predicate("a + b * c >= 0 && !done || x => y").unwrap();
+        // ((a + (b * c) >= 0) && !done || x) => y
+        match p {
+            Predicate::Implies(lhs, rhs) => {
+                assert_eq!(*rhs, Predicate::Var("y".into()));
+                assert!(matches!(*lhs, Predicate::Or(..)));
+            }
+            other => panic!("expected Implies, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn negative_literals_and_negation() {
+        assert_eq!(parse_predicate("-5").unwrap(), Predicate::IntLit(-5));
+        assert_eq!(
+            parse_predicate("-(5)").unwrap(),
+            Predicate::Neg(Box::new(Predicate::IntLit(5)))
+        );
+        assert_eq!(
+            parse_predicate("-170141183460469231731687303715884105728").unwrap(),
+            Predicate::IntLit(i128::MIN)
+        );
+        assert!(parse_predicate("170141183460469231731687303715884105728").is_err());
+    }
+
+    #[test]
+    fn quantifier_and_apply() {
+        let p = parse_predicate("forall i in i < len(xs) : xs_at(i) >= 0").unwrap();
+        match p {
+            Predicate::ForAll { var, range, body } => {
+                assert_eq!(var, "i");
+                assert!(matches!(*range, Predicate::Lt(..)));
+                assert!(matches!(*body, Predicate::Ge(..)));
+            }
+            other => panic!("expected ForAll, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(parse_type("i32").unwrap(), Type::i32());
+        assert_eq!(
+            parse_type("u7").unwrap(),
+            Type::Int {
+                width: 7,
+                signedness: Signedness::Unsigned
+            }
+        );
+        assert_eq!(
+            parse_type("Vec<Option<f64>>").unwrap(),
+            Type::Vec {
+                element: Box::new(Type::Option(Box::new(Type::f64())))
+            }
+        );
+        assert_eq!(
+            parse_type("Matrix<f32; 3, N>").unwrap(),
+            Type::Parameterized {
+                name: "Matrix".into(),
+                type_params: vec![Type::f32()],
+                value_params: vec![ValueParam::Concrete(3), ValueParam::Symbolic("N".into())],
+            }
+        );
+        assert_eq!(parse_type("Motor").unwrap(), Type::Named("Motor".into()));
+        assert!(parse_type("f8").is_err());
+    }
+
+    #[test]
+    fn minimal_graph_with_generated_ids() {
+        let g = parse_graph(
+            "graph {
+                node a = Literal;
+                node b = Literal;
+                node sum = Arithmetic(Add) { sig (i32, i32) -> (i32); }
+                edge a.0 -> sum.0;
+                edge b.0 -> sum.1 : i32;
+                region r = parallel [a, b];
+            }",
+        )
+        .unwrap();
+        assert_eq!(g.node_count(), 3);
+        assert_eq!(g.edge_count(), 2);
+        assert_eq!(g.region_count(), 1);
+    }
+
+    #[test]
+    fn unknown_label_reported_with_position() {
+        let err = parse_graph("graph {\n  node a = Literal;\n  edge a.0 -> b.0;\n}").unwrap_err();
+        assert!(matches!(
+            err,
+            TglError::UnknownLabel { line: 3, ref label, .. } if label == "b"
+        ));
+    }
+
+    #[test]
+    fn duplicate_label_rejected() {
+        let err = parse_graph("graph { node a = Literal; node a = Literal; }").unwrap_err();
+        assert!(matches!(err, TglError::DuplicateLabel { .. }));
+    }
+
+    #[test]
+    fn syntax_error_has_position() {
+        let err = parse_graph("graph {\n  node a = Bogus;\n}").unwrap_err();
+        assert!(matches!(err, TglError::Syntax { line: 2, .. }));
+        assert!(err.to_string().contains("unknown node kind"));
+    }
+}
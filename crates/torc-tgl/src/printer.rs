@@ -0,0 +1,1020 @@
+//! Pretty-printer from `Graph` to TGL source text.
+//!
+//! Output is deterministic: nodes appear in topological order (ties broken
+//! by ID), edges in source order, and regions outermost-first. Every field
+//! is written so that parsing the output reproduces the original graph.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use torc_core::contract::{Contract, EffectSet, ProofStatus, RecoveryStrategy, Variant};
+use torc_core::graph::constraints::{Constraint, Lifetime};
+use torc_core::graph::edge::Edge;
+use torc_core::graph::node::{Node, NodeId};
+use torc_core::graph::port::PortDirection;
+use torc_core::graph::region::Region;
+use torc_core::graph::Graph;
+use torc_core::provenance::{Author, Provenance};
+use torc_core::types::{
+    Effect, FloatPrecision, Linearity, Predicate, Signedness, Type, TypeDef, TypeSignature,
+    ValueParam,
+};
+use torc_core::value::Value;
+use uuid::Uuid;
+
+/// Words with a fixed meaning in type position.
+pub(crate) const TYPE_KEYWORDS: &[&str] = &[
+    "Void",
+    "Unit",
+    "Bool",
+    "Fixed",
+    "Variant",
+    "Vec",
+    "Option",
+    "Named",
+    "Linear",
+    "Affine",
+    "Shared",
+    "Unique",
+    "Counted",
+    "Unrestricted",
+    "Timed",
+    "Sized",
+    "Powered",
+    "Bandwidth",
+    "Distribution",
+    "Posterior",
+    "Interval",
+    "Approximate",
+];
+
+/// Words with a fixed meaning in predicate position.
+pub(crate) const PREDICATE_KEYWORDS: &[&str] =
+    &["true", "false", "forall", "exists", "in", "inf", "NaN"];
+
+/// Words with a fixed meaning in value position.
+pub(crate) const VALUE_KEYWORDS: &[&str] = &[
+    "true", "false", "Some", "None", "vec", "Fixed", "inf", "NaN",
+];
+
+/// Render a whole graph as TGL source.
+pub fn print_graph(graph: &Graph) -> String {
+    let nodes: Vec<&Node> = match graph.topological_sort() {
+        Ok(order) => order.iter().filter_map(|id| graph.get_node(id)).collect(),
+        Err(_) => {
+            let mut nodes: Vec<&Node> = graph.nodes().collect();
+            nodes.sort_by_key(|n| n.id);
+            nodes
+        }
+    };
+    let node_pos: HashMap<NodeId, usize> =
+        nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+
+    let mut regions: Vec<&Region> = graph.regions().collect();
+    regions.sort_by_key(|r| (region_depth(graph, r), r.id));
+
+    let labels = Labels::assign(&nodes, &regions);
+
+    let mut edges: Vec<&Edge> = graph.edges().collect();
+    edges.sort_by_key(|e| {
+        (
+            node_pos.get(&e.source.0).copied(),
+            e.source.1,
+            node_pos.get(&e.target.0).copied(),
+            e.target.1,
+            e.id,
+        )
+    });
+
+    let mut out = String::from("graph {\n");
+    for (name, def) in graph.types().iter() {
+        write_type_def(&mut out, name, def);
+    }
+    if !graph.types().is_empty() && !nodes.is_empty() {
+        out.push('\n');
+    }
+    for node in &nodes {
+        write_node(&mut out, node, &labels);
+    }
+    if !edges.is_empty() {
+        out.push('\n');
+    }
+    for edge in &edges {
+        write_edge(&mut out, edge, &labels);
+    }
+    if !regions.is_empty() {
+        out.push('\n');
+    }
+    for region in &regions {
+        write_region(&mut out, region, &labels);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Render a type in TGL syntax.
+pub fn print_type(ty: &Type) -> String {
+    let mut out = String::new();
+    write_type(&mut out, ty);
+    out
+}
+
+/// Render a predicate in TGL syntax.
+pub fn print_predicate(pred: &Predicate) -> String {
+    let mut out = String::new();
+    write_predicate(&mut out, pred, 0);
+    out
+}
+
+/// Render a constant value in TGL syntax.
+pub fn print_value(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn region_depth(graph: &Graph, region: &Region) -> usize {
+    let mut depth = 0;
+    let mut seen = HashSet::new();
+    let mut current = region.parent;
+    while let Some(parent) = current {
+        if !seen.insert(parent) {
+            break;
+        }
+        depth += 1;
+        current = graph.get_region(&parent).and_then(|r| r.parent);
+    }
+    depth
+}
+
+/// Local labels for nodes and regions.
+///
+/// Nodes whose `name` annotation is a unique identifier use it as their
+/// label; everything else gets a generated `n<i>` / `r<i>` label.
+struct Labels {
+    labels: HashMap<Uuid, String>,
+}
+
+impl Labels {
+    fn assign(nodes: &[&Node], regions: &[&Region]) -> Self {
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for node in nodes {
+            if let Some(name) = node.annotations.get("name") {
+                *name_counts.entry(name.as_str()).or_default() += 1;
+            }
+        }
+
+        let mut labels = HashMap::new();
+        let mut used = HashSet::new();
+        for node in nodes {
+            if let Some(name) = node.annotations.get("name") {
+                if name_counts[name.as_str()] == 1 && is_plain_ident(name) {
+                    labels.insert(node.id, name.clone());
+                    used.insert(name.clone());
+                }
+            }
+        }
+
+        let mut fresh = |prefix: char, counter: &mut usize| loop {
+            let candidate = format!("{prefix}{counter}");
+            *counter += 1;
+            if used.insert(candidate.clone()) {
+                return candidate;
+            }
+        };
+        let mut next_node = 0;
+        for node in nodes {
+            labels
+                .entry(node.id)
+                .or_insert_with(|| fresh('n', &mut next_node));
+        }
+        let mut next_region = 0;
+        for region in regions {
+            labels.insert(region.id, fresh('r', &mut next_region));
+        }
+        Self { labels }
+    }
+
+    /// A label reference, or an explicit `@id` for elements not in the graph.
+    fn reference(&self, id: &Uuid) -> String {
+        match self.labels.get(id) {
+            Some(label) => label.clone(),
+            None => format!("@{id}"),
+        }
+    }
+}
+
+fn write_type_def(out: &mut String, name: &str, def: &TypeDef) {
+    out.push_str("    type ");
+    out.push_str(&plain_or_quoted(name, &[]));
+    if !def.type_params.is_empty() || !def.value_params.is_empty() {
+        let names = |params: &[String]| {
+            params
+                .iter()
+                .map(|p| plain_or_quoted(p, &[]))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        out.push('<');
+        out.push_str(&names(&def.type_params));
+        if !def.value_params.is_empty() {
+            out.push_str("; ");
+            out.push_str(&names(&def.value_params));
+        }
+        out.push('>');
+    }
+    out.push_str(" = ");
+    write_type(out, &def.body);
+    out.push_str(";\n");
+}
+
+fn write_node(out: &mut String, node: &Node, labels: &Labels) {
+    let _ = write!(
+        out,
+        "    node {} @ {} = {}",
+        labels.reference(&node.id),
+        node.id,
+        node.kind
+    );
+
+    let has_body = node.type_signature.is_some()
+        || node.value.is_some()
+        || node.contract.is_some()
+        || node.provenance.is_some()
+        || !node.annotations.is_empty();
+    if !has_body {
+        out.push_str(";\n");
+        return;
+    }
+
+    out.push_str(" {\n");
+    if let Some(sig) = &node.type_signature {
+        let _ = writeln!(out, "        sig {};", signature(sig));
+    }
+    if let Some(value) = &node.value {
+        let _ = writeln!(out, "        value {};", print_value(value));
+    }
+    if let Some(contract) = &node.contract {
+        write_contract(out, contract);
+    }
+    if let Some(provenance) = &node.provenance {
+        write_provenance(out, provenance);
+    }
+    let mut annotations: Vec<_> = node.annotations.iter().collect();
+    annotations.sort();
+    for (key, value) in annotations {
+        let _ = writeln!(out, "        annotate {} = {};", quote(key), quote(value));
+    }
+    out.push_str("    }\n");
+}
+
+fn signature(sig: &TypeSignature) -> String {
+    let list = |types: &[Type]| types.iter().map(print_type).collect::<Vec<_>>().join(", ");
+    format!("({}) -> ({})", list(&sig.inputs), list(&sig.outputs))
+}
+
+fn write_contract(out: &mut String, contract: &Contract) {
+    out.push_str("        contract {\n");
+    for (var, binding) in &contract.bindings {
+        let direction = match binding.direction {
+            PortDirection::Input => "in",
+            PortDirection::Output => "out",
+        };
+        let _ = writeln!(
+            out,
+            "            bind {} = {direction} {};",
+            plain_or_quoted(var, PREDICATE_KEYWORDS),
+            binding.port
+        );
+    }
+    for pre in &contract.preconditions {
+        let _ = writeln!(out, "            requires {};", print_predicate(pre));
+    }
+    for post in &contract.postconditions {
+        let _ = writeln!(out, "            ensures {};", print_predicate(post));
+    }
+    if let Some(variant) = &contract.variant {
+        let _ = writeln!(out, "            variant {};", ranking(variant));
+    }
+    if let Some(tb) = &contract.time_bound {
+        out.push_str("            time");
+        for (key, value) in [
+            ("wcet", tb.wcet_ns),
+            ("bcet", tb.bcet_ns),
+            ("avg", tb.avg_ns),
+        ] {
+            if let Some(v) = value {
+                let _ = write!(out, " {key} {v}");
+            }
+        }
+        if let Some(target) = &tb.target {
+            let _ = write!(out, " target {}", quote(target));
+        }
+        out.push_str(";\n");
+    }
+    if let Some(mb) = &contract.memory_bound {
+        out.push_str("            memory");
+        for (key, value) in [
+            ("peak", mb.peak_bytes),
+            ("allocated", mb.allocated_bytes),
+            ("freed", mb.freed_bytes),
+        ] {
+            if let Some(v) = value {
+                let _ = write!(out, " {key} {v}");
+            }
+        }
+        out.push_str(";\n");
+    }
+    if let Some(eb) = &contract.energy_bound {
+        let _ = writeln!(out, "            energy {};", eb.max_uj);
+    }
+    if let Some(sb) = &contract.stack_bound {
+        let _ = writeln!(out, "            stack {};", sb.max_bytes);
+    }
+    if contract.effects != EffectSet::pure_set() {
+        let effects: Vec<String> = contract.effects.effects.iter().map(effect).collect();
+        let _ = writeln!(out, "            effects [{}];", effects.join(", "));
+    }
+    for fm in &contract.failure_modes {
+        let _ = writeln!(
+            out,
+            "            failure {} {} recover {};",
+            quote(&fm.name),
+            quote(&fm.description),
+            recovery(&fm.recovery)
+        );
+    }
+    if contract.recovery_strategy != RecoveryStrategy::Propagate {
+        let _ = writeln!(
+            out,
+            "            recovery {};",
+            recovery(&contract.recovery_strategy)
+        );
+    }
+    if contract.proof_status != ProofStatus::Pending {
+        let status = match contract.proof_status {
+            ProofStatus::Verified => "verified",
+            ProofStatus::Assumed => "assumed",
+            ProofStatus::Pending => "pending",
+            ProofStatus::Waived => "waived",
+        };
+        let _ = writeln!(out, "            status {status};");
+    }
+    if let Some(w) = &contract.proof_witness {
+        let data: String = w.data.iter().map(|b| format!("{b:02x}")).collect();
+        let _ = writeln!(
+            out,
+            "            witness {} solver {} data {};",
+            quote(&w.hash),
+            quote(&w.solver),
+            quote(&data)
+        );
+    }
+    out.push_str("        }\n");
+}
+
+fn effect(effect: &Effect) -> String {
+    match effect {
+        Effect::Pure => "Pure".to_string(),
+        Effect::Alloc(r) => format!("Alloc({})", quote(r)),
+        Effect::IO(d) => format!("IO({})", quote(d)),
+        Effect::Atomic(o) => format!("Atomic({})", quote(o)),
+        Effect::FFI(abi) => format!("FFI({})", quote(abi)),
+        Effect::Diverge => "Diverge".to_string(),
+        Effect::Panic => "Panic".to_string(),
+    }
+}
+
+fn recovery(strategy: &RecoveryStrategy) -> String {
+    match strategy {
+        RecoveryStrategy::Abort => "abort".to_string(),
+        RecoveryStrategy::Retry(n) => format!("retry({n})"),
+        RecoveryStrategy::Degrade(v) => format!("degrade({})", quote(v)),
+        RecoveryStrategy::Propagate => "propagate".to_string(),
+    }
+}
+
+fn author(author: &Author) -> String {
+    match author {
+        Author::AI {
+            model,
+            provider,
+            version,
+        } => format!(
+            "ai({}, {}, {})",
+            quote(model),
+            quote(provider),
+            quote(version)
+        ),
+        Author::Human { identity } => format!("human({})", quote(identity)),
+        Author::Toolchain { version } => format!("toolchain({})", quote(version)),
+    }
+}
+
+fn write_provenance(out: &mut String, provenance: &Provenance) {
+    out.push_str("        provenance {\n");
+    let _ = writeln!(
+        out,
+        "            created {} by {};",
+        quote(&provenance.created),
+        author(&provenance.created_by)
+    );
+    let _ = writeln!(
+        out,
+        "            reason {};",
+        quote(&provenance.creation_reason)
+    );
+    for req in &provenance.requirements {
+        let _ = write!(out, "            requirement {}", quote(&req.id));
+        if let Some(doc) = &req.document {
+            let _ = write!(out, " document {}", quote(doc));
+        }
+        if let Some(desc) = &req.description {
+            let _ = write!(out, " description {}", quote(desc));
+        }
+        out.push_str(";\n");
+    }
+    for edit in &provenance.edit_history {
+        let _ = write!(
+            out,
+            "            edit {} by {} {}",
+            quote(&edit.timestamp),
+            author(&edit.author),
+            quote(&edit.description)
+        );
+        if let Some(prev) = &edit.previous_hash {
+            let _ = write!(out, " previous {}", quote(prev));
+        }
+        out.push_str(";\n");
+    }
+    out.push_str("        }\n");
+}
+
+fn write_edge(out: &mut String, edge: &Edge, labels: &Labels) {
+    let _ = write!(
+        out,
+        "    edge @ {} {}.{} -> {}.{}",
+        edge.id,
+        labels.reference(&edge.source.0),
+        edge.source.1,
+        labels.reference(&edge.target.0),
+        edge.target.1
+    );
+    if let Some(ty) = &edge.data_type {
+        let _ = write!(out, " : {}", print_type(ty));
+    }
+    if edge.lifetime != Lifetime::Static {
+        let lifetime = match &edge.lifetime {
+            Lifetime::Region(r) => format!("region({})", labels.reference(r)),
+            Lifetime::Static => "static".to_string(),
+            Lifetime::Manual => "manual".to_string(),
+            Lifetime::Bounded(ns) => format!("bounded({ns})"),
+        };
+        let _ = write!(out, " lifetime {lifetime}");
+    }
+    if let Some(bw) = &edge.bandwidth {
+        let _ = write!(out, " bandwidth min {}", bw.min_bytes_per_sec);
+        if let Some(max) = bw.max_bytes_per_sec {
+            let _ = write!(out, " max {max}");
+        }
+    }
+    out.push_str(";\n");
+}
+
+fn write_region(out: &mut String, region: &Region, labels: &Labels) {
+    let children: Vec<String> = region
+        .children
+        .iter()
+        .map(|c| labels.reference(c))
+        .collect();
+    let _ = write!(
+        out,
+        "    region {} @ {} = {} [{}]",
+        labels.reference(&region.id),
+        region.id,
+        region.kind,
+        children.join(", ")
+    );
+
+    if region.parent.is_none()
+        && region.constraints.is_empty()
+        && region.interfaces.is_empty()
+        && region.variant.is_none()
+    {
+        out.push_str(";\n");
+        return;
+    }
+
+    out.push_str(" {\n");
+    if let Some(parent) = &region.parent {
+        let _ = writeln!(out, "        parent {};", labels.reference(parent));
+    }
+    if let Some(variant) = &region.variant {
+        let _ = writeln!(out, "        variant {};", ranking(variant));
+    }
+    for constraint in &region.constraints {
+        let text = match constraint {
+            Constraint::MaxTime(ns) => format!("max_time {ns}"),
+            Constraint::MaxMemory(bytes) => format!("max_memory {bytes}"),
+            Constraint::MaxEnergy(uj) => format!("max_energy {uj}"),
+            Constraint::MaxIterations(n) => format!("max_iterations {n}"),
+            Constraint::Custom { name, description } => {
+                format!("custom {} {}", quote(name), quote(description))
+            }
+        };
+        let _ = writeln!(out, "        constraint {text};");
+    }
+    for port in &region.interfaces {
+        let direction = match port.direction {
+            PortDirection::Input => "in",
+            PortDirection::Output => "out",
+        };
+        let _ = writeln!(
+            out,
+            "        port {direction} {} {} : {};",
+            quote(&port.name),
+            port.index,
+            print_type(&port.port_type)
+        );
+    }
+    out.push_str("    }\n");
+}
+
+fn ranking(variant: &Variant) -> String {
+    let mut text = format!(
+        "{} -> {}",
+        print_predicate(&variant.measure),
+        print_predicate(&variant.next)
+    );
+    if variant.lower_bound != Predicate::IntLit(0) {
+        let _ = write!(text, " bound {}", print_predicate(&variant.lower_bound));
+    }
+    text
+}
+
+// === Types ===
+
+fn write_type(out: &mut String, ty: &Type) {
+    match ty {
+        Type::Void => out.push_str("Void"),
+        Type::Unit => out.push_str("Unit"),
+        Type::Bool => out.push_str("Bool"),
+        Type::Int { width, signedness } => {
+            let prefix = match signedness {
+                Signedness::Signed => 'i',
+                Signedness::Unsigned => 'u',
+            };
+            let _ = write!(out, "{prefix}{width}");
+        }
+        Type::Float { precision } => out.push_str(match precision {
+            FloatPrecision::F16 => "f16",
+            FloatPrecision::F32 => "f32",
+            FloatPrecision::F64 => "f64",
+            FloatPrecision::F128 => "f128",
+        }),
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => {
+            let _ = write!(out, "Fixed<{total_bits}, {frac_bits}>");
+        }
+        Type::Tuple(elems) => {
+            out.push('(');
+            for (i, e) in elems.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_type(out, e);
+            }
+            if elems.len() == 1 {
+                out.push(',');
+            }
+            out.push(')');
+        }
+        Type::Record(fields) => {
+            out.push('{');
+            for (i, (name, ty)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&field_name(name));
+                out.push_str(": ");
+                write_type(out, ty);
+            }
+            out.push('}');
+        }
+        Type::Variant(cases) => {
+            out.push_str("Variant<");
+            for (i, (tag, ty)) in cases.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(" | ");
+                }
+                out.push_str(&field_name(tag));
+                out.push('(');
+                write_type(out, ty);
+                out.push(')');
+            }
+            out.push('>');
+        }
+        Type::Array { element, length } => {
+            out.push('[');
+            write_type(out, element);
+            let _ = write!(out, "; {length}]");
+        }
+        Type::Vec { element } => wrapper(out, "Vec", element, None),
+        Type::Refined { base, predicate } => {
+            out.push('(');
+            write_type(out, base);
+            out.push_str(" where ");
+            write_predicate(out, predicate, 0);
+            out.push(')');
+        }
+        Type::Linear { inner, linearity } => {
+            let name = match linearity {
+                Linearity::Linear => "Linear",
+                Linearity::Affine => "Affine",
+                Linearity::Shared => "Shared",
+                Linearity::Unique => "Unique",
+                Linearity::Counted => "Counted",
+                Linearity::Unrestricted => "Unrestricted",
+            };
+            wrapper(out, name, inner, None);
+        }
+        Type::Timed { inner, bound } => {
+            let extra = format!("{}ns, {}", bound.wcet_ns, quote(&bound.target));
+            wrapper(out, "Timed", inner, Some(extra));
+        }
+        Type::Sized { inner, max_bytes } => {
+            wrapper(out, "Sized", inner, Some(format!("{max_bytes}B")))
+        }
+        Type::Powered { inner, energy_uj } => {
+            wrapper(out, "Powered", inner, Some(format!("{energy_uj}uJ")))
+        }
+        Type::Bandwidth { inner, min_bps } => {
+            wrapper(out, "Bandwidth", inner, Some(format!("{min_bps}Bps")))
+        }
+        Type::Distribution(inner) => wrapper(out, "Distribution", inner, None),
+        Type::Posterior { inner, evidence } => {
+            wrapper(out, "Posterior", inner, Some(quote(evidence)))
+        }
+        Type::Interval { inner, confidence } => {
+            wrapper(out, "Interval", inner, Some(format!("{confidence:?}")))
+        }
+        Type::Approximate { inner, max_error } => {
+            wrapper(out, "Approximate", inner, Some(format!("{max_error:?}")))
+        }
+        Type::Parameterized {
+            name,
+            type_params,
+            value_params,
+        } => {
+            out.push_str(&named(name));
+            out.push('<');
+            for (i, tp) in type_params.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_type(out, tp);
+            }
+            out.push(';');
+            for (i, vp) in value_params.iter().enumerate() {
+                out.push_str(if i > 0 { ", " } else { " " });
+                match vp {
+                    ValueParam::Concrete(v) => {
+                        let _ = write!(out, "{v}");
+                    }
+                    ValueParam::Symbolic(s) => out.push_str(&plain_or_quoted(s, &[])),
+                }
+            }
+            out.push('>');
+        }
+        Type::Option(inner) => wrapper(out, "Option", inner, None),
+        Type::Named(name) => out.push_str(&named(name)),
+    }
+}
+
+fn wrapper(out: &mut String, name: &str, inner: &Type, extra: Option<String>) {
+    out.push_str(name);
+    out.push('<');
+    write_type(out, inner);
+    if let Some(extra) = extra {
+        out.push_str(", ");
+        out.push_str(&extra);
+    }
+    out.push('>');
+}
+
+/// A named type reference, escaped when it would read as a builtin.
+fn named(name: &str) -> String {
+    if is_plain_ident(name) && !is_builtin_type_name(name) {
+        name.to_string()
+    } else {
+        format!("Named({})", quote(name))
+    }
+}
+
+pub(crate) fn is_builtin_type_name(name: &str) -> bool {
+    if TYPE_KEYWORDS.contains(&name) {
+        return true;
+    }
+    let mut chars = name.chars();
+    matches!(chars.next(), Some('i' | 'u' | 'f'))
+        && !chars.as_str().is_empty()
+        && chars.as_str().chars().all(|c| c.is_ascii_digit())
+}
+
+fn field_name(name: &str) -> String {
+    if is_plain_ident(name) {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+// === Values ===
+
+/// Scalars carry their type as a suffix (`42i32`, `1.5f32`) so that a value
+/// reads back with the same width and precision.
+fn write_value(out: &mut String, value: &Value) {
+    let list = |out: &mut String, elems: &[Value]| {
+        for (i, e) in elems.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            write_value(out, e);
+        }
+    };
+    match value {
+        Value::Unit => out.push_str("()"),
+        Value::Bool(b) => {
+            let _ = write!(out, "{b}");
+        }
+        Value::Int(v) => {
+            if v.is_negative() {
+                let magnitude = v.to_i128().map_or(0, i128::unsigned_abs);
+                let _ = write!(out, "-{magnitude}");
+            } else {
+                let _ = write!(out, "{}", v.bits());
+            }
+            write_type(out, &v.ty());
+        }
+        Value::Float(v) => {
+            let x = v.value();
+            if x.is_nan() {
+                out.push_str("NaN ");
+            } else if x.is_infinite() {
+                out.push_str(if x < 0.0 { "-inf " } else { "inf " });
+            } else {
+                let _ = write!(out, "{x:?}");
+            }
+            write_type(out, &v.ty());
+        }
+        Value::Fixed(v) => {
+            let _ = write!(
+                out,
+                "Fixed<{}, {}>({})",
+                v.total_bits(),
+                v.frac_bits(),
+                v.raw()
+            );
+        }
+        Value::Tuple(elems) => {
+            out.push('(');
+            list(out, elems);
+            if elems.len() == 1 {
+                out.push(',');
+            }
+            out.push(')');
+        }
+        Value::Record(fields) => {
+            out.push('{');
+            for (i, (name, v)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                let _ = write!(out, "{}: ", field_name(name));
+                write_value(out, v);
+            }
+            out.push('}');
+        }
+        Value::Variant { tag, value } => {
+            out.push_str(&plain_or_quoted(tag, VALUE_KEYWORDS));
+            out.push('(');
+            write_value(out, value);
+            out.push(')');
+        }
+        Value::Array(elems) => {
+            out.push('[');
+            list(out, elems);
+            out.push(']');
+        }
+        Value::Vec(elems) => {
+            out.push_str("vec[");
+            list(out, elems);
+            out.push(']');
+        }
+        Value::Option(Some(v)) => {
+            out.push_str("Some(");
+            write_value(out, v);
+            out.push(')');
+        }
+        Value::Option(None) => out.push_str("None"),
+    }
+}
+
+// === Predicates ===
+
+/// Binding strength of a predicate's outermost operator.
+fn precedence(pred: &Predicate) -> u8 {
+    match pred {
+        Predicate::ForAll { .. } | Predicate::Exists { .. } => 0,
+        Predicate::Implies(..) => 1,
+        Predicate::Or(..) => 2,
+        Predicate::And(..) => 3,
+        Predicate::Not(_) => 4,
+        Predicate::Eq(..)
+        | Predicate::Ne(..)
+        | Predicate::Lt(..)
+        | Predicate::Le(..)
+        | Predicate::Gt(..)
+        | Predicate::Ge(..) => 5,
+        Predicate::Add(..) | Predicate::Sub(..) => 6,
+        Predicate::Mul(..) | Predicate::Div(..) | Predicate::Mod(..) => 7,
+        Predicate::Neg(_) => 8,
+        Predicate::BoolLit(_)
+        | Predicate::IntLit(_)
+        | Predicate::FloatLit(_)
+        | Predicate::Var(_)
+        | Predicate::Apply(..) => 9,
+    }
+}
+
+fn write_predicate(out: &mut String, pred: &Predicate, min_prec: u8) {
+    let prec = precedence(pred);
+    let parens = prec < min_prec;
+    if parens {
+        out.push('(');
+    }
+    let binary = |out: &mut String, op: &str, a: &Predicate, b: &Predicate, lp, rp| {
+        write_predicate(out, a, lp);
+        let _ = write!(out, " {op} ");
+        write_predicate(out, b, rp);
+    };
+    match pred {
+        Predicate::BoolLit(b) => {
+            let _ = write!(out, "{b}");
+        }
+        Predicate::IntLit(n) => {
+            let _ = write!(out, "{n}");
+        }
+        Predicate::FloatLit(x) => {
+            let _ = write!(out, "{x:?}");
+        }
+        Predicate::Var(name) => out.push_str(&plain_or_quoted(name, PREDICATE_KEYWORDS)),
+        Predicate::Add(a, b) => binary(out, "+", a, b, 6, 7),
+        Predicate::Sub(a, b) => binary(out, "-", a, b, 6, 7),
+        Predicate::Mul(a, b) => binary(out, "*", a, b, 7, 8),
+        Predicate::Div(a, b) => binary(out, "/", a, b, 7, 8),
+        Predicate::Mod(a, b) => binary(out, "%", a, b, 7, 8),
+        Predicate::Neg(a) => {
+            out.push('-');
+            // A literal directly after '-' would be read back as a negative literal.
+            let literal = matches!(a.as_ref(), Predicate::IntLit(_) | Predicate::FloatLit(_));
+            write_predicate(out, a, if literal { 10 } else { 8 });
+        }
+        Predicate::Eq(a, b) => binary(out, "==", a, b, 6, 6),
+        Predicate::Ne(a, b) => binary(out, "!=", a, b, 6, 6),
+        Predicate::Lt(a, b) => binary(out, "<", a, b, 6, 6),
+        Predicate::Le(a, b) => binary(out, "<=", a, b, 6, 6),
+        Predicate::Gt(a, b) => binary(out, ">", a, b, 6, 6),
+        Predicate::Ge(a, b) => binary(out, ">=", a, b, 6, 6),
+        Predicate::And(a, b) => binary(out, "&&", a, b, 3, 4),
+        Predicate::Or(a, b) => binary(out, "||", a, b, 2, 3),
+        Predicate::Not(a) => {
+            out.push('!');
+            write_predicate(out, a, 6);
+        }
+        Predicate::Implies(a, b) => binary(out, "=>", a, b, 2, 1),
+        Predicate::ForAll { var, range, body } | Predicate::Exists { var, range, body } => {
+            let quantifier = if matches!(pred, Predicate::ForAll { .. }) {
+                "forall"
+            } else {
+                "exists"
+            };
+            let _ = write!(
+                out,
+                "{quantifier} {} in ",
+                plain_or_quoted(var, PREDICATE_KEYWORDS)
+            );
+            write_predicate(out, range, 1);
+            out.push_str(" : ");
+            write_predicate(out, body, 0);
+        }
+        Predicate::Apply(name, args) => {
+            out.push_str(&plain_or_quoted(name, PREDICATE_KEYWORDS));
+            out.push('(');
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_predicate(out, arg, 0);
+            }
+            out.push(')');
+        }
+    }
+    if parens {
+        out.push(')');
+    }
+}
+
+// === Lexical helpers ===
+
+pub(crate) fn is_plain_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn plain_or_quoted(name: &str, reserved: &[&str]) -> String {
+    if is_plain_ident(name) && !reserved.contains(&name) {
+        name.to_string()
+    } else {
+        format!("${}", quote(name))
+    }
+}
+
+/// Quote a string literal, escaping quotes, backslashes and control characters.
+pub(crate) fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn predicate_precedence() {
+        let p = Predicate::Mul(
+            Box::new(Predicate::Add(
+                Box::new(Predicate::Var("a".into())),
+                Box::new(Predicate::IntLit(1)),
+            )),
+            Box::new(Predicate::Var("b".into())),
+        );
+        assert_eq!(print_predicate(&p), "(a + 1) * b");
+
+        let range = Predicate::in_range("value", 0, 4095);
+        assert_eq!(print_predicate(&range), "value >= 0 && value <= 4095");
+    }
+
+    #[test]
+    fn negation_of_literal_is_parenthesized() {
+        let p = Predicate::Neg(Box::new(Predicate::IntLit(5)));
+        assert_eq!(print_predicate(&p), "-(5)");
+        assert_eq!(print_predicate(&Predicate::IntLit(-5)), "-5");
+    }
+
+    #[test]
+    fn reserved_names_are_escaped() {
+        assert_eq!(print_predicate(&Predicate::Var("in".into())), "$\"in\"");
+        assert_eq!(print_type(&Type::Named("i32".into())), "Named(\"i32\")");
+        assert_eq!(print_type(&Type::Named("Motor".into())), "Motor");
+    }
+
+    #[test]
+    fn composite_types() {
+        let rec = Type::Record(BTreeMap::from([
+            ("x".to_string(), Type::f32()),
+            ("y".to_string(), Type::Tuple(vec![Type::u8()])),
+        ]));
+        assert_eq!(print_type(&rec), "{x: f32, y: (u8,)}");
+
+        let refined = Type::i32().refined(Predicate::positive("value"));
+        assert_eq!(print_type(&refined), "(i32 where value > 0)");
+
+        let timed = Type::f32().timed(50, "arm");
+        assert_eq!(print_type(&timed), "Timed<f32, 50ns, \"arm\">");
+    }
+
+    #[test]
+    fn quote_escapes() {
+        assert_eq!(quote("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+    }
+}
@@ -325,6 +325,18 @@ mod tests {
         g
     }
 
+    #[test]
+    fn canonical_graphs_write_identical_bytes() {
+        let bytes = |mut g: Graph| {
+            g.canonicalize("sample");
+            TrcFile::new(g).to_bytes().unwrap()
+        };
+        let (first, second) = (bytes(sample_graph()), bytes(sample_graph()));
+        assert_eq!(first, second);
+        let read = TrcFile::from_bytes(&first).unwrap();
+        assert_eq!(read.graph.node_count(), 3);
+    }
+
     #[test]
     fn round_trip() {
         let graph = sample_graph();
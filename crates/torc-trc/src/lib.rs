@@ -29,4 +29,4 @@ mod format;
 mod merge;
 
 pub use format::{TrcError, TrcFile, TrcFlags, TrcVersion};
-pub use merge::{merge_trc_files, MergeError};
+pub use merge::{merge3_trc_files, merge_trc_files, MergeError};
@@ -1,9 +1,11 @@
 //! TRC-level graph merging.
 //!
-//! Merges two TRC files by combining their graphs and recomputing flags.
+//! Merges two TRC files by combining their graphs and recomputing flags,
+//! either as a disjoint union or as a three-way merge of concurrent edits.
 
 use thiserror::Error;
 
+use torc_core::graph::three_way::{merge3, Conflict};
 use torc_core::graph::GraphError;
 
 use crate::format::{TrcError, TrcFile};
@@ -28,6 +30,19 @@ pub fn merge_trc_files(base: TrcFile, other: &TrcFile) -> Result<TrcFile, MergeE
     Ok(TrcFile::new(graph))
 }
 
+/// Three-way merge of two TRC files edited concurrently from `base`.
+///
+/// Returns the merged file, with flags recomputed, and the conflicts found.
+/// Conflicting elements keep `ours`' version.
+pub fn merge3_trc_files(
+    base: &TrcFile,
+    ours: &TrcFile,
+    theirs: &TrcFile,
+) -> (TrcFile, Vec<Conflict>) {
+    let merged = merge3(&base.graph, &ours.graph, &theirs.graph);
+    (TrcFile::new(merged.graph), merged.conflicts)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,4 +107,37 @@ mod tests {
         assert!(merged.flags.has(TrcFlags::HAS_PROVENANCE));
         assert!(merged.flags.has(TrcFlags::HAS_PROOFS));
     }
+
+    #[test]
+    fn three_way_merge_of_concurrent_edits() {
+        let mut base = Graph::new();
+        let n1 = Node::new(NodeKind::Literal);
+        let id1 = n1.id;
+        base.add_node(n1).unwrap();
+
+        let mut ours = base.clone();
+        let n2 = Node::new(NodeKind::Literal);
+        let id2 = n2.id;
+        ours.add_node(n2).unwrap();
+        ours.add_edge(Edge::new((id1, 0), (id2, 0))).unwrap();
+
+        let mut theirs = base.clone();
+        let mut contract = Contract::pure_default();
+        contract.proof_witness = Some(ProofWitness {
+            hash: "sha256:abc".to_string(),
+            solver: "z3".to_string(),
+            data: vec![1, 2, 3],
+        });
+        theirs.get_node_mut(&id1).unwrap().contract = Some(contract);
+
+        let (merged, conflicts) = merge3_trc_files(
+            &TrcFile::new(base),
+            &TrcFile::new(ours),
+            &TrcFile::new(theirs),
+        );
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.graph.node_count(), 2);
+        assert_eq!(merged.graph.edge_count(), 1);
+        assert!(merged.flags.has(TrcFlags::HAS_PROOFS));
+    }
 }
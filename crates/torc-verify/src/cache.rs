@@ -1,10 +1,74 @@
-//! Content-addressed in-memory proof cache.
+//! Content-addressed proof cache.
+//!
+//! Proofs are keyed by `cache_key`: the obligation's fingerprint
+//! (`incremental::fingerprints`), which covers the canonical
+//! `obligation_hash` together with everything in the graph the obligation
+//! can depend on, since a proof by interval analysis holds only for the
+//! ranges of the ports it read; and the solver backends in use, which
+//! `CacheVersion` does not cover when they are external binaries. A cache
+//! created with `new` lives in memory only; one created with `open` is also
+//! backed by a directory, so proofs outlive the engine and are shared by
+//! every run (and, through bundles, every machine) using the same
+//! toolchain.
+//!
+//! Layout:
+//! ```text
+//! <root>/
+//!   <version>/            — one per engine and solver version
+//!     <hh>/<hash>.json    — a `CacheEntry`, sharded by hash prefix
+//! ```
+//!
+//! Entries are written to a temporary file and renamed into place, so
+//! concurrent writers never leave a torn entry behind; the last one wins,
+//! and both wrote the same proof. Unreadable entries are treated as misses.
+//!
+//! Entries added from a bundle are marked `imported`: they were proven on
+//! another machine, so the engine re-checks their certificates before
+//! reusing them.
 
-use std::collections::HashMap;
+use std::collections::{hash_map, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 use torc_core::contract::{ProofObligation, ProofWitness};
 
+/// Errors from the on-disk proof cache.
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("proof cache I/O error at {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("malformed proof cache data in {path}: {source}")]
+    Format {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("bundle was produced by {bundle}, but this toolchain is {current}")]
+    VersionMismatch { bundle: String, current: String },
+
+    #[error("bundle entry has an invalid key: {0:?}")]
+    InvalidHash(String),
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> CacheError + '_ {
+    move |source| CacheError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
 /// Statistics about cache usage.
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
@@ -14,72 +78,346 @@ pub struct CacheStats {
 }
 
 /// A cached proof entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CacheEntry {
     pub witness: ProofWitness,
-    pub obligation_hash: String,
+    /// The `cache_key` the witness was stored under.
+    pub key: String,
     pub timestamp: u64,
+    /// Whether the entry came from a bundle rather than a local proof.
+    #[serde(default)]
+    pub imported: bool,
+}
+
+/// The toolchain a proof was produced by.
+///
+/// A proof is only reused by the engine and solvers that produced it, so
+/// each version has its own directory and bundles record theirs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheVersion {
+    /// Version of this crate.
+    pub engine: String,
+    /// The solvers compiled in, with their versions where known.
+    pub solver: String,
+}
+
+impl CacheVersion {
+    /// The version of the running toolchain.
+    pub fn current() -> Self {
+        #[cfg(feature = "z3")]
+        let solver = format!("interval+z3-{}", z3::full_version());
+        #[cfg(not(feature = "z3"))]
+        let solver = "interval".to_string();
+        Self {
+            engine: env!("CARGO_PKG_VERSION").to_string(),
+            solver,
+        }
+    }
+
+    /// Directory name for this version's entries.
+    fn dir_name(&self) -> String {
+        format!("engine-{}_solver-{}", self.engine, self.solver)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for CacheVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "engine {} with {}", self.engine, self.solver)
+    }
+}
+
+/// When `evict` drops entries from a disk-backed cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvictionPolicy {
+    /// Keep at most this many entries, dropping the oldest first.
+    pub max_entries: Option<usize>,
+    /// Drop entries stored more than this many seconds ago.
+    pub max_age_secs: Option<u64>,
+}
+
+impl EvictionPolicy {
+    pub fn with_max_entries(mut self, max: usize) -> Self {
+        self.max_entries = Some(max);
+        self
+    }
+
+    pub fn with_max_age_secs(mut self, secs: u64) -> Self {
+        self.max_age_secs = Some(secs);
+        self
+    }
 }
 
-/// Content-addressed proof cache keyed by obligation hash.
+/// A portable set of cache entries, for seeding one cache from another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheBundle {
+    pub version: CacheVersion,
+    pub entries: Vec<CacheEntry>,
+}
+
+/// Content-addressed proof cache keyed by `cache_key`.
 #[derive(Debug, Clone)]
 pub struct ProofCache {
     entries: HashMap<String, CacheEntry>,
+    /// This version's directory, for a disk-backed cache.
+    dir: Option<PathBuf>,
+    version: CacheVersion,
     hits: usize,
     misses: usize,
 }
 
 impl ProofCache {
-    /// Create a new empty cache.
+    /// Create a new empty in-memory cache.
     pub fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            dir: None,
+            version: CacheVersion::current(),
             hits: 0,
             misses: 0,
         }
     }
 
-    /// Look up a cached witness for the given obligation.
-    pub fn lookup(&mut self, obligation: &ProofObligation) -> Option<&ProofWitness> {
-        let hash = obligation_hash(obligation);
-        if self.entries.contains_key(&hash) {
+    /// Open (creating if needed) the disk-backed cache under `root` for the
+    /// current toolchain.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, CacheError> {
+        Self::open_version(root, CacheVersion::current())
+    }
+
+    /// Open the disk-backed cache under `root` for a given toolchain version.
+    pub fn open_version(root: impl AsRef<Path>, version: CacheVersion) -> Result<Self, CacheError> {
+        let dir = root.as_ref().join(version.dir_name());
+        fs::create_dir_all(&dir).map_err(io_error(&dir))?;
+        Ok(Self {
+            dir: Some(dir),
+            version,
+            ..Self::new()
+        })
+    }
+
+    /// The toolchain version whose proofs this cache holds.
+    pub fn version(&self) -> &CacheVersion {
+        &self.version
+    }
+
+    /// Look up the entry stored under a cache key.
+    pub fn lookup(&mut self, key: &str) -> Option<&CacheEntry> {
+        if !self.entries.contains_key(key) {
+            if let Some(entry) = self.read_entry(key) {
+                self.entries.insert(key.to_string(), entry);
+            }
+        }
+        if self.entries.contains_key(key) {
             self.hits += 1;
-            Some(&self.entries[&hash].witness)
+            Some(&self.entries[key])
         } else {
             self.misses += 1;
             None
         }
     }
 
-    /// Store a proof witness for the given obligation.
-    pub fn store(&mut self, obligation: &ProofObligation, witness: ProofWitness) {
-        let hash = obligation_hash(obligation);
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-        self.entries.insert(
-            hash.clone(),
-            CacheEntry {
-                witness,
-                obligation_hash: hash,
-                timestamp,
-            },
-        );
+    /// Store a proof witness under a cache key.
+    ///
+    /// For a disk-backed cache the entry is also written through; a failed
+    /// write only costs a re-proof later, so it is not reported.
+    pub fn store(&mut self, key: &str, witness: ProofWitness) {
+        let entry = CacheEntry {
+            witness,
+            key: key.to_string(),
+            timestamp: now(),
+            imported: false,
+        };
+        let _ = self.write_entry(&entry);
+        self.entries.insert(key.to_string(), entry);
     }
 
-    /// Invalidate (remove) a cached entry by obligation hash.
-    pub fn invalidate(&mut self, obligation_hash: &str) {
-        self.entries.remove(obligation_hash);
+    /// Invalidate (remove) a cached entry by cache key.
+    pub fn invalidate(&mut self, key: &str) {
+        self.entries.remove(key);
+        if let Some(path) = self.entry_path(key) {
+            let _ = fs::remove_file(path);
+        }
     }
 
     /// Return cache usage statistics.
     pub fn statistics(&self) -> CacheStats {
+        let entries = match &self.dir {
+            Some(_) => self.stored_hashes().len(),
+            None => self.entries.len(),
+        };
         CacheStats {
             hits: self.hits,
             misses: self.misses,
-            entries: self.entries.len(),
+            entries,
+        }
+    }
+
+    /// Drop entries the policy no longer admits, along with the directories
+    /// of other toolchain versions. Returns the number of entries removed.
+    pub fn evict(&mut self, policy: &EvictionPolicy) -> Result<usize, CacheError> {
+        let mut removed = 0;
+        if let Some(dir) = &self.dir {
+            let root = dir.parent().unwrap_or(dir);
+            for other in fs::read_dir(root).map_err(io_error(root))? {
+                let path = other.map_err(io_error(root))?.path();
+                if path.is_dir() && path != *dir {
+                    removed += count_files(&path);
+                    fs::remove_dir_all(&path).map_err(io_error(&path))?;
+                }
+            }
+        }
+
+        let mut entries = self.all_entries();
+        entries.sort_by(|a, b| {
+            b.timestamp
+                .cmp(&a.timestamp)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        let cutoff = policy.max_age_secs.map(|age| now().saturating_sub(age));
+        let keep = policy.max_entries.unwrap_or(usize::MAX);
+        for (i, entry) in entries.iter().enumerate() {
+            let expired = cutoff.is_some_and(|c| entry.timestamp < c);
+            if expired || i >= keep {
+                self.invalidate(&entry.key);
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Every entry, in memory or on disk, as a bundle.
+    pub fn bundle(&self) -> CacheBundle {
+        let mut entries = self.all_entries();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        CacheBundle {
+            version: self.version.clone(),
+            entries,
+        }
+    }
+
+    /// Write every entry to a bundle file. Returns the number written.
+    pub fn export(&self, path: &Path) -> Result<usize, CacheError> {
+        let bundle = self.bundle();
+        let json = serde_json::to_vec(&bundle).map_err(|source| CacheError::Format {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        fs::write(path, json).map_err(io_error(path))?;
+        Ok(bundle.entries.len())
+    }
+
+    /// Add the entries of a bundle file produced by the same toolchain,
+    /// marked as imported. Entries already present are kept. Returns the
+    /// number added.
+    pub fn import(&mut self, path: &Path) -> Result<usize, CacheError> {
+        let bytes = fs::read(path).map_err(io_error(path))?;
+        let bundle: CacheBundle =
+            serde_json::from_slice(&bytes).map_err(|source| CacheError::Format {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        self.merge(bundle)
+    }
+
+    /// Add the entries of a bundle produced by the same toolchain.
+    pub fn merge(&mut self, bundle: CacheBundle) -> Result<usize, CacheError> {
+        if bundle.version != self.version {
+            return Err(CacheError::VersionMismatch {
+                bundle: bundle.version.to_string(),
+                current: self.version.to_string(),
+            });
+        }
+        if let Some(bad) = bundle.entries.iter().find(|e| !is_hash(&e.key)) {
+            return Err(CacheError::InvalidHash(bad.key.clone()));
+        }
+        let mut added = 0;
+        for mut entry in bundle.entries {
+            if self.entries.contains_key(&entry.key) || self.read_entry(&entry.key).is_some() {
+                continue;
+            }
+            entry.imported = true;
+            self.write_entry(&entry)?;
+            self.entries.insert(entry.key.clone(), entry);
+            added += 1;
         }
+        Ok(added)
+    }
+
+    fn entry_path(&self, hash: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        is_hash(hash).then(|| dir.join(&hash[..2]).join(format!("{hash}.json")))
+    }
+
+    fn read_entry(&self, hash: &str) -> Option<CacheEntry> {
+        let bytes = fs::read(self.entry_path(hash)?).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&bytes).ok()?;
+        (entry.key == hash).then_some(entry)
+    }
+
+    /// Write an entry atomically: to a unique temporary file, then renamed.
+    fn write_entry(&self, entry: &CacheEntry) -> Result<(), CacheError> {
+        let Some(path) = self.entry_path(&entry.key) else {
+            return Ok(());
+        };
+        let shard = path.parent().unwrap_or(&path);
+        fs::create_dir_all(shard).map_err(io_error(shard))?;
+
+        static WRITES: AtomicUsize = AtomicUsize::new(0);
+        let tmp = shard.join(format!(
+            ".{}.{}.{}.tmp",
+            entry.key,
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        let json = serde_json::to_vec(entry).map_err(|source| CacheError::Format {
+            path: path.clone(),
+            source,
+        })?;
+        fs::write(&tmp, json).map_err(io_error(&tmp))?;
+        fs::rename(&tmp, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            io_error(&path)(e)
+        })
+    }
+
+    /// Hashes of the entries on disk.
+    fn stored_hashes(&self) -> Vec<String> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+        let files = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .flat_map(|shard| fs::read_dir(shard.path()).into_iter().flatten().flatten());
+        files
+            .filter_map(|file| {
+                let name = file.file_name().into_string().ok()?;
+                let hash = name.strip_suffix(".json")?;
+                is_hash(hash).then(|| hash.to_string())
+            })
+            .collect()
+    }
+
+    /// Entries in memory, plus the readable ones on disk.
+    fn all_entries(&self) -> Vec<CacheEntry> {
+        let mut all = self.entries.clone();
+        for hash in self.stored_hashes() {
+            if let hash_map::Entry::Vacant(slot) = all.entry(hash) {
+                if let Some(entry) = self.read_entry(slot.key()) {
+                    slot.insert(entry);
+                }
+            }
+        }
+        all.into_values().collect()
     }
 }
 
@@ -89,15 +427,60 @@ impl Default for ProofCache {
     }
 }
 
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether `s` is a lowercase hex SHA-256, as cache keys are.
+fn is_hash(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn count_files(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| {
+            let path = e.path();
+            if path.is_dir() {
+                count_files(&path)
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
 /// Compute a content hash for an obligation: SHA-256 of (kind, predicate, description).
+///
+/// The predicate is canonicalized first, so obligations that differ only in
+/// bound variable names, operand order or redundant literals share a hash.
+/// It says nothing about the graph around the obligation, so it is not a
+/// cache key on its own; see `cache_key`.
 pub fn obligation_hash(obligation: &ProofObligation) -> String {
     let mut hasher = Sha256::new();
     hasher.update(format!("{:?}", obligation.kind).as_bytes());
-    hasher.update(format!("{:?}", obligation.predicate).as_bytes());
+    hasher.update(format!("{:?}", obligation.predicate.canonicalize()).as_bytes());
     hasher.update(obligation.description.as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
+/// The key for the proof of an obligation with fingerprint `fingerprint`
+/// (see `incremental::fingerprints`), made with the named solver backends.
+pub fn cache_key(fingerprint: &str, backends: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(fingerprint.as_bytes());
+    for backend in backends {
+        hasher.update([0]);
+        hasher.update(backend.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,20 +512,23 @@ mod tests {
         let ob = sample_obligation();
         let witness = sample_witness();
 
-        assert!(cache.lookup(&ob).is_none());
-        cache.store(&ob, witness.clone());
-        assert_eq!(cache.lookup(&ob).unwrap().solver, "interval_domain");
+        assert!(cache.lookup(&obligation_hash(&ob)).is_none());
+        cache.store(&obligation_hash(&ob), witness.clone());
+        assert_eq!(
+            cache.lookup(&obligation_hash(&ob)).unwrap().witness.solver,
+            "interval_domain"
+        );
     }
 
     #[test]
     fn cache_hit_on_unchanged() {
         let mut cache = ProofCache::new();
         let ob = sample_obligation();
-        cache.store(&ob, sample_witness());
+        cache.store(&obligation_hash(&ob), sample_witness());
 
         // Same obligation should hit
-        let _ = cache.lookup(&ob);
-        let _ = cache.lookup(&ob);
+        let _ = cache.lookup(&obligation_hash(&ob));
+        let _ = cache.lookup(&obligation_hash(&ob));
         let stats = cache.statistics();
         assert!(stats.hits >= 2);
         assert_eq!(stats.entries, 1);
@@ -152,12 +538,140 @@ mod tests {
     fn invalidation() {
         let mut cache = ProofCache::new();
         let ob = sample_obligation();
-        cache.store(&ob, sample_witness());
+        cache.store(&obligation_hash(&ob), sample_witness());
 
         let hash = obligation_hash(&ob);
         cache.invalidate(&hash);
 
-        assert!(cache.lookup(&ob).is_none());
+        assert!(cache.lookup(&obligation_hash(&ob)).is_none());
         assert_eq!(cache.statistics().entries, 0);
     }
+
+    #[test]
+    fn equivalent_obligations_share_hash() {
+        let ob = sample_obligation();
+        let mut reordered = ob.clone();
+        // output <= 4095 && 0 <= output
+        reordered.predicate = Predicate::And(
+            Box::new(Predicate::Le(
+                Box::new(Predicate::Var("output".into())),
+                Box::new(Predicate::IntLit(4095)),
+            )),
+            Box::new(Predicate::Le(
+                Box::new(Predicate::IntLit(0)),
+                Box::new(Predicate::Var("output".into())),
+            )),
+        );
+
+        let mut cache = ProofCache::new();
+        cache.store(&obligation_hash(&ob), sample_witness());
+        assert!(cache.lookup(&obligation_hash(&reordered)).is_some());
+        assert_eq!(obligation_hash(&ob), obligation_hash(&reordered));
+    }
+
+    #[test]
+    fn keys_depend_on_backends() {
+        let fingerprint = obligation_hash(&sample_obligation());
+        let interval = cache_key(&fingerprint, &[]);
+        let cvc5 = cache_key(&fingerprint, &["smtlib:cvc5"]);
+        assert!(is_hash(&interval) && is_hash(&cvc5));
+        assert_ne!(interval, cvc5);
+        assert_ne!(cvc5, cache_key(&fingerprint, &["smtlib:cvc5", "z3"]));
+        assert_eq!(cvc5, cache_key(&fingerprint, &["smtlib:cvc5"]));
+    }
+
+    #[test]
+    fn disk_cache_outlives_the_engine() {
+        let dir = tempfile::tempdir().unwrap();
+        let ob = sample_obligation();
+        ProofCache::open(dir.path())
+            .unwrap()
+            .store(&obligation_hash(&ob), sample_witness());
+
+        let mut reopened = ProofCache::open(dir.path()).unwrap();
+        assert_eq!(reopened.statistics().entries, 1);
+        assert_eq!(
+            reopened
+                .lookup(&obligation_hash(&ob))
+                .unwrap()
+                .witness
+                .solver,
+            "interval_domain"
+        );
+
+        // Another toolchain version does not see it.
+        let other = CacheVersion {
+            engine: "0.0.0-other".into(),
+            solver: "interval".into(),
+        };
+        let mut stale = ProofCache::open_version(dir.path(), other).unwrap();
+        assert!(stale.lookup(&obligation_hash(&ob)).is_none());
+
+        // Evicting from the current version drops the other's directory.
+        let removed = reopened.evict(&EvictionPolicy::default()).unwrap();
+        assert_eq!(removed, 0);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn eviction_keeps_the_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = ProofCache::open(dir.path()).unwrap();
+        let obligations: Vec<_> = (0..3)
+            .map(|i| {
+                let mut ob = sample_obligation();
+                ob.description = format!("obligation {i}");
+                ob
+            })
+            .collect();
+        for ob in &obligations {
+            cache.store(&obligation_hash(ob), sample_witness());
+        }
+        // Backdate the first entry.
+        let hash = obligation_hash(&obligations[0]);
+        let mut old = cache.entries[&hash].clone();
+        old.timestamp = 1;
+        cache.write_entry(&old).unwrap();
+        cache.entries.insert(hash, old);
+
+        let removed = cache
+            .evict(&EvictionPolicy::default().with_max_age_secs(3600))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert!(cache.lookup(&obligation_hash(&obligations[0])).is_none());
+
+        let removed = cache
+            .evict(&EvictionPolicy::default().with_max_entries(1))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(cache.statistics().entries, 1);
+    }
+
+    #[test]
+    fn bundles_seed_other_caches() {
+        let ci = tempfile::tempdir().unwrap();
+        let dev = tempfile::tempdir().unwrap();
+        let bundle = ci.path().join("proofs.json");
+        let ob = sample_obligation();
+
+        let mut source = ProofCache::open(ci.path().join("cache")).unwrap();
+        source.store(&obligation_hash(&ob), sample_witness());
+        assert_eq!(source.export(&bundle).unwrap(), 1);
+
+        let mut target = ProofCache::open(dev.path()).unwrap();
+        assert_eq!(target.import(&bundle).unwrap(), 1);
+        assert_eq!(target.import(&bundle).unwrap(), 0);
+        let key = obligation_hash(&ob);
+        assert!(!source.lookup(&key).unwrap().imported);
+        let mut reopened = ProofCache::open(dev.path()).unwrap();
+        assert!(reopened.lookup(&key).unwrap().imported);
+
+        // Bundles from another toolchain are refused.
+        let mut foreign = source.bundle();
+        foreign.version.solver = "other".into();
+        assert!(matches!(
+            target.merge(foreign),
+            Err(CacheError::VersionMismatch { .. })
+        ));
+    }
 }
@@ -0,0 +1,655 @@
+//! Independent checking of proof witnesses.
+//!
+//! `ProofChecker` re-establishes a verified obligation from the
+//! `Certificate` in its witness, without trusting the pass that produced it:
+//!
+//! - **Interval**: every binding must be justified by the graph (the
+//!   variable names the bound port, or is the refinement variable of an edge
+//!   obligation whose source is that port), the port's range recomputed from
+//!   the graph must lie within the bound, and interval evaluation under the
+//!   bindings must prove the predicate. Both steps use `exact`, a small
+//!   evaluator over rationals that shares no code with `interval`, so a
+//!   rounding slip in the analyzer cannot carry over into the replay.
+//! - **Structural**: the named check is run again on the graph.
+//! - **Unsat**: the script must be exactly the `smtlib::proof_script` of
+//!   the obligation, and an `UnsatChecker` must accept the solver's proof.
+//!   The proof is whatever the solver printed for `(get-proof)`, in its own
+//!   format (for cvc5, the one `--proof-format-mode` selects), so the
+//!   checker must be one for that format. Only `smtlib::ProcessSolver`
+//!   produces proofs; the linked Z3 backend does not.
+//!
+//! The certification profile runs every verified obligation through the
+//! checker and refuses those that fail.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use thiserror::Error;
+use torc_core::contract::binding::parse_port_var;
+use torc_core::contract::ObligationKind;
+use torc_core::fixed;
+use torc_core::graph::edge::PortRef;
+use torc_core::graph::node::{ArithmeticOp, NodeKind};
+use torc_core::graph::Graph;
+use torc_core::types::Type;
+use torc_core::value::Value;
+
+use crate::exact::{self, at_most, Range, Ratio};
+use crate::registry::TrackedObligation;
+use crate::smtlib::{proof_script, run_process, RunError};
+use crate::witness::{verify_witness, Binding, Certificate};
+
+/// Why a witness failed to check.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CheckError {
+    #[error("no witness")]
+    MissingWitness,
+    #[error("witness hash does not match the obligation")]
+    HashMismatch,
+    #[error("witness from {solver} carries no certificate")]
+    NoCertificate { solver: String },
+    #[error("binding of `{var}` to {port:?} is not justified by the graph")]
+    UnjustifiedBinding { var: String, port: PortRef },
+    #[error("range of {port:?} is not within the bound assumed for `{var}`")]
+    RangeExceeded { var: String, port: PortRef },
+    #[error("interval evaluation does not prove the predicate")]
+    IntervalReplayFailed,
+    #[error("structural check `{check}` does not hold")]
+    StructuralCheckFailed { check: String },
+    #[error("certificate script does not encode the obligation")]
+    ScriptMismatch,
+    #[error("no checker for UNSAT proofs is configured")]
+    NoUnsatChecker,
+    #[error("UNSAT proof rejected: {reason}")]
+    ProofRejected { reason: String },
+}
+
+/// Validates a solver's proof that a script is unsatisfiable.
+///
+/// The script is an `smtlib::proof_script` and the proof the solver's answer
+/// to its `(get-proof)`.
+pub trait UnsatChecker {
+    /// Accept `proof` as a refutation of `script`, or say why not.
+    fn check(&self, script: &str, proof: &str) -> Result<(), String>;
+}
+
+/// An external proof checker, run as
+/// `<program> <args>... <script file> <proof file>`; exit status 0 accepts.
+#[derive(Debug, Clone)]
+pub struct ProcessProofChecker {
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ProcessProofChecker {
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            timeout: Duration::from_secs(600),
+        }
+    }
+
+    /// Give up on a proof after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl UnsatChecker for ProcessProofChecker {
+    fn check(&self, script: &str, proof: &str) -> Result<(), String> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let stem = std::env::temp_dir().join(format!(
+            "torc-proof-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let (script_path, proof_path) = (stem.with_extension("smt2"), stem.with_extension("proof"));
+        let written = fs::write(&script_path, script).and_then(|()| fs::write(&proof_path, proof));
+
+        let mut args = self.args.clone();
+        args.push(script_path.display().to_string());
+        args.push(proof_path.display().to_string());
+        let result = match written {
+            Err(e) => Err(format!("writing proof files: {e}")),
+            Ok(()) => match run_process(&self.program, &args, "", self.timeout) {
+                Ok((status, _)) if status.success() => Ok(()),
+                Ok((_, output)) => Err(format!(
+                    "{} rejected the proof: {}",
+                    self.program.display(),
+                    output.trim()
+                )),
+                Err(RunError::Timeout) => Err("proof checker timed out".into()),
+                Err(RunError::Failed(reason)) => Err(reason),
+            },
+        };
+        let _ = fs::remove_file(&script_path);
+        let _ = fs::remove_file(&proof_path);
+        result
+    }
+}
+
+/// Checks the witnesses of obligations on one graph.
+pub struct ProofChecker<'a> {
+    graph: &'a Graph,
+    ranges: HashMap<PortRef, (Ratio, Ratio)>,
+    unsat: Option<&'a dyn UnsatChecker>,
+}
+
+impl<'a> ProofChecker<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            ranges: replay_ranges(graph),
+            unsat: None,
+        }
+    }
+
+    /// Validate UNSAT certificates with `checker`. Without one they are
+    /// rejected.
+    pub fn with_unsat_checker(mut self, checker: &'a dyn UnsatChecker) -> Self {
+        self.unsat = Some(checker);
+        self
+    }
+
+    /// Check the witness of a verified obligation.
+    pub fn check(&self, tracked: &TrackedObligation) -> Result<(), CheckError> {
+        let obligation = &tracked.obligation;
+        let witness = obligation
+            .witness
+            .as_ref()
+            .ok_or(CheckError::MissingWitness)?;
+        if !verify_witness(witness, obligation) {
+            return Err(CheckError::HashMismatch);
+        }
+        let certificate =
+            Certificate::decode(&witness.data).ok_or_else(|| CheckError::NoCertificate {
+                solver: witness.solver.clone(),
+            })?;
+
+        match certificate {
+            Certificate::Interval { bindings } => self.check_interval(tracked, &bindings),
+            Certificate::Structural { check } => {
+                let holds = match check.as_str() {
+                    "ownership" => {
+                        obligation.kind == ObligationKind::Linearity
+                            && self.graph.validate_ownership().is_ok()
+                    }
+                    _ => false,
+                };
+                if holds {
+                    Ok(())
+                } else {
+                    Err(CheckError::StructuralCheckFailed { check })
+                }
+            }
+            Certificate::Unsat {
+                script: certified,
+                proof,
+            } => {
+                if proof_script(&obligation.predicate.simplify()).as_deref()
+                    != Some(certified.as_str())
+                {
+                    return Err(CheckError::ScriptMismatch);
+                }
+                let checker = self.unsat.ok_or(CheckError::NoUnsatChecker)?;
+                checker
+                    .check(&certified, &proof)
+                    .map_err(|reason| CheckError::ProofRejected { reason })
+            }
+        }
+    }
+
+    fn check_interval(
+        &self,
+        tracked: &TrackedObligation,
+        bindings: &[Binding],
+    ) -> Result<(), CheckError> {
+        let edge_source = tracked
+            .edge_id
+            .and_then(|id| self.graph.get_edge(&id))
+            .map(|edge| edge.source);
+        let vars = tracked.obligation.predicate.free_vars();
+        let single_var = vars.first().filter(|_| vars.len() == 1);
+        for binding in bindings {
+            let (var, port) = (&binding.var, binding.port);
+            let justified = parse_port_var(var) == Some(port)
+                || (edge_source == Some(port) && single_var == Some(var));
+            if !justified {
+                return Err(CheckError::UnjustifiedBinding {
+                    var: var.clone(),
+                    port,
+                });
+            }
+            let within = self.ranges.get(&port).is_some_and(|&(lo, hi)| {
+                let exact_bound = |bound: f64| Ratio::of_f64(bound);
+                binding
+                    .range
+                    .lo
+                    .is_none_or(|b| exact_bound(b).is_some_and(|b| at_most(b, lo)))
+                    && binding
+                        .range
+                        .hi
+                        .is_none_or(|b| exact_bound(b).is_some_and(|b| at_most(hi, b)))
+            });
+            if !within {
+                return Err(CheckError::RangeExceeded {
+                    var: var.clone(),
+                    port,
+                });
+            }
+        }
+        let env = bindings
+            .iter()
+            .map(|b| {
+                let range = Range {
+                    lo: b.range.lo.and_then(Ratio::of_f64),
+                    hi: b.range.hi.and_then(Ratio::of_f64),
+                };
+                (b.var.clone(), range)
+            })
+            .collect();
+        if exact::proves(&tracked.obligation.predicate, &env) {
+            Ok(())
+        } else {
+            Err(CheckError::IntervalReplayFailed)
+        }
+    }
+}
+
+/// The bounds of each output port that the checker can establish on its
+/// own: literals are points, and `Add`, `Sub`, `Mul` and `Div` (by a range
+/// excluding zero) of two bounded inputs give the bounds of the exact
+/// result, widened to the output type's resolution. A result that may leave
+/// its output type gets no bounds, whatever the node's overflow behavior,
+/// and neither does a floating-point result, whose rounding the checker does
+/// not model, nor any other port, so a certificate relying on one is
+/// refused.
+fn replay_ranges(graph: &Graph) -> HashMap<PortRef, (Ratio, Ratio)> {
+    let mut ranges = HashMap::new();
+    for id in graph.topological_sort().unwrap_or_default() {
+        let Some(node) = graph.get_node(&id) else {
+            continue;
+        };
+        let input = |port| {
+            let edge = graph.input_edge((id, port))?;
+            ranges.get(&edge.source).copied()
+        };
+        let range = match &node.kind {
+            NodeKind::Literal => match node.value.as_ref() {
+                Some(Value::Int(v)) => v.to_i128().map(Ratio::int),
+                Some(Value::Float(v)) => Ratio::of_f64(v.value()),
+                Some(Value::Fixed(v)) => Ratio::pow2(-(v.frac_bits() as i32))
+                    .and_then(|step| Ratio::int(v.raw()).mul(step)),
+                _ => None,
+            }
+            .map(|v| (v, v)),
+            NodeKind::Arithmetic(op) => input(0)
+                .zip(input(1))
+                .and_then(|(a, b)| arithmetic(*op, a, b))
+                .and_then(|range| {
+                    let ty = node.type_signature.as_ref()?.outputs.first()?;
+                    fit(range, ty)
+                }),
+            _ => None,
+        };
+        if let Some(range) = range {
+            ranges.insert((id, 0), range);
+        }
+    }
+    ranges
+}
+
+/// The bounds of `a op b` for every `a` and `b` within the given bounds.
+fn arithmetic(
+    op: ArithmeticOp,
+    (a, b): (Ratio, Ratio),
+    (c, d): (Ratio, Ratio),
+) -> Option<(Ratio, Ratio)> {
+    let (lhs, rhs) = (
+        Range {
+            lo: Some(a),
+            hi: Some(b),
+        },
+        Range {
+            lo: Some(c),
+            hi: Some(d),
+        },
+    );
+    match op {
+        ArithmeticOp::Add => lhs.add(&rhs),
+        ArithmeticOp::Sub => lhs.sub(&rhs),
+        ArithmeticOp::Mul => lhs.mul(&rhs),
+        ArithmeticOp::Div => lhs.div(&rhs),
+        ArithmeticOp::Mod | ArithmeticOp::Pow => return None,
+    }
+    .bounds()
+}
+
+/// `range` widened outward to the resolution of `ty`, if it stays within
+/// the values `ty` represents and `ty` is an integer or fixed-point type.
+fn fit((lo, hi): (Ratio, Ratio), ty: &Type) -> Option<(Ratio, Ratio)> {
+    let (step, (min, max)) = match ty.base_type() {
+        Type::Int { width, signedness } => (Ratio::int(1), fixed::int_range(*width, *signedness)),
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => (
+            Ratio::pow2(-(*frac_bits as i32))?,
+            fixed::raw_range(*total_bits),
+        ),
+        _ => return None,
+    };
+    let lo = lo.div(step)?.floor();
+    let hi = hi.div(step)?.ceil()?;
+    (at_most(Ratio::int(min), lo) && at_most(hi, Ratio::int(max)))
+        .then(|| Some((lo.mul(step)?, hi.mul(step)?)))
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::contract::{ProofObligation, ProofStatus};
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::{Node, NodeKind};
+    use torc_core::types::{Predicate, Type, TypeSignature};
+    use torc_core::value::Value;
+
+    use crate::interval::{Interval, IntervalAnalyzer};
+    use crate::witness::generate_witness;
+
+    /// A literal 42 flowing into an input refined to [0, 4095], with the
+    /// edge obligation that carries the refinement.
+    fn refined_edge() -> (Graph, TrackedObligation) {
+        let mut g = Graph::new();
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_value(Value::i32(42));
+        let sink = Node::new(NodeKind::Write).with_type_signature(TypeSignature::sink(
+            Type::i32().refined(Predicate::in_range("value", 0, 4095)),
+        ));
+        let (lit_id, sink_id) = (lit.id, sink.id);
+        g.add_node(lit).unwrap();
+        g.add_node(sink).unwrap();
+        let edge_id = g.add_edge(Edge::new((lit_id, 0), (sink_id, 0))).unwrap();
+        let (_, obligation) = g.edge_obligations().unwrap().remove(0);
+        let tracked = TrackedObligation {
+            id: 0,
+            obligation,
+            node_id: None,
+            edge_id: Some(edge_id),
+            region_id: None,
+        };
+        (g, tracked)
+    }
+
+    fn with_certificate(
+        tracked: &TrackedObligation,
+        certificate: Certificate,
+    ) -> TrackedObligation {
+        let mut tracked = tracked.clone();
+        let witness = generate_witness("test", &tracked.obligation, certificate.encode());
+        tracked.obligation.status = ProofStatus::Verified;
+        tracked.obligation.witness = Some(witness);
+        tracked
+    }
+
+    #[test]
+    fn interval_certificates_replay() {
+        let (g, tracked) = refined_edge();
+        let (_, _, certificate) = IntervalAnalyzer::derive_in_graph(&g, &[&tracked]).remove(0);
+        let checker = ProofChecker::new(&g);
+        assert_eq!(
+            checker.check(&with_certificate(&tracked, certificate.clone())),
+            Ok(())
+        );
+
+        let Certificate::Interval { bindings } = certificate else {
+            panic!("expected an interval certificate");
+        };
+        let port = bindings[0].port;
+
+        // Claiming a tighter range than the graph gives
+        let mut narrowed = bindings.clone();
+        narrowed[0].range = Interval::point(7.0);
+        assert!(matches!(
+            checker.check(&with_certificate(
+                &tracked,
+                Certificate::Interval { bindings: narrowed }
+            )),
+            Err(CheckError::RangeExceeded { .. })
+        ));
+
+        // Binding the variable to a port it does not stand for
+        let mut elsewhere = bindings;
+        elsewhere[0].port = (port.0, 1);
+        assert!(matches!(
+            checker.check(&with_certificate(
+                &tracked,
+                Certificate::Interval {
+                    bindings: elsewhere
+                }
+            )),
+            Err(CheckError::UnjustifiedBinding { .. })
+        ));
+
+        // No bindings at all: the predicate alone does not hold
+        assert_eq!(
+            checker.check(&with_certificate(
+                &tracked,
+                Certificate::Interval { bindings: vec![] }
+            )),
+            Err(CheckError::IntervalReplayFailed)
+        );
+
+        // Witnesses without certificates, or tampered with
+        let mut bare = with_certificate(&tracked, Certificate::Interval { bindings: vec![] });
+        bare.obligation.witness = Some(generate_witness("z3", &tracked.obligation, vec![]));
+        assert!(matches!(
+            checker.check(&bare),
+            Err(CheckError::NoCertificate { .. })
+        ));
+        bare.obligation.witness.as_mut().unwrap().data = b"{}".to_vec();
+        assert_eq!(checker.check(&bare), Err(CheckError::HashMismatch));
+    }
+
+    #[test]
+    fn ranges_replayed_from_literals_and_arithmetic() {
+        use torc_core::graph::node::Node;
+
+        let mut g = Graph::new();
+        let mut literal = |ty: Type, value| {
+            g.add_node(
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(ty))
+                    .with_value(value),
+            )
+            .unwrap()
+        };
+        let (seven, two, zero) = (
+            literal(Type::i32(), Value::i32(7)),
+            literal(Type::i32(), Value::i32(2)),
+            literal(Type::i32(), Value::i32(0)),
+        );
+        let (hundred, three) = (
+            literal(Type::u8(), Value::u8(100)),
+            literal(Type::u8(), Value::u8(3)),
+        );
+        let mut binary = |op, ty: Type, lhs, rhs| {
+            let id = g
+                .add_node(
+                    Node::new(NodeKind::Arithmetic(op))
+                        .with_type_signature(TypeSignature::new(vec![ty.clone(); 2], vec![ty])),
+                )
+                .unwrap();
+            g.add_edge(Edge::new((lhs, 0), (id, 0))).unwrap();
+            g.add_edge(Edge::new((rhs, 0), (id, 1))).unwrap();
+            id
+        };
+        let sum = binary(ArithmeticOp::Add, Type::i32(), seven, two);
+        let half = binary(ArithmeticOp::Div, Type::i32(), seven, two);
+        let by_zero = binary(ArithmeticOp::Div, Type::i32(), seven, zero);
+        let wraps = binary(ArithmeticOp::Mul, Type::u8(), hundred, three);
+
+        let ranges = replay_ranges(&g);
+        assert_eq!(ranges[&(sum, 0)], (Ratio::int(9), Ratio::int(9)));
+        assert_eq!(ranges[&(half, 0)], (Ratio::int(3), Ratio::int(4)));
+        assert!(!ranges.contains_key(&(by_zero, 0)));
+        assert!(!ranges.contains_key(&(wraps, 0)));
+    }
+
+    #[test]
+    fn rounded_certificates_are_rejected() {
+        use torc_core::contract::binding::port_var;
+
+        // What evaluating in plain f64 accepts: bounds rounded to the
+        // nearest f64, and sums that round back down to the limit
+        let certify = |value: i64, limit: i128| {
+            let mut g = Graph::new();
+            let id = g
+                .add_node(
+                    Node::new(NodeKind::Literal)
+                        .with_type_signature(TypeSignature::source(Type::i64()))
+                        .with_value(Value::i64(value)),
+                )
+                .unwrap();
+            let var = port_var((id, 0));
+            let predicate = Predicate::Le(
+                Box::new(Predicate::Add(
+                    Box::new(Predicate::Var(var.clone())),
+                    Box::new(Predicate::IntLit(1)),
+                )),
+                Box::new(Predicate::IntLit(limit)),
+            );
+            let tracked = TrackedObligation {
+                id: 0,
+                obligation: ProofObligation {
+                    kind: ObligationKind::NoOverflow,
+                    predicate,
+                    description: "x + 1 does not overflow".into(),
+                    status: ProofStatus::Pending,
+                    witness: None,
+                    waiver: None,
+                },
+                node_id: Some(id),
+                edge_id: None,
+                region_id: None,
+            };
+            let certificate = Certificate::Interval {
+                bindings: vec![Binding {
+                    var,
+                    port: (id, 0),
+                    range: Interval::point(value as f64),
+                }],
+            };
+            let result = ProofChecker::new(&g).check(&with_certificate(&tracked, certificate));
+            result
+        };
+
+        // 2^53 + 1 <= 2^53 only after rounding
+        assert_eq!(
+            certify(1 << 53, 1 << 53),
+            Err(CheckError::IntervalReplayFailed)
+        );
+        // i64::MAX as an f64 is 2^63, which does not bound i64::MAX from below
+        assert!(matches!(
+            certify(i64::MAX, i64::MAX as i128),
+            Err(CheckError::RangeExceeded { .. })
+        ));
+        // The same certificates hold where the arithmetic is exact
+        assert_eq!(certify(1 << 52, 1 << 53), Ok(()));
+    }
+
+    struct Accepts(&'static str);
+
+    impl UnsatChecker for Accepts {
+        fn check(&self, _: &str, proof: &str) -> Result<(), String> {
+            if proof == self.0 {
+                Ok(())
+            } else {
+                Err("not a proof".into())
+            }
+        }
+    }
+
+    #[test]
+    fn unsat_certificates_need_a_checker() {
+        let x = || Box::new(Predicate::Var("x".into()));
+        let predicate = Predicate::Gt(
+            Box::new(Predicate::Add(x(), Box::new(Predicate::IntLit(1)))),
+            x(),
+        );
+        let tracked = TrackedObligation {
+            id: 0,
+            obligation: ProofObligation {
+                kind: ObligationKind::Postcondition,
+                predicate: predicate.clone(),
+                description: "x + 1 > x".into(),
+                status: ProofStatus::Pending,
+                witness: None,
+                waiver: None,
+            },
+            node_id: None,
+            edge_id: None,
+            region_id: None,
+        };
+        let unsat = |script: Option<String>, proof: &str| {
+            with_certificate(
+                &tracked,
+                Certificate::Unsat {
+                    script: script.unwrap(),
+                    proof: proof.into(),
+                },
+            )
+        };
+        let g = Graph::new();
+        let genuine = unsat(proof_script(&predicate.simplify()), "(proof)");
+
+        assert_eq!(
+            ProofChecker::new(&g).check(&genuine),
+            Err(CheckError::NoUnsatChecker)
+        );
+        let accepts = Accepts("(proof)");
+        let checker = ProofChecker::new(&g).with_unsat_checker(&accepts);
+        assert_eq!(checker.check(&genuine), Ok(()));
+        assert!(matches!(
+            checker.check(&unsat(proof_script(&predicate.simplify()), "(bogus)")),
+            Err(CheckError::ProofRejected { .. })
+        ));
+        assert_eq!(
+            checker.check(&unsat(proof_script(&Predicate::BoolLit(true)), "(proof)")),
+            Err(CheckError::ScriptMismatch)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn external_proof_checker() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mock-checker");
+        // Accepts a proof file mentioning `refl` for a script with check-sat
+        fs::write(
+            &path,
+            "#!/bin/sh\ngrep -q check-sat \"$1\" && grep -q refl \"$2\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let checker = ProcessProofChecker::new(&path, Vec::new());
+        assert_eq!(checker.check("(check-sat)", "(proof refl)"), Ok(()));
+        assert!(checker.check("(check-sat)", "(proof trust)").is_err());
+        assert!(
+            ProcessProofChecker::new(dir.path().join("missing"), Vec::new())
+                .check("(check-sat)", "(proof refl)")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! Counterexamples mapped back onto the graph.
+//!
+//! When interval analysis or a solver disproves an obligation, the failing
+//! assignment names predicate variables. `Counterexample::locate` ties each
+//! variable to the output port producing its value and, where there is one,
+//! the input port consuming it:
+//!
+//! | Variable                        | Output port             | Input port          |
+//! |---------------------------------|-------------------------|---------------------|
+//! | port variable `<node>.<port>`   | the port it names       | the edge into the obligation's node, if any |
+//! | refinement variable of an edge  | the edge's source       | the edge's target   |
+//! | contract variable of a node     | its bound output, or the source of its bound input | its bound input |
+//!
+//! A `TestVector` turns a counterexample into inputs for the reference
+//! interpreter: values for `Read` nodes and replacement values for `Literal`
+//! nodes. Replaying it and checking `TestVector::fails_on` against the
+//! produced values confirms the failure on a concrete run.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use torc_core::contract::binding::{parse_port_var, PortBinding};
+use torc_core::graph::edge::PortRef;
+use torc_core::graph::node::{NodeId, NodeKind};
+use torc_core::graph::port::PortDirection;
+use torc_core::graph::Graph;
+use torc_core::types::{Predicate, Type};
+use torc_core::value::{FixedValue, FloatValue, IntValue, Value};
+
+use crate::registry::TrackedObligation;
+use crate::witness::Binding;
+
+/// The value a counterexample gives one variable, and where it flows.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub var: String,
+    pub value: String,
+    /// The output port producing the value.
+    pub output: Option<PortRef>,
+    /// The input port of the obligation's node consuming it.
+    pub input: Option<PortRef>,
+}
+
+/// A concrete assignment under which an obligation fails.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Counterexample {
+    pub obligation_id: u64,
+    pub description: String,
+    /// The node the obligation belongs to (an edge's consumer for edge
+    /// obligations).
+    pub node: Option<NodeId>,
+    /// The pass that found it: `interval_domain` or a solver name.
+    pub found_by: String,
+    pub predicate: Predicate,
+    /// Assignments in variable order.
+    pub assignments: Vec<Assignment>,
+}
+
+impl Counterexample {
+    /// Map a failing assignment of `tracked`'s variables onto `graph`.
+    pub fn locate(
+        graph: &Graph,
+        tracked: &TrackedObligation,
+        found_by: &str,
+        values: &HashMap<String, String>,
+    ) -> Self {
+        let edge = tracked.edge_id.and_then(|id| graph.get_edge(&id));
+        let node = tracked.node_id.or(edge.map(|e| e.target.0));
+        let contract = node
+            .and_then(|id| graph.get_node(&id))
+            .and_then(|n| n.contract.as_ref());
+        let refined = tracked.obligation.predicate.free_vars();
+        let refined = refined.first().filter(|_| refined.len() == 1);
+
+        let values: BTreeMap<_, _> = values.iter().collect();
+        let assignments = values
+            .into_iter()
+            .map(|(var, value)| {
+                let (output, input) = if let Some(output) = parse_port_var(var) {
+                    let input = node.and_then(|node| {
+                        graph
+                            .incoming_edges(&node)
+                            .iter()
+                            .filter_map(|id| graph.get_edge(id))
+                            .find(|e| e.source == output)
+                            .map(|e| e.target)
+                    });
+                    (Some(output), input)
+                } else if let (Some(edge), Some(_)) = (edge, refined.filter(|v| *v == var)) {
+                    (Some(edge.source), Some(edge.target))
+                } else {
+                    let binding = match contract {
+                        Some(c) => c.binding(var),
+                        None => PortBinding::conventional(var),
+                    };
+                    match (node, binding) {
+                        (Some(node), Some(b)) if b.direction == PortDirection::Output => {
+                            (Some((node, b.port)), None)
+                        }
+                        (Some(node), Some(b)) => {
+                            let input = (node, b.port);
+                            (graph.input_source(input), Some(input))
+                        }
+                        _ => (None, None),
+                    }
+                };
+                Assignment {
+                    var: var.clone(),
+                    value: normalize_number(value),
+                    output,
+                    input,
+                }
+            })
+            .collect();
+
+        Self {
+            obligation_id: tracked.id,
+            description: tracked.obligation.description.clone(),
+            node,
+            found_by: found_by.to_string(),
+            predicate: tracked.obligation.predicate.clone(),
+            assignments,
+        }
+    }
+
+    /// The assignment as a variable-to-value map.
+    pub fn values(&self) -> HashMap<String, String> {
+        self.assignments
+            .iter()
+            .map(|a| (a.var.clone(), a.value.clone()))
+            .collect()
+    }
+
+    /// Inputs that make the reference interpreter reproduce the failure.
+    pub fn test_vector(&self, graph: &Graph) -> TestVector {
+        let mut vector = TestVector {
+            obligation: self.description.clone(),
+            predicate: self.predicate.clone(),
+            observe: BTreeMap::new(),
+            reads: BTreeMap::new(),
+            literals: BTreeMap::new(),
+            unmapped: Vec::new(),
+        };
+        for a in &self.assignments {
+            let Some(output) = a.output else {
+                vector.unmapped.push(a.var.clone());
+                continue;
+            };
+            vector.observe.insert(a.var.clone(), output);
+            let Some(node) = graph.get_node(&output.0) else {
+                vector.unmapped.push(a.var.clone());
+                continue;
+            };
+            let value = node
+                .type_signature
+                .as_ref()
+                .and_then(|s| s.outputs.get(output.1))
+                .and_then(|ty| typed_value(ty, &a.value));
+            match (&node.kind, value) {
+                (NodeKind::Read, Some(v)) => {
+                    vector.reads.insert(node.id, v);
+                }
+                (NodeKind::Literal, Some(v)) => {
+                    vector.literals.insert(node.id, v);
+                }
+                // Computed values can only be observed, not set.
+                _ => vector.unmapped.push(a.var.clone()),
+            }
+        }
+        vector
+    }
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{}: {} (found by {})",
+            self.obligation_id, self.description, self.found_by
+        )?;
+        if let Some(node) = self.node {
+            write!(f, " at node {node}")?;
+        }
+        for a in &self.assignments {
+            write!(f, "\n  {} = {}", a.var, a.value)?;
+            match (a.output, a.input) {
+                (Some((n, p)), Some((m, q))) => write!(f, "  [{n}:{p} -> {m}:{q}]")?,
+                (Some((n, p)), None) => write!(f, "  [{n}:{p}]")?,
+                (None, Some((m, q))) => write!(f, "  [-> {m}:{q}]")?,
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interpreter inputs reproducing a counterexample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub obligation: String,
+    pub predicate: Predicate,
+    /// The output port whose value each predicate variable reads.
+    pub observe: BTreeMap<String, PortRef>,
+    /// Values to queue for `Read` nodes.
+    pub reads: BTreeMap<NodeId, Value>,
+    /// Replacement values for `Literal` nodes.
+    pub literals: BTreeMap<NodeId, Value>,
+    /// Variables whose values are computed, or could not be placed.
+    pub unmapped: Vec<String>,
+}
+
+impl TestVector {
+    /// Set the vector's literal values in `graph`.
+    pub fn apply(&self, graph: &mut Graph) {
+        for (id, value) in &self.literals {
+            if let Some(node) = graph.get_node_mut(id) {
+                node.value = Some(value.clone());
+            }
+        }
+    }
+
+    /// Whether the obligation fails on the port values of a run, or `None`
+    /// if a value it reads is missing or not numeric.
+    pub fn fails_on(&self, values: &HashMap<PortRef, Value>) -> Option<bool> {
+        let map = self
+            .observe
+            .iter()
+            .map(|(var, port)| Some((var.clone(), literal(values.get(port)?)?)))
+            .collect::<Option<BTreeMap<_, _>>>()?;
+        match self.predicate.substitute(&map).simplify() {
+            Predicate::BoolLit(holds) => Some(!holds),
+            _ => None,
+        }
+    }
+}
+
+/// A point of the ranges interval analysis disproved `predicate` over:
+/// each bound variable's lower bound (else its upper bound, else 0), and 0
+/// for unbound variables. A disproof holds for every such point.
+pub fn representative(predicate: &Predicate, bindings: &[Binding]) -> HashMap<String, String> {
+    let mut values: HashMap<String, String> = predicate
+        .free_vars()
+        .into_iter()
+        .map(|var| (var, "0".to_string()))
+        .collect();
+    for b in bindings {
+        let x = b.range.lo.or(b.range.hi).unwrap_or(0.0);
+        values.insert(b.var.clone(), x.to_string());
+    }
+    values
+}
+
+/// A value as a predicate literal.
+fn literal(value: &Value) -> Option<Predicate> {
+    match value {
+        Value::Bool(b) => Some(Predicate::BoolLit(*b)),
+        Value::Int(v) => Some(Predicate::IntLit(v.to_i128()?)),
+        Value::Float(v) => Some(Predicate::FloatLit(v.value())),
+        Value::Fixed(v) => Some(Predicate::FloatLit(v.to_f64())),
+        _ => None,
+    }
+}
+
+/// Solver output such as `(- 4)` as plain `-4`.
+fn normalize_number(text: &str) -> String {
+    let text = text.trim();
+    match text
+        .strip_prefix("(-")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        Some(inner) => format!("-{}", inner.trim()),
+        None => text.to_string(),
+    }
+}
+
+/// Parse a counterexample value as a value of `ty`, if it fits.
+fn typed_value(ty: &Type, text: &str) -> Option<Value> {
+    match *ty.base_type() {
+        Type::Bool => text.parse().ok().map(Value::Bool),
+        Type::Int { width, signedness } => {
+            let n: i128 = text.parse().ok()?;
+            IntValue::checked(n, width, signedness).map(Value::Int)
+        }
+        Type::Float { precision } => {
+            let x: f64 = text.parse().ok()?;
+            Some(Value::Float(FloatValue::new(x, precision)))
+        }
+        Type::Fixed {
+            total_bits,
+            frac_bits,
+        } => {
+            let x: f64 = text.parse().ok()?;
+            FixedValue::from_f64(x, total_bits, frac_bits).map(Value::Fixed)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::contract::binding::port_var;
+    use torc_core::contract::{Contract, ObligationKind, ProofObligation, ProofStatus};
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::Node;
+    use torc_core::types::TypeSignature;
+
+    fn tracked(predicate: Predicate, node: Option<NodeId>) -> TrackedObligation {
+        TrackedObligation {
+            id: 7,
+            obligation: ProofObligation {
+                kind: ObligationKind::Precondition,
+                predicate,
+                description: "test".into(),
+                status: ProofStatus::Pending,
+                witness: None,
+                waiver: None,
+            },
+            node_id: node,
+            edge_id: None,
+            region_id: None,
+        }
+    }
+
+    #[test]
+    fn variables_map_to_ports() {
+        // read -> write, the writer requiring `x < 100` of its input
+        let mut g = Graph::new();
+        let read =
+            Node::new(NodeKind::Read).with_type_signature(TypeSignature::source(Type::i32()));
+        let write = Node::new(NodeKind::Write)
+            .with_type_signature(TypeSignature::sink(Type::i32()))
+            .with_contract(
+                Contract::with_conditions(
+                    vec![Predicate::Lt(
+                        Box::new(Predicate::Var("x".into())),
+                        Box::new(Predicate::IntLit(100)),
+                    )],
+                    vec![],
+                )
+                .with_binding("x", PortBinding::input(0)),
+            );
+        let (r, w) = (read.id, write.id);
+        g.add_node(read).unwrap();
+        g.add_node(write).unwrap();
+        g.add_edge(Edge::new((r, 0), (w, 0))).unwrap();
+
+        let predicate = Predicate::Lt(
+            Box::new(Predicate::Var("x".into())),
+            Box::new(Predicate::IntLit(100)),
+        );
+        let values = HashMap::from([("x".to_string(), "(- 5)".to_string())]);
+        let ce = Counterexample::locate(&g, &tracked(predicate.clone(), Some(w)), "z3", &values);
+        assert_eq!(ce.node, Some(w));
+        assert_eq!(
+            ce.assignments,
+            vec![Assignment {
+                var: "x".into(),
+                value: "-5".into(),
+                output: Some((r, 0)),
+                input: Some((w, 0)),
+            }]
+        );
+        assert!(ce.to_string().contains("x = -5"));
+
+        // The same failure over the port variable
+        let var = port_var((r, 0));
+        let bound = g.bind_predicate(w, &predicate);
+        let values = HashMap::from([(var.clone(), "100".to_string())]);
+        let ce = Counterexample::locate(&g, &tracked(bound, Some(w)), "z3", &values);
+        assert_eq!(ce.assignments[0].output, Some((r, 0)));
+        assert_eq!(ce.assignments[0].input, Some((w, 0)));
+
+        let vector = ce.test_vector(&g);
+        assert_eq!(vector.reads.get(&r), Some(&Value::i32(100)));
+        assert!(vector.unmapped.is_empty());
+        let run = HashMap::from([((r, 0), Value::i32(100))]);
+        assert_eq!(vector.fails_on(&run), Some(true));
+        let run = HashMap::from([((r, 0), Value::i32(3))]);
+        assert_eq!(vector.fails_on(&run), Some(false));
+        assert_eq!(vector.fails_on(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn out_of_range_values_are_not_placed() {
+        assert_eq!(typed_value(&Type::u8(), "300"), None);
+        assert_eq!(typed_value(&Type::u8(), "-1"), None);
+        assert_eq!(typed_value(&Type::i32(), "-7"), Some(Value::i32(-7)));
+        assert_eq!(normalize_number(" (- 12) "), "-12");
+    }
+}
@@ -2,115 +2,416 @@
 //!
 //! Ties together structural analysis, interval analysis, SMT solving,
 //! proof witness generation, and caching into a single `verify()` pipeline.
+//!
+//! Under a profile with `check_witnesses` (certification), every verified
+//! obligation is re-checked from the certificate in its witness by
+//! `checker::ProofChecker`, and those that fail are refused: they go back to
+//! pending with an error diagnostic.
+//!
+//! Given a baseline `Snapshot` from an earlier run, unchanged obligations
+//! keep their earlier status and only new or changed ones are analyzed; see
+//! `incremental`.
+//!
+//! Cached proofs are looked up by the same obligation fingerprints and the
+//! solvers in use, so a proof is only reused while the graph it depended on
+//! is unchanged. Proofs imported from a bundle are re-checked by
+//! `ProofChecker` before reuse.
+//!
+//! Solvers added with `with_cross_checker` re-check every obligation the
+//! main solver decides; if any of them concludes the opposite, the
+//! obligation stays pending with an error diagnostic.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use torc_core::contract::ProofStatus;
 use torc_core::graph::Graph;
 
-use crate::cache::ProofCache;
+use crate::cache::{cache_key, ProofCache};
+use crate::checker::{ProofChecker, UnsatChecker};
+use crate::counterexample::Counterexample;
+use crate::incremental::{fingerprints, Snapshot};
 use crate::interval::{IntervalAnalyzer, IntervalResult};
 use crate::profile::{ProfileLevel, SmtScope, VerificationProfile};
-use crate::registry::ObligationRegistry;
-use crate::report::VerificationReport;
+use crate::registry::{ObligationRegistry, TrackedObligation};
+use crate::report::{Diagnostic, Severity, VerificationReport};
+use crate::smt::{cross_check, CrossCheck, SmtResult, SolverBackend};
+use crate::smtlib;
 use crate::structural::StructuralAnalyzer;
-use crate::witness::generate_witness;
+use crate::witness::{generate_witness, Certificate};
 
 /// The main verification engine.
 pub struct VerificationEngine {
     profile: VerificationProfile,
     cache: ProofCache,
+    solver: Option<Box<dyn SolverBackend>>,
+    cross_checkers: Vec<Box<dyn SolverBackend>>,
+    unsat_checker: Option<Box<dyn UnsatChecker>>,
+    baseline: Option<Snapshot>,
+    snapshot: Option<Snapshot>,
 }
 
 impl VerificationEngine {
-    /// Create a new engine with the given profile.
+    /// Create a new engine with the given profile. SMT solving uses Z3 when
+    /// the `z3` feature is enabled, and is skipped otherwise unless a solver
+    /// is set with `with_solver`.
     pub fn new(profile: VerificationProfile) -> Self {
+        #[cfg(feature = "z3")]
+        let solver: Option<Box<dyn SolverBackend>> =
+            Some(Box::new(crate::smt::SmtSolver::new(profile.solver_timeout)));
+        #[cfg(not(feature = "z3"))]
+        let solver = None;
         Self {
             profile,
             cache: ProofCache::new(),
+            solver,
+            cross_checkers: Vec::new(),
+            unsat_checker: None,
+            baseline: None,
+            snapshot: None,
         }
     }
 
+    /// Use `cache` for proof reuse, e.g. a disk-backed one from
+    /// `ProofCache::open`, instead of a fresh in-memory cache.
+    pub fn with_cache(mut self, cache: ProofCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Discharge obligations with `solver`, e.g. an external binary through
+    /// `smtlib::ProcessSolver`, instead of the built-in Z3.
+    pub fn with_solver(mut self, solver: Box<dyn SolverBackend>) -> Self {
+        self.solver = Some(solver);
+        self
+    }
+
+    /// Also run `solver` on every obligation the main solver decides, and
+    /// refuse the verdict if it concludes the opposite.
+    pub fn with_cross_checker(mut self, solver: Box<dyn SolverBackend>) -> Self {
+        self.cross_checkers.push(solver);
+        self
+    }
+
+    /// Validate solvers' UNSAT proofs with `checker` when checking
+    /// witnesses. Without one, SMT proofs are refused by certification.
+    pub fn with_unsat_checker(mut self, checker: Box<dyn UnsatChecker>) -> Self {
+        self.unsat_checker = Some(checker);
+        self
+    }
+
+    /// Verify incrementally against `snapshot`, the outcome of an earlier
+    /// run. Ignored if the snapshot is not usable at this profile.
+    pub fn with_baseline(mut self, snapshot: Snapshot) -> Self {
+        self.baseline = Some(snapshot);
+        self
+    }
+
+    /// The outcome of the last `verify`, to use as a later baseline.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// The engine's proof cache.
+    pub fn cache_mut(&mut self) -> &mut ProofCache {
+        &mut self.cache
+    }
+
     /// Run the full verification pipeline on a graph.
     pub fn verify(&mut self, graph: &Graph) -> VerificationReport {
         // 1. Collect obligations
         let mut registry = ObligationRegistry::collect_from_graph(graph);
 
-        // 2. Check cache — reuse cached proofs (skip for certification)
+        // 2. Carry forward what a previous run concluded about unchanged
+        //    obligations, with the counterexample of any that failed
+        let fingerprints = fingerprints(graph, &registry);
+        let mut reused = HashSet::new();
+        let mut counterexamples = BTreeMap::new();
+        if let Some(baseline) = self
+            .baseline
+            .as_ref()
+            .filter(|b| b.usable_at(self.profile.level))
+        {
+            for (&id, fingerprint) in &fingerprints {
+                if let Some(entry) = baseline.get(fingerprint) {
+                    registry.update_status(id, entry.status, entry.witness.clone());
+                    if let Some(waiver) = entry.waiver.clone() {
+                        registry.apply_waiver(id, waiver);
+                    }
+                    if let Some(mut ce) = entry.counterexample.clone() {
+                        ce.obligation_id = id;
+                        counterexamples.insert(id, ce);
+                    }
+                    reused.insert(id);
+                }
+            }
+        }
+
+        // 3. Check cache — reuse cached proofs (skip for certification);
+        //    imported ones only if their certificates check on this graph
+        let backends: Vec<&str> = self
+            .solver
+            .iter()
+            .chain(&self.cross_checkers)
+            .map(|s| s.name())
+            .collect();
+        let keys: HashMap<u64, String> = fingerprints
+            .iter()
+            .map(|(&id, fingerprint)| (id, cache_key(fingerprint, &backends)))
+            .collect();
         if self.profile.level != ProfileLevel::Certification {
             let ids_to_cache: Vec<u64> = registry.pending().map(|o| o.id).collect();
+            let mut checker = None;
             for id in ids_to_cache {
-                if let Some(tracked) = registry.get(id) {
-                    if let Some(witness) = self.cache.lookup(&tracked.obligation) {
-                        let w = witness.clone();
-                        registry.update_status(id, ProofStatus::Verified, Some(w));
+                let (Some(tracked), Some(key)) = (registry.get(id), keys.get(&id)) else {
+                    continue;
+                };
+                let Some(entry) = self.cache.lookup(key).cloned() else {
+                    continue;
+                };
+                if entry.imported {
+                    let checker = checker.get_or_insert_with(|| {
+                        witness_checker(graph, self.unsat_checker.as_deref())
+                    });
+                    let mut candidate = tracked.clone();
+                    candidate.obligation.witness = Some(entry.witness.clone());
+                    if checker.check(&candidate).is_err() {
+                        continue;
                     }
                 }
+                registry.update_status(id, ProofStatus::Verified, Some(entry.witness));
             }
         }
 
-        // 3. Structural analysis
+        // 4. Structural analysis
         let structural_diagnostics = if self.profile.run_structural {
             StructuralAnalyzer::analyze(graph, &mut registry)
         } else {
             Vec::new()
         };
 
-        // 4. Interval analysis on remaining pending obligations
+        // 5. Interval analysis on remaining pending, changed obligations
         if self.profile.run_interval {
-            let pending: Vec<_> = registry.pending().collect();
-            let results = IntervalAnalyzer::analyze(&pending);
+            let pending: Vec<_> = registry
+                .pending()
+                .filter(|o| !reused.contains(&o.id))
+                .collect();
+            let results = IntervalAnalyzer::derive_in_graph(graph, &pending);
 
-            for (id, result) in results {
+            for (id, result, certificate) in results {
                 match result {
                     IntervalResult::Proven => {
                         if let Some(tracked) = registry.get(id) {
-                            let witness =
-                                generate_witness("interval_domain", &tracked.obligation, vec![]);
-                            self.cache.store(&tracked.obligation, witness.clone());
+                            let witness = generate_witness(
+                                "interval_domain",
+                                &tracked.obligation,
+                                certificate.encode(),
+                            );
+                            if let Some(key) = keys.get(&id) {
+                                self.cache.store(key, witness.clone());
+                            }
                             registry.update_status(id, ProofStatus::Verified, Some(witness));
                         }
                     }
-                    IntervalResult::Disproven { .. } | IntervalResult::Inconclusive => {
+                    IntervalResult::Disproven { .. } => {
+                        // Any point of the ranges fails; leave pending for
+                        // SMT or manual review
+                        if let (Some(tracked), Certificate::Interval { bindings }) =
+                            (registry.get(id), certificate)
+                        {
+                            let values = crate::counterexample::representative(
+                                &tracked.obligation.predicate,
+                                &bindings,
+                            );
+                            let ce =
+                                Counterexample::locate(graph, tracked, "interval_domain", &values);
+                            counterexamples.insert(id, ce);
+                        }
+                    }
+                    IntervalResult::Inconclusive => {
                         // Leave pending for SMT or manual review
                     }
                 }
             }
         }
 
-        // 5. SMT solving (feature-gated)
-        #[cfg(feature = "z3")]
-        {
+        // 6. SMT solving, if a solver is available; `ChangedOnly` leaves
+        //    obligations carried forward as they were. Cross-checkers must
+        //    not contradict the solver's verdict.
+        let mut disagreements = Vec::new();
+        if let Some(solver) = self.solver.as_deref() {
             if self.profile.run_smt != SmtScope::Skip {
-                let solver = crate::smt::SmtSolver::new(self.profile.solver_timeout);
-
-                let pending_ids: Vec<u64> = registry.pending().map(|o| o.id).collect();
+                let pending_ids: Vec<u64> = registry
+                    .pending()
+                    .filter(|o| self.profile.run_smt == SmtScope::All || !reused.contains(&o.id))
+                    .map(|o| o.id)
+                    .collect();
                 for id in pending_ids {
                     if let Some(tracked) = registry.get(id) {
-                        let result = solver.check_obligation(&tracked.obligation);
-                        match result {
-                            crate::smt::SmtResult::Proven => {
-                                let witness = generate_witness("z3", &tracked.obligation, vec![]);
-                                self.cache.store(&tracked.obligation, witness.clone());
-                                registry.update_status(id, ProofStatus::Verified, Some(witness));
+                        let (result, proof) = if self.profile.check_witnesses {
+                            solver.check_with_proof(&tracked.obligation)
+                        } else {
+                            (solver.check_obligation(&tracked.obligation), None)
+                        };
+                        if let Some(check) = self.cross_check(solver, &result, tracked) {
+                            disagreements.push((id, check));
+                            continue;
+                        }
+                        if let SmtResult::Disproven { counterexample } = &result {
+                            let ce = Counterexample::locate(
+                                graph,
+                                tracked,
+                                solver.name(),
+                                counterexample,
+                            );
+                            counterexamples.insert(id, ce);
+                        }
+                        if let SmtResult::Proven = result {
+                            counterexamples.remove(&id);
+                            let script =
+                                smtlib::proof_script(&tracked.obligation.predicate.simplify());
+                            let data = match (script, proof) {
+                                (Some(script), Some(proof)) => {
+                                    Certificate::Unsat { script, proof }.encode()
+                                }
+                                _ => Vec::new(),
+                            };
+                            let witness =
+                                generate_witness(solver.name(), &tracked.obligation, data);
+                            if let Some(key) = keys.get(&id) {
+                                self.cache.store(key, witness.clone());
                             }
-                            _ => {}
+                            registry.update_status(id, ProofStatus::Verified, Some(witness));
                         }
                     }
                 }
             }
         }
 
-        // Suppress unused variable warning when z3 feature is not enabled
-        #[cfg(not(feature = "z3"))]
-        let _ = SmtScope::Skip;
+        // 7. Check witnesses independently, refusing proofs that fail
+        let mut refused = Vec::new();
+        if self.profile.check_witnesses {
+            let checker = witness_checker(graph, self.unsat_checker.as_deref());
+            for tracked in registry.all() {
+                if tracked.obligation.status == ProofStatus::Verified {
+                    if let Err(e) = checker.check(tracked) {
+                        refused.push((tracked.id, e));
+                    }
+                }
+            }
+            for (id, _) in &refused {
+                registry.update_status(*id, ProofStatus::Pending, None);
+            }
+        }
 
-        // 6. Build report
+        // 8. Build report
         let cache_stats = self.cache.statistics();
-        VerificationReport::build(
+        let mut report = VerificationReport::build(
             &registry,
             &cache_stats,
             self.profile.level,
             &structural_diagnostics,
-        )
+        );
+        report.reused = registry
+            .all()
+            .iter()
+            .map(|o| o.id)
+            .filter(|id| reused.contains(id))
+            .collect();
+        report.summary.reused = report.reused.len();
+        for ce in counterexamples.into_values() {
+            if let Some(diag) = report
+                .diagnostics
+                .iter_mut()
+                .find(|d| d.obligation_id == ce.obligation_id)
+            {
+                diag.severity = Severity::Error;
+                diag.message = format!("obligation fails: {}", ce.description);
+                diag.counterexample = Some(ce.values());
+            }
+            report.counterexamples.push(ce);
+        }
+        for (id, check) in disagreements {
+            let description = registry
+                .get(id)
+                .map(|t| t.obligation.description.clone())
+                .unwrap_or_default();
+            let verdicts: Vec<_> = check
+                .results
+                .iter()
+                .map(|(name, result)| format!("{name}: {}", verdict(result)))
+                .collect();
+            report.diagnostics.push(Diagnostic {
+                obligation_id: id,
+                severity: Severity::Error,
+                message: format!("solvers disagree ({}): {description}", verdicts.join(", ")),
+                context: "cross-check".into(),
+                counterexample: None,
+                suggestions: vec!["Report the disagreement to the solvers' maintainers".into()],
+            });
+        }
+        for (id, error) in refused {
+            let description = registry
+                .get(id)
+                .map(|t| t.obligation.description.clone())
+                .unwrap_or_default();
+            report.diagnostics.push(Diagnostic {
+                obligation_id: id,
+                severity: Severity::Error,
+                message: format!("proof refused: {error}: {description}"),
+                context: "witness check".into(),
+                counterexample: None,
+                suggestions: vec!["Re-verify with a solver that produces checkable proofs".into()],
+            });
+        }
+        self.snapshot = Some(Snapshot::capture(
+            graph,
+            &registry,
+            &report.counterexamples,
+            self.profile.level,
+        ));
+        report
+    }
+
+    /// Run the cross-checkers on an obligation `solver` concluded `result`
+    /// for, returning all verdicts if one contradicts it.
+    fn cross_check(
+        &self,
+        solver: &dyn SolverBackend,
+        result: &SmtResult,
+        tracked: &TrackedObligation,
+    ) -> Option<CrossCheck> {
+        if self.cross_checkers.is_empty()
+            || !matches!(result, SmtResult::Proven | SmtResult::Disproven { .. })
+        {
+            return None;
+        }
+        let others: Vec<&dyn SolverBackend> =
+            self.cross_checkers.iter().map(|s| s.as_ref()).collect();
+        let mut check = cross_check(&others, &tracked.obligation);
+        check
+            .results
+            .insert(0, (solver.name().to_string(), result.clone()));
+        check.conflicting().then_some(check)
+    }
+}
+
+/// A one-word summary of a solver's result, for diagnostics.
+fn verdict(result: &SmtResult) -> &'static str {
+    match result {
+        SmtResult::Proven => "proven",
+        SmtResult::Disproven { .. } => "refuted",
+        SmtResult::Unknown { .. } => "unknown",
+        SmtResult::Timeout => "timeout",
+    }
+}
+
+/// A checker for witnesses on `graph`, validating UNSAT proofs with
+/// `unsat` if there is one.
+fn witness_checker<'a>(graph: &'a Graph, unsat: Option<&'a dyn UnsatChecker>) -> ProofChecker<'a> {
+    let checker = ProofChecker::new(graph);
+    match unsat {
+        Some(unsat) => checker.with_unsat_checker(unsat),
+        None => checker,
     }
 }
 
@@ -119,7 +420,7 @@ mod tests {
     use super::*;
     use torc_core::contract::Contract;
     use torc_core::graph::edge::Edge;
-    use torc_core::graph::node::{Node, NodeKind};
+    use torc_core::graph::node::{Node, NodeId, NodeKind};
     use torc_core::types::{Predicate, Type, TypeSignature};
 
     fn make_simple_graph() -> Graph {
@@ -173,6 +474,408 @@ mod tests {
         assert!(report2.summary.cache_hits > 0 || report2.summary.verified >= verified1);
     }
 
+    #[test]
+    fn disk_cache_shared_across_engines() {
+        let dir = tempfile::tempdir().unwrap();
+        let g = make_simple_graph();
+        let open = || {
+            VerificationEngine::new(VerificationProfile::development())
+                .with_cache(ProofCache::open(dir.path()).unwrap())
+        };
+
+        let first = open().verify(&g);
+        assert_eq!(first.summary.cache_hits, 0);
+        let second = open().verify(&g);
+        assert_eq!(second.summary.cache_hits, first.summary.verified);
+    }
+
+    /// `7 / divisor`, with the literal node feeding the divisor.
+    fn division(divisor: i32) -> (Graph, NodeId) {
+        use torc_core::graph::node::ArithmeticOp;
+        use torc_core::value::Value;
+
+        let mut g = Graph::new();
+        let literal = |v| {
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(Type::i32()))
+                .with_value(Value::i32(v))
+        };
+        let lhs = g.add_node(literal(7)).unwrap();
+        let rhs = g.add_node(literal(divisor)).unwrap();
+        let div = g
+            .add_node(
+                Node::new(NodeKind::Arithmetic(ArithmeticOp::Div)).with_type_signature(
+                    TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
+                ),
+            )
+            .unwrap();
+        g.add_edge(Edge::new((lhs, 0), (div, 0))).unwrap();
+        g.add_edge(Edge::new((rhs, 0), (div, 1))).unwrap();
+        (g, rhs)
+    }
+
+    #[test]
+    fn cached_proofs_do_not_survive_upstream_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let open = || {
+            VerificationEngine::new(VerificationProfile::development())
+                .with_cache(ProofCache::open(dir.path()).unwrap())
+        };
+        let (mut g, divisor) = division(2);
+        let first = open().verify(&g);
+        assert!(first.counterexamples.is_empty());
+        assert_eq!(first.summary.verified, first.summary.total);
+
+        // The divisor obligation is unchanged, but the range it was proven
+        // from is not.
+        g.get_node_mut(&divisor).unwrap().value = Some(torc_core::value::Value::i32(0));
+        let second = open().verify(&g);
+        assert_eq!(second.summary.cache_hits, 0);
+        assert_eq!(second.counterexamples.len(), 1);
+        assert!(second.summary.verified < second.summary.total);
+    }
+
+    #[test]
+    fn imported_proofs_are_rechecked() {
+        let (g, _) = division(2);
+        let bundle = |tamper: bool| {
+            let mut engine = VerificationEngine::new(VerificationProfile::development());
+            engine.verify(&g);
+            let mut bundle = engine.cache_mut().bundle();
+            if tamper {
+                for entry in &mut bundle.entries {
+                    entry.witness.data.clear();
+                }
+            }
+            bundle
+        };
+        let import = |bundle| {
+            let mut cache = ProofCache::new();
+            cache.merge(bundle).unwrap();
+            VerificationEngine::new(VerificationProfile::development())
+                .with_cache(cache)
+                .verify(&g)
+        };
+
+        let genuine = import(bundle(false));
+        assert_eq!(genuine.summary.cache_hits, genuine.summary.total);
+        assert_eq!(genuine.summary.verified, genuine.summary.total);
+
+        // Witnesses without certificates are hits, but not trusted: the
+        // obligations are proven again.
+        let tampered = bundle(true);
+        assert_eq!(tampered.entries.len(), genuine.summary.total);
+        let mut cache = ProofCache::new();
+        cache.merge(tampered).unwrap();
+        let mut engine =
+            VerificationEngine::new(VerificationProfile::development()).with_cache(cache);
+        let report = engine.verify(&g);
+        assert_eq!(report.summary.verified, report.summary.total);
+        assert!(engine
+            .cache_mut()
+            .bundle()
+            .entries
+            .iter()
+            .all(|e| !e.imported && !e.witness.data.is_empty()));
+    }
+
+    #[test]
+    fn incremental_run_reuses_unchanged_obligations() {
+        let mut g = make_simple_graph();
+        let mut engine = VerificationEngine::new(VerificationProfile::development());
+        let first = engine.verify(&g);
+        assert_eq!(first.summary.reused, 0);
+        let baseline = engine.snapshot().unwrap().clone();
+
+        let second = VerificationEngine::new(VerificationProfile::development())
+            .with_baseline(baseline.clone())
+            .verify(&g);
+        assert_eq!(second.summary.reused, second.summary.total);
+        assert_eq!(second.summary.verified, first.summary.verified);
+
+        // Editing the literal invalidates its obligations.
+        let lit = g.nodes().find(|n| n.contract.is_some()).unwrap().id;
+        g.get_node_mut(&lit)
+            .unwrap()
+            .annotations
+            .insert("note".into(), "edited".into());
+        let third = VerificationEngine::new(VerificationProfile::development())
+            .with_baseline(baseline.clone())
+            .verify(&g);
+        assert!(third.summary.reused < third.summary.total);
+        assert_eq!(third.summary.verified, first.summary.verified);
+
+        // Certification re-establishes everything.
+        let certified = VerificationEngine::new(VerificationProfile::certification())
+            .with_baseline(baseline)
+            .verify(&g);
+        assert_eq!(certified.summary.reused, 0);
+    }
+
+    #[test]
+    fn external_solver_discharges_what_intervals_cannot() {
+        struct Oracle;
+        impl SolverBackend for Oracle {
+            fn name(&self) -> &str {
+                "oracle"
+            }
+            fn check_obligation(&self, _: &torc_core::contract::ProofObligation) -> SmtResult {
+                SmtResult::Proven
+            }
+        }
+
+        // x + 1 > x needs a solver: intervals lose the relation between
+        // the two occurrences of x.
+        let mut g = Graph::new();
+        let x = || Box::new(Predicate::Var("x".into()));
+        let node = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_contract(Contract::with_conditions(
+                vec![],
+                vec![Predicate::Gt(
+                    Box::new(Predicate::Add(x(), Box::new(Predicate::IntLit(1)))),
+                    x(),
+                )],
+            ));
+        g.add_node(node).unwrap();
+
+        let without = VerificationEngine::new(VerificationProfile::integration()).verify(&g);
+        let with = VerificationEngine::new(VerificationProfile::integration())
+            .with_solver(Box::new(Oracle))
+            .verify(&g);
+        assert_eq!(with.summary.verified, with.summary.total);
+        if !crate::Z3_AVAILABLE {
+            assert!(without.summary.verified < without.summary.total);
+        }
+    }
+
+    #[test]
+    fn cross_checkers_must_not_contradict_the_solver() {
+        struct Fixed(&'static str, fn() -> SmtResult);
+        impl SolverBackend for Fixed {
+            fn name(&self) -> &str {
+                self.0
+            }
+            fn check_obligation(&self, _: &torc_core::contract::ProofObligation) -> SmtResult {
+                (self.1)()
+            }
+        }
+        let proves = || Box::new(Fixed("proves", || SmtResult::Proven));
+        let refutes = || {
+            Box::new(Fixed("refutes", || SmtResult::Disproven {
+                counterexample: Default::default(),
+            }))
+        };
+        let unsure = || {
+            Box::new(Fixed("unsure", || SmtResult::Unknown {
+                reason: "incomplete".into(),
+            }))
+        };
+
+        // x * x >= 0 is beyond intervals.
+        let mut g = Graph::new();
+        let x = || Box::new(Predicate::Var("x".into()));
+        let node = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_contract(Contract::with_conditions(
+                vec![],
+                vec![Predicate::Ge(
+                    Box::new(Predicate::Mul(x(), x())),
+                    Box::new(Predicate::IntLit(0)),
+                )],
+            ));
+        g.add_node(node).unwrap();
+        let engine = || VerificationEngine::new(VerificationProfile::integration());
+
+        let agreed = engine()
+            .with_solver(proves())
+            .with_cross_checker(unsure())
+            .verify(&g);
+        assert_eq!(agreed.summary.verified, agreed.summary.total);
+
+        let disputed = engine()
+            .with_solver(proves())
+            .with_cross_checker(unsure())
+            .with_cross_checker(refutes())
+            .verify(&g);
+        assert_eq!(disputed.summary.verified, 0);
+        assert!(disputed.counterexamples.is_empty());
+        let diag = disputed
+            .diagnostics
+            .iter()
+            .find(|d| d.context == "cross-check")
+            .unwrap();
+        assert_eq!(diag.severity, Severity::Error);
+        assert!(diag
+            .message
+            .contains("proves: proven, unsure: unknown, refutes: refuted"));
+    }
+
+    #[test]
+    fn disproven_obligations_carry_located_counterexamples() {
+        // A literal 12 flows into an input refined to 0..=9.
+        let mut g = Graph::new();
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_value(torc_core::value::Value::i32(12));
+        let sink = Node::new(NodeKind::Write).with_type_signature(TypeSignature::sink(
+            Type::i32().refined(Predicate::in_range("value", 0, 9)),
+        ));
+        let lit = g.add_node(lit).unwrap();
+        let sink = g.add_node(sink).unwrap();
+        g.add_edge(Edge::new((lit, 0), (sink, 0))).unwrap();
+
+        let mut engine = VerificationEngine::new(VerificationProfile::development());
+        let report = engine.verify(&g);
+        let ce = &report.counterexamples[0];
+        assert_eq!(ce.found_by, "interval_domain");
+        assert_eq!(ce.node, Some(sink));
+        assert_eq!(ce.assignments[0].value, "12");
+        assert_eq!(ce.assignments[0].output, Some((lit, 0)));
+        assert_eq!(ce.assignments[0].input, Some((sink, 0)));
+
+        let diag = report
+            .diagnostics
+            .iter()
+            .find(|d| d.obligation_id == ce.obligation_id)
+            .unwrap();
+        assert_eq!(diag.severity, Severity::Error);
+        assert_eq!(diag.counterexample, Some(ce.values()));
+        assert!(report.to_string().contains("--- Counterexamples ---"));
+
+        // Reusing the failure from a baseline reports it again
+        let rerun = VerificationEngine::new(VerificationProfile::development())
+            .with_baseline(engine.snapshot().unwrap().clone())
+            .verify(&g);
+        assert_eq!(rerun.summary.reused, rerun.summary.total);
+        assert_eq!(rerun.counterexamples.len(), report.counterexamples.len());
+        let ce = &rerun.counterexamples[0];
+        let diag = rerun
+            .diagnostics
+            .iter()
+            .find(|d| d.obligation_id == ce.obligation_id)
+            .unwrap();
+        assert_eq!(diag.severity, Severity::Error);
+        assert_eq!(diag.counterexample, Some(ce.values()));
+    }
+
+    #[test]
+    fn constant_recursion_metrics_are_disproven() {
+        use torc_core::graph::node::ArithmeticOp;
+        use torc_core::value::Value;
+
+        // Counts down from 10, but reports the same metric every time
+        let mut g = Graph::new();
+        let mut literal = |value| {
+            g.add_node(
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(Type::u32()))
+                    .with_value(value),
+            )
+            .unwrap()
+        };
+        let (start, one, five) = (
+            literal(Value::u32(10)),
+            literal(Value::u32(1)),
+            literal(Value::u32(5)),
+        );
+        let rec = g
+            .add_node(
+                Node::new(NodeKind::Recurse).with_type_signature(TypeSignature::new(
+                    vec![Type::u32(), Type::Bool, Type::u32(), Type::u32()],
+                    vec![Type::u32()],
+                )),
+            )
+            .unwrap();
+        let step = g
+            .add_node(Node::new(NodeKind::Arithmetic(ArithmeticOp::Sub)))
+            .unwrap();
+        for (from, to) in [
+            ((start, 0), (rec, 0)),
+            ((rec, 0), (step, 0)),
+            ((one, 0), (step, 1)),
+            ((step, 0), (rec, 2)),
+            ((five, 0), (rec, 3)),
+        ] {
+            g.add_edge(Edge::new(from, to)).unwrap();
+        }
+
+        let report = VerificationEngine::new(VerificationProfile::development()).verify(&g);
+        let refuted: Vec<_> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .collect();
+        assert_eq!(refuted.len(), 1);
+        assert!(refuted[0].message.contains("must strictly decrease"));
+        assert!(report
+            .counterexamples
+            .iter()
+            .any(|ce| ce.obligation_id == refuted[0].obligation_id && ce.node == Some(rec)));
+    }
+
+    #[test]
+    fn certification_refuses_unchecked_proofs() {
+        struct Oracle;
+        impl SolverBackend for Oracle {
+            fn name(&self) -> &str {
+                "oracle"
+            }
+            fn check_obligation(&self, _: &torc_core::contract::ProofObligation) -> SmtResult {
+                SmtResult::Proven
+            }
+            fn check_with_proof(
+                &self,
+                obligation: &torc_core::contract::ProofObligation,
+            ) -> (SmtResult, Option<String>) {
+                (self.check_obligation(obligation), Some("(proof)".into()))
+            }
+        }
+        struct Trusting;
+        impl UnsatChecker for Trusting {
+            fn check(&self, _: &str, proof: &str) -> Result<(), String> {
+                (proof == "(proof)")
+                    .then_some(())
+                    .ok_or_else(|| "bad proof".into())
+            }
+        }
+
+        // 10 > 5 is proven by intervals; x * x >= 0 needs the solver.
+        let mut g = make_simple_graph();
+        let x = || Box::new(Predicate::Var("x".into()));
+        let node = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_contract(Contract::with_conditions(
+                vec![],
+                vec![Predicate::Ge(
+                    Box::new(Predicate::Mul(x(), x())),
+                    Box::new(Predicate::IntLit(0)),
+                )],
+            ));
+        g.add_node(node).unwrap();
+
+        let unchecked = VerificationEngine::new(VerificationProfile::certification())
+            .with_solver(Box::new(Oracle))
+            .verify(&g);
+        assert_eq!(unchecked.summary.verified, unchecked.summary.total - 1);
+        assert!(unchecked
+            .diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error && d.message.starts_with("proof refused")));
+
+        let checked = VerificationEngine::new(VerificationProfile::certification())
+            .with_solver(Box::new(Oracle))
+            .with_unsat_checker(Box::new(Trusting))
+            .verify(&g);
+        assert_eq!(checked.summary.verified, checked.summary.total);
+
+        // Below certification, witnesses are taken as they are.
+        let integration = VerificationEngine::new(VerificationProfile::integration())
+            .with_solver(Box::new(Oracle))
+            .verify(&g);
+        assert_eq!(integration.summary.verified, integration.summary.total);
+    }
+
     #[test]
     fn profile_respecting() {
         let g = make_simple_graph();
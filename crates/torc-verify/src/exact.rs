@@ -0,0 +1,433 @@
+//! Exact rational interval evaluation, used by the proof checker.
+//!
+//! This is a second implementation of what `interval` does, kept apart from
+//! it so that replaying a certificate does not trust the code that produced
+//! it. Bounds are rationals with `i128` parts, so nothing is rounded: an
+//! operation whose bound does not fit loses that bound instead, and a
+//! comparison that cannot be decided exactly is not proven.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use torc_core::types::Predicate;
+
+/// A rational number `num / den` in lowest terms, with `den > 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    pub fn int(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    fn new(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let divisor = gcd(num.unsigned_abs(), den.unsigned_abs());
+        // The divisor is at most |den|, so it fits an i128 unless den is
+        // i128::MIN and num is 0 or i128::MIN
+        let divisor = i128::try_from(divisor).ok()?;
+        let (num, den) = (num / divisor, den / divisor);
+        if den < 0 {
+            Some(Self {
+                num: num.checked_neg()?,
+                den: den.checked_neg()?,
+            })
+        } else {
+            Some(Self { num, den })
+        }
+    }
+
+    /// `2^exp`, if it fits.
+    pub fn pow2(exp: i32) -> Option<Self> {
+        let power = 2i128.checked_pow(exp.unsigned_abs())?;
+        if exp >= 0 {
+            Some(Self::int(power))
+        } else {
+            Self::new(1, power)
+        }
+    }
+
+    /// The exact value of a finite `f64`, if it fits.
+    pub fn of_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = (bits & ((1 << 52) - 1)) as i128;
+        let (mantissa, exponent) = match exponent {
+            0 => (fraction, -1074),
+            e => (fraction | 1 << 52, e - 1075),
+        };
+        let mantissa = if value < 0.0 { -mantissa } else { mantissa };
+        Self::int(mantissa).mul(Self::pow2(exponent)?)
+    }
+
+    pub fn add(self, other: Self) -> Option<Self> {
+        let num = self
+            .num
+            .checked_mul(other.den)?
+            .checked_add(other.num.checked_mul(self.den)?)?;
+        Self::new(num, self.den.checked_mul(other.den)?)
+    }
+
+    pub fn neg(self) -> Option<Self> {
+        Some(Self {
+            num: self.num.checked_neg()?,
+            den: self.den,
+        })
+    }
+
+    pub fn mul(self, other: Self) -> Option<Self> {
+        // Cross-reduce first so exact products of large values still fit
+        let a = gcd(self.num.unsigned_abs(), other.den.unsigned_abs()).max(1) as i128;
+        let b = gcd(other.num.unsigned_abs(), self.den.unsigned_abs()).max(1) as i128;
+        Self::new(
+            (self.num / a).checked_mul(other.num / b)?,
+            (self.den / b).checked_mul(other.den / a)?,
+        )
+    }
+
+    pub fn div(self, other: Self) -> Option<Self> {
+        let inverse = Self::new(other.den, other.num)?;
+        self.mul(inverse)
+    }
+
+    /// How `self` compares to `other`, if the comparison fits.
+    pub fn compare(self, other: Self) -> Option<Ordering> {
+        Some(
+            self.num
+                .checked_mul(other.den)?
+                .cmp(&other.num.checked_mul(self.den)?),
+        )
+    }
+
+    /// The largest integer not above `self`.
+    pub fn floor(self) -> Self {
+        Self::int(self.num.div_euclid(self.den))
+    }
+
+    /// The smallest integer not below `self`.
+    pub fn ceil(self) -> Option<Self> {
+        let floor = self.num.div_euclid(self.den);
+        if self.num.rem_euclid(self.den) == 0 {
+            Some(Self::int(floor))
+        } else {
+            Some(Self::int(floor.checked_add(1)?))
+        }
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// `a <= b`, if that can be decided.
+pub fn at_most(a: Ratio, b: Ratio) -> bool {
+    matches!(a.compare(b), Some(Ordering::Less | Ordering::Equal))
+}
+
+/// `a < b`, if that can be decided.
+fn below(a: Ratio, b: Ratio) -> bool {
+    a.compare(b) == Some(Ordering::Less)
+}
+
+/// A closed range with exact bounds; a missing bound is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub lo: Option<Ratio>,
+    pub hi: Option<Ratio>,
+}
+
+impl Range {
+    pub const UNBOUNDED: Range = Range { lo: None, hi: None };
+
+    pub fn point(value: Ratio) -> Self {
+        Self {
+            lo: Some(value),
+            hi: Some(value),
+        }
+    }
+
+    /// Both bounds, if the range has them.
+    pub fn bounds(&self) -> Option<(Ratio, Ratio)> {
+        self.lo.zip(self.hi)
+    }
+
+    pub fn add(&self, other: &Range) -> Range {
+        let bound = |a: Option<Ratio>, b: Option<Ratio>| a?.add(b?);
+        Range {
+            lo: bound(self.lo, other.lo),
+            hi: bound(self.hi, other.hi),
+        }
+    }
+
+    pub fn neg(&self) -> Range {
+        Range {
+            lo: self.hi.and_then(Ratio::neg),
+            hi: self.lo.and_then(Ratio::neg),
+        }
+    }
+
+    pub fn sub(&self, other: &Range) -> Range {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Range) -> Range {
+        Self::corners(self, other, Ratio::mul)
+    }
+
+    /// The quotient, over the reals; unbounded unless the divisor's range
+    /// excludes zero.
+    pub fn div(&self, other: &Range) -> Range {
+        let zero = Ratio::int(0);
+        match other.bounds() {
+            Some((lo, hi)) if below(zero, lo) || below(hi, zero) => {
+                Self::corners(self, other, Ratio::div)
+            }
+            _ => Range::UNBOUNDED,
+        }
+    }
+
+    /// The hull of `op` applied to every pair of bounds.
+    fn corners(a: &Range, b: &Range, op: fn(Ratio, Ratio) -> Option<Ratio>) -> Range {
+        let ((a_lo, a_hi), (b_lo, b_hi)) = match a.bounds().zip(b.bounds()) {
+            Some(bounds) => bounds,
+            None => return Range::UNBOUNDED,
+        };
+        let corners =
+            [(a_lo, b_lo), (a_lo, b_hi), (a_hi, b_lo), (a_hi, b_hi)].map(|(x, y)| op(x, y));
+        if corners.iter().any(Option::is_none) {
+            return Range::UNBOUNDED;
+        }
+        let corners = corners.map(Option::unwrap);
+        Range {
+            lo: extreme(&corners, Ordering::Less),
+            hi: extreme(&corners, Ordering::Greater),
+        }
+    }
+
+    fn min(&self, other: &Range) -> Range {
+        Range {
+            lo: self
+                .lo
+                .zip(other.lo)
+                .and_then(|(a, b)| extreme(&[a, b], Ordering::Less)),
+            hi: match (self.hi, other.hi) {
+                (Some(a), Some(b)) => extreme(&[a, b], Ordering::Less),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    fn max(&self, other: &Range) -> Range {
+        self.neg().min(&other.neg()).neg()
+    }
+
+    fn abs(&self) -> Range {
+        let zero = Ratio::int(0);
+        match (self.lo, self.hi) {
+            (Some(lo), _) if at_most(zero, lo) => *self,
+            (_, Some(hi)) if at_most(hi, zero) => self.neg(),
+            _ => Range {
+                lo: Some(zero),
+                hi: self
+                    .neg()
+                    .hi
+                    .zip(self.hi)
+                    .and_then(|(a, b)| extreme(&[a, b], Ordering::Greater)),
+            },
+        }
+    }
+}
+
+/// The least (`Less`) or greatest (`Greater`) of `values`, if every
+/// comparison fits.
+fn extreme(values: &[Ratio], which: Ordering) -> Option<Ratio> {
+    let mut best = *values.first()?;
+    for &value in &values[1..] {
+        if value.compare(best)? == which {
+            best = value;
+        }
+    }
+    Some(best)
+}
+
+/// Whether `predicate` holds for every assignment of its variables within
+/// their ranges in `env`; variables without one are unbounded.
+///
+/// Division is widened to the integers around the real quotient, which
+/// covers both real and integer division.
+pub fn proves(predicate: &Predicate, env: &HashMap<String, Range>) -> bool {
+    let compare = |a: &Predicate, b: &Predicate| (eval(a, env), eval(b, env));
+    match predicate {
+        Predicate::BoolLit(value) => *value,
+        Predicate::Lt(a, b) | Predicate::Gt(b, a) => {
+            let (a, b) = compare(a, b);
+            a.hi.zip(b.lo).is_some_and(|(a, b)| below(a, b))
+        }
+        Predicate::Le(a, b) | Predicate::Ge(b, a) => {
+            let (a, b) = compare(a, b);
+            a.hi.zip(b.lo).is_some_and(|(a, b)| at_most(a, b))
+        }
+        Predicate::Ne(a, b) => {
+            let (a, b) = compare(a, b);
+            a.hi.zip(b.lo).is_some_and(|(a, b)| below(a, b))
+                || b.hi.zip(a.lo).is_some_and(|(b, a)| below(b, a))
+        }
+        Predicate::And(a, b) => proves(a, env) && proves(b, env),
+        Predicate::Or(a, b) => proves(a, env) || proves(b, env),
+        Predicate::Implies(a, b) => {
+            let assumed = conjuncts(a)
+                .into_iter()
+                .map(Predicate::canonicalize)
+                .collect::<Vec<_>>();
+            if conjuncts(b)
+                .into_iter()
+                .all(|c| assumed.contains(&c.canonicalize()))
+            {
+                return true;
+            }
+            let mut narrowed = env.clone();
+            assume(a, &mut narrowed);
+            proves(b, &narrowed)
+        }
+        _ => false,
+    }
+}
+
+/// Narrow `env` with the bounds that comparisons between a variable and an
+/// expression over the others, in a conjunction, place on the variable.
+/// Strict bounds are kept non-strict.
+fn assume(predicate: &Predicate, env: &mut HashMap<String, Range>) {
+    let (lhs, rhs, lower, upper) = match predicate {
+        Predicate::And(a, b) => {
+            assume(a, env);
+            assume(b, env);
+            return;
+        }
+        Predicate::Ge(a, b) | Predicate::Gt(a, b) => (a, b, true, false),
+        Predicate::Le(a, b) | Predicate::Lt(a, b) => (a, b, false, true),
+        Predicate::Eq(a, b) => (a, b, true, true),
+        _ => return,
+    };
+    let (var, bound, lower, upper) = match (&**lhs, &**rhs) {
+        (Predicate::Var(x), k) => (x, k, lower, upper),
+        (k, Predicate::Var(x)) => (x, k, upper, lower),
+        _ => return,
+    };
+    let k = eval(bound, env);
+    let current = env.entry(var.clone()).or_insert(Range::UNBOUNDED);
+    if lower {
+        current.lo = match (current.lo, k.lo) {
+            (Some(a), Some(b)) => extreme(&[a, b], Ordering::Greater).or(Some(a)),
+            (a, b) => a.or(b),
+        };
+    }
+    if upper {
+        current.hi = match (current.hi, k.hi) {
+            (Some(a), Some(b)) => extreme(&[a, b], Ordering::Less).or(Some(a)),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// The range of an arithmetic expression.
+fn eval(expr: &Predicate, env: &HashMap<String, Range>) -> Range {
+    match expr {
+        Predicate::IntLit(n) => Range::point(Ratio::int(*n)),
+        Predicate::FloatLit(f) => Ratio::of_f64(*f).map_or(Range::UNBOUNDED, Range::point),
+        Predicate::Var(name) => env.get(name).copied().unwrap_or(Range::UNBOUNDED),
+        Predicate::Add(a, b) => eval(a, env).add(&eval(b, env)),
+        Predicate::Sub(a, b) => eval(a, env).sub(&eval(b, env)),
+        Predicate::Mul(a, b) => eval(a, env).mul(&eval(b, env)),
+        Predicate::Div(a, b) => {
+            let quotient = eval(a, env).div(&eval(b, env));
+            Range {
+                lo: quotient.lo.map(Ratio::floor),
+                hi: quotient.hi.and_then(Ratio::ceil),
+            }
+        }
+        Predicate::Neg(a) => eval(a, env).neg(),
+        Predicate::Apply(f, args) if f == "max" || f == "min" => args
+            .iter()
+            .map(|a| eval(a, env))
+            .reduce(|a, b| if f == "max" { a.max(&b) } else { a.min(&b) })
+            .unwrap_or(Range::UNBOUNDED),
+        Predicate::Apply(f, args) if f == "abs" && args.len() == 1 => eval(&args[0], env).abs(),
+        _ => Range::UNBOUNDED,
+    }
+}
+
+/// The operands of a chain of `&&`.
+fn conjuncts(predicate: &Predicate) -> Vec<&Predicate> {
+    match predicate {
+        Predicate::And(a, b) => {
+            let mut out = conjuncts(a);
+            out.extend(conjuncts(b));
+            out
+        }
+        other => vec![other],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(num: i128, den: i128) -> Ratio {
+        Ratio::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn ratios_are_exact() {
+        assert_eq!(Ratio::of_f64(0.75), Some(ratio(3, 4)));
+        assert_eq!(Ratio::of_f64(-2.0), Some(Ratio::int(-2)));
+        assert_eq!(Ratio::of_f64(2f64.powi(63)), Some(Ratio::int(1 << 63)));
+        assert_eq!(Ratio::of_f64(f64::MIN_POSITIVE), None);
+        assert_eq!(
+            Ratio::int(i64::MAX as i128).add(Ratio::int(1)),
+            Some(Ratio::int(1 << 63))
+        );
+        assert_eq!(ratio(7, 2).floor(), Ratio::int(3));
+        assert_eq!(ratio(-7, 2).floor(), Ratio::int(-4));
+        assert_eq!(ratio(7, 2).ceil(), Some(Ratio::int(4)));
+        assert_eq!(Ratio::int(i128::MAX).add(Ratio::int(1)), None);
+    }
+
+    #[test]
+    fn proves_only_what_holds_exactly() {
+        let x = || Box::new(Predicate::Var("x".into()));
+        let env = HashMap::from([("x".to_string(), Range::point(Ratio::int(1 << 53)))]);
+        let plus_one = || Box::new(Predicate::Add(x(), Box::new(Predicate::IntLit(1))));
+        let limit = || Box::new(Predicate::IntLit(1 << 53));
+
+        // 2^53 + 1 rounds to 2^53 as an f64
+        assert!(!proves(&Predicate::Le(plus_one(), limit()), &env));
+        assert!(proves(&Predicate::Gt(plus_one(), limit()), &env));
+
+        // Overflowing bounds are dropped, not wrapped
+        let huge = HashMap::from([("x".to_string(), Range::point(Ratio::int(i128::MAX)))]);
+        assert!(!proves(&Predicate::Gt(plus_one(), x()), &huge));
+
+        let implied = Predicate::Implies(
+            Box::new(Predicate::And(
+                Box::new(Predicate::Le(Box::new(Predicate::IntLit(2)), x())),
+                Box::new(Predicate::Le(x(), Box::new(Predicate::IntLit(10)))),
+            )),
+            Box::new(Predicate::Ne(
+                Box::new(Predicate::Div(x(), Box::new(Predicate::IntLit(2)))),
+                Box::new(Predicate::IntLit(0)),
+            )),
+        );
+        assert!(proves(&implied, &HashMap::new()));
+    }
+}
@@ -0,0 +1,258 @@
+//! Incremental verification against a previous run.
+//!
+//! A `Snapshot` records, for each obligation of a verified graph, a
+//! fingerprint of the obligation and of everything in the graph it can
+//! depend on, with the status (and witness, waiver or counterexample) the
+//! run reached. Verifying again with that snapshot as baseline carries
+//! forward the status of every obligation whose fingerprint is unchanged,
+//! reporting its counterexample again if it failed, and re-verifies only the
+//! new or changed ones.
+//!
+//! A fingerprint hashes the canonical `obligation_hash` with its site:
+//!
+//! | Site   | Also hashed                                                  |
+//! |--------|--------------------------------------------------------------|
+//! | Node   | the node's dependency cone and its enclosing region          |
+//! | Edge   | the edge and the consumer's dependency cone                  |
+//! | Region | the region and the dependency cones of its children          |
+//!
+//! Dependency cones come from `Graph::cone_hashes`, so an edit anywhere
+//! upstream of an obligation invalidates it. A snapshot is only used by the
+//! toolchain version that wrote it, and only when its profile was at least
+//! as strict as the current one.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use torc_core::contract::{ProofStatus, ProofWitness, Waiver};
+use torc_core::graph::node::NodeId;
+use torc_core::graph::{Graph, ObligationSite};
+use torc_core::hash::{content_hash, hash_hex, ContentHash};
+
+use crate::cache::{obligation_hash, CacheVersion};
+use crate::counterexample::Counterexample;
+use crate::profile::ProfileLevel;
+use crate::registry::{ObligationRegistry, TrackedObligation};
+
+/// What a verification run concluded about one obligation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub status: ProofStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<ProofWitness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiver: Option<Waiver>,
+    /// Why the obligation failed, if the run disproved it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterexample: Option<Counterexample>,
+}
+
+/// The outcome of a verification run, keyed by obligation fingerprint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: CacheVersion,
+    pub profile: ProfileLevel,
+    pub obligations: BTreeMap<String, SnapshotEntry>,
+}
+
+impl Snapshot {
+    /// Record the statuses `registry` reached on `graph`, and the
+    /// `counterexamples` found for obligations that failed.
+    pub fn capture(
+        graph: &Graph,
+        registry: &ObligationRegistry,
+        counterexamples: &[Counterexample],
+        profile: ProfileLevel,
+    ) -> Self {
+        let fingerprints = fingerprints(graph, registry);
+        let obligations = registry
+            .all()
+            .iter()
+            .filter_map(|tracked| {
+                let entry = SnapshotEntry {
+                    status: tracked.obligation.status,
+                    witness: tracked.obligation.witness.clone(),
+                    waiver: tracked.obligation.waiver.clone(),
+                    counterexample: counterexamples
+                        .iter()
+                        .find(|ce| ce.obligation_id == tracked.id)
+                        .cloned(),
+                };
+                Some((fingerprints.get(&tracked.id)?.clone(), entry))
+            })
+            .collect();
+        Self {
+            version: CacheVersion::current(),
+            profile,
+            obligations,
+        }
+    }
+
+    /// Whether a run at `profile` by this toolchain may reuse the snapshot.
+    ///
+    /// Certification never does: it re-establishes every proof.
+    pub fn usable_at(&self, profile: ProfileLevel) -> bool {
+        profile != ProfileLevel::Certification
+            && self.profile >= profile
+            && self.version == CacheVersion::current()
+    }
+
+    /// The recorded outcome for an obligation fingerprint.
+    pub fn get(&self, fingerprint: &str) -> Option<&SnapshotEntry> {
+        self.obligations.get(fingerprint)
+    }
+}
+
+/// The fingerprint of every obligation in `registry`, by obligation ID.
+pub fn fingerprints(graph: &Graph, registry: &ObligationRegistry) -> HashMap<u64, String> {
+    let cones = graph.cone_hashes();
+    registry
+        .all()
+        .iter()
+        .filter_map(|tracked| Some((tracked.id, fingerprint(graph, &cones, tracked)?)))
+        .collect()
+}
+
+/// An obligation's fingerprint, or `None` if its site is no longer in the
+/// graph.
+fn fingerprint(
+    graph: &Graph,
+    cones: &HashMap<NodeId, ContentHash>,
+    tracked: &TrackedObligation,
+) -> Option<String> {
+    let site = match (tracked.node_id, tracked.edge_id, tracked.region_id) {
+        (Some(node), ..) => ObligationSite::Node(node),
+        (_, Some(edge), _) => ObligationSite::Edge(edge),
+        (.., Some(region)) => ObligationSite::Region(region),
+        _ => return None,
+    };
+    let context = match site {
+        ObligationSite::Node(id) => {
+            let region = graph
+                .containing_region(&id)
+                .and_then(|r| graph.get_region(r));
+            content_hash(&(id, cones.get(&id)?, region))
+        }
+        ObligationSite::Edge(id) => {
+            let edge = graph.get_edge(&id)?;
+            content_hash(&(edge, cones.get(&edge.target.0)?))
+        }
+        ObligationSite::Region(id) => {
+            let region = graph.get_region(&id)?;
+            let children: Vec<_> = region.children.iter().map(|c| cones.get(c)).collect();
+            content_hash(&(region, children))
+        }
+    };
+    let mut hasher = Sha256::new();
+    hasher.update(obligation_hash(&tracked.obligation).as_bytes());
+    hasher.update(hash_hex(&context).as_bytes());
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::contract::Contract;
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::{ArithmeticOp, Node, NodeKind};
+    use torc_core::types::{Predicate, Type, TypeSignature};
+    use torc_core::value::Value;
+
+    fn graph() -> (Graph, Vec<NodeId>) {
+        let mut g = Graph::new();
+        let lit = |v| {
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(Type::i32()))
+                .with_value(Value::i32(v))
+                .with_contract(Contract::with_conditions(
+                    vec![],
+                    vec![Predicate::Ge(
+                        Box::new(Predicate::IntLit(v as i128)),
+                        Box::new(Predicate::IntLit(0)),
+                    )],
+                ))
+        };
+        let (a, b) = (lit(1), lit(2));
+        let add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
+            TypeSignature::pure_fn(vec![Type::i32(), Type::i32()], Type::i32()),
+        );
+        let ids = vec![a.id, b.id, add.id];
+        g.add_node(a).unwrap();
+        g.add_node(b).unwrap();
+        g.add_node(add).unwrap();
+        g.add_edge(Edge::new((ids[0], 0), (ids[2], 0))).unwrap();
+        g.add_edge(Edge::new((ids[1], 0), (ids[2], 1))).unwrap();
+        (g, ids)
+    }
+
+    #[test]
+    fn fingerprints_follow_dependency_cones() {
+        let (mut g, ids) = graph();
+        let registry = ObligationRegistry::collect_from_graph(&g);
+        let before = fingerprints(&g, &registry);
+        let at = |prints: &HashMap<u64, String>, node| {
+            let tracked = registry.all().iter().find(|t| t.node_id == Some(node));
+            prints[&tracked.unwrap().id].clone()
+        };
+
+        // Editing `b` leaves `a`'s obligation alone but not `b`'s.
+        g.get_node_mut(&ids[1])
+            .unwrap()
+            .annotations
+            .insert("note".into(), "edited".into());
+        let after = fingerprints(&g, &registry);
+        assert_eq!(at(&before, ids[0]), at(&after, ids[0]));
+        assert_ne!(at(&before, ids[1]), at(&after, ids[1]));
+    }
+
+    #[test]
+    fn retyped_edges_invalidate_their_consumer() {
+        let (mut g, ids) = graph();
+        g.get_node_mut(&ids[2]).unwrap().contract = Some(Contract::with_conditions(
+            vec![],
+            vec![Predicate::Ge(
+                Box::new(Predicate::Var("result".into())),
+                Box::new(Predicate::IntLit(0)),
+            )],
+        ));
+        let registry = ObligationRegistry::collect_from_graph(&g);
+        let snapshot = Snapshot::capture(&g, &registry, &[], ProfileLevel::Integration);
+        let sum = registry
+            .all()
+            .iter()
+            .find(|t| t.node_id == Some(ids[2]))
+            .unwrap()
+            .id;
+        assert!(snapshot.get(&fingerprints(&g, &registry)[&sum]).is_some());
+
+        // Only the refinement on an edge into the sum changes
+        let edge = g.input_edge((ids[2], 0)).unwrap().id;
+        g.remove_edge(edge).unwrap();
+        let refined = Type::i32().refined(Predicate::in_range("value", 0, 9));
+        g.add_edge(Edge::typed((ids[0], 0), (ids[2], 0), refined))
+            .unwrap();
+        let registry = ObligationRegistry::collect_from_graph(&g);
+        let sum = registry
+            .all()
+            .iter()
+            .find(|t| t.node_id == Some(ids[2]))
+            .unwrap()
+            .id;
+        assert!(snapshot.get(&fingerprints(&g, &registry)[&sum]).is_none());
+    }
+
+    #[test]
+    fn snapshots_respect_profile_and_version() {
+        let (g, _) = graph();
+        let registry = ObligationRegistry::collect_from_graph(&g);
+        let mut snapshot = Snapshot::capture(&g, &registry, &[], ProfileLevel::Integration);
+        assert_eq!(snapshot.obligations.len(), registry.len());
+        assert!(snapshot.usable_at(ProfileLevel::Development));
+        assert!(snapshot.usable_at(ProfileLevel::Integration));
+        assert!(!snapshot.usable_at(ProfileLevel::Certification));
+
+        snapshot.version.engine = "0.0.0-other".into();
+        assert!(!snapshot.usable_at(ProfileLevel::Development));
+    }
+}
@@ -1,13 +1,28 @@
 //! Abstract interpretation with interval domain for pre-screening obligations.
+//!
+//! Bounds are `f64`s rounded outward: an integer that has no exact `f64`
+//! (beyond 2^53) is enclosed by its two neighbours, and a bound computed by
+//! an inexact operation is moved one step away from the interval. Every
+//! interval therefore contains the exact values it stands for, and 64- and
+//! 128-bit ranges stay sound.
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use torc_core::types::Predicate;
+use serde::{Deserialize, Serialize};
+use torc_core::contract::binding::parse_port_var;
+use torc_core::fixed::{self, FixedSemantics, Overflow};
+use torc_core::graph::edge::PortRef;
+use torc_core::graph::node::{ArithmeticOp, NodeKind};
+use torc_core::graph::Graph;
+use torc_core::types::{Predicate, Type};
+use torc_core::value::Value;
 
 use crate::registry::TrackedObligation;
+use crate::witness::{Binding, Certificate};
 
 /// An interval [lo, hi] where None means unbounded in that direction.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Interval {
     pub lo: Option<f64>,
     pub hi: Option<f64>,
@@ -35,15 +50,52 @@ impl Interval {
         }
     }
 
+    /// The smallest interval of `f64`s containing the integer `n`: a point
+    /// if `n` has an exact `f64`, its two neighbours otherwise.
+    pub fn of_int(n: i128) -> Self {
+        let f = n as f64;
+        // `f as i128` saturates, so 2^127 would compare equal to i128::MAX
+        let order = if f >= 2f64.powi(127) {
+            Ordering::Greater
+        } else {
+            (f as i128).cmp(&n)
+        };
+        Self::around(f, order)
+    }
+
+    /// Like `of_int`, for unsigned integers beyond `i128`.
+    fn of_uint(n: u128) -> Self {
+        if let Ok(n) = i128::try_from(n) {
+            return Self::of_int(n);
+        }
+        let f = n as f64;
+        let order = if f >= 2f64.powi(128) {
+            Ordering::Greater
+        } else {
+            (f as u128).cmp(&n)
+        };
+        Self::around(f, order)
+    }
+
+    /// The interval between `f` and its neighbour on the side of an exact
+    /// value that compares to `f` as `order` says.
+    fn around(f: f64, order: Ordering) -> Self {
+        match order {
+            Ordering::Equal => Self::point(f),
+            Ordering::Greater => Self::bounded(f.next_down(), f),
+            Ordering::Less => Self::bounded(f, f.next_up()),
+        }
+    }
+
     /// Add two intervals: [a,b] + [c,d] = [a+c, b+d].
     pub fn add(&self, other: &Interval) -> Interval {
         Interval {
             lo: match (self.lo, other.lo) {
-                (Some(a), Some(c)) => Some(a + c),
+                (Some(a), Some(c)) => Some(rounded_add(a, c).0),
                 _ => None,
             },
             hi: match (self.hi, other.hi) {
-                (Some(b), Some(d)) => Some(b + d),
+                (Some(b), Some(d)) => Some(rounded_add(b, d).1),
                 _ => None,
             },
         }
@@ -51,27 +103,14 @@ impl Interval {
 
     /// Subtract two intervals: [a,b] - [c,d] = [a-d, b-c].
     pub fn sub(&self, other: &Interval) -> Interval {
-        Interval {
-            lo: match (self.lo, other.hi) {
-                (Some(a), Some(d)) => Some(a - d),
-                _ => None,
-            },
-            hi: match (self.hi, other.lo) {
-                (Some(b), Some(c)) => Some(b - c),
-                _ => None,
-            },
-        }
+        self.add(&other.neg())
     }
 
     /// Multiply two intervals using the four-corners method.
     pub fn mul(&self, other: &Interval) -> Interval {
         match (self.lo, self.hi, other.lo, other.hi) {
             (Some(a), Some(b), Some(c), Some(d)) => {
-                let products = [a * c, a * d, b * c, b * d];
-                Interval {
-                    lo: products.iter().cloned().reduce(f64::min),
-                    hi: products.iter().cloned().reduce(f64::max),
-                }
+                Self::hull([(a, c), (a, d), (b, c), (b, d)].map(|(x, y)| rounded_mul(x, y)))
             }
             _ => Interval::unbounded(),
         }
@@ -85,16 +124,134 @@ impl Interval {
                 if c <= 0.0 && d >= 0.0 {
                     return Interval::unbounded();
                 }
-                let quotients = [a / c, a / d, b / c, b / d];
-                Interval {
-                    lo: quotients.iter().cloned().reduce(f64::min),
-                    hi: quotients.iter().cloned().reduce(f64::max),
-                }
+                Self::hull([(a, c), (a, d), (b, c), (b, d)].map(|(x, y)| rounded_div(x, y)))
             }
             _ => Interval::unbounded(),
         }
     }
 
+    /// The smallest interval containing every `(down, up)` pair.
+    fn hull(bounds: [(f64, f64); 4]) -> Interval {
+        Interval {
+            lo: bounds.iter().map(|b| b.0).reduce(f64::min),
+            hi: bounds.iter().map(|b| b.1).reduce(f64::max),
+        }
+    }
+
+    /// The interval of a numeric constant, if it has one: a point unless
+    /// the constant has no exact `f64`.
+    pub fn of_value(value: &Value) -> Option<Interval> {
+        let iv = match value {
+            Value::Int(v) => match v.to_i128() {
+                Some(n) => Self::of_int(n),
+                None => Self::of_uint(v.to_u128()?),
+            },
+            Value::Float(v) => Self::point(v.value()),
+            Value::Fixed(v) => Self::of_int(v.raw()).scale(-(v.frac_bits() as i32)),
+            _ => return None,
+        };
+        let finite = |b: Option<f64>| b.is_some_and(f64::is_finite);
+        (finite(iv.lo) && finite(iv.hi)).then_some(iv)
+    }
+
+    /// Multiply by `2^exp`, which is exact.
+    fn scale(&self, exp: i32) -> Interval {
+        let factor = 2f64.powi(exp);
+        Interval {
+            lo: self.lo.map(|lo| lo * factor),
+            hi: self.hi.map(|hi| hi * factor),
+        }
+    }
+
+    /// The least and greatest values representable in an integer or
+    /// fixed-point type, each as an interval containing it.
+    fn type_limits(ty: &Type) -> Option<(Interval, Interval)> {
+        match ty.base_type() {
+            Type::Int { width, signedness } => {
+                let (lo, hi) = fixed::int_range(*width, *signedness);
+                Some((Self::of_int(lo), Self::of_int(hi)))
+            }
+            Type::Fixed {
+                total_bits,
+                frac_bits,
+            } => {
+                let (lo, hi) = fixed::raw_range(*total_bits);
+                let exp = -(*frac_bits as i32);
+                Some((Self::of_int(lo).scale(exp), Self::of_int(hi).scale(exp)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The values representable in an integer or fixed-point type.
+    pub fn of_type(ty: &Type) -> Option<Interval> {
+        let (min, max) = Self::type_limits(ty)?;
+        Some(Interval {
+            lo: min.lo,
+            hi: max.hi,
+        })
+    }
+
+    /// Widen outward to the nearest multiples of `step`, which bounds the
+    /// result of any rounding mode.
+    pub fn quantize(&self, step: f64) -> Interval {
+        Interval {
+            lo: self.lo.map(|lo| (lo / step).floor() * step),
+            hi: self.hi.map(|hi| (hi / step).ceil() * step),
+        }
+    }
+
+    /// The range of a result of type `ty` whose exact value lies in `self`:
+    /// rounded to the type's resolution, then wrapped or clamped to its
+    /// range. Trapping and proven results never leave the range, so they
+    /// clamp too.
+    /// Types without a fixed range are returned unchanged.
+    pub fn fit(&self, ty: &Type, overflow: Overflow) -> Interval {
+        let Some((min, max)) = Interval::type_limits(ty) else {
+            return self.clone();
+        };
+        let step = match ty.base_type() {
+            Type::Fixed { frac_bits, .. } => 2f64.powi(-(*frac_bits as i32)),
+            _ => 1.0,
+        };
+        let rounded = self.quantize(step);
+        // Inside only if within the limits wherever they lie exactly
+        let inside = rounded.lo.zip(min.hi).is_some_and(|(lo, min)| lo >= min)
+            && rounded.hi.zip(max.lo).is_some_and(|(hi, max)| hi <= max);
+        let (min, max) = (min.lo.unwrap(), max.hi.unwrap());
+        let range = Interval::bounded(min, max);
+        match overflow {
+            _ if inside => rounded,
+            Overflow::Wrap => range,
+            Overflow::Saturate | Overflow::Trap | Overflow::Proven => Interval::bounded(
+                rounded.lo.map_or(min, |lo| lo.clamp(min, max)),
+                rounded.hi.map_or(max, |hi| hi.clamp(min, max)),
+            ),
+        }
+    }
+
+    /// The larger of two values: [max(a,c), max(b,d)].
+    pub fn max(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: match (self.lo, other.lo) {
+                (Some(a), Some(c)) => Some(a.max(c)),
+                (a, c) => a.or(c),
+            },
+            hi: self.hi.zip(other.hi).map(|(b, d)| b.max(d)),
+        }
+    }
+
+    /// The smaller of two values: [min(a,c), min(b,d)].
+    pub fn min(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: self.lo.zip(other.lo).map(|(a, c)| a.min(c)),
+            hi: match (self.hi, other.hi) {
+                (Some(b), Some(d)) => Some(b.min(d)),
+                (b, d) => b.or(d),
+            },
+        }
+    }
+
     /// Negate an interval: -[a,b] = [-b, -a].
     pub fn neg(&self) -> Interval {
         Interval {
@@ -102,6 +259,18 @@ impl Interval {
             hi: self.lo.map(|l| -l),
         }
     }
+
+    /// Absolute value: |[a,b]| = [0, max(-a, b)] when it straddles zero.
+    pub fn abs(&self) -> Interval {
+        match (self.lo, self.hi) {
+            (Some(lo), _) if lo >= 0.0 => self.clone(),
+            (_, Some(hi)) if hi <= 0.0 => self.neg(),
+            (lo, hi) => Interval {
+                lo: Some(0.0),
+                hi: lo.zip(hi).map(|(l, h)| (-l).max(h)),
+            },
+        }
+    }
 }
 
 /// Result of interval analysis on a single obligation.
@@ -127,6 +296,127 @@ impl IntervalAnalyzer {
             .collect()
     }
 
+    /// Analyze obligations with ranges taken from the graph.
+    ///
+    /// An edge obligation whose predicate has a single free variable (the
+    /// refinement variable) has it bound to the range of the edge's source
+    /// port, so a constant flowing into a refined input can be discharged.
+    /// Port variables (as in no-overflow obligations) are bound to the
+    /// range of the port they name.
+    pub fn analyze_in_graph(
+        graph: &Graph,
+        obligations: &[&TrackedObligation],
+    ) -> Vec<(u64, IntervalResult)> {
+        Self::derive_in_graph(graph, obligations)
+            .into_iter()
+            .map(|(id, result, _)| (id, result))
+            .collect()
+    }
+
+    /// Like `analyze_in_graph`, with the certificate of each proof: the
+    /// port ranges its variables were bound to.
+    pub fn derive_in_graph(
+        graph: &Graph,
+        obligations: &[&TrackedObligation],
+    ) -> Vec<(u64, IntervalResult, Certificate)> {
+        let ranges = Self::port_ranges(graph);
+        obligations
+            .iter()
+            .map(|o| {
+                let source = o
+                    .edge_id
+                    .and_then(|id| graph.get_edge(&id))
+                    .map(|edge| edge.source);
+                let mut vars = Vec::new();
+                let mut bindings = Vec::new();
+                if free_vars(&o.obligation.predicate, &mut vars) {
+                    if let (Some(port), [var]) = (source, vars.as_slice()) {
+                        bindings.push((var.clone(), port));
+                    }
+                    for var in &vars {
+                        if let Some(port) = parse_port_var(var) {
+                            bindings.push((var.clone(), port));
+                        }
+                    }
+                }
+                let bindings: Vec<Binding> = bindings
+                    .into_iter()
+                    .filter_map(|(var, port)| {
+                        let range = ranges.get(&port)?.clone();
+                        Some(Binding { var, port, range })
+                    })
+                    .collect();
+                let result = Self::check_under(&o.obligation.predicate, &bindings);
+                (o.id, result, Certificate::Interval { bindings })
+            })
+            .collect()
+    }
+
+    /// Check a predicate with variables bound as given; later bindings of a
+    /// variable take precedence. This is what replaying an interval
+    /// certificate evaluates.
+    pub fn check_under(predicate: &Predicate, bindings: &[Binding]) -> IntervalResult {
+        let mut env: HashMap<String, Interval> = bindings
+            .iter()
+            .map(|b| (b.var.clone(), b.range.clone()))
+            .collect();
+        Self::check_with_env(predicate, &mut env)
+    }
+
+    /// Ranges of output ports known from literal values, propagated forward
+    /// through arithmetic and conversions.
+    ///
+    /// Integer and fixed-point results are fitted to their output type with
+    /// the node's overflow behavior, so a wrapping node whose exact result
+    /// may leave its type's range gets the whole range.
+    pub fn port_ranges(graph: &Graph) -> HashMap<PortRef, Interval> {
+        let mut ranges: HashMap<PortRef, Interval> = HashMap::new();
+        let order = graph.topological_sort().unwrap_or_default();
+
+        for id in order {
+            let Some(node) = graph.get_node(&id) else {
+                continue;
+            };
+            let input = |port: usize| {
+                graph
+                    .incoming_edges(&id)
+                    .iter()
+                    .filter_map(|e| graph.get_edge(e))
+                    .find(|e| e.target.1 == port)
+                    .and_then(|e| ranges.get(&e.source))
+            };
+            let range = match &node.kind {
+                NodeKind::Literal => node.value.as_ref().and_then(Interval::of_value),
+                NodeKind::Arithmetic(op) => match (input(0), input(1)) {
+                    (Some(a), Some(b)) => match op {
+                        ArithmeticOp::Add => Some(a.add(b)),
+                        ArithmeticOp::Sub => Some(a.sub(b)),
+                        ArithmeticOp::Mul => Some(a.mul(b)),
+                        ArithmeticOp::Div => Some(a.div(b)),
+                        ArithmeticOp::Mod | ArithmeticOp::Pow => None,
+                    },
+                    _ => None,
+                },
+                NodeKind::Conversion => input(0).cloned(),
+                _ => None,
+            };
+            let output_ty = node.type_signature.as_ref().and_then(|s| s.outputs.first());
+            let range = match (range, output_ty, &node.kind) {
+                (Some(range), Some(ty), NodeKind::Arithmetic(_) | NodeKind::Conversion) => {
+                    FixedSemantics::of_node(node)
+                        .ok()
+                        .map(|semantics| range.fit(ty, semantics.overflow))
+                }
+                (range, _, _) => range,
+            };
+            if let Some(range) = range {
+                ranges.insert((id, 0), range);
+            }
+        }
+
+        ranges
+    }
+
     /// Check whether a predicate can be proven/disproven by interval analysis.
     fn check_predicate(predicate: &Predicate) -> IntervalResult {
         let mut env: HashMap<String, Interval> = HashMap::new();
@@ -203,6 +493,22 @@ impl IntervalAnalyzer {
                 }
             }
 
+            // x != k: proven if the intervals are disjoint
+            Predicate::Ne(lhs, rhs) => {
+                let lhs_iv = Self::eval_interval(lhs, env);
+                let rhs_iv = Self::eval_interval(rhs, env);
+                match (lhs_iv.lo, lhs_iv.hi, rhs_iv.lo, rhs_iv.hi) {
+                    (_, Some(hi), Some(lo), _) if hi < lo => IntervalResult::Proven,
+                    (Some(lo), _, _, Some(hi)) if lo > hi => IntervalResult::Proven,
+                    (Some(a), Some(b), Some(c), Some(d)) if a == b && c == d && a == c => {
+                        IntervalResult::Disproven {
+                            counterexample: format!("both sides are {a}"),
+                        }
+                    }
+                    _ => IntervalResult::Inconclusive,
+                }
+            }
+
             // And: both must hold
             Predicate::And(lhs, rhs) => {
                 let l = Self::check_with_env(lhs, env);
@@ -235,14 +541,73 @@ impl IntervalAnalyzer {
                 }
             }
 
+            // Implies: the consequent is one of the antecedent's conjuncts, or
+            // holds under the bounds the antecedent places on its variables
+            Predicate::Implies(lhs, rhs) => {
+                let assumed = conjuncts(lhs)
+                    .into_iter()
+                    .map(Predicate::canonicalize)
+                    .collect::<Vec<_>>();
+                if conjuncts(rhs)
+                    .into_iter()
+                    .all(|c| assumed.contains(&c.canonicalize()))
+                {
+                    return IntervalResult::Proven;
+                }
+                let mut narrowed = env.clone();
+                Self::assume(lhs, &mut narrowed);
+                match Self::check_with_env(rhs, &mut narrowed) {
+                    IntervalResult::Proven => IntervalResult::Proven,
+                    _ => IntervalResult::Inconclusive,
+                }
+            }
+
             _ => IntervalResult::Inconclusive,
         }
     }
 
+    /// Narrow `env` with the bounds that simple comparisons between a
+    /// variable and a constant (`x >= k`, `k < x`, `x == k`, ...) in a
+    /// conjunction place on their variable.
+    ///
+    /// Strict bounds are widened to non-strict ones, which is sound for
+    /// both integer and real variables.
+    fn assume(predicate: &Predicate, env: &mut HashMap<String, Interval>) {
+        let (lhs, rhs, lower, upper) = match predicate {
+            Predicate::And(a, b) => {
+                Self::assume(a, env);
+                Self::assume(b, env);
+                return;
+            }
+            Predicate::Ge(a, b) | Predicate::Gt(a, b) => (a, b, true, false),
+            Predicate::Le(a, b) | Predicate::Lt(a, b) => (a, b, false, true),
+            Predicate::Eq(a, b) => (a, b, true, true),
+            _ => return,
+        };
+        // `x op k`, or `k op x` with the bound flipped
+        let (var, bound, lower, upper) = match (&**lhs, &**rhs) {
+            (Predicate::Var(x), k) => (x, k, lower, upper),
+            (k, Predicate::Var(x)) => (x, k, upper, lower),
+            _ => return,
+        };
+        let k = Self::eval_interval(bound, env);
+        let current = env.entry(var.clone()).or_insert_with(Interval::unbounded);
+        if lower {
+            if let Some(k) = k.lo {
+                current.lo = Some(current.lo.map_or(k, |lo| lo.max(k)));
+            }
+        }
+        if upper {
+            if let Some(k) = k.hi {
+                current.hi = Some(current.hi.map_or(k, |hi| hi.min(k)));
+            }
+        }
+    }
+
     /// Evaluate a predicate expression to an interval.
     fn eval_interval(expr: &Predicate, env: &HashMap<String, Interval>) -> Interval {
         match expr {
-            Predicate::IntLit(n) => Interval::point(*n as f64),
+            Predicate::IntLit(n) => Interval::of_int(*n),
             Predicate::FloatLit(f) => Interval::point(*f),
             Predicate::Var(name) => env.get(name).cloned().unwrap_or(Interval::unbounded()),
             Predicate::Add(a, b) => Self::eval_interval(a, env).add(&Self::eval_interval(b, env)),
@@ -250,16 +615,108 @@ impl IntervalAnalyzer {
             Predicate::Mul(a, b) => Self::eval_interval(a, env).mul(&Self::eval_interval(b, env)),
             Predicate::Div(a, b) => Self::eval_interval(a, env).div(&Self::eval_interval(b, env)),
             Predicate::Neg(a) => Self::eval_interval(a, env).neg(),
+            Predicate::Apply(f, args) if f == "max" || f == "min" => args
+                .iter()
+                .map(|a| Self::eval_interval(a, env))
+                .reduce(|a, b| if f == "max" { a.max(&b) } else { a.min(&b) })
+                .unwrap_or(Interval::unbounded()),
+            Predicate::Apply(f, args) if f == "abs" && args.len() == 1 => {
+                Self::eval_interval(&args[0], env).abs()
+            }
             _ => Interval::unbounded(),
         }
     }
 }
 
+/// `a + b` rounded down and up: the exact sum lies between the two.
+fn rounded_add(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    // The rounding error of the sum, exactly (TwoSum)
+    let b_part = sum - a;
+    let error = (a - (sum - b_part)) + (b - b_part);
+    widen(sum, error)
+}
+
+/// `a * b` rounded down and up.
+fn rounded_mul(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    widen(product, a.mul_add(b, -product))
+}
+
+/// `a / b` rounded down and up.
+fn rounded_div(a: f64, b: f64) -> (f64, f64) {
+    let quotient = a / b;
+    // a - quotient * b, exactly; over b, it has the sign of the error
+    let remainder = -quotient.mul_add(b, -a);
+    let error = match remainder {
+        0.0 => 0.0,
+        r => r.signum() * b.signum(),
+    };
+    widen(quotient, error)
+}
+
+/// The bounds of an exact value that `approx` misses by `error`; a NaN
+/// error (from an overflow) widens both ways.
+fn widen(approx: f64, error: f64) -> (f64, f64) {
+    match error.partial_cmp(&0.0) {
+        Some(Ordering::Equal) => (approx, approx),
+        Some(Ordering::Greater) => (approx, approx.next_up()),
+        Some(Ordering::Less) => (approx.next_down(), approx),
+        None => (approx.next_down(), approx.next_up()),
+    }
+}
+
+/// The operands of a chain of `&&`.
+fn conjuncts(predicate: &Predicate) -> Vec<&Predicate> {
+    match predicate {
+        Predicate::And(a, b) => {
+            let mut out = conjuncts(a);
+            out.extend(conjuncts(b));
+            out
+        }
+        other => vec![other],
+    }
+}
+
+/// Collect the distinct variables of a quantifier-free predicate.
+///
+/// Returns `false` if the predicate binds variables of its own.
+fn free_vars(predicate: &Predicate, vars: &mut Vec<String>) -> bool {
+    match predicate {
+        Predicate::BoolLit(_) | Predicate::IntLit(_) | Predicate::FloatLit(_) => true,
+        Predicate::Var(name) => {
+            if !vars.contains(name) {
+                vars.push(name.clone());
+            }
+            true
+        }
+        Predicate::Neg(a) | Predicate::Not(a) => free_vars(a, vars),
+        Predicate::Add(a, b)
+        | Predicate::Sub(a, b)
+        | Predicate::Mul(a, b)
+        | Predicate::Div(a, b)
+        | Predicate::Mod(a, b)
+        | Predicate::Eq(a, b)
+        | Predicate::Ne(a, b)
+        | Predicate::Lt(a, b)
+        | Predicate::Le(a, b)
+        | Predicate::Gt(a, b)
+        | Predicate::Ge(a, b)
+        | Predicate::And(a, b)
+        | Predicate::Or(a, b)
+        | Predicate::Implies(a, b) => free_vars(a, vars) && free_vars(b, vars),
+        Predicate::Apply(_, args) => args.iter().all(|a| free_vars(a, vars)),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use torc_core::contract::{ObligationKind, ProofObligation, ProofStatus};
-    use torc_core::types::Predicate;
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::{Node, NodeId};
+    use torc_core::types::{Predicate, TypeSignature};
 
     fn make_tracked(id: u64, predicate: Predicate) -> TrackedObligation {
         TrackedObligation {
@@ -274,9 +731,36 @@ mod tests {
             },
             node_id: None,
             edge_id: None,
+            region_id: None,
         }
     }
 
+    /// Interval analysis of a single obligation over the ranges of `graph`.
+    fn analyze_single(graph: &Graph, predicate: Predicate) -> IntervalResult {
+        let tracked = make_tracked(0, predicate);
+        IntervalAnalyzer::analyze_in_graph(graph, &[&tracked])
+            .remove(0)
+            .1
+    }
+
+    /// A literal of type `ty` holding `value`.
+    fn literal(g: &mut Graph, ty: Type, value: Value) -> NodeId {
+        g.add_node(
+            Node::new(NodeKind::Literal)
+                .with_type_signature(TypeSignature::source(ty))
+                .with_value(value),
+        )
+        .unwrap()
+    }
+
+    /// `node` fed by `lhs` and `rhs` on ports 0 and 1.
+    fn binary(g: &mut Graph, node: Node, lhs: NodeId, rhs: NodeId) -> NodeId {
+        let id = g.add_node(node).unwrap();
+        g.add_edge(Edge::new((lhs, 0), (id, 0))).unwrap();
+        g.add_edge(Edge::new((rhs, 0), (id, 1))).unwrap();
+        id
+    }
+
     #[test]
     fn range_propagation_through_arithmetic() {
         // (5 + 1) > 0: both operands are concrete points, so interval is [6, 6] > [0, 0] → proven
@@ -327,4 +811,261 @@ mod tests {
         let results = IntervalAnalyzer::analyze(&[&tracked]);
         assert!(matches!(results[0].1, IntervalResult::Inconclusive));
     }
+
+    #[test]
+    fn literal_value_discharges_edge_refinement() {
+        use torc_core::graph::edge::Edge;
+        use torc_core::graph::node::Node;
+        use torc_core::types::{Type, TypeSignature};
+
+        let mut g = Graph::new();
+        let lit = Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i32()))
+            .with_value(Value::i32(42));
+        let sink = Node::new(NodeKind::Write).with_type_signature(TypeSignature::sink(
+            Type::i32().refined(Predicate::in_range("value", 0, 4095)),
+        ));
+        let (lit_id, sink_id) = (lit.id, sink.id);
+        g.add_node(lit).unwrap();
+        g.add_node(sink).unwrap();
+        let edge_id = g.add_edge(Edge::new((lit_id, 0), (sink_id, 0))).unwrap();
+
+        let (_, obligation) = g.edge_obligations().unwrap().remove(0);
+        let mut tracked = make_tracked(0, obligation.predicate);
+        assert!(matches!(
+            IntervalAnalyzer::analyze(&[&tracked])[0].1,
+            IntervalResult::Inconclusive
+        ));
+
+        tracked.edge_id = Some(edge_id);
+        let results = IntervalAnalyzer::analyze_in_graph(&g, &[&tracked]);
+        assert!(matches!(results[0].1, IntervalResult::Proven));
+    }
+
+    #[test]
+    fn implication_uses_antecedent_bounds() {
+        let x = || Box::new(Predicate::Var("x".into()));
+        // 0 <= x <= 9  =>  x < 10
+        let pred = Predicate::Implies(
+            Box::new(Predicate::in_range("x", 0, 9)),
+            Box::new(Predicate::Lt(x(), Box::new(Predicate::IntLit(10)))),
+        );
+        let results = IntervalAnalyzer::analyze(&[&make_tracked(0, pred)]);
+        assert!(matches!(results[0].1, IntervalResult::Proven));
+
+        // x > 0 && x <= 9  =>  0 < x: strict, but a conjunct of the antecedent
+        let pred = Predicate::Implies(
+            Box::new(Predicate::And(
+                Box::new(Predicate::positive("x")),
+                Box::new(Predicate::Le(x(), Box::new(Predicate::IntLit(9)))),
+            )),
+            Box::new(Predicate::Lt(Box::new(Predicate::IntLit(0)), x())),
+        );
+        let results = IntervalAnalyzer::analyze(&[&make_tracked(0, pred)]);
+        assert!(matches!(results[0].1, IntervalResult::Proven));
+
+        // x >= 0  =>  x < 10 does not follow
+        let pred = Predicate::Implies(
+            Box::new(Predicate::Ge(x(), Box::new(Predicate::IntLit(0)))),
+            Box::new(Predicate::Lt(x(), Box::new(Predicate::IntLit(10)))),
+        );
+        let results = IntervalAnalyzer::analyze(&[&make_tracked(0, pred)]);
+        assert!(matches!(results[0].1, IntervalResult::Inconclusive));
+    }
+
+    #[test]
+    fn fixed_point_ranges_follow_overflow_mode() {
+        use torc_core::graph::edge::Edge;
+        use torc_core::graph::node::Node;
+        use torc_core::types::TypeSignature;
+        use torc_core::value::FixedValue;
+
+        let q15 = Type::Fixed {
+            total_bits: 16,
+            frac_bits: 15,
+        };
+        let mut g = Graph::new();
+        let lit = g
+            .add_node(
+                Node::new(NodeKind::Literal)
+                    .with_type_signature(TypeSignature::source(q15.clone()))
+                    .with_value(Value::Fixed(FixedValue::from_f64(0.75, 16, 15).unwrap())),
+            )
+            .unwrap();
+        let mut add = |overflow: &str| {
+            let mut node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
+                TypeSignature::new(vec![q15.clone(), q15.clone()], vec![q15.clone()]),
+            );
+            node.annotations.insert("overflow".into(), overflow.into());
+            let id = g.add_node(node).unwrap();
+            g.add_edge(Edge::new((lit, 0), (id, 0))).unwrap();
+            g.add_edge(Edge::new((lit, 0), (id, 1))).unwrap();
+            id
+        };
+        let (wrap, saturate) = (add("wrap"), add("saturate"));
+
+        let ranges = IntervalAnalyzer::port_ranges(&g);
+        let max = 32767.0 / 32768.0;
+        assert_eq!(ranges[&(wrap, 0)], Interval::bounded(-1.0, max));
+        assert_eq!(ranges[&(saturate, 0)], Interval::point(max));
+    }
+
+    #[test]
+    fn fit_rounds_to_type_resolution() {
+        let q8 = Type::Fixed {
+            total_bits: 16,
+            frac_bits: 8,
+        };
+        let exact = Interval::bounded(0.1, 0.3);
+        let fitted = exact.fit(&q8, Overflow::Trap);
+        assert_eq!(fitted, Interval::bounded(25.0 / 256.0, 77.0 / 256.0));
+        assert_eq!(
+            Interval::bounded(-3.5, 300.0).fit(&Type::u8(), Overflow::Saturate),
+            Interval::bounded(0.0, 255.0)
+        );
+    }
+
+    #[test]
+    fn no_overflow_obligation_discharged_from_literal_ranges() {
+        use torc_core::contract::generate::overflow_obligation;
+
+        let mut g = Graph::new();
+        let small = literal(&mut g, Type::i32(), Value::i32(1000));
+        let big = literal(&mut g, Type::i32(), Value::i32(i32::MAX));
+        let mut mul = |rhs| {
+            let mut node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Mul)).with_type_signature(
+                TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
+            );
+            node.annotations.insert("overflow".into(), "proven".into());
+            let id = binary(&mut g, node, small, rhs);
+            overflow_obligation(&g, g.get_node(&id).unwrap())
+                .unwrap()
+                .predicate
+        };
+        let (fits, overflows) = (mul(small), mul(big));
+
+        assert!(matches!(analyze_single(&g, fits), IntervalResult::Proven));
+        assert!(matches!(
+            analyze_single(&g, overflows),
+            IntervalResult::Disproven { .. }
+        ));
+    }
+
+    #[test]
+    fn wide_integer_bounds_are_not_rounded_away() {
+        use torc_core::contract::generate::overflow_obligation;
+
+        // i64::MAX + 1 overflows, though both are 2^63 as nearest f64s.
+        let mut g = Graph::new();
+        let max = literal(&mut g, Type::i64(), Value::i64(i64::MAX));
+        let one = literal(&mut g, Type::i64(), Value::i64(1));
+        let mut node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
+            TypeSignature::new(vec![Type::i64(), Type::i64()], vec![Type::i64()]),
+        );
+        node.annotations.insert("overflow".into(), "proven".into());
+        let id = binary(&mut g, node, max, one);
+        let overflows = overflow_obligation(&g, g.get_node(&id).unwrap())
+            .unwrap()
+            .predicate;
+        assert!(!matches!(
+            analyze_single(&g, overflows),
+            IntervalResult::Proven
+        ));
+
+        let bound = Interval::of_int(i64::MAX as i128);
+        assert!(bound.lo.unwrap() < i64::MAX as f64 && bound.hi.unwrap() >= i64::MAX as f64);
+        assert_eq!(Interval::of_int(1 << 53), Interval::point(2f64.powi(53)));
+    }
+
+    #[test]
+    fn divisor_obligation_decided_from_literal_ranges() {
+        use torc_core::contract::generate::domain_obligations;
+
+        let mut g = Graph::new();
+        let seven = literal(&mut g, Type::i32(), Value::i32(7));
+        let zero = literal(&mut g, Type::i32(), Value::i32(0));
+        let mut div = |rhs| {
+            let node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Div)).with_type_signature(
+                TypeSignature::new(vec![Type::i32(), Type::i32()], vec![Type::i32()]),
+            );
+            let id = binary(&mut g, node, seven, rhs);
+            domain_obligations(&g, g.get_node(&id).unwrap())
+                .remove(0)
+                .1
+                .predicate
+        };
+        let (safe, unsafe_) = (div(seven), div(zero));
+
+        assert!(matches!(analyze_single(&g, safe), IntervalResult::Proven));
+        assert!(matches!(
+            analyze_single(&g, unsafe_),
+            IntervalResult::Disproven { .. }
+        ));
+    }
+
+    #[test]
+    fn region_budget_decided_from_composed_bounds() {
+        use torc_core::contract::generate::resource_obligations;
+        use torc_core::contract::Contract;
+        use torc_core::graph::constraints::Constraint;
+        use torc_core::graph::region::{Region, RegionKind};
+
+        let mut g = Graph::new();
+        let mut timed = |ns| {
+            g.add_node(
+                Node::new(NodeKind::Literal)
+                    .with_contract(Contract::pure_default().with_wcet(ns, "m4")),
+            )
+            .unwrap()
+        };
+        let (a, b) = (timed(400), timed(700));
+        let mut budgeted = |kind| {
+            let region =
+                Region::new(kind, vec![a, b]).with_constraints(vec![Constraint::MaxTime(1_000)]);
+            let id = g.add_region(region).unwrap();
+            resource_obligations(&g, g.get_region(&id).unwrap())
+                .remove(0)
+                .predicate
+        };
+        let (fork, chain) = (
+            budgeted(RegionKind::Parallel),
+            budgeted(RegionKind::Sequential),
+        );
+
+        assert!(matches!(analyze_single(&g, fork), IntervalResult::Proven));
+        assert!(matches!(
+            analyze_single(&g, chain),
+            IntervalResult::Disproven { .. }
+        ));
+    }
+
+    #[test]
+    fn approximation_error_propagates_through_mul() {
+        use torc_core::contract::generate::approximation_obligation;
+
+        let approx = |max_error| Type::Approximate {
+            inner: Box::new(Type::f64()),
+            max_error,
+        };
+        let mut g = Graph::new();
+        let a = literal(&mut g, approx(0.01), Value::f64(2.0));
+        let b = literal(&mut g, approx(0.01), Value::f64(-3.0));
+        // |a|·e_b + |b|·e_a + e_a·e_b = 0.0501
+        let mut product = |max_error| {
+            let node = Node::new(NodeKind::Arithmetic(ArithmeticOp::Mul)).with_type_signature(
+                TypeSignature::pure_fn(vec![approx(0.01), approx(0.01)], approx(max_error)),
+            );
+            let id = binary(&mut g, node, a, b);
+            approximation_obligation(&g, g.get_node(&id).unwrap())
+                .unwrap()
+                .predicate
+        };
+        let (loose, tight) = (product(0.1), product(0.01));
+
+        assert!(matches!(analyze_single(&g, loose), IntervalResult::Proven));
+        assert!(matches!(
+            analyze_single(&g, tight),
+            IntervalResult::Disproven { .. }
+        ));
+    }
 }
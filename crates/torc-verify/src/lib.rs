@@ -1,16 +1,22 @@
 //! Verification framework for the Torc language.
 //!
 //! Integrates structural analysis, abstract interpretation (interval domain),
-//! SMT solvers (Z3, feature-gated), proof caching, and reporting to discharge
-//! proof obligations generated by contracts and types.
+//! SMT solvers (Z3, feature-gated, or any SMT-LIB2 solver binary), proof
+//! caching, and reporting to discharge proof obligations generated by
+//! contracts and types.
 
 pub mod cache;
+pub mod checker;
+pub mod counterexample;
 pub mod engine;
+mod exact;
+pub mod incremental;
 pub mod interval;
 pub mod profile;
 pub mod registry;
 pub mod report;
 pub mod smt;
+pub mod smtlib;
 pub mod structural;
 pub mod witness;
 
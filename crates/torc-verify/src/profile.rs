@@ -2,8 +2,10 @@
 
 use std::time::Duration;
 
-/// The level of verification rigor.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+/// The level of verification rigor, from least to most strict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ProfileLevel {
     /// Fast iteration: structural + interval only, short timeouts.
     Development,
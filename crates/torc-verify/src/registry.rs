@@ -5,7 +5,10 @@ use std::collections::HashMap;
 use torc_core::contract::{ObligationKind, ProofObligation, ProofStatus, ProofWitness, Waiver};
 use torc_core::graph::edge::EdgeId;
 use torc_core::graph::node::NodeId;
-use torc_core::graph::Graph;
+use torc_core::graph::region::RegionId;
+use torc_core::graph::{Graph, ObligationSite};
+
+use crate::cache::obligation_hash;
 
 /// A proof obligation with tracking metadata.
 #[derive(Debug, Clone)]
@@ -18,6 +21,8 @@ pub struct TrackedObligation {
     pub node_id: Option<NodeId>,
     /// Source edge (if applicable).
     pub edge_id: Option<EdgeId>,
+    /// Source region (if applicable).
+    pub region_id: Option<RegionId>,
 }
 
 /// Statistics about obligation statuses.
@@ -36,6 +41,8 @@ pub struct ObligationRegistry {
     obligations: Vec<TrackedObligation>,
     /// Index mapping obligation ID → position in the `obligations` Vec for O(1) lookup.
     id_index: HashMap<u64, usize>,
+    /// Canonical obligation hash and source → obligation ID, for deduplication.
+    seen: HashMap<(String, ObligationSite), u64>,
     next_id: u64,
 }
 
@@ -45,6 +52,7 @@ impl ObligationRegistry {
         Self {
             obligations: Vec::new(),
             id_index: HashMap::new(),
+            seen: HashMap::new(),
             next_id: 0,
         }
     }
@@ -55,40 +63,61 @@ impl ObligationRegistry {
 
         // Collect type/edge obligations (may include refinement subtyping obligations)
         match graph.validate_types() {
-            Ok(obligations) => {
-                for ob in obligations {
-                    registry.add(ob, None, None);
+            Ok(_) => {
+                // Re-collect edge obligations so each keeps its source edge
+                for (edge_id, ob) in graph.edge_obligations().unwrap_or_default() {
+                    registry.add(ob, ObligationSite::Edge(edge_id));
                 }
+                registry.add_contract_obligations(graph);
             }
             Err(_errors) => {
                 // Structural errors are handled separately by StructuralAnalyzer;
                 // validate_types also calls validate_contracts internally, so
                 // if it fails we still collect contract obligations directly.
-                let contract_obs = graph.validate_contracts();
-                for ob in contract_obs {
-                    registry.add(ob, None, None);
-                }
+                registry.add_contract_obligations(graph);
             }
         }
 
         registry
     }
 
+    fn add_contract_obligations(&mut self, graph: &Graph) {
+        for (site, ob) in graph.contract_obligations() {
+            self.add(ob, site);
+        }
+    }
+
     /// Add an obligation with optional source metadata.
-    fn add(
-        &mut self,
-        obligation: ProofObligation,
-        node_id: Option<NodeId>,
-        edge_id: Option<EdgeId>,
-    ) {
+    ///
+    /// The predicate is simplified first, and an obligation equivalent (up to
+    /// canonical form) to one already registered from the same source is
+    /// dropped.
+    fn add(&mut self, mut obligation: ProofObligation, site: ObligationSite) {
+        obligation.predicate = obligation.predicate.simplify();
+        let key = (obligation_hash(&obligation), site);
+        if self.seen.contains_key(&key) {
+            return;
+        }
+
         let id = self.next_id;
+        self.seen.insert(key, id);
         self.next_id += 1;
         let pos = self.obligations.len();
         self.obligations.push(TrackedObligation {
             id,
             obligation,
-            node_id,
-            edge_id,
+            node_id: match site {
+                ObligationSite::Node(id) => Some(id),
+                _ => None,
+            },
+            edge_id: match site {
+                ObligationSite::Edge(id) => Some(id),
+                _ => None,
+            },
+            region_id: match site {
+                ObligationSite::Region(id) => Some(id),
+                _ => None,
+            },
         });
         self.id_index.insert(id, pos);
     }
@@ -258,4 +287,30 @@ mod tests {
         assert_eq!(stats.verified, 1);
         assert_eq!(stats.pending, stats.total - 1);
     }
+
+    #[test]
+    fn equivalent_obligations_deduplicated() {
+        let mut g = Graph::new();
+        let x_gt_0 = Predicate::positive("x");
+        let zero_lt_x = Predicate::Lt(
+            Box::new(Predicate::IntLit(0)),
+            Box::new(Predicate::Var("x".into())),
+        );
+        let mut n = Node::new(NodeKind::Read);
+        n.contract = Some(Contract::with_conditions(
+            vec![],
+            vec![
+                Predicate::BoolLit(true).conjoin(x_gt_0),
+                Predicate::And(Box::new(zero_lt_x), Box::new(Predicate::BoolLit(true))),
+            ],
+        ));
+        g.add_node(n).unwrap();
+
+        let registry = ObligationRegistry::collect_from_graph(&g);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(
+            registry.all()[0].obligation.predicate,
+            Predicate::positive("x")
+        );
+    }
 }
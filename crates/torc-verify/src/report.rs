@@ -6,6 +6,7 @@ use std::fmt;
 use torc_core::contract::ProofStatus;
 
 use crate::cache::CacheStats;
+use crate::counterexample::Counterexample;
 use crate::profile::ProfileLevel;
 use crate::registry::ObligationRegistry;
 use crate::structural::StructuralDiagnostic;
@@ -48,6 +49,8 @@ pub struct ReportSummary {
     pub waived: usize,
     pub failed: usize,
     pub cache_hits: usize,
+    /// Obligations whose status was carried forward from a previous run.
+    pub reused: usize,
 }
 
 /// The complete verification report.
@@ -56,6 +59,10 @@ pub struct VerificationReport {
     pub summary: ReportSummary,
     pub diagnostics: Vec<Diagnostic>,
     pub profile: ProfileLevel,
+    /// IDs of the obligations carried forward from a previous run.
+    pub reused: Vec<u64>,
+    /// Failing assignments found for disproven obligations.
+    pub counterexamples: Vec<Counterexample>,
 }
 
 impl VerificationReport {
@@ -75,6 +82,7 @@ impl VerificationReport {
             waived: reg_stats.waived,
             failed: reg_stats.assumed,
             cache_hits: cache_stats.hits,
+            reused: 0,
         };
 
         let mut diagnostics = Vec::new();
@@ -150,6 +158,8 @@ impl VerificationReport {
             summary,
             diagnostics,
             profile,
+            reused: Vec::new(),
+            counterexamples: Vec::new(),
         }
     }
 
@@ -180,6 +190,7 @@ fn suggest_for_kind(kind: &torc_core::contract::ObligationKind) -> Vec<String> {
         ],
         ObligationKind::ResourceBound => vec![
             "Optimize implementation to meet bound".into(),
+            "Bound every node in the region, starting with the largest contributor".into(),
             "Relax resource bound if safe".into(),
             "Waive obligation (requires justification)".into(),
         ],
@@ -189,6 +200,18 @@ fn suggest_for_kind(kind: &torc_core::contract::ObligationKind) -> Vec<String> {
         ],
         ObligationKind::Termination => vec![
             "Provide a ranking function or variant".into(),
+            "Declare a Diverge effect with a diverge.justification annotation".into(),
+            "Waive obligation (requires justification)".into(),
+        ],
+        ObligationKind::NoOverflow => vec![
+            "Bound the operands with preconditions on the producers".into(),
+            "Use a wrapping, saturating or trapping overflow policy".into(),
+            "Waive obligation (requires justification)".into(),
+        ],
+        ObligationKind::ApproximationError => vec![
+            "Tighten the error bounds of the approximate inputs".into(),
+            "Bound the operands with preconditions on the producers".into(),
+            "Widen the output's max_error if the application allows it".into(),
             "Waive obligation (requires justification)".into(),
         ],
     }
@@ -207,6 +230,15 @@ impl fmt::Display for VerificationReport {
             self.summary.failed,
             self.summary.cache_hits,
         )?;
+        if !self.reused.is_empty() {
+            let ids: Vec<_> = self.reused.iter().map(|id| format!("#{id}")).collect();
+            writeln!(
+                f,
+                "Reused from previous run: {} ({})",
+                self.summary.reused,
+                ids.join(", ")
+            )?;
+        }
 
         if self.diagnostics.is_empty() {
             writeln!(f, "No diagnostics.")?;
@@ -226,6 +258,12 @@ impl fmt::Display for VerificationReport {
                 }
             }
         }
+        if !self.counterexamples.is_empty() {
+            writeln!(f, "--- Counterexamples ---")?;
+            for ce in &self.counterexamples {
+                writeln!(f, "{ce}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -306,9 +344,12 @@ mod tests {
                 waived: 0,
                 failed: 0,
                 cache_hits: 10,
+                reused: 0,
             },
             diagnostics: vec![],
             profile: ProfileLevel::Development,
+            reused: vec![],
+            counterexamples: vec![],
         };
         assert_eq!(
             report.format_spec_summary(),
@@ -1,13 +1,16 @@
-//! SMT solver backend using Z3 (feature-gated).
+//! SMT solver backends.
 //!
-//! All code in this module requires the `z3` feature flag.
+//! `SolverBackend` is implemented by `SmtSolver`, which links Z3 and requires
+//! the `z3` feature flag, and by `smtlib::ProcessSolver`, which drives any
+//! SMT-LIB2 solver binary. `cross_check` runs one obligation through several
+//! backends and flags disagreement; the engine uses it for the solvers added
+//! with `VerificationEngine::with_cross_checker`.
 
 use std::collections::HashMap;
 
 #[cfg(feature = "z3")]
 use std::time::Duration;
 
-#[cfg(feature = "z3")]
 use torc_core::contract::ProofObligation;
 #[cfg(feature = "z3")]
 use torc_core::types::Predicate;
@@ -27,6 +30,61 @@ pub enum SmtResult {
     Timeout,
 }
 
+/// A solver that can discharge proof obligations.
+pub trait SolverBackend {
+    /// The name recorded in proof witnesses, e.g. `z3` or `smtlib:cvc5`.
+    fn name(&self) -> &str;
+
+    /// Check an obligation by asserting its negation: UNSAT proves it.
+    fn check_obligation(&self, obligation: &ProofObligation) -> SmtResult;
+
+    /// Like `check_obligation`, also returning the solver's proof of
+    /// unsatisfiability when it proves the obligation and can produce one.
+    ///
+    /// The proof must be the solver's answer to `(get-proof)` in the
+    /// `smtlib::proof_script` of the obligation; a backend that does not
+    /// solve that script returns `None`.
+    fn check_with_proof(&self, obligation: &ProofObligation) -> (SmtResult, Option<String>) {
+        (self.check_obligation(obligation), None)
+    }
+}
+
+/// The results of several solvers on one obligation.
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    /// Each solver's name and result, in the order they were run.
+    pub results: Vec<(String, SmtResult)>,
+}
+
+impl CrossCheck {
+    /// Whether one solver proved the obligation and another refuted it.
+    pub fn conflicting(&self) -> bool {
+        let any = |f: fn(&SmtResult) -> bool| self.results.iter().any(|(_, r)| f(r));
+        any(|r| matches!(r, SmtResult::Proven)) && any(|r| matches!(r, SmtResult::Disproven { .. }))
+    }
+
+    /// The agreed result: the first conclusive one, unless solvers conflict.
+    pub fn verdict(&self) -> Option<&SmtResult> {
+        if self.conflicting() {
+            return None;
+        }
+        self.results
+            .iter()
+            .map(|(_, r)| r)
+            .find(|r| matches!(r, SmtResult::Proven | SmtResult::Disproven { .. }))
+    }
+}
+
+/// Check `obligation` with every backend.
+pub fn cross_check(backends: &[&dyn SolverBackend], obligation: &ProofObligation) -> CrossCheck {
+    CrossCheck {
+        results: backends
+            .iter()
+            .map(|b| (b.name().to_string(), b.check_obligation(obligation)))
+            .collect(),
+    }
+}
+
 /// SMT solver wrapper around Z3.
 #[cfg(feature = "z3")]
 pub struct SmtSolver {
@@ -39,6 +97,17 @@ impl SmtSolver {
     pub fn new(timeout: Duration) -> Self {
         Self { timeout }
     }
+}
+
+/// Z3 proves obligations through its API, not from an SMT-LIB2 script, and
+/// its native proof terms are in no format a proof checker accepts, so
+/// `check_with_proof` keeps the default: no proof. Certification needs a
+/// subprocess solver (`smtlib::ProcessSolver`) for checkable proofs.
+#[cfg(feature = "z3")]
+impl SolverBackend for SmtSolver {
+    fn name(&self) -> &str {
+        "z3"
+    }
 
     /// Check a proof obligation using Z3.
     ///
@@ -46,7 +115,7 @@ impl SmtSolver {
     /// - UNSAT → predicate always holds → Proven
     /// - SAT → predicate can fail → Disproven with counterexample
     /// - UNKNOWN/timeout → Unknown/Timeout
-    pub fn check_obligation(&self, obligation: &ProofObligation) -> SmtResult {
+    fn check_obligation(&self, obligation: &ProofObligation) -> SmtResult {
         let cfg = z3::Config::new();
         let ctx = z3::Context::new(&cfg);
         let solver = z3::Solver::new(&ctx);
@@ -59,7 +128,8 @@ impl SmtSolver {
 
         // Translate predicate to Z3 AST, then negate and check.
         // Bind the result so that Z3 temporaries are dropped before `ctx`.
-        let result = match predicate_to_z3(&ctx, &obligation.predicate) {
+        let predicate = obligation.predicate.simplify();
+        let result = match predicate_to_z3(&ctx, &predicate) {
             Some(ast) => {
                 let negated = ast.not();
                 solver.assert(&negated);
@@ -70,7 +140,7 @@ impl SmtSolver {
                         let model = solver
                             .get_model()
                             .expect("Z3 SAT result should provide model");
-                        let counterexample = extract_model(&ctx, &model, &obligation.predicate);
+                        let counterexample = extract_model(&ctx, &model, &predicate);
                         SmtResult::Disproven { counterexample }
                     }
                     z3::SatResult::Unknown => {
@@ -286,10 +356,9 @@ fn collect_vars(pred: &Predicate, vars: &mut Vec<String>) {
     }
 }
 
-#[cfg(all(test, feature = "z3"))]
+#[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
     use torc_core::contract::{ObligationKind, ProofObligation, ProofStatus};
     use torc_core::types::Predicate;
 
@@ -304,6 +373,42 @@ mod tests {
         }
     }
 
+    /// A backend that answers every obligation the same way.
+    struct Fixed(&'static str, SmtResult);
+
+    impl SolverBackend for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn check_obligation(&self, _: &ProofObligation) -> SmtResult {
+            self.1.clone()
+        }
+    }
+
+    #[test]
+    fn cross_check_flags_disagreement() {
+        let obligation = make_obligation(Predicate::BoolLit(true));
+        let proven = Fixed("a", SmtResult::Proven);
+        let refuted = Fixed(
+            "b",
+            SmtResult::Disproven {
+                counterexample: HashMap::new(),
+            },
+        );
+        let unsure = Fixed("c", SmtResult::Timeout);
+
+        let agreed = cross_check(&[&unsure, &proven], &obligation);
+        assert!(!agreed.conflicting());
+        assert!(matches!(agreed.verdict(), Some(SmtResult::Proven)));
+        assert_eq!(agreed.results[0].0, "c");
+
+        let split = cross_check(&[&proven, &unsure, &refuted], &obligation);
+        assert!(split.conflicting());
+        assert!(split.verdict().is_none());
+    }
+
+    #[cfg(feature = "z3")]
     #[test]
     fn simple_arithmetic_proven() {
         // x + 1 > x is always true for integers
@@ -319,6 +424,24 @@ mod tests {
         assert!(matches!(result, SmtResult::Proven));
     }
 
+    #[cfg(feature = "z3")]
+    #[test]
+    fn no_proof_from_the_api() {
+        // x + 1 > x
+        let pred = Predicate::Gt(
+            Box::new(Predicate::Add(
+                Box::new(Predicate::Var("x".into())),
+                Box::new(Predicate::IntLit(1)),
+            )),
+            Box::new(Predicate::Var("x".into())),
+        );
+        let solver = SmtSolver::new(Duration::from_secs(10));
+        let (result, proof) = solver.check_with_proof(&make_obligation(pred));
+        assert!(matches!(result, SmtResult::Proven));
+        assert!(proof.is_none());
+    }
+
+    #[cfg(feature = "z3")]
     #[test]
     fn implication_proven() {
         // x > 0 => x >= 0
@@ -337,6 +460,7 @@ mod tests {
         assert!(matches!(result, SmtResult::Proven));
     }
 
+    #[cfg(feature = "z3")]
     #[test]
     fn counterexample_extracted() {
         // x > 10 with no constraints → SAT (x can be <= 10)
@@ -354,6 +478,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "z3")]
     #[test]
     fn timeout_handling() {
         // Use a very short timeout (1ms) with a complex formula
@@ -371,6 +496,7 @@ mod tests {
         ));
     }
 
+    #[cfg(feature = "z3")]
     #[test]
     fn quantifier_support() {
         // forall x: x + 0 = x
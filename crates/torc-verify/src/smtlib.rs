@@ -0,0 +1,708 @@
+//! SMT-LIB2 export and a solver backend driving external solver binaries.
+//!
+//! Predicates are printed over the theory of integers, matching the Z3
+//! backend and the predicate normalizer: variables are `Int` constants and
+//! `abs` is the only interpreted function. An obligation becomes a script
+//! asserting its negation, so `unsat` means the obligation holds:
+//!
+//! ```text
+//! (set-option :produce-models true)
+//! (set-logic QF_LIA)
+//! (declare-const x Int)
+//! (assert (not (> (+ x 1) x)))
+//! (check-sat)
+//! (get-value (x))
+//! (exit)
+//! ```
+//!
+//! `ProcessSolver` pipes such a script into any solver that reads SMT-LIB2
+//! on stdin (z3, cvc5, yices) and reads the verdict, and a model on `sat`,
+//! from its stdout. The logic is chosen from the predicate (quantified or
+//! not, linear or not) so that solvers without `ALL` accept it.
+//!
+//! For certification, `proof_script` also asks for the solver's proof of
+//! unsatisfiability (`get-proof`), which `parse_proof` extracts so it can be
+//! kept in the witness and checked independently.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use torc_core::contract::ProofObligation;
+use torc_core::types::Predicate;
+
+use crate::smt::{SmtResult, SolverBackend};
+
+/// Print `predicate` as an SMT-LIB2 formula, or `None` if it uses
+/// constructs outside integer arithmetic.
+pub fn formula(predicate: &Predicate) -> Option<String> {
+    let binary =
+        |op: &str, a: &Predicate, b: &Predicate| Some(format!("({op} {} {})", term(a)?, term(b)?));
+    let logical = |op: &str, a: &Predicate, b: &Predicate| {
+        Some(format!("({op} {} {})", formula(a)?, formula(b)?))
+    };
+    match predicate {
+        Predicate::BoolLit(b) => Some(b.to_string()),
+        Predicate::Eq(a, b) => binary("=", a, b),
+        Predicate::Ne(a, b) => binary("distinct", a, b),
+        Predicate::Lt(a, b) => binary("<", a, b),
+        Predicate::Le(a, b) => binary("<=", a, b),
+        Predicate::Gt(a, b) => binary(">", a, b),
+        Predicate::Ge(a, b) => binary(">=", a, b),
+        Predicate::And(a, b) => logical("and", a, b),
+        Predicate::Or(a, b) => logical("or", a, b),
+        Predicate::Implies(a, b) => logical("=>", a, b),
+        Predicate::Not(a) => Some(format!("(not {})", formula(a)?)),
+        Predicate::ForAll { var, range, body } => Some(format!(
+            "(forall (({} Int)) (=> {} {}))",
+            symbol(var)?,
+            formula(range)?,
+            formula(body)?
+        )),
+        Predicate::Exists { var, range, body } => Some(format!(
+            "(exists (({} Int)) (and {} {}))",
+            symbol(var)?,
+            formula(range)?,
+            formula(body)?
+        )),
+        _ => None,
+    }
+}
+
+/// Print an integer-valued expression.
+fn term(expr: &Predicate) -> Option<String> {
+    let binary =
+        |op: &str, a: &Predicate, b: &Predicate| Some(format!("({op} {} {})", term(a)?, term(b)?));
+    match expr {
+        Predicate::IntLit(n) => Some(int(*n)),
+        Predicate::FloatLit(x) if x.fract() == 0.0 && x.abs() < 2f64.powi(127) => {
+            Some(int(*x as i128))
+        }
+        Predicate::Var(name) => symbol(name),
+        Predicate::Add(a, b) => binary("+", a, b),
+        Predicate::Sub(a, b) => binary("-", a, b),
+        Predicate::Mul(a, b) => binary("*", a, b),
+        Predicate::Div(a, b) => binary("div", a, b),
+        Predicate::Mod(a, b) => binary("mod", a, b),
+        Predicate::Neg(a) => Some(format!("(- {})", term(a)?)),
+        Predicate::Apply(f, args) if f == "abs" && args.len() == 1 => {
+            Some(format!("(abs {})", term(&args[0])?))
+        }
+        _ => None,
+    }
+}
+
+fn int(n: i128) -> String {
+    if n < 0 {
+        format!("(- {})", n.unsigned_abs())
+    } else {
+        n.to_string()
+    }
+}
+
+/// A variable name as an SMT-LIB2 symbol, quoted unless it is a simple
+/// symbol. Port variables (`<uuid>.<port>`) always need quoting.
+pub fn symbol(name: &str) -> Option<String> {
+    const EXTRA: &str = "~!@$%^&*_-+=<>.?/";
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || EXTRA.contains(c));
+    if simple {
+        Some(name.to_string())
+    } else if name.contains(['|', '\\']) {
+        None
+    } else {
+        Some(format!("|{name}|"))
+    }
+}
+
+/// The SMT-LIB2 logic `predicate` falls in.
+fn logic(predicate: &Predicate) -> &'static str {
+    fn scan(p: &Predicate, quantified: &mut bool, nonlinear: &mut bool) {
+        let constant = |p: &Predicate| matches!(p, Predicate::IntLit(_) | Predicate::FloatLit(_));
+        match p {
+            Predicate::ForAll { range, body, .. } | Predicate::Exists { range, body, .. } => {
+                *quantified = true;
+                scan(range, quantified, nonlinear);
+                scan(body, quantified, nonlinear);
+                return;
+            }
+            Predicate::Mul(a, b) if !constant(a) && !constant(b) => *nonlinear = true,
+            Predicate::Div(_, b) | Predicate::Mod(_, b) if !constant(b) => *nonlinear = true,
+            _ => {}
+        }
+        match p {
+            Predicate::Add(a, b)
+            | Predicate::Sub(a, b)
+            | Predicate::Mul(a, b)
+            | Predicate::Div(a, b)
+            | Predicate::Mod(a, b)
+            | Predicate::Eq(a, b)
+            | Predicate::Ne(a, b)
+            | Predicate::Lt(a, b)
+            | Predicate::Le(a, b)
+            | Predicate::Gt(a, b)
+            | Predicate::Ge(a, b)
+            | Predicate::And(a, b)
+            | Predicate::Or(a, b)
+            | Predicate::Implies(a, b) => {
+                scan(a, quantified, nonlinear);
+                scan(b, quantified, nonlinear);
+            }
+            Predicate::Not(a) | Predicate::Neg(a) => scan(a, quantified, nonlinear),
+            Predicate::Apply(_, args) => {
+                for a in args {
+                    scan(a, quantified, nonlinear);
+                }
+            }
+            _ => {}
+        }
+    }
+    let (mut quantified, mut nonlinear) = (false, false);
+    scan(predicate, &mut quantified, &mut nonlinear);
+    match (quantified, nonlinear) {
+        (false, false) => "QF_LIA",
+        (false, true) => "QF_NIA",
+        (true, false) => "LIA",
+        (true, true) => "NIA",
+    }
+}
+
+/// The script checking `predicate`: its negation is asserted, and on `sat`
+/// the values of its free variables are requested.
+pub fn script(predicate: &Predicate) -> Option<String> {
+    let negated = format!("(not {})", formula(predicate)?);
+    let vars = predicate
+        .free_vars()
+        .iter()
+        .map(|v| symbol(v))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut out = String::from("(set-option :produce-models true)\n");
+    out.push_str(&format!("(set-logic {})\n", logic(predicate)));
+    for v in &vars {
+        out.push_str(&format!("(declare-const {v} Int)\n"));
+    }
+    out.push_str(&format!("(assert {negated})\n(check-sat)\n"));
+    if !vars.is_empty() {
+        out.push_str(&format!("(get-value ({}))\n", vars.join(" ")));
+    }
+    out.push_str("(exit)\n");
+    Some(out)
+}
+
+/// `script`, also asking for a proof when the negation is unsatisfiable.
+pub fn proof_script(predicate: &Predicate) -> Option<String> {
+    let script = script(predicate)?;
+    Some(format!(
+        "(set-option :produce-proofs true)\n{}",
+        script.replacen("(check-sat)\n", "(check-sat)\n(get-proof)\n", 1)
+    ))
+}
+
+/// The verdict line of a solver's output and the responses after it.
+fn split_output(output: &str) -> (&str, Vec<&str>) {
+    let output = output.trim_start();
+    let (verdict, rest) = output.split_once('\n').unwrap_or((output, ""));
+    let responses = responses(rest)
+        .into_iter()
+        .filter(|r| !r.starts_with("(error"))
+        .collect();
+    (verdict.trim(), responses)
+}
+
+/// Interpret a solver's output for a `script` or `proof_script`.
+pub fn parse_response(output: &str) -> SmtResult {
+    let (verdict, responses) = split_output(output);
+    match verdict {
+        "unsat" => SmtResult::Proven,
+        "sat" => SmtResult::Disproven {
+            counterexample: responses
+                .iter()
+                .find_map(|r| parse_values(r))
+                .unwrap_or_default(),
+        },
+        "timeout" => SmtResult::Timeout,
+        "unknown" => SmtResult::Unknown {
+            reason: "solver returned unknown".into(),
+        },
+        "" => SmtResult::Unknown {
+            reason: "solver produced no output".into(),
+        },
+        other => SmtResult::Unknown {
+            reason: other.to_string(),
+        },
+    }
+}
+
+/// The proof in a solver's output for a `proof_script`, if it reported
+/// `unsat` and produced one.
+pub fn parse_proof(output: &str) -> Option<String> {
+    match split_output(output) {
+        ("unsat", responses) => responses.first().map(|r| r.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether the solver answered `sat` or `unsat` and reported an error
+/// after it, as asking for a model after `unsat` or for a proof after `sat`
+/// does. Solvers exit unsuccessfully after such an error, but the answer
+/// stands.
+fn failed_request(output: &str) -> bool {
+    let output = output.trim_start();
+    let (verdict, rest) = output.split_once('\n').unwrap_or((output, ""));
+    matches!(verdict.trim(), "sat" | "unsat")
+        && responses(rest).iter().any(|r| r.starts_with("(error"))
+}
+
+/// The top-level s-expressions of `s`, as text. Parentheses inside quoted
+/// symbols, strings and comments do not count.
+fn responses(s: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '|' | '"' => {
+                chars.by_ref().find(|&(_, q)| q == c);
+            }
+            ';' => {
+                chars.by_ref().find(|&(_, q)| q == '\n');
+            }
+            '(' => {
+                if depth == 0 {
+                    start = i;
+                }
+                depth += 1;
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    out.push(&s[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// An s-expression, as far as `get-value` responses need one.
+#[derive(Debug, Clone, PartialEq)]
+enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+fn parse_sexp(tokens: &mut std::iter::Peekable<std::vec::IntoIter<String>>) -> Option<Sexp> {
+    match tokens.next()?.as_str() {
+        "(" => {
+            let mut items = Vec::new();
+            while tokens.peek()? != ")" {
+                items.push(parse_sexp(tokens)?);
+            }
+            tokens.next();
+            Some(Sexp::List(items))
+        }
+        ")" => None,
+        atom => Some(Sexp::Atom(atom.to_string())),
+    }
+}
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' => tokens.push(c.to_string()),
+            '|' => {
+                let quoted: String = chars.by_ref().take_while(|&c| c != '|').collect();
+                tokens.push(quoted);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '(' || next == ')' {
+                        break;
+                    }
+                    atom.push(next);
+                    chars.next();
+                }
+                tokens.push(atom);
+            }
+        }
+    }
+    tokens
+}
+
+/// Parse `((x 5) (y (- 3)))` into `{x: "5", y: "-3"}`.
+fn parse_values(s: &str) -> Option<HashMap<String, String>> {
+    let mut tokens = tokenize(s).into_iter().peekable();
+    let Sexp::List(pairs) = parse_sexp(&mut tokens)? else {
+        return None;
+    };
+    pairs
+        .into_iter()
+        .map(|pair| match pair {
+            Sexp::List(items) => match items.as_slice() {
+                [Sexp::Atom(name), value] => Some((name.clone(), render(value)?)),
+                _ => None,
+            },
+            Sexp::Atom(_) => None,
+        })
+        .collect()
+}
+
+/// A model value: an integer, possibly negated.
+fn render(value: &Sexp) -> Option<String> {
+    match value {
+        Sexp::Atom(a) => Some(a.clone()),
+        Sexp::List(items) => match items.as_slice() {
+            [Sexp::Atom(minus), inner] if minus == "-" => Some(format!("-{}", render(inner)?)),
+            _ => None,
+        },
+    }
+}
+
+/// A solver run as a child process, fed SMT-LIB2 on stdin.
+#[derive(Debug, Clone)]
+pub struct ProcessSolver {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ProcessSolver {
+    /// Run `program` with `args`; it must read a script from stdin.
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>) -> Self {
+        let program = program.into();
+        let tool = program
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self {
+            name: format!("smtlib:{tool}"),
+            program,
+            args,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// A known solver by name (`z3`, `cvc5`, `yices`) with the flags it
+    /// needs to read SMT-LIB2 from stdin, or else `tool` run as is.
+    pub fn named(tool: &str) -> Self {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect();
+        match tool {
+            "z3" => Self::new("z3", args(&["-in", "-smt2"])),
+            "cvc5" => Self::new("cvc5", args(&["--lang=smt2"])),
+            "yices" => Self::new("yices-smt2", Vec::new()),
+            other => Self::new(other, Vec::new()),
+        }
+    }
+
+    /// Kill the solver after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run `script` through the solver and interpret its output with
+    /// `parse`. A solver that crashes, or exits unsuccessfully other than
+    /// after a `failed_request`, gives `Unknown`.
+    fn run<T>(&self, script: &str, parse: impl FnOnce(&str) -> T) -> Result<T, SmtResult> {
+        match run_process(&self.program, &self.args, script, self.timeout) {
+            Ok((status, output))
+                if status.success() || (status.code().is_some() && failed_request(&output)) =>
+            {
+                Ok(parse(&output))
+            }
+            Ok((status, output)) => Err(SmtResult::Unknown {
+                reason: format!(
+                    "{} failed ({status}): {}",
+                    self.program.display(),
+                    output.trim()
+                ),
+            }),
+            Err(RunError::Timeout) => Err(SmtResult::Timeout),
+            Err(RunError::Failed(reason)) => Err(SmtResult::Unknown { reason }),
+        }
+    }
+}
+
+/// Why `run_process` did not produce output.
+#[derive(Debug)]
+pub(crate) enum RunError {
+    Failed(String),
+    Timeout,
+}
+
+/// Run `program` with `input` on stdin, killing it after `timeout`.
+/// Returns its exit status and stdout.
+///
+/// Input is written and output read on threads of their own, so a process
+/// that answers before reading all its input cannot block on a full pipe,
+/// and the timeout covers both.
+pub(crate) fn run_process(
+    program: &Path,
+    args: &[String],
+    input: &str,
+    timeout: Duration,
+) -> Result<(ExitStatus, String), RunError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| RunError::Failed(format!("could not run {}: {e}", program.display())))?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        let _ = tx.send(out);
+    });
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_owned();
+    thread::spawn(move || {
+        // A process that exits early closes the pipe; its output says why.
+        let _ = stdin.write_all(input.as_bytes());
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(5));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(RunError::Timeout);
+            }
+            Err(e) => {
+                return Err(RunError::Failed(format!(
+                    "waiting for {}: {e}",
+                    program.display()
+                )))
+            }
+        }
+    };
+    let output = rx
+        .recv_timeout(Duration::from_secs(1))
+        .map_err(|_| RunError::Failed("output was not closed".into()))?;
+    Ok((status, output))
+}
+
+impl SolverBackend for ProcessSolver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check_obligation(&self, obligation: &ProofObligation) -> SmtResult {
+        let Some(script) = script(&obligation.predicate.simplify()) else {
+            return SmtResult::Unknown {
+                reason: "unsupported predicate structure".into(),
+            };
+        };
+        self.run(&script, parse_response)
+            .unwrap_or_else(|result| result)
+    }
+
+    fn check_with_proof(&self, obligation: &ProofObligation) -> (SmtResult, Option<String>) {
+        let Some(script) = proof_script(&obligation.predicate.simplify()) else {
+            return (self.check_obligation(obligation), None);
+        };
+        self.run(&script, |output| {
+            (parse_response(output), parse_proof(output))
+        })
+        .unwrap_or_else(|result| (result, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use torc_core::contract::{ObligationKind, ProofStatus};
+
+    fn x() -> Box<Predicate> {
+        Box::new(Predicate::Var("x".into()))
+    }
+
+    fn obligation(predicate: Predicate) -> ProofObligation {
+        ProofObligation {
+            kind: ObligationKind::Postcondition,
+            predicate,
+            description: "test".into(),
+            status: ProofStatus::Pending,
+            witness: None,
+            waiver: None,
+        }
+    }
+
+    #[test]
+    fn prints_scripts() {
+        // x + 1 > x
+        let pred = Predicate::Gt(
+            Box::new(Predicate::Add(x(), Box::new(Predicate::IntLit(1)))),
+            x(),
+        );
+        assert_eq!(
+            script(&pred).unwrap(),
+            "(set-option :produce-models true)\n\
+             (set-logic QF_LIA)\n\
+             (declare-const x Int)\n\
+             (assert (not (> (+ x 1) x)))\n\
+             (check-sat)\n\
+             (get-value (x))\n\
+             (exit)\n"
+        );
+
+        // Negative literals, port variables and quantifiers
+        let port = Predicate::Var("6ba7b810-9dad-11d1-80b4-00c04fd430c8.0".into());
+        let pred = Predicate::ForAll {
+            var: "i".into(),
+            range: Box::new(Predicate::Ge(
+                Box::new(Predicate::Var("i".into())),
+                Box::new(Predicate::IntLit(-3)),
+            )),
+            body: Box::new(Predicate::Le(
+                Box::new(Predicate::Mul(Box::new(Predicate::Var("i".into())), x())),
+                Box::new(port),
+            )),
+        };
+        let text = script(&pred).unwrap();
+        assert!(text.contains("(set-logic NIA)"));
+        assert!(text.contains("(forall ((i Int)) (=> (>= i (- 3)) (<= (* i x) |6ba7b810"));
+        assert!(text.contains("(declare-const |6ba7b810-9dad-11d1-80b4-00c04fd430c8.0| Int)"));
+        assert!(!text.contains("(declare-const i Int)"));
+
+        assert_eq!(formula(&Predicate::Apply("sqrt".into(), vec![*x()])), None);
+    }
+
+    #[test]
+    fn parses_responses() {
+        assert!(matches!(parse_response("unsat\n"), SmtResult::Proven));
+        assert!(matches!(
+            parse_response("unknown\n"),
+            SmtResult::Unknown { .. }
+        ));
+        let proof = "unsat\n(error \"no values\")\n(proof (|a)b| ; )\n lemma))\n";
+        assert!(matches!(parse_response(proof), SmtResult::Proven));
+        assert_eq!(
+            parse_proof(proof).as_deref(),
+            Some("(proof (|a)b| ; )\n lemma))")
+        );
+        assert_eq!(parse_proof("sat\n((x 1))\n"), None);
+        match parse_response("sat\n(error \"no proof\")\n((x (- 4)) (|a.0| 7))\n") {
+            SmtResult::Disproven { counterexample } => {
+                assert_eq!(counterexample["x"], "-4");
+                assert_eq!(counterexample["a.0"], "7");
+            }
+            other => panic!("expected Disproven, got {other:?}"),
+        }
+    }
+
+    /// A stand-in solver: refutes `x > 10` with `x = 10`, proves anything
+    /// else, and hangs on `x = 99`.
+    #[cfg(unix)]
+    #[test]
+    fn processes_may_answer_before_reading_their_input() {
+        // Both pipes fill up unless input and output flow at once
+        let args = vec![
+            "-c".to_string(),
+            "head -c 200000 /dev/zero | tr '\\0' x; cat > /dev/null".to_string(),
+        ];
+        let input = "y".repeat(200_000);
+        let (status, output) =
+            run_process(Path::new("sh"), &args, &input, Duration::from_secs(10)).unwrap();
+        assert!(status.success());
+        assert_eq!(output.len(), 200_000);
+    }
+
+    #[cfg(unix)]
+    fn mock_solver(dir: &std::path::Path) -> ProcessSolver {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("mock-solver");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\n\
+             input=$(cat)\n\
+             case \"$input\" in\n\
+             *'(> x 10)'*) printf 'sat\\n((x 10))\\n' ;;\n\
+             *'(= x 99)'*) exec sleep 10 ;;\n\
+             *'(< x 0)'*) printf 'unsat\\n'; kill -SEGV $$ ;;\n\
+             *'(< x 1)'*) printf 'unsat\\n'; exit 2 ;;\n\
+             *'(< x 2)'*) printf 'unsat\\n(error \"model is not available\")\\n'; exit 1 ;;\n\
+             *get-proof*) printf 'unsat\\n(proof refl)\\n' ;;\n\
+             *) printf 'unsat\\n' ;;\n\
+             esac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ProcessSolver::new(path, Vec::new())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn drives_an_external_solver() {
+        let dir = tempfile::tempdir().unwrap();
+        let solver = mock_solver(dir.path());
+        assert_eq!(solver.name(), "smtlib:mock-solver");
+
+        let holds = Predicate::Ge(x(), x());
+        assert!(matches!(
+            solver.check_obligation(&obligation(holds)),
+            SmtResult::Proven
+        ));
+
+        let fails = Predicate::Gt(x(), Box::new(Predicate::IntLit(10)));
+        match solver.check_obligation(&obligation(fails)) {
+            SmtResult::Disproven { counterexample } => assert_eq!(counterexample["x"], "10"),
+            other => panic!("expected Disproven, got {other:?}"),
+        }
+
+        assert!(matches!(
+            solver.check_with_proof(&obligation(Predicate::Ge(x(), x()))),
+            (SmtResult::Proven, Some(proof)) if proof == "(proof refl)"
+        ));
+
+        let hangs = Predicate::Eq(x(), Box::new(Predicate::IntLit(99)));
+        let solver = solver.with_timeout(Duration::from_millis(200));
+        assert!(matches!(
+            solver.check_obligation(&obligation(hangs)),
+            SmtResult::Timeout
+        ));
+
+        // Crashing or failing for no reason it gives is not an answer;
+        // failing to produce a model after `unsat` is
+        let below = |n| obligation(Predicate::Lt(x(), Box::new(Predicate::IntLit(n))));
+        for n in [0, 1] {
+            assert!(matches!(
+                solver.check_obligation(&below(n)),
+                SmtResult::Unknown { .. }
+            ));
+        }
+        assert!(matches!(
+            solver.check_obligation(&below(2)),
+            SmtResult::Proven
+        ));
+
+        let missing = ProcessSolver::new(dir.path().join("no-such-solver"), Vec::new());
+        assert!(matches!(
+            missing.check_obligation(&obligation(Predicate::BoolLit(true))),
+            SmtResult::Unknown { .. }
+        ));
+    }
+}
@@ -2,10 +2,10 @@
 
 use torc_core::contract::{ObligationKind, ProofStatus};
 use torc_core::graph::node::NodeId;
-use torc_core::graph::Graph;
+use torc_core::graph::{Graph, GraphError};
 
 use crate::registry::ObligationRegistry;
-use crate::witness::generate_witness;
+use crate::witness::{generate_witness, Certificate};
 
 /// Severity of a structural diagnostic.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,15 +44,39 @@ impl StructuralAnalyzer {
             }
         }
 
-        // Run linearity validation
-        let linearity_result = graph.validate_linearity();
+        // Run ownership validation: linearity, lifetimes and allocations
+        let linearity_result = graph.validate_ownership();
         if let Err(ref errors) = linearity_result {
             for err in errors {
+                let (node_id, suggestion) = match err {
+                    GraphError::LifetimeEscape { target, .. } => (
+                        Some(*target),
+                        "Consume region-scoped values inside their region",
+                    ),
+                    GraphError::UnpairedAllocation { node, .. } => (
+                        Some(*node),
+                        "Deallocate the allocation exactly once on every path",
+                    ),
+                    GraphError::UseAfterFree { node, .. } => (
+                        Some(*node),
+                        "Order uses of the allocation before its deallocation",
+                    ),
+                    GraphError::SharedOwnership { consumer, .. } => (
+                        Some(*consumer),
+                        "Take the counted value by reference, or drop the other references",
+                    ),
+                    GraphError::LinearityViolation { node, .. }
+                    | GraphError::PathLinearityViolation { node, .. } => (
+                        Some(*node),
+                        "Ensure linear values are consumed exactly once",
+                    ),
+                    _ => (None, "Ensure linear values are consumed exactly once"),
+                };
                 diagnostics.push(StructuralDiagnostic {
                     severity: Severity::Error,
                     message: err.to_string(),
-                    node_id: None,
-                    suggestion: Some("Ensure linear values are consumed exactly once".into()),
+                    node_id,
+                    suggestion: Some(suggestion.into()),
                 });
             }
         }
@@ -69,6 +93,23 @@ impl StructuralAnalyzer {
             }
         }
 
+        // Declared effects narrower than what a node (or its loop body)
+        // actually performs
+        if let Err(errors) = graph.validate_declared_effects() {
+            for err in errors {
+                let node_id = match &err {
+                    GraphError::UndeclaredEffect { node, .. } => Some(*node),
+                    _ => None,
+                };
+                diagnostics.push(StructuralDiagnostic {
+                    severity: Severity::Warning,
+                    message: err.to_string(),
+                    node_id,
+                    suggestion: Some("Add the inferred effect to the node's contract".into()),
+                });
+            }
+        }
+
         // Discharge Linearity obligations structurally:
         // If linearity validation passed, mark all Linearity obligations as Verified.
         if linearity_result.is_ok() {
@@ -80,8 +121,14 @@ impl StructuralAnalyzer {
 
             for id in linearity_ids {
                 if let Some(tracked) = registry.all().iter().find(|o| o.id == id) {
-                    let witness =
-                        generate_witness("structural_analysis", &tracked.obligation, vec![]);
+                    let witness = generate_witness(
+                        "structural_analysis",
+                        &tracked.obligation,
+                        Certificate::Structural {
+                            check: "ownership".into(),
+                        }
+                        .encode(),
+                    );
                     registry.update_status(id, ProofStatus::Verified, Some(witness));
                 }
             }
@@ -169,6 +216,25 @@ mod tests {
             .any(|d| d.severity == Severity::Error && d.message.contains("effect violation")));
     }
 
+    #[test]
+    fn undeclared_effects_warned() {
+        let mut g = Graph::new();
+        let mut tx = Node::new(NodeKind::Write);
+        tx.annotations.insert("io.device".into(), "uart0".into());
+        tx.contract = Some(Contract::pure_default());
+        let tx_id = g.add_node(tx).unwrap();
+
+        let mut registry = ObligationRegistry::collect_from_graph(&g);
+        let diagnostics = StructuralAnalyzer::analyze(&g, &mut registry);
+
+        let warning = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Warning)
+            .expect("undeclared IO is reported");
+        assert_eq!(warning.node_id, Some(tx_id));
+        assert!(warning.message.contains("IO<uart0>"));
+    }
+
     #[test]
     fn wellformedness_errors_diagnosed() {
         use torc_core::graph::region::{Region, RegionKind};
@@ -1,18 +1,61 @@
 //! Proof witness generation and verification.
+//!
+//! A witness's `data` holds a serialized `Certificate`: the evidence that
+//! `checker::ProofChecker` replays to re-establish the proof without
+//! trusting the pass that produced it.
 
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use torc_core::contract::{ProofObligation, ProofWitness};
+use torc_core::graph::edge::PortRef;
+
+use crate::interval::Interval;
+
+/// Checkable evidence that an obligation holds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Certificate {
+    /// Interval evaluation proves the predicate with its variables bound
+    /// to these ranges, each justified by the range of a graph port.
+    Interval { bindings: Vec<Binding> },
+    /// The graph passes the named structural check (`ownership`).
+    Structural { check: String },
+    /// A solver found `script`, the `smtlib::proof_script` asserting the
+    /// negation of the predicate, unsatisfiable, and answered its
+    /// `(get-proof)` with `proof`.
+    Unsat { script: String, proof: String },
+}
+
+/// A predicate variable bound to the range of the port it stands for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Binding {
+    pub var: String,
+    pub port: PortRef,
+    pub range: Interval,
+}
+
+impl Certificate {
+    /// Serialize for a witness's `data`.
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("certificates serialize")
+    }
+
+    /// The certificate in a witness's `data`, if there is one.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(data).ok()
+    }
+}
 
 /// Generate a proof witness for a discharged obligation.
 ///
 /// Creates a `ProofWitness` whose hash is the SHA-256 of the obligation
-/// predicate (debug-printed) concatenated with the solver name.
+/// predicate (debug-printed), the solver name and the proof data.
 pub fn generate_witness(
     solver_name: &str,
     obligation: &ProofObligation,
     data: Vec<u8>,
 ) -> ProofWitness {
-    let hash = compute_witness_hash(solver_name, obligation);
+    let hash = compute_witness_hash(solver_name, obligation, &data);
     ProofWitness {
         hash,
         solver: solver_name.to_string(),
@@ -21,16 +64,20 @@ pub fn generate_witness(
 }
 
 /// Verify that a witness hash matches the obligation it claims to prove.
+///
+/// This only shows the witness is intact; `checker::ProofChecker` checks
+/// the proof it carries.
 pub fn verify_witness(witness: &ProofWitness, obligation: &ProofObligation) -> bool {
-    let expected = compute_witness_hash(&witness.solver, obligation);
+    let expected = compute_witness_hash(&witness.solver, obligation, &witness.data);
     witness.hash == expected
 }
 
-/// Compute the SHA-256 hash for a (solver, obligation) pair.
-fn compute_witness_hash(solver_name: &str, obligation: &ProofObligation) -> String {
+/// Compute the SHA-256 hash for a (solver, obligation, data) triple.
+fn compute_witness_hash(solver_name: &str, obligation: &ProofObligation, data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(format!("{:?}", obligation.predicate).as_bytes());
     hasher.update(solver_name.as_bytes());
+    hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
 
@@ -68,5 +115,28 @@ mod tests {
         // Tamper with the hash
         witness.hash = "0000000000000000000000000000000000000000000000000000000000000000".into();
         assert!(!verify_witness(&witness, &ob));
+
+        // Or with the proof data
+        let mut witness = generate_witness("z3", &ob, vec![1, 2, 3]);
+        witness.data.push(4);
+        assert!(!verify_witness(&witness, &ob));
+    }
+
+    #[test]
+    fn certificates_round_trip() {
+        let certificate = Certificate::Interval {
+            bindings: vec![Binding {
+                var: "output".into(),
+                port: (uuid::Uuid::nil(), 0),
+                range: Interval::bounded(0.0, 0.1 + 0.2),
+            }],
+        };
+        let witness = generate_witness(
+            "interval_domain",
+            &sample_obligation(),
+            certificate.encode(),
+        );
+        assert_eq!(Certificate::decode(&witness.data), Some(certificate));
+        assert_eq!(Certificate::decode(&[]), None);
     }
 }
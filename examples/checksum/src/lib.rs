@@ -11,6 +11,7 @@ use torc_core::graph::node::{ArithmeticOp, BitwiseOp, NodeKind};
 use torc_core::graph::Graph;
 use torc_core::provenance::Provenance;
 use torc_core::types::{Effect, Predicate, Type, TypeSignature};
+use torc_core::value::Value;
 
 /// Build the ones' complement checksum graph.
 ///
@@ -63,7 +64,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(init_acc, "value", "0").unwrap();
+    b.set_value(init_acc, Value::u32(0)).unwrap();
 
     // --- Iteration: accumulate sum over input bytes ---
     let widen = b.add_full_node(
@@ -97,7 +98,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(shift_16, "value", "16").unwrap();
+    b.set_value(shift_16, Value::u32(16)).unwrap();
 
     let mask_ffff = b.add_full_node(
         NodeKind::Literal,
@@ -106,7 +107,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(mask_ffff, "value", "0xFFFF").unwrap();
+    b.set_value(mask_ffff, Value::u32(0xFFFF)).unwrap();
 
     let high = b.add_full_node(
         NodeKind::Bitwise(BitwiseOp::ShiftRight),
@@ -239,7 +240,8 @@ pub fn build_graph() -> Graph {
     b.connect(not_result, 0, truncate, 0).unwrap();
     b.connect(truncate, 0, output, 0).unwrap();
 
-    b.build().expect("Checksum graph construction failed")
+    b.build_canonical("checksum")
+        .expect("Checksum graph construction failed")
 }
 
 /// Standard provenance for AI-authored nodes in this example.
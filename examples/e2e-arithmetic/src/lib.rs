@@ -9,6 +9,7 @@ use torc_core::graph::node::{ArithmeticOp, NodeKind};
 use torc_core::graph::Graph;
 use torc_core::provenance::Provenance;
 use torc_core::types::{Type, TypeSignature};
+use torc_core::value::Value;
 
 /// Build the arithmetic graph: `(10 + 32) * 2 - 5 = 79`.
 ///
@@ -33,7 +34,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(lit_10, "value", "10").unwrap();
+    b.set_value(lit_10, Value::i32(10)).unwrap();
 
     let lit_32 = b.add_full_node(
         NodeKind::Literal,
@@ -42,7 +43,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(lit_32, "value", "32").unwrap();
+    b.set_value(lit_32, Value::i32(32)).unwrap();
 
     let lit_2 = b.add_full_node(
         NodeKind::Literal,
@@ -51,7 +52,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(lit_2, "value", "2").unwrap();
+    b.set_value(lit_2, Value::i32(2)).unwrap();
 
     let lit_5 = b.add_full_node(
         NodeKind::Literal,
@@ -60,7 +61,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(lit_5, "value", "5").unwrap();
+    b.set_value(lit_5, Value::i32(5)).unwrap();
 
     // --- Arithmetic nodes ---
 
@@ -119,7 +120,8 @@ pub fn build_graph() -> Graph {
     b.connect(mul, 0, sub, 0).unwrap();
     b.connect(lit_5, 0, sub, 1).unwrap();
 
-    b.build().expect("Arithmetic graph construction failed")
+    b.build_canonical("e2e-arithmetic")
+        .expect("Arithmetic graph construction failed")
 }
 
 /// Standard provenance for AI-authored nodes in this example.
@@ -43,6 +43,99 @@ fn topological_sort() {
     assert_eq!(topo.len(), 7, "all 7 nodes should be in topo order");
 }
 
+#[test]
+fn counterexample_replays_in_interpreter() {
+    use torc_core::graph::node::{ArithmeticOp, NodeKind};
+    use torc_core::types::Predicate;
+    use torc_interp::{Interpreter, ScriptedHost};
+    use torc_verify::counterexample::TestVector;
+    use torc_verify::engine::VerificationEngine;
+    use torc_verify::profile::VerificationProfile;
+
+    // Restrict add's first input to a single digit; lit_10 violates it.
+    let mut graph = build_graph();
+    let add = graph
+        .nodes()
+        .find(|n| n.kind == NodeKind::Arithmetic(ArithmeticOp::Add))
+        .map(|n| n.id)
+        .unwrap();
+    let sig = graph
+        .get_node_mut(&add)
+        .unwrap()
+        .type_signature
+        .as_mut()
+        .unwrap();
+    sig.inputs[0] = sig.inputs[0]
+        .clone()
+        .refined(Predicate::in_range("value", 0, 9));
+
+    let report = VerificationEngine::new(VerificationProfile::development()).verify(&graph);
+    let ce = report
+        .counterexamples
+        .first()
+        .expect("the refinement should be disproven");
+    assert_eq!(ce.node, Some(add));
+    assert_eq!(ce.assignments[0].input, Some((add, 0)));
+
+    // Export and reload the vector as a test fixture would.
+    let json = serde_json::to_string(&ce.test_vector(&graph)).unwrap();
+    let vector: TestVector = serde_json::from_str(&json).unwrap();
+    assert_eq!(vector.literals.len(), 1);
+
+    let mut replay = build_graph();
+    vector.apply(&mut replay);
+    let mut host = vector
+        .reads
+        .iter()
+        .fold(ScriptedHost::new(), |host, (node, value)| {
+            host.with_input(*node, value.clone())
+        });
+    let exec = Interpreter::new(&replay).run(&mut host).expect("run");
+    assert_eq!(vector.fails_on(exec.values()), Some(true));
+}
+
+#[test]
+fn overflow_beyond_f64_precision_is_not_proven() {
+    use torc_core::graph::edge::Edge;
+    use torc_core::graph::node::{ArithmeticOp, Node, NodeKind};
+    use torc_core::graph::Graph;
+    use torc_core::types::{Type, TypeSignature};
+    use torc_core::value::Value;
+    use torc_verify::engine::VerificationEngine;
+    use torc_verify::profile::VerificationProfile;
+
+    // i64::MAX + 1 under `overflow = "proven"`: the interpreter traps, so
+    // verification must not prove the no-overflow obligation.
+    let mut graph = Graph::new();
+    let literal = |v| {
+        Node::new(NodeKind::Literal)
+            .with_type_signature(TypeSignature::source(Type::i64()))
+            .with_value(Value::i64(v))
+    };
+    let max = graph.add_node(literal(i64::MAX)).unwrap();
+    let one = graph.add_node(literal(1)).unwrap();
+    let mut add = Node::new(NodeKind::Arithmetic(ArithmeticOp::Add)).with_type_signature(
+        TypeSignature::new(vec![Type::i64(), Type::i64()], vec![Type::i64()]),
+    );
+    add.annotations.insert("overflow".into(), "proven".into());
+    let add = graph.add_node(add).unwrap();
+    graph.add_edge(Edge::new((max, 0), (add, 0))).unwrap();
+    graph.add_edge(Edge::new((one, 0), (add, 1))).unwrap();
+
+    assert!(matches!(
+        torc_interp::run(&graph),
+        Err(torc_interp::InterpError::Overflow { .. })
+    ));
+    for profile in [
+        VerificationProfile::development(),
+        VerificationProfile::certification(),
+    ] {
+        let report = VerificationEngine::new(profile).verify(&graph);
+        assert_eq!(report.summary.total, 1);
+        assert_eq!(report.summary.verified, 0);
+    }
+}
+
 #[cfg(feature = "llvm")]
 mod llvm_tests {
     use super::*;
@@ -11,6 +11,7 @@ use torc_core::graph::region::RegionKind;
 use torc_core::graph::Graph;
 use torc_core::provenance::Provenance;
 use torc_core::types::{Effect, Predicate, Type, TypeSignature};
+use torc_core::value::Value;
 
 // ---------------------------------------------------------------------------
 // ID bundles returned by each subgraph builder
@@ -144,7 +145,7 @@ fn build_clarke_transform(b: &mut GraphBuilder, adc: &AdcIds) -> ClarkeIds {
         None,
         Some(prov()),
     );
-    b.annotate(zero, "value", "0.0").unwrap();
+    b.set_value(zero, Value::f32(0.0)).unwrap();
 
     let i_alpha = b.add_full_node(
         NodeKind::Arithmetic(ArithmeticOp::Add),
@@ -162,7 +163,7 @@ fn build_clarke_transform(b: &mut GraphBuilder, adc: &AdcIds) -> ClarkeIds {
         None,
         Some(prov()),
     );
-    b.annotate(two, "value", "2.0").unwrap();
+    b.set_value(two, Value::f32(2.0)).unwrap();
 
     let one_over_sqrt3 = b.add_full_node(
         NodeKind::Literal,
@@ -171,7 +172,7 @@ fn build_clarke_transform(b: &mut GraphBuilder, adc: &AdcIds) -> ClarkeIds {
         None,
         Some(prov()),
     );
-    b.annotate(one_over_sqrt3, "value", "0.57735026918962576")
+    b.set_value(one_over_sqrt3, Value::f32(0.577_350_26))
         .unwrap();
 
     let two_ib = b.add_full_node(
@@ -309,7 +310,7 @@ fn build_park_transform(b: &mut GraphBuilder, clarke: &ClarkeIds) -> ParkIds {
         None,
         Some(prov()),
     );
-    b.annotate(neg_one, "value", "-1.0").unwrap();
+    b.set_value(neg_one, Value::f32(-1.0)).unwrap();
 
     let neg_alpha_sin = b.add_full_node(
         NodeKind::Arithmetic(ArithmeticOp::Mul),
@@ -388,7 +389,7 @@ fn build_pid_controller(b: &mut GraphBuilder, prefix: &str, measurement_node: No
         None,
         Some(prov()),
     );
-    b.annotate(setpoint, "value", "0.0").unwrap();
+    b.set_value(setpoint, Value::f32(0.0)).unwrap();
 
     // Error = setpoint - measurement
     let error = b.add_full_node(
@@ -407,7 +408,7 @@ fn build_pid_controller(b: &mut GraphBuilder, prefix: &str, measurement_node: No
         None,
         Some(prov()),
     );
-    b.annotate(kp, "value", "1.0").unwrap();
+    b.set_value(kp, Value::f32(1.0)).unwrap();
     b.annotate(kp, "tuning", "proportional_gain").unwrap();
 
     let ki = b.add_full_node(
@@ -417,7 +418,7 @@ fn build_pid_controller(b: &mut GraphBuilder, prefix: &str, measurement_node: No
         None,
         Some(prov()),
     );
-    b.annotate(ki, "value", "0.1").unwrap();
+    b.set_value(ki, Value::f32(0.1)).unwrap();
     b.annotate(ki, "tuning", "integral_gain").unwrap();
 
     let kd = b.add_full_node(
@@ -427,7 +428,7 @@ fn build_pid_controller(b: &mut GraphBuilder, prefix: &str, measurement_node: No
         None,
         Some(prov()),
     );
-    b.annotate(kd, "value", "0.01").unwrap();
+    b.set_value(kd, Value::f32(0.01)).unwrap();
     b.annotate(kd, "tuning", "derivative_gain").unwrap();
 
     // P term = kp * error
@@ -538,7 +539,7 @@ fn build_safety_monitor(b: &mut GraphBuilder, adc: &AdcIds) -> SafetyIds {
         None,
         Some(prov()),
     );
-    b.annotate(oc_thresh, "value", "45.0").unwrap();
+    b.set_value(oc_thresh, Value::f32(45.0)).unwrap();
     b.annotate(oc_thresh, "safety_class", "ASIL-B").unwrap();
 
     let ov_thresh = b.add_full_node(
@@ -548,7 +549,7 @@ fn build_safety_monitor(b: &mut GraphBuilder, adc: &AdcIds) -> SafetyIds {
         None,
         Some(prov()),
     );
-    b.annotate(ov_thresh, "value", "55.0").unwrap();
+    b.set_value(ov_thresh, Value::f32(55.0)).unwrap();
     b.annotate(ov_thresh, "safety_class", "ASIL-B").unwrap();
 
     let uv_thresh = b.add_full_node(
@@ -558,7 +559,7 @@ fn build_safety_monitor(b: &mut GraphBuilder, adc: &AdcIds) -> SafetyIds {
         None,
         Some(prov()),
     );
-    b.annotate(uv_thresh, "value", "10.0").unwrap();
+    b.set_value(uv_thresh, Value::f32(10.0)).unwrap();
     b.annotate(uv_thresh, "safety_class", "ASIL-B").unwrap();
 
     let ot_thresh = b.add_full_node(
@@ -568,7 +569,7 @@ fn build_safety_monitor(b: &mut GraphBuilder, adc: &AdcIds) -> SafetyIds {
         None,
         Some(prov()),
     );
-    b.annotate(ot_thresh, "value", "140.0").unwrap();
+    b.set_value(ot_thresh, Value::f32(140.0)).unwrap();
     b.annotate(ot_thresh, "safety_class", "ASIL-B").unwrap();
 
     // Parallel overcurrent comparisons
@@ -813,7 +814,7 @@ fn build_pwm_outputs(
         None,
         Some(prov()),
     );
-    b.annotate(zero_duty, "value", "0.0").unwrap();
+    b.set_value(zero_duty, Value::f32(0.0)).unwrap();
 
     // Select nodes: if pwm_enabled then duty else 0.0
     let gate_a = b.add_full_node(
@@ -934,7 +935,8 @@ pub fn build_graph() -> Graph {
     // 6. PWM Output (gated by safety monitor's pwm_enabled signal)
     let _pwm = build_pwm_outputs(&mut b, &pid_d, &pid_q, &safety);
 
-    b.build().expect("FOC graph construction failed")
+    b.build_canonical("foc-controller")
+        .expect("FOC graph construction failed")
 }
 
 // ---------------------------------------------------------------------------
@@ -22,6 +22,7 @@ use torc_core::graph::node::{ArithmeticOp, ComparisonOp, NodeKind};
 use torc_core::graph::Graph;
 use torc_core::provenance::Provenance;
 use torc_core::types::{Predicate, Type, TypeSignature};
+use torc_core::value::Value;
 
 /// Build the PID controller graph (18 nodes, 23 edges).
 pub fn build_graph() -> Graph {
@@ -40,7 +41,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(setpoint, "value", "10.0").unwrap();
+    b.set_value(setpoint, Value::f32(10.0)).unwrap();
 
     let measurement = b.add_full_node(
         NodeKind::Literal,
@@ -49,7 +50,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(measurement, "value", "7.5").unwrap();
+    b.set_value(measurement, Value::f32(7.5)).unwrap();
 
     let kp = b.add_full_node(
         NodeKind::Literal,
@@ -58,7 +59,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(kp, "value", "2.0").unwrap();
+    b.set_value(kp, Value::f32(2.0)).unwrap();
 
     let ki = b.add_full_node(
         NodeKind::Literal,
@@ -67,7 +68,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(ki, "value", "0.5").unwrap();
+    b.set_value(ki, Value::f32(0.5)).unwrap();
 
     let kd = b.add_full_node(
         NodeKind::Literal,
@@ -76,7 +77,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(kd, "value", "0.1").unwrap();
+    b.set_value(kd, Value::f32(0.1)).unwrap();
 
     let max_output = b.add_full_node(
         NodeKind::Literal,
@@ -85,7 +86,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(max_output, "value", "100.0").unwrap();
+    b.set_value(max_output, Value::f32(100.0)).unwrap();
 
     let min_output = b.add_full_node(
         NodeKind::Literal,
@@ -94,7 +95,7 @@ pub fn build_graph() -> Graph {
         None,
         Some(prov()),
     );
-    b.annotate(min_output, "value", "-100.0").unwrap();
+    b.set_value(min_output, Value::f32(-100.0)).unwrap();
 
     // --- Arithmetic: error, p_term, i_term, d_term, pi_sum, pid_raw ---
 
@@ -265,7 +266,8 @@ pub fn build_graph() -> Graph {
     // exit_code = fptosi(output)
     b.connect(output, 0, exit_code, 0).unwrap();
 
-    b.build().expect("PID graph construction failed")
+    b.build_canonical("pid-controller")
+        .expect("PID graph construction failed")
 }
 
 /// Standard provenance for AI-authored nodes in this example.